@@ -1,11 +1,6 @@
 mod ui;
 
-use ferrite::core::{
-    application::{Application, Layer},
-    renderer::ui::UIRenderer,
-    scene::Scene,
-    window::Window,
-};
+use ferrite::prelude::*;
 use glfw::{Glfw, WindowEvent};
 use ui::ecs::EntityComponentsPanel;
 
@@ -24,6 +19,8 @@ impl EditorLayer {
     fn new() -> Self {
         let mut ui = UIRenderer::new();
         ui.add(Box::new(EntityComponentsPanel::new()));
+        ui.add(ui::assets::asset_browser());
+        ui.on_scene_drop(ui::assets::on_scene_drop());
         Self {
             scene: Scene::new(),
             ui,
@@ -39,11 +36,16 @@ impl Layer for EditorLayer {
         self.ui.render(&mut self.scene);
     }
 
-    fn on_event(&mut self, glfw: &mut Glfw, window: &mut glfw::Window, event: &WindowEvent) {
+    fn on_event(
+        &mut self,
+        glfw: &mut Glfw,
+        window: &mut glfw::Window,
+        event: &WindowEvent,
+    ) -> bool {
         if self.ui.handle_events(&mut self.scene, window, glfw, &event) {
-            return;
+            return true;
         }
-        self.scene.handle_event(glfw, window, event);
+        self.scene.handle_event(glfw, window, event)
     }
 
     fn get_name(&self) -> &str {
@@ -0,0 +1,81 @@
+use std::path::Path;
+
+use cgmath::{EuclideanSpace, Point3};
+use ferrite::core::{
+    entity::{
+        component::{camera_component::CameraComponent, model_component::ModelComponent},
+        Entity,
+    },
+    model::ModelBuilder,
+    renderer::ui::{
+        asset_browser::{AssetBrowser, AssetEntry, AssetKind, ASSET_PAYLOAD},
+        drag::{DragPayload, SceneDropHandler},
+        UI,
+    },
+    scene::Scene,
+};
+
+const ASSETS_DIRECTORY: &str = "assets";
+/// Models are loaded relative to this directory
+const MODELS_DIRECTORY: &str = "assets/models";
+
+/// Lists the assets, double-clicking a model spawns it at the origin
+pub fn asset_browser() -> Box<AssetBrowser> {
+    UI::asset_browser(ASSETS_DIRECTORY, |builder| {
+        builder
+            .position(10.0, 410.0)
+            .size(300.0, 300.0)
+            .on_open(Box::new(|scene: &mut Scene, entry: &AssetEntry| {
+                open(scene, entry, Point3::origin())
+            }))
+    })
+}
+
+/// Spawns models dragged out of the asset browser where the cursor points at the ground
+pub fn on_scene_drop() -> SceneDropHandler {
+    Box::new(
+        |scene: &mut Scene, payload: &DragPayload, cursor: (f32, f32)| {
+            if payload.kind != ASSET_PAYLOAD {
+                return;
+            }
+            let path = Path::new(&payload.value);
+            let entry = AssetEntry {
+                path: path.to_path_buf(),
+                name: payload.value.clone(),
+                kind: AssetKind::from_path(path),
+            };
+            let position = get_ground_position(scene, cursor).unwrap_or(Point3::origin());
+            open(scene, &entry, position);
+        },
+    )
+}
+
+fn open(scene: &mut Scene, entry: &AssetEntry, position: Point3<f32>) {
+    if entry.kind != AssetKind::Model {
+        log::info!("No action for {:?} asset {}", entry.kind, entry.name);
+        return;
+    }
+    let Ok(path) = entry.path.strip_prefix(MODELS_DIRECTORY) else {
+        log::warn!("Models have to be below {MODELS_DIRECTORY}: {}", entry.name);
+        return;
+    };
+    let path = path.to_string_lossy();
+    let mut entity = Entity::new(&path);
+    entity.add_component(ModelComponent::loading(
+        ModelBuilder::new(&path).build_async(),
+    ));
+    entity.set_position(scene, position);
+    scene.add_entity(entity);
+}
+
+/// Point where the ray from the camera through `cursor` hits the plane y = 0
+fn get_ground_position(scene: &Scene, cursor: (f32, f32)) -> Option<Point3<f32>> {
+    let camera_component = scene.get_component::<CameraComponent>()?;
+    let camera = camera_component.get_camera();
+    let eye = camera.get_eye_position();
+    let direction = camera.get_ray_direction(camera_component.get_projection(), cursor.0, cursor.1);
+    if direction.y >= 0.0 {
+        return None;
+    }
+    Some(eye + direction * (-eye.y / direction.y))
+}
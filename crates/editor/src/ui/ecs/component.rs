@@ -3,11 +3,11 @@ use ferrite::core::{
     renderer::ui::{
         button::Button,
         primitives::{Offset, Size, UIElementHandle},
-        UIElement, UI,
+        UIElement, UIWindow, UI,
     },
     scene::Scene,
 };
-use glfw::{Glfw, Window, WindowEvent};
+use glfw::{Glfw, WindowEvent};
 
 use super::AddComponentButton;
 
@@ -32,7 +32,7 @@ impl UIElement for AddComponentButton {
     fn handle_events(
         &mut self,
         scene: &mut Scene,
-        window: &mut Window,
+        window: &mut dyn UIWindow,
         glfw: &mut Glfw,
         event: &WindowEvent,
     ) -> bool {
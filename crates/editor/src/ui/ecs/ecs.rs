@@ -1,7 +1,7 @@
 use ferrite::core::{
     renderer::ui::{
         primitives::{Offset, Size, UIElementHandle},
-        UIElement, UI,
+        UIElement, UIWindow, UI,
     },
     scene::Scene,
 };
@@ -61,7 +61,7 @@ impl UIElement for EntityComponentsPanel {
     fn handle_events(
         &mut self,
         scene: &mut Scene,
-        window: &mut glfw::Window,
+        window: &mut dyn UIWindow,
         glfw: &mut glfw::Glfw,
         event: &glfw::WindowEvent,
     ) -> bool {
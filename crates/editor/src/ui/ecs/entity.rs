@@ -4,12 +4,12 @@ use ferrite::core::{
     entity::{Entity, EntityHandle},
     renderer::ui::{
         primitives::{Offset, Size, UIElementHandle},
-        UIElement, UI,
+        UIElement, UIWindow, UI,
     },
     scene::Scene,
     utils::DataSource,
 };
-use glfw::{Glfw, Window, WindowEvent};
+use glfw::{Glfw, WindowEvent};
 
 use super::{AddEntityButton, EditEntityButton, EntityUI};
 
@@ -41,7 +41,7 @@ impl UIElement for EntityUI {
     fn handle_events(
         &mut self,
         scene: &mut Scene,
-        window: &mut Window,
+        window: &mut dyn UIWindow,
         glfw: &mut Glfw,
         event: &WindowEvent,
     ) -> bool {
@@ -158,7 +158,7 @@ impl UIElement for AddEntityButton {
     fn handle_events(
         &mut self,
         scene: &mut Scene,
-        window: &mut Window,
+        window: &mut dyn UIWindow,
         glfw: &mut Glfw,
         event: &WindowEvent,
     ) -> bool {
@@ -241,7 +241,7 @@ impl UIElement for EditEntityButton {
     fn handle_events(
         &mut self,
         scene: &mut Scene,
-        window: &mut Window,
+        window: &mut dyn UIWindow,
         glfw: &mut Glfw,
         event: &WindowEvent,
     ) -> bool {
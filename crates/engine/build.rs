@@ -0,0 +1,46 @@
+use std::{env, process::Command};
+
+/// Passes the git hash, build profile, target and enabled features to `BuildInfo`
+fn main() {
+    let git_hash = Command::new("git")
+        .args(["rev-parse", "--short", "HEAD"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|hash| hash.trim().to_string())
+        .unwrap_or_default();
+    let dirty = Command::new("git")
+        .args(["status", "--porcelain", "--untracked-files=no"])
+        .output()
+        .is_ok_and(|output| output.status.success() && !output.stdout.is_empty());
+    let mut features: Vec<String> = env::vars()
+        .filter_map(|(name, _)| {
+            name.strip_prefix("CARGO_FEATURE_")
+                .map(|feature| feature.to_lowercase().replace('_', "-"))
+        })
+        .collect();
+    features.sort();
+
+    println!(
+        "cargo:rustc-env=FERRITE_GIT_HASH={}{}",
+        git_hash,
+        if dirty && !git_hash.is_empty() {
+            "-dirty"
+        } else {
+            ""
+        }
+    );
+    println!(
+        "cargo:rustc-env=FERRITE_BUILD_PROFILE={}",
+        env::var("PROFILE").unwrap_or_default()
+    );
+    println!(
+        "cargo:rustc-env=FERRITE_TARGET={}",
+        env::var("TARGET").unwrap_or_default()
+    );
+    println!("cargo:rustc-env=FERRITE_FEATURES={}", features.join(","));
+    println!("cargo:rerun-if-changed=../../.git/HEAD");
+    println!("cargo:rerun-if-changed=../../.git/index");
+    println!("cargo:rerun-if-changed=build.rs");
+}
@@ -1,17 +1,43 @@
-use crate::core::{
-    renderer::{plane::PlaneRenderer, text::TextRenderer},
-    window::Window,
+use std::cmp::Reverse;
+
+use crate::{
+    core::{
+        asset::AssetManager,
+        determinism::Determinism,
+        event::EventCategory,
+        logger::Logger,
+        profiler::Profiler,
+        renderer::{
+            plane::PlaneRenderer, render_settings::RenderSettings, shader_manager::ShaderManager,
+            text::TextRenderer, ui::UIRenderer,
+        },
+        save_worker::SaveWorker,
+        window::Window,
+    },
+    profile_scope,
 };
 
 use super::{Application, Layer};
 
 impl Application {
     pub fn new(width: u32, height: u32, title: &str) -> Self {
-        env_logger::init();
-        let mut window = Window::new(width, height, title);
+        Self::create(Window::new(width, height, title), false)
+    }
+
+    /// Application with a hidden window for benchmarks and integration tests. The window only
+    /// provides the gl context, so the terrain, its meshing and the scene run as usual but a
+    /// display (or xvfb) is still required. Nothing can close the window, drive the layers with
+    /// `run_frames` instead of `start`
+    pub fn headless(width: u32, height: u32) -> Self {
+        Self::create(Window::new_hidden(width, height, "Headless"), true)
+    }
 
-        TextRenderer::resize(width, height);
-        PlaneRenderer::resize(width, height);
+    fn create(mut window: Window, headless: bool) -> Self {
+        Logger::init();
+
+        TextRenderer::resize(window.width, window.height);
+        PlaneRenderer::resize(window.width, window.height);
+        UIRenderer::set_content_scale(window.get_content_scale());
 
         window.clear(
             (0.3, 0.3, 0.5, 1.0),
@@ -22,31 +48,97 @@ impl Application {
         Self {
             window,
             layers: Vec::new(),
+            headless,
         }
     }
 
     pub fn start(&mut self) {
         while !self.window.should_close() {
-            self.window.clear(
-                (0.3, 0.3, 0.5, 1.0),
-                gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT,
-            );
+            self.run_frame();
+        }
+        self.detach_layers();
+    }
 
-            self.window.handle_events(|window, glfw, event| {
-                PlaneRenderer::resize_from_event(&event);
-                TextRenderer::resize_from_event(&event);
+    /// Runs `frames` frames and returns, the layers stay attached so it can be called again.
+    /// Enable `Determinism` beforehand for a fixed delta time per frame
+    pub fn run_frames(&mut self, frames: usize) {
+        for _ in 0..frames {
+            self.run_frame();
+        }
+    }
 
-                for layer in &mut self.layers {
-                    layer.on_event(glfw, window, &event);
-                }
-            });
+    /// Detaches all layers, `start` does this once the window closes. Returns once the saves
+    /// the layers queued are on the disk
+    pub fn detach_layers(&mut self) {
+        for mut layer in self.layers.drain(..) {
+            layer.on_detach();
+        }
+        SaveWorker::flush();
+    }
 
-            for layer in &mut self.layers {
-                layer.on_update(&self.window, self.window.calculate_frametime());
+    pub fn is_headless(&self) -> bool {
+        self.headless
+    }
+
+    fn run_frame(&mut self) {
+        ShaderManager::poll();
+        AssetManager::poll();
+        SaveWorker::dispatch();
+        self.window.apply_render_settings(&RenderSettings::get());
+        self.window.clear(
+            (0.3, 0.3, 0.5, 1.0),
+            gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT,
+        );
+
+        self.window.handle_events(|window, glfw, event| {
+            profile_scope!("events");
+            PlaneRenderer::resize_from_event(&event);
+            TextRenderer::resize_from_event(&event);
+            if let glfw::WindowEvent::ContentScale(scale, _) = event {
+                UIRenderer::set_content_scale(scale);
             }
 
-            self.window.swap_buffers();
+            let category = EventCategory::from(&event);
+            let mut layers: Vec<(i32, &mut Box<dyn Layer>)> = self
+                .layers
+                .iter_mut()
+                .filter_map(|layer| Some((layer.get_event_priority(category)?, layer)))
+                .collect();
+            layers.sort_by_key(|(priority, _)| Reverse(*priority));
+            for (_, layer) in layers {
+                if layer.on_event(glfw, window, &event) {
+                    break;
+                }
+            }
+        });
+
+        // a connected gamepad must not steer benchmarks and tests
+        if !self.headless {
+            self.window.handle_gamepad_events(|window, glfw, event| {
+                let mut layers: Vec<(i32, &mut Box<dyn Layer>)> = self
+                    .layers
+                    .iter_mut()
+                    .filter_map(|layer| {
+                        Some((layer.get_event_priority(EventCategory::Gamepad)?, layer))
+                    })
+                    .collect();
+                layers.sort_by_key(|(priority, _)| Reverse(*priority));
+                for (_, layer) in layers {
+                    if layer.on_gamepad_event(glfw, window, &event) {
+                        break;
+                    }
+                }
+            });
         }
+
+        let frame_time = self.window.calculate_frametime();
+        let delta_time = Determinism::get_timestep(frame_time);
+        for layer in &mut self.layers {
+            layer.on_update(&self.window, delta_time);
+        }
+
+        self.window.swap_buffers();
+        Profiler::end_frame(frame_time);
     }
 
     pub fn add_layer(&mut self, mut layer: Box<dyn Layer>) {
@@ -1,22 +1,44 @@
-use super::window::Window;
+use super::{
+    event::{EventCategory, DEFAULT_EVENT_PRIORITY},
+    input::GamepadEvent,
+    window::Window,
+};
 
 mod application;
 
 pub struct Application {
     window: Window,
     layers: Vec<Box<dyn Layer>>,
+    /// Set for applications without a visible window, see `Application::headless`
+    headless: bool,
 }
 
 pub trait Layer {
     fn on_attach(&mut self) {}
     fn on_detach(&mut self) {}
     fn on_update(&mut self, window: &Window, delta_time: f64);
+    /// Priority with which the layer receives events of `category`, None if it is not interested in them
+    fn get_event_priority(&self, _category: EventCategory) -> Option<i32> {
+        Some(DEFAULT_EVENT_PRIORITY)
+    }
+    /// Returns true if the event was consumed and must not reach layers with a lower priority
     fn on_event(
         &mut self,
         glfw: &mut glfw::Glfw,
         window: &mut glfw::Window,
         event: &glfw::WindowEvent,
-    );
+    ) -> bool;
+    /// Receives gamepad connections and button changes, returns true if the event was consumed.
+    /// Layers are asked from the highest to the lowest priority for `EventCategory::Gamepad`,
+    /// like `on_event`
+    fn on_gamepad_event(
+        &mut self,
+        _glfw: &mut glfw::Glfw,
+        _window: &mut glfw::Window,
+        _event: &GamepadEvent,
+    ) -> bool {
+        false
+    }
 
     fn get_name(&self) -> &str;
 }
@@ -0,0 +1,117 @@
+use std::{collections::HashMap, fmt, marker::PhantomData};
+
+use crate::core::determinism::Determinism;
+
+use super::{AssetHandle, Assets};
+
+impl<T> AssetHandle<T> {
+    fn new() -> Self {
+        Self {
+            id: Determinism::next_id(),
+            asset: PhantomData,
+        }
+    }
+}
+
+// derived impls would require `T` itself to be `Clone`, `Copy` and `Debug`
+impl<T> Clone for AssetHandle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for AssetHandle<T> {}
+
+impl<T> PartialEq for AssetHandle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl<T> Eq for AssetHandle<T> {}
+
+impl<T> std::hash::Hash for AssetHandle<T> {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.id.hash(state);
+    }
+}
+
+impl<T> fmt::Debug for AssetHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("AssetHandle")
+            .field(&self.id)
+            .field(&std::any::type_name::<T>())
+            .finish()
+    }
+}
+
+impl<T> Assets<T> {
+    pub fn new() -> Self {
+        Self {
+            assets: HashMap::new(),
+            paths: HashMap::new(),
+        }
+    }
+
+    pub fn add(&mut self, asset: T) -> AssetHandle<T> {
+        let handle = AssetHandle::new();
+        self.assets.insert(handle.id, asset);
+        handle
+    }
+
+    /// Loads the asset at `path` with `load`, or returns the handle of the asset loaded from
+    /// `path` before
+    pub fn load<F>(
+        &mut self,
+        path: &str,
+        load: F,
+    ) -> Result<AssetHandle<T>, Box<dyn std::error::Error>>
+    where
+        F: FnOnce(&str) -> Result<T, Box<dyn std::error::Error>>,
+    {
+        if let Some(id) = self.paths.get(path) {
+            if self.assets.contains_key(id) {
+                return Ok(AssetHandle {
+                    id: *id,
+                    asset: PhantomData,
+                });
+            }
+        }
+        let handle = self.add(load(path)?);
+        self.paths.insert(path.to_owned(), handle.id);
+        Ok(handle)
+    }
+
+    /// The asset of `handle`, None if it was removed
+    pub fn get(&self, handle: &AssetHandle<T>) -> Option<&T> {
+        self.assets.get(&handle.id)
+    }
+
+    pub fn get_mut(&mut self, handle: &AssetHandle<T>) -> Option<&mut T> {
+        self.assets.get_mut(&handle.id)
+    }
+
+    pub fn contains(&self, handle: &AssetHandle<T>) -> bool {
+        self.assets.contains_key(&handle.id)
+    }
+
+    /// Removes the asset of `handle`, other copies of the handle resolve to None afterwards
+    pub fn remove(&mut self, handle: &AssetHandle<T>) -> Option<T> {
+        self.paths.retain(|_, id| *id != handle.id);
+        self.assets.remove(&handle.id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.assets.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.assets.is_empty()
+    }
+}
+
+impl<T> Default for Assets<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
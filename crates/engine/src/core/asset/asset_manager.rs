@@ -0,0 +1,186 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+    time::{Instant, SystemTime},
+};
+
+use lazy_static::lazy_static;
+
+use crate::core::renderer::shader_manager::{ShaderManager, POLL_INTERVAL};
+
+use super::{Asset, AssetManager, LoadedAsset, ReloadFn};
+
+lazy_static! {
+    static ref ASSET_MANAGER: Mutex<AssetManager> = Mutex::new(AssetManager::new());
+}
+
+impl AssetManager {
+    fn new() -> Self {
+        Self {
+            search_paths: vec![PathBuf::from("assets")],
+            loaded: HashMap::new(),
+            last_poll: Instant::now(),
+        }
+    }
+
+    /// Looks up paths in `path` before the search paths added earlier
+    pub fn add_search_path(path: impl Into<PathBuf>) {
+        ASSET_MANAGER
+            .lock()
+            .unwrap()
+            .search_paths
+            .insert(0, path.into());
+    }
+
+    pub fn set_search_paths(paths: Vec<PathBuf>) {
+        ASSET_MANAGER.lock().unwrap().search_paths = paths;
+    }
+
+    /// Directories paths are looked up in, in order
+    pub fn get_search_paths() -> Vec<PathBuf> {
+        ASSET_MANAGER.lock().unwrap().search_paths.clone()
+    }
+
+    /// File of `path` in the first search path that has it. Paths found in none of them are used
+    /// as they are if they exist, e.g. for files outside of the asset directories, or else
+    /// resolve to the first search path
+    pub fn resolve(path: &str) -> PathBuf {
+        let manager = ASSET_MANAGER.lock().unwrap();
+        let found = manager
+            .search_paths
+            .iter()
+            .map(|directory| directory.join(path))
+            .find(|file| file.exists());
+        match found {
+            Some(file) => file,
+            None if Path::new(path).exists() => PathBuf::from(path),
+            None => manager
+                .search_paths
+                .first()
+                .map_or_else(|| PathBuf::from(path), |directory| directory.join(path)),
+        }
+    }
+
+    /// Contents of the file of `path`, see `resolve`
+    pub fn read(path: &str) -> Result<Vec<u8>, Box<dyn Error>> {
+        let file = AssetManager::resolve(path);
+        fs::read(&file).map_err(|err| format!("Failed to read {}: {}", file.display(), err).into())
+    }
+
+    /// The asset loaded from `path`, loading it if it is not in use yet
+    pub fn load<T: Asset>(path: &str) -> Result<Arc<T>, Box<dyn Error>> {
+        if let Some(asset) = AssetManager::get::<T>(path) {
+            return Ok(asset);
+        }
+        // the lock is released while loading, so assets can load other assets
+        let file = AssetManager::resolve(path);
+        let asset = Arc::new(
+            T::load(&file).map_err(|err| format!("Failed to load {}: {}", file.display(), err))?,
+        );
+
+        let mut manager = ASSET_MANAGER.lock().unwrap();
+        manager.remove_unused();
+        let key = (TypeId::of::<T>(), path.to_string());
+        // another thread may have loaded the same asset meanwhile
+        if let Some(existing) = manager.get_loaded::<T>(&key) {
+            return Ok(existing);
+        }
+        let shared: Arc<dyn Any + Send + Sync> = asset.clone();
+        let reload: Option<ReloadFn> = T::is_reloadable().then_some(reload_asset::<T> as ReloadFn);
+        manager.loaded.insert(
+            key,
+            LoadedAsset {
+                asset: Arc::downgrade(&shared),
+                modified: AssetManager::get_modified(&file),
+                file,
+                reload,
+            },
+        );
+        Ok(asset)
+    }
+
+    /// The asset loaded from `path` if it is in use
+    pub fn get<T: Asset>(path: &str) -> Option<Arc<T>> {
+        ASSET_MANAGER
+            .lock()
+            .unwrap()
+            .get_loaded(&(TypeId::of::<T>(), path.to_string()))
+    }
+
+    /// Number of loaded assets that are in use
+    pub fn get_loaded_count() -> usize {
+        let mut manager = ASSET_MANAGER.lock().unwrap();
+        manager.remove_unused();
+        manager.loaded.len()
+    }
+
+    /// Reloads the assets whose files changed since the last call. Called once per frame, does
+    /// nothing in release builds
+    pub fn poll() {
+        if !ShaderManager::is_enabled() {
+            return;
+        }
+        let mut changed = Vec::new();
+        {
+            let mut manager = ASSET_MANAGER.lock().unwrap();
+            if manager.last_poll.elapsed() < POLL_INTERVAL {
+                return;
+            }
+            manager.last_poll = Instant::now();
+            manager.remove_unused();
+            for ((_, path), loaded) in manager.loaded.iter_mut() {
+                let Some(reload) = loaded.reload else {
+                    continue;
+                };
+                let modified = AssetManager::get_modified(&loaded.file);
+                if modified.is_none() || modified == loaded.modified {
+                    continue;
+                }
+                loaded.modified = modified;
+                if let Some(asset) = loaded.asset.upgrade() {
+                    changed.push((path.clone(), asset, loaded.file.clone(), reload));
+                }
+            }
+        }
+        for (path, asset, file, reload) in changed {
+            match reload(&*asset, &file) {
+                Ok(()) => log::info!("Reloaded {}", path),
+                Err(err) => log::warn!(
+                    "Reloading {} failed, keeping the previous version\n{}",
+                    path,
+                    err
+                ),
+            }
+        }
+    }
+
+    fn get_loaded<T: Asset>(&self, key: &(TypeId, String)) -> Option<Arc<T>> {
+        self.loaded.get(key)?.asset.upgrade()?.downcast::<T>().ok()
+    }
+
+    /// Forgets the assets whose last user dropped them
+    fn remove_unused(&mut self) {
+        self.loaded
+            .retain(|_, loaded| loaded.asset.strong_count() > 0);
+    }
+
+    fn get_modified(file: &Path) -> Option<SystemTime> {
+        fs::metadata(file)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    }
+}
+
+fn reload_asset<T: Asset>(
+    asset: &(dyn Any + Send + Sync),
+    path: &Path,
+) -> Result<(), Box<dyn Error>> {
+    match asset.downcast_ref::<T>() {
+        Some(asset) => asset.reload(path),
+        None => Err("the asset changed its type".into()),
+    }
+}
@@ -0,0 +1,73 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    error::Error,
+    marker::PhantomData,
+    path::{Path, PathBuf},
+    sync::Weak,
+    time::{Instant, SystemTime},
+};
+
+mod asset;
+mod asset_manager;
+
+/// Typed id of an asset in an `Assets<T>` store, cheap to copy into components and game code
+pub struct AssetHandle<T> {
+    id: u64,
+    asset: PhantomData<fn() -> T>,
+}
+
+/// Type of asset the `AssetManager` loads from files
+pub trait Asset: Send + Sync + 'static {
+    /// Loads the asset from the file at `path`, called on the thread that asked for the asset
+    fn load(path: &Path) -> Result<Self, Box<dyn Error>>
+    where
+        Self: Sized;
+
+    /// Whether `reload` updates the asset when its file changes
+    fn is_reloadable() -> bool
+    where
+        Self: Sized,
+    {
+        false
+    }
+
+    /// Loads the changed file at `path` into the asset in place, so everything sharing it sees
+    /// the new version. On an error the asset keeps its previous version
+    fn reload(&self, _path: &Path) -> Result<(), Box<dyn Error>> {
+        Ok(())
+    }
+}
+
+/// Loads the files of every `Asset` type once per path and shares them while they are in use.
+/// An asset is unloaded when the last `Arc` to it is dropped, loading it again afterwards reads
+/// the file again.
+///
+/// Paths are looked up in the search paths in order, `assets` by default, so a directory added
+/// with `add_search_path` can replace single files. In debug builds the files of reloadable
+/// assets are watched like shader files
+pub struct AssetManager {
+    search_paths: Vec<PathBuf>,
+    loaded: HashMap<(TypeId, String), LoadedAsset>,
+    last_poll: Instant,
+}
+
+struct LoadedAsset {
+    asset: Weak<dyn Any + Send + Sync>,
+    /// File the asset was read from
+    file: PathBuf,
+    /// Modification time of the file when it was last read
+    modified: Option<SystemTime>,
+    /// Calls `Asset::reload` of the asset's type, None if the type can not be reloaded
+    reload: Option<ReloadFn>,
+}
+
+type ReloadFn = fn(&(dyn Any + Send + Sync), &Path) -> Result<(), Box<dyn Error>>;
+
+/// Store of loaded assets of one type, handed out as `AssetHandle`s instead of references so
+/// game code does not hold borrows of the store
+pub struct Assets<T> {
+    assets: HashMap<u64, T>,
+    /// Paths the assets were loaded from, so loading a path twice returns the same handle
+    paths: HashMap<String, u64>,
+}
@@ -0,0 +1,110 @@
+use std::{ffi::CStr, fmt, sync::OnceLock};
+
+static GPU_INFO: OnceLock<GpuInfo> = OnceLock::new();
+
+/// How the engine was built, for the about panel, the debug dump and bug reports
+#[derive(Clone, Debug)]
+pub struct BuildInfo {
+    pub version: &'static str,
+    /// Short hash of the commit, suffixed with "-dirty" for uncommitted changes. Empty when
+    /// built outside of a git checkout
+    pub git_hash: &'static str,
+    /// Cargo profile, "debug" or "release"
+    pub profile: &'static str,
+    pub target: &'static str,
+    /// Cargo features of the engine crate
+    pub features: Vec<&'static str>,
+}
+
+/// Driver strings of the OpenGL context
+#[derive(Clone, Debug)]
+pub struct GpuInfo {
+    pub vendor: String,
+    pub renderer: String,
+    pub version: String,
+    pub shading_language_version: String,
+}
+
+impl BuildInfo {
+    pub fn get() -> Self {
+        Self {
+            version: env!("CARGO_PKG_VERSION"),
+            git_hash: env!("FERRITE_GIT_HASH"),
+            profile: env!("FERRITE_BUILD_PROFILE"),
+            target: env!("FERRITE_TARGET"),
+            features: env!("FERRITE_FEATURES")
+                .split(',')
+                .filter(|feature| !feature.is_empty())
+                .collect(),
+        }
+    }
+
+    /// Lines of the about panel, the build followed by the gpu if the context was created
+    pub fn get_lines() -> Vec<String> {
+        let build = BuildInfo::get();
+        let mut lines = vec![
+            format!("ferrite {}", build),
+            format!("target: {}", build.target),
+            format!(
+                "features: {}",
+                if build.features.is_empty() {
+                    String::from("none")
+                } else {
+                    build.features.join(", ")
+                }
+            ),
+        ];
+        match GpuInfo::get() {
+            Some(gpu) => {
+                lines.push(format!("gpu: {} ({})", gpu.renderer, gpu.vendor));
+                lines.push(format!(
+                    "opengl: {}, glsl {}",
+                    gpu.version, gpu.shading_language_version
+                ));
+            }
+            None => lines.push(String::from("gpu: no OpenGL context")),
+        }
+        lines
+    }
+}
+
+impl fmt::Display for BuildInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.version)?;
+        if !self.git_hash.is_empty() {
+            write!(f, " ({})", self.git_hash)?;
+        }
+        write!(f, " {}", self.profile)
+    }
+}
+
+impl GpuInfo {
+    /// Strings of the current context, queried once the gl functions are loaded. Has to be
+    /// called on the thread owning the context the first time
+    pub fn get() -> Option<&'static GpuInfo> {
+        if let Some(info) = GPU_INFO.get() {
+            return Some(info);
+        }
+        if !gl::GetString::is_loaded() {
+            return None;
+        }
+        Some(GPU_INFO.get_or_init(|| GpuInfo {
+            vendor: GpuInfo::get_string(gl::VENDOR),
+            renderer: GpuInfo::get_string(gl::RENDERER),
+            version: GpuInfo::get_string(gl::VERSION),
+            shading_language_version: GpuInfo::get_string(gl::SHADING_LANGUAGE_VERSION),
+        }))
+    }
+
+    fn get_string(name: gl::types::GLenum) -> String {
+        unsafe {
+            let string = gl::GetString(name);
+            if string.is_null() {
+                return String::from("unknown");
+            }
+            CStr::from_ptr(string as *const _)
+                .to_string_lossy()
+                .into_owned()
+        }
+    }
+}
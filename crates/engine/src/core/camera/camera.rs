@@ -0,0 +1,160 @@
+use cgmath::{
+    perspective, EuclideanSpace, Euler, InnerSpace, Matrix4, Point3, Rad, SquareMatrix, Vector3,
+    Vector4,
+};
+
+use super::{Camera, Projection, OPENGL_TO_WGPU_MATRIX};
+
+impl Camera {
+    pub fn new<V: Into<Point3<f32>>, Y: Into<Rad<f32>>, P: Into<Rad<f32>>>(
+        position: V,
+        yaw: Y,
+        pitch: P,
+    ) -> Self {
+        Self {
+            relative_position: Point3::origin(),
+            position: position.into(),
+            yaw: yaw.into(),
+            pitch: pitch.into(),
+            matrix: Matrix4::identity(),
+        }
+    }
+
+    fn calc_matrix(&mut self) {
+        self.matrix = Matrix4::look_to_rh(
+            self.get_eye_position(),
+            self.get_direction(),
+            Vector3::unit_y(),
+        );
+    }
+
+    pub fn calc_rotation_matrix(&self) -> Matrix4<f32> {
+        Matrix4::from(Euler::new(-self.yaw, -self.pitch, Rad(0.0)))
+    }
+
+    pub fn update(&mut self, position: Point3<f32>, yaw: Rad<f32>, pitch: Rad<f32>) {
+        self.relative_position = position;
+        self.yaw = yaw;
+        self.pitch = pitch;
+        self.calc_matrix();
+    }
+
+    pub fn set_position<P: Into<Point3<f32>>>(&mut self, position: P) {
+        self.position = position.into();
+        self.calc_matrix();
+    }
+
+    pub fn set_relative_position<P: Into<Point3<f32>>>(&mut self, position: P) {
+        self.relative_position = position.into();
+        self.calc_matrix();
+    }
+
+    pub fn get_position(&self) -> Point3<f32> {
+        self.position
+    }
+
+    pub fn get_relative_position(&self) -> Point3<f32> {
+        self.relative_position
+    }
+
+    pub fn get_yaw(&self) -> Rad<f32> {
+        self.yaw
+    }
+
+    pub fn get_pitch(&self) -> Rad<f32> {
+        self.pitch
+    }
+
+    pub fn get_matrix(&self) -> Matrix4<f32> {
+        self.matrix
+    }
+
+    /// World position the camera looks from
+    pub fn get_eye_position(&self) -> Point3<f32> {
+        self.position + self.relative_position.to_vec()
+    }
+
+    /// Normalized view direction
+    pub fn get_direction(&self) -> Vector3<f32> {
+        let (sin_pitch, cos_pitch) = self.pitch.0.sin_cos();
+        let (sin_yaw, cos_yaw) = self.yaw.0.sin_cos();
+        Vector3::new(cos_pitch * cos_yaw, sin_pitch, cos_pitch * sin_yaw).normalize()
+    }
+
+    /// Normalized direction of the ray from the eye through a point in normalized device coordinates
+    pub fn get_ray_direction(&self, projection: &Projection, x: f32, y: f32) -> Vector3<f32> {
+        let inverse_projection = projection
+            .get_matrix()
+            .invert()
+            .unwrap_or(Matrix4::identity());
+        let ray_eye = inverse_projection * Vector4::new(x, y, -1.0, 1.0);
+        let ray_eye = Vector4::new(ray_eye.x, ray_eye.y, -1.0, 0.0);
+        (self.matrix.invert().unwrap_or(Matrix4::identity()) * ray_eye)
+            .truncate()
+            .normalize()
+    }
+}
+
+impl Projection {
+    pub fn new<F: Into<Rad<f32>>>(width: u32, height: u32, fovy: F, znear: f32, zfar: f32) -> Self {
+        let mut projection = Self {
+            aspect: width as f32 / height as f32,
+            fovy: fovy.into(),
+            znear,
+            zfar,
+            tile: Matrix4::identity(),
+            matrix: Matrix4::identity(),
+        };
+        projection.calc_matrix();
+        projection
+    }
+
+    pub fn resize(&mut self, event: &glfw::WindowEvent) {
+        if let glfw::WindowEvent::FramebufferSize(width, height) = event {
+            self.aspect = *width as f32 / *height as f32;
+            self.calc_matrix();
+            unsafe {
+                gl::Viewport(0, 0, *width, *height);
+            }
+        }
+    }
+
+    fn calc_matrix(&mut self) {
+        self.matrix = self.tile
+            * OPENGL_TO_WGPU_MATRIX
+            * perspective(self.fovy, self.aspect, self.znear, self.zfar);
+    }
+
+    pub fn set_fovy<F: Into<Rad<f32>>>(&mut self, fovy: F) {
+        self.fovy = fovy.into();
+        self.calc_matrix();
+    }
+
+    /// Restricts the projection to tile `(x, y)` of a `tiles`x`tiles` grid over the view, counted from the bottom left
+    pub fn set_tile(&mut self, tiles: u32, x: u32, y: u32) {
+        let tiles_f = tiles as f32;
+        self.tile = Matrix4::from_translation(Vector3::new(
+            tiles_f - 1.0 - 2.0 * x as f32,
+            tiles_f - 1.0 - 2.0 * y as f32,
+            0.0,
+        )) * Matrix4::from_nonuniform_scale(tiles_f, tiles_f, 1.0);
+        self.calc_matrix();
+    }
+
+    pub fn reset_tile(&mut self) {
+        self.tile = Matrix4::identity();
+        self.calc_matrix();
+    }
+
+    pub fn get_znear(&self) -> f32 {
+        self.znear
+    }
+
+    pub fn get_zfar(&self) -> f32 {
+        self.zfar
+    }
+
+    pub fn get_matrix(&self) -> Matrix4<f32> {
+        self.matrix
+    }
+}
@@ -0,0 +1,128 @@
+use cgmath::{Deg, Point3, Vector3};
+
+use crate::core::{
+    camera::{Camera, Projection},
+    input::Input,
+    scene::Scene,
+    utils::DataSource,
+};
+
+use super::{
+    CameraController, CameraControllerType, CameraKeyframe, CameraSettings, CinematicController,
+    CinematicSettings,
+};
+
+impl CinematicController {
+    pub fn from_settings(settings: &CinematicSettings, speed: DataSource<f32>) -> Self {
+        speed.write(settings.speed);
+        Self {
+            path: settings.path.clone(),
+            speed,
+            looped: settings.looped,
+            time: 0.0,
+            playing: true,
+        }
+    }
+
+    pub fn is_playing(&self) -> bool {
+        self.playing
+    }
+
+    pub fn set_playing(&mut self, playing: bool) {
+        self.playing = playing;
+    }
+
+    pub fn restart(&mut self) {
+        self.time = 0.0;
+        self.playing = true;
+    }
+
+    /// Keyframe `index` positions away from the start, wrapped for looped paths and clamped otherwise
+    fn get_keyframe(&self, index: i32) -> &CameraKeyframe {
+        let count = self.path.len() as i32;
+        let index = if self.looped {
+            index.rem_euclid(count)
+        } else {
+            index.clamp(0, count - 1)
+        };
+        &self.path[index as usize]
+    }
+
+    fn get_segment_count(&self) -> f32 {
+        if self.looped {
+            self.path.len() as f32
+        } else {
+            (self.path.len() - 1) as f32
+        }
+    }
+}
+
+impl CameraController for CinematicController {
+    fn get_type(&self) -> CameraControllerType {
+        CameraControllerType::Cinematic
+    }
+
+    fn process_keyboard(&mut self, _: &mut glfw::Window, event: &glfw::WindowEvent) -> bool {
+        match Input::get_pressed_action(&["cinematic_play", "cinematic_restart"], event) {
+            Some("cinematic_play") => self.playing = !self.playing,
+            Some(_) => self.restart(),
+            None => return false,
+        }
+        true
+    }
+
+    fn process_mouse(&mut self, _: &mut glfw::Window, _: &glfw::WindowEvent) {}
+
+    fn update_camera(&mut self, _: &Scene, camera: &mut Camera, _: &Projection, delta_time: f32) {
+        if self.path.is_empty() {
+            return;
+        }
+        if self.playing {
+            self.time += self.speed.read() * delta_time;
+            let segments = self.get_segment_count();
+            if self.looped {
+                self.time = self.time.rem_euclid(segments.max(1.0));
+            } else if self.time >= segments {
+                self.time = segments;
+                self.playing = false;
+            }
+        }
+
+        let segment = self.time.floor() as i32;
+        let t = self.time - self.time.floor();
+        let points = [
+            self.get_keyframe(segment - 1),
+            self.get_keyframe(segment),
+            self.get_keyframe(segment + 1),
+            self.get_keyframe(segment + 2),
+        ];
+        let [p0, p1, p2, p3] = points.map(|k| Vector3::from(k.position));
+
+        // Catmull-Rom spline through the keyframe positions
+        let t2 = t * t;
+        let t3 = t2 * t;
+        let position = ((p1 * 2.0)
+            + (p2 - p0) * t
+            + (p0 * 2.0 - p1 * 5.0 + p2 * 4.0 - p3) * t2
+            + (p1 * 3.0 - p0 - p2 * 3.0 + p3) * t3)
+            * 0.5;
+        // turn the short way around when the yaw wraps between keyframes
+        let yaw_delta = (points[2].yaw - points[1].yaw + 180.0).rem_euclid(360.0) - 180.0;
+        let yaw = points[1].yaw + yaw_delta * t;
+        let pitch = points[1].pitch + (points[2].pitch - points[1].pitch) * t;
+
+        camera.update(
+            Point3::new(position.x, position.y, position.z),
+            Deg(yaw).into(),
+            Deg(pitch).into(),
+        );
+    }
+
+    fn get_speed_ref(&self) -> DataSource<f32> {
+        self.speed.clone()
+    }
+
+    fn store_settings(&self, settings: &mut CameraSettings) {
+        settings.cinematic.speed = self.speed.read();
+    }
+}
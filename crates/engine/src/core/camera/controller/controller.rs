@@ -0,0 +1,230 @@
+use cgmath::Rad;
+use glfw::{Action, CursorMode, GamepadAxis};
+
+use crate::core::{camera::SAFE_FRAC_PI_2, input::Input, utils::DataSource};
+
+use super::{
+    CameraController, CameraControllerType, CameraKeyframe, CameraSettings, CinematicController,
+    CinematicSettings, FlyController, FlySettings, FollowController, FollowSettings, FpsController,
+    FpsSettings, LookInput, MovementInput, OrbitController, OrbitSettings, GAMEPAD_LOOK_SPEED,
+};
+
+impl CameraControllerType {
+    pub fn all() -> [CameraControllerType; 5] {
+        [
+            CameraControllerType::Fly,
+            CameraControllerType::Orbit,
+            CameraControllerType::Fps,
+            CameraControllerType::Cinematic,
+            CameraControllerType::Follow,
+        ]
+    }
+
+    pub fn get_name(&self) -> &str {
+        match self {
+            CameraControllerType::Fly => "Fly",
+            CameraControllerType::Orbit => "Orbit",
+            CameraControllerType::Fps => "FPS",
+            CameraControllerType::Cinematic => "Cinematic",
+            CameraControllerType::Follow => "Follow",
+        }
+    }
+}
+
+impl CameraSettings {
+    /// Creates the controller selected in the settings
+    pub fn create_controller(&self) -> Box<dyn CameraController> {
+        self.create_controller_of(self.controller, DataSource::new(0.0))
+    }
+
+    /// Creates a controller of `controller_type`, its speed is written to `speed` so
+    /// UI elements bound to the previous controller's speed keep working
+    pub fn create_controller_of(
+        &self,
+        controller_type: CameraControllerType,
+        speed: DataSource<f32>,
+    ) -> Box<dyn CameraController> {
+        match controller_type {
+            CameraControllerType::Fly => Box::new(FlyController::from_settings(&self.fly, speed)),
+            CameraControllerType::Orbit => {
+                Box::new(OrbitController::from_settings(&self.orbit, speed))
+            }
+            CameraControllerType::Fps => Box::new(FpsController::from_settings(&self.fps, speed)),
+            CameraControllerType::Cinematic => {
+                Box::new(CinematicController::from_settings(&self.cinematic, speed))
+            }
+            CameraControllerType::Follow => {
+                Box::new(FollowController::from_settings(&self.follow, speed))
+            }
+        }
+    }
+}
+
+impl Default for FlySettings {
+    fn default() -> Self {
+        Self {
+            speed: 10.0,
+            sensitivity: 1.0,
+            altitude_scaling: true,
+        }
+    }
+}
+
+impl Default for OrbitSettings {
+    fn default() -> Self {
+        Self {
+            speed: 2.0,
+            sensitivity: 1.0,
+            distance: 10.0,
+            min_distance: 2.0,
+            max_distance: 100.0,
+        }
+    }
+}
+
+impl Default for FpsSettings {
+    fn default() -> Self {
+        Self {
+            speed: 5.0,
+            sensitivity: 1.0,
+            gravity: 9.81,
+            jump_speed: 5.0,
+            eye_height: 1.7,
+        }
+    }
+}
+
+impl Default for FollowSettings {
+    fn default() -> Self {
+        Self {
+            speed: 1.0,
+            sensitivity: 1.0,
+            target: "player".to_string(),
+            distance: 5.0,
+            min_distance: 1.5,
+            max_distance: 20.0,
+            height: 1.6,
+        }
+    }
+}
+
+impl Default for CinematicSettings {
+    fn default() -> Self {
+        let keyframe = |x: f32, z: f32, yaw: f32| CameraKeyframe {
+            position: [x, 30.0, z],
+            yaw,
+            pitch: -20.0,
+        };
+        Self {
+            speed: 0.25,
+            looped: true,
+            path: vec![
+                keyframe(40.0, 0.0, 180.0),
+                keyframe(0.0, 40.0, 270.0),
+                keyframe(-40.0, 0.0, 360.0),
+                keyframe(0.0, -40.0, 450.0),
+            ],
+        }
+    }
+}
+
+impl MovementInput {
+    pub fn is_moving(&self) -> bool {
+        self.get_axes() != (0.0, 0.0, 0.0)
+    }
+
+    /// Movement along the forward, right and up axes from -1 to 1, the keys combined with the
+    /// left stick and the triggers
+    pub fn get_axes(&self) -> (f32, f32, f32) {
+        let forward = self.forward - self.backward - Input::get_axis(GamepadAxis::AxisLeftY);
+        let right = self.right - self.left + Input::get_axis(GamepadAxis::AxisLeftX);
+        let up = self.up - self.down + Input::get_axis(GamepadAxis::AxisRightTrigger)
+            - Input::get_axis(GamepadAxis::AxisLeftTrigger);
+        (
+            forward.clamp(-1.0, 1.0),
+            right.clamp(-1.0, 1.0),
+            up.clamp(-1.0, 1.0),
+        )
+    }
+
+    /// Presses and releases the movement actions like "move_forward"
+    pub fn process_keyboard(&mut self, event: &glfw::WindowEvent) -> bool {
+        let mut handled = false;
+        for (action, amount) in [
+            ("move_forward", &mut self.forward),
+            ("move_backward", &mut self.backward),
+            ("move_left", &mut self.left),
+            ("move_right", &mut self.right),
+            ("move_up", &mut self.up),
+            ("move_down", &mut self.down),
+        ] {
+            match Input::get_action(action, event) {
+                Some(Action::Press) => *amount = 1.0,
+                Some(Action::Release) => *amount = 0.0,
+                _ => continue,
+            }
+            handled = true;
+        }
+        handled
+    }
+}
+
+impl LookInput {
+    /// Toggles capturing the cursor with the "toggle_cursor" action
+    pub fn process_keyboard(
+        &mut self,
+        window: &mut glfw::Window,
+        event: &glfw::WindowEvent,
+    ) -> bool {
+        if !Input::is_action_pressed("toggle_cursor", event) {
+            return false;
+        }
+        match window.get_cursor_mode() {
+            CursorMode::Disabled => window.set_cursor_mode(CursorMode::Normal),
+            CursorMode::Normal => window.set_cursor_mode(CursorMode::Disabled),
+            _ => {}
+        }
+        self.is_active = !self.is_active;
+        true
+    }
+
+    pub fn process_mouse(&mut self, window: &mut glfw::Window, event: &glfw::WindowEvent) {
+        if let glfw::WindowEvent::CursorPos(xpos, ypos) = event {
+            if window.get_cursor_mode() == CursorMode::Disabled && self.is_active {
+                self.rotate_horizontal = *xpos as f32;
+                self.rotate_vertical = *ypos as f32;
+
+                if self.rotate_horizontal.abs() > 250.0 {
+                    self.rotate_horizontal = 0.0;
+                }
+                if self.rotate_vertical.abs() > 250.0 {
+                    self.rotate_vertical = 0.0;
+                }
+
+                window.set_cursor_pos(0.0, 0.0);
+            }
+        }
+    }
+
+    /// Applies and resets the captured mouse movement and turns by the right stick, the pitch is
+    /// kept from going too high/low
+    pub fn rotate(
+        &mut self,
+        yaw: Rad<f32>,
+        pitch: Rad<f32>,
+        sensitivity: f32,
+        delta_time: f32,
+    ) -> (Rad<f32>, Rad<f32>) {
+        let yaw = yaw + Rad(self.rotate_horizontal) * sensitivity * delta_time;
+        let pitch = pitch + Rad(-self.rotate_vertical) * sensitivity * delta_time;
+        let stick_turn = GAMEPAD_LOOK_SPEED * sensitivity * delta_time;
+        let yaw = yaw + Rad(Input::get_axis(GamepadAxis::AxisRightX) * stick_turn);
+        let pitch = pitch - Rad(Input::get_axis(GamepadAxis::AxisRightY) * stick_turn);
+
+        self.rotate_horizontal = 0.0;
+        self.rotate_vertical = 0.0;
+
+        let pitch = Rad(pitch.0.clamp(-SAFE_FRAC_PI_2, SAFE_FRAC_PI_2));
+        (yaw, pitch)
+    }
+}
@@ -0,0 +1,176 @@
+use cgmath::{EuclideanSpace, InnerSpace, Vector3};
+use glfw::CursorMode;
+
+use crate::core::{
+    camera::{Camera, Projection},
+    input::Input,
+    scene::Scene,
+    utils::DataSource,
+};
+
+use super::{
+    CameraController, CameraControllerType, CameraFlight, CameraSettings, FlyController,
+    FlySettings, LookInput, MovementInput,
+};
+
+/// Factor the speed changes by per scroll step
+const SCROLL_SPEED_FACTOR: f32 = 1.2;
+const MIN_SPEED: f32 = 0.1;
+/// Height above the terrain up to which the speed is not scaled
+const ALTITUDE_SPEED_REFERENCE: f32 = 32.0;
+/// Distance the camera stops in front of the point it flies to
+const FLY_TO_DISTANCE: f32 = 8.0;
+/// Seconds a fly-to-cursor flight takes
+const FLY_TO_DURATION: f32 = 0.75;
+
+impl FlyController {
+    pub fn new(speed: f32, sensitivity: f32) -> Self {
+        Self::from_settings(
+            &FlySettings {
+                speed,
+                sensitivity,
+                ..FlySettings::default()
+            },
+            DataSource::new(speed),
+        )
+    }
+
+    pub fn from_settings(settings: &FlySettings, speed: DataSource<f32>) -> Self {
+        speed.write(settings.speed);
+        Self {
+            movement: MovementInput::default(),
+            look: LookInput::default(),
+            speed,
+            sensitivity: settings.sensitivity,
+            altitude_scaling: settings.altitude_scaling,
+            fly_to_cursor: None,
+            flight: None,
+        }
+    }
+
+    /// Speed after scaling it with the height of the camera above the terrain
+    fn get_scaled_speed(&self, scene: &Scene, camera: &Camera) -> f32 {
+        let speed = self.speed.read();
+        if !self.altitude_scaling {
+            return speed;
+        }
+        let eye = camera.get_eye_position();
+        match scene.physics_engine.height_at(eye.x, eye.z) {
+            Some(height) => speed * ((eye.y - height) / ALTITUDE_SPEED_REFERENCE).max(1.0),
+            None => speed,
+        }
+    }
+
+    /// Starts a flight towards the terrain hit by the ray through `cursor`
+    fn start_flight(
+        &mut self,
+        scene: &Scene,
+        camera: &Camera,
+        projection: &Projection,
+        cursor: (f32, f32),
+    ) {
+        let eye = camera.get_eye_position();
+        let direction = camera.get_ray_direction(projection, cursor.0, cursor.1);
+        let Some(distance) = scene
+            .physics_engine
+            .cast_ray(eye, direction, projection.get_zfar())
+        else {
+            return;
+        };
+        let target = eye + direction * (distance - FLY_TO_DISTANCE).max(0.0);
+        self.flight = Some(CameraFlight {
+            start: camera.relative_position,
+            target: target - camera.position.to_vec(),
+            time: 0.0,
+        });
+    }
+}
+
+impl CameraController for FlyController {
+    fn get_type(&self) -> CameraControllerType {
+        CameraControllerType::Fly
+    }
+
+    fn process_keyboard(&mut self, window: &mut glfw::Window, event: &glfw::WindowEvent) -> bool {
+        if Input::is_action_pressed("fly_to_cursor", event) {
+            // while looking around the cursor is captured and the view center is aimed at
+            self.fly_to_cursor = Some(if window.get_cursor_mode() == CursorMode::Disabled {
+                (0.0, 0.0)
+            } else {
+                let (x, y) = window.get_cursor_pos();
+                let (width, height) = window.get_size();
+                (
+                    (2.0 * x / width as f64 - 1.0) as f32,
+                    (1.0 - 2.0 * y / height as f64) as f32,
+                )
+            });
+            return true;
+        }
+        self.movement.process_keyboard(event) || self.look.process_keyboard(window, event)
+    }
+
+    fn process_mouse(&mut self, window: &mut glfw::Window, event: &glfw::WindowEvent) {
+        self.look.process_mouse(window, event);
+        if let glfw::WindowEvent::Scroll(_, y) = event {
+            // logarithmic, so every step changes slow and fast speeds noticeably
+            let speed = self.speed.read().max(MIN_SPEED) * SCROLL_SPEED_FACTOR.powf(*y as f32);
+            self.set_speed(speed.max(MIN_SPEED));
+        }
+    }
+
+    fn update_camera(
+        &mut self,
+        scene: &Scene,
+        camera: &mut Camera,
+        projection: &Projection,
+        delta_time: f32,
+    ) {
+        if let Some(cursor) = self.fly_to_cursor.take() {
+            self.start_flight(scene, camera, projection, cursor);
+        }
+        if self.movement.is_moving() {
+            self.flight = None;
+        }
+
+        let mut position = camera.relative_position;
+        if let Some(flight) = &mut self.flight {
+            flight.time += delta_time;
+            let progress = (flight.time / FLY_TO_DURATION).min(1.0);
+            let eased = progress * progress * (3.0 - 2.0 * progress);
+            position = flight.start + (flight.target - flight.start) * eased;
+            if progress >= 1.0 {
+                self.flight = None;
+            }
+        } else {
+            // Move forward/backward and left/right
+            let (yaw_sin, yaw_cos) = camera.yaw.0.sin_cos();
+            let forward = Vector3::new(yaw_cos, 0.0, yaw_sin).normalize();
+            let right = Vector3::new(-yaw_sin, 0.0, yaw_cos).normalize();
+
+            let speed = self.get_scaled_speed(scene, camera);
+            let (forward_amount, right_amount, up_amount) = self.movement.get_axes();
+
+            position += forward * forward_amount * speed * delta_time;
+            position += right * right_amount * speed * delta_time;
+
+            // Move up/down. Since we don't use roll, we can just
+            // modify the y coordinate directly.
+            position.y += up_amount * speed * delta_time;
+        }
+
+        let (yaw, pitch) = self
+            .look
+            .rotate(camera.yaw, camera.pitch, self.sensitivity, delta_time);
+        camera.update(position, yaw, pitch);
+    }
+
+    fn get_speed_ref(&self) -> DataSource<f32> {
+        self.speed.clone()
+    }
+
+    fn store_settings(&self, settings: &mut CameraSettings) {
+        settings.fly.speed = self.speed.read();
+        settings.fly.sensitivity = self.sensitivity;
+        settings.fly.altitude_scaling = self.altitude_scaling;
+    }
+}
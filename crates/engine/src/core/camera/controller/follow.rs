@@ -0,0 +1,126 @@
+use cgmath::{EuclideanSpace, InnerSpace, Point3, Vector3};
+
+use crate::core::{
+    camera::{Camera, Projection},
+    entity::EntityFilter,
+    scene::Scene,
+    utils::DataSource,
+};
+
+use super::{
+    CameraController, CameraControllerType, CameraSettings, FollowController, FollowSettings,
+    LookInput,
+};
+
+/// Distance the camera keeps in front of terrain between it and the target
+const COLLISION_MARGIN: f32 = 0.3;
+/// Closest the camera is pulled in by terrain behind it
+const MIN_COLLISION_DISTANCE: f32 = 0.5;
+/// Speed in units per second at which the camera moves back out once the terrain is cleared
+const ZOOM_OUT_SPEED: f32 = 8.0;
+
+impl FollowController {
+    pub fn from_settings(settings: &FollowSettings, speed: DataSource<f32>) -> Self {
+        speed.write(settings.speed);
+        Self {
+            look: LookInput::default(),
+            speed,
+            sensitivity: settings.sensitivity,
+            target: settings.target.clone(),
+            distance: settings.distance,
+            min_distance: settings.min_distance,
+            max_distance: settings.max_distance,
+            height: settings.height,
+            current_distance: settings.distance,
+        }
+    }
+
+    pub fn get_distance(&self) -> f32 {
+        self.distance
+    }
+
+    pub fn set_distance(&mut self, distance: f32) {
+        self.distance = distance.clamp(self.min_distance, self.max_distance);
+    }
+
+    /// Follows the first entity tagged `target` from the next update on
+    pub fn set_target(&mut self, target: &str) {
+        self.target = target.to_string();
+    }
+
+    /// World position of the followed entity, None if no entity has the target tag
+    fn find_target(&self, scene: &Scene) -> Option<Point3<f32>> {
+        let entity = scene
+            .query_filter(&EntityFilter::has_tag(&self.target))
+            .into_iter()
+            .next()?;
+        let transform = scene.get_world_transform(&entity)?;
+        Some(Point3::from_vec(transform.w.truncate()))
+    }
+}
+
+impl CameraController for FollowController {
+    fn get_type(&self) -> CameraControllerType {
+        CameraControllerType::Follow
+    }
+
+    fn process_keyboard(&mut self, window: &mut glfw::Window, event: &glfw::WindowEvent) -> bool {
+        self.look.process_keyboard(window, event)
+    }
+
+    fn process_mouse(&mut self, window: &mut glfw::Window, event: &glfw::WindowEvent) {
+        self.look.process_mouse(window, event);
+        if let glfw::WindowEvent::Scroll(_, y) = event {
+            self.set_distance(self.distance - *y as f32 * self.speed.read());
+        }
+    }
+
+    fn update_camera(
+        &mut self,
+        scene: &Scene,
+        camera: &mut Camera,
+        _: &Projection,
+        delta_time: f32,
+    ) {
+        let (yaw, pitch) = self
+            .look
+            .rotate(camera.yaw, camera.pitch, self.sensitivity, delta_time);
+        if let Some(target) = self.find_target(scene) {
+            camera.position = target;
+        }
+
+        let (sin_pitch, cos_pitch) = pitch.0.sin_cos();
+        let (sin_yaw, cos_yaw) = yaw.0.sin_cos();
+        let direction =
+            Vector3::new(cos_pitch * cos_yaw, sin_pitch, cos_pitch * sin_yaw).normalize();
+        let pivot = Vector3::new(0.0, self.height, 0.0);
+
+        // pull the camera in front of terrain between it and the target, then ease back out
+        let free_distance = scene
+            .physics_engine
+            .cast_ray(camera.position + pivot, -direction, self.distance)
+            .map_or(self.distance, |hit| {
+                (hit - COLLISION_MARGIN).max(MIN_COLLISION_DISTANCE)
+            });
+        self.current_distance = if free_distance < self.current_distance {
+            free_distance
+        } else {
+            (self.current_distance + ZOOM_OUT_SPEED * delta_time).min(free_distance)
+        };
+        camera.update(
+            Point3::from_vec(pivot - direction * self.current_distance),
+            yaw,
+            pitch,
+        );
+    }
+
+    fn get_speed_ref(&self) -> DataSource<f32> {
+        self.speed.clone()
+    }
+
+    fn store_settings(&self, settings: &mut CameraSettings) {
+        settings.follow.speed = self.speed.read();
+        settings.follow.sensitivity = self.sensitivity;
+        settings.follow.distance = self.distance;
+    }
+}
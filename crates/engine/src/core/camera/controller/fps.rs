@@ -0,0 +1,109 @@
+use cgmath::{EuclideanSpace, InnerSpace, Vector3};
+use glfw::GamepadButton;
+
+use crate::core::{
+    camera::{Camera, Projection},
+    input::Input,
+    scene::Scene,
+    utils::DataSource,
+};
+
+use super::{
+    CameraController, CameraControllerType, CameraSettings, FpsController, FpsSettings, LookInput,
+    MovementInput,
+};
+
+/// Distance below the feet in which the ground is still detected
+const GROUND_TOLERANCE: f32 = 0.05;
+
+impl FpsController {
+    pub fn from_settings(settings: &FpsSettings, speed: DataSource<f32>) -> Self {
+        speed.write(settings.speed);
+        Self {
+            movement: MovementInput::default(),
+            look: LookInput::default(),
+            speed,
+            sensitivity: settings.sensitivity,
+            gravity: settings.gravity,
+            jump_speed: settings.jump_speed,
+            eye_height: settings.eye_height,
+            vertical_velocity: 0.0,
+            grounded: false,
+        }
+    }
+
+    pub fn is_grounded(&self) -> bool {
+        self.grounded
+    }
+}
+
+impl CameraController for FpsController {
+    fn get_type(&self) -> CameraControllerType {
+        CameraControllerType::Fps
+    }
+
+    fn process_keyboard(&mut self, window: &mut glfw::Window, event: &glfw::WindowEvent) -> bool {
+        self.movement.process_keyboard(event) || self.look.process_keyboard(window, event)
+    }
+
+    fn process_mouse(&mut self, window: &mut glfw::Window, event: &glfw::WindowEvent) {
+        self.look.process_mouse(window, event);
+    }
+
+    fn update_camera(
+        &mut self,
+        scene: &Scene,
+        camera: &mut Camera,
+        _: &Projection,
+        delta_time: f32,
+    ) {
+        let (yaw_sin, yaw_cos) = camera.yaw.0.sin_cos();
+        let forward = Vector3::new(yaw_cos, 0.0, yaw_sin).normalize();
+        let right = Vector3::new(-yaw_sin, 0.0, yaw_cos).normalize();
+
+        let mut position = camera.relative_position;
+        let speed = self.speed.read();
+        let (forward_amount, right_amount, up_amount) = self.movement.get_axes();
+
+        position += forward * forward_amount * speed * delta_time;
+        position += right * right_amount * speed * delta_time;
+
+        if self.grounded && (up_amount > 0.0 || Input::is_button_down(GamepadButton::ButtonA)) {
+            self.vertical_velocity = self.jump_speed;
+        }
+        self.vertical_velocity -= self.gravity * delta_time;
+        let fall = self.vertical_velocity * delta_time;
+
+        // look for the ground below the eye, far enough to catch it within this frame's fall
+        let eye = camera.position + position.to_vec();
+        let ground_distance = scene.physics_engine.cast_ray(
+            eye,
+            -Vector3::unit_y(),
+            self.eye_height + (-fall).max(0.0) + GROUND_TOLERANCE,
+        );
+        self.grounded = false;
+        position.y += fall;
+        if let Some(distance) = ground_distance {
+            let ground = eye.y - distance;
+            if self.vertical_velocity <= 0.0 && eye.y + fall - self.eye_height <= ground {
+                position.y = ground + self.eye_height - camera.position.y;
+                self.vertical_velocity = 0.0;
+                self.grounded = true;
+            }
+        }
+
+        let (yaw, pitch) = self
+            .look
+            .rotate(camera.yaw, camera.pitch, self.sensitivity, delta_time);
+        camera.update(position, yaw, pitch);
+    }
+
+    fn get_speed_ref(&self) -> DataSource<f32> {
+        self.speed.clone()
+    }
+
+    fn store_settings(&self, settings: &mut CameraSettings) {
+        settings.fps.speed = self.speed.read();
+        settings.fps.sensitivity = self.sensitivity;
+    }
+}
@@ -0,0 +1,219 @@
+use cgmath::Point3;
+use serde::{Deserialize, Serialize};
+
+use crate::core::{scene::Scene, utils::DataSource};
+
+use super::{Camera, Projection};
+
+mod cinematic;
+mod controller;
+mod fly;
+mod follow;
+mod fps;
+mod orbit;
+
+/// Turn rate in radians per second of a fully deflected right stick at a sensitivity of 1
+const GAMEPAD_LOOK_SPEED: f32 = 2.5;
+
+/// Moves a camera from keyboard, mouse and gamepad input, the active controller of a
+/// `CameraComponent` can be swapped at runtime
+pub trait CameraController {
+    fn get_type(&self) -> CameraControllerType;
+    fn process_keyboard(&mut self, window: &mut glfw::Window, event: &glfw::WindowEvent) -> bool;
+    fn process_mouse(&mut self, window: &mut glfw::Window, event: &glfw::WindowEvent);
+    fn update_camera(
+        &mut self,
+        scene: &Scene,
+        camera: &mut Camera,
+        projection: &Projection,
+        delta_time: f32,
+    );
+    fn get_speed_ref(&self) -> DataSource<f32>;
+    fn get_speed(&self) -> f32 {
+        self.get_speed_ref().read()
+    }
+    fn set_speed(&mut self, speed: f32) {
+        self.get_speed_ref().write(speed.max(0.0));
+    }
+    /// Writes the current settings of the controller back into `settings`
+    fn store_settings(&self, settings: &mut CameraSettings);
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CameraControllerType {
+    #[default]
+    Fly,
+    Orbit,
+    Fps,
+    Cinematic,
+    Follow,
+}
+
+/// Settings of all camera controllers, stored in the `EngineConfig`
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CameraSettings {
+    /// Controller the camera starts with
+    pub controller: CameraControllerType,
+    pub fly: FlySettings,
+    pub orbit: OrbitSettings,
+    pub fps: FpsSettings,
+    pub cinematic: CinematicSettings,
+    pub follow: FollowSettings,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FlySettings {
+    pub speed: f32,
+    pub sensitivity: f32,
+    /// Multiplies the speed with the height above the terrain, so high flights cover more ground
+    pub altitude_scaling: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct OrbitSettings {
+    /// Zoom speed per scroll step
+    pub speed: f32,
+    pub sensitivity: f32,
+    pub distance: f32,
+    pub min_distance: f32,
+    pub max_distance: f32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FpsSettings {
+    pub speed: f32,
+    pub sensitivity: f32,
+    pub gravity: f32,
+    pub jump_speed: f32,
+    /// Height of the camera above the ground it stands on
+    pub eye_height: f32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CinematicSettings {
+    /// Path segments travelled per second
+    pub speed: f32,
+    pub looped: bool,
+    pub path: Vec<CameraKeyframe>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FollowSettings {
+    /// Zoom speed per scroll step
+    pub speed: f32,
+    pub sensitivity: f32,
+    /// Tag of the entity to follow
+    pub target: String,
+    pub distance: f32,
+    pub min_distance: f32,
+    pub max_distance: f32,
+    /// Height above the target's origin the camera looks at
+    pub height: f32,
+}
+
+/// Point on a cinematic camera path, the position is relative to the camera's anchor
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct CameraKeyframe {
+    pub position: [f32; 3],
+    /// Yaw in degrees
+    pub yaw: f32,
+    /// Pitch in degrees
+    pub pitch: f32,
+}
+
+/// Free flying camera, `F` flies to the terrain under the cursor
+pub struct FlyController {
+    movement: MovementInput,
+    look: LookInput,
+    speed: DataSource<f32>,
+    sensitivity: f32,
+    altitude_scaling: bool,
+    /// Cursor position in normalized device coordinates to fly to on the next update
+    fly_to_cursor: Option<(f32, f32)>,
+    flight: Option<CameraFlight>,
+}
+
+/// Eased movement of the camera towards a point
+#[derive(Debug)]
+struct CameraFlight {
+    /// Relative positions of the camera at the start and the end of the flight
+    start: Point3<f32>,
+    target: Point3<f32>,
+    /// Seconds since the flight started
+    time: f32,
+}
+
+/// Rotates around the camera's anchor at a zoomable distance
+pub struct OrbitController {
+    look: LookInput,
+    speed: DataSource<f32>,
+    sensitivity: f32,
+    distance: f32,
+    min_distance: f32,
+    max_distance: f32,
+}
+
+/// Walks on the terrain colliders with gravity and jumping
+pub struct FpsController {
+    movement: MovementInput,
+    look: LookInput,
+    speed: DataSource<f32>,
+    sensitivity: f32,
+    gravity: f32,
+    jump_speed: f32,
+    eye_height: f32,
+    vertical_velocity: f32,
+    grounded: bool,
+}
+
+/// Orbits an entity found by its tag from behind, terrain between the camera and the entity pulls
+/// the camera closer so it does not clip into hills
+pub struct FollowController {
+    look: LookInput,
+    speed: DataSource<f32>,
+    sensitivity: f32,
+    target: String,
+    distance: f32,
+    min_distance: f32,
+    max_distance: f32,
+    height: f32,
+    /// Distance after the pull in by terrain, eased back out to `distance`
+    current_distance: f32,
+}
+
+/// Follows a path of keyframes with Catmull-Rom interpolation
+pub struct CinematicController {
+    path: Vec<CameraKeyframe>,
+    speed: DataSource<f32>,
+    looped: bool,
+    /// Position on the path in segments
+    time: f32,
+    playing: bool,
+}
+
+/// Held movement keys, shared by the controllers that move freely. The left stick and the
+/// triggers of the first gamepad are added in `get_axes`
+#[derive(Debug, Default)]
+struct MovementInput {
+    left: f32,
+    right: f32,
+    forward: f32,
+    backward: f32,
+    up: f32,
+    down: f32,
+}
+
+/// Mouse movement captured while the cursor is disabled, the right stick of the first gamepad
+/// is added in `rotate`
+#[derive(Debug, Default)]
+struct LookInput {
+    rotate_horizontal: f32,
+    rotate_vertical: f32,
+    is_active: bool,
+}
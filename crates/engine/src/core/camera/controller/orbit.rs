@@ -0,0 +1,74 @@
+use cgmath::{EuclideanSpace, InnerSpace, Point3, Vector3};
+
+use crate::core::{
+    camera::{Camera, Projection},
+    scene::Scene,
+    utils::DataSource,
+};
+
+use super::{
+    CameraController, CameraControllerType, CameraSettings, LookInput, OrbitController,
+    OrbitSettings,
+};
+
+impl OrbitController {
+    pub fn from_settings(settings: &OrbitSettings, speed: DataSource<f32>) -> Self {
+        speed.write(settings.speed);
+        Self {
+            look: LookInput::default(),
+            speed,
+            sensitivity: settings.sensitivity,
+            distance: settings.distance,
+            min_distance: settings.min_distance,
+            max_distance: settings.max_distance,
+        }
+    }
+
+    pub fn get_distance(&self) -> f32 {
+        self.distance
+    }
+
+    pub fn set_distance(&mut self, distance: f32) {
+        self.distance = distance.clamp(self.min_distance, self.max_distance);
+    }
+}
+
+impl CameraController for OrbitController {
+    fn get_type(&self) -> CameraControllerType {
+        CameraControllerType::Orbit
+    }
+
+    fn process_keyboard(&mut self, window: &mut glfw::Window, event: &glfw::WindowEvent) -> bool {
+        self.look.process_keyboard(window, event)
+    }
+
+    fn process_mouse(&mut self, window: &mut glfw::Window, event: &glfw::WindowEvent) {
+        self.look.process_mouse(window, event);
+        if let glfw::WindowEvent::Scroll(_, y) = event {
+            self.set_distance(self.distance - *y as f32 * self.speed.read());
+        }
+    }
+
+    fn update_camera(&mut self, _: &Scene, camera: &mut Camera, _: &Projection, delta_time: f32) {
+        let (yaw, pitch) = self
+            .look
+            .rotate(camera.yaw, camera.pitch, self.sensitivity, delta_time);
+
+        // stay behind the anchor so it is always in the center of the view
+        let (sin_pitch, cos_pitch) = pitch.0.sin_cos();
+        let (sin_yaw, cos_yaw) = yaw.0.sin_cos();
+        let direction =
+            Vector3::new(cos_pitch * cos_yaw, sin_pitch, cos_pitch * sin_yaw).normalize();
+        camera.update(Point3::from_vec(-direction * self.distance), yaw, pitch);
+    }
+
+    fn get_speed_ref(&self) -> DataSource<f32> {
+        self.speed.clone()
+    }
+
+    fn store_settings(&self, settings: &mut CameraSettings) {
+        settings.orbit.speed = self.speed.read();
+        settings.orbit.sensitivity = self.sensitivity;
+        settings.orbit.distance = self.distance;
+    }
+}
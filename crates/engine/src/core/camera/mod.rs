@@ -0,0 +1,37 @@
+use std::f32::consts::FRAC_PI_2;
+
+use cgmath::{Matrix4, Point3, Rad};
+
+mod camera;
+pub mod controller;
+
+#[rustfmt::skip]
+pub const OPENGL_TO_WGPU_MATRIX: cgmath::Matrix4<f32> = Matrix4::new(
+    1.0, 0.0, 0.0, 0.0,
+    0.0, 1.0, 0.0, 0.0,
+    0.0, 0.0, 0.5, 0.5,
+    0.0, 0.0, 0.0, 1.0,
+);
+
+const SAFE_FRAC_PI_2: f32 = FRAC_PI_2 - 0.0001;
+
+#[derive(Debug)]
+pub struct Camera {
+    relative_position: Point3<f32>,
+    position: Point3<f32>,
+    yaw: Rad<f32>,
+    pitch: Rad<f32>,
+
+    matrix: Matrix4<f32>,
+}
+
+pub struct Projection {
+    pub aspect: f32,
+    pub fovy: Rad<f32>,
+    pub znear: f32,
+    zfar: f32,
+    /// Scales a tile of the view to the whole viewport, used for rendering above the native resolution
+    tile: Matrix4<f32>,
+
+    matrix: Matrix4<f32>,
+}
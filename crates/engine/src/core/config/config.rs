@@ -0,0 +1,41 @@
+use std::path::Path;
+
+use super::EngineConfig;
+
+impl EngineConfig {
+    /// Loads a config from `path`, the format is chosen by the file extension (`.ron` or `.toml`)
+    pub fn load(path: &str) -> Result<EngineConfig, Box<dyn std::error::Error>> {
+        let source = std::fs::read_to_string(path)?;
+        match Path::new(path).extension().and_then(|e| e.to_str()) {
+            Some("toml") => Ok(toml::from_str(&source)?),
+            Some("ron") => Ok(ron::from_str(&source)?),
+            _ => Err(format!("Unsupported engine config format: {}", path).into()),
+        }
+    }
+
+    /// Writes the config to `path` as RON
+    pub fn save(&self, path: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let source = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        std::fs::write(path, source)?;
+        Ok(())
+    }
+}
+
+impl Default for EngineConfig {
+    fn default() -> Self {
+        Self {
+            chunk_upload_budget_bytes: 4 * 1024 * 1024,
+            chunk_upload_budget_ms: 2.0,
+            quality: Default::default(),
+            auto_quality: Default::default(),
+            camera: Default::default(),
+            motion_blur: Default::default(),
+            ssao: Default::default(),
+            ssr: Default::default(),
+            water: Default::default(),
+            bloom: Default::default(),
+            tone_mapping: Default::default(),
+            render: Default::default(),
+        }
+    }
+}
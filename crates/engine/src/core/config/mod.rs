@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+
+use super::{
+    camera::controller::CameraSettings,
+    quality::{AutoQualitySettings, QualitySettings},
+    renderer::{
+        hdr::{BloomSettings, ToneMappingSettings},
+        motion_blur::MotionBlurSettings,
+        render_settings::RenderSettings,
+        ssao::SsaoSettings,
+        ssr::SsrSettings,
+        water::WaterSettings,
+    },
+};
+
+mod config;
+
+/// Engine wide settings, stored on the scene so components can read them
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EngineConfig {
+    /// Maximum number of bytes of chunk meshes uploaded to the GPU per frame
+    pub chunk_upload_budget_bytes: usize,
+    /// Maximum time in milliseconds spent uploading chunk meshes per frame
+    pub chunk_upload_budget_ms: f64,
+    pub quality: QualitySettings,
+    pub auto_quality: AutoQualitySettings,
+    pub camera: CameraSettings,
+    pub motion_blur: MotionBlurSettings,
+    pub ssao: SsaoSettings,
+    pub ssr: SsrSettings,
+    pub water: WaterSettings,
+    pub bloom: BloomSettings,
+    pub tone_mapping: ToneMappingSettings,
+    /// Saved copy of the current render settings, applied with `RenderSettings::set`
+    pub render: RenderSettings,
+}
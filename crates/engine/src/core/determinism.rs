@@ -0,0 +1,66 @@
+use std::cell::RefCell;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+thread_local! {
+    static STATE: RefCell<Option<DeterministicState>> = const { RefCell::new(None) };
+}
+
+/// Deterministic mode for tests and simulations that have to be reproducible: ids of entities
+/// and UI elements come from a seeded generator and every frame advances by a fixed timestep.
+///
+/// The mode is per thread, so tests running in parallel do not disturb each other. ChaCha is used
+/// because its output is the same on every platform
+pub struct Determinism;
+
+struct DeterministicState {
+    rng: ChaCha8Rng,
+    timestep: f64,
+}
+
+impl Determinism {
+    /// Seeds the ids generated on this thread from `seed` and updates with `timestep` seconds
+    /// per frame instead of the measured frame time
+    pub fn enable(seed: u64, timestep: f64) {
+        STATE.with(|state| {
+            *state.borrow_mut() = Some(DeterministicState {
+                rng: ChaCha8Rng::seed_from_u64(seed),
+                timestep,
+            });
+        });
+    }
+
+    pub fn disable() {
+        STATE.with(|state| *state.borrow_mut() = None);
+    }
+
+    pub fn is_enabled() -> bool {
+        STATE.with(|state| state.borrow().is_some())
+    }
+
+    /// Random id, taken from the seeded generator in deterministic mode
+    pub fn next_id() -> u64 {
+        STATE.with(|state| match state.borrow_mut().as_mut() {
+            Some(state) => state.rng.gen(),
+            None => rand::thread_rng().gen(),
+        })
+    }
+
+    /// Generator for simulation code, seeded from the deterministic generator in deterministic
+    /// mode and from entropy otherwise
+    pub fn create_rng() -> ChaCha8Rng {
+        ChaCha8Rng::seed_from_u64(Determinism::next_id())
+    }
+
+    /// Time the frame advances the scene by, the fixed timestep in deterministic mode and the
+    /// `measured` frame time otherwise
+    pub fn get_timestep(measured: f64) -> f64 {
+        STATE.with(|state| {
+            state
+                .borrow()
+                .as_ref()
+                .map_or(measured, |state| state.timestep)
+        })
+    }
+}
@@ -0,0 +1,26 @@
+use std::sync::atomic::Ordering;
+
+use super::{ChangeTracker, CHANGE_TICK};
+
+impl ChangeTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Closes the current change tick and returns the last tick seen before, entities changed
+    /// since the previous call report `is_changed_since` for it
+    pub fn advance(&mut self) -> u64 {
+        let since = self.last_seen;
+        self.last_seen = CHANGE_TICK.fetch_add(1, Ordering::Relaxed);
+        since
+    }
+
+    pub fn get_last_seen(&self) -> u64 {
+        self.last_seen
+    }
+
+    /// The tick mutations are currently stamped with
+    pub fn get_current_tick() -> u64 {
+        CHANGE_TICK.load(Ordering::Relaxed)
+    }
+}
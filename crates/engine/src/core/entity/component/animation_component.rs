@@ -1,33 +1,123 @@
+use std::collections::HashMap;
+
 use glfw::{Glfw, WindowEvent};
 
-use crate::core::{entity::Entity, model::animation_graph::AnimationGraph, scene::Scene};
+use crate::core::{
+    entity::Entity,
+    event::EventCategory,
+    model::{animation_graph::AnimationGraph, AnimationLayer, Pose, Skeleton},
+    scene::Scene,
+};
 
-use super::{model_component::ModelComponent, Component};
+use super::{
+    bone_attachment_component::BoneAttachmentComponent, model_component::ModelComponent, Component,
+    InspectableComponent,
+};
 
 pub struct AnimationComponent {
     animation_graph: AnimationGraph,
+    /// Played over the pose of the graph in the order they were added
+    layers: Vec<AnimationLayer>,
+    inputs: HashMap<String, f32>,
+    /// Polled every frame to set the input of the same name
+    input_sources: HashMap<String, Box<dyn FnMut() -> f32>>,
+    /// Bind transforms for the bones layers animate that the graph doesn't, loaded with the first layer
+    skeleton: Option<Skeleton>,
 }
 
 impl AnimationComponent {
     pub fn new(animation_graph: AnimationGraph) -> Self {
-        AnimationComponent { animation_graph }
+        AnimationComponent {
+            animation_graph,
+            layers: Vec::new(),
+            inputs: HashMap::new(),
+            input_sources: HashMap::new(),
+            skeleton: None,
+        }
     }
 
+    /// Sets an input of the animation graph and of the bone drivers of the layers
     pub fn set_input(&mut self, name: &str, value: f32) {
         self.animation_graph.set_input(name, value);
+        self.inputs.insert(name.to_string(), value);
+    }
+
+    /// Drives the input `name` from an external source such as the amplitude of a playing voice
+    /// line, the source is polled once per frame before the animation is updated
+    pub fn set_input_source(&mut self, name: &str, source: Box<dyn FnMut() -> f32>) {
+        self.input_sources.insert(name.to_string(), source);
+    }
+
+    pub fn remove_input_source(&mut self, name: &str) {
+        self.input_sources.remove(name);
+    }
+
+    pub fn add_layer(&mut self, layer: AnimationLayer) {
+        self.layers.push(layer);
+    }
+
+    pub fn get_layer_mut(&mut self, name: &str) -> Option<&mut AnimationLayer> {
+        self.layers
+            .iter_mut()
+            .find(|layer| layer.get_name() == name)
+    }
+}
+
+impl InspectableComponent for AnimationComponent {
+    fn get_inspector_name(&self) -> &str {
+        "Animation"
     }
 }
 
 impl Component for AnimationComponent {
-    fn update(&mut self, _: &mut Scene, entity: &mut Entity, delta_time: f64) {
+    fn update(&mut self, scene: &mut Scene, entity: &mut Entity, delta_time: f64) {
+        let values: Vec<(String, f32)> = self
+            .input_sources
+            .iter_mut()
+            .map(|(name, source)| (name.clone(), source()))
+            .collect();
+        for (name, value) in values {
+            self.set_input(&name, value);
+        }
+
         self.animation_graph.update(delta_time as f32);
+        for layer in &mut self.layers {
+            layer.update(delta_time as f32);
+        }
         let pose = self.animation_graph.get_pose();
-        if let Some(pose) = pose {
-            if let Some(model_component) = entity.get_component_mut::<ModelComponent>() {
-                model_component.get_model_mut().apply_pose(&pose);
+        if pose.is_none() && self.layers.is_empty() {
+            BoneAttachmentComponent::update_attachments(scene, entity);
+            return;
+        }
+        if let Some(model) = entity
+            .get_component_mut::<ModelComponent>()
+            .and_then(ModelComponent::get_model_mut)
+        {
+            let mut pose = pose.unwrap_or_else(Pose::new);
+            if !self.layers.is_empty() {
+                let skeleton = self.skeleton.get_or_insert_with(|| model.get_skeleton());
+                for layer in &self.layers {
+                    layer.apply(&mut pose, skeleton, &self.inputs);
+                }
             }
+            model.apply_pose(&pose);
         }
+        BoneAttachmentComponent::update_attachments(scene, entity);
+    }
+
+    fn get_event_priority(&self, _: EventCategory) -> Option<i32> {
+        None
+    }
+
+    fn handle_event(&mut self, _: &mut Glfw, _: &mut glfw::Window, _: &WindowEvent) -> bool {
+        false
     }
 
-    fn handle_event(&mut self, _: &mut Glfw, _: &mut glfw::Window, _: &WindowEvent) {}
+    fn as_inspectable(&self) -> Option<&dyn InspectableComponent> {
+        Some(self)
+    }
+
+    fn as_inspectable_mut(&mut self) -> Option<&mut dyn InspectableComponent> {
+        Some(self)
+    }
 }
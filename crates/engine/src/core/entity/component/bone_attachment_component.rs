@@ -0,0 +1,139 @@
+use cgmath::{InnerSpace, Matrix3, Matrix4, Point3, Quaternion, SquareMatrix};
+use glfw::{Glfw, WindowEvent};
+
+use crate::core::{
+    entity::{Entity, EntityHandle},
+    event::EventCategory,
+    model::Model,
+    scene::Scene,
+};
+
+use super::{model_component::ModelComponent, Component, InspectableComponent};
+
+/// Keeps its entity on a bone of the model of its parent entity, e.g. a light or a torch in the
+/// hand of a character. Every component of the entity follows the bone, lights and emitters
+/// included.
+///
+/// The parent's `AnimationComponent` moves the attached children after it applied the pose,
+/// parents without one move them when their `ModelComponent` is updated
+pub struct BoneAttachmentComponent {
+    bone: String,
+    /// Transform relative to the bone, in world units and the bone's orientation
+    offset: Matrix4<f32>,
+}
+
+impl BoneAttachmentComponent {
+    pub fn new(bone: &str) -> Self {
+        Self {
+            bone: bone.to_string(),
+            offset: Matrix4::identity(),
+        }
+    }
+
+    pub fn with_offset(mut self, offset: Matrix4<f32>) -> Self {
+        self.offset = offset;
+        self
+    }
+
+    pub fn get_bone(&self) -> &str {
+        &self.bone
+    }
+
+    pub fn set_offset(&mut self, offset: Matrix4<f32>) {
+        self.offset = offset;
+    }
+
+    /// Moves the children of `entity` with an attachment to their bones in the current pose of the
+    /// model of `entity`. Children attached to bones the model does not have stay where they are
+    pub fn update_attachments(scene: &mut Scene, entity: &mut Entity) {
+        let Some(model) = entity
+            .get_own_component::<ModelComponent>()
+            .and_then(ModelComponent::get_model)
+        else {
+            return;
+        };
+        let placements = BoneAttachmentComponent::get_placements(entity, model);
+        BoneAttachmentComponent::apply_placements(scene, entity, placements);
+    }
+
+    /// Moves the attached children of `entity` like `update_attachments`, for components that
+    /// hold the model of `entity` while they are updated
+    pub fn place_children(scene: &mut Scene, entity: &mut Entity, model: &Model) {
+        let placements = BoneAttachmentComponent::get_placements(entity, model);
+        BoneAttachmentComponent::apply_placements(scene, entity, placements);
+    }
+
+    /// Transforms relative to `entity` of its children attached to a bone of `model`
+    fn get_placements(entity: &Entity, model: &Model) -> Vec<(EntityHandle, Matrix4<f32>)> {
+        entity
+            .get_children()
+            .iter()
+            .filter_map(|child| {
+                let attachment = child.get_own_component::<BoneAttachmentComponent>()?;
+                let socket = model.get_socket_transform(&attachment.bone)?;
+                Some((
+                    child.id,
+                    BoneAttachmentComponent::remove_scale(&socket) * attachment.offset,
+                ))
+            })
+            .collect()
+    }
+
+    fn apply_placements(
+        scene: &mut Scene,
+        entity: &mut Entity,
+        placements: Vec<(EntityHandle, Matrix4<f32>)>,
+    ) {
+        for (id, transform) in placements {
+            if let Some(child) = entity.get_child_mut(&id) {
+                child.set_position(
+                    scene,
+                    Point3::new(transform.w.x, transform.w.y, transform.w.z),
+                );
+                child.set_rotation(
+                    scene,
+                    Quaternion::from(Matrix3::from_cols(
+                        transform.x.truncate(),
+                        transform.y.truncate(),
+                        transform.z.truncate(),
+                    )),
+                );
+            }
+        }
+    }
+
+    /// Rigid part of `transform`, entities only have a position and a rotation
+    fn remove_scale(transform: &Matrix4<f32>) -> Matrix4<f32> {
+        let mut rigid = *transform;
+        rigid.x = rigid.x.truncate().normalize().extend(0.0);
+        rigid.y = rigid.y.truncate().normalize().extend(0.0);
+        rigid.z = rigid.z.truncate().normalize().extend(0.0);
+        rigid
+    }
+}
+
+impl InspectableComponent for BoneAttachmentComponent {
+    fn get_inspector_name(&self) -> &str {
+        "Bone attachment"
+    }
+}
+
+impl Component for BoneAttachmentComponent {
+    fn update(&mut self, _: &mut Scene, _: &mut Entity, _: f64) {}
+
+    fn get_event_priority(&self, _: EventCategory) -> Option<i32> {
+        None
+    }
+
+    fn handle_event(&mut self, _: &mut Glfw, _: &mut glfw::Window, _: &WindowEvent) -> bool {
+        false
+    }
+
+    fn as_inspectable(&self) -> Option<&dyn InspectableComponent> {
+        Some(self)
+    }
+
+    fn as_inspectable_mut(&mut self) -> Option<&mut dyn InspectableComponent> {
+        Some(self)
+    }
+}
@@ -0,0 +1,146 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use cgmath::{EuclideanSpace, InnerSpace, Matrix4, Point3, Transform};
+use glfw::{Glfw, WindowEvent};
+
+use crate::core::{
+    entity::Entity, event::EventCategory, renderer::line::Line, scene::Scene,
+    view_frustum::ViewFrustum,
+};
+
+use super::{Component, InspectableComponent, InspectorField, InspectorValue};
+
+/// When set, entities are culled against the middle half of the screen so culling can be seen,
+/// and the debug controller draws the bounds of visible entities green and culled ones red
+pub static DEBUG_CULLING: AtomicBool = AtomicBool::new(false);
+
+/// Axis aligned box around an entity and its children in the entity's local space.
+/// Entities with bounds are skipped while rendering when the box is outside the view
+pub struct BoundsComponent {
+    min: Point3<f32>,
+    max: Point3<f32>,
+}
+
+impl BoundsComponent {
+    pub fn new<P: Into<Point3<f32>>>(min: P, max: P) -> Self {
+        Self {
+            min: min.into(),
+            max: max.into(),
+        }
+    }
+
+    /// Axis aligned box around the bounds transformed by `transform`
+    pub fn get_world_bounds(&self, transform: &Matrix4<f32>) -> (Point3<f32>, Point3<f32>) {
+        let mut min = Point3::new(f32::MAX, f32::MAX, f32::MAX);
+        let mut max = Point3::new(f32::MIN, f32::MIN, f32::MIN);
+        for corner in 0..8 {
+            let point = transform.transform_point(Point3::new(
+                if corner & 1 == 0 {
+                    self.min.x
+                } else {
+                    self.max.x
+                },
+                if corner & 2 == 0 {
+                    self.min.y
+                } else {
+                    self.max.y
+                },
+                if corner & 4 == 0 {
+                    self.min.z
+                } else {
+                    self.max.z
+                },
+            ));
+            min = Point3::new(min.x.min(point.x), min.y.min(point.y), min.z.min(point.z));
+            max = Point3::new(max.x.max(point.x), max.y.max(point.y), max.z.max(point.z));
+        }
+        (min, max)
+    }
+
+    /// Whether the bounds transformed by `transform` are at least partly inside the view of
+    /// `view_projection`
+    pub fn is_visible(&self, view_projection: &Matrix4<f32>, transform: &Matrix4<f32>) -> bool {
+        let (min, max) = self.get_world_bounds(transform);
+        let view_projection = if DEBUG_CULLING.load(Ordering::Relaxed) {
+            Matrix4::from_nonuniform_scale(2.0, 2.0, 1.0) * view_projection
+        } else {
+            *view_projection
+        };
+        ViewFrustum::is_box_in_frustum(&view_projection, min, max)
+    }
+
+    /// Lines along the edges of the world space box around the bounds
+    pub fn get_edges(&self, transform: &Matrix4<f32>) -> Vec<Line> {
+        let (min, max) = self.get_world_bounds(transform);
+        let corner = |i: usize| {
+            Point3::new(
+                if i & 1 == 0 { min.x } else { max.x },
+                if i & 2 == 0 { min.y } else { max.y },
+                if i & 4 == 0 { min.z } else { max.z },
+            )
+        };
+        let mut edges = Vec::with_capacity(12);
+        for i in 0..8 {
+            for axis in [1, 2, 4] {
+                if i & axis == 0 {
+                    let direction = corner(i | axis) - corner(i);
+                    if direction.magnitude() > 0.0 {
+                        edges.push(Line::new(
+                            corner(i),
+                            direction.normalize(),
+                            direction.magnitude(),
+                        ));
+                    }
+                }
+            }
+        }
+        edges
+    }
+}
+
+impl InspectableComponent for BoundsComponent {
+    fn get_inspector_name(&self) -> &str {
+        "Bounds"
+    }
+
+    fn get_fields(&self) -> Vec<InspectorField> {
+        vec![
+            InspectorField {
+                name: "Min",
+                value: InspectorValue::Vector3(self.min.to_vec()),
+            },
+            InspectorField {
+                name: "Max",
+                value: InspectorValue::Vector3(self.max.to_vec()),
+            },
+        ]
+    }
+
+    fn set_field(&mut self, name: &str, value: InspectorValue) {
+        match (name, value) {
+            ("Min", InspectorValue::Vector3(min)) => self.min = Point3::from_vec(min),
+            ("Max", InspectorValue::Vector3(max)) => self.max = Point3::from_vec(max),
+            _ => {}
+        }
+    }
+}
+
+impl Component for BoundsComponent {
+    fn update(&mut self, _: &mut Scene, _: &mut Entity, _: f64) {}
+
+    fn get_event_priority(&self, _: EventCategory) -> Option<i32> {
+        None
+    }
+
+    fn handle_event(&mut self, _: &mut Glfw, _: &mut glfw::Window, _: &WindowEvent) -> bool {
+        false
+    }
+
+    fn as_inspectable(&self) -> Option<&dyn InspectableComponent> {
+        Some(self)
+    }
+
+    fn as_inspectable_mut(&mut self) -> Option<&mut dyn InspectableComponent> {
+        Some(self)
+    }
+}
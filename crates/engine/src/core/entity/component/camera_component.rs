@@ -1,24 +1,27 @@
 use cgmath::Matrix4;
 
 use crate::core::{
-    camera::{Camera, CameraController, Projection},
+    camera::{
+        controller::{CameraController, CameraControllerType, CameraSettings},
+        Camera, Projection,
+    },
     entity::Entity,
     scene::Scene,
 };
 
-use super::Component;
+use super::{Component, InspectableComponent, InspectorField, InspectorValue};
 
 pub struct CameraComponent {
     camera: Camera,
     projection: Projection,
-    camera_controller: CameraController,
+    camera_controller: Box<dyn CameraController>,
 }
 
 impl CameraComponent {
     pub fn new(
         camera: Camera,
         projection: Projection,
-        camera_controller: CameraController,
+        camera_controller: Box<dyn CameraController>,
     ) -> Self {
         CameraComponent {
             camera,
@@ -43,12 +46,29 @@ impl CameraComponent {
         &mut self.projection
     }
 
-    pub fn get_camera_controller(&self) -> &CameraController {
-        &self.camera_controller
+    pub fn get_camera_controller(&self) -> &dyn CameraController {
+        self.camera_controller.as_ref()
     }
 
-    pub fn get_camera_controller_mut(&mut self) -> &mut CameraController {
-        &mut self.camera_controller
+    pub fn get_camera_controller_mut(&mut self) -> &mut dyn CameraController {
+        self.camera_controller.as_mut()
+    }
+
+    pub fn set_camera_controller(&mut self, camera_controller: Box<dyn CameraController>) {
+        self.camera_controller = camera_controller;
+    }
+
+    /// Replaces the controller with one of `controller_type`, the settings of the previous
+    /// controller are written to `settings` before the new one is created from them
+    pub fn switch_camera_controller(
+        &mut self,
+        controller_type: CameraControllerType,
+        settings: &mut CameraSettings,
+    ) {
+        self.camera_controller.store_settings(settings);
+        settings.controller = controller_type;
+        self.camera_controller =
+            settings.create_controller_of(controller_type, self.camera_controller.get_speed_ref());
     }
 
     pub fn get_view_projection(&self) -> Matrix4<f32> {
@@ -56,10 +76,37 @@ impl CameraComponent {
     }
 }
 
+impl InspectableComponent for CameraComponent {
+    fn get_inspector_name(&self) -> &str {
+        "Camera"
+    }
+
+    fn get_fields(&self) -> Vec<InspectorField> {
+        vec![InspectorField {
+            name: "Speed",
+            value: InspectorValue::Float(self.camera_controller.get_speed_ref().read()),
+        }]
+    }
+
+    fn set_field(&mut self, name: &str, value: InspectorValue) {
+        if let ("Speed", InspectorValue::Float(speed)) = (name, value) {
+            self.camera_controller.set_speed(speed.max(0.0));
+        }
+    }
+}
+
 impl Component for CameraComponent {
-    fn update(&mut self, _: &mut Scene, _: &mut Entity, delta_time: f64) {
-        self.camera_controller
-            .update_camera(&mut self.camera, delta_time as f32);
+    fn update(&mut self, scene: &mut Scene, _: &mut Entity, delta_time: f64) {
+        self.camera_controller.update_camera(
+            scene,
+            &mut self.camera,
+            &self.projection,
+            delta_time as f32,
+        );
+    }
+
+    fn is_realtime(&self) -> bool {
+        true
     }
 
     fn handle_event(
@@ -67,9 +114,18 @@ impl Component for CameraComponent {
         _: &mut glfw::Glfw,
         window: &mut glfw::Window,
         event: &glfw::WindowEvent,
-    ) {
+    ) -> bool {
         self.camera_controller.process_keyboard(window, event);
         self.camera_controller.process_mouse(window, event);
         self.projection.resize(&event);
+        false
+    }
+
+    fn as_inspectable(&self) -> Option<&dyn InspectableComponent> {
+        Some(self)
+    }
+
+    fn as_inspectable_mut(&mut self) -> Option<&mut dyn InspectableComponent> {
+        Some(self)
     }
 }
@@ -0,0 +1,283 @@
+use cgmath::{Deg, InnerSpace, Quaternion, Rad, Rotation3, Vector3, Zero};
+use glfw::{Glfw, WindowEvent};
+use rapier3d::{
+    control::{CharacterAutostep, CharacterLength, KinematicCharacterController},
+    prelude::{vector, Capsule, Collider, ColliderBuilder},
+};
+
+use crate::core::{entity::Entity, event::EventCategory, scene::Scene};
+
+use super::{
+    animation_component::AnimationComponent, model_component::ModelComponent, Component,
+    InspectableComponent, InspectorField, InspectorValue,
+};
+
+const DEFAULT_WALK_SPEED: f32 = 1.5;
+const DEFAULT_RUN_SPEED: f32 = 4.0;
+/// Slightly more than one block, so single block ledges are climbed without jumping
+const DEFAULT_STEP_HEIGHT: f32 = 1.05;
+const DEFAULT_MAX_SLOPE: Deg<f32> = Deg(50.0);
+const GRAVITY: f32 = 9.81;
+/// Distance the capsule keeps from the colliders it touches
+const SKIN_WIDTH: f32 = 0.02;
+/// Distance below the capsule within which it is pulled down onto the ground
+const SNAP_DISTANCE: f32 = 0.3;
+/// How fast the speed input of the animation follows the measured speed, per second
+const LOCOMOTION_ACCELERATION: f32 = 4.0;
+/// Horizontal speed below which the character counts as standing and keeps its facing
+const IDLE_SPEED: f32 = 0.05;
+
+/// Moves its entity as a capsule standing on the entity's position through the terrain
+/// colliders. It walks in the direction set with `set_movement`, falls while it is not grounded,
+/// steps up low ledges and does not climb slopes steeper than its maximum slope. The `speed`
+/// input of the entity's animation is 0 while standing, 1 at walking and 2 at running speed
+pub struct CharacterControllerComponent {
+    controller: KinematicCharacterController,
+    radius: f32,
+    half_height: f32,
+    walk_speed: f32,
+    run_speed: f32,
+
+    /// Horizontal direction to walk in, its length scales the speed
+    movement: Vector3<f32>,
+    running: bool,
+
+    velocity: Vector3<f32>,
+    grounded: bool,
+    /// Input of the locomotion animation, eased towards the measured speed
+    speed: f32,
+}
+
+impl CharacterControllerComponent {
+    /// Character with a capsule of `radius` that is `height` tall including its caps
+    pub fn new(radius: f32, height: f32) -> Self {
+        let mut controller = KinematicCharacterController {
+            offset: CharacterLength::Absolute(SKIN_WIDTH),
+            snap_to_ground: Some(CharacterLength::Absolute(SNAP_DISTANCE)),
+            ..Default::default()
+        };
+        Self::set_max_slope(&mut controller, DEFAULT_MAX_SLOPE);
+        let mut character = Self {
+            controller,
+            radius,
+            half_height: (height * 0.5 - radius).max(0.0),
+            walk_speed: DEFAULT_WALK_SPEED,
+            run_speed: DEFAULT_RUN_SPEED,
+            movement: Vector3::zero(),
+            running: false,
+            velocity: Vector3::zero(),
+            grounded: false,
+            speed: 0.0,
+        };
+        character.set_step_height(DEFAULT_STEP_HEIGHT);
+        character
+    }
+
+    pub fn with_speeds(mut self, walk_speed: f32, run_speed: f32) -> Self {
+        self.walk_speed = walk_speed;
+        self.run_speed = run_speed;
+        self
+    }
+
+    pub fn with_step_height(mut self, step_height: f32) -> Self {
+        self.set_step_height(step_height);
+        self
+    }
+
+    pub fn with_max_slope<A: Into<Deg<f32>>>(mut self, max_slope: A) -> Self {
+        Self::set_max_slope(&mut self.controller, max_slope.into());
+        self
+    }
+
+    /// Collider matching the capsule, for a kinematic rigid body on the same entity so dynamic
+    /// bodies are pushed away by the character
+    pub fn get_collider(&self) -> Collider {
+        ColliderBuilder::capsule_y(self.half_height, self.radius)
+            .translation(vector![0.0, self.half_height + self.radius, 0.0])
+            .build()
+    }
+
+    /// Walks in the horizontal world space `direction` from the next update on, at running speed
+    /// if `running` is set. A zero direction stops the character
+    pub fn set_movement(&mut self, direction: Vector3<f32>, running: bool) {
+        let direction = Vector3::new(direction.x, 0.0, direction.z);
+        self.movement = if direction.magnitude2() > 1.0 {
+            direction.normalize()
+        } else {
+            direction
+        };
+        self.running = running;
+    }
+
+    pub fn get_velocity(&self) -> Vector3<f32> {
+        self.velocity
+    }
+
+    /// Whether the capsule stood on the ground after the last update
+    pub fn is_grounded(&self) -> bool {
+        self.grounded
+    }
+
+    fn set_step_height(&mut self, step_height: f32) {
+        self.controller.autostep = (step_height > 0.0).then_some(CharacterAutostep {
+            max_height: CharacterLength::Absolute(step_height),
+            min_width: CharacterLength::Absolute(self.radius * 0.5),
+            include_dynamic_bodies: false,
+        });
+    }
+
+    fn get_step_height(&self) -> f32 {
+        match self.controller.autostep {
+            Some(CharacterAutostep {
+                max_height: CharacterLength::Absolute(height),
+                ..
+            }) => height,
+            _ => 0.0,
+        }
+    }
+
+    fn set_max_slope(controller: &mut KinematicCharacterController, max_slope: Deg<f32>) {
+        let angle = Rad::from(max_slope).0;
+        controller.max_slope_climb_angle = angle;
+        // slopes that can not be climbed are slid down
+        controller.min_slope_slide_angle = angle;
+    }
+
+    /// Animation speed input for a horizontal `speed`, 1 at walking and 2 at running speed
+    fn get_locomotion_speed(&self, speed: f32) -> f32 {
+        if speed <= self.walk_speed {
+            speed / self.walk_speed.max(f32::EPSILON)
+        } else {
+            1.0 + (speed - self.walk_speed) / (self.run_speed - self.walk_speed).max(f32::EPSILON)
+        }
+    }
+}
+
+impl InspectableComponent for CharacterControllerComponent {
+    fn get_inspector_name(&self) -> &str {
+        "Character controller"
+    }
+
+    fn get_fields(&self) -> Vec<InspectorField> {
+        vec![
+            InspectorField {
+                name: "Walk speed",
+                value: InspectorValue::Float(self.walk_speed),
+            },
+            InspectorField {
+                name: "Run speed",
+                value: InspectorValue::Float(self.run_speed),
+            },
+            InspectorField {
+                name: "Step height",
+                value: InspectorValue::Float(self.get_step_height()),
+            },
+            InspectorField {
+                name: "Max slope",
+                value: InspectorValue::Float(
+                    Deg::from(Rad(self.controller.max_slope_climb_angle)).0,
+                ),
+            },
+        ]
+    }
+
+    fn set_field(&mut self, name: &str, value: InspectorValue) {
+        match (name, value) {
+            ("Walk speed", InspectorValue::Float(speed)) => self.walk_speed = speed.max(0.0),
+            ("Run speed", InspectorValue::Float(speed)) => self.run_speed = speed.max(0.0),
+            ("Step height", InspectorValue::Float(height)) => self.set_step_height(height),
+            ("Max slope", InspectorValue::Float(slope)) => {
+                Self::set_max_slope(&mut self.controller, Deg(slope.clamp(0.0, 90.0)))
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Component for CharacterControllerComponent {
+    fn update(&mut self, scene: &mut Scene, entity: &mut Entity, delta_time: f64) {
+        let delta_time = delta_time as f32;
+        if delta_time <= 0.0 {
+            return;
+        }
+        let position = entity.get_position();
+        let center = position + Vector3::unit_y() * (self.half_height + self.radius);
+
+        let speed = if self.running {
+            self.run_speed
+        } else {
+            self.walk_speed
+        };
+        let mut velocity = self.movement * speed;
+        velocity.y = if scene
+            .physics_engine
+            .height_at(position.x, position.z)
+            .is_none()
+        {
+            // the terrain below is not loaded yet, the character waits instead of falling through
+            0.0
+        } else if self.grounded {
+            // pressing into the ground keeps it detected on the way down slopes
+            -GRAVITY * delta_time
+        } else {
+            self.velocity.y - GRAVITY * delta_time
+        };
+
+        let shape = Capsule::new_y(self.half_height, self.radius);
+        let movement = scene.physics_engine.move_shape(
+            &self.controller,
+            &shape,
+            center,
+            velocity * delta_time,
+            delta_time,
+        );
+        let translation = Vector3::new(
+            movement.translation.x,
+            movement.translation.y,
+            movement.translation.z,
+        );
+        self.grounded = movement.grounded;
+        self.velocity = translation / delta_time;
+        if self.grounded && self.velocity.y < 0.0 {
+            self.velocity.y = 0.0;
+        }
+        entity.set_position(scene, position + translation);
+
+        let horizontal = Vector3::new(self.velocity.x, 0.0, self.velocity.z);
+        let horizontal_speed = horizontal.magnitude();
+        if horizontal_speed > IDLE_SPEED {
+            let yaw = Rad(horizontal.x.atan2(horizontal.z));
+            entity.set_rotation(scene, Quaternion::from_angle_y(yaw));
+        }
+
+        let target_speed = self.get_locomotion_speed(horizontal_speed);
+        let step = LOCOMOTION_ACCELERATION * delta_time;
+        self.speed += (target_speed - self.speed).clamp(-step, step);
+        if let Some(animation_component) = entity.get_component_mut::<AnimationComponent>() {
+            animation_component.set_input("speed", self.speed);
+        }
+        // the controller moves the entity, root motion of the animations is dropped
+        if let Some(model) = entity
+            .get_component_mut::<ModelComponent>()
+            .and_then(ModelComponent::get_model_mut)
+        {
+            model.reset_position();
+        }
+    }
+
+    fn get_event_priority(&self, _: EventCategory) -> Option<i32> {
+        None
+    }
+
+    fn handle_event(&mut self, _: &mut Glfw, _: &mut glfw::Window, _: &WindowEvent) -> bool {
+        false
+    }
+
+    fn as_inspectable(&self) -> Option<&dyn InspectableComponent> {
+        Some(self)
+    }
+
+    fn as_inspectable_mut(&mut self) -> Option<&mut dyn InspectableComponent> {
+        Some(self)
+    }
+}
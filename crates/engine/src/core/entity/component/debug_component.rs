@@ -1,28 +1,94 @@
-use glfw::{Action, Glfw, Key};
+use std::sync::atomic::Ordering;
+
+use glfw::Glfw;
 
 use crate::{
     core::{
+        build_info::BuildInfo,
         entity::{
-            component::{camera_component, Component},
+            component::{
+                bounds_component::{BoundsComponent, DEBUG_CULLING},
+                camera_component, Component, InspectableComponent,
+            },
             Entity,
         },
+        event::EventCategory,
+        input::Input,
+        logger::Logger,
+        model::FORCE_LINEAR_SKINNING,
+        profiler::Profiler,
         renderer::{
             line::{Line, LineRenderer},
             text::{Fonts, Text},
         },
         scene::Scene,
     },
-    terrain::{dual_contouring::DualContouringChunk, ChunkBounds, Terrain, CHUNK_SIZE},
+    terrain::{
+        dual_contouring::DualContouringChunk, resources::XRAY, Chunk, ChunkBounds, Seam, SeamKind,
+        Terrain, CHUNK_SIZE, CHUNK_SIZE_FLOAT, LOD_COLORING, WIREFRAME,
+    },
 };
-use cgmath::{Deg, EuclideanSpace, Matrix4, Point3, Vector3};
+use cgmath::{Deg, EuclideanSpace, InnerSpace, Matrix4, Point3, SquareMatrix, Vector3};
 
 use super::model_component::ModelComponent;
 
+/// Debug toggles receive keyboard events before gameplay components
+const DEBUG_EVENT_PRIORITY: i32 = 100;
+/// Actions toggling the debug views, F1 to F12 by default, the time controls, the scene dump and
+/// the about panel
+const DEBUG_ACTIONS: [&str; 18] = [
+    "toggle_wireframe",
+    "toggle_vsync",
+    "toggle_debug",
+    "toggle_rays",
+    "toggle_xray",
+    "toggle_linear_skinning",
+    "toggle_culling_debug",
+    "toggle_profiler",
+    "toggle_chunk_bounds",
+    "toggle_normals",
+    "toggle_lod_coloring",
+    "toggle_seams",
+    "toggle_pause",
+    "step_frame",
+    "slow_down",
+    "speed_up",
+    "dump_scene",
+    "toggle_about",
+];
+/// File "dump_scene" writes the scene dump to, relative to the working directory
+const DUMP_PATH: &str = "debug_dump.txt";
+/// Factor "slow_down" and "speed_up" change the time scale by
+const TIME_SCALE_STEP: f32 = 2.0;
+/// Latest log records shown below the debug info
+const LOG_LINES: usize = 8;
+/// Top of the about panel, below the log records
+const ABOUT_TOP: i32 = 140 + LOG_LINES as i32 * 18 + 12;
+/// Vertex normals are only drawn for chunks whose center is this close to the camera
+const NORMALS_DISTANCE: f32 = CHUNK_SIZE_FLOAT;
+const NORMAL_LENGTH: f32 = 0.5;
+/// Distance up to which the vertices of neighbouring chunks count as the same
+const SEAM_POSITION_EPSILON: f32 = 0.01;
+/// Angle in radians up to which the normals of neighbouring chunks count as the same
+const SEAM_NORMAL_EPSILON: f32 = 0.05;
+/// Half the size of the boxes marking seams
+const SEAM_MARKER_SIZE: f32 = 0.15;
+
 pub struct DebugController {
     pub debug_ui: bool,
-    wireframe: bool,
     vsync: bool,
     show_rays: bool,
+    show_chunk_bounds: bool,
+    show_normals: bool,
+    /// Seams between the loaded chunks while validating them, with the terrain version they
+    /// were found in
+    seams: Option<(u64, Vec<Seam>)>,
+    /// Set by "dump_scene", the dump needs the scene and is made in the next update
+    dump_requested: bool,
+    /// Time controls pressed since the last update, they change the scene's `TimeControl`
+    time_actions: Vec<&'static str>,
+    /// Dump waiting to be copied to the clipboard, which needs the window of the next event
+    pending_clipboard: Option<String>,
     delta_time: f64,
 
     bounds: ChunkBounds,
@@ -33,15 +99,25 @@ pub struct DebugController {
     chunk_min_text: Text,
     chunk_max_text: Text,
     triangle_count_text: Text,
+    log_texts: Vec<Text>,
+    /// Version, build and gpu, shown with the debug info or on its own with "toggle_about".
+    /// Created when it is first shown, the gpu is only known once the context exists
+    about_texts: Option<Vec<Text>>,
+    show_about: bool,
 }
 
 impl DebugController {
     pub fn new() -> Self {
         Self {
             debug_ui: false,
-            wireframe: false,
             vsync: true,
             show_rays: false,
+            show_chunk_bounds: false,
+            show_normals: false,
+            seams: None,
+            dump_requested: false,
+            time_actions: Vec::new(),
+            pending_clipboard: None,
             delta_time: 0.0,
 
             bounds: ChunkBounds {
@@ -55,20 +131,206 @@ impl DebugController {
             chunk_min_text: Text::new(Fonts::RobotoMono, 5, 70, 0, 16.0, String::from("")),
             chunk_max_text: Text::new(Fonts::RobotoMono, 5, 90, 0, 16.0, String::from("")),
             triangle_count_text: Text::new(Fonts::RobotoMono, 5, 110, 0, 16.0, String::from("")),
+            log_texts: (0..LOG_LINES)
+                .map(|line| {
+                    Text::new(
+                        Fonts::RobotoMono,
+                        5,
+                        140 + line as i32 * 18,
+                        0,
+                        14.0,
+                        String::from(""),
+                    )
+                })
+                .collect(),
+            about_texts: None,
+            show_about: false,
         }
     }
 }
 
+impl DebugController {
+    fn create_about_texts() -> Vec<Text> {
+        BuildInfo::get_lines()
+            .into_iter()
+            .enumerate()
+            .map(|(line, content)| {
+                Text::new(
+                    Fonts::RobotoMono,
+                    5,
+                    ABOUT_TOP + line as i32 * 18,
+                    0,
+                    14.0,
+                    content,
+                )
+            })
+            .collect()
+    }
+
+    /// Writes the scene dump to `DUMP_PATH` and keeps it for the clipboard
+    fn dump_scene(&mut self, scene: &Scene) {
+        let dump = scene.debug_dump();
+        match std::fs::write(DUMP_PATH, &dump) {
+            Ok(()) => log::info!("Wrote the scene dump to {}", DUMP_PATH),
+            Err(err) => log::error!("Failed to write the scene dump to {}: {}", DUMP_PATH, err),
+        }
+        self.pending_clipboard = Some(dump);
+    }
+
+    /// Scans the chunks for seams when the terrain changed since the last scan, logs the result
+    /// and marks the seams, position seams in red and normal seams in orange
+    fn validate_seams(&mut self, scene: &mut Scene) {
+        let Some((version, seams)) = &mut self.seams else {
+            return;
+        };
+        if let Some(terrain_entity) = scene
+            .get_entities_with_component::<Terrain<DualContouringChunk>>()
+            .first()
+        {
+            let terrain = terrain_entity
+                .get_own_component::<Terrain<DualContouringChunk>>()
+                .unwrap();
+            if terrain.get_version() != *version {
+                *version = terrain.get_version();
+                *seams =
+                    terrain.find_seams(terrain_entity, SEAM_POSITION_EPSILON, SEAM_NORMAL_EPSILON);
+                let position_seams = seams
+                    .iter()
+                    .filter(|seam| seam.kind == SeamKind::Position)
+                    .count();
+                log::info!(
+                    target: "terrain",
+                    "Found {} position and {} normal seams between {} chunks",
+                    position_seams,
+                    seams.len() - position_seams,
+                    terrain.get_chunks().len()
+                );
+            }
+        }
+        let offset = Vector3::new(SEAM_MARKER_SIZE, SEAM_MARKER_SIZE, SEAM_MARKER_SIZE);
+        for seam in seams.iter() {
+            let color = match seam.kind {
+                SeamKind::Position => Vector3::new(1.0, 0.0, 0.0),
+                SeamKind::Normal => Vector3::new(1.0, 0.5, 0.0),
+            };
+            scene.debug_draw.draw_aabb(
+                seam.position - offset,
+                seam.position + offset,
+                color,
+                false,
+            );
+        }
+    }
+
+    /// Lines along the vertex normals of the terrain chunks close to the camera
+    fn get_normal_lines(scene: &Scene) -> Vec<(Point3<f32>, Point3<f32>)> {
+        let Some(camera_component) = scene.get_component::<camera_component::CameraComponent>()
+        else {
+            return Vec::new();
+        };
+        let eye = camera_component.get_camera().get_position();
+        let mut lines = Vec::new();
+        for terrain_entity in scene.get_entities_with_component::<Terrain<DualContouringChunk>>() {
+            let terrain = terrain_entity
+                .get_own_component::<Terrain<DualContouringChunk>>()
+                .unwrap();
+            for loaded in terrain.get_chunks() {
+                if (loaded.bounds.center() - eye).magnitude() > NORMALS_DISTANCE {
+                    continue;
+                }
+                let Some(chunk) = terrain_entity
+                    .get_child(&loaded.entity)
+                    .and_then(|entity| entity.get_own_component::<DualContouringChunk>())
+                else {
+                    continue;
+                };
+                let origin = chunk.get_position().to_vec();
+                for (vertex, normal) in chunk.get_vertices().iter().zip(chunk.get_normals()) {
+                    let from = Point3::from(*vertex) + origin;
+                    lines.push((from, from + Vector3::from(normal) * NORMAL_LENGTH));
+                }
+            }
+        }
+        lines
+    }
+}
+
+impl InspectableComponent for DebugController {
+    fn get_inspector_name(&self) -> &str {
+        "Debug controller"
+    }
+}
+
 impl Component for DebugController {
+    fn is_realtime(&self) -> bool {
+        true
+    }
+
     fn update(&mut self, scene: &mut Scene, _: &mut Entity, delta_time: f64) {
         self.delta_time = delta_time;
 
+        let time_control = &mut scene.time_control;
+        for action in self.time_actions.drain(..) {
+            match action {
+                "toggle_pause" => time_control.toggle_paused(),
+                "step_frame" => time_control.step(),
+                "slow_down" => {
+                    time_control.set_time_scale(time_control.get_time_scale() / TIME_SCALE_STEP)
+                }
+                "speed_up" => {
+                    time_control.set_time_scale(time_control.get_time_scale() * TIME_SCALE_STEP)
+                }
+                _ => {}
+            }
+        }
         let fps = 1.0 / self.delta_time;
+        let time = if time_control.is_paused() {
+            String::from(" paused")
+        } else if time_control.get_time_scale() != 1.0 {
+            format!(" x{:.2}", time_control.get_time_scale())
+        } else {
+            String::new()
+        };
         self.fps_text.set_content(&format!(
-            "{:.2} FPS ({:.2}ms)",
+            "{:.2} FPS ({:.2}ms){}",
             fps,
-            self.delta_time * 1000.0
+            self.delta_time * 1000.0,
+            time
         ));
+        if self.show_chunk_bounds {
+            let bounds: Vec<ChunkBounds> = scene
+                .get_component::<Terrain<DualContouringChunk>>()
+                .map(|terrain| {
+                    terrain
+                        .get_chunks()
+                        .iter()
+                        .map(|chunk| chunk.bounds.clone())
+                        .collect()
+                })
+                .unwrap_or_default();
+            for bounds in &bounds {
+                scene
+                    .debug_draw
+                    .draw_chunk_bounds(bounds, Vector3::new(0.0, 0.6, 1.0), true);
+            }
+        }
+        if self.show_normals {
+            for (from, to) in DebugController::get_normal_lines(scene) {
+                scene
+                    .debug_draw
+                    .draw_line(from, to, Vector3::new(1.0, 0.0, 1.0), true);
+            }
+        }
+        if self.seams.is_some() {
+            self.validate_seams(scene);
+        }
+        if self.dump_requested {
+            self.dump_requested = false;
+            self.dump_scene(scene);
+        }
+        if (self.debug_ui || self.show_about) && self.about_texts.is_none() {
+            self.about_texts = Some(DebugController::create_about_texts());
+        }
         if self.debug_ui {
             if let Some(camera_component) =
                 scene.get_component::<camera_component::CameraComponent>()
@@ -105,22 +367,39 @@ impl Component for DebugController {
             }
             self.triangle_count_text
                 .set_content(&format!("Triangles: {}", triangle_count));
+
+            let records = Logger::get_records(LOG_LINES);
+            for (line, text) in self.log_texts.iter_mut().enumerate() {
+                match records.get(line) {
+                    Some(record) => text.set_content(&record.to_string()),
+                    None => text.set_content(""),
+                }
+            }
         }
     }
 
-    fn handle_event(&mut self, glfw: &mut Glfw, _: &mut glfw::Window, event: &glfw::WindowEvent) {
-        match event {
-            glfw::WindowEvent::Key(Key::F1, _, Action::Press, _) => {
-                self.wireframe = !self.wireframe;
-                unsafe {
-                    if self.wireframe {
-                        gl::PolygonMode(gl::FRONT_AND_BACK, gl::LINE);
-                    } else {
-                        gl::PolygonMode(gl::FRONT_AND_BACK, gl::FILL);
-                    }
-                }
+    fn get_event_priority(&self, category: EventCategory) -> Option<i32> {
+        match category {
+            EventCategory::Keyboard => Some(DEBUG_EVENT_PRIORITY),
+            _ => None,
+        }
+    }
+
+    fn handle_event(
+        &mut self,
+        glfw: &mut Glfw,
+        window: &mut glfw::Window,
+        event: &glfw::WindowEvent,
+    ) -> bool {
+        // usually the release of the key that requested the dump
+        if let Some(dump) = self.pending_clipboard.take() {
+            window.set_clipboard_string(&dump);
+        }
+        match Input::get_pressed_action(&DEBUG_ACTIONS, event) {
+            Some("toggle_wireframe") => {
+                WIREFRAME.fetch_xor(true, Ordering::Relaxed);
             }
-            glfw::WindowEvent::Key(Key::F2, _, Action::Press, _) => {
+            Some("toggle_vsync") => {
                 self.vsync = !self.vsync;
                 if self.vsync {
                     glfw.set_swap_interval(glfw::SwapInterval::Sync(1));
@@ -128,17 +407,58 @@ impl Component for DebugController {
                     glfw.set_swap_interval(glfw::SwapInterval::None);
                 }
             }
-            glfw::WindowEvent::Key(Key::F3, _, Action::Press, _) => {
+            Some("toggle_debug") => {
                 self.debug_ui = !self.debug_ui;
             }
-            glfw::WindowEvent::Key(Key::F4, _, Action::Press, _) => {
+            Some("toggle_rays") => {
                 self.show_rays = !self.show_rays;
             }
-            _ => {}
+            Some("toggle_xray") => {
+                XRAY.fetch_xor(true, Ordering::Relaxed);
+            }
+            Some("toggle_linear_skinning") => {
+                FORCE_LINEAR_SKINNING.fetch_xor(true, Ordering::Relaxed);
+            }
+            Some("toggle_culling_debug") => {
+                DEBUG_CULLING.fetch_xor(true, Ordering::Relaxed);
+            }
+            Some("toggle_profiler") => {
+                Profiler::set_enabled(!Profiler::is_enabled());
+            }
+            Some("toggle_chunk_bounds") => {
+                self.show_chunk_bounds = !self.show_chunk_bounds;
+            }
+            Some("toggle_normals") => {
+                self.show_normals = !self.show_normals;
+            }
+            Some("toggle_lod_coloring") => {
+                LOD_COLORING.fetch_xor(true, Ordering::Relaxed);
+            }
+            Some("toggle_seams") => {
+                // an empty version makes the next update scan the chunks
+                self.seams = match self.seams {
+                    Some(_) => None,
+                    None => Some((0, Vec::new())),
+                };
+            }
+            Some(action @ ("toggle_pause" | "step_frame" | "slow_down" | "speed_up")) => {
+                self.time_actions.push(action)
+            }
+            Some("dump_scene") => self.dump_requested = true,
+            Some("toggle_about") => {
+                self.show_about = !self.show_about;
+            }
+            _ => return false,
         }
+        true
     }
 
     fn render(&self, scene: &Scene, _: &Entity, view_projection: &Matrix4<f32>, _: &Matrix4<f32>) {
+        if self.debug_ui || self.show_about {
+            for text in self.about_texts.iter().flatten() {
+                text.render();
+            }
+        }
         if self.show_rays {
             if let Some(terrain) = scene.get_component::<Terrain<DualContouringChunk>>() {
                 if let Some((line, _)) = &terrain.get_mouse_picker().ray {
@@ -152,6 +472,33 @@ impl Component for DebugController {
             }
         }
 
+        if DEBUG_CULLING.load(Ordering::Relaxed) {
+            let mut bounds = Vec::new();
+            for entity in scene.get_entities().iter() {
+                entity.collect_components::<BoundsComponent>(Matrix4::identity(), &mut bounds);
+            }
+            let (mut visible, mut culled) = (Vec::new(), Vec::new());
+            for (transform, bounds) in bounds {
+                if bounds.is_visible(view_projection, &transform) {
+                    visible.extend(bounds.get_edges(&transform));
+                } else {
+                    culled.extend(bounds.get_edges(&transform));
+                }
+            }
+            LineRenderer::render_lines(
+                view_projection,
+                &visible,
+                Vector3::new(0.0, 1.0, 0.0),
+                false,
+            );
+            LineRenderer::render_lines(
+                view_projection,
+                &culled,
+                Vector3::new(1.0, 0.0, 0.0),
+                false,
+            );
+        }
+
         if self.debug_ui {
             self.fps_text.render();
             self.pos_text.render();
@@ -159,6 +506,9 @@ impl Component for DebugController {
             self.chunk_min_text.render();
             self.chunk_max_text.render();
             self.triangle_count_text.render();
+            for text in &self.log_texts {
+                text.render();
+            }
 
             let mut lines: Vec<Line> = Vec::new();
             let mut corner_lines: Vec<Line> = Vec::new();
@@ -192,12 +542,21 @@ impl Component for DebugController {
 
             for entity in scene.get_entities_with_component::<ModelComponent>() {
                 let transform = Matrix4::from_translation(entity.get_position().to_vec());
-                if let Some(model_component) = entity.get_component::<ModelComponent>() {
-                    model_component
-                        .get_model()
-                        .render_bones(view_projection, &transform);
+                if let Some(model) = entity
+                    .get_component::<ModelComponent>()
+                    .and_then(ModelComponent::get_model)
+                {
+                    model.render_bones(view_projection, &transform);
                 }
             }
         }
     }
+
+    fn as_inspectable(&self) -> Option<&dyn InspectableComponent> {
+        Some(self)
+    }
+
+    fn as_inspectable_mut(&mut self) -> Option<&mut dyn InspectableComponent> {
+        Some(self)
+    }
 }
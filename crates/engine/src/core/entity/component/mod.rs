@@ -1,9 +1,13 @@
 use as_any::AsAny;
 
-use cgmath::Matrix4;
+use cgmath::{Matrix4, Vector3};
 use glfw::{Glfw, Window};
 
-use crate::core::scene::Scene;
+use crate::core::{
+    event::{EventCategory, DEFAULT_EVENT_PRIORITY},
+    renderer::transparency::TransparencyQueue,
+    scene::Scene,
+};
 
 use super::Entity;
 
@@ -17,10 +21,75 @@ pub trait Component: AsAny {
         _parent_transform: &Matrix4<f32>,
     ) {
     }
-    fn handle_event(&mut self, glfw: &mut Glfw, window: &mut Window, event: &glfw::WindowEvent);
+    /// Starts recording the draws of the frame on the `RenderWorkers`, called on every entity
+    /// before the passes render. The passes drawing the component submit them in `render`.
+    /// Only the terrain records its chunk batch this way, other components draw in `render`
+    fn record(&self, _scene: &Scene, _entity: &Entity) {}
+    /// Adds the blended draws of the component to `queue`, they are left out of `render` and
+    /// drawn back to front after the opaque geometry
+    fn queue_transparent<'a>(
+        &'a self,
+        _scene: &'a Scene,
+        _entity: &'a Entity,
+        _view_projection: &Matrix4<f32>,
+        _parent_transform: &Matrix4<f32>,
+        _queue: &mut TransparencyQueue<'a>,
+    ) {
+    }
+    /// Priority with which the component receives events of `category`, None if it is not interested in them
+    fn get_event_priority(&self, _category: EventCategory) -> Option<i32> {
+        Some(DEFAULT_EVENT_PRIORITY)
+    }
+    /// Returns true if the event was consumed and must not reach handlers with a lower priority
+    fn handle_event(
+        &mut self,
+        glfw: &mut Glfw,
+        window: &mut Window,
+        event: &glfw::WindowEvent,
+    ) -> bool;
+    /// The component as the inspector shows it, None if it does not implement
+    /// `InspectableComponent`
+    /// Whether the component updates with the real frame time, also while `TimeControl` pauses
+    /// the rest of the scene
+    fn is_realtime(&self) -> bool {
+        false
+    }
+    fn as_inspectable(&self) -> Option<&dyn InspectableComponent> {
+        None
+    }
+    fn as_inspectable_mut(&mut self) -> Option<&mut dyn InspectableComponent> {
+        None
+    }
+}
+
+/// Value of a field the inspector shows
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InspectorValue {
+    Float(f32),
+    Vector3(Vector3<f32>),
+}
+
+/// Editable field of an `InspectableComponent`
+pub struct InspectorField {
+    pub name: &'static str,
+    pub value: InspectorValue,
+}
+
+/// Component the `Inspector` lists by name and whose fields it lets the user edit
+pub trait InspectableComponent {
+    fn get_inspector_name(&self) -> &str;
+    fn get_fields(&self) -> Vec<InspectorField> {
+        Vec::new()
+    }
+    /// Sets the field `name` to a value the user entered, which has the type `get_fields`
+    /// reported for it
+    fn set_field(&mut self, _name: &str, _value: InspectorValue) {}
 }
 
 pub mod animation_component;
+pub mod bone_attachment_component;
+pub mod bounds_component;
 pub mod camera_component;
+pub mod character_controller_component;
 pub mod debug_component;
 pub mod model_component;
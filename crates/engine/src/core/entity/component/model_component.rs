@@ -1,42 +1,171 @@
-use cgmath::Matrix4;
+use cgmath::{EuclideanSpace, Matrix4, MetricSpace, Point3, Transform, Vector3};
 
-use crate::core::{entity::Entity, model::Model, renderer::light::skylight, scene::Scene};
+use crate::core::{
+    entity::Entity,
+    event::EventCategory,
+    model::{Model, ModelHandle},
+    renderer::{debug_draw::DebugDraw, light::skylight, transparency::TransparencyQueue},
+    scene::Scene,
+};
 
-use super::Component;
+use super::{
+    animation_component::AnimationComponent, bone_attachment_component::BoneAttachmentComponent,
+    bounds_component::BoundsComponent, camera_component::CameraComponent, Component,
+    InspectableComponent, InspectorField, InspectorValue,
+};
+
+/// Box drawn while the model is loading for entities without a `BoundsComponent`
+const PLACEHOLDER_MIN: (f32, f32, f32) = (-0.5, 0.0, -0.5);
+const PLACEHOLDER_MAX: (f32, f32, f32) = (0.5, 2.0, 0.5);
 
 pub struct ModelComponent {
-    model: Model,
+    model: ModelHandle,
 }
 
 impl ModelComponent {
     pub fn new(model: Model) -> Self {
+        ModelComponent {
+            model: ModelHandle::from(model),
+        }
+    }
+
+    /// Component for a model that is still loading, see `ModelBuilder::build_async`. The bounds
+    /// of the entity are drawn in its place until it is ready
+    pub fn loading(model: ModelHandle) -> Self {
         ModelComponent { model }
     }
 
-    pub fn get_model(&self) -> &Model {
-        &self.model
+    /// The model, None while it is loading or if it failed to load
+    pub fn get_model(&self) -> Option<&Model> {
+        self.model.get()
     }
 
-    pub fn get_model_mut(&mut self) -> &mut Model {
-        &mut self.model
+    pub fn get_model_mut(&mut self) -> Option<&mut Model> {
+        self.model.get_mut()
+    }
+
+    pub fn is_loading(&self) -> bool {
+        self.model.is_loading()
+    }
+
+    fn render_placeholder(
+        entity: &Entity,
+        view_projection: &Matrix4<f32>,
+        transform: &Matrix4<f32>,
+    ) {
+        let (min, max) = match entity.get_own_component::<BoundsComponent>() {
+            Some(bounds) => bounds.get_world_bounds(transform),
+            None => (
+                transform.transform_point(Point3::from(PLACEHOLDER_MIN)),
+                transform.transform_point(Point3::from(PLACEHOLDER_MAX)),
+            ),
+        };
+        let mut placeholder = DebugDraw::new();
+        placeholder.draw_aabb(min, max, Vector3::new(0.8, 0.8, 0.8), true);
+        placeholder.render(view_projection);
+    }
+}
+
+impl InspectableComponent for ModelComponent {
+    fn get_inspector_name(&self) -> &str {
+        "Model"
+    }
+
+    fn get_fields(&self) -> Vec<InspectorField> {
+        let Some(model) = self.model.get() else {
+            return Vec::new();
+        };
+        vec![InspectorField {
+            name: "Roughness",
+            value: InspectorValue::Float(model.get_roughness()),
+        }]
+    }
+
+    fn set_field(&mut self, name: &str, value: InspectorValue) {
+        if let (Some(model), "Roughness", InspectorValue::Float(roughness)) =
+            (self.model.get_mut(), name, value)
+        {
+            model.set_roughness(roughness);
+        }
     }
 }
 
 impl Component for ModelComponent {
-    fn update(&mut self, _: &mut Scene, _: &mut Entity, _: f64) {}
+    fn update(&mut self, scene: &mut Scene, entity: &mut Entity, _: f64) {
+        self.model.poll();
+        let Some(model) = self.model.get_mut() else {
+            return;
+        };
+        if model.get_skeleton_lod().is_some() {
+            if let Some(camera) = scene.get_component::<CameraComponent>() {
+                let eye = camera.get_camera().get_eye_position();
+                model.update_lod((entity.get_position() + model.position.to_vec()).distance(eye));
+            }
+        }
+        // animated models move their attachments once they are posed
+        if !entity.has_component::<AnimationComponent>() {
+            BoneAttachmentComponent::place_children(scene, entity, model);
+        }
+    }
 
     fn render(
         &self,
         scene: &Scene,
-        _: &Entity,
+        entity: &Entity,
         view_projection: &Matrix4<f32>,
         parent_transform: &Matrix4<f32>,
     ) {
+        let Some(model) = self.model.get() else {
+            if self.model.is_loading() {
+                ModelComponent::render_placeholder(entity, view_projection, parent_transform);
+            }
+            return;
+        };
         if let Some(skylight) = scene.get_component::<skylight::SkyLight>() {
-            self.model
-                .render(&skylight.get_position(), &parent_transform, view_projection);
+            model.render(&skylight.get_position(), parent_transform, view_projection);
         }
     }
 
-    fn handle_event(&mut self, _: &mut glfw::Glfw, _: &mut glfw::Window, _: &glfw::WindowEvent) {}
+    fn queue_transparent<'a>(
+        &'a self,
+        scene: &'a Scene,
+        _: &'a Entity,
+        view_projection: &Matrix4<f32>,
+        parent_transform: &Matrix4<f32>,
+        queue: &mut TransparencyQueue<'a>,
+    ) {
+        let (Some(model), Some(skylight)) = (
+            self.model.get(),
+            scene.get_component::<skylight::SkyLight>(),
+        ) else {
+            return;
+        };
+        model.queue_transparent(
+            skylight.get_position(),
+            *parent_transform,
+            *view_projection,
+            queue,
+        );
+    }
+
+    fn get_event_priority(&self, _: EventCategory) -> Option<i32> {
+        None
+    }
+
+    fn handle_event(
+        &mut self,
+        _: &mut glfw::Glfw,
+        _: &mut glfw::Window,
+        _: &glfw::WindowEvent,
+    ) -> bool {
+        false
+    }
+
+    fn as_inspectable(&self) -> Option<&dyn InspectableComponent> {
+        Some(self)
+    }
+
+    fn as_inspectable_mut(&mut self) -> Option<&mut dyn InspectableComponent> {
+        Some(self)
+    }
 }
@@ -1,30 +1,73 @@
+use std::cmp::Reverse;
+
 use cgmath::{EuclideanSpace, Matrix4, Point3, Quaternion};
 
-use crate::core::{physics::rigidbody::RigidBody, scene::Scene, utils::DataSource};
+use crate::core::{
+    event::EventCategory, physics::rigidbody::RigidBody, renderer::transparency::TransparencyQueue,
+    scene::Scene, utils::DataSource,
+};
 
-use super::{component::Component, Entity, EntityHandle};
+use super::{
+    component::{bounds_component::BoundsComponent, Component, InspectableComponent},
+    ChangeTracker, ComponentHandle, Entity, EntityFilter, EntityHandle,
+};
 
 impl Entity {
     pub fn new(name: &str) -> Self {
         Entity {
             id: EntityHandle::new(),
             name: DataSource::new(name.to_string()),
+            tags: Vec::new(),
             children: Vec::new(),
             components: Vec::new(),
+            component_ticks: Vec::new(),
             position: Point3::new(0.0, 0.0, 0.0),
             rotation: Quaternion::new(1.0, 0.0, 0.0, 0.0),
+            transform_tick: ChangeTracker::get_current_tick(),
         }
     }
 
     pub fn update(&mut self, scene: &mut Scene, delta_time: f64) {
+        self.update_with_time(scene, Some(delta_time), delta_time);
+    }
+
+    /// Updates the realtime components with `real_delta_time` and the rest with the scaled
+    /// `delta_time`, skipping them while it is None
+    pub fn update_with_time(
+        &mut self,
+        scene: &mut Scene,
+        delta_time: Option<f64>,
+        real_delta_time: f64,
+    ) {
         for i in 0..self.components.len() {
+            let delta_time = if self.components[i].is_realtime() {
+                real_delta_time
+            } else if let Some(delta_time) = delta_time {
+                delta_time
+            } else {
+                continue;
+            };
             let mut component = self.components.remove(i);
             component.update(scene, self, delta_time);
             self.components.insert(i, component);
         }
 
         for child in self.children.iter_mut() {
-            child.update(scene, delta_time);
+            child.update_with_time(scene, delta_time, real_delta_time);
+        }
+    }
+
+    pub fn update_component<T: Component>(&mut self, scene: &mut Scene, delta_time: f64) {
+        for i in 0..self.components.len() {
+            if self.components[i].as_any().is::<T>() {
+                let mut component = self.components.remove(i);
+                component.update(scene, self, delta_time);
+                self.components.insert(i, component);
+            }
+        }
+
+        for child in self.children.iter_mut() {
+            child.update_component::<T>(scene, delta_time);
         }
     }
 
@@ -34,9 +77,16 @@ impl Entity {
         view_projection: &Matrix4<f32>,
         parent_transform: Matrix4<f32>,
     ) {
-        let transform = parent_transform
-            * Matrix4::from_translation(self.position.to_vec())
-            * Matrix4::from(self.rotation);
+        let transform = self.get_transform(parent_transform);
+        let bounds = self
+            .components
+            .iter()
+            .find_map(|component| component.as_any().downcast_ref::<BoundsComponent>());
+        if let Some(bounds) = bounds {
+            if !bounds.is_visible(view_projection, &transform) {
+                return;
+            }
+        }
         for component in self.components.iter() {
             component.render(scene, self, view_projection, &transform);
         }
@@ -46,6 +96,45 @@ impl Entity {
         }
     }
 
+    /// Lets the components of the entity and its children start recording the draws of the
+    /// frame, see `Component::record`
+    pub fn record(&self, scene: &Scene) {
+        for component in self.components.iter() {
+            component.record(scene, self);
+        }
+        for child in self.children.iter() {
+            child.record(scene);
+        }
+    }
+
+    /// Queues the blended draws of the components of the entity and its children, like `render`
+    /// skipping entities whose bounds are not visible
+    pub fn queue_transparent<'a>(
+        &'a self,
+        scene: &'a Scene,
+        view_projection: &Matrix4<f32>,
+        parent_transform: Matrix4<f32>,
+        queue: &mut TransparencyQueue<'a>,
+    ) {
+        let transform = self.get_transform(parent_transform);
+        let bounds = self
+            .components
+            .iter()
+            .find_map(|component| component.as_any().downcast_ref::<BoundsComponent>());
+        if let Some(bounds) = bounds {
+            if !bounds.is_visible(view_projection, &transform) {
+                return;
+            }
+        }
+        for component in self.components.iter() {
+            component.queue_transparent(scene, self, view_projection, &transform, queue);
+        }
+
+        for child in self.children.iter() {
+            child.queue_transparent(scene, view_projection, transform, queue);
+        }
+    }
+
     pub fn add_child(&mut self, child: Entity) {
         self.children.push(child);
     }
@@ -78,23 +167,64 @@ impl Entity {
         None
     }
 
+    /// Passes the event to the components of this entity and its children by priority,
+    /// returns true if it was consumed
     pub fn handle_event(
         &mut self,
         glfw: &mut glfw::Glfw,
         window: &mut glfw::Window,
         event: &glfw::WindowEvent,
+    ) -> bool {
+        let mut handlers = Vec::new();
+        self.collect_event_handlers(EventCategory::from(event), &mut handlers);
+        Entity::dispatch_event(handlers, glfw, window, event)
+    }
+
+    /// Collects the components of this entity and its children interested in `category` with their priority
+    pub(crate) fn collect_event_handlers<'a>(
+        &'a mut self,
+        category: EventCategory,
+        handlers: &mut Vec<(i32, &'a mut Box<dyn Component>)>,
     ) {
         for component in self.components.iter_mut() {
-            component.handle_event(glfw, window, event);
+            if let Some(priority) = component.get_event_priority(category) {
+                handlers.push((priority, component));
+            }
         }
 
         for child in self.children.iter_mut() {
-            child.handle_event(glfw, window, event);
+            child.collect_event_handlers(category, handlers);
         }
     }
 
-    pub fn add_component<T: 'static + Component>(&mut self, component: T) {
+    /// Calls the handlers from the highest to the lowest priority until one consumes the event,
+    /// handlers with the same priority keep their order
+    pub(crate) fn dispatch_event(
+        mut handlers: Vec<(i32, &mut Box<dyn Component>)>,
+        glfw: &mut glfw::Glfw,
+        window: &mut glfw::Window,
+        event: &glfw::WindowEvent,
+    ) -> bool {
+        handlers.sort_by_key(|(priority, _)| Reverse(*priority));
+        for (_, component) in handlers {
+            if component.handle_event(glfw, window, event) {
+                return true;
+            }
+        }
+        false
+    }
+
+    /// Adds `component` to the entity, the returned handle finds it in the scene later on
+    pub fn add_component<T: 'static + Component>(&mut self, component: T) -> ComponentHandle<T> {
         self.components.push(Box::new(component));
+        self.component_ticks.push(ChangeTracker::get_current_tick());
+        ComponentHandle::new(self.id)
+    }
+
+    /// Handle of the component of type `T` of this entity, children are not searched
+    pub fn get_component_handle<T: Component>(&self) -> Option<ComponentHandle<T>> {
+        self.get_own_component::<T>()
+            .map(|_| ComponentHandle::new(self.id))
     }
 
     pub fn get_component<T>(&self) -> Option<&T>
@@ -114,6 +244,25 @@ impl Entity {
         None
     }
 
+    /// Collects the components of type `T` of the entity and its children with their world transforms
+    pub fn collect_components<'a, T>(
+        &'a self,
+        parent_transform: Matrix4<f32>,
+        components: &mut Vec<(Matrix4<f32>, &'a T)>,
+    ) where
+        T: Component,
+    {
+        let transform = self.get_transform(parent_transform);
+        for component in self.components.iter() {
+            if let Some(component) = component.as_any().downcast_ref::<T>() {
+                components.push((transform, component));
+            }
+        }
+        for child in self.children.iter() {
+            child.collect_components(transform, components);
+        }
+    }
+
     pub fn get_with_own_component<T>(&self) -> Vec<&Entity>
     where
         T: Component,
@@ -130,41 +279,170 @@ impl Entity {
         entities
     }
 
+    /// Whether this entity itself has a component of type `T`
+    pub fn has_component<T>(&self) -> bool
+    where
+        T: Component,
+    {
+        self.get_own_component::<T>().is_some()
+    }
+
+    /// Collects the entity and its children that match `filter`, parents before their children
+    pub fn collect_matching<'a>(&'a self, filter: &EntityFilter, entities: &mut Vec<&'a Entity>) {
+        if filter.matches(self) {
+            entities.push(self);
+        }
+        for child in self.children.iter() {
+            child.collect_matching(filter, entities);
+        }
+    }
+
+    /// Component of type `T` of this entity, unlike `get_component` its children are not searched
+    pub fn get_own_component<T>(&self) -> Option<&T>
+    where
+        T: Component,
+    {
+        self.components
+            .iter()
+            .find_map(|component| component.as_any().downcast_ref::<T>())
+    }
+
     pub fn get_component_mut<T>(&mut self) -> Option<&mut T>
     where
         T: Component,
     {
-        for component in self.components.iter_mut() {
+        let tick = ChangeTracker::get_current_tick();
+        for (component, component_tick) in self.components.iter_mut().zip(&mut self.component_ticks)
+        {
             if let Some(component) = component.as_any_mut().downcast_mut::<T>() {
+                *component_tick = tick;
                 return Some(component);
             }
         }
         None
     }
 
+    /// Components of this entity that implement `InspectableComponent`, in the order they were
+    /// added
+    pub fn get_inspectable_components(&self) -> Vec<&dyn InspectableComponent> {
+        self.components
+            .iter()
+            .filter_map(|component| component.as_inspectable())
+            .collect()
+    }
+
+    /// Inspectable component at `index` of `get_inspectable_components`
+    pub fn get_inspectable_component_mut(
+        &mut self,
+        index: usize,
+    ) -> Option<&mut dyn InspectableComponent> {
+        let tick = ChangeTracker::get_current_tick();
+        self.components
+            .iter_mut()
+            .zip(&mut self.component_ticks)
+            .filter_map(|(component, component_tick)| {
+                component
+                    .as_inspectable_mut()
+                    .map(|component| (component, component_tick))
+            })
+            .nth(index)
+            .map(|(component, component_tick)| {
+                *component_tick = tick;
+                component
+            })
+    }
+
+    /// World transform of this entity or the child `id`, for this entity's parent at
+    /// `parent_transform`
+    pub fn find_world_transform(
+        &self,
+        id: &EntityHandle,
+        parent_transform: Matrix4<f32>,
+    ) -> Option<Matrix4<f32>> {
+        let transform = self.get_transform(parent_transform);
+        if self.id == *id {
+            return Some(transform);
+        }
+        self.children
+            .iter()
+            .find_map(|child| child.find_world_transform(id, transform))
+    }
+
+    fn get_transform(&self, parent_transform: Matrix4<f32>) -> Matrix4<f32> {
+        parent_transform
+            * Matrix4::from_translation(self.position.to_vec())
+            * Matrix4::from(self.rotation)
+    }
+
     pub fn get_position(&self) -> Point3<f32> {
         self.position
     }
 
+    /// Moves the entity and its rigid body, a position equal to the current one is not counted
+    /// as a change
     pub fn set_position<P: Into<Point3<f32>>>(&mut self, scene: &mut Scene, position: P) {
         let position = position.into();
+        if position == self.position {
+            return;
+        }
         self.position = position;
+        self.transform_tick = ChangeTracker::get_current_tick();
         if let Some(rigid_body) = self.get_component_mut::<RigidBody>() {
             rigid_body.set_position(scene, position);
         }
     }
 
+    pub fn get_rotation(&self) -> Quaternion<f32> {
+        self.rotation
+    }
+
     pub fn set_rotation(&mut self, scene: &mut Scene, rotation: Quaternion<f32>) {
+        if rotation == self.rotation {
+            return;
+        }
         self.rotation = rotation;
+        self.transform_tick = ChangeTracker::get_current_tick();
         if let Some(rigid_body) = self.get_component_mut::<RigidBody>() {
             rigid_body.set_rotation(scene, rotation);
         }
     }
 
+    /// Whether the position or rotation changed after `tick`, see `ChangeTracker`
+    pub fn is_transform_changed_since(&self, tick: u64) -> bool {
+        self.transform_tick > tick
+    }
+
+    /// Whether the component of type `T` of this entity was added or mutably accessed after
+    /// `tick`. Components changing themselves in `Component::update` are not tracked
+    pub fn is_component_changed_since<T>(&self, tick: u64) -> bool
+    where
+        T: Component,
+    {
+        self.components
+            .iter()
+            .zip(&self.component_ticks)
+            .any(|(component, component_tick)| {
+                component.as_any().is::<T>() && *component_tick > tick
+            })
+    }
+
+    /// Whether the transform or any component of this entity changed after `tick`
+    pub fn is_changed_since(&self, tick: u64) -> bool {
+        self.is_transform_changed_since(tick)
+            || self
+                .component_ticks
+                .iter()
+                .any(|component_tick| *component_tick > tick)
+    }
+
     pub fn child_count(&self) -> usize {
         self.children.len()
     }
 
+    pub fn component_count(&self) -> usize {
+        self.components.len()
+    }
+
     pub fn get_name(&self) -> String {
         self.name.read()
     }
@@ -176,4 +454,22 @@ impl Entity {
     pub fn get_name_ref(&self) -> DataSource<String> {
         self.name.clone()
     }
+
+    pub fn add_tag(&mut self, tag: &str) {
+        if !self.has_tag(tag) {
+            self.tags.push(tag.to_string());
+        }
+    }
+
+    pub fn remove_tag(&mut self, tag: &str) {
+        self.tags.retain(|existing| existing != tag);
+    }
+
+    pub fn has_tag(&self, tag: &str) -> bool {
+        self.tags.iter().any(|existing| existing == tag)
+    }
+
+    pub fn get_tags(&self) -> &Vec<String> {
+        &self.tags
+    }
 }
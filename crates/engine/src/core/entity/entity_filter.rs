@@ -0,0 +1,71 @@
+use std::ops::Not;
+
+use super::{component::Component, Entity, EntityFilter};
+
+impl<'a> EntityFilter<'a> {
+    pub fn new<F>(predicate: F) -> Self
+    where
+        F: Fn(&Entity) -> bool + 'a,
+    {
+        Self {
+            predicate: Box::new(predicate),
+        }
+    }
+
+    /// Matches every entity
+    pub fn all() -> Self {
+        Self::new(|_| true)
+    }
+
+    /// Entities that have a component of type `T` themselves
+    pub fn has_component<T>() -> Self
+    where
+        T: Component,
+    {
+        Self::new(|entity| entity.has_component::<T>())
+    }
+
+    /// Entities whose position or rotation changed after `tick`, see `ChangeTracker`
+    pub fn transform_changed_since(tick: u64) -> Self {
+        Self::new(move |entity| entity.is_transform_changed_since(tick))
+    }
+
+    /// Entities whose component of type `T` changed after `tick`
+    pub fn component_changed_since<T>(tick: u64) -> Self
+    where
+        T: Component,
+    {
+        Self::new(move |entity| entity.is_component_changed_since::<T>(tick))
+    }
+
+    pub fn has_tag(tag: &str) -> Self {
+        let tag = tag.to_string();
+        Self::new(move |entity| entity.has_tag(&tag))
+    }
+
+    /// Entities whose name contains `text` ignoring case, e.g. for search boxes
+    pub fn name_contains(text: &str) -> Self {
+        let text = text.to_lowercase();
+        Self::new(move |entity| entity.get_name().to_lowercase().contains(&text))
+    }
+
+    pub fn and(self, other: EntityFilter<'a>) -> Self {
+        Self::new(move |entity| self.matches(entity) && other.matches(entity))
+    }
+
+    pub fn or(self, other: EntityFilter<'a>) -> Self {
+        Self::new(move |entity| self.matches(entity) || other.matches(entity))
+    }
+
+    pub fn matches(&self, entity: &Entity) -> bool {
+        (self.predicate)(entity)
+    }
+}
+
+impl<'a> Not for EntityFilter<'a> {
+    type Output = EntityFilter<'a>;
+
+    fn not(self) -> Self::Output {
+        Self::new(move |entity| !self.matches(entity))
+    }
+}
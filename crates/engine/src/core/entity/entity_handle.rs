@@ -1,15 +1,72 @@
-use rand::Rng;
+use std::{fmt, marker::PhantomData};
 
-use super::EntityHandle;
+use crate::core::{determinism::Determinism, scene::Scene};
+
+use super::{component::Component, ComponentHandle, Entity, EntityHandle};
 
 impl EntityHandle {
     pub fn new() -> Self {
-        EntityHandle(rand::thread_rng().gen())
+        EntityHandle(Determinism::next_id())
     }
 
     pub fn from(id: u64) -> Self {
         EntityHandle(id)
     }
+
+    /// The entity in `scene`, None if it was never added or has been removed
+    pub fn get_entity<'a>(&self, scene: &'a Scene) -> Option<&'a Entity> {
+        scene.get_entity(self)
+    }
+
+    pub fn get_entity_mut<'a>(&self, scene: &'a mut Scene) -> Option<&'a mut Entity> {
+        scene.get_entity_mut(self)
+    }
+}
+
+impl<T: Component> ComponentHandle<T> {
+    pub fn new(entity: EntityHandle) -> Self {
+        Self {
+            entity,
+            component: PhantomData,
+        }
+    }
+
+    pub fn get_entity_handle(&self) -> EntityHandle {
+        self.entity
+    }
+
+    /// The component in `scene`, None if its entity is not in the scene
+    pub fn get<'a>(&self, scene: &'a Scene) -> Option<&'a T> {
+        scene.get_entity(&self.entity)?.get_own_component::<T>()
+    }
+
+    pub fn get_mut<'a>(&self, scene: &'a mut Scene) -> Option<&'a mut T> {
+        scene.get_entity_mut(&self.entity)?.get_component_mut::<T>()
+    }
+}
+
+// derived impls would require `T` itself to be `Clone`, `Copy` and `Debug`
+impl<T: Component> Clone for ComponentHandle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: Component> Copy for ComponentHandle<T> {}
+
+impl<T: Component> PartialEq for ComponentHandle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.entity == other.entity
+    }
+}
+
+impl<T: Component> fmt::Debug for ComponentHandle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ComponentHandle")
+            .field(&self.entity)
+            .field(&std::any::type_name::<T>())
+            .finish()
+    }
 }
 
 impl From<EntityHandle> for u64 {
@@ -1,20 +1,53 @@
+use std::{marker::PhantomData, sync::atomic::AtomicU64};
+
 use cgmath::{Point3, Quaternion};
 use component::Component;
 
 use super::utils::DataSource;
 
+mod change_tracker;
 pub mod component;
 mod entity;
+mod entity_filter;
 mod entity_handle;
 
+/// Tick entity mutations are stamped with, advanced by every `ChangeTracker::advance`
+static CHANGE_TICK: AtomicU64 = AtomicU64::new(1);
+
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub struct EntityHandle(u64);
 
+/// Component of type `T` on the entity `entity`, returned by `Entity::add_component`. Resolving it
+/// through the scene fails gracefully once the entity or the component is gone
+pub struct ComponentHandle<T: Component> {
+    entity: EntityHandle,
+    component: PhantomData<fn() -> T>,
+}
+
 pub struct Entity {
     pub id: EntityHandle,
     name: DataSource<String>,
+    /// Labels to find the entity by, see `EntityFilter::has_tag`
+    tags: Vec<String>,
     children: Vec<Entity>,
     components: Vec<Box<dyn Component>>,
+    /// Change tick of every component's last mutable access, parallel to `components`
+    component_ticks: Vec<u64>,
     position: Point3<f32>,
     rotation: Quaternion<f32>,
+    /// Change tick of the last change of the position or rotation
+    transform_tick: u64,
+}
+
+/// Remembers up to which change tick a system like a spatial index, the physics sync or
+/// replication has processed the scene, so it only visits the entities changed in between
+#[derive(Clone, Debug, Default)]
+pub struct ChangeTracker {
+    last_seen: u64,
+}
+
+/// Condition on entities for `Scene::query`, built from predicates and combined with `and`, `or`
+/// and `!`
+pub struct EntityFilter<'a> {
+    predicate: Box<dyn Fn(&Entity) -> bool + 'a>,
 }
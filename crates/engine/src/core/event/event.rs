@@ -0,0 +1,18 @@
+use glfw::WindowEvent;
+
+use super::EventCategory;
+
+impl From<&WindowEvent> for EventCategory {
+    fn from(event: &WindowEvent) -> Self {
+        match event {
+            WindowEvent::Key(..) | WindowEvent::Char(_) | WindowEvent::CharModifiers(..) => {
+                EventCategory::Keyboard
+            }
+            WindowEvent::MouseButton(..)
+            | WindowEvent::CursorPos(..)
+            | WindowEvent::CursorEnter(_)
+            | WindowEvent::Scroll(..) => EventCategory::Mouse,
+            _ => EventCategory::Window,
+        }
+    }
+}
@@ -0,0 +1,56 @@
+use std::{any::Any, sync::mpsc};
+
+use super::{Channel, EventBus, EventChannel};
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues `event` for the subscribers of its type until the next `flush`
+    pub fn emit<T: Clone + 'static>(&mut self, event: T) {
+        self.get_channel::<T>().pending.push(event);
+    }
+
+    /// Returns a receiver for the events of type `T` flushed from now on, dropping it
+    /// unsubscribes it
+    pub fn subscribe<T: Clone + 'static>(&mut self) -> mpsc::Receiver<T> {
+        let (tx, rx) = mpsc::channel();
+        self.get_channel::<T>().subscribers.push(tx);
+        rx
+    }
+
+    /// Delivers the queued events of every type to their subscribers
+    pub fn flush(&mut self) {
+        for channel in self.channels.values_mut() {
+            channel.flush();
+        }
+    }
+
+    fn get_channel<T: Clone + 'static>(&mut self) -> &mut Channel<T> {
+        self.channels
+            .entry(std::any::TypeId::of::<T>())
+            .or_insert_with(|| {
+                Box::new(Channel::<T> {
+                    pending: Vec::new(),
+                    subscribers: Vec::new(),
+                })
+            })
+            .as_any_mut()
+            .downcast_mut::<Channel<T>>()
+            .unwrap()
+    }
+}
+
+impl<T: Clone + 'static> EventChannel for Channel<T> {
+    fn flush(&mut self) {
+        for event in self.pending.drain(..) {
+            self.subscribers
+                .retain(|subscriber| subscriber.send(event.clone()).is_ok());
+        }
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
@@ -0,0 +1,40 @@
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    sync::mpsc,
+};
+
+mod event;
+mod event_bus;
+
+/// Coarse grouping of window and gamepad events, handlers declare a priority per category
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EventCategory {
+    Keyboard,
+    Mouse,
+    Window,
+    /// `GamepadEvent`s, only layers receive them
+    Gamepad,
+}
+
+/// Priority handlers get when they don't declare one, higher priorities receive events first
+pub const DEFAULT_EVENT_PRIORITY: i32 = 0;
+
+/// Typed events components, layers and UI callbacks exchange through the scene instead of
+/// looking each other up. Emitted events are queued and delivered to every subscriber when
+/// `Scene::update` finishes, so handlers never run in the middle of another component's update
+#[derive(Default)]
+pub struct EventBus {
+    channels: HashMap<TypeId, Box<dyn EventChannel>>,
+}
+
+/// Queued events of one type with the subscribers to deliver them to
+struct Channel<T> {
+    pending: Vec<T>,
+    subscribers: Vec<mpsc::Sender<T>>,
+}
+
+trait EventChannel {
+    fn flush(&mut self);
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
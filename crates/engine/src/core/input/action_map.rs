@@ -0,0 +1,142 @@
+use std::collections::BTreeMap;
+
+use glfw::{Action, Key, MouseButton, WindowEvent};
+
+use super::{ActionMap, Binding};
+
+impl ActionMap {
+    /// A map without any bindings, `ActionMap::default` has the engine's controls
+    pub fn new() -> Self {
+        Self {
+            bindings: BTreeMap::new(),
+        }
+    }
+
+    /// Loads the bindings saved with `save` from the RON file at `path`, actions missing in the
+    /// file keep their default bindings
+    pub fn load(path: &str) -> Result<ActionMap, Box<dyn std::error::Error>> {
+        let source = std::fs::read_to_string(path)?;
+        let loaded: ActionMap = ron::from_str(&source)?;
+        let mut actions = ActionMap::default();
+        actions.bindings.extend(loaded.bindings);
+        Ok(actions)
+    }
+
+    /// Writes the bindings to `path` as RON
+    pub fn save(&self, path: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let source = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        std::fs::write(path, source)?;
+        Ok(())
+    }
+
+    /// Adds `binding` to the bindings of `action`
+    pub fn bind(&mut self, action: &str, binding: Binding) {
+        let bindings = self.bindings.entry(action.to_string()).or_default();
+        if !bindings.contains(&binding) {
+            bindings.push(binding);
+        }
+    }
+
+    pub fn set_bindings(&mut self, action: &str, bindings: Vec<Binding>) {
+        self.bindings.insert(action.to_string(), bindings);
+    }
+
+    pub fn get_bindings(&self, action: &str) -> &[Binding] {
+        self.bindings.get(action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Names of all actions in alphabetical order
+    pub fn get_actions(&self) -> Vec<&str> {
+        self.bindings.keys().map(String::as_str).collect()
+    }
+
+    pub fn is_bound(&self, action: &str, binding: Binding) -> bool {
+        self.get_bindings(action).contains(&binding)
+    }
+
+    /// Whether `event` presses, repeats or releases a binding of `action`
+    pub fn get_action(&self, action: &str, event: &WindowEvent) -> Option<Action> {
+        let (binding, state) = Binding::from_event(event)?;
+        self.is_bound(action, binding).then_some(state)
+    }
+}
+
+impl Default for ActionMap {
+    fn default() -> Self {
+        let mut actions = ActionMap::new();
+        let keys: [(&str, &[Key]); 36] = [
+            // free moving cameras
+            ("move_forward", &[Key::I, Key::Up]),
+            ("move_backward", &[Key::K, Key::Down]),
+            ("move_left", &[Key::J, Key::Left]),
+            ("move_right", &[Key::L, Key::Right]),
+            ("move_up", &[Key::Space]),
+            ("move_down", &[Key::LeftShift]),
+            ("toggle_cursor", &[Key::Escape]),
+            ("fly_to_cursor", &[Key::F]),
+            ("cinematic_play", &[Key::Space]),
+            ("cinematic_restart", &[Key::R]),
+            // player
+            ("walk_forward", &[Key::W]),
+            ("walk_backward", &[Key::S]),
+            ("walk_left", &[Key::A]),
+            ("walk_right", &[Key::D]),
+            ("run", &[Key::LeftShift]),
+            // debug views
+            ("toggle_wireframe", &[Key::F1]),
+            ("toggle_vsync", &[Key::F2]),
+            ("toggle_debug", &[Key::F3]),
+            ("toggle_rays", &[Key::F4]),
+            ("toggle_xray", &[Key::F5]),
+            ("toggle_linear_skinning", &[Key::F6]),
+            ("toggle_culling_debug", &[Key::F7]),
+            ("toggle_profiler", &[Key::F8]),
+            ("toggle_chunk_bounds", &[Key::F9]),
+            ("toggle_normals", &[Key::F10]),
+            ("toggle_lod_coloring", &[Key::F11]),
+            ("toggle_seams", &[Key::F12]),
+            // time controls
+            ("toggle_pause", &[Key::Pause]),
+            ("step_frame", &[Key::Period]),
+            ("slow_down", &[Key::LeftBracket]),
+            ("speed_up", &[Key::RightBracket]),
+            ("dump_scene", &[Key::Insert]),
+            ("toggle_about", &[Key::ScrollLock]),
+            // selection
+            ("toggle_gizmo_mode", &[Key::T]),
+            // photo mode
+            ("toggle_photo_mode", &[Key::F6]),
+            ("capture_photo", &[Key::F7]),
+            ("toggle_photo_ui", &[Key::H]),
+        ];
+        for (action, keys) in keys {
+            for key in keys {
+                actions.bind(action, Binding::Key(*key));
+            }
+        }
+        actions.bind("dig", Binding::Mouse(MouseButton::Button1));
+        actions.bind("place", Binding::Mouse(MouseButton::Button2));
+        actions.bind("focus_at_cursor", Binding::Mouse(MouseButton::Button1));
+        actions.bind("select", Binding::Mouse(MouseButton::Button1));
+        actions
+    }
+}
+
+impl Binding {
+    /// The binding `event` presses, repeats or releases with its state
+    pub fn from_event(event: &WindowEvent) -> Option<(Binding, Action)> {
+        match event {
+            WindowEvent::Key(key, _, action, _) => Some((Binding::Key(*key), *action)),
+            WindowEvent::MouseButton(button, action, _) => Some((Binding::Mouse(*button), *action)),
+            _ => None,
+        }
+    }
+
+    /// Name shown in the bindings editor, e.g. "LeftShift" or "Mouse 1"
+    pub fn get_name(&self) -> String {
+        match self {
+            Binding::Key(key) => format!("{:?}", key),
+            Binding::Mouse(button) => format!("Mouse {}", *button as i32 + 1),
+        }
+    }
+}
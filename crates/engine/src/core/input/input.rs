@@ -0,0 +1,172 @@
+use std::sync::Mutex;
+
+use glfw::{Action, GamepadAxis, GamepadButton, Glfw, JoystickId, WindowEvent};
+use lazy_static::lazy_static;
+
+use super::{ActionMap, Binding, Gamepad, GamepadEvent, Input, GAMEPAD_DEADZONE};
+
+/// Number of joysticks GLFW tracks
+const JOYSTICK_COUNT: i32 = 16;
+
+lazy_static! {
+    static ref INPUT: Mutex<Input> = Mutex::new(Input::new());
+}
+
+impl Input {
+    fn new() -> Self {
+        Self {
+            gamepads: Vec::new(),
+            actions: ActionMap::default(),
+        }
+    }
+
+    /// Copy of the bindings currently in use
+    pub fn get_action_map() -> ActionMap {
+        INPUT.lock().unwrap().actions.clone()
+    }
+
+    pub fn set_action_map(actions: ActionMap) {
+        INPUT.lock().unwrap().actions = actions;
+    }
+
+    /// Whether `event` presses, repeats or releases a binding of `action`
+    pub fn get_action(action: &str, event: &WindowEvent) -> Option<Action> {
+        INPUT.lock().unwrap().actions.get_action(action, event)
+    }
+
+    /// Whether `event` presses a binding of `action`
+    pub fn is_action_pressed(action: &str, event: &WindowEvent) -> bool {
+        Input::get_action(action, event) == Some(Action::Press)
+    }
+
+    /// The first of `actions` that `event` presses a binding of
+    pub fn get_pressed_action<'a>(actions: &[&'a str], event: &WindowEvent) -> Option<&'a str> {
+        let input = INPUT.lock().unwrap();
+        actions
+            .iter()
+            .find(|action| input.actions.get_action(action, event) == Some(Action::Press))
+            .copied()
+    }
+
+    pub fn is_bound(action: &str, binding: Binding) -> bool {
+        INPUT.lock().unwrap().actions.is_bound(action, binding)
+    }
+
+    /// Reads the state of all gamepads, returns the connections, disconnections and button
+    /// changes since the last poll
+    pub fn poll(glfw: &Glfw) -> Vec<GamepadEvent> {
+        let mut input = INPUT.lock().unwrap();
+        let mut events = Vec::new();
+        let mut gamepads = Vec::new();
+        for id in (0..JOYSTICK_COUNT).filter_map(JoystickId::from_i32) {
+            let joystick = glfw.get_joystick(id);
+            let Some(state) = joystick
+                .get_gamepad_state()
+                .filter(|_| joystick.is_gamepad())
+            else {
+                continue;
+            };
+            let previous = input.gamepads.iter().find(|gamepad| gamepad.id == id);
+            let mut gamepad = match previous {
+                Some(previous) => previous.clone(),
+                None => {
+                    let name = joystick
+                        .get_gamepad_name()
+                        .unwrap_or_else(|| format!("Gamepad {}", id as i32 + 1));
+                    log::info!("Gamepad connected: {}", name);
+                    events.push(GamepadEvent::Connected {
+                        id,
+                        name: name.clone(),
+                    });
+                    Gamepad {
+                        id,
+                        name,
+                        axes: [0.0; 6],
+                        buttons: [false; 15],
+                    }
+                }
+            };
+            for (i, axis) in gamepad.axes.iter_mut().enumerate() {
+                let Some(gamepad_axis) = GamepadAxis::from_i32(i as i32) else {
+                    continue;
+                };
+                *axis = Input::normalize_axis(gamepad_axis, state.get_axis(gamepad_axis));
+            }
+            for (i, pressed) in gamepad.buttons.iter_mut().enumerate() {
+                let Some(button) = GamepadButton::from_i32(i as i32) else {
+                    continue;
+                };
+                let is_pressed = state.get_button_state(button) == Action::Press;
+                if is_pressed != *pressed {
+                    events.push(if is_pressed {
+                        GamepadEvent::ButtonPressed { id, button }
+                    } else {
+                        GamepadEvent::ButtonReleased { id, button }
+                    });
+                }
+                *pressed = is_pressed;
+            }
+            gamepads.push(gamepad);
+        }
+        for gamepad in &input.gamepads {
+            if !gamepads.iter().any(|connected| connected.id == gamepad.id) {
+                log::info!("Gamepad disconnected: {}", gamepad.name);
+                events.push(GamepadEvent::Disconnected { id: gamepad.id });
+            }
+        }
+        input.gamepads = gamepads;
+        events
+    }
+
+    /// Sticks from -1 to 1 with the deadzone cut out, triggers from 0 when released to 1
+    fn normalize_axis(axis: GamepadAxis, value: f32) -> f32 {
+        match axis {
+            GamepadAxis::AxisLeftTrigger | GamepadAxis::AxisRightTrigger => {
+                ((value + 1.0) / 2.0).clamp(0.0, 1.0)
+            }
+            _ if value.abs() < GAMEPAD_DEADZONE => 0.0,
+            _ => value.signum() * ((value.abs() - GAMEPAD_DEADZONE) / (1.0 - GAMEPAD_DEADZONE)),
+        }
+    }
+
+    /// Deflection of `axis` on the first connected gamepad, 0 without one
+    pub fn get_axis(axis: GamepadAxis) -> f32 {
+        let input = INPUT.lock().unwrap();
+        input
+            .gamepads
+            .first()
+            .map_or(0.0, |gamepad| gamepad.get_axis(axis))
+    }
+
+    /// Whether `button` is held on the first connected gamepad
+    pub fn is_button_down(button: GamepadButton) -> bool {
+        let input = INPUT.lock().unwrap();
+        input
+            .gamepads
+            .first()
+            .is_some_and(|gamepad| gamepad.is_button_down(button))
+    }
+
+    /// The connected gamepads in the order GLFW numbers them
+    pub fn get_gamepads() -> Vec<Gamepad> {
+        INPUT.lock().unwrap().gamepads.clone()
+    }
+}
+
+impl Gamepad {
+    pub fn get_id(&self) -> JoystickId {
+        self.id
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_axis(&self, axis: GamepadAxis) -> f32 {
+        self.axes[axis as usize]
+    }
+
+    pub fn is_button_down(&self, button: GamepadButton) -> bool {
+        self.buttons[button as usize]
+    }
+}
@@ -0,0 +1,64 @@
+use std::collections::BTreeMap;
+
+use glfw::{GamepadButton, JoystickId, Key, MouseButton};
+use serde::{Deserialize, Serialize};
+
+mod action_map;
+mod input;
+
+/// Stick deflections below this read as 0, as sticks rarely rest exactly centered
+pub const GAMEPAD_DEADZONE: f32 = 0.15;
+
+/// Gamepads polled from GLFW once per frame by the application. Components read the first
+/// connected gamepad with `Input::get_axis` and `Input::is_button_down`, layers receive
+/// `GamepadEvent`s through `Layer::on_gamepad_event`
+pub struct Input {
+    gamepads: Vec<Gamepad>,
+    actions: ActionMap,
+}
+
+/// State of a connected gamepad in the standard layout GLFW maps controllers to
+#[derive(Clone, Debug)]
+pub struct Gamepad {
+    id: JoystickId,
+    name: String,
+    /// Indexed by `GamepadAxis`, sticks from -1 to 1 and triggers from 0 to 1
+    axes: [f32; 6],
+    /// Indexed by `GamepadButton`
+    buttons: [bool; 15],
+}
+
+/// Key or mouse button an action is bound to
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Binding {
+    Key(Key),
+    Mouse(MouseButton),
+}
+
+/// Named actions like "move_forward" or "dig" with the keys and mouse buttons that trigger
+/// them, loadable from a RON file. Components ask `Input::get_action` instead of matching raw
+/// keys so the controls can be rebound
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ActionMap {
+    bindings: BTreeMap<String, Vec<Binding>>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum GamepadEvent {
+    Connected {
+        id: JoystickId,
+        name: String,
+    },
+    Disconnected {
+        id: JoystickId,
+    },
+    ButtonPressed {
+        id: JoystickId,
+        button: GamepadButton,
+    },
+    ButtonReleased {
+        id: JoystickId,
+        button: GamepadButton,
+    },
+}
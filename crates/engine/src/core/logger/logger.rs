@@ -0,0 +1,199 @@
+use std::{
+    collections::VecDeque,
+    env,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use lazy_static::lazy_static;
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+use super::{LogFilter, LogRecord, Logger, TimingSpan, RECORD_CAPACITY};
+
+lazy_static! {
+    static ref LOGGER: Logger = Logger::new();
+}
+
+impl Logger {
+    fn new() -> Self {
+        Self {
+            filter: Mutex::new(LogFilter::parse(&env::var("RUST_LOG").unwrap_or_default())),
+            records: Mutex::new(VecDeque::with_capacity(RECORD_CAPACITY)),
+            start: Instant::now(),
+        }
+    }
+
+    /// Installs the logger, later calls do nothing
+    pub fn init() {
+        if log::set_logger(&*LOGGER).is_ok() {
+            LOGGER.update_max_level();
+        }
+    }
+
+    /// Level of every target without its own level
+    pub fn set_level(level: LevelFilter) {
+        LOGGER.filter.lock().unwrap().level = level;
+        LOGGER.update_max_level();
+    }
+
+    /// Level of `module` and everything below it, e.g. `ferrite::terrain` or `gl`
+    pub fn set_module_level(module: &str, level: LevelFilter) {
+        LOGGER
+            .filter
+            .lock()
+            .unwrap()
+            .set_module_level(module, level);
+        LOGGER.update_max_level();
+    }
+
+    /// The latest `count` records, oldest first
+    pub fn get_records(count: usize) -> Vec<LogRecord> {
+        let records = LOGGER.records.lock().unwrap();
+        records
+            .iter()
+            .skip(records.len().saturating_sub(count))
+            .cloned()
+            .collect()
+    }
+
+    pub fn clear_records() {
+        LOGGER.records.lock().unwrap().clear();
+    }
+
+    /// The `log` macros skip everything above the maximum level before asking the logger, so it
+    /// has to cover the most verbose module
+    fn update_max_level(&self) {
+        let filter = self.filter.lock().unwrap();
+        let max_level = filter
+            .modules
+            .iter()
+            .map(|(_, level)| *level)
+            .fold(filter.level, Ord::max);
+        log::set_max_level(max_level);
+    }
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.filter.lock().unwrap().get_level(metadata.target())
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        let record = LogRecord {
+            level: record.level(),
+            target: record.target().to_string(),
+            message: record.args().to_string(),
+            time: self.start.elapsed(),
+        };
+        eprintln!("{}", record);
+
+        let mut records = self.records.lock().unwrap();
+        if records.len() == RECORD_CAPACITY {
+            records.pop_front();
+        }
+        records.push_back(record);
+    }
+
+    fn flush(&self) {}
+}
+
+impl LogFilter {
+    /// Parses a comma separated list of levels, entries without a module set the default level.
+    /// Invalid entries are ignored
+    fn parse(spec: &str) -> Self {
+        let mut filter = Self {
+            level: LevelFilter::Info,
+            modules: Vec::new(),
+        };
+        for entry in spec
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+        {
+            match entry.split_once('=') {
+                Some((module, level)) => {
+                    if let Ok(level) = level.trim().parse() {
+                        filter.set_module_level(module.trim(), level);
+                    }
+                }
+                None => {
+                    if let Ok(level) = entry.parse() {
+                        filter.level = level;
+                    } else {
+                        // a module on its own enables everything it logs, like env_logger does
+                        filter.set_module_level(entry, LevelFilter::Trace);
+                    }
+                }
+            }
+        }
+        filter
+    }
+
+    fn set_module_level(&mut self, module: &str, level: LevelFilter) {
+        match self.modules.iter_mut().find(|(name, _)| name == module) {
+            Some((_, existing)) => *existing = level,
+            None => self.modules.push((module.to_string(), level)),
+        }
+    }
+
+    fn get_level(&self, target: &str) -> LevelFilter {
+        self.modules
+            .iter()
+            .filter(|(module, _)| {
+                target == module
+                    || target
+                        .strip_prefix(module.as_str())
+                        .is_some_and(|rest| rest.starts_with("::"))
+            })
+            .max_by_key(|(module, _)| module.len())
+            .map_or(self.level, |(_, level)| *level)
+    }
+}
+
+impl std::fmt::Display for LogRecord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "[{:>9.3} {:<5} {}] {}",
+            self.time.as_secs_f64(),
+            self.level,
+            self.target,
+            self.message
+        )
+    }
+}
+
+impl TimingSpan {
+    pub fn new(level: Level, target: &'static str, name: &str) -> Self {
+        Self {
+            level,
+            target,
+            started: log::log_enabled!(target: target, level)
+                .then(|| (name.to_string(), Instant::now())),
+        }
+    }
+
+    /// Time since the span started, zero while it is disabled
+    pub fn elapsed(&self) -> Duration {
+        self.started
+            .as_ref()
+            .map_or(Duration::ZERO, |(_, start)| start.elapsed())
+    }
+}
+
+impl Drop for TimingSpan {
+    fn drop(&mut self) {
+        if let Some((name, start)) = &self.started {
+            log::log!(
+                target: self.target,
+                self.level,
+                "{} took {:.3}ms",
+                name,
+                start.elapsed().as_secs_f64() * 1000.0
+            );
+        }
+    }
+}
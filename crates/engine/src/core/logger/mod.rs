@@ -0,0 +1,46 @@
+use std::{
+    collections::VecDeque,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use log::{Level, LevelFilter};
+
+mod logger;
+
+/// Number of records the ring buffer keeps for the debug UI
+const RECORD_CAPACITY: usize = 256;
+
+/// Logger behind the `log` macros. Records are written to stderr and the latest ones are kept in
+/// a ring buffer the debug UI reads with `Logger::get_records`.
+///
+/// Levels are set per module (or any other log target), the longest matching prefix of a record's
+/// target decides. They start from `RUST_LOG`, e.g. `info,ferrite::terrain=debug,gl=warn`
+pub struct Logger {
+    filter: Mutex<LogFilter>,
+    records: Mutex<VecDeque<LogRecord>>,
+    start: Instant,
+}
+
+struct LogFilter {
+    level: LevelFilter,
+    modules: Vec<(String, LevelFilter)>,
+}
+
+#[derive(Clone, Debug)]
+pub struct LogRecord {
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+    /// Time since the logger was created
+    pub time: Duration,
+}
+
+/// Logs the time between its creation and its drop, e.g. to measure chunk generation or a render
+/// pass. Does nothing if `level` is disabled for `target`
+pub struct TimingSpan {
+    level: Level,
+    target: &'static str,
+    /// Name and start of the span, `None` while disabled
+    started: Option<(String, Instant)>,
+}
@@ -1,11 +1,26 @@
 pub mod application;
+pub mod asset;
+pub mod build_info;
 pub mod camera;
+pub mod config;
+pub mod determinism;
 pub mod entity;
+pub mod event;
+pub mod input;
+pub mod logger;
 pub mod model;
 pub mod mouse_picker;
+pub mod net;
+pub mod photo_mode;
 pub mod physics;
+pub mod profiler;
+pub mod quality;
 pub mod renderer;
+pub mod save_worker;
 pub mod scene;
+pub mod scene_file;
+pub mod selection;
+pub mod time_control;
 pub mod utils;
 pub mod view_frustum;
 pub mod window;
@@ -1,8 +1,9 @@
 use std::collections::HashMap;
 
+use cgmath::InnerSpace;
 use russimp::scene::Scene;
 
-use super::{Animation, Channel, Pose};
+use super::{gltf_import::is_gltf, Animation, BoneMap, Channel, Model, Pose, Skeleton};
 
 impl Animation {
     pub fn new(animation: &russimp::animation::Animation) -> Animation {
@@ -19,7 +20,14 @@ impl Animation {
         }
     }
 
+    /// Loads the first animation of the file at `path` inside `assets/animations`, glTF files
+    /// are read with the gltf crate
     pub fn from_file(name: &str, path: &str) -> Result<Animation, Box<dyn std::error::Error>> {
+        if is_gltf(path) {
+            let (mut animation, _) = Animation::from_gltf(&format!("assets/animations/{path}"))?;
+            animation.set_name(name);
+            return Ok(animation);
+        }
         let scene = Scene::from_file(format!("assets/animations/{path}").as_str(), vec![])?;
         if scene.animations.len() == 0 {
             return Err("No animations found".into());
@@ -29,10 +37,71 @@ impl Animation {
         Ok(animation)
     }
 
+    /// Loads the first animation of an animation file and retargets it from the skeleton of the
+    /// file onto the skeleton of `model`
+    pub fn from_file_retargeted(
+        name: &str,
+        path: &str,
+        model: &Model,
+        bone_map: &BoneMap,
+    ) -> Result<Animation, Box<dyn std::error::Error>> {
+        let (animation, skeleton) = if is_gltf(path) {
+            Animation::from_gltf(&format!("assets/animations/{path}"))?
+        } else {
+            let scene = Scene::from_file(format!("assets/animations/{path}").as_str(), vec![])?;
+            if scene.animations.is_empty() {
+                return Err("No animations found".into());
+            }
+            (Animation::new(&scene.animations[0]), Skeleton::new(&scene))
+        };
+        let mut animation = animation.retarget(&skeleton, &model.get_skeleton(), bone_map);
+        animation.set_name(name);
+        Ok(animation)
+    }
+
+    /// Creates the animation for the `target` skeleton from this animation authored on `source`.
+    /// Channels of bones the `bone_map` maps to no bone of the target are dropped. The topmost
+    /// mapped bone carries the motion, its translation is scaled by the ratio of its height in
+    /// the two skeletons
+    pub fn retarget(&self, source: &Skeleton, target: &Skeleton, bone_map: &BoneMap) -> Animation {
+        let mapped: HashMap<&str, &str> = self
+            .channels
+            .keys()
+            .filter_map(|bone| Some((bone.as_str(), bone_map.get_target(bone, target)?)))
+            .collect();
+        let mut channels = HashMap::<String, Channel>::new();
+        for (source_bone, target_bone) in &mapped {
+            let is_root = !target.has_ancestor(target_bone, |ancestor| {
+                mapped.values().any(|bone| *bone == ancestor)
+            });
+            let root_scale = is_root.then(|| {
+                let source_height = source.get_global_bind(source_bone).w.truncate().magnitude();
+                let target_height = target.get_global_bind(target_bone).w.truncate().magnitude();
+                if source_height > f32::EPSILON {
+                    target_height / source_height
+                } else {
+                    1.0
+                }
+            });
+            let channel = self.channels[*source_bone].retarget(
+                (source, source_bone),
+                (target, target_bone),
+                root_scale,
+            );
+            channels.insert(target_bone.to_string(), channel);
+        }
+        Animation {
+            name: self.name.clone(),
+            duration: self.duration,
+            ticks_per_second: self.ticks_per_second,
+            channels,
+        }
+    }
+
     pub fn sample(&self, time: f32) -> Pose {
         let mut pose = Pose::new();
         if time > self.duration {
-            println!("Cycle completed");
+            log::trace!("{} completed a cycle", self.name);
             pose.cycle_completed = true;
         }
         let sample_time = time % self.duration;
@@ -1,8 +1,8 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use crate::core::model::{Animation, Pose};
 
-use super::{AnimationGraph, State, Transition};
+use super::{AnimationGraph, BlendSpace, State, Transition};
 
 impl AnimationGraph {
     pub fn new() -> Self {
@@ -25,7 +25,7 @@ impl AnimationGraph {
         }
         if let Some(previous_state) = &self.previous_state {
             if let Some(state) = self.states.get_mut(previous_state) {
-                state.update(delta_time);
+                state.update(delta_time, &self.inputs);
             }
         }
         let mut transition = false;
@@ -42,7 +42,7 @@ impl AnimationGraph {
                     }
                 }
             }
-            state.update(delta_time);
+            state.update(delta_time, &self.inputs);
         }
         if transition {
             if let Some(state) = self.states.get_mut(&self.current_state) {
@@ -99,15 +99,19 @@ impl State {
     pub fn new(name: &str) -> Self {
         State {
             name: name.to_string(),
-            animations: HashMap::new(),
-            animation_times: HashMap::new(),
-            animation_cycled: HashMap::new(),
+            animations: BTreeMap::new(),
+            animation_times: BTreeMap::new(),
+            animation_cycled: BTreeMap::new(),
             sync_animations: false,
+            blend_space: None,
             transitions: Vec::new(),
         }
     }
 
-    pub fn update(&mut self, delta_time: f32) {
+    pub fn update(&mut self, delta_time: f32, inputs: &HashMap<String, f32>) {
+        if let Some(blend_space) = &mut self.blend_space {
+            blend_space.update(delta_time, inputs);
+        }
         for (name, animation) in &self.animations {
             let time = self.animation_times.entry(name.clone()).or_insert(0.0);
             let cycled = self.animation_cycled.entry(name.clone()).or_insert(false);
@@ -122,6 +126,9 @@ impl State {
     }
 
     pub fn get_pose(&self) -> Option<Pose> {
+        if let Some(blend_space) = &self.blend_space {
+            return blend_space.get_pose();
+        }
         let mut final_pose: Option<Pose> = None;
         let mut cycled = false;
         let mut progress = None;
@@ -157,6 +164,9 @@ impl State {
         for cycled in self.animation_cycled.values_mut() {
             *cycled = false;
         }
+        if let Some(blend_space) = &mut self.blend_space {
+            blend_space.reset();
+        }
     }
 
     pub fn add_animation(&mut self, animation: Animation) {
@@ -176,6 +186,11 @@ impl State {
         });
     }
 
+    /// Plays the blend space instead of the animations of the state
+    pub fn set_blend_space(&mut self, blend_space: BlendSpace) {
+        self.blend_space = Some(blend_space);
+    }
+
     pub fn sync_animations(&mut self, sync: bool) {
         self.sync_animations = sync;
     }
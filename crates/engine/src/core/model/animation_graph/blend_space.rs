@@ -0,0 +1,159 @@
+use std::collections::HashMap;
+
+use crate::core::model::{Animation, Pose};
+
+use super::{BlendSpace, BlendSpaceClip};
+
+impl BlendSpace {
+    /// Blend space along a single input, clips are placed at `(x, 0.0)`
+    pub fn new_1d(input: &str) -> Self {
+        BlendSpace {
+            x_input: input.to_string(),
+            y_input: None,
+            clips: Vec::new(),
+            grid_x: Vec::new(),
+            grid_y: Vec::new(),
+            weights: Vec::new(),
+            phase: 0.0,
+            cycled: false,
+        }
+    }
+
+    pub fn new_2d(x_input: &str, y_input: &str) -> Self {
+        BlendSpace {
+            y_input: Some(y_input.to_string()),
+            ..BlendSpace::new_1d(x_input)
+        }
+    }
+
+    /// Places `animation` at the input coordinates `position`. Grid cells missing a corner clip
+    /// make it up from the clips at the corners next to it
+    pub fn add_clip(&mut self, animation: Animation, position: (f32, f32)) {
+        let position = match self.y_input {
+            Some(_) => position,
+            None => (position.0, 0.0),
+        };
+        BlendSpace::insert_grid_line(&mut self.grid_x, position.0);
+        BlendSpace::insert_grid_line(&mut self.grid_y, position.1);
+        self.clips.push(BlendSpaceClip {
+            animation,
+            position,
+        });
+    }
+
+    pub fn update(&mut self, delta_time: f32, inputs: &HashMap<String, f32>) {
+        let x = inputs.get(&self.x_input).copied().unwrap_or(0.0);
+        let y = self
+            .y_input
+            .as_ref()
+            .and_then(|input| inputs.get(input))
+            .copied()
+            .unwrap_or(0.0);
+        self.weights = self.calculate_weights(x, y);
+
+        // the clips advance at the weighted average of their lengths
+        let duration: f32 = self
+            .weights
+            .iter()
+            .map(|(clip, weight)| {
+                let animation = &self.clips[*clip].animation;
+                if animation.ticks_per_second > 0.0 {
+                    animation.duration / animation.ticks_per_second * weight
+                } else {
+                    animation.duration * weight
+                }
+            })
+            .sum();
+        if duration > 0.0 {
+            self.phase += delta_time / duration;
+        }
+        self.cycled = self.phase >= 1.0;
+        self.phase = self.phase.fract();
+    }
+
+    pub fn get_pose(&self) -> Option<Pose> {
+        let mut blended: Option<Pose> = None;
+        let mut total_weight = 0.0;
+        for (clip, weight) in &self.weights {
+            let animation = &self.clips[*clip].animation;
+            let pose = animation.sample(self.phase * animation.duration);
+            total_weight += weight;
+            blended = Some(match blended {
+                Some(blended) => pose.interpolate(&blended, weight / total_weight),
+                None => pose,
+            });
+        }
+        blended.map(|mut pose| {
+            pose.cycle_completed = self.cycled;
+            pose
+        })
+    }
+
+    pub fn reset(&mut self) {
+        self.phase = 0.0;
+        self.cycled = false;
+    }
+
+    fn insert_grid_line(lines: &mut Vec<f32>, value: f32) {
+        if let Err(index) = lines.binary_search_by(|line| line.total_cmp(&value)) {
+            lines.insert(index, value);
+        }
+    }
+
+    /// Grid lines below and above `value` and how far it lies between them
+    fn locate(lines: &[f32], value: f32) -> (usize, usize, f32) {
+        if lines.len() < 2 {
+            return (0, 0, 0.0);
+        }
+        let upper = lines
+            .partition_point(|line| *line < value)
+            .clamp(1, lines.len() - 1);
+        let lower = upper - 1;
+        let factor = ((value - lines[lower]) / (lines[upper] - lines[lower])).clamp(0.0, 1.0);
+        (lower, upper, factor)
+    }
+
+    /// Bilinear weights of the clips at the corners of the grid cell containing `(x, y)`
+    fn calculate_weights(&self, x: f32, y: f32) -> Vec<(usize, f32)> {
+        if self.clips.is_empty() {
+            return Vec::new();
+        }
+        let (x0, x1, fx) = BlendSpace::locate(&self.grid_x, x);
+        let (y0, y1, fy) = BlendSpace::locate(&self.grid_y, y);
+        // the index of a corner flips its x with bit 0 and its y with bit 1
+        let corners = [
+            ((x0, y0), (1.0 - fx) * (1.0 - fy)),
+            ((x1, y0), fx * (1.0 - fy)),
+            ((x0, y1), (1.0 - fx) * fy),
+            ((x1, y1), fx * fy),
+        ];
+        let find = |(i, j): (usize, usize)| {
+            self.clips
+                .iter()
+                .position(|clip| clip.position == (self.grid_x[i], self.grid_y[j]))
+        };
+        let mut weights = Vec::<(usize, f32)>::new();
+        let mut add = |clip: usize, weight: f32| match weights.iter_mut().find(|(c, _)| *c == clip)
+        {
+            Some((_, total)) => *total += weight,
+            None => weights.push((clip, weight)),
+        };
+        for (corner, (position, weight)) in corners.iter().enumerate() {
+            if *weight <= 0.0 {
+                continue;
+            }
+            if let Some(clip) = find(*position) {
+                add(clip, *weight);
+                continue;
+            }
+            let neighbours: Vec<usize> = [corner ^ 1, corner ^ 2]
+                .iter()
+                .filter_map(|neighbour| find(corners[*neighbour].0))
+                .collect();
+            for clip in &neighbours {
+                add(*clip, weight / neighbours.len() as f32);
+            }
+        }
+        weights
+    }
+}
@@ -1,8 +1,9 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use super::Animation;
 
 mod animation_graph;
+mod blend_space;
 
 pub struct AnimationGraph {
     inputs: HashMap<String, f32>,
@@ -16,10 +17,13 @@ pub struct AnimationGraph {
 
 pub struct State {
     name: String,
-    animations: HashMap<String, Animation>,
-    animation_times: HashMap<String, f32>,
-    animation_cycled: HashMap<String, bool>,
+    /// Ordered by name, so the clips are always blended in the same order
+    animations: BTreeMap<String, Animation>,
+    animation_times: BTreeMap<String, f32>,
+    animation_cycled: BTreeMap<String, bool>,
     sync_animations: bool,
+    /// Replaces the animations of the state when set
+    blend_space: Option<BlendSpace>,
     transitions: Vec<Transition>,
 }
 
@@ -28,3 +32,26 @@ pub struct Transition {
     condition: Box<dyn Fn(&HashMap<String, f32>) -> bool>,
     transition_time: f32,
 }
+
+/// Clips placed at coordinates of one or two inputs, such as speed and direction. The clips at
+/// the corners of the grid cell around the current inputs are blended bilinearly and played in
+/// sync, so their cycles line up while the weights change
+pub struct BlendSpace {
+    x_input: String,
+    /// None for a one dimensional blend space, the clips are placed at y 0 then
+    y_input: Option<String>,
+    clips: Vec<BlendSpaceClip>,
+    /// Coordinates of the clips along each axis, sorted and without duplicates
+    grid_x: Vec<f32>,
+    grid_y: Vec<f32>,
+    /// Clips weighted for the current inputs
+    weights: Vec<(usize, f32)>,
+    /// Shared progress of the clips through their cycle, from 0 to 1
+    phase: f32,
+    cycled: bool,
+}
+
+struct BlendSpaceClip {
+    animation: Animation,
+    position: (f32, f32),
+}
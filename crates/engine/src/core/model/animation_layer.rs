@@ -0,0 +1,96 @@
+use std::collections::{HashMap, HashSet};
+
+use cgmath::{InnerSpace, Quaternion, Rad, Rotation3, Vector3};
+
+use super::{Animation, AnimationLayer, BoneDriver, Pose, Skeleton};
+
+impl AnimationLayer {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            animation: None,
+            mask: HashSet::new(),
+            weight: 1.0,
+            time: 0.0,
+            drivers: Vec::new(),
+        }
+    }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    /// Loops `animation` on the masked bones, e.g. blinking or an idle facial expression
+    pub fn set_animation(&mut self, animation: Animation) {
+        self.animation = Some(animation);
+        self.time = 0.0;
+    }
+
+    /// Restricts the layer's animation to `bone`, further calls add more bones
+    pub fn add_bone(&mut self, bone: &str) {
+        self.mask.insert(bone.to_string());
+    }
+
+    pub fn add_driver(&mut self, driver: BoneDriver) {
+        self.drivers.push(driver);
+    }
+
+    /// How much the layer overrides the pose below it, from 0 to 1
+    pub fn set_weight(&mut self, weight: f32) {
+        self.weight = weight.clamp(0.0, 1.0);
+    }
+
+    pub fn update(&mut self, delta_time: f32) {
+        if let Some(animation) = &self.animation {
+            if animation.duration > 0.0 {
+                self.time =
+                    (self.time + delta_time * animation.ticks_per_second) % animation.duration;
+            }
+        }
+    }
+
+    /// Layers the animation and drivers over `pose`, bones the pose has no transform for start
+    /// from their bind transform in `skeleton`
+    pub fn apply(&self, pose: &mut Pose, skeleton: &Skeleton, inputs: &HashMap<String, f32>) {
+        if self.weight <= 0.0 {
+            return;
+        }
+        if let Some(animation) = &self.animation {
+            for (bone, transform) in animation.sample(self.time).transforms {
+                if !self.mask.is_empty() && !self.mask.contains(&bone) {
+                    continue;
+                }
+                let transform = match pose.get_base_transform(&bone, skeleton) {
+                    Some(base) => transform.interpolate(&base, self.weight),
+                    None => transform,
+                };
+                pose.add_transform(bone, transform);
+            }
+        }
+        for driver in &self.drivers {
+            let value = inputs.get(&driver.input).copied().unwrap_or(0.0);
+            let Some(mut transform) = pose.get_base_transform(&driver.bone, skeleton) else {
+                continue;
+            };
+            transform.rotation = transform.rotation
+                * Quaternion::from_axis_angle(
+                    driver.axis,
+                    driver.angle * (value.clamp(0.0, 1.0) * self.weight),
+                );
+            pose.add_transform(driver.bone.clone(), transform);
+        }
+    }
+}
+
+impl BoneDriver {
+    /// Rotates `bone` around its local `axis` by up to `angle` as `input` goes from 0 to 1,
+    /// e.g. a jaw bone opened by a "mouth_open" input
+    pub fn new<A: Into<Rad<f32>>>(bone: &str, input: &str, axis: Vector3<f32>, angle: A) -> Self {
+        Self {
+            bone: bone.to_string(),
+            input: input.to_string(),
+            axis: axis.normalize(),
+            angle: angle.into(),
+        }
+    }
+}
@@ -1,4 +1,6 @@
-use cgmath::Vector3;
+use std::collections::HashSet;
+
+use cgmath::{Matrix4, Vector3};
 
 use super::{Bone, Pose};
 
@@ -14,7 +16,30 @@ impl Bone {
         bones
     }
 
-    pub fn apply_pose(&mut self, pose: &Pose, is_root: bool) -> Vector3<f32> {
+    /// Transform of the bone `name` in the space of the mesh, `None` if it is not part of this
+    /// bone's hierarchy
+    pub fn get_global_transform(
+        &self,
+        name: &str,
+        parent_transform: Matrix4<f32>,
+    ) -> Option<Matrix4<f32>> {
+        let transform = parent_transform * self.current_transform;
+        if self.name == name {
+            return Some(transform);
+        }
+        self.children
+            .as_ref()?
+            .iter()
+            .find_map(|child| child.get_global_transform(name, transform))
+    }
+
+    /// Poses the bone and its children, the bones in `collapsed` and their children are skipped
+    pub fn apply_pose(
+        &mut self,
+        pose: &Pose,
+        is_root: bool,
+        collapsed: &HashSet<usize>,
+    ) -> Vector3<f32> {
         let mut root_motion = Vector3::new(0.0, 0.0, 0.0);
         if let Some(transform) = pose.transforms.get(&self.name) {
             if is_root {
@@ -31,7 +56,9 @@ impl Bone {
         }
         if let Some(children) = &mut self.children {
             for child in children {
-                child.apply_pose(pose, false);
+                if !collapsed.contains(&child.id) {
+                    child.apply_pose(pose, false, collapsed);
+                }
             }
         }
         root_motion
@@ -0,0 +1,14 @@
+const int MAX_BONES = 100;
+
+// bone transforms of the mesh being drawn
+layout (std140, binding = 1) uniform BonePalette {
+    mat4 boneTransforms[MAX_BONES];
+    // rotation quaternion in the first column, dual part with the translation in the second
+    mat2x4 boneDualQuaternions[MAX_BONES];
+};
+
+// bone transforms of the mesh when its velocity was last rendered
+layout (std140, binding = 2) uniform PreviousBonePalette {
+    mat4 previousBoneTransforms[MAX_BONES];
+    mat2x4 previousBoneDualQuaternions[MAX_BONES];
+};
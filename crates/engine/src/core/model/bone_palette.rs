@@ -0,0 +1,71 @@
+use std::{ffi::c_void, mem::size_of};
+
+use cgmath::Matrix4;
+use gl::types::{GLintptr, GLsizeiptr, GLuint};
+
+use crate::profile_scope;
+
+use super::{BonePaletteBuffer, Model, SkinningMethod, MAX_BONES};
+
+/// Size of a `mat4` in the std140 layout
+const MATRIX_SIZE: usize = size_of::<[f32; 16]>();
+/// Size of a `mat2x4` in the std140 layout, two columns of four floats
+const DUAL_QUATERNION_SIZE: usize = size_of::<[f32; 8]>();
+
+impl BonePaletteBuffer {
+    /// Allocates a palette for the block at the uniform buffer binding `binding`
+    pub fn new(binding: GLuint) -> Self {
+        let mut buffer = 0;
+        let size = MAX_BONES * (MATRIX_SIZE + DUAL_QUATERNION_SIZE);
+        unsafe {
+            gl::GenBuffers(1, &mut buffer);
+            gl::BindBuffer(gl::UNIFORM_BUFFER, buffer);
+            gl::BufferData(
+                gl::UNIFORM_BUFFER,
+                size as GLsizeiptr,
+                std::ptr::null(),
+                gl::DYNAMIC_DRAW,
+            );
+            gl::BindBuffer(gl::UNIFORM_BUFFER, 0);
+        }
+        Self { buffer, binding }
+    }
+
+    /// Uploads the bone transforms as the shaders read them with `skinning` and binds the palette.
+    /// Bones past `MAX_BONES` are left out
+    pub fn upload(&self, transforms: &[Matrix4<f32>], skinning: SkinningMethod) {
+        profile_scope!("bone_palette_upload");
+        let transforms = &transforms[..transforms.len().min(MAX_BONES)];
+        unsafe {
+            gl::BindBuffer(gl::UNIFORM_BUFFER, self.buffer);
+            match skinning {
+                SkinningMethod::Linear => gl::BufferSubData(
+                    gl::UNIFORM_BUFFER,
+                    0,
+                    (transforms.len() * MATRIX_SIZE) as GLsizeiptr,
+                    transforms.as_ptr() as *const c_void,
+                ),
+                SkinningMethod::DualQuaternion => {
+                    let dual_quaternions: Vec<[f32; 8]> =
+                        transforms.iter().map(Model::to_dual_quaternion).collect();
+                    gl::BufferSubData(
+                        gl::UNIFORM_BUFFER,
+                        (MAX_BONES * MATRIX_SIZE) as GLintptr,
+                        (dual_quaternions.len() * DUAL_QUATERNION_SIZE) as GLsizeiptr,
+                        dual_quaternions.as_ptr() as *const c_void,
+                    )
+                }
+            }
+            gl::BindBuffer(gl::UNIFORM_BUFFER, 0);
+            gl::BindBufferBase(gl::UNIFORM_BUFFER, self.binding, self.buffer);
+        }
+    }
+}
+
+impl Drop for BonePaletteBuffer {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteBuffers(1, &self.buffer);
+        }
+    }
+}
@@ -1,7 +1,7 @@
-use cgmath::{Quaternion, Vector3};
+use cgmath::{InnerSpace, Quaternion, SquareMatrix, Vector3};
 use russimp::animation::NodeAnim;
 
-use super::{Channel, LocalTransform};
+use super::{Channel, LocalTransform, Skeleton};
 
 impl Channel {
     pub fn new(channel: &NodeAnim) -> Channel {
@@ -34,6 +34,74 @@ impl Channel {
         }
     }
 
+    /// Maps the keys from the `source_bone` of the `source` skeleton onto the `target_bone`.
+    /// The rotations keep their change from the bind pose in world space. Only the bone carrying
+    /// the motion of the animation, given a `root_scale`, keeps its animated translation scaled by
+    /// it, the other bones keep the proportions of the target
+    pub fn retarget(
+        &self,
+        (source, source_bone): (&Skeleton, &str),
+        (target, target_bone): (&Skeleton, &str),
+        root_scale: Option<f32>,
+    ) -> Channel {
+        let rotation = |skeleton: &Skeleton, bone: &str| {
+            (
+                LocalTransform::from_matrix_4(&skeleton.get_parent_global_bind(bone)).rotation,
+                LocalTransform::from_matrix_4(&skeleton.get_global_bind(bone)).rotation,
+            )
+        };
+        let (source_parent, source_global) = rotation(source, source_bone);
+        let (target_parent, target_global) = rotation(target, target_bone);
+        let to_target_parent = target_parent.conjugate() * source_parent;
+        let to_target_bind = source_global.conjugate() * target_global;
+        let rotation_keys = self
+            .rotation_keys
+            .iter()
+            .map(|(time, rotation)| {
+                (
+                    *time,
+                    (to_target_parent * rotation * to_target_bind).normalize(),
+                )
+            })
+            .collect();
+
+        let target_bind = target
+            .get_bind(target_bone)
+            .cloned()
+            .unwrap_or(LocalTransform {
+                translation: Vector3::new(0.0, 0.0, 0.0),
+                rotation: Quaternion::new(1.0, 0.0, 0.0, 0.0),
+                scale: Vector3::new(1.0, 1.0, 1.0),
+            });
+        let position_keys = match (root_scale, source.get_bind(source_bone)) {
+            (Some(scale), Some(source_bind)) => {
+                let source_parent = source.get_parent_global_bind(source_bone);
+                let target_parent = target
+                    .get_parent_global_bind(target_bone)
+                    .invert()
+                    .unwrap_or(SquareMatrix::identity());
+                self.position_keys
+                    .iter()
+                    .map(|(time, translation)| {
+                        let offset =
+                            source_parent * (translation - source_bind.translation).extend(0.0);
+                        (
+                            *time,
+                            target_bind.translation + (target_parent * offset).truncate() * scale,
+                        )
+                    })
+                    .collect()
+            }
+            _ => vec![(0.0, target_bind.translation)],
+        };
+        Channel {
+            bone_id: target_bone.to_string(),
+            position_keys,
+            rotation_keys,
+            scaling_keys: vec![(0.0, target_bind.scale)],
+        }
+    }
+
     pub fn sample(&self, time: f32) -> LocalTransform {
         let position = self.interpolate_position(time);
         let rotation = self.interpolate_rotation(time);
@@ -1,23 +1,81 @@
-#version 330 core
+#version 460 core
+
+// the material compiles a variant with ALBEDO_TEXTURE, NORMAL_TEXTURE,
+// METALLIC_ROUGHNESS_TEXTURE and EMISSIVE_TEXTURE defined for the slots it has a texture in
 
 in vec3 Normal;
 in vec3 toLightVector;
 in vec2 TexCoords;
+in vec3 WorldPosition;
+
+// textures of the material, only the slots that are defined are sampled
+uniform sampler2D albedoTexture;
+uniform sampler2D normalTexture;
+// roughness in the green and metalness in the blue channel
+uniform sampler2D metallicRoughnessTexture;
+uniform sampler2D emissiveTexture;
+// factors of the material, they multiply the textures
+uniform vec4 baseColor;
+uniform float roughness;
+uniform vec3 emissive;
+// 1 for materials blended over the opaque geometry, which keep the alpha of the base color
+uniform int transparent;
 
-uniform sampler2D texture_diffuse;
-uniform sampler2D texture_normals;
-uniform sampler2D texture_shininess;
-uniform sampler2D texture_specular;
+layout (location = 0) out vec4 FragColor;
+// world space normal for the SSAO G-buffer
+layout (location = 1) out vec4 FragNormal;
+// x roughness for screen space reflections
+layout (location = 2) out vec4 FragMaterial;
 
-out vec4 FragColor;
+#include "lights.glsl"
+#include "environment.glsl"
+
+// tangent frame from the screen space derivatives of the position and the texture coordinates,
+// the meshes have no tangents
+mat3 getTangentFrame(vec3 normal)
+{
+    vec3 positionX = dFdx(WorldPosition);
+    vec3 positionY = dFdy(WorldPosition);
+    vec2 texCoordsX = dFdx(TexCoords);
+    vec2 texCoordsY = dFdy(TexCoords);
+    vec3 perpendicularY = cross(positionY, normal);
+    vec3 perpendicularX = cross(normal, positionX);
+    vec3 tangent = perpendicularY * texCoordsX.x + perpendicularX * texCoordsY.x;
+    vec3 bitangent = perpendicularY * texCoordsX.y + perpendicularX * texCoordsY.y;
+    float scale = inversesqrt(max(max(dot(tangent, tangent), dot(bitangent, bitangent)), 1e-12));
+    return mat3(tangent * scale, bitangent * scale, normal);
+}
 
 void main()
 {
-    vec3 unitNormal = normalize(Normal * texture(texture_normals, TexCoords).rgb);
+    vec3 unitNormal = normalize(Normal);
+#ifdef NORMAL_TEXTURE
+    vec3 tangentNormal = texture(normalTexture, TexCoords).rgb * 2.0 - 1.0;
+    unitNormal = normalize(getTangentFrame(unitNormal) * tangentNormal);
+#endif
+    vec3 albedo = baseColor.rgb;
+    float alpha = baseColor.a;
+#ifdef ALBEDO_TEXTURE
+    vec4 albedoSample = texture(albedoTexture, TexCoords);
+    albedo *= albedoSample.rgb;
+    alpha *= albedoSample.a;
+#endif
+    float surfaceRoughness = roughness;
+#ifdef METALLIC_ROUGHNESS_TEXTURE
+    surfaceRoughness *= texture(metallicRoughnessTexture, TexCoords).g;
+#endif
+    vec3 emission = emissive;
+#ifdef EMISSIVE_TEXTURE
+    emission *= texture(emissiveTexture, TexCoords).rgb;
+#endif
+
     vec3 unitToLightVector = normalize(toLightVector);
     float intensity = dot(unitNormal, unitToLightVector);
-    float brightness = max(intensity, 0.5);
-    vec3 diffuse = brightness * texture(texture_diffuse, TexCoords).rgb;
+    float brightness = max(intensity, 0.0) * 0.5;
+    vec3 lighting = brightness + skyAmbient(unitNormal) * screenAmbientOcclusion() + pointLighting(WorldPosition, unitNormal);
+    vec3 color = lighting * albedo + emission;
 
-    FragColor = vec4(diffuse, 1.0);
-}
\ No newline at end of file
+    FragColor = vec4(applyFog(color, WorldPosition), transparent == 1 ? alpha : 1.0);
+    FragNormal = vec4(unitNormal, 1.0);
+    FragMaterial = vec4(surfaceRoughness, 0.0, 0.0, 1.0);
+}
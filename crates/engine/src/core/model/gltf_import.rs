@@ -0,0 +1,419 @@
+use std::collections::HashMap;
+
+use cgmath::{InnerSpace, Matrix3, Matrix4, Quaternion, SquareMatrix, Vector3, Vector4, Zero};
+use gltf::{
+    animation::{util::ReadOutputs, Interpolation},
+    image::{Data as ImageData, Format},
+    material::AlphaMode,
+    Document, Node,
+};
+use image::RgbaImage;
+
+use super::{
+    Animation, Bone, Channel, LocalTransform, MaterialData, MaterialSlot, ModelData, ModelMaterial,
+    ModelMeshData, Skeleton, SkeletonBone,
+};
+
+/// Whether the file at `path` is read with the gltf crate instead of assimp
+pub(super) fn is_gltf(path: &str) -> bool {
+    let path = path.to_lowercase();
+    path.ends_with(".gltf") || path.ends_with(".glb")
+}
+
+impl ModelData {
+    /// Parses a glTF file. Every primitive becomes a mesh, static meshes are moved into place
+    /// by the transforms of their nodes while skinned meshes follow the joints of their skin.
+    /// Primitives without a material share a default one
+    pub(super) fn load_gltf(path: &str) -> Result<ModelData, Box<dyn std::error::Error>> {
+        let (document, buffers, images) = gltf::import(path)?;
+        let globals = get_global_transforms(&document);
+        let mut materials: Vec<MaterialData> = document
+            .materials()
+            .map(|material| get_material(&material, &images))
+            .collect();
+        let mut default_material = None;
+        let mut meshes = Vec::new();
+        for node in document.nodes() {
+            let (Some(mesh), Some(global)) = (node.mesh(), globals.get(&node.index())) else {
+                continue;
+            };
+            let mesh_name = get_name(mesh.name(), "mesh", mesh.index());
+            for primitive in mesh.primitives() {
+                let reader = primitive.reader(|buffer| Some(buffers[buffer.index()].0.as_slice()));
+                let Some(positions) = reader.read_positions() else {
+                    continue;
+                };
+                let mut positions: Vec<Vector3<f32>> = positions.map(Vector3::from).collect();
+                let indices: Vec<u32> = match reader.read_indices() {
+                    Some(indices) => indices.into_u32().collect(),
+                    None => (0..positions.len() as u32).collect(),
+                };
+                let mut normals: Vec<Vector3<f32>> = match reader.read_normals() {
+                    Some(normals) => normals.map(Vector3::from).collect(),
+                    None => get_smooth_normals(&positions, &indices),
+                };
+                let texture_coords: Vec<f32> = match reader.read_tex_coords(0) {
+                    Some(texture_coords) => texture_coords.into_f32().flatten().collect(),
+                    None => vec![0.0; positions.len() * 2],
+                };
+
+                let root_bone = match (node.skin(), reader.read_joints(0), reader.read_weights(0)) {
+                    (Some(skin), Some(joints), Some(weights)) => {
+                        // weights per joint in the order of the skin's joints
+                        let mut joint_weights = vec![Vec::new(); skin.joints().count()];
+                        for (vertex, (joints, weights)) in
+                            joints.into_u16().zip(weights.into_f32()).enumerate()
+                        {
+                            for (joint, weight) in joints.into_iter().zip(weights) {
+                                if let Some(joint_weights) = joint_weights.get_mut(joint as usize) {
+                                    joint_weights.push((vertex as u32, weight));
+                                }
+                            }
+                        }
+                        let inverse_binds: Vec<Matrix4<f32>> = skin
+                            .reader(|buffer| Some(buffers[buffer.index()].0.as_slice()))
+                            .read_inverse_bind_matrices()
+                            .map(|matrices| matrices.map(Matrix4::from).collect())
+                            .unwrap_or_default();
+                        let joints: HashMap<usize, usize> = skin
+                            .joints()
+                            .enumerate()
+                            .map(|(id, joint)| (joint.index(), id))
+                            .collect();
+                        let skeleton = GltfSkeleton {
+                            joints,
+                            inverse_binds,
+                            weights: joint_weights,
+                        };
+                        get_root_nodes(&document)
+                            .into_iter()
+                            .flat_map(|root| skeleton.get_bones(root, Matrix4::identity()))
+                            .next()
+                    }
+                    _ => None,
+                };
+                if root_bone.is_none() {
+                    let normal_matrix = Matrix3::from_cols(
+                        global.x.truncate(),
+                        global.y.truncate(),
+                        global.z.truncate(),
+                    );
+                    for position in positions.iter_mut() {
+                        *position = (global * position.extend(1.0)).truncate();
+                    }
+                    for normal in normals.iter_mut() {
+                        *normal = (normal_matrix * *normal).normalize();
+                    }
+                }
+
+                let mut model_mesh = ModelMeshData::new(
+                    positions.iter().flat_map(|p| [p.x, p.y, p.z]).collect(),
+                    indices,
+                    normals.iter().flat_map(|n| [n.x, n.y, n.z]).collect(),
+                    texture_coords,
+                    root_bone,
+                );
+                model_mesh.material = match primitive.material().index() {
+                    Some(index) => index,
+                    None => *default_material.get_or_insert_with(|| {
+                        materials.push(MaterialData::default());
+                        materials.len() - 1
+                    }),
+                };
+                meshes.push((format!("{}_{}", mesh_name, primitive.index()), model_mesh));
+            }
+        }
+
+        if materials.is_empty() {
+            materials.push(MaterialData::default());
+        }
+
+        Ok(ModelData {
+            meshes,
+            materials,
+            skeleton: Skeleton::from_gltf(&document),
+            // glTF is in meters
+            scale: 1.0,
+        })
+    }
+}
+
+impl Skeleton {
+    /// Node hierarchy of the default scene of a glTF file
+    pub(super) fn from_gltf(document: &Document) -> Skeleton {
+        let mut bones = HashMap::new();
+        for root in get_root_nodes(document) {
+            Skeleton::add_gltf_node(&root, None, &mut bones);
+        }
+        Skeleton { bones }
+    }
+
+    fn add_gltf_node(node: &Node, parent: Option<&str>, bones: &mut HashMap<String, SkeletonBone>) {
+        let name = get_node_name(node);
+        bones.insert(
+            name.clone(),
+            SkeletonBone {
+                parent: parent.map(str::to_string),
+                bind: get_local_transform(node),
+            },
+        );
+        for child in node.children() {
+            Skeleton::add_gltf_node(&child, Some(&name), bones);
+        }
+    }
+}
+
+impl Animation {
+    /// Loads the first animation of a glTF file with the skeleton it animates. Key times are in
+    /// seconds, so the animation plays at one tick per second
+    pub(super) fn from_gltf(
+        path: &str,
+    ) -> Result<(Animation, Skeleton), Box<dyn std::error::Error>> {
+        let (document, buffers, _) = gltf::import(path)?;
+        let animation = document.animations().next().ok_or("No animations found")?;
+        let mut channels = HashMap::<String, Channel>::new();
+        let mut duration = 0.0f32;
+        for channel in animation.channels() {
+            let reader = channel.reader(|buffer| Some(buffers[buffer.index()].0.as_slice()));
+            let (Some(inputs), Some(outputs)) = (reader.read_inputs(), reader.read_outputs())
+            else {
+                continue;
+            };
+            let times: Vec<f32> = inputs.collect();
+            duration = times.iter().copied().fold(duration, f32::max);
+            let node = channel.target().node();
+            let bind = get_local_transform(&node);
+            let target = channels
+                .entry(get_node_name(&node))
+                .or_insert_with_key(|bone_id| Channel {
+                    bone_id: bone_id.clone(),
+                    position_keys: Vec::new(),
+                    rotation_keys: Vec::new(),
+                    scaling_keys: Vec::new(),
+                });
+            // cubic splines store an in-tangent, the value and an out-tangent per key, only the
+            // values are kept and interpolated linearly
+            let is_cubic = channel.sampler().interpolation() == Interpolation::CubicSpline;
+            let values =
+                |count: usize| (0..count).map(move |key| if is_cubic { key * 3 + 1 } else { key });
+            match outputs {
+                ReadOutputs::Translations(translations) => {
+                    let translations: Vec<[f32; 3]> = translations.collect();
+                    target.position_keys = values(times.len())
+                        .zip(&times)
+                        .filter_map(|(value, time)| {
+                            Some((*time, Vector3::from(*translations.get(value)?)))
+                        })
+                        .collect();
+                }
+                ReadOutputs::Rotations(rotations) => {
+                    let rotations: Vec<[f32; 4]> = rotations.into_f32().collect();
+                    target.rotation_keys = values(times.len())
+                        .zip(&times)
+                        .filter_map(|(value, time)| {
+                            let [x, y, z, w] = *rotations.get(value)?;
+                            Some((*time, Quaternion::new(w, x, y, z)))
+                        })
+                        .collect();
+                }
+                ReadOutputs::Scales(scales) => {
+                    let scales: Vec<[f32; 3]> = scales.collect();
+                    target.scaling_keys = values(times.len())
+                        .zip(&times)
+                        .filter_map(|(value, time)| {
+                            Some((*time, Vector3::from(*scales.get(value)?)))
+                        })
+                        .collect();
+                }
+                ReadOutputs::MorphTargetWeights(_) => {}
+            }
+            // properties the animation leaves out keep the node's transform
+            if target.position_keys.is_empty() {
+                target.position_keys.push((0.0, bind.translation));
+            }
+            if target.rotation_keys.is_empty() {
+                target.rotation_keys.push((0.0, bind.rotation));
+            }
+            if target.scaling_keys.is_empty() {
+                target.scaling_keys.push((0.0, bind.scale));
+            }
+        }
+        let animation = Animation {
+            name: animation
+                .name()
+                .map_or_else(|| format!("animation{}", animation.index()), str::to_string),
+            duration,
+            ticks_per_second: 1.0,
+            channels,
+        };
+        Ok((animation, Skeleton::from_gltf(&document)))
+    }
+}
+
+/// Joints of a skin with their inverse bind matrices and the vertex weights of a primitive
+struct GltfSkeleton {
+    /// Joint index by node index
+    joints: HashMap<usize, usize>,
+    inverse_binds: Vec<Matrix4<f32>>,
+    weights: Vec<Vec<(u32, f32)>>,
+}
+
+impl GltfSkeleton {
+    /// Bones of the joints at and below `node`. The transforms of nodes between the joints are
+    /// folded into the joint below them
+    fn get_bones(&self, node: Node, offset: Matrix4<f32>) -> Vec<Bone> {
+        let transform = offset * get_local_transform(&node).to_matrix_4();
+        let Some(&id) = self.joints.get(&node.index()) else {
+            return node
+                .children()
+                .flat_map(|child| self.get_bones(child, transform))
+                .collect();
+        };
+        let children: Vec<Bone> = node
+            .children()
+            .flat_map(|child| self.get_bones(child, Matrix4::identity()))
+            .collect();
+        vec![Bone {
+            id,
+            name: get_node_name(&node),
+            offset_matrix: self
+                .inverse_binds
+                .get(id)
+                .copied()
+                .unwrap_or(Matrix4::identity()),
+            weights: self.weights.get(id).cloned().unwrap_or_default(),
+            children: (node.children().len() > 0).then_some(children),
+            current_transform: transform,
+            last_translation: Vector3::zero(),
+        }]
+    }
+}
+
+/// Nodes at the root of the default scene, or of the first scene without a default one
+fn get_root_nodes(document: &Document) -> Vec<Node<'_>> {
+    document
+        .default_scene()
+        .or_else(|| document.scenes().next())
+        .map(|scene| scene.nodes().collect())
+        .unwrap_or_default()
+}
+
+/// Transforms of the nodes of the scene relative to its root by node index
+fn get_global_transforms(document: &Document) -> HashMap<usize, Matrix4<f32>> {
+    fn add_node(node: Node, parent: Matrix4<f32>, globals: &mut HashMap<usize, Matrix4<f32>>) {
+        let global = parent * get_local_transform(&node).to_matrix_4();
+        globals.insert(node.index(), global);
+        for child in node.children() {
+            add_node(child, global, globals);
+        }
+    }
+    let mut globals = HashMap::new();
+    for root in get_root_nodes(document) {
+        add_node(root, Matrix4::identity(), &mut globals);
+    }
+    globals
+}
+
+fn get_local_transform(node: &Node) -> LocalTransform {
+    let (translation, [x, y, z, w], scale) = node.transform().decomposed();
+    LocalTransform {
+        translation: Vector3::from(translation),
+        rotation: Quaternion::new(w, x, y, z),
+        scale: Vector3::from(scale),
+    }
+}
+
+/// Name of the node, unnamed nodes are named after their index so animations still find them
+fn get_node_name(node: &Node) -> String {
+    get_name(node.name(), "node", node.index())
+}
+
+fn get_name(name: Option<&str>, kind: &str, index: usize) -> String {
+    name.map_or_else(|| format!("{}{}", kind, index), str::to_string)
+}
+
+/// Vertex normals averaged from the normals of the faces around each vertex, weighted by the
+/// faces' area
+fn get_smooth_normals(positions: &[Vector3<f32>], indices: &[u32]) -> Vec<Vector3<f32>> {
+    let mut normals = vec![Vector3::zero(); positions.len()];
+    for face in indices.chunks_exact(3) {
+        let [a, b, c] = [face[0], face[1], face[2]].map(|index| index as usize);
+        let normal = (positions[b] - positions[a]).cross(positions[c] - positions[a]);
+        for vertex in [a, b, c] {
+            normals[vertex] += normal;
+        }
+    }
+    normals
+        .into_iter()
+        .map(|normal: Vector3<f32>| {
+            if normal.magnitude2() > 0.0 {
+                normal.normalize()
+            } else {
+                Vector3::unit_y()
+            }
+        })
+        .collect()
+}
+
+/// Parameters and textures of a metallic roughness material
+fn get_material(material: &gltf::Material, images: &[ImageData]) -> MaterialData {
+    let pbr = material.pbr_metallic_roughness();
+    let mut material_data = MaterialData::new(ModelMaterial {
+        base_color: Vector4::from(pbr.base_color_factor()),
+        metallic: pbr.metallic_factor(),
+        roughness: pbr.roughness_factor(),
+        emissive: Vector3::from(material.emissive_factor()),
+        transparent: material.alpha_mode() == AlphaMode::Blend,
+    });
+    for (slot, texture) in [
+        (
+            MaterialSlot::Albedo,
+            pbr.base_color_texture().map(|info| info.texture()),
+        ),
+        (
+            MaterialSlot::Normal,
+            material.normal_texture().map(|info| info.texture()),
+        ),
+        (
+            MaterialSlot::MetallicRoughness,
+            pbr.metallic_roughness_texture().map(|info| info.texture()),
+        ),
+        (
+            MaterialSlot::Emissive,
+            material.emissive_texture().map(|info| info.texture()),
+        ),
+    ] {
+        if let Some(image) =
+            texture.and_then(|texture| images.get(texture.source().index()).and_then(to_rgba_image))
+        {
+            material_data.add_texture(slot, image);
+        }
+    }
+    material_data
+}
+
+fn to_rgba_image(image: &ImageData) -> Option<RgbaImage> {
+    let pixels = match image.format {
+        Format::R8G8B8A8 => image.pixels.clone(),
+        Format::R8G8B8 => image
+            .pixels
+            .chunks_exact(3)
+            .flat_map(|pixel| [pixel[0], pixel[1], pixel[2], 255])
+            .collect(),
+        Format::R8G8 => image
+            .pixels
+            .chunks_exact(2)
+            .flat_map(|pixel| [pixel[0], pixel[1], 0, 255])
+            .collect(),
+        Format::R8 => image
+            .pixels
+            .iter()
+            .flat_map(|value| [*value, *value, *value, 255])
+            .collect(),
+        format => {
+            log::warn!("Unsupported glTF texture format {:?}", format);
+            return None;
+        }
+    };
+    RgbaImage::from_raw(image.width, image.height, pixels)
+}
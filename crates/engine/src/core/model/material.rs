@@ -0,0 +1,211 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+    sync::{Arc, Mutex},
+};
+
+use image::RgbaImage;
+use lazy_static::lazy_static;
+use russimp::material::TextureType;
+
+use crate::core::renderer::{
+    shader::{Shader, ShaderDefines},
+    texture::Texture,
+};
+
+use super::{Material, MaterialData, MaterialImage, MaterialManager, MaterialSlot, ModelMaterial};
+
+lazy_static! {
+    static ref MATERIAL_MANAGER: Mutex<MaterialManager> = Mutex::new(MaterialManager::new());
+}
+
+impl MaterialSlot {
+    pub const ALL: [MaterialSlot; 4] = [
+        MaterialSlot::Albedo,
+        MaterialSlot::Normal,
+        MaterialSlot::MetallicRoughness,
+        MaterialSlot::Emissive,
+    ];
+
+    /// Slot of an assimp texture type, types without a slot are not loaded
+    pub fn from_texture_type(texture_type: TextureType) -> Option<Self> {
+        match texture_type {
+            TextureType::Diffuse | TextureType::BaseColor => Some(MaterialSlot::Albedo),
+            TextureType::Normals | TextureType::NormalCamera => Some(MaterialSlot::Normal),
+            TextureType::Metalness | TextureType::Roughness => {
+                Some(MaterialSlot::MetallicRoughness)
+            }
+            TextureType::Emissive | TextureType::EmissionColor => Some(MaterialSlot::Emissive),
+            _ => None,
+        }
+    }
+
+    /// Texture unit the slot is bound to while drawing
+    pub fn get_texture_unit(&self) -> u32 {
+        match self {
+            MaterialSlot::Albedo => 0,
+            MaterialSlot::Normal => 1,
+            MaterialSlot::MetallicRoughness => 2,
+            MaterialSlot::Emissive => 3,
+        }
+    }
+
+    /// Define the model shader samples the slot's texture with
+    pub fn get_define(&self) -> &'static str {
+        match self {
+            MaterialSlot::Albedo => "ALBEDO_TEXTURE",
+            MaterialSlot::Normal => "NORMAL_TEXTURE",
+            MaterialSlot::MetallicRoughness => "METALLIC_ROUGHNESS_TEXTURE",
+            MaterialSlot::Emissive => "EMISSIVE_TEXTURE",
+        }
+    }
+
+    /// Name of the slot's sampler in the model shader
+    fn get_uniform(&self) -> &'static str {
+        match self {
+            MaterialSlot::Albedo => "albedoTexture",
+            MaterialSlot::Normal => "normalTexture",
+            MaterialSlot::MetallicRoughness => "metallicRoughnessTexture",
+            MaterialSlot::Emissive => "emissiveTexture",
+        }
+    }
+}
+
+impl Material {
+    /// Uploads the textures of a parsed material, reusing textures other materials already
+    /// uploaded
+    pub(super) fn from_data(data: &MaterialData) -> Self {
+        Self {
+            parameters: data.parameters,
+            textures: data
+                .textures
+                .iter()
+                .map(|(slot, image)| (*slot, MaterialManager::get_texture(image)))
+                .collect(),
+        }
+    }
+
+    pub fn get_parameters(&self) -> ModelMaterial {
+        self.parameters
+    }
+
+    pub fn set_parameters(&mut self, parameters: ModelMaterial) {
+        self.parameters = parameters;
+    }
+
+    pub fn is_transparent(&self) -> bool {
+        self.parameters.transparent
+    }
+
+    pub fn has_texture(&self, slot: MaterialSlot) -> bool {
+        self.textures.contains_key(&slot)
+    }
+
+    /// Defines of the model shader variant the material is drawn with, one per texture slot
+    /// that has a texture
+    pub fn get_shader_defines(&self) -> ShaderDefines {
+        let mut defines = ShaderDefines::new();
+        for slot in MaterialSlot::ALL {
+            defines.set(slot.get_define(), self.has_texture(slot));
+        }
+        defines
+    }
+
+    /// Sets the parameters of the material on `shader` and binds its textures. `shader` has to
+    /// be the variant for `get_shader_defines`, which only samples the slots that have a texture
+    pub(super) fn bind(&self, shader: &Shader) {
+        let base_color = self.parameters.base_color;
+        shader.set_uniform_4f(
+            "baseColor",
+            base_color.x,
+            base_color.y,
+            base_color.z,
+            base_color.w,
+        );
+        shader.set_uniform_1f("roughness", self.parameters.roughness);
+        let emissive = self.parameters.emissive;
+        shader.set_uniform_3f("emissive", emissive.x, emissive.y, emissive.z);
+        shader.set_uniform_1i("transparent", self.parameters.transparent as i32);
+        for slot in MaterialSlot::ALL {
+            let unit = slot.get_texture_unit();
+            shader.set_uniform_1i(slot.get_uniform(), unit as i32);
+            if let Some(texture) = self.textures.get(&slot) {
+                unsafe { gl::ActiveTexture(gl::TEXTURE0 + unit) };
+                texture.bind();
+            }
+        }
+        unsafe { gl::ActiveTexture(gl::TEXTURE0) };
+    }
+}
+
+impl MaterialData {
+    pub(super) fn new(parameters: ModelMaterial) -> Self {
+        Self {
+            parameters,
+            textures: Vec::new(),
+        }
+    }
+
+    /// Adds `image` as the texture of `slot` unless the slot already has one
+    pub(super) fn add_texture(&mut self, slot: MaterialSlot, image: RgbaImage) {
+        if !self.textures.iter().any(|(existing, _)| *existing == slot) {
+            self.textures.push((slot, MaterialImage::new(image)));
+        }
+    }
+}
+
+impl Default for MaterialData {
+    fn default() -> Self {
+        Self::new(ModelMaterial::default())
+    }
+}
+
+impl MaterialImage {
+    /// Hashes the pixels on the loading thread, so the upload only has to look the key up
+    fn new(image: RgbaImage) -> Self {
+        let mut hasher = DefaultHasher::new();
+        image.dimensions().hash(&mut hasher);
+        image.as_raw().hash(&mut hasher);
+        Self {
+            key: hasher.finish(),
+            image,
+        }
+    }
+}
+
+impl MaterialManager {
+    fn new() -> Self {
+        Self {
+            textures: HashMap::new(),
+        }
+    }
+
+    /// Texture of `image`, uploaded unless a texture with the same pixels is still in use
+    fn get_texture(image: &MaterialImage) -> Arc<Texture> {
+        let mut manager = MATERIAL_MANAGER.lock().unwrap();
+        if let Some(texture) = manager
+            .textures
+            .get(&image.key)
+            .and_then(|weak| weak.upgrade())
+        {
+            return texture;
+        }
+        manager.textures.retain(|_, weak| weak.strong_count() > 0);
+        let texture = Arc::new(Texture::new());
+        let (width, height) = image.image.dimensions();
+        texture.load_from_data(width, height, image.image.as_raw().clone());
+        manager.textures.insert(image.key, Arc::downgrade(&texture));
+        texture
+    }
+
+    /// Number of uploaded textures that are in use by a material
+    pub fn get_texture_count() -> usize {
+        MATERIAL_MANAGER
+            .lock()
+            .unwrap()
+            .textures
+            .values()
+            .filter(|weak| weak.strong_count() > 0)
+            .count()
+    }
+}
@@ -1,32 +1,217 @@
-use std::collections::HashMap;
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    sync::{atomic::AtomicBool, mpsc::Receiver, Arc, Weak},
+    time::{Instant, SystemTime},
+};
 
-use cgmath::{Matrix4, Point3, Quaternion, Vector3};
-use russimp::{material::TextureType, scene::Scene};
+use cgmath::{Matrix4, Point3, Quaternion, Rad, Vector3, Vector4};
+use gl::types::GLuint;
+use image::RgbaImage;
 
 use crate::core::renderer::{
-    shader::{DynamicVertexArray, Shader},
+    shader::{DynamicVertexArray, ShaderPermutations},
     texture::Texture,
 };
 
 mod animation;
 pub mod animation_graph;
+mod animation_layer;
 mod bone;
+mod bone_palette;
 mod channel;
+mod gltf_import;
+mod material;
 mod model;
+mod model_data;
+mod model_handle;
 mod model_mesh;
 mod pose;
+mod retarget;
+mod skeleton_lod;
+
+/// Uniform buffer binding of the `BonePalette` block from `bone_palette.glsl`
+pub const BONE_PALETTE_BINDING: GLuint = 1;
+/// Uniform buffer binding of the `PreviousBonePalette` block the velocity pass reads
+pub const PREVIOUS_BONE_PALETTE_BINDING: GLuint = 2;
+/// Bones a palette holds, matches `MAX_BONES` in `bone_palette.glsl`
+pub const MAX_BONES: usize = 100;
+const BONE_PALETTE_GLSL: &str = include_str!("bone_palette.glsl");
+const BONE_PALETTE_INCLUDE: &str = "#include \"bone_palette.glsl\"";
+
+/// When set, every model is skinned linearly to compare against dual quaternion skinning
+pub static FORCE_LINEAR_SKINNING: AtomicBool = AtomicBool::new(false);
 
 pub struct Model {
-    model: Scene,
+    skeleton: Skeleton,
     meshes: HashMap<String, ModelMesh>,
-    shader: Shader,
-    textures: HashMap<TextureType, Texture>,
+    /// Variants of the model shader for the feature sets of the materials
+    shaders: ShaderPermutations,
+    /// Materials the meshes refer to by their index
+    materials: Vec<Material>,
     pub position: Point3<f32>,
     scale: f32,
+    skinning: SkinningMethod,
+    skeleton_lod: Option<SkeletonLod>,
+    /// Level of the skeleton LOD the model is posed and drawn with, 0 for all bones
+    lod: usize,
+    /// Transforms of the meshes when the velocities were last rendered
+    previous_motion: RefCell<HashMap<String, MeshMotion>>,
+    bone_palette: BonePaletteBuffer,
+    previous_bone_palette: BonePaletteBuffer,
+}
+
+/// Uniform buffer holding the bone transforms of the mesh being drawn for the skinning in the
+/// vertex shaders, the model uploads it once per mesh and draw
+pub struct BonePaletteBuffer {
+    buffer: GLuint,
+    binding: GLuint,
+}
+
+struct MeshMotion {
+    model: Matrix4<f32>,
+    bone_transforms: Vec<Matrix4<f32>>,
+    /// Level of the skeleton LOD the bone transforms are ordered for
+    lod: usize,
+}
+
+/// Surface parameters of a material, read from the metallic roughness material of glTF files
+#[derive(Clone, Copy, Debug)]
+pub struct ModelMaterial {
+    /// Multiplies the albedo texture
+    pub base_color: Vector4<f32>,
+    /// Multiplies the blue channel of the metallic roughness texture
+    pub metallic: f32,
+    /// Roughness of the surfaces, smooth ones show screen space reflections. Multiplies the green
+    /// channel of the metallic roughness texture
+    pub roughness: f32,
+    /// Light the surface emits, multiplies the emissive texture
+    pub emissive: Vector3<f32>,
+    /// Blended over the opaque geometry with the alpha of the base color and albedo texture,
+    /// like glTF materials with the BLEND alpha mode. Transparent meshes cast no shadows
+    pub transparent: bool,
+}
+
+/// Texture slots of a `Material`, laid out like glTF's metallic roughness materials
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MaterialSlot {
+    Albedo,
+    Normal,
+    /// Roughness in the green and metalness in the blue channel
+    MetallicRoughness,
+    Emissive,
+}
+
+/// Surface of a mesh, the parameters along with the textures of its slots. Slots without a
+/// texture only use the parameters
+pub struct Material {
+    parameters: ModelMaterial,
+    /// Shared with the materials of other meshes and models through the `MaterialManager`
+    textures: HashMap<MaterialSlot, Arc<Texture>>,
+}
+
+/// Material as parsed from a model file, without a gl context. `Material::from_data` uploads it
+struct MaterialData {
+    parameters: ModelMaterial,
+    textures: Vec<(MaterialSlot, MaterialImage)>,
 }
 
+/// Texture image of a parsed material, `key` identifies its pixels
+struct MaterialImage {
+    key: u64,
+    image: RgbaImage,
+}
+
+/// Uploads the textures of the materials of all models and hands out the same texture for
+/// images with the same pixels, so models and meshes sharing a texture only upload it once.
+/// A texture is freed when the last material using it is dropped
+pub struct MaterialManager {
+    textures: HashMap<u64, Weak<Texture>>,
+}
+
+/// How the bone transforms are blended for vertices with several weights
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SkinningMethod {
+    /// Blends the bone matrices, cheap but collapses the volume around twisting joints
+    #[default]
+    Linear,
+    /// Blends the rigid bone transforms as dual quaternions, which keeps the volume on twists.
+    /// Scale in the bone transforms is ignored
+    DualQuaternion,
+}
+
+/// Distances from which minor bones like fingers and twist bones are collapsed into their
+/// parents, so crowds of distant characters are cheaper to pose and to skin.
+///
+/// Every level keeps the bones collapsed by the levels before it. The weights of collapsed bones
+/// are moved to their closest kept ancestor when the LOD is set on a model, see
+/// `Model::set_skeleton_lod`
+#[derive(Clone, Debug)]
+pub struct SkeletonLod {
+    /// Sorted by distance
+    levels: Vec<SkeletonLodLevel>,
+}
+
+#[derive(Clone, Debug)]
+pub struct SkeletonLodLevel {
+    /// Camera distance from which on the level is used
+    pub distance: f32,
+    /// Bones with a name containing one of these, ignoring case, are collapsed along with their
+    /// children
+    pub collapsed: Vec<String>,
+}
+
+/// Settings of a model to load, `build` loads it right away while `build_async` returns a
+/// `ModelHandle` and loads it on a worker thread
+#[derive(Clone)]
 pub struct ModelBuilder {
-    model: Model,
+    path: String,
+    position: Point3<f32>,
+    /// Overrides the roughness of the file's material when set
+    roughness: Option<f32>,
+    skinning: SkinningMethod,
+    skeleton_lod: Option<SkeletonLod>,
+}
+
+/// Meshes, textures and skeleton of a model file, parsed without a gl context so it can be
+/// loaded on a worker thread. Loaded through the `AssetManager`, so models of the same file
+/// share it while it is in use. `Model::from_data` uploads it. glTF files (`.gltf` and `.glb`)
+/// are read with the gltf crate, all other formats with assimp
+pub struct ModelData {
+    meshes: Vec<(String, ModelMeshData)>,
+    /// Materials the meshes refer to by their index, there is at least one
+    materials: Vec<MaterialData>,
+    skeleton: Skeleton,
+    /// Scale from the file's units to meters
+    scale: f32,
+}
+
+/// Model loading on a worker thread, see `ModelBuilder::build_async`. `poll` uploads it once it
+/// is parsed, and in debug builds loads it again when its file changes
+pub struct ModelHandle {
+    state: ModelState,
+    /// None for handles of models that were not loaded from a file by the handle
+    watch: Option<ModelWatch>,
+}
+
+/// File of a `ModelHandle` that is checked for changes
+struct ModelWatch {
+    builder: ModelBuilder,
+    /// Modification time of the file when it was last parsed
+    modified: Option<SystemTime>,
+    last_check: Instant,
+    /// Receives the parsed file while it is reloaded, the previous model is drawn meanwhile
+    reload: Option<Receiver<Result<Arc<ModelData>, String>>>,
+}
+
+enum ModelState {
+    /// Receives the parsed file, or why it could not be loaded
+    Loading {
+        receiver: Receiver<Result<Arc<ModelData>, String>>,
+        builder: ModelBuilder,
+    },
+    Loaded(Box<Model>),
+    Failed,
 }
 
 #[derive(Debug, Clone)]
@@ -39,11 +224,31 @@ struct ModelMeshVertex {
     bone_weights: (f32, f32, f32, f32),
 }
 
+/// Mesh as parsed from a model file, without a gl context. `ModelMesh::from_data` uploads it
+struct ModelMeshData {
+    indices: Vec<u32>,
+    vertices: Vec<ModelMeshVertex>,
+    root_bone: Option<Bone>,
+    /// Index of the mesh's material in the materials of its model
+    material: usize,
+}
+
 struct ModelMesh {
     vertex_array: Option<DynamicVertexArray<ModelMeshVertex>>,
     indices: Vec<u32>,
     vertices: Vec<ModelMeshVertex>,
     root_bone: Option<Bone>,
+    /// Index of the mesh's material in the materials of its model
+    material: usize,
+    /// Skinning of the levels of the model's skeleton LOD, the first entry is level 1
+    lods: Vec<MeshLod>,
+}
+
+/// Vertices of a mesh weighted to the bones kept by a level of a `SkeletonLod`
+struct MeshLod {
+    vertex_array: DynamicVertexArray<ModelMeshVertex>,
+    /// Ids of the collapsed bones, they are neither posed nor uploaded
+    collapsed: HashSet<usize>,
 }
 
 #[derive(Clone)]
@@ -77,6 +282,46 @@ pub struct Animation {
     channels: HashMap<String, Channel>,
 }
 
+/// Animation played on some bones on top of the body animation, such as the face, with bones
+/// that follow external inputs like the mouth opening with the amplitude of a voice line
+pub struct AnimationLayer {
+    name: String,
+    animation: Option<Animation>,
+    /// Bones taken from the animation, all of its channels when empty
+    mask: HashSet<String>,
+    weight: f32,
+    time: f32,
+    drivers: Vec<BoneDriver>,
+}
+
+/// Rotates a bone away from its animated orientation by an input in the range `0.0..=1.0`
+pub struct BoneDriver {
+    bone: String,
+    input: String,
+    axis: Vector3<f32>,
+    /// Rotation at an input of 1
+    angle: Rad<f32>,
+}
+
+/// Node hierarchy of a model or animation file with the bind transforms of its nodes, used to
+/// retarget animations between skeletons
+#[derive(Clone)]
+pub struct Skeleton {
+    bones: HashMap<String, SkeletonBone>,
+}
+
+#[derive(Clone)]
+struct SkeletonBone {
+    parent: Option<String>,
+    bind: LocalTransform,
+}
+
+/// Names of the target bones the bones of a source skeleton are retargeted onto
+#[derive(Clone, Debug, Default)]
+pub struct BoneMap {
+    bones: HashMap<String, String>,
+}
+
 #[derive(Clone)]
 struct Channel {
     bone_id: String,
@@ -84,3 +329,9 @@ struct Channel {
     rotation_keys: Vec<(f32, cgmath::Quaternion<f32>)>,
     scaling_keys: Vec<(f32, cgmath::Vector3<f32>)>,
 }
+
+/// Replaces the `#include "bone_palette.glsl"` line of a shader source with the bone palette
+/// blocks
+pub fn include_bone_palette(source: &str) -> String {
+    source.replace(BONE_PALETTE_INCLUDE, BONE_PALETTE_GLSL)
+}
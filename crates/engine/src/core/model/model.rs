@@ -1,117 +1,71 @@
-use std::{collections::HashMap, rc::Rc};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    sync::atomic::Ordering,
+};
 
-use cgmath::{EuclideanSpace, InnerSpace, Matrix4, Point3, SquareMatrix, Vector3, Vector4, Zero};
-use russimp::{
-    material::{DataContent, TextureType},
-    node::Node,
-    scene::{PostProcess, Scene},
+use cgmath::{
+    EuclideanSpace, InnerSpace, Matrix3, Matrix4, Point3, Quaternion, SquareMatrix, Vector3,
+    Vector4, Zero,
 };
 
 use crate::core::renderer::{
+    environment::include_environment,
+    light::light_pass::include_lights,
     line::{Line, LineRenderer},
-    shader::Shader,
-    texture::Texture,
+    shader::{Shader, ShaderPermutations},
+    shader_manager::ShaderSource,
+    transparency::TransparencyQueue,
 };
 
-use super::{Bone, Model, ModelBuilder, ModelMesh, Pose};
-use crate::core::utils::ToMatrix4;
+use super::{
+    include_bone_palette, Bone, BonePaletteBuffer, Material, MeshMotion, Model, ModelBuilder,
+    ModelData, ModelHandle, ModelMaterial, ModelMesh, Pose, Skeleton, SkeletonLod, SkinningMethod,
+    BONE_PALETTE_BINDING, FORCE_LINEAR_SKINNING, PREVIOUS_BONE_PALETTE_BINDING,
+};
 
 impl Model {
+    /// Loads and uploads the model file at `path` inside `models` of the asset search paths,
+    /// blocking until it is
+    /// parsed. See `ModelBuilder::build_async` to load it in the background
     pub fn new<P: Into<Point3<f32>>>(
         path: &str,
         position: P,
     ) -> Result<Model, Box<dyn std::error::Error>> {
-        let scene = Scene::from_file(
-            format!("assets/models/{path}").as_str(),
-            vec![
-                PostProcess::Triangulate,
-                // PostProcess::JoinIdenticalVertices,
-                PostProcess::GenerateSmoothNormals,
-                PostProcess::FlipUVs,
-            ],
-        )?;
-        let shader: Shader =
-            Shader::new(include_str!("vertex.glsl"), include_str!("fragment.glsl"));
-        Ok(Model {
-            model: scene,
-            meshes: HashMap::<String, ModelMesh>::new(),
-            shader,
-            textures: HashMap::<TextureType, Texture>::new(),
-            position: position.into(),
-            scale: 0.01,
-        })
+        Ok(Model::from_data(&ModelData::load(path)?, position))
     }
 
-    pub fn init(&mut self) {
-        let materials = &self.model.materials;
-        for material in materials {
-            for (tex_type, texture) in &material.textures {
-                let tex = texture.borrow();
-                if let DataContent::Bytes(texture_data) = &tex.data {
-                    let data = image::load_from_memory(texture_data.as_slice()).unwrap();
-                    let texture = Texture::new();
-                    texture.load_from_data(data.width(), data.height(), data.to_rgba8().into_raw());
-                    self.textures.insert(tex_type.clone(), texture);
-                }
-            }
-        }
-        let texture_coords: Vec<f32> = self.model.meshes[0]
-            .texture_coords
+    /// Uploads the meshes and textures of a parsed model file
+    pub fn from_data<P: Into<Point3<f32>>>(data: &ModelData, position: P) -> Model {
+        let shaders = ShaderPermutations::new(
+            ShaderSource::new("model/vertex.glsl", include_str!("vertex.glsl"))
+                .preprocess(include_bone_palette),
+            ShaderSource::new("model/fragment.glsl", include_str!("fragment.glsl"))
+                .preprocess(|source| include_lights(&include_environment(source))),
+        );
+        let materials = data.materials.iter().map(Material::from_data).collect();
+        let meshes = data
+            .meshes
             .iter()
-            .flat_map(|tx| {
-                if let Some(tx) = tx {
-                    let coords: Vec<f32> = tx.iter().flat_map(|v| vec![v.x, v.y]).collect();
-                    coords
-                } else {
-                    Vec::<f32>::new()
-                }
-            })
+            .map(|(name, mesh)| (name.clone(), ModelMesh::from_data(mesh)))
             .collect();
-        for mesh in &self.model.meshes {
-            let mut root_bone = None;
-            if let Some(root_node) = &self.model.root {
-                for node in root_node.children.borrow().iter() {
-                    for (id, bone) in mesh.bones.iter().enumerate() {
-                        if bone.name != node.name {
-                            continue;
-                        }
-                        root_bone = Some(Bone {
-                            id,
-                            name: bone.name.clone(),
-                            current_transform: node.transformation.to_matrix_4(),
-                            offset_matrix: bone.offset_matrix.to_matrix_4(),
-                            weights: bone
-                                .weights
-                                .iter()
-                                .map(|w| (w.vertex_id, w.weight))
-                                .collect(),
-                            children: self.get_child_bones(node, &mesh.bones, Matrix4::identity()),
-                            last_translation: Vector3::zero(),
-                        });
-                    }
-                }
-            }
-            let mut model_mesh = ModelMesh::new(
-                mesh.vertices
-                    .iter()
-                    .flat_map(|v| vec![v.x, v.y, v.z])
-                    .collect(),
-                mesh.faces
-                    .iter()
-                    .flat_map(|f| vec![f.0[0], f.0[1], f.0[2]])
-                    .collect::<Vec<u32>>(),
-                mesh.normals
-                    .iter()
-                    .flat_map(|v| vec![v.x, v.y, v.z])
-                    .collect(),
-                texture_coords.clone(),
-                root_bone,
-            );
-            model_mesh.buffer_data();
-            self.meshes.insert(mesh.name.clone(), model_mesh);
+        Model {
+            skeleton: data.skeleton.clone(),
+            meshes,
+            shaders,
+            materials,
+            position: position.into(),
+            scale: data.scale,
+            skinning: SkinningMethod::Linear,
+            skeleton_lod: None,
+            lod: 0,
+            previous_motion: RefCell::new(HashMap::new()),
+            bone_palette: BonePaletteBuffer::new(BONE_PALETTE_BINDING),
+            previous_bone_palette: BonePaletteBuffer::new(PREVIOUS_BONE_PALETTE_BINDING),
         }
     }
 
+    /// Renders the opaque meshes, the transparent ones are drawn through `queue_transparent`
     pub fn render(
         &self,
         light_position: &Point3<f32>,
@@ -119,49 +73,105 @@ impl Model {
         camera_projection: &Matrix4<f32>,
     ) {
         for mesh in self.meshes.values() {
-            if !mesh.is_buffered() {
-                panic!("Mesh is not buffered");
+            if !self.materials[mesh.material].is_transparent() {
+                self.render_mesh(mesh, light_position, parent_transform, camera_projection);
             }
-            self.shader.bind();
-            self.shader.set_uniform_3f(
-                "lightPosition",
-                light_position.x,
-                light_position.y,
-                light_position.z,
-            );
-            self.shader
-                .set_uniform_mat4("viewProjection", &camera_projection);
-            if let Some(root_bone) = &mesh.root_bone {
-                let mut bone_transforms =
-                    Model::get_bone_transformations(root_bone, Matrix4::identity());
-                bone_transforms.sort_by(|a, b| a.0.cmp(&b.0));
-                let sorted_bone_transforms = bone_transforms.iter().map(|(_, m)| m);
-                let sorted: Vec<Matrix4<f32>> = Vec::from_iter(sorted_bone_transforms.cloned());
-                self.shader
-                    .set_uniform_mat4_array("boneTransforms", &sorted);
+        }
+    }
+
+    /// Queues a draw of each transparent mesh, sorted by the position of the model
+    pub fn queue_transparent<'a>(
+        &'a self,
+        light_position: Point3<f32>,
+        parent_transform: Matrix4<f32>,
+        camera_projection: Matrix4<f32>,
+        queue: &mut TransparencyQueue<'a>,
+    ) {
+        let position =
+            Point3::from_vec((parent_transform * self.position.to_homogeneous()).truncate());
+        for mesh in self.meshes.values() {
+            if self.materials[mesh.material].is_transparent() {
+                queue.push(position, move || {
+                    self.render_mesh(mesh, &light_position, &parent_transform, &camera_projection)
+                });
             }
-            for (i, (texture_type, texture)) in self.textures.iter().enumerate() {
-                unsafe { gl::ActiveTexture(gl::TEXTURE0 + i as u32) };
-                texture.bind();
-                match texture_type {
-                    TextureType::Diffuse => self.shader.set_uniform_1i("texture_diffuse", i as i32),
-                    TextureType::Shininess => {
-                        self.shader.set_uniform_1i("texture_shininess", i as i32)
-                    }
-                    TextureType::Normals => self.shader.set_uniform_1i("texture_normal", i as i32),
-                    TextureType::Specular => {
-                        self.shader.set_uniform_1i("texture_specular", i as i32)
-                    }
-                    _ => {}
-                }
+        }
+    }
+
+    fn render_mesh(
+        &self,
+        mesh: &ModelMesh,
+        light_position: &Point3<f32>,
+        parent_transform: &Matrix4<f32>,
+        camera_projection: &Matrix4<f32>,
+    ) {
+        if !mesh.is_buffered() {
+            panic!("Mesh is not buffered");
+        }
+        let material = &self.materials[mesh.material];
+        let shader = self.shaders.get(&material.get_shader_defines());
+        shader.bind();
+        shader.set_uniform_3f(
+            "lightPosition",
+            light_position.x,
+            light_position.y,
+            light_position.z,
+        );
+        shader.set_uniform_mat4("viewProjection", &camera_projection);
+        material.bind(&shader);
+        let skinning = self.get_active_skinning();
+        shader.set_uniform_1i("skinning", skinning as i32);
+        self.bone_palette
+            .upload(&self.get_bone_palette(mesh), skinning);
+        unsafe { gl::Disable(gl::CULL_FACE) };
+        mesh.render(
+            &shader,
+            parent_transform * Matrix4::from_translation(self.position.to_vec().into()),
+            Some(self.scale),
+            self.lod,
+        );
+        unsafe { gl::Enable(gl::CULL_FACE) };
+    }
+
+    /// Renders the screen space motion of the meshes since the previous call with a velocity shader
+    pub fn render_velocity(
+        &self,
+        shader: &Shader,
+        parent_transform: &Matrix4<f32>,
+        view_projection: &Matrix4<f32>,
+        previous_view_projection: &Matrix4<f32>,
+    ) {
+        let transform = parent_transform * Matrix4::from_translation(self.position.to_vec());
+        let model = transform * Matrix4::from_scale(self.scale);
+        let mut previous_motion = self.previous_motion.borrow_mut();
+        shader.bind();
+        shader.set_uniform_mat4("viewProjection", view_projection);
+        shader.set_uniform_mat4("previousViewProjection", previous_view_projection);
+        for (name, mesh) in self.meshes.iter() {
+            if !mesh.is_buffered() {
+                continue;
             }
-            unsafe { gl::Disable(gl::CULL_FACE) };
-            mesh.render(
-                &self.shader,
-                parent_transform * Matrix4::from_translation(self.position.to_vec().into()),
-                Some(self.scale),
+            let bone_transforms = self.get_bone_palette(mesh);
+            // a palette of another LOD level is ordered differently
+            let previous = previous_motion
+                .get(name)
+                .filter(|previous| previous.lod == self.lod);
+            shader.set_uniform_mat4("previousModel", previous.map_or(&model, |m| &m.model));
+            self.bone_palette
+                .upload(&bone_transforms, SkinningMethod::Linear);
+            self.previous_bone_palette.upload(
+                previous.map_or(&bone_transforms, |m| &m.bone_transforms),
+                SkinningMethod::Linear,
+            );
+            mesh.render(shader, transform, Some(self.scale), self.lod);
+            previous_motion.insert(
+                name.clone(),
+                MeshMotion {
+                    model,
+                    bone_transforms,
+                    lod: self.lod,
+                },
             );
-            unsafe { gl::Enable(gl::CULL_FACE) };
         }
     }
 
@@ -183,6 +193,125 @@ impl Model {
         );
     }
 
+    /// Transform of the bone `bone` in its current pose relative to the model's entity, used as a
+    /// socket to attach other entities to. Includes the scale of the model
+    pub fn get_socket_transform(&self, bone: &str) -> Option<Matrix4<f32>> {
+        let root =
+            Matrix4::from_translation(self.position.to_vec()) * Matrix4::from_scale(self.scale);
+        self.meshes
+            .values()
+            .find_map(|mesh| mesh.root_bone.as_ref()?.get_global_transform(bone, root))
+    }
+
+    /// Transform of the bone `bone` in its current pose in world space, for the model's entity
+    /// at `entity_transform`. See `Scene::get_bone_world_transform` to look it up by entity
+    pub fn get_bone_world_transform(
+        &self,
+        bone: &str,
+        entity_transform: &Matrix4<f32>,
+    ) -> Option<Matrix4<f32>> {
+        self.get_socket_transform(bone)
+            .map(|socket| entity_transform * socket)
+    }
+
+    /// Names of the bones of the meshes that can be attached to, sorted
+    pub fn get_bone_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .meshes
+            .values()
+            .filter_map(|mesh| mesh.root_bone.as_ref())
+            .flat_map(Bone::get_as_vec)
+            .map(|bone| bone.name)
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    /// Corners of the box around the vertices in their bind pose, relative to the model's entity
+    pub fn get_bounds(&self) -> (Point3<f32>, Point3<f32>) {
+        let mut vertices = self
+            .meshes
+            .values()
+            .flat_map(|mesh| mesh.vertices.iter())
+            .map(|vertex| {
+                let (x, y, z) = vertex.position;
+                self.position + Vector3::new(x, y, z) * self.scale
+            });
+        let Some(first) = vertices.next() else {
+            return (self.position, self.position);
+        };
+        vertices.fold((first, first), |(min, max), vertex| {
+            (
+                Point3::new(
+                    min.x.min(vertex.x),
+                    min.y.min(vertex.y),
+                    min.z.min(vertex.z),
+                ),
+                Point3::new(
+                    max.x.max(vertex.x),
+                    max.y.max(vertex.y),
+                    max.z.max(vertex.z),
+                ),
+            )
+        })
+    }
+
+    /// Skeleton of the model in its bind pose, to retarget animations onto
+    pub fn get_skeleton(&self) -> Skeleton {
+        self.skeleton.clone()
+    }
+
+    /// Roughness of the first material
+    pub fn get_roughness(&self) -> f32 {
+        self.materials[0].parameters.roughness
+    }
+
+    /// Sets the roughness of every material
+    pub fn set_roughness(&mut self, roughness: f32) {
+        for material in self.materials.iter_mut() {
+            material.parameters.roughness = roughness.clamp(0.0, 1.0);
+        }
+    }
+
+    /// Parameters of the first material
+    pub fn get_material(&self) -> ModelMaterial {
+        self.materials[0].parameters
+    }
+
+    /// Replaces the parameters of every material, the textures are kept
+    pub fn set_material(&mut self, material: ModelMaterial) {
+        for existing in self.materials.iter_mut() {
+            existing.parameters = material;
+        }
+    }
+
+    /// Materials the meshes refer to, every model has at least one
+    pub fn get_materials(&self) -> &Vec<Material> {
+        &self.materials
+    }
+
+    pub fn get_materials_mut(&mut self) -> &mut Vec<Material> {
+        &mut self.materials
+    }
+
+    pub fn get_skinning(&self) -> SkinningMethod {
+        self.skinning
+    }
+
+    pub fn set_skinning(&mut self, skinning: SkinningMethod) {
+        self.skinning = skinning;
+    }
+
+    /// Skinning method the model is drawn with, `FORCE_LINEAR_SKINNING` overrides the model's own
+    fn get_active_skinning(&self) -> SkinningMethod {
+        if FORCE_LINEAR_SKINNING.load(Ordering::Relaxed) {
+            SkinningMethod::Linear
+        } else {
+            self.skinning
+        }
+    }
+
     pub fn reset_position(&mut self) -> Vector3<f32> {
         let position = self.position;
         self.position = Point3::new(0.0, 0.0, 0.0);
@@ -192,9 +321,7 @@ impl Model {
     pub fn apply_pose(&mut self, pose: &Pose) {
         let mut root_translation = Vector3::zero();
         for mesh in self.meshes.values_mut() {
-            if let Some(root_bone) = &mut mesh.root_bone {
-                root_translation += root_bone.apply_pose(pose, true);
-            }
+            root_translation += mesh.apply_pose(pose, self.lod);
         }
         self.position += root_translation * self.scale;
     }
@@ -217,58 +344,95 @@ impl Model {
         lines
     }
 
-    fn get_child_bones(
-        &self,
-        node: &Rc<Node>,
-        bones: &Vec<russimp::bone::Bone>,
-        offset_matrix: Matrix4<f32>,
-    ) -> Option<Vec<Bone>> {
-        if node.children.borrow().len() == 0 {
-            return None;
+    /// Sets the distances from which minor bones are collapsed and weights the meshes for them,
+    /// None poses all bones at any distance. Attachments to collapsed bones stop following
+    /// the animation
+    pub fn set_skeleton_lod(&mut self, skeleton_lod: Option<SkeletonLod>) {
+        for mesh in self.meshes.values_mut() {
+            mesh.build_lods(skeleton_lod.as_ref());
+        }
+        self.skeleton_lod = skeleton_lod;
+        self.lod = 0;
+    }
+
+    pub fn get_skeleton_lod(&self) -> Option<&SkeletonLod> {
+        self.skeleton_lod.as_ref()
+    }
+
+    /// Picks the level of the skeleton LOD for a camera `distance` away
+    pub fn update_lod(&mut self, distance: f32) {
+        if let Some(skeleton_lod) = &self.skeleton_lod {
+            self.lod = skeleton_lod.get_level(distance);
         }
-        let mut children = Vec::<Bone>::new();
-        for child in node.children.borrow().iter() {
-            if bones.iter().any(|b| b.name == child.name) {
-                for (id, bone) in bones.iter().enumerate() {
-                    if bone.name != child.name {
-                        continue;
-                    }
-                    children.push(Bone {
-                        id,
-                        name: bone.name.clone(),
-                        current_transform: offset_matrix * child.transformation.to_matrix_4(),
-                        offset_matrix: bone.offset_matrix.to_matrix_4(),
-                        weights: bone
-                            .weights
-                            .iter()
-                            .map(|w| (w.vertex_id, w.weight))
-                            .collect(),
-                        children: self.get_child_bones(child, bones, Matrix4::identity()),
-                        last_translation: Vector3::zero(),
-                    });
-                }
-            } else if let Some(child_bones) = self.get_child_bones(
-                child,
-                bones,
-                offset_matrix * child.transformation.to_matrix_4(),
-            ) {
-                children.extend(child_bones);
+    }
+
+    /// Level of the skeleton LOD the model is drawn with, 0 when all bones are posed
+    pub fn get_lod(&self) -> usize {
+        self.lod
+    }
+
+    /// Bone transforms the vertices of `mesh` refer to at the current level of the skeleton LOD
+    fn get_bone_palette(&self, mesh: &ModelMesh) -> Vec<Matrix4<f32>> {
+        let Some(root_bone) = &mesh.root_bone else {
+            // static meshes are bound to a single bone that doesn't move them
+            return vec![Matrix4::identity()];
+        };
+        match mesh.get_lod(self.lod) {
+            Some(mesh_lod) => {
+                Model::get_sorted_bone_transformations(root_bone, &mesh_lod.collapsed)
             }
+            None => Model::get_sorted_bone_transformations(root_bone, &HashSet::new()),
         }
-        Some(children)
+    }
+
+    /// Bone transformations ordered by the bone ids the vertices refer to, without the bones in
+    /// `collapsed` and their children
+    fn get_sorted_bone_transformations(
+        root_bone: &Bone,
+        collapsed: &HashSet<usize>,
+    ) -> Vec<Matrix4<f32>> {
+        let mut bone_transforms =
+            Model::get_bone_transformations(root_bone, Matrix4::identity(), collapsed);
+        bone_transforms.sort_by_key(|(id, _)| *id);
+        bone_transforms
+            .into_iter()
+            .map(|(_, transform)| transform)
+            .collect()
+    }
+
+    /// Converts a bone transform into a unit dual quaternion, laid out as the rotation followed by
+    /// the dual part, each as x, y, z, w
+    pub(super) fn to_dual_quaternion(transform: &Matrix4<f32>) -> [f32; 8] {
+        let rotation = Matrix3::from_cols(
+            transform.x.truncate().normalize(),
+            transform.y.truncate().normalize(),
+            transform.z.truncate().normalize(),
+        );
+        let real = Quaternion::from(rotation).normalize();
+        let dual = Quaternion::from_sv(0.0, transform.w.truncate()) * real * 0.5;
+        [
+            real.v.x, real.v.y, real.v.z, real.s, dual.v.x, dual.v.y, dual.v.z, dual.s,
+        ]
     }
 
     fn get_bone_transformations(
         bone: &Bone,
         parent_transform: Matrix4<f32>,
+        collapsed: &HashSet<usize>,
     ) -> Vec<(usize, Matrix4<f32>)> {
         let mut transformations = Vec::<(usize, Matrix4<f32>)>::new();
         let global_transformation = parent_transform * bone.current_transform;
         transformations.push((bone.id, global_transformation * bone.offset_matrix));
         if let Some(children) = &bone.children {
-            for child in children {
-                transformations
-                    .extend(Self::get_bone_transformations(child, global_transformation));
+            for child in children
+                .iter()
+                .filter(|child| !collapsed.contains(&child.id))
+            {
+                transformations.extend(Self::get_bone_transformations(
+                    child,
+                    global_transformation,
+                    collapsed,
+                ));
             }
         }
         transformations
@@ -276,18 +440,68 @@ impl Model {
 }
 
 impl ModelBuilder {
-    pub fn new(path: &str) -> Result<ModelBuilder, Box<dyn std::error::Error>> {
-        Ok(ModelBuilder {
-            model: Model::new(path, (0.0, 0.0, 0.0))?,
-        })
+    pub fn new(path: &str) -> ModelBuilder {
+        ModelBuilder {
+            path: path.to_owned(),
+            position: Point3::new(0.0, 0.0, 0.0),
+            roughness: None,
+            skinning: SkinningMethod::Linear,
+            skeleton_lod: None,
+        }
     }
 
     pub fn with_position<P: Into<Point3<f32>>>(mut self, position: P) -> ModelBuilder {
-        self.model.position = position.into();
+        self.position = position.into();
+        self
+    }
+
+    /// Replaces the roughness of the file's material
+    pub fn with_roughness(mut self, roughness: f32) -> ModelBuilder {
+        self.roughness = Some(roughness.clamp(0.0, 1.0));
+        self
+    }
+
+    pub fn with_skinning(mut self, skinning: SkinningMethod) -> ModelBuilder {
+        self.skinning = skinning;
         self
     }
 
-    pub fn build(self) -> Model {
-        self.model
+    /// Collapses minor bones at a distance, see `Model::set_skeleton_lod`
+    pub fn with_skeleton_lod(mut self, skeleton_lod: SkeletonLod) -> ModelBuilder {
+        self.skeleton_lod = Some(skeleton_lod);
+        self
+    }
+
+    /// Loads the model, blocking until the file is parsed
+    pub fn build(self) -> Result<Model, Box<dyn std::error::Error>> {
+        Ok(self.apply(Model::new(&self.path, self.position)?))
+    }
+
+    /// Returns right away and loads the model on a worker thread, see `ModelHandle`
+    pub fn build_async(self) -> ModelHandle {
+        ModelHandle::load(self)
+    }
+
+    pub(super) fn apply(&self, mut model: Model) -> Model {
+        if let Some(roughness) = self.roughness {
+            model.set_roughness(roughness);
+        }
+        model.skinning = self.skinning;
+        if self.skeleton_lod.is_some() {
+            model.set_skeleton_lod(self.skeleton_lod.clone());
+        }
+        model
+    }
+}
+
+impl Default for ModelMaterial {
+    fn default() -> Self {
+        Self {
+            base_color: Vector4::new(1.0, 1.0, 1.0, 1.0),
+            metallic: 0.0,
+            roughness: 1.0,
+            emissive: Vector3::zero(),
+            transparent: false,
+        }
     }
 }
@@ -0,0 +1,184 @@
+use std::{path::Path, rc::Rc, sync::Arc};
+
+use cgmath::{Matrix4, SquareMatrix, Vector3, Zero};
+use russimp::{
+    material::DataContent,
+    node::Node,
+    scene::{PostProcess, Scene},
+};
+
+use crate::core::{
+    asset::{Asset, AssetManager},
+    utils::ToMatrix4,
+};
+
+use super::{
+    gltf_import::is_gltf, Bone, MaterialData, MaterialSlot, ModelData, ModelMeshData, Skeleton,
+};
+
+impl Asset for ModelData {
+    fn load(path: &Path) -> Result<Self, Box<dyn std::error::Error>> {
+        ModelData::parse(&path.to_string_lossy())
+    }
+}
+
+impl ModelData {
+    /// File of the model `path` inside the `models` directory of the asset search paths
+    pub(super) fn get_file_path(path: &str) -> String {
+        AssetManager::resolve(&ModelData::get_asset_path(path))
+            .to_string_lossy()
+            .into_owned()
+    }
+
+    fn get_asset_path(path: &str) -> String {
+        format!("models/{path}")
+    }
+
+    /// The model file at `path` inside `models` of the asset search paths, parsed unless it is
+    /// still in use by another load
+    pub fn load(path: &str) -> Result<Arc<ModelData>, Box<dyn std::error::Error>> {
+        AssetManager::load(&ModelData::get_asset_path(path))
+    }
+
+    /// Parses the model file at `file`
+    fn parse(file: &str) -> Result<ModelData, Box<dyn std::error::Error>> {
+        if is_gltf(file) {
+            return ModelData::load_gltf(file);
+        }
+        let scene = Scene::from_file(
+            file,
+            vec![
+                PostProcess::Triangulate,
+                // PostProcess::JoinIdenticalVertices,
+                PostProcess::GenerateSmoothNormals,
+                PostProcess::FlipUVs,
+            ],
+        )?;
+
+        let mut materials = Vec::new();
+        for material in &scene.materials {
+            let mut material_data = MaterialData::default();
+            for (tex_type, texture) in &material.textures {
+                let Some(slot) = MaterialSlot::from_texture_type(*tex_type) else {
+                    continue;
+                };
+                let tex = texture.borrow();
+                if let DataContent::Bytes(texture_data) = &tex.data {
+                    let data = image::load_from_memory(texture_data.as_slice())?;
+                    material_data.add_texture(slot, data.to_rgba8());
+                }
+            }
+            materials.push(material_data);
+        }
+        if materials.is_empty() {
+            materials.push(MaterialData::default());
+        }
+        let texture_coords: Vec<f32> = scene
+            .meshes
+            .first()
+            .map(|mesh| {
+                mesh.texture_coords
+                    .iter()
+                    .flatten()
+                    .flat_map(|tx| tx.iter().flat_map(|v| vec![v.x, v.y]))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let mut meshes = Vec::new();
+        for mesh in &scene.meshes {
+            let mut root_bone = None;
+            if let Some(root_node) = &scene.root {
+                for node in root_node.children.borrow().iter() {
+                    for (id, bone) in mesh.bones.iter().enumerate() {
+                        if bone.name != node.name {
+                            continue;
+                        }
+                        root_bone = Some(Bone {
+                            id,
+                            name: bone.name.clone(),
+                            current_transform: node.transformation.to_matrix_4(),
+                            offset_matrix: bone.offset_matrix.to_matrix_4(),
+                            weights: bone
+                                .weights
+                                .iter()
+                                .map(|w| (w.vertex_id, w.weight))
+                                .collect(),
+                            children: ModelData::get_child_bones(
+                                node,
+                                &mesh.bones,
+                                Matrix4::identity(),
+                            ),
+                            last_translation: Vector3::zero(),
+                        });
+                    }
+                }
+            }
+            let mut model_mesh = ModelMeshData::new(
+                mesh.vertices
+                    .iter()
+                    .flat_map(|v| vec![v.x, v.y, v.z])
+                    .collect(),
+                mesh.faces
+                    .iter()
+                    .flat_map(|f| vec![f.0[0], f.0[1], f.0[2]])
+                    .collect::<Vec<u32>>(),
+                mesh.normals
+                    .iter()
+                    .flat_map(|v| vec![v.x, v.y, v.z])
+                    .collect(),
+                texture_coords.clone(),
+                root_bone,
+            );
+            model_mesh.material = (mesh.material_index as usize).min(materials.len() - 1);
+            meshes.push((mesh.name.clone(), model_mesh));
+        }
+
+        Ok(ModelData {
+            meshes,
+            materials,
+            skeleton: Skeleton::new(&scene),
+            // the models are authored in centimeters
+            scale: 0.01,
+        })
+    }
+
+    fn get_child_bones(
+        node: &Rc<Node>,
+        bones: &Vec<russimp::bone::Bone>,
+        offset_matrix: Matrix4<f32>,
+    ) -> Option<Vec<Bone>> {
+        if node.children.borrow().is_empty() {
+            return None;
+        }
+        let mut children = Vec::<Bone>::new();
+        for child in node.children.borrow().iter() {
+            if bones.iter().any(|b| b.name == child.name) {
+                for (id, bone) in bones.iter().enumerate() {
+                    if bone.name != child.name {
+                        continue;
+                    }
+                    children.push(Bone {
+                        id,
+                        name: bone.name.clone(),
+                        current_transform: offset_matrix * child.transformation.to_matrix_4(),
+                        offset_matrix: bone.offset_matrix.to_matrix_4(),
+                        weights: bone
+                            .weights
+                            .iter()
+                            .map(|w| (w.vertex_id, w.weight))
+                            .collect(),
+                        children: ModelData::get_child_bones(child, bones, Matrix4::identity()),
+                        last_translation: Vector3::zero(),
+                    });
+                }
+            } else if let Some(child_bones) = ModelData::get_child_bones(
+                child,
+                bones,
+                offset_matrix * child.transformation.to_matrix_4(),
+            ) {
+                children.extend(child_bones);
+            }
+        }
+        Some(children)
+    }
+}
@@ -0,0 +1,148 @@
+use std::{
+    fs,
+    sync::{
+        mpsc::{self, Receiver, TryRecvError},
+        Arc,
+    },
+    time::{Instant, SystemTime},
+};
+
+use crate::core::renderer::shader_manager::{ShaderManager, POLL_INTERVAL};
+
+use super::{Model, ModelBuilder, ModelData, ModelHandle, ModelState, ModelWatch};
+
+impl ModelHandle {
+    /// Starts loading the model of `builder` on a worker thread
+    pub fn load(builder: ModelBuilder) -> Self {
+        let watch = ModelHandle::is_watched().then(|| ModelWatch {
+            builder: builder.clone(),
+            modified: ModelHandle::get_modified(&builder.path),
+            last_check: Instant::now(),
+            reload: None,
+        });
+        Self {
+            state: ModelState::Loading {
+                receiver: ModelHandle::parse(&builder.path),
+                builder,
+            },
+            watch,
+        }
+    }
+
+    /// Whether the files of loaded models are watched, only in debug builds like shader files
+    pub fn is_watched() -> bool {
+        ShaderManager::is_enabled()
+    }
+
+    /// Uploads the model once the worker thread parsed it, returns it when it is loaded. Called
+    /// once per frame on the thread with the gl context
+    pub fn poll(&mut self) -> Option<&mut Model> {
+        if let ModelState::Loading { receiver, builder } = &self.state {
+            match receiver.try_recv() {
+                Ok(Ok(data)) => {
+                    let model = builder.apply(Model::from_data(&data, builder.position));
+                    self.state = ModelState::Loaded(Box::new(model));
+                }
+                Ok(Err(err)) => {
+                    log::error!("Failed to load model {}: {}", builder.path, err);
+                    self.state = ModelState::Failed;
+                }
+                Err(TryRecvError::Disconnected) => {
+                    log::error!("Failed to load model {}: the loader stopped", builder.path);
+                    self.state = ModelState::Failed;
+                }
+                Err(TryRecvError::Empty) => {}
+            }
+        } else {
+            self.poll_reload();
+        }
+        self.get_mut()
+    }
+
+    /// Parses the file again once it changed on disk and swaps the model when it is uploaded.
+    /// The model is replaced as a whole with the settings of its builder, a file that fails to
+    /// load keeps the previous model
+    fn poll_reload(&mut self) {
+        let Some(watch) = &mut self.watch else {
+            return;
+        };
+        let path = &watch.builder.path;
+        if let Some(receiver) = &watch.reload {
+            match receiver.try_recv() {
+                Ok(Ok(data)) => {
+                    let model = watch
+                        .builder
+                        .apply(Model::from_data(&data, watch.builder.position));
+                    self.state = ModelState::Loaded(Box::new(model));
+                    log::info!("Reloaded model {}", path);
+                }
+                Ok(Err(err)) => log::warn!(
+                    "Reloading model {} failed, keeping the previous version\n{}",
+                    path,
+                    err
+                ),
+                Err(TryRecvError::Disconnected) => {
+                    log::warn!("Reloading model {} failed: the loader stopped", path)
+                }
+                Err(TryRecvError::Empty) => return,
+            }
+            watch.reload = None;
+            return;
+        }
+        if watch.last_check.elapsed() < POLL_INTERVAL {
+            return;
+        }
+        watch.last_check = Instant::now();
+        let modified = ModelHandle::get_modified(path);
+        if modified.is_none() || modified == watch.modified {
+            return;
+        }
+        watch.modified = modified;
+        watch.reload = Some(ModelHandle::parse(path));
+    }
+
+    /// Parses the model file at `path` on a worker thread
+    fn parse(path: &str) -> Receiver<Result<Arc<ModelData>, String>> {
+        let (sender, receiver) = mpsc::channel();
+        let path = path.to_string();
+        std::thread::spawn(move || {
+            // the error is not Send, only its message is passed on
+            let _ = sender.send(ModelData::load(&path).map_err(|err| err.to_string()));
+        });
+        receiver
+    }
+
+    fn get_modified(path: &str) -> Option<SystemTime> {
+        fs::metadata(ModelData::get_file_path(path))
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    }
+
+    /// The model, None while it is loading or if it failed to load
+    pub fn get(&self) -> Option<&Model> {
+        match &self.state {
+            ModelState::Loaded(model) => Some(model),
+            _ => None,
+        }
+    }
+
+    pub fn get_mut(&mut self) -> Option<&mut Model> {
+        match &mut self.state {
+            ModelState::Loaded(model) => Some(model),
+            _ => None,
+        }
+    }
+
+    pub fn is_loading(&self) -> bool {
+        matches!(self.state, ModelState::Loading { .. })
+    }
+}
+
+impl From<Model> for ModelHandle {
+    fn from(model: Model) -> Self {
+        Self {
+            state: ModelState::Loaded(Box::new(model)),
+            watch: None,
+        }
+    }
+}
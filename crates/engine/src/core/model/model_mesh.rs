@@ -1,83 +1,132 @@
-use cgmath::Matrix4;
+use std::collections::HashSet;
 
-use crate::core::renderer::shader::{DynamicVertexArray, Shader, VertexAttributes};
+use cgmath::{Matrix4, Vector3, Zero};
 
-use super::{Bone, ModelMesh, ModelMeshVertex};
+use crate::core::{
+    profiler::Profiler,
+    renderer::{
+        gl_debug,
+        shader::{DynamicVertexArray, Shader, VertexAttributes},
+    },
+};
 
-impl ModelMesh {
+use super::{Bone, ModelMesh, ModelMeshData, ModelMeshVertex, Pose};
+
+impl ModelMeshData {
     pub fn new(
         vertices: Vec<f32>,
         indices: Vec<u32>,
         normals: Vec<f32>,
         texture_coords: Vec<f32>,
         root_bone: Option<Bone>,
-    ) -> ModelMesh {
+    ) -> ModelMeshData {
         let mut mesh_vertices = Vec::<ModelMeshVertex>::new();
-        if let Some(root_bone) = &root_bone {
-            let bone_weights = ModelMesh::get_bone_weights(root_bone.clone());
-            for i in 0..vertices.len() / 3 {
-                let weights = &bone_weights[i];
-                mesh_vertices.push(ModelMeshVertex {
-                    position: (vertices[i * 3], vertices[i * 3 + 1], vertices[i * 3 + 2]),
-                    normal: (normals[i * 3], normals[i * 3 + 1], normals[i * 3 + 2]),
-                    texture_coords: (texture_coords[i * 2], texture_coords[i * 2 + 1]),
-                    bone_ids: (
-                        if weights.len() >= 1 {
-                            weights[0].0 as u32
-                        } else {
-                            0
-                        },
-                        if weights.len() >= 2 {
-                            weights[1].0 as u32
-                        } else {
-                            0
-                        },
-                        if weights.len() >= 3 {
-                            weights[2].0 as u32
-                        } else {
-                            0
-                        },
-                        if weights.len() >= 4 {
-                            weights[3].0 as u32
-                        } else {
-                            0
-                        },
-                    ),
-                    bone_weights: (
-                        if weights.len() >= 1 {
-                            weights[0].1
-                        } else {
-                            0.0
-                        },
-                        if weights.len() >= 2 {
-                            weights[1].1
-                        } else {
-                            0.0
-                        },
-                        if weights.len() >= 3 {
-                            weights[2].1
-                        } else {
-                            0.0
-                        },
-                        if weights.len() >= 4 {
-                            weights[3].1
-                        } else {
-                            0.0
-                        },
-                    ),
-                });
-            }
+        let bone_weights = match &root_bone {
+            Some(root_bone) => ModelMeshData::get_bone_weights(root_bone.clone()),
+            // static meshes follow a single bone that `Model::render` leaves in place
+            None => vec![vec![(0, 1.0)]; vertices.len() / 3],
+        };
+        for i in 0..vertices.len() / 3 {
+            let weights = bone_weights.get(i).map_or(&[][..], Vec::as_slice);
+            mesh_vertices.push(ModelMeshVertex {
+                position: (vertices[i * 3], vertices[i * 3 + 1], vertices[i * 3 + 2]),
+                normal: (normals[i * 3], normals[i * 3 + 1], normals[i * 3 + 2]),
+                texture_coords: (texture_coords[i * 2], texture_coords[i * 2 + 1]),
+                bone_ids: (
+                    if !weights.is_empty() {
+                        weights[0].0 as u32
+                    } else {
+                        0
+                    },
+                    if weights.len() >= 2 {
+                        weights[1].0 as u32
+                    } else {
+                        0
+                    },
+                    if weights.len() >= 3 {
+                        weights[2].0 as u32
+                    } else {
+                        0
+                    },
+                    if weights.len() >= 4 {
+                        weights[3].0 as u32
+                    } else {
+                        0
+                    },
+                ),
+                bone_weights: (
+                    if !weights.is_empty() {
+                        weights[0].1
+                    } else {
+                        0.0
+                    },
+                    if weights.len() >= 2 {
+                        weights[1].1
+                    } else {
+                        0.0
+                    },
+                    if weights.len() >= 3 {
+                        weights[2].1
+                    } else {
+                        0.0
+                    },
+                    if weights.len() >= 4 {
+                        weights[3].1
+                    } else {
+                        0.0
+                    },
+                ),
+            });
         }
-        ModelMesh {
+        ModelMeshData {
             root_bone,
             indices,
             vertices: mesh_vertices,
-            vertex_array: None,
+            material: 0,
+        }
+    }
+
+    fn get_bone_weights(root_bone: Bone) -> Vec<Vec<(usize, f32)>> {
+        let bones = root_bone.get_as_vec();
+        let mut bone_weights: Vec<Vec<(usize, f32)>> = Vec::new();
+        for bone in bones {
+            for weight in &bone.weights {
+                if weight.1 == 0.0 {
+                    continue;
+                }
+                if bone_weights.len() <= weight.0 as usize {
+                    bone_weights.resize(weight.0 as usize + 1, Vec::new());
+                }
+                bone_weights[weight.0 as usize].push((bone.id, weight.1));
+            }
         }
+        bone_weights
     }
+}
 
-    pub fn render(&self, shader: &Shader, position: Matrix4<f32>, scale: Option<f32>) {
-        if let Some(vertex_array) = &self.vertex_array {
+impl ModelMesh {
+    /// Uploads a copy of the parsed mesh `data`, which stays shared with the other models of its
+    /// file
+    pub fn from_data(data: &ModelMeshData) -> ModelMesh {
+        let mut vertex_array = DynamicVertexArray::<ModelMeshVertex>::new();
+        vertex_array.buffer_data(&data.vertices, &Some(data.indices.clone()));
+        ModelMesh {
+            root_bone: data.root_bone.clone(),
+            indices: data.indices.clone(),
+            vertices: data.vertices.clone(),
+            vertex_array: Some(vertex_array),
+            material: data.material,
+            lods: Vec::new(),
+        }
+    }
+
+    /// Draws the mesh skinned for the level `lod` of the model's skeleton LOD
+    pub fn render(&self, shader: &Shader, position: Matrix4<f32>, scale: Option<f32>, lod: usize) {
+        let vertex_array = match self.get_lod(lod) {
+            Some(mesh_lod) => Some(&mesh_lod.vertex_array),
+            None => self.vertex_array.as_ref(),
+        };
+        if let Some(vertex_array) = vertex_array {
             unsafe {
                 gl::Enable(gl::DEPTH_TEST);
                 gl::Enable(gl::CULL_FACE);
@@ -95,6 +144,8 @@ impl ModelMesh {
                     gl::UNSIGNED_INT,
                     std::ptr::null(),
                 );
+                Profiler::count_draw_call();
+                gl_debug::check_errors("ModelMesh::render");
                 DynamicVertexArray::<ModelMeshVertex>::unbind();
                 gl::Disable(gl::DEPTH_TEST);
                 gl::Disable(gl::CULL_FACE);
@@ -102,31 +153,19 @@ impl ModelMesh {
         }
     }
 
-    pub fn is_buffered(&self) -> bool {
-        self.vertex_array.is_some()
-    }
-
-    pub fn buffer_data(&mut self) {
-        let mut vertex_array = DynamicVertexArray::<ModelMeshVertex>::new();
-        vertex_array.buffer_data(&self.vertices, &Some(self.indices.clone()));
-        self.vertex_array = Some(vertex_array);
+    /// Poses the bones kept by the level `lod` of the skeleton LOD, returns the root motion
+    pub fn apply_pose(&mut self, pose: &Pose, lod: usize) -> Vector3<f32> {
+        let Some(root_bone) = &mut self.root_bone else {
+            return Vector3::zero();
+        };
+        match lod.checked_sub(1).and_then(|level| self.lods.get(level)) {
+            Some(mesh_lod) => root_bone.apply_pose(pose, true, &mesh_lod.collapsed),
+            None => root_bone.apply_pose(pose, true, &HashSet::new()),
+        }
     }
 
-    fn get_bone_weights(root_bone: Bone) -> Vec<Vec<(usize, f32)>> {
-        let bones = root_bone.get_as_vec();
-        let mut bone_weights: Vec<Vec<(usize, f32)>> = Vec::new();
-        for bone in bones {
-            for weight in &bone.weights {
-                if weight.1 == 0.0 {
-                    continue;
-                }
-                if bone_weights.len() <= weight.0 as usize {
-                    bone_weights.resize(weight.0 as usize + 1, Vec::new());
-                }
-                bone_weights[weight.0 as usize].push((bone.id, weight.1));
-            }
-        }
-        bone_weights
+    pub fn is_buffered(&self) -> bool {
+        self.vertex_array.is_some()
     }
 }
 
@@ -1,8 +1,8 @@
 use std::collections::HashMap;
 
-use cgmath::Matrix4;
+use cgmath::{InnerSpace, Matrix3, Matrix4, Quaternion, Vector3};
 
-use super::{LocalTransform, Pose};
+use super::{LocalTransform, Pose, Skeleton};
 
 impl LocalTransform {
     pub fn interpolate(&self, other: &LocalTransform, factor: f32) -> LocalTransform {
@@ -13,6 +13,25 @@ impl LocalTransform {
         }
     }
 
+    /// Splits a transform into translation, rotation and scale, shear is lost
+    pub fn from_matrix_4(matrix: &Matrix4<f32>) -> LocalTransform {
+        let scale = Vector3::new(
+            matrix.x.truncate().magnitude(),
+            matrix.y.truncate().magnitude(),
+            matrix.z.truncate().magnitude(),
+        );
+        let rotation = Matrix3::from_cols(
+            matrix.x.truncate() / scale.x,
+            matrix.y.truncate() / scale.y,
+            matrix.z.truncate() / scale.z,
+        );
+        LocalTransform {
+            translation: matrix.w.truncate(),
+            rotation: Quaternion::from(rotation).normalize(),
+            scale,
+        }
+    }
+
     pub fn to_matrix_4(&self) -> Matrix4<f32> {
         Matrix4::from_translation(self.translation)
             * Matrix4::from(self.rotation)
@@ -49,4 +68,12 @@ impl Pose {
     pub fn add_transform(&mut self, name: String, transform: LocalTransform) {
         self.transforms.insert(name, transform);
     }
+
+    /// Transform of `bone` in the pose, or its bind transform if the pose doesn't animate it
+    pub fn get_base_transform(&self, bone: &str, skeleton: &Skeleton) -> Option<LocalTransform> {
+        self.transforms
+            .get(bone)
+            .or_else(|| skeleton.get_bind(bone))
+            .cloned()
+    }
 }
@@ -0,0 +1,103 @@
+use std::{collections::HashMap, rc::Rc};
+
+use cgmath::{Matrix4, SquareMatrix};
+use russimp::{node::Node, scene::Scene};
+
+use crate::core::utils::ToMatrix4;
+
+use super::{gltf_import::is_gltf, BoneMap, LocalTransform, Skeleton, SkeletonBone};
+
+impl Skeleton {
+    pub fn new(scene: &Scene) -> Skeleton {
+        let mut bones = HashMap::new();
+        if let Some(root) = &scene.root {
+            Skeleton::add_node(root, None, &mut bones);
+        }
+        Skeleton { bones }
+    }
+
+    /// Loads the skeleton the animations of an animation file were authored on
+    pub fn from_file(path: &str) -> Result<Skeleton, Box<dyn std::error::Error>> {
+        if is_gltf(path) {
+            let (document, _, _) = gltf::import(format!("assets/animations/{path}"))?;
+            return Ok(Skeleton::from_gltf(&document));
+        }
+        let scene = Scene::from_file(format!("assets/animations/{path}").as_str(), vec![])?;
+        Ok(Skeleton::new(&scene))
+    }
+
+    fn add_node(node: &Rc<Node>, parent: Option<&str>, bones: &mut HashMap<String, SkeletonBone>) {
+        bones.insert(
+            node.name.clone(),
+            SkeletonBone {
+                parent: parent.map(str::to_string),
+                bind: LocalTransform::from_matrix_4(&node.transformation.to_matrix_4()),
+            },
+        );
+        for child in node.children.borrow().iter() {
+            Skeleton::add_node(child, Some(&node.name), bones);
+        }
+    }
+
+    pub fn contains(&self, bone: &str) -> bool {
+        self.bones.contains_key(bone)
+    }
+
+    pub fn get_parent(&self, bone: &str) -> Option<&str> {
+        self.bones.get(bone)?.parent.as_deref()
+    }
+
+    pub fn get_bind(&self, bone: &str) -> Option<&LocalTransform> {
+        self.bones.get(bone).map(|bone| &bone.bind)
+    }
+
+    /// Bind transform of the bone relative to the root of the skeleton
+    pub fn get_global_bind(&self, bone: &str) -> Matrix4<f32> {
+        match self.bones.get(bone) {
+            Some(SkeletonBone {
+                parent: Some(parent),
+                bind,
+            }) => self.get_global_bind(parent) * bind.to_matrix_4(),
+            Some(SkeletonBone { parent: None, bind }) => bind.to_matrix_4(),
+            None => Matrix4::identity(),
+        }
+    }
+
+    /// Bind transform of the bone's parent relative to the root of the skeleton
+    pub fn get_parent_global_bind(&self, bone: &str) -> Matrix4<f32> {
+        self.get_parent(bone)
+            .map_or(Matrix4::identity(), |parent| self.get_global_bind(parent))
+    }
+
+    /// Whether any ancestor of `bone` satisfies `predicate`
+    pub fn has_ancestor(&self, bone: &str, predicate: impl Fn(&str) -> bool) -> bool {
+        let mut current = self.get_parent(bone);
+        while let Some(parent) = current {
+            if predicate(parent) {
+                return true;
+            }
+            current = self.get_parent(parent);
+        }
+        false
+    }
+}
+
+impl BoneMap {
+    pub fn new() -> Self {
+        Self {
+            bones: HashMap::new(),
+        }
+    }
+
+    /// Retargets the `source` bone onto the `target` bone
+    pub fn with_bone(mut self, source: &str, target: &str) -> Self {
+        self.bones.insert(source.to_string(), target.to_string());
+        self
+    }
+
+    /// Target bone of the `source` bone, bones missing from the map are matched by their name
+    pub fn get_target<'a>(&'a self, source: &'a str, target: &Skeleton) -> Option<&'a str> {
+        let name = self.bones.get(source).map_or(source, String::as_str);
+        target.contains(name).then_some(name)
+    }
+}
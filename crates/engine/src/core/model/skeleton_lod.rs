@@ -0,0 +1,167 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::core::renderer::shader::DynamicVertexArray;
+
+use super::{Bone, MeshLod, ModelMesh, ModelMeshVertex, SkeletonLod, SkeletonLodLevel};
+
+impl SkeletonLod {
+    pub fn new() -> Self {
+        Self { levels: Vec::new() }
+    }
+
+    /// Collapses the bones with a name containing one of `patterns`, ignoring case, from a
+    /// camera distance of `distance` on
+    pub fn level(mut self, distance: f32, patterns: &[&str]) -> Self {
+        self.levels.push(SkeletonLodLevel {
+            distance,
+            collapsed: patterns
+                .iter()
+                .map(|pattern| pattern.to_lowercase())
+                .collect(),
+        });
+        self.levels
+            .sort_by(|a, b| a.distance.total_cmp(&b.distance));
+        self
+    }
+
+    /// Level used at a camera distance of `distance`, 0 keeps all bones
+    pub fn get_level(&self, distance: f32) -> usize {
+        self.levels
+            .iter()
+            .take_while(|level| distance >= level.distance)
+            .count()
+    }
+
+    pub fn get_levels(&self) -> &Vec<SkeletonLodLevel> {
+        &self.levels
+    }
+
+    /// Whether the level `level` or one before it collapses the bone `name`
+    fn is_collapsed(&self, level: usize, name: &str) -> bool {
+        let name = name.to_lowercase();
+        self.levels[..level]
+            .iter()
+            .flat_map(|level| level.collapsed.iter())
+            .any(|pattern| name.contains(pattern.as_str()))
+    }
+}
+
+impl Default for SkeletonLod {
+    /// Collapses fingers, toes and twist bones from 15 units on, and hands, feet and the neck
+    /// from 40 units on
+    fn default() -> Self {
+        SkeletonLod::new()
+            .level(
+                15.0,
+                &[
+                    "thumb", "index", "middle", "ring", "pinky", "finger", "toe", "twist",
+                ],
+            )
+            .level(40.0, &["hand", "foot", "neck"])
+    }
+}
+
+impl ModelMesh {
+    /// Weights the vertices for every level of `skeleton_lod` and uploads them, static meshes
+    /// have no levels
+    pub(super) fn build_lods(&mut self, skeleton_lod: Option<&SkeletonLod>) {
+        self.lods.clear();
+        let (Some(root_bone), Some(skeleton_lod)) = (&self.root_bone, skeleton_lod) else {
+            return;
+        };
+        for level in 1..=skeleton_lod.levels.len() {
+            let mut targets = HashMap::new();
+            let mut collapsed = HashSet::new();
+            ModelMesh::collect_lod_targets(
+                root_bone,
+                None,
+                &|bone| skeleton_lod.is_collapsed(level, &bone.name),
+                &mut targets,
+                &mut collapsed,
+            );
+            // the palette only holds the kept bones in the order of their ids
+            let mut kept: Vec<usize> = targets
+                .iter()
+                .filter(|(id, target)| id == target)
+                .map(|(id, _)| *id)
+                .collect();
+            kept.sort();
+            let palette_indices: HashMap<usize, u32> = kept
+                .iter()
+                .enumerate()
+                .map(|(index, id)| (*id, index as u32))
+                .collect();
+            let vertices: Vec<ModelMeshVertex> = self
+                .vertices
+                .iter()
+                .map(|vertex| {
+                    ModelMesh::remap_weights(vertex, |id| {
+                        let target = targets.get(&(id as usize)).copied().unwrap_or(id as usize);
+                        palette_indices.get(&target).copied().unwrap_or(0)
+                    })
+                })
+                .collect();
+            let mut vertex_array = DynamicVertexArray::<ModelMeshVertex>::new();
+            vertex_array.buffer_data(&vertices, &Some(self.indices.clone()));
+            self.lods.push(MeshLod {
+                vertex_array,
+                collapsed,
+            });
+        }
+    }
+
+    /// Skinning of the level `lod`, None for level 0 and static meshes
+    pub(super) fn get_lod(&self, lod: usize) -> Option<&MeshLod> {
+        self.lods.get(lod.checked_sub(1)?)
+    }
+
+    /// Maps the ids of `bone` and its children to the id of the bone they are skinned to, which
+    /// is the closest kept ancestor for the collapsed ones. The root bone is always kept
+    fn collect_lod_targets(
+        bone: &Bone,
+        parent: Option<usize>,
+        is_collapsed: &dyn Fn(&Bone) -> bool,
+        targets: &mut HashMap<usize, usize>,
+        collapsed: &mut HashSet<usize>,
+    ) {
+        let target = match parent {
+            Some(parent) if collapsed.contains(&parent) || is_collapsed(bone) => {
+                collapsed.insert(bone.id);
+                targets.get(&parent).copied().unwrap_or(parent)
+            }
+            _ => bone.id,
+        };
+        targets.insert(bone.id, target);
+        for child in bone.children.iter().flatten() {
+            ModelMesh::collect_lod_targets(child, Some(bone.id), is_collapsed, targets, collapsed);
+        }
+    }
+
+    /// Copy of `vertex` with its bone ids mapped by `remap`, weights of bones mapped to the
+    /// same id are added up
+    fn remap_weights(vertex: &ModelMeshVertex, remap: impl Fn(u32) -> u32) -> ModelMeshVertex {
+        let (ids, weights) = (vertex.bone_ids, vertex.bone_weights);
+        let mut merged: Vec<(u32, f32)> = Vec::with_capacity(4);
+        for (id, weight) in [
+            (ids.0, weights.0),
+            (ids.1, weights.1),
+            (ids.2, weights.2),
+            (ids.3, weights.3),
+        ] {
+            if weight == 0.0 {
+                continue;
+            }
+            let id = remap(id);
+            match merged.iter_mut().find(|(merged_id, _)| *merged_id == id) {
+                Some((_, merged_weight)) => *merged_weight += weight,
+                None => merged.push((id, weight)),
+            }
+        }
+        merged.resize(4, (0, 0.0));
+        ModelMeshVertex {
+            bone_ids: (merged[0].0, merged[1].0, merged[2].0, merged[3].0),
+            bone_weights: (merged[0].1, merged[1].1, merged[2].1, merged[3].1),
+            ..vertex.clone()
+        }
+    }
+}
@@ -1,7 +1,8 @@
-#version 330 core
+#version 460 core
 
-const int MAX_BONES = 100;
 const int MAX_WEIGHTS = 4;
+const int SKINNING_LINEAR = 0;
+const int SKINNING_DUAL_QUATERNION = 1;
 
 layout (location = 0) in vec3 position;
 layout (location = 1) in vec3 normals;
@@ -12,26 +13,72 @@ layout (location = 4) in vec4 weights;
 out vec3 Normal;
 out vec3 toLightVector;
 out vec2 TexCoords;
+out vec3 WorldPosition;
 
 uniform vec3 lightPosition;
 uniform mat4 model;
 uniform mat4 viewProjection;
-uniform mat4 boneTransforms[MAX_BONES];
+uniform int skinning;
 
-void main()
+#include "bone_palette.glsl"
+
+mat4 blendBoneTransforms()
 {
-    mat4 BoneTransform = boneTransforms[boneIDs[0]] * weights[0];
+    mat4 boneTransform = boneTransforms[boneIDs[0]] * weights[0];
+    for (int i = 1; i < MAX_WEIGHTS; i++)
+    {
+        if (weights[i] == 0.0)
+            break;
+        boneTransform += boneTransforms[boneIDs[i]] * weights[i];
+    }
+    return boneTransform;
+}
 
+mat2x4 blendBoneDualQuaternions()
+{
+    mat2x4 pivot = boneDualQuaternions[boneIDs[0]];
+    mat2x4 dualQuaternion = pivot * weights[0];
     for (int i = 1; i < MAX_WEIGHTS; i++)
     {
         if (weights[i] == 0.0)
             break;
-        BoneTransform += boneTransforms[boneIDs[i]] * weights[i];
+        mat2x4 boneDualQuaternion = boneDualQuaternions[boneIDs[i]];
+        // q and -q are the same rotation, blend along the shorter arc
+        float weight = dot(pivot[0], boneDualQuaternion[0]) < 0.0 ? -weights[i] : weights[i];
+        dualQuaternion += boneDualQuaternion * weight;
+    }
+    return dualQuaternion / length(dualQuaternion[0]);
+}
+
+vec3 rotate(vec4 rotation, vec3 vector)
+{
+    return vector + 2.0 * cross(rotation.xyz, cross(rotation.xyz, vector) + rotation.w * vector);
+}
+
+void main()
+{
+    vec3 skinnedPosition;
+    vec3 skinnedNormal;
+    if (skinning == SKINNING_DUAL_QUATERNION)
+    {
+        mat2x4 dualQuaternion = blendBoneDualQuaternions();
+        vec4 real = dualQuaternion[0];
+        vec4 dual = dualQuaternion[1];
+        vec3 translation = 2.0 * (real.w * dual.xyz - dual.w * real.xyz + cross(real.xyz, dual.xyz));
+        skinnedPosition = rotate(real, position) + translation;
+        skinnedNormal = rotate(real, normals);
+    }
+    else
+    {
+        mat4 boneTransform = blendBoneTransforms();
+        skinnedPosition = (boneTransform * vec4(position, 1.0)).xyz;
+        skinnedNormal = (boneTransform * vec4(normals, 0.0)).xyz;
     }
 
-    vec4 worldPosition = model * (BoneTransform * vec4(position, 1.0));
+    vec4 worldPosition = model * vec4(skinnedPosition, 1.0);
     gl_Position = viewProjection * worldPosition;
-    Normal = (BoneTransform * vec4(normals, 0.0)).xyz;
+    Normal = skinnedNormal;
     TexCoords = texCoords;
     toLightVector = lightPosition - worldPosition.xyz;
-}
\ No newline at end of file
+    WorldPosition = worldPosition.xyz;
+}
@@ -1,7 +1,10 @@
 use cgmath::{InnerSpace, Matrix4, Point3, SquareMatrix, Vector3, Vector4};
 use glfw::{Action, MouseButton};
 
-use crate::core::renderer::line::Line;
+use crate::core::{
+    input::{Binding, Input},
+    renderer::line::Line,
+};
 
 use super::camera::{Camera, Projection};
 
@@ -49,11 +52,9 @@ impl MousePicker {
                 if *action == Action::Press {
                     let ray = self.calculate_ray();
                     let line = Line::new(self.position, ray, 20.0);
-                    match button {
-                        glfw::MouseButton::Button1 => Some((line, glfw::MouseButton::Button1)),
-                        glfw::MouseButton::Button2 => Some((line, glfw::MouseButton::Button2)),
-                        _ => None,
-                    }
+                    let binding = Binding::Mouse(*button);
+                    (Input::is_bound("dig", binding) || Input::is_bound("place", binding))
+                        .then_some((line, *button))
                 } else {
                     None
                 }
@@ -0,0 +1,160 @@
+use std::{
+    io,
+    net::{TcpStream, ToSocketAddrs},
+};
+
+use cgmath::{Point3, Quaternion};
+
+use crate::terrain::{structures::StructureBlock, world_gen::WorldGenConfig, ChunkCoord};
+
+use super::{
+    Client, ClientEvent, ClientId, Connection, Message, NetEntity, NetEntityId, PROTOCOL_VERSION,
+};
+
+impl Client {
+    /// Connects to the server at `address` and says hello, `ClientEvent::Joined` follows once
+    /// the server accepted the client
+    pub fn connect<A: ToSocketAddrs>(address: A) -> io::Result<Self> {
+        let stream = TcpStream::connect(address)?;
+        let mut connection = Connection::new(stream)?;
+        connection.send(&Message::Hello {
+            version: PROTOCOL_VERSION,
+        });
+        connection.flush();
+        Ok(Self {
+            connection,
+            client: None,
+            player: None,
+            config: None,
+            entities: Default::default(),
+        })
+    }
+
+    /// Handles the messages the server sent since the last update and sends the queued ones
+    pub fn update(&mut self) -> Vec<ClientEvent> {
+        let mut events = Vec::new();
+        let was_closed = self.connection.is_closed();
+        for message in self.connection.receive() {
+            self.handle_message(message, &mut events);
+        }
+        self.connection.flush();
+        if self.connection.is_closed() && !was_closed {
+            events.push(ClientEvent::Disconnected);
+        }
+        events
+    }
+
+    /// Asks the server for the chunk at `coord`, call it for every chunk the terrain loads. It
+    /// arrives as `ClientEvent::ChunkReceived`, or as `ClientEvent::BlocksEdited` with its edits
+    /// if the server's chunks have no serialized form
+    pub fn request_chunk(&mut self, coord: ChunkCoord) {
+        self.connection.send(&Message::RequestChunk { coord });
+    }
+
+    /// Sends blocks the player set to the server, which forwards them to the other clients.
+    /// Apply them to the own terrain right away
+    pub fn edit_blocks(&mut self, blocks: Vec<StructureBlock>) {
+        self.connection.send(&Message::EditBlocks { blocks });
+    }
+
+    /// Moves the own player on the server and the other clients
+    pub fn set_player_transform(&mut self, position: Point3<f32>, rotation: Quaternion<f32>) {
+        let Some(id) = self.player else {
+            return;
+        };
+        if let Some(entity) = self.entities.get_mut(&id) {
+            entity.position = position;
+            entity.rotation = rotation;
+        }
+        self.connection.send(&Message::EntityTransform {
+            id,
+            position,
+            rotation,
+        });
+    }
+
+    /// World gen config of the server, None until the client joined
+    pub fn get_config(&self) -> Option<&WorldGenConfig> {
+        self.config.as_ref()
+    }
+
+    pub fn get_id(&self) -> Option<ClientId> {
+        self.client
+    }
+
+    /// Entity of the own player, None until the client joined
+    pub fn get_player(&self) -> Option<NetEntityId> {
+        self.player
+    }
+
+    pub fn get_entity(&self, id: NetEntityId) -> Option<&NetEntity> {
+        self.entities.get(&id)
+    }
+
+    pub fn get_entities(&self) -> impl Iterator<Item = &NetEntity> {
+        self.entities.values()
+    }
+
+    pub fn is_connected(&self) -> bool {
+        !self.connection.is_closed()
+    }
+
+    fn handle_message(&mut self, message: Message, events: &mut Vec<ClientEvent>) {
+        match message {
+            Message::Welcome {
+                client,
+                player,
+                config,
+            } => match WorldGenConfig::from_ron(&config) {
+                Ok(config) => {
+                    self.client = Some(client);
+                    self.player = Some(player);
+                    self.config = Some(config);
+                    events.push(ClientEvent::Joined);
+                }
+                Err(err) => {
+                    log::error!(target: "net", "Failed to read the server's world gen config: {}", err);
+                    events.push(ClientEvent::Rejected {
+                        reason: err.to_string(),
+                    });
+                }
+            },
+            Message::Rejected { reason } => {
+                log::warn!(target: "net", "The server rejected the client: {}", reason);
+                events.push(ClientEvent::Rejected { reason });
+            }
+            Message::ChunkSnapshot { coord, data } => {
+                events.push(ClientEvent::ChunkReceived { coord, data });
+            }
+            Message::ChunkEdits { blocks, .. } | Message::BlocksEdited { blocks } => {
+                if !blocks.is_empty() {
+                    events.push(ClientEvent::BlocksEdited { blocks });
+                }
+            }
+            Message::EntitySpawned { entity } => {
+                let id = entity.id;
+                self.entities.insert(id, entity);
+                events.push(ClientEvent::EntitySpawned { id });
+            }
+            Message::EntityTransform {
+                id,
+                position,
+                rotation,
+            } => {
+                if let Some(entity) = self.entities.get_mut(&id) {
+                    entity.position = position;
+                    entity.rotation = rotation;
+                    events.push(ClientEvent::EntityMoved { id });
+                }
+            }
+            Message::EntityDespawned { id } => {
+                if self.entities.remove(&id).is_some() {
+                    events.push(ClientEvent::EntityDespawned { id });
+                }
+            }
+            message => {
+                log::warn!(target: "net", "Ignoring unexpected {:?} from the server", message);
+            }
+        }
+    }
+}
@@ -0,0 +1,213 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    net::{TcpListener, TcpStream},
+    sync::{
+        mpsc::{Receiver, Sender},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use cgmath::{Point3, Quaternion};
+
+use crate::terrain::{
+    codec::RegionStore,
+    structures::{PendingStructures, StructureBlock},
+    world_gen::WorldGenConfig,
+    Chunk, ChunkCoord,
+};
+
+mod client;
+mod protocol;
+mod server;
+
+/// Clients and servers only talk to each other with the same protocol version
+pub const PROTOCOL_VERSION: u32 = 1;
+pub const DEFAULT_PORT: u16 = 24780;
+/// Messages longer than this close the connection, chunk snapshots and edits stay far below it
+pub const MAX_MESSAGE_SIZE: usize = 16 * 1024 * 1024;
+/// Chunks no client requested or edited for this long are evicted from the `Server`
+pub const CHUNK_IDLE_TIME: Duration = Duration::from_secs(60);
+
+pub type ClientId = u32;
+pub type NetEntityId = u64;
+
+/// Message exchanged between a `Server` and its `Client`s, sent as a little endian u32 length
+/// followed by the message encoded with the chunk codec's `ByteWriter`
+#[derive(Clone, Debug, PartialEq)]
+pub enum Message {
+    /// First message of a client
+    Hello {
+        version: u32,
+    },
+    /// Accepts a client with the entity of its player and the world gen config, clients generate
+    /// the chunks they load from it until the server's snapshots of them arrive
+    Welcome {
+        client: ClientId,
+        player: NetEntityId,
+        config: String,
+    },
+    Rejected {
+        reason: String,
+    },
+    /// Asks for a chunk the client loads
+    RequestChunk {
+        coord: ChunkCoord,
+    },
+    /// The server's chunk encoded with `Chunk::serialize`, edits included
+    ChunkSnapshot {
+        coord: ChunkCoord,
+        data: Vec<u8>,
+    },
+    /// Every edit the server knows of in a chunk without a serialized form, sent even if there
+    /// are none
+    ChunkEdits {
+        coord: ChunkCoord,
+        blocks: Vec<StructureBlock>,
+    },
+    /// Blocks a client set at world block positions
+    EditBlocks {
+        blocks: Vec<StructureBlock>,
+    },
+    /// Blocks another client set, forwarded by the server
+    BlocksEdited {
+        blocks: Vec<StructureBlock>,
+    },
+    EntitySpawned {
+        entity: NetEntity,
+    },
+    /// Sent by the server for every moved entity and by clients for their player
+    EntityTransform {
+        id: NetEntityId,
+        position: Point3<f32>,
+        rotation: Quaternion<f32>,
+    },
+    EntityDespawned {
+        id: NetEntityId,
+    },
+}
+
+/// Entity replicated from the server to every client
+#[derive(Clone, Debug, PartialEq)]
+pub struct NetEntity {
+    pub id: NetEntityId,
+    pub name: String,
+    pub position: Point3<f32>,
+    pub rotation: Quaternion<f32>,
+    /// Client allowed to move the entity, the server moves entities without an owner
+    pub owner: Option<ClientId>,
+}
+
+/// Non-blocking TCP stream that frames messages
+pub struct Connection {
+    stream: TcpStream,
+    /// Received bytes that do not form a whole message yet
+    incoming: Vec<u8>,
+    /// Encoded messages the socket did not accept yet
+    outgoing: Vec<u8>,
+    closed: bool,
+}
+
+/// Headless authority over a world: it owns the chunks the clients load, applies every block
+/// edit to them and replicates entities. Clients receive the chunks they load as snapshots and
+/// the edits of the other clients as they happen.
+///
+/// Requested chunks are loaded from the region files or generated on a separate thread with
+/// `MeshingBackend::DataOnly`, so they are never lit or meshed and the server runs without a
+/// window or GPU. Chunks idle for `CHUNK_IDLE_TIME` are evicted, edited ones are saved to the
+/// region files first if `set_save_directory` was called, otherwise they are generated again
+/// with the edits.
+///
+/// Call `update` every tick to accept clients, handle their messages and send moved entities
+pub struct Server<T: Chunk> {
+    listener: TcpListener,
+    config: WorldGenConfig,
+    clients: BTreeMap<ClientId, ServerClient>,
+    next_client: ClientId,
+    /// Loaded chunks with the edits applied, keyed by their grid position
+    chunks: BTreeMap<ChunkCoord, ServerChunk<T>>,
+    /// Clients waiting for chunks the generator thread is still loading, by chunk
+    waiting: BTreeMap<ChunkCoord, BTreeSet<ClientId>>,
+    /// Chunks for the generator thread to load
+    chunk_requests: Sender<ChunkCoord>,
+    /// Chunks the generator thread loaded
+    chunk_receiver: Receiver<(ChunkCoord, T)>,
+    /// Structure blocks passed between the neighbouring chunks the server generates
+    structures: Arc<Mutex<PendingStructures>>,
+    /// Region files evicted chunks are saved to, shared with the generator thread
+    regions: Arc<Mutex<Option<RegionStore>>>,
+    /// Edited blocks per chunk, keyed by world block position
+    edits: BTreeMap<ChunkCoord, BTreeMap<(i32, i32, i32), u32>>,
+    entities: BTreeMap<NetEntityId, NetEntity>,
+    /// Entities whose transform changed since the last update
+    moved: BTreeSet<NetEntityId>,
+    next_entity: NetEntityId,
+}
+
+struct ServerChunk<T> {
+    chunk: T,
+    /// Last time a client requested or edited the chunk
+    last_used: Instant,
+    /// Whether the chunk changed since it was loaded or saved
+    edited: bool,
+}
+
+struct ServerClient {
+    connection: Connection,
+    /// Set once the client said hello
+    player: Option<NetEntityId>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ServerEvent {
+    Connected {
+        client: ClientId,
+        player: NetEntityId,
+    },
+    Disconnected {
+        client: ClientId,
+    },
+    BlocksEdited {
+        client: ClientId,
+        blocks: Vec<StructureBlock>,
+    },
+}
+
+/// Connection to a `Server`. Request every chunk the terrain loads with `request_chunk`, then
+/// feed the received chunks to `Terrain::restore_chunk` and the edits to `Terrain::edit_blocks`
+pub struct Client {
+    connection: Connection,
+    client: Option<ClientId>,
+    player: Option<NetEntityId>,
+    config: Option<WorldGenConfig>,
+    entities: BTreeMap<NetEntityId, NetEntity>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ClientEvent {
+    /// The server accepted the client, create the terrain from `Client::get_config` now
+    Joined,
+    Rejected {
+        reason: String,
+    },
+    /// Snapshot of a requested chunk, encoded with `Chunk::serialize`
+    ChunkReceived {
+        coord: ChunkCoord,
+        data: Vec<u8>,
+    },
+    /// Blocks to apply to the terrain, edits of another client or of a requested chunk without
+    /// a serialized form
+    BlocksEdited {
+        blocks: Vec<StructureBlock>,
+    },
+    EntitySpawned {
+        id: NetEntityId,
+    },
+    EntityMoved {
+        id: NetEntityId,
+    },
+    EntityDespawned {
+        id: NetEntityId,
+    },
+    Disconnected,
+}
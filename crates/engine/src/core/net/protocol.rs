@@ -0,0 +1,296 @@
+use std::{
+    io::{self, ErrorKind, Read, Write},
+    net::TcpStream,
+};
+
+use cgmath::{Point3, Quaternion};
+
+use crate::terrain::{
+    codec::{ByteReader, ByteWriter, CodecError},
+    structures::StructureBlock,
+    ChunkCoord,
+};
+
+use super::{Connection, Message, NetEntity, MAX_MESSAGE_SIZE};
+
+/// Bytes read from the socket at once
+const READ_SIZE: usize = 64 * 1024;
+
+impl Message {
+    fn get_tag(&self) -> u8 {
+        match self {
+            Message::Hello { .. } => 1,
+            Message::Welcome { .. } => 2,
+            Message::Rejected { .. } => 3,
+            Message::RequestChunk { .. } => 4,
+            Message::ChunkEdits { .. } => 5,
+            Message::EditBlocks { .. } => 6,
+            Message::BlocksEdited { .. } => 7,
+            Message::EntitySpawned { .. } => 8,
+            Message::EntityTransform { .. } => 9,
+            Message::EntityDespawned { .. } => 10,
+            Message::ChunkSnapshot { .. } => 11,
+        }
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut writer = ByteWriter::new();
+        writer.write_u8(self.get_tag());
+        match self {
+            Message::Hello { version } => writer.write_varint(*version as u64),
+            Message::Welcome {
+                client,
+                player,
+                config,
+            } => {
+                writer.write_varint(*client as u64);
+                writer.write_varint(*player);
+                writer.write_string(config);
+            }
+            Message::Rejected { reason } => writer.write_string(reason),
+            Message::RequestChunk { coord } => write_coord(&mut writer, coord),
+            Message::ChunkSnapshot { coord, data } => {
+                write_coord(&mut writer, coord);
+                writer.write_bytes(data);
+            }
+            Message::ChunkEdits { coord, blocks } => {
+                write_coord(&mut writer, coord);
+                write_blocks(&mut writer, blocks);
+            }
+            Message::EditBlocks { blocks } | Message::BlocksEdited { blocks } => {
+                write_blocks(&mut writer, blocks)
+            }
+            Message::EntitySpawned { entity } => {
+                writer.write_varint(entity.id);
+                writer.write_string(&entity.name);
+                write_transform(&mut writer, entity.position, entity.rotation);
+                writer.write_varint(entity.owner.map_or(0, |owner| owner as u64 + 1));
+            }
+            Message::EntityTransform {
+                id,
+                position,
+                rotation,
+            } => {
+                writer.write_varint(*id);
+                write_transform(&mut writer, *position, *rotation);
+            }
+            Message::EntityDespawned { id } => writer.write_varint(*id),
+        }
+        writer.into_bytes()
+    }
+
+    pub fn decode(bytes: &[u8]) -> Result<Message, CodecError> {
+        let mut reader = ByteReader::new(bytes);
+        let message = match reader.read_u8()? {
+            1 => Message::Hello {
+                version: reader.read_u32()?,
+            },
+            2 => Message::Welcome {
+                client: reader.read_u32()?,
+                player: reader.read_varint()?,
+                config: reader.read_string()?,
+            },
+            3 => Message::Rejected {
+                reason: reader.read_string()?,
+            },
+            4 => Message::RequestChunk {
+                coord: read_coord(&mut reader)?,
+            },
+            5 => Message::ChunkEdits {
+                coord: read_coord(&mut reader)?,
+                blocks: read_blocks(&mut reader)?,
+            },
+            6 => Message::EditBlocks {
+                blocks: read_blocks(&mut reader)?,
+            },
+            7 => Message::BlocksEdited {
+                blocks: read_blocks(&mut reader)?,
+            },
+            8 => {
+                let id = reader.read_varint()?;
+                let name = reader.read_string()?;
+                let (position, rotation) = read_transform(&mut reader)?;
+                let owner = reader.read_u32()?;
+                Message::EntitySpawned {
+                    entity: NetEntity {
+                        id,
+                        name,
+                        position,
+                        rotation,
+                        owner: owner.checked_sub(1),
+                    },
+                }
+            }
+            9 => {
+                let id = reader.read_varint()?;
+                let (position, rotation) = read_transform(&mut reader)?;
+                Message::EntityTransform {
+                    id,
+                    position,
+                    rotation,
+                }
+            }
+            10 => Message::EntityDespawned {
+                id: reader.read_varint()?,
+            },
+            11 => Message::ChunkSnapshot {
+                coord: read_coord(&mut reader)?,
+                data: reader.read_bytes()?.to_vec(),
+            },
+            _ => return Err(CodecError::Invalid("unknown message")),
+        };
+        if reader.remaining() > 0 {
+            return Err(CodecError::Invalid("trailing bytes after the message"));
+        }
+        Ok(message)
+    }
+}
+
+fn write_coord(writer: &mut ByteWriter, coord: &ChunkCoord) {
+    writer.write_i32(coord.x);
+    writer.write_i32(coord.y);
+    writer.write_i32(coord.z);
+}
+
+fn read_coord(reader: &mut ByteReader) -> Result<ChunkCoord, CodecError> {
+    Ok(ChunkCoord::new(
+        reader.read_i32()?,
+        reader.read_i32()?,
+        reader.read_i32()?,
+    ))
+}
+
+fn write_blocks(writer: &mut ByteWriter, blocks: &[StructureBlock]) {
+    writer.write_varint(blocks.len() as u64);
+    for ((x, y, z), block) in blocks {
+        writer.write_i32(*x);
+        writer.write_i32(*y);
+        writer.write_i32(*z);
+        writer.write_varint(*block as u64);
+    }
+}
+
+fn read_blocks(reader: &mut ByteReader) -> Result<Vec<StructureBlock>, CodecError> {
+    let count = reader.read_varint()?;
+    // every block takes at least four bytes
+    if count > reader.remaining() as u64 / 4 {
+        return Err(CodecError::UnexpectedEnd);
+    }
+    (0..count)
+        .map(|_| {
+            let position = (reader.read_i32()?, reader.read_i32()?, reader.read_i32()?);
+            Ok((position, reader.read_u32()?))
+        })
+        .collect()
+}
+
+fn write_transform(writer: &mut ByteWriter, position: Point3<f32>, rotation: Quaternion<f32>) {
+    for value in [
+        position.x,
+        position.y,
+        position.z,
+        rotation.s,
+        rotation.v.x,
+        rotation.v.y,
+        rotation.v.z,
+    ] {
+        writer.write_f32(value);
+    }
+}
+
+fn read_transform(reader: &mut ByteReader) -> Result<(Point3<f32>, Quaternion<f32>), CodecError> {
+    let position = Point3::new(reader.read_f32()?, reader.read_f32()?, reader.read_f32()?);
+    let rotation = Quaternion::new(
+        reader.read_f32()?,
+        reader.read_f32()?,
+        reader.read_f32()?,
+        reader.read_f32()?,
+    );
+    Ok((position, rotation))
+}
+
+impl Connection {
+    pub fn new(stream: TcpStream) -> io::Result<Self> {
+        stream.set_nonblocking(true)?;
+        stream.set_nodelay(true)?;
+        Ok(Self {
+            stream,
+            incoming: Vec::new(),
+            outgoing: Vec::new(),
+            closed: false,
+        })
+    }
+
+    /// Queues `message`, it is written with the next `flush`
+    pub fn send(&mut self, message: &Message) {
+        let bytes = message.encode();
+        self.outgoing
+            .extend_from_slice(&(bytes.len() as u32).to_le_bytes());
+        self.outgoing.extend_from_slice(&bytes);
+    }
+
+    /// Writes as much of the queued messages as the socket accepts without blocking
+    pub fn flush(&mut self) {
+        while !self.outgoing.is_empty() && !self.closed {
+            match self.stream.write(&self.outgoing) {
+                Ok(0) => self.closed = true,
+                Ok(written) => {
+                    self.outgoing.drain(..written);
+                }
+                Err(err) if err.kind() == ErrorKind::WouldBlock => return,
+                Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(err) => {
+                    log::warn!(target: "net", "Failed to send to {:?}: {}", self.stream.peer_addr(), err);
+                    self.closed = true;
+                }
+            }
+        }
+    }
+
+    /// Messages received since the last call. Malformed messages close the connection
+    pub fn receive(&mut self) -> Vec<Message> {
+        let mut buffer = [0u8; READ_SIZE];
+        while !self.closed {
+            match self.stream.read(&mut buffer) {
+                Ok(0) => self.closed = true,
+                Ok(read) => self.incoming.extend_from_slice(&buffer[..read]),
+                Err(err) if err.kind() == ErrorKind::WouldBlock => break,
+                Err(err) if err.kind() == ErrorKind::Interrupted => {}
+                Err(err) => {
+                    log::warn!(target: "net", "Failed to receive from {:?}: {}", self.stream.peer_addr(), err);
+                    self.closed = true;
+                }
+            }
+        }
+        let mut messages = Vec::new();
+        let mut start = 0;
+        while self.incoming.len() - start >= 4 {
+            let length =
+                u32::from_le_bytes(self.incoming[start..start + 4].try_into().unwrap()) as usize;
+            if length > MAX_MESSAGE_SIZE {
+                log::warn!(target: "net", "Closing the connection after a message of {} bytes", length);
+                self.closed = true;
+                break;
+            }
+            if self.incoming.len() - start - 4 < length {
+                break;
+            }
+            match Message::decode(&self.incoming[start + 4..start + 4 + length]) {
+                Ok(message) => messages.push(message),
+                Err(err) => {
+                    log::warn!(target: "net", "Closing the connection after a malformed message: {}", err);
+                    self.closed = true;
+                    break;
+                }
+            }
+            start += 4 + length;
+        }
+        self.incoming.drain(..start);
+        messages
+    }
+
+    /// Whether the peer closed the connection or it failed
+    pub fn is_closed(&self) -> bool {
+        self.closed
+    }
+}
@@ -0,0 +1,569 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    io::{self, ErrorKind},
+    net::{SocketAddr, TcpListener, ToSocketAddrs},
+    path::Path,
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread,
+    time::Instant,
+};
+
+use cgmath::{Point3, Quaternion};
+
+use crate::{
+    core::entity::component::Component,
+    terrain::{
+        codec::RegionStore,
+        structures::{PendingStructures, StructureBlock, StructureRegistry},
+        world_gen::WorldGenConfig,
+        Chunk, ChunkCoord, MeshingBackend, Terrain,
+    },
+};
+
+use super::{
+    ClientId, Connection, Message, NetEntity, NetEntityId, Server, ServerChunk, ServerClient,
+    ServerEvent, CHUNK_IDLE_TIME, PROTOCOL_VERSION,
+};
+
+impl<T: Chunk + Component + Send + 'static> Server<T> {
+    /// Listens for clients on `address`, e.g. "0.0.0.0:24780", handing them `config`
+    pub fn bind<A: ToSocketAddrs>(address: A, config: WorldGenConfig) -> io::Result<Self> {
+        let listener = TcpListener::bind(address)?;
+        listener.set_nonblocking(true)?;
+        log::info!(target: "net", "Listening on {}", listener.local_addr()?);
+        let (chunk_requests, requests) = mpsc::channel();
+        let (loaded, chunk_receiver) = mpsc::channel();
+        let structures = Arc::new(Mutex::new(PendingStructures::default()));
+        let regions = Arc::new(Mutex::new(None));
+        let generator_config = config.clone();
+        let generator_structures = structures.clone();
+        let generator_regions = regions.clone();
+        let _ = thread::Builder::new()
+            .name("chunk-generator".to_string())
+            .spawn(move || {
+                Server::<T>::generate_chunks(
+                    generator_config,
+                    generator_structures,
+                    generator_regions,
+                    requests,
+                    loaded,
+                )
+            })?;
+        Ok(Self {
+            listener,
+            config,
+            clients: BTreeMap::new(),
+            next_client: 1,
+            chunks: BTreeMap::new(),
+            waiting: BTreeMap::new(),
+            chunk_requests,
+            chunk_receiver,
+            structures,
+            regions,
+            edits: BTreeMap::new(),
+            entities: BTreeMap::new(),
+            moved: Default::default(),
+            next_entity: 1,
+        })
+    }
+
+    pub fn get_address(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    pub fn get_config(&self) -> &WorldGenConfig {
+        &self.config
+    }
+
+    /// Saves edited chunks to region files in `directory` when they are evicted and loads them
+    /// from there again
+    pub fn set_save_directory<P: AsRef<Path>>(&mut self, directory: P) {
+        *self.regions.lock().unwrap() = Some(RegionStore::new(directory));
+    }
+
+    /// Saves the edited chunks that are loaded to the region files, does nothing without a save
+    /// directory
+    pub fn save(&mut self) {
+        let mut regions = self.regions.lock().unwrap();
+        let Some(regions) = regions.as_mut() else {
+            return;
+        };
+        for (coord, loaded) in self.chunks.iter_mut() {
+            if loaded.edited {
+                loaded.edited = !Server::save_chunk(regions, coord, &loaded.chunk);
+            }
+        }
+        if let Err(err) = regions.flush() {
+            log::error!(target: "net", "Failed to write the region files: {}", err);
+        }
+    }
+
+    /// Accepts new clients, handles the messages of the connected ones and sends the entities
+    /// that moved since the last update
+    pub fn update(&mut self) -> Vec<ServerEvent> {
+        let mut events = Vec::new();
+        self.accept();
+        self.receive_chunks();
+        let ids: Vec<ClientId> = self.clients.keys().copied().collect();
+        for id in ids {
+            let messages = self.clients.get_mut(&id).unwrap().connection.receive();
+            for message in messages {
+                // unexpected messages disconnect the client
+                if !self.clients.contains_key(&id) {
+                    break;
+                }
+                self.handle_message(id, message, &mut events);
+            }
+        }
+        for id in std::mem::take(&mut self.moved) {
+            if let Some(entity) = self.entities.get(&id) {
+                let message = Message::EntityTransform {
+                    id,
+                    position: entity.position,
+                    rotation: entity.rotation,
+                };
+                // owners predict their own entities
+                self.broadcast(&message, entity.owner);
+            }
+        }
+        let closed: Vec<ClientId> = self
+            .clients
+            .iter()
+            .filter(|(_, client)| client.connection.is_closed())
+            .map(|(id, _)| *id)
+            .collect();
+        for id in closed {
+            self.disconnect(id);
+            events.push(ServerEvent::Disconnected { client: id });
+        }
+        self.evict_idle_chunks();
+        for client in self.clients.values_mut() {
+            client.connection.flush();
+        }
+        events
+    }
+
+    /// Replicates a new entity moved by the server, returns its id
+    pub fn spawn_entity(
+        &mut self,
+        name: &str,
+        position: Point3<f32>,
+        rotation: Quaternion<f32>,
+    ) -> NetEntityId {
+        self.spawn(name, position, rotation, None)
+    }
+
+    /// Moves the entity on every client with the next update, returns false if it does not
+    /// exist
+    pub fn set_transform(
+        &mut self,
+        id: NetEntityId,
+        position: Point3<f32>,
+        rotation: Quaternion<f32>,
+    ) -> bool {
+        let Some(entity) = self.entities.get_mut(&id) else {
+            return false;
+        };
+        entity.position = position;
+        entity.rotation = rotation;
+        self.moved.insert(id);
+        true
+    }
+
+    pub fn despawn_entity(&mut self, id: NetEntityId) -> bool {
+        if self.entities.remove(&id).is_none() {
+            return false;
+        }
+        self.moved.remove(&id);
+        self.broadcast(&Message::EntityDespawned { id }, None);
+        true
+    }
+
+    pub fn get_entity(&self, id: NetEntityId) -> Option<&NetEntity> {
+        self.entities.get(&id)
+    }
+
+    pub fn get_entities(&self) -> impl Iterator<Item = &NetEntity> {
+        self.entities.values()
+    }
+
+    /// Sets blocks at world block positions in the server's chunks and on every client, like an
+    /// edit of a client
+    pub fn edit_blocks(&mut self, blocks: Vec<StructureBlock>) {
+        self.record_edits(&blocks);
+        self.broadcast(&Message::BlocksEdited { blocks }, None);
+    }
+
+    /// Chunk at `coord` with the edits applied, None while no client uses it
+    pub fn get_chunk(&self, coord: &ChunkCoord) -> Option<&T> {
+        self.chunks.get(coord).map(|loaded| &loaded.chunk)
+    }
+
+    /// Number of loaded chunks
+    pub fn get_chunk_count(&self) -> usize {
+        self.chunks.len()
+    }
+
+    /// Every edit in the chunk at `coord`, sent to clients that load it if the chunk has no
+    /// serialized form
+    pub fn get_edits(&self, coord: &ChunkCoord) -> Vec<StructureBlock> {
+        self.edits
+            .get(coord)
+            .map(|blocks| {
+                blocks
+                    .iter()
+                    .map(|(position, block)| (*position, *block))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn get_client_count(&self) -> usize {
+        self.clients.len()
+    }
+
+    fn accept(&mut self) {
+        loop {
+            match self.listener.accept() {
+                Ok((stream, address)) => match Connection::new(stream) {
+                    Ok(connection) => {
+                        log::info!(target: "net", "Client {} connected from {}", self.next_client, address);
+                        self.clients.insert(
+                            self.next_client,
+                            ServerClient {
+                                connection,
+                                player: None,
+                            },
+                        );
+                        self.next_client += 1;
+                    }
+                    Err(err) => {
+                        log::warn!(target: "net", "Failed to set up the connection from {}: {}", address, err)
+                    }
+                },
+                Err(err) if err.kind() == ErrorKind::WouldBlock => return,
+                Err(err) => {
+                    log::warn!(target: "net", "Failed to accept a client: {}", err);
+                    return;
+                }
+            }
+        }
+    }
+
+    fn handle_message(&mut self, id: ClientId, message: Message, events: &mut Vec<ServerEvent>) {
+        let joined = self.clients[&id].player.is_some();
+        match message {
+            Message::Hello { version } if !joined => self.welcome(id, version, events),
+            Message::RequestChunk { coord } if joined => {
+                if self.chunks.contains_key(&coord) {
+                    self.send_chunk(id, coord);
+                } else {
+                    let waiting = self.waiting.entry(coord).or_default();
+                    if waiting.is_empty() && self.chunk_requests.send(coord).is_err() {
+                        log::error!(target: "net", "The chunk generator stopped, chunk {:?} is not loaded", coord);
+                    }
+                    waiting.insert(id);
+                }
+            }
+            Message::EditBlocks { blocks } if joined => {
+                self.record_edits(&blocks);
+                self.broadcast(
+                    &Message::BlocksEdited {
+                        blocks: blocks.clone(),
+                    },
+                    Some(id),
+                );
+                events.push(ServerEvent::BlocksEdited { client: id, blocks });
+            }
+            Message::EntityTransform {
+                id: entity,
+                position,
+                rotation,
+            } if joined => {
+                if self.entities.get(&entity).and_then(|entity| entity.owner) == Some(id) {
+                    self.set_transform(entity, position, rotation);
+                } else {
+                    log::warn!(target: "net", "Client {} moved entity {} it does not own", id, entity);
+                }
+            }
+            message => {
+                log::warn!(target: "net", "Closing the connection to client {} after an unexpected {:?}", id, message);
+                self.disconnect(id);
+                events.push(ServerEvent::Disconnected { client: id });
+            }
+        }
+    }
+
+    /// Accepts the client if it speaks `version` of the protocol and spawns its player
+    fn welcome(&mut self, id: ClientId, version: u32, events: &mut Vec<ServerEvent>) {
+        if version != PROTOCOL_VERSION {
+            self.send(
+                id,
+                &Message::Rejected {
+                    reason: format!(
+                        "The server speaks protocol version {}, the client {}",
+                        PROTOCOL_VERSION, version
+                    ),
+                },
+            );
+            return;
+        }
+        let config = match self.config.to_ron() {
+            Ok(config) => config,
+            Err(err) => {
+                log::error!(target: "net", "Failed to encode the world gen config: {}", err);
+                return;
+            }
+        };
+        let player = self.spawn(
+            &format!("player-{}", id),
+            Point3::new(0.0, 0.0, 0.0),
+            Quaternion::new(1.0, 0.0, 0.0, 0.0),
+            Some(id),
+        );
+        self.clients.get_mut(&id).unwrap().player = Some(player);
+        self.send(
+            id,
+            &Message::Welcome {
+                client: id,
+                player,
+                config,
+            },
+        );
+        // the client only receives broadcasts once it joined, so its player is among these
+        let entities: Vec<NetEntity> = self.entities.values().cloned().collect();
+        for entity in entities {
+            self.send(id, &Message::EntitySpawned { entity });
+        }
+        events.push(ServerEvent::Connected { client: id, player });
+    }
+
+    fn spawn(
+        &mut self,
+        name: &str,
+        position: Point3<f32>,
+        rotation: Quaternion<f32>,
+        owner: Option<ClientId>,
+    ) -> NetEntityId {
+        let id = self.next_entity;
+        self.next_entity += 1;
+        let entity = NetEntity {
+            id,
+            name: name.to_string(),
+            position,
+            rotation,
+            owner,
+        };
+        self.broadcast(
+            &Message::EntitySpawned {
+                entity: entity.clone(),
+            },
+            None,
+        );
+        self.entities.insert(id, entity);
+        id
+    }
+
+    fn disconnect(&mut self, id: ClientId) {
+        let Some(client) = self.clients.remove(&id) else {
+            return;
+        };
+        log::info!(target: "net", "Client {} disconnected", id);
+        if let Some(player) = client.player {
+            self.despawn_entity(player);
+        }
+    }
+
+    /// Records the edits and applies them to the loaded chunks, chunks that are loaded later
+    /// get them once they arrive
+    fn record_edits(&mut self, blocks: &[StructureBlock]) {
+        let mut coords = BTreeSet::new();
+        for (position, block) in blocks {
+            let coord = ChunkCoord::from_block(*position);
+            self.edits
+                .entry(coord)
+                .or_default()
+                .insert(*position, *block);
+            coords.insert(coord);
+        }
+        for coord in coords {
+            if let Some(loaded) = self.chunks.get_mut(&coord) {
+                loaded.edited |= Terrain::apply_blocks(&mut loaded.chunk, blocks.iter().copied());
+                loaded.chunk.take_mining_yields();
+                loaded.last_used = Instant::now();
+            }
+        }
+    }
+
+    /// Sends the chunk at `coord` to the client as a snapshot, or its edits if the chunk has no
+    /// serialized form
+    fn send_chunk(&mut self, id: ClientId, coord: ChunkCoord) {
+        let Some(loaded) = self.chunks.get_mut(&coord) else {
+            return;
+        };
+        loaded.last_used = Instant::now();
+        let message = match loaded.chunk.serialize() {
+            Some(data) => Message::ChunkSnapshot { coord, data },
+            None => Message::ChunkEdits {
+                coord,
+                blocks: self.get_edits(&coord),
+            },
+        };
+        self.send(id, &message);
+    }
+
+    /// Applies the edits to the chunks the generator thread loaded and sends them to the
+    /// clients waiting for them
+    fn receive_chunks(&mut self) {
+        let received: Vec<(ChunkCoord, T)> = self.chunk_receiver.try_iter().collect();
+        if received.is_empty() {
+            return;
+        }
+        for (coord, mut chunk) in received {
+            let edited = Terrain::apply_blocks(&mut chunk, self.get_edits(&coord));
+            chunk.take_mining_yields();
+            self.chunks.insert(
+                coord,
+                ServerChunk {
+                    chunk,
+                    last_used: Instant::now(),
+                    edited,
+                },
+            );
+            for id in self.waiting.remove(&coord).unwrap_or_default() {
+                self.send_chunk(id, coord);
+            }
+        }
+        self.place_late_structures();
+    }
+
+    /// Applies structure blocks that reached loaded chunks after them, like the terrain does.
+    /// The edits are applied again as structures never replace a block the players set
+    fn place_late_structures(&mut self) {
+        let mut structures = self.structures.lock().unwrap();
+        for coord in structures.get_late_chunks() {
+            let Some(loaded) = self.chunks.get_mut(&coord) else {
+                continue;
+            };
+            let blocks = structures.take_late(&coord).unwrap_or_default();
+            let edits = self.edits.get(&coord).into_iter().flatten();
+            Terrain::apply_blocks(
+                &mut loaded.chunk,
+                blocks
+                    .into_iter()
+                    .chain(edits.map(|(position, block)| (*position, *block))),
+            );
+            loaded.chunk.take_mining_yields();
+        }
+    }
+
+    /// Drops the chunks no client used for `CHUNK_IDLE_TIME`, saving the edited ones first
+    fn evict_idle_chunks(&mut self) {
+        let idle: Vec<ChunkCoord> = self
+            .chunks
+            .iter()
+            .filter(|(_, loaded)| loaded.last_used.elapsed() > CHUNK_IDLE_TIME)
+            .map(|(coord, _)| *coord)
+            .collect();
+        if idle.is_empty() {
+            return;
+        }
+        let mut regions = self.regions.lock().unwrap();
+        for coord in idle {
+            let loaded = self.chunks.remove(&coord).unwrap();
+            if let (true, Some(regions)) = (loaded.edited, regions.as_mut()) {
+                Server::save_chunk(regions, &coord, &loaded.chunk);
+            }
+        }
+        if let Some(Err(err)) = regions.as_mut().map(|regions| regions.flush()) {
+            log::error!(target: "net", "Failed to write the region files: {}", err);
+        }
+    }
+
+    /// Returns false if the chunk could not be saved
+    fn save_chunk(regions: &mut RegionStore, coord: &ChunkCoord, chunk: &T) -> bool {
+        let Some(data) = chunk.serialize() else {
+            return false;
+        };
+        match regions.save(coord, &data) {
+            Ok(()) => true,
+            Err(err) => {
+                log::error!(target: "net", "Failed to save chunk {:?}: {}", coord, err);
+                false
+            }
+        }
+    }
+
+    /// Loads the requested chunks from the region files or generates them without meshes, until
+    /// the server is dropped
+    fn generate_chunks(
+        config: WorldGenConfig,
+        structures: Arc<Mutex<PendingStructures>>,
+        regions: Arc<Mutex<Option<RegionStore>>>,
+        requests: Receiver<ChunkCoord>,
+        loaded: Sender<(ChunkCoord, T)>,
+    ) {
+        for coord in requests {
+            let position = (coord.x as f32, coord.y as f32, coord.z as f32);
+            let chunk = match Server::load_saved_chunk(&config, &regions, coord) {
+                Some(chunk) => {
+                    // the saved chunk has its structures, its neighbours still need theirs
+                    let blocks = StructureRegistry::generate(&config, coord);
+                    structures.lock().unwrap().distribute(coord, blocks);
+                    chunk
+                }
+                None => Terrain::create_chunk(
+                    &config,
+                    MeshingBackend::DataOnly,
+                    &structures,
+                    position,
+                    coord.get_lod(),
+                ),
+            };
+            if loaded.send((coord, chunk)).is_err() {
+                break;
+            }
+        }
+    }
+
+    /// Chunk saved to the region files at `coord`, None if it was never saved
+    fn load_saved_chunk(
+        config: &WorldGenConfig,
+        regions: &Mutex<Option<RegionStore>>,
+        coord: ChunkCoord,
+    ) -> Option<T> {
+        let data = match regions.lock().unwrap().as_mut()?.load(&coord) {
+            Ok(data) => data?,
+            Err(err) => {
+                log::error!(target: "net", "Failed to load chunk {:?}: {}", coord, err);
+                return None;
+            }
+        };
+        let position = (coord.x as f32, coord.y as f32, coord.z as f32);
+        match T::deserialize_data_only(&config.for_chunk(coord.into()), position, &data) {
+            Ok(chunk) => Some(chunk),
+            Err(err) => {
+                log::error!(target: "net", "Failed to decode chunk {:?}, generating it again: {}", coord, err);
+                None
+            }
+        }
+    }
+
+    fn send(&mut self, id: ClientId, message: &Message) {
+        if let Some(client) = self.clients.get_mut(&id) {
+            client.connection.send(message);
+        }
+    }
+
+    /// Sends `message` to every client that joined, except `except`
+    fn broadcast(&mut self, message: &Message, except: Option<ClientId>) {
+        for (id, client) in self.clients.iter_mut() {
+            if client.player.is_some() && Some(*id) != except {
+                client.connection.send(message);
+            }
+        }
+    }
+}
@@ -0,0 +1,13 @@
+#version 460 core
+
+out vec4 FragColor;
+
+in vec2 texCoord;
+
+uniform sampler2D colorTexture;
+uniform float exposure;
+
+void main() {
+    vec3 color = texture(colorTexture, texCoord).rgb;
+    FragColor = vec4(clamp(color * exp2(exposure), 0.0, 1.0), 1.0);
+}
@@ -0,0 +1,55 @@
+use cgmath::{Point3, Rad};
+
+use super::{
+    camera::controller::CameraControllerType,
+    renderer::{
+        depth_of_field::DepthOfField, framebuffer::ColorFrameBuffer, screen_quad::ScreenQuad,
+        shader::Shader, ui::UIRenderer,
+    },
+    utils::DataSource,
+};
+
+mod photo_mode;
+
+/// Pauses the scene and frees the camera for taking screenshots.
+///
+/// While active the owning layer hands its events and frame to the photo mode
+/// instead of updating the scene and rendering its own UI.
+pub struct PhotoMode {
+    active: bool,
+    show_ui: bool,
+    settings: PhotoSettings,
+    ui: UIRenderer,
+    shader: Shader,
+    screen_quad: ScreenQuad,
+    depth_of_field: DepthOfField,
+    scene_target: Option<ColorFrameBuffer>,
+    capture_target: Option<ColorFrameBuffer>,
+    previous_camera: Option<CameraState>,
+    /// Motion blur is turned off while paused, tiles of a screenshot would blur into each other
+    previous_motion_blur: bool,
+}
+
+/// Values edited through the photo mode panel
+pub struct PhotoSettings {
+    /// Exposure compensation in stops
+    pub exposure: DataSource<f32>,
+    /// Vertical field of view in degrees
+    pub fov: DataSource<f32>,
+    /// Shared with the depth of field pass, also set by clicking into the scene
+    pub focus_distance: DataSource<f32>,
+    /// Strength of the depth of field blur, 0 keeps everything sharp
+    pub aperture: DataSource<f32>,
+    /// Screenshots are rendered in `tiles`x`tiles` tiles of the window size
+    pub screenshot_tiles: DataSource<u32>,
+    capture: DataSource<bool>,
+}
+
+/// Camera setup before entering photo mode, restored when leaving it
+struct CameraState {
+    controller: CameraControllerType,
+    relative_position: Point3<f32>,
+    yaw: Rad<f32>,
+    pitch: Rad<f32>,
+    fovy: Rad<f32>,
+}
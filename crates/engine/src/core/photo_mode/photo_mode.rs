@@ -0,0 +1,350 @@
+use std::{
+    error::Error,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use cgmath::{Deg, Matrix4};
+use glfw::CursorMode;
+use image::{Rgba, RgbaImage};
+
+use crate::core::{
+    camera::controller::{FlyController, FlySettings},
+    entity::component::{camera_component::CameraComponent, Component},
+    input::Input,
+    renderer::{
+        depth_of_field::DepthOfField,
+        framebuffer::{ColorFrameBuffer, FrameBuffer},
+        screen_quad::ScreenQuad,
+        shader::Shader,
+        shader_manager::ShaderSource,
+        ui::{UIRenderer, UI},
+    },
+    scene::Scene,
+    utils::DataSource,
+    window::Window,
+};
+
+use super::{CameraState, PhotoMode, PhotoSettings};
+
+const PHOTO_CAMERA_SPEED: f32 = 2.0;
+const PHOTO_CAMERA_SENSITIVITY: f32 = 0.25;
+const MIN_FOV: f32 = 5.0;
+const MAX_FOV: f32 = 120.0;
+const MAX_SCREENSHOT_TILES: u32 = 8;
+const SCREENSHOT_DIRECTORY: &str = "screenshots";
+const CLEAR_COLOR: (f32, f32, f32, f32) = (0.3, 0.3, 0.5, 1.0);
+
+impl PhotoMode {
+    pub fn new() -> Self {
+        let settings = PhotoSettings::default();
+        let mut ui = UIRenderer::new();
+        let capture = settings.capture.clone();
+        let exposure = settings.exposure.clone();
+        let fov = settings.fov.clone();
+        let focus_distance = settings.focus_distance.clone();
+        let aperture = settings.aperture.clone();
+        let screenshot_tiles = settings.screenshot_tiles.clone();
+        let depth_of_field = DepthOfField::new(focus_distance.clone(), aperture.clone());
+        ui.add(UI::panel("Photo mode", move |builder| {
+            builder
+                .position(10.0, 10.0, 0.0)
+                .size(200.0, 330.0)
+                .add_child(None, UI::text("Exposure (EV)", 16.0, |b| b))
+                .add_child(None, UI::input(exposure, |i| i.size(190.0, 26.0)))
+                .add_child(None, UI::text("Field of view", 16.0, |b| b))
+                .add_child(None, UI::input(fov, |i| i.size(190.0, 26.0)))
+                .add_child(None, UI::text("Focus distance", 16.0, |b| b))
+                .add_child(None, UI::input(focus_distance, |i| i.size(190.0, 26.0)))
+                .add_child(None, UI::text("Aperture", 16.0, |b| b))
+                .add_child(None, UI::input(aperture, |i| i.size(190.0, 26.0)))
+                .add_child(None, UI::text("Screenshot tiles", 16.0, |b| b))
+                .add_child(None, UI::input(screenshot_tiles, |i| i.size(190.0, 26.0)))
+                .add_child(
+                    None,
+                    UI::button("Capture", Box::new(move |_| capture.write(true)), |b| b),
+                )
+        }));
+
+        Self {
+            active: false,
+            show_ui: true,
+            settings,
+            ui,
+            shader: Shader::load(
+                ScreenQuad::get_vertex_shader(),
+                ShaderSource::new("photo_mode/fragment.glsl", include_str!("fragment.glsl")),
+            ),
+            screen_quad: ScreenQuad::new(),
+            depth_of_field,
+            scene_target: None,
+            capture_target: None,
+            previous_camera: None,
+            previous_motion_blur: false,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    pub fn get_settings(&self) -> &PhotoSettings {
+        &self.settings
+    }
+
+    /// Swaps the camera controller for a slow free flying one, the previous camera is restored by `exit`
+    pub fn enter(&mut self, scene: &mut Scene, window: &mut glfw::Window) {
+        if self.active {
+            return;
+        }
+        let mut settings = scene.config.camera.clone();
+        let Some(camera) = scene.get_component_mut::<CameraComponent>() else {
+            return;
+        };
+        let controller = camera.get_camera_controller();
+        controller.store_settings(&mut settings);
+        let speed = controller.get_speed_ref();
+        let fovy = camera.get_projection().fovy;
+        self.previous_camera = Some(CameraState {
+            controller: controller.get_type(),
+            relative_position: camera.get_camera().get_relative_position(),
+            yaw: camera.get_camera().get_yaw(),
+            pitch: camera.get_camera().get_pitch(),
+            fovy,
+        });
+        camera.set_camera_controller(Box::new(FlyController::from_settings(
+            &FlySettings {
+                speed: PHOTO_CAMERA_SPEED,
+                sensitivity: PHOTO_CAMERA_SENSITIVITY,
+                // framing a shot needs the same slow speed at any height
+                altitude_scaling: false,
+            },
+            speed,
+        )));
+        scene.config.camera = settings;
+        self.previous_motion_blur = scene.config.motion_blur.enabled;
+        scene.config.motion_blur.enabled = false;
+        self.settings.fov.write(Deg::from(fovy).0);
+        window.set_cursor_mode(CursorMode::Normal);
+        self.active = true;
+    }
+
+    pub fn exit(&mut self, scene: &mut Scene, window: &mut glfw::Window) {
+        if !self.active {
+            return;
+        }
+        self.active = false;
+        window.set_cursor_mode(CursorMode::Normal);
+        scene.config.motion_blur.enabled = self.previous_motion_blur;
+        let Some(state) = self.previous_camera.take() else {
+            return;
+        };
+        let settings = scene.config.camera.clone();
+        if let Some(camera) = scene.get_component_mut::<CameraComponent>() {
+            let speed = camera.get_camera_controller().get_speed_ref();
+            camera.set_camera_controller(settings.create_controller_of(state.controller, speed));
+            camera
+                .get_camera_mut()
+                .update(state.relative_position, state.yaw, state.pitch);
+            camera.get_projection_mut().set_fovy(state.fovy);
+        }
+    }
+
+    /// "toggle_photo_mode" (F6) toggles photo mode, while it is active all events are consumed
+    /// and only reach the photo mode panel and the camera. "focus_at_cursor" (clicking into the
+    /// scene) focuses the depth of field on the point under the cursor
+    pub fn handle_event(
+        &mut self,
+        scene: &mut Scene,
+        window: &mut glfw::Window,
+        glfw: &mut glfw::Glfw,
+        event: &glfw::WindowEvent,
+    ) -> bool {
+        if Input::is_action_pressed("toggle_photo_mode", event) {
+            if self.active {
+                self.exit(scene, window);
+            } else {
+                self.enter(scene, window);
+            }
+            return true;
+        }
+        if !self.active {
+            return false;
+        }
+        if Input::is_action_pressed("capture_photo", event) {
+            self.settings.capture.write(true);
+            return true;
+        }
+        if self.show_ui && self.ui.handle_events(scene, window, glfw, event) {
+            return true;
+        }
+        if Input::is_action_pressed("toggle_photo_ui", event) {
+            self.show_ui = !self.show_ui;
+            return true;
+        }
+        if Input::is_action_pressed("focus_at_cursor", event)
+            && window.get_cursor_mode() == CursorMode::Normal
+        {
+            self.depth_of_field.focus_at_cursor(scene, window);
+            return true;
+        }
+        if let Some(camera) = scene.get_component_mut::<CameraComponent>() {
+            camera.handle_event(glfw, window, event);
+        }
+        true
+    }
+
+    /// Moves the camera and renders the frame, the rest of the scene is not updated
+    pub fn update(&mut self, scene: &mut Scene, window: &Window, delta_time: f64) {
+        scene.update_component::<CameraComponent>(delta_time);
+        let fov = self.settings.fov.read().clamp(MIN_FOV, MAX_FOV);
+        if let Some(camera) = scene.get_component_mut::<CameraComponent>() {
+            camera.get_projection_mut().set_fovy(Deg(fov));
+        }
+
+        if self.settings.capture.read() {
+            self.settings.capture.write(false);
+            match self.capture(scene, window) {
+                Ok(path) => log::info!("Saving screenshot to {}", path),
+                Err(err) => log::error!("Failed to capture screenshot: {}", err),
+            }
+        }
+
+        let scene_target =
+            ColorFrameBuffer::reuse(self.scene_target.take(), window.width, window.height);
+        PhotoMode::render_scene(scene, window, &scene_target);
+        window.reset_viewport();
+        if let Some(camera) = scene.get_component::<CameraComponent>() {
+            self.composite(&scene_target, camera.get_projection().get_matrix(), 1.0);
+        }
+        self.scene_target = Some(scene_target);
+
+        if self.show_ui {
+            self.ui.render(scene);
+        }
+    }
+
+    /// Renders the view in tiles of the window size and stitches them into one image
+    /// `screenshot_tiles` times the window resolution, returns the path it is saved to
+    pub fn capture(
+        &mut self,
+        scene: &mut Scene,
+        window: &Window,
+    ) -> Result<String, Box<dyn Error>> {
+        let tiles = self
+            .settings
+            .screenshot_tiles
+            .read()
+            .clamp(1, MAX_SCREENSHOT_TILES);
+        let (width, height) = (window.width, window.height);
+        let scene_target = ColorFrameBuffer::reuse(self.scene_target.take(), width, height);
+        let capture_target = ColorFrameBuffer::reuse(self.capture_target.take(), width, height);
+
+        let mut image = RgbaImage::new(width * tiles, height * tiles);
+        for tile_y in 0..tiles {
+            for tile_x in 0..tiles {
+                let Some(camera) = scene.get_component_mut::<CameraComponent>() else {
+                    break;
+                };
+                let projection = camera.get_projection_mut();
+                projection.set_tile(tiles, tile_x, tile_y);
+                let projection = projection.get_matrix();
+
+                PhotoMode::render_scene(scene, window, &scene_target);
+                capture_target.bind();
+                self.composite(&scene_target, projection, tiles as f32);
+                let pixels = capture_target.read_pixels();
+
+                // tiles and pixel rows are counted from the bottom, image rows from the top
+                for row in 0..height {
+                    let y = (tiles - 1 - tile_y) * height + (height - 1 - row);
+                    for column in 0..width {
+                        let i = ((row * width + column) * 4) as usize;
+                        image.put_pixel(
+                            tile_x * width + column,
+                            y,
+                            Rgba([pixels[i], pixels[i + 1], pixels[i + 2], 255]),
+                        );
+                    }
+                }
+            }
+        }
+        if let Some(camera) = scene.get_component_mut::<CameraComponent>() {
+            camera.get_projection_mut().reset_tile();
+        }
+        FrameBuffer::unbind();
+        window.reset_viewport();
+        self.scene_target = Some(scene_target);
+        self.capture_target = Some(capture_target);
+
+        std::fs::create_dir_all(SCREENSHOT_DIRECTORY)?;
+        let path = format!(
+            "{}/photo-{}.png",
+            SCREENSHOT_DIRECTORY,
+            SystemTime::now().duration_since(UNIX_EPOCH)?.as_millis()
+        );
+        // encoding a large png takes a while, don't stall the frame for it
+        let image_path = path.clone();
+        std::thread::spawn(move || {
+            if let Err(err) = image.save(&image_path) {
+                log::error!("Failed to save screenshot {}: {}", image_path, err);
+            }
+        });
+        Ok(path)
+    }
+
+    fn render_scene(scene: &mut Scene, window: &Window, target: &ColorFrameBuffer) {
+        target.bind();
+        window.clear(CLEAR_COLOR, gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+        scene.render(window);
+        FrameBuffer::unbind();
+    }
+
+    /// Applies depth of field and exposure to `scene_target` and draws it into the bound framebuffer
+    fn composite(
+        &mut self,
+        scene_target: &ColorFrameBuffer,
+        projection: Matrix4<f32>,
+        blur_scale: f32,
+    ) {
+        let frame = if self.depth_of_field.is_enabled() {
+            self.depth_of_field
+                .render(scene_target, &projection, blur_scale)
+        } else {
+            scene_target
+        };
+        let Some(color) = frame.get_color_texture() else {
+            return;
+        };
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0);
+        }
+        color.bind();
+        self.shader.bind();
+        self.shader.set_uniform_1i("colorTexture", 0);
+        self.shader
+            .set_uniform_1f("exposure", self.settings.exposure.read());
+        self.screen_quad.render(&self.shader);
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+        }
+    }
+}
+
+impl Default for PhotoMode {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Default for PhotoSettings {
+    fn default() -> Self {
+        Self {
+            exposure: DataSource::new(0.0),
+            fov: DataSource::new(45.0),
+            focus_distance: DataSource::new(10.0),
+            aperture: DataSource::new(0.0),
+            screenshot_tiles: DataSource::new(2),
+            capture: DataSource::new(false),
+        }
+    }
+}
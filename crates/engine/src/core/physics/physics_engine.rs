@@ -1,4 +1,14 @@
-use rapier3d::prelude::*;
+use cgmath::{Point3, Vector3};
+use rapier3d::{
+    control::{EffectiveCharacterMovement, KinematicCharacterController},
+    crossbeam::channel::{self, Receiver},
+    prelude::*,
+};
+
+/// Seconds one physics step simulates at a time scale of 1
+const STEP_TIME: f32 = 1.0 / 60.0;
+/// Height the rays of `height_at` start from, above any terrain
+const HEIGHT_QUERY_TOP: f32 = 4096.0;
 
 pub struct PhysicsEngine {
     pub rigid_bodies: RigidBodySet,
@@ -17,7 +27,10 @@ pub struct PhysicsEngine {
     query_pipeline: QueryPipeline,
 
     physics_hooks: (),
-    physics_events: (),
+    physics_events: ChannelEventCollector,
+    /// Contacts of colliders with `ActiveEvents::COLLISION_EVENTS` since the last
+    /// `take_collision_events`
+    collision_events: Receiver<CollisionEvent>,
 }
 
 impl PhysicsEngine {
@@ -35,7 +48,10 @@ impl PhysicsEngine {
         let ccd_solver = CCDSolver::new();
         let query_pipeline = QueryPipeline::new();
         let physics_hooks = ();
-        let physics_events = ();
+        let (collision_sender, collision_events) = channel::unbounded();
+        // contact forces are not used, their events are dropped
+        let (contact_force_sender, _) = channel::unbounded();
+        let physics_events = ChannelEventCollector::new(collision_sender, contact_force_sender);
 
         PhysicsEngine {
             rigid_bodies,
@@ -54,9 +70,15 @@ impl PhysicsEngine {
 
             physics_hooks,
             physics_events,
+            collision_events,
         }
     }
 
+    /// Slows down or speeds up the following steps by `time_scale`
+    pub fn set_time_scale(&mut self, time_scale: f32) {
+        self.integration_parameters.dt = STEP_TIME * time_scale;
+    }
+
     pub fn update(&mut self) {
         self.physics_pipeline.step(
             &self.gravity,
@@ -75,6 +97,12 @@ impl PhysicsEngine {
         );
     }
 
+    /// Collisions that started or stopped in the steps since the last call, the scene emits
+    /// them on its event bus
+    pub fn take_collision_events(&mut self) -> Vec<CollisionEvent> {
+        self.collision_events.try_iter().collect()
+    }
+
     pub fn add_rigid_body(&mut self, rigid_body: RigidBody) -> RigidBodyHandle {
         self.rigid_bodies.insert(rigid_body)
     }
@@ -91,4 +119,60 @@ impl PhysicsEngine {
             self.colliders.insert(collider)
         }
     }
+
+    /// Casts a ray against the fixed colliders, returns the distance to the closest hit
+    pub fn cast_ray(
+        &self,
+        origin: Point3<f32>,
+        direction: Vector3<f32>,
+        max_distance: f32,
+    ) -> Option<f32> {
+        let ray = Ray::new(
+            point![origin.x, origin.y, origin.z],
+            vector![direction.x, direction.y, direction.z],
+        );
+        self.query_pipeline
+            .cast_ray(
+                &self.rigid_bodies,
+                &self.colliders,
+                &ray,
+                max_distance,
+                true,
+                QueryFilter::only_fixed(),
+            )
+            .map(|(_, distance)| distance)
+    }
+
+    /// Height of the highest fixed collider at the world column `(x, z)`
+    pub fn height_at(&self, x: f32, z: f32) -> Option<f32> {
+        self.cast_ray(
+            Point3::new(x, HEIGHT_QUERY_TOP, z),
+            -Vector3::unit_y(),
+            HEIGHT_QUERY_TOP * 2.0,
+        )
+        .map(|distance| HEIGHT_QUERY_TOP - distance)
+    }
+
+    /// Moves `shape` placed at `position` by up to `translation` against the fixed colliders,
+    /// sliding along walls, stepping up ledges and snapping to the ground as `controller` allows
+    pub fn move_shape(
+        &self,
+        controller: &KinematicCharacterController,
+        shape: &dyn Shape,
+        position: Point3<f32>,
+        translation: Vector3<f32>,
+        delta_time: f32,
+    ) -> EffectiveCharacterMovement {
+        controller.move_shape(
+            delta_time,
+            &self.rigid_bodies,
+            &self.colliders,
+            &self.query_pipeline,
+            shape,
+            &Isometry::translation(position.x, position.y, position.z),
+            vector![translation.x, translation.y, translation.z],
+            QueryFilter::only_fixed(),
+            |_| {},
+        )
+    }
 }
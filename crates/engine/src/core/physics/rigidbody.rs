@@ -4,7 +4,11 @@ use nalgebra::UnitQuaternion;
 use rapier3d::prelude::*;
 
 use crate::core::{
-    entity::{component::Component, Entity},
+    entity::{
+        component::{Component, InspectableComponent},
+        Entity,
+    },
+    event::EventCategory,
     scene::Scene,
 };
 
@@ -50,15 +54,30 @@ impl RigidBody {
     pub fn set_rotation(&mut self, scene: &mut Scene, rotation: cgmath::Quaternion<f32>) {
         let rigid_body = &mut scene.physics_engine.rigid_bodies[self.rigid_body_handle];
         rigid_body.set_rotation(
-            UnitQuaternion::new(vector![rotation.v.x, rotation.v.y, rotation.v.z]),
+            UnitQuaternion::from_quaternion(nalgebra::Quaternion::new(
+                rotation.s,
+                rotation.v.x,
+                rotation.v.y,
+                rotation.v.z,
+            )),
             true,
         );
     }
 }
 
+impl InspectableComponent for RigidBody {
+    fn get_inspector_name(&self) -> &str {
+        "Rigid body"
+    }
+}
+
 impl Component for RigidBody {
     fn update(&mut self, scene: &mut Scene, entity: &mut Entity, _: f64) {
         let rigidbody = &scene.physics_engine.rigid_bodies[self.rigid_body_handle];
+        // resting bodies keep their pose, so their entities are not marked as changed
+        if rigidbody.is_sleeping() {
+            return;
+        }
         let translation = rigidbody.translation();
         let rotation = rigidbody.rotation();
         let quat = cgmath::Quaternion::new(rotation.w, rotation.i, rotation.j, rotation.k);
@@ -66,5 +85,19 @@ impl Component for RigidBody {
         entity.set_rotation(scene, quat);
     }
 
-    fn handle_event(&mut self, _: &mut Glfw, _: &mut glfw::Window, _: &WindowEvent) {}
+    fn get_event_priority(&self, _: EventCategory) -> Option<i32> {
+        None
+    }
+
+    fn handle_event(&mut self, _: &mut Glfw, _: &mut glfw::Window, _: &WindowEvent) -> bool {
+        false
+    }
+
+    fn as_inspectable(&self) -> Option<&dyn InspectableComponent> {
+        Some(self)
+    }
+
+    fn as_inspectable_mut(&mut self) -> Option<&mut dyn InspectableComponent> {
+        Some(self)
+    }
 }
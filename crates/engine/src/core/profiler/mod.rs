@@ -0,0 +1,60 @@
+use std::{collections::VecDeque, time::Duration, time::Instant};
+
+use gl::types::GLuint;
+
+mod profiler;
+
+/// Frames the profiler keeps in its history
+pub const PROFILER_HISTORY: usize = 240;
+/// Frames a gpu timer result may lag behind, its queries are reused round robin
+const GPU_TIMER_LATENCY: usize = 4;
+
+/// Collects cpu scopes, gpu pass timings and counters per frame and keeps them for the last
+/// `PROFILER_HISTORY` frames.
+///
+/// Profiling is off until `Profiler::set_enabled`, until then scopes and counters only check a
+/// flag and the render graph issues no timer queries
+pub struct Profiler {
+    current: FrameProfile,
+    history: VecDeque<FrameProfile>,
+}
+
+/// Timings and counters of one frame
+#[derive(Clone, Debug, Default)]
+pub struct FrameProfile {
+    /// Seconds the whole frame took
+    pub frame_time: f64,
+    /// Cpu time per scope, scopes entered several times per frame are summed up. Scopes on the
+    /// chunk loader threads count for the frame they end in
+    pub scopes: Vec<(&'static str, Duration)>,
+    /// Gpu time per render pass in milliseconds, measured a few frames earlier since the queries
+    /// are read without stalling
+    pub gpu_passes: Vec<(String, f64)>,
+    pub draw_calls: u32,
+    pub chunks_generated: u32,
+}
+
+/// Adds the time until it is dropped to its scope in the current frame, see `profile_scope!`
+pub struct ProfileScope {
+    name: &'static str,
+    /// `None` while profiling is disabled
+    start: Option<Instant>,
+}
+
+/// Measures the gpu time of the commands between `begin` and `end` with `GL_TIME_ELAPSED`
+/// queries. Timers must not be nested
+pub struct GpuTimer {
+    queries: [GLuint; GPU_TIMER_LATENCY],
+    /// Whether the query still holds a result that was not read
+    pending: [bool; GPU_TIMER_LATENCY],
+    next: usize,
+}
+
+/// Measures the cpu time until the end of the enclosing block and adds it to the scope `$name`
+/// of the current frame
+#[macro_export]
+macro_rules! profile_scope {
+    ($name:expr) => {
+        let _profile_scope = $crate::core::profiler::ProfileScope::new($name);
+    };
+}
@@ -0,0 +1,222 @@
+use std::{
+    collections::VecDeque,
+    sync::{
+        atomic::{AtomicBool, AtomicU32, Ordering},
+        Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use gl::types::*;
+use lazy_static::lazy_static;
+
+use super::{FrameProfile, GpuTimer, ProfileScope, Profiler, GPU_TIMER_LATENCY, PROFILER_HISTORY};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static DRAW_CALLS: AtomicU32 = AtomicU32::new(0);
+static CHUNKS_GENERATED: AtomicU32 = AtomicU32::new(0);
+
+lazy_static! {
+    static ref PROFILER: Mutex<Profiler> = Mutex::new(Profiler::new());
+}
+
+impl Profiler {
+    fn new() -> Self {
+        Self {
+            current: FrameProfile::default(),
+            history: VecDeque::with_capacity(PROFILER_HISTORY),
+        }
+    }
+
+    pub fn set_enabled(enabled: bool) {
+        ENABLED.store(enabled, Ordering::Relaxed);
+        if !enabled {
+            let mut profiler = PROFILER.lock().unwrap();
+            profiler.current = FrameProfile::default();
+            profiler.history.clear();
+        }
+    }
+
+    pub fn is_enabled() -> bool {
+        ENABLED.load(Ordering::Relaxed)
+    }
+
+    /// Closes the current frame, which took `frame_time` seconds. Called by the application after
+    /// every frame
+    pub fn end_frame(frame_time: f64) {
+        if !Self::is_enabled() {
+            return;
+        }
+        let mut profiler = PROFILER.lock().unwrap();
+        let mut frame = std::mem::take(&mut profiler.current);
+        frame.frame_time = frame_time;
+        frame.draw_calls = DRAW_CALLS.swap(0, Ordering::Relaxed);
+        frame.chunks_generated = CHUNKS_GENERATED.swap(0, Ordering::Relaxed);
+        if profiler.history.len() == PROFILER_HISTORY {
+            profiler.history.pop_front();
+        }
+        profiler.history.push_back(frame);
+    }
+
+    pub fn count_draw_call() {
+        if Self::is_enabled() {
+            DRAW_CALLS.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn count_generated_chunk() {
+        if Self::is_enabled() {
+            CHUNKS_GENERATED.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Adds the gpu time of the render pass `name` to the current frame
+    pub fn record_gpu_time(name: &str, milliseconds: f64) {
+        if Self::is_enabled() {
+            PROFILER
+                .lock()
+                .unwrap()
+                .current
+                .gpu_passes
+                .push((name.to_string(), milliseconds));
+        }
+    }
+
+    fn record_scope(name: &'static str, duration: Duration) {
+        let scopes = &mut PROFILER.lock().unwrap().current.scopes;
+        match scopes.iter_mut().find(|(scope, _)| *scope == name) {
+            Some((_, total)) => *total += duration,
+            None => scopes.push((name, duration)),
+        }
+    }
+
+    /// The last completed frame
+    pub fn get_latest() -> Option<FrameProfile> {
+        PROFILER.lock().unwrap().history.back().cloned()
+    }
+
+    /// Frame times of the history in seconds, oldest first
+    pub fn get_frame_times() -> Vec<f64> {
+        let profiler = PROFILER.lock().unwrap();
+        profiler
+            .history
+            .iter()
+            .map(|frame| frame.frame_time)
+            .collect()
+    }
+
+    /// Average cpu time of the scope `name` over the history in seconds
+    pub fn get_average_scope_time(name: &str) -> f64 {
+        let profiler = PROFILER.lock().unwrap();
+        let total: Duration = profiler
+            .history
+            .iter()
+            .flat_map(|frame| frame.scopes.iter())
+            .filter(|(scope, _)| *scope == name)
+            .map(|(_, duration)| *duration)
+            .sum();
+        total.as_secs_f64() / profiler.history.len().max(1) as f64
+    }
+
+    /// Chunks generated per second over the history
+    pub fn get_chunk_throughput() -> f64 {
+        let profiler = PROFILER.lock().unwrap();
+        let time: f64 = profiler.history.iter().map(|frame| frame.frame_time).sum();
+        let chunks: u32 = profiler
+            .history
+            .iter()
+            .map(|frame| frame.chunks_generated)
+            .sum();
+        if time > 0.0 {
+            chunks as f64 / time
+        } else {
+            0.0
+        }
+    }
+}
+
+impl ProfileScope {
+    pub fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            start: Profiler::is_enabled().then(Instant::now),
+        }
+    }
+}
+
+impl Drop for ProfileScope {
+    fn drop(&mut self) {
+        if let Some(start) = self.start {
+            Profiler::record_scope(self.name, start.elapsed());
+        }
+    }
+}
+
+impl GpuTimer {
+    pub fn new() -> Self {
+        let mut queries = [0; GPU_TIMER_LATENCY];
+        unsafe {
+            gl::GenQueries(GPU_TIMER_LATENCY as GLsizei, queries.as_mut_ptr());
+        }
+        Self {
+            queries,
+            pending: [false; GPU_TIMER_LATENCY],
+            next: 0,
+        }
+    }
+
+    pub fn begin(&mut self) {
+        unsafe {
+            gl::BeginQuery(gl::TIME_ELAPSED, self.queries[self.next]);
+        }
+    }
+
+    /// Ends the measurement, returns the milliseconds of the oldest measurement whose result is
+    /// available by now
+    pub fn end(&mut self) -> Option<f64> {
+        unsafe {
+            gl::EndQuery(gl::TIME_ELAPSED);
+        }
+        self.pending[self.next] = true;
+        self.next = (self.next + 1) % GPU_TIMER_LATENCY;
+        // the query reused next is the oldest one
+        self.read(self.next)
+    }
+
+    fn read(&mut self, index: usize) -> Option<f64> {
+        if !self.pending[index] {
+            return None;
+        }
+        let mut available = 0;
+        unsafe {
+            gl::GetQueryObjectiv(
+                self.queries[index],
+                gl::QUERY_RESULT_AVAILABLE,
+                &mut available,
+            );
+        }
+        if available == 0 {
+            return None;
+        }
+        let mut nanoseconds = 0;
+        unsafe {
+            gl::GetQueryObjectui64v(self.queries[index], gl::QUERY_RESULT, &mut nanoseconds);
+        }
+        self.pending[index] = false;
+        Some(nanoseconds as f64 / 1_000_000.0)
+    }
+}
+
+impl Default for GpuTimer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for GpuTimer {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteQueries(GPU_TIMER_LATENCY as GLsizei, self.queries.as_ptr());
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+
+mod quality;
+
+/// Detail settings that trade image quality for frame time, stored in the `EngineConfig` and
+/// adjusted by the `AutoQuality` controller while `AutoQualitySettings::enabled` is set
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct QualitySettings {
+    /// Number of chunks around the camera the terrain draws in every direction, limited to the
+    /// `chunk_radius` of the terrain as it only draws the chunks it loaded
+    pub view_radius: usize,
+    /// Divides the distances at which vegetation is culled or swapped for impostors, values above
+    /// 1 lower the detail sooner
+    pub lod_bias: f32,
+    /// Width and height of the shadow map, None keeps the size it was added to the scene with
+    pub shadow_resolution: Option<u32>,
+}
+
+/// Bounds and target of the `AutoQuality` controller
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AutoQualitySettings {
+    pub enabled: bool,
+    pub target_fps: f32,
+    /// Fraction the average frame time has to miss the target by before the quality changes,
+    /// so the controller doesn't flip between two levels around the target
+    pub hysteresis: f32,
+    /// Seconds the frame time is averaged over between two adjustments
+    pub interval: f32,
+    pub min_view_radius: usize,
+    pub max_view_radius: usize,
+    pub min_lod_bias: f32,
+    pub max_lod_bias: f32,
+    pub min_shadow_resolution: u32,
+    pub max_shadow_resolution: u32,
+}
+
+/// Lowers the quality settings one step at a time while the frames take longer than the target
+/// allows and raises them again once there is time to spare. Shadows are reduced first and
+/// restored last, the view radius the other way around
+#[derive(Default)]
+pub struct AutoQuality {
+    /// Seconds and frames since the last adjustment
+    elapsed: f64,
+    frames: u32,
+}
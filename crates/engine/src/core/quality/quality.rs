@@ -0,0 +1,120 @@
+use crate::terrain::CHUNK_RADIUS;
+
+use super::{AutoQuality, AutoQualitySettings, QualitySettings};
+
+/// Step the lod bias changes by per adjustment
+const LOD_BIAS_STEP: f32 = 0.25;
+
+impl AutoQuality {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a frame that took `delta_time` seconds and adjusts `quality` once the interval
+    /// passed, returns true if it changed
+    pub fn update(
+        &mut self,
+        settings: &AutoQualitySettings,
+        quality: &mut QualitySettings,
+        delta_time: f64,
+    ) -> bool {
+        if !settings.enabled || settings.target_fps <= 0.0 {
+            self.reset();
+            return false;
+        }
+        self.elapsed += delta_time;
+        self.frames += 1;
+        if self.elapsed < settings.interval as f64 {
+            return false;
+        }
+        let frame_time = self.elapsed / self.frames as f64;
+        self.reset();
+
+        let target = 1.0 / settings.target_fps as f64;
+        let hysteresis = settings.hysteresis.max(0.0) as f64;
+        let changed = if frame_time > target * (1.0 + hysteresis) {
+            AutoQuality::lower(settings, quality)
+        } else if frame_time < target * (1.0 - hysteresis) {
+            AutoQuality::raise(settings, quality)
+        } else {
+            false
+        };
+        if changed {
+            log::debug!(
+                "Average frame time {:.2}ms, quality changed to {:?}",
+                frame_time * 1000.0,
+                quality
+            );
+        }
+        changed
+    }
+
+    fn reset(&mut self) {
+        self.elapsed = 0.0;
+        self.frames = 0;
+    }
+
+    fn lower(settings: &AutoQualitySettings, quality: &mut QualitySettings) -> bool {
+        let shadow_resolution = quality
+            .shadow_resolution
+            .unwrap_or(settings.max_shadow_resolution);
+        if shadow_resolution / 2 >= settings.min_shadow_resolution {
+            quality.shadow_resolution = Some(shadow_resolution / 2);
+            return true;
+        }
+        if quality.lod_bias + LOD_BIAS_STEP <= settings.max_lod_bias {
+            quality.lod_bias += LOD_BIAS_STEP;
+            return true;
+        }
+        if quality.view_radius > settings.min_view_radius {
+            quality.view_radius -= 1;
+            return true;
+        }
+        false
+    }
+
+    fn raise(settings: &AutoQualitySettings, quality: &mut QualitySettings) -> bool {
+        if quality.view_radius < settings.max_view_radius {
+            quality.view_radius += 1;
+            return true;
+        }
+        if quality.lod_bias - LOD_BIAS_STEP >= settings.min_lod_bias {
+            quality.lod_bias -= LOD_BIAS_STEP;
+            return true;
+        }
+        if let Some(shadow_resolution) = quality.shadow_resolution {
+            if shadow_resolution * 2 <= settings.max_shadow_resolution {
+                quality.shadow_resolution = Some(shadow_resolution * 2);
+                return true;
+            }
+        }
+        false
+    }
+}
+
+impl Default for QualitySettings {
+    fn default() -> Self {
+        Self {
+            view_radius: CHUNK_RADIUS,
+            lod_bias: 1.0,
+            shadow_resolution: None,
+        }
+    }
+}
+
+impl Default for AutoQualitySettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            target_fps: 60.0,
+            hysteresis: 0.15,
+            interval: 1.0,
+            min_view_radius: 2,
+            max_view_radius: CHUNK_RADIUS,
+            min_lod_bias: 1.0,
+            max_lod_bias: 2.0,
+            min_shadow_resolution: 1024,
+            max_shadow_resolution: 4096,
+        }
+    }
+}
@@ -0,0 +1,237 @@
+use std::f32::consts::TAU;
+
+use cgmath::{Matrix4, Point3, SquareMatrix, Vector3, Vector4};
+
+use crate::{
+    core::{
+        entity::component::camera_component::CameraComponent,
+        renderer::{
+            line::{Line, LineRenderer},
+            render_graph::{RenderPass, RenderResources, DEBUG_PASS_ORDER},
+        },
+        scene::Scene,
+        window::Window,
+    },
+    terrain::ChunkBounds,
+};
+
+use super::{DebugBatch, DebugDraw, DebugDrawPass, SPHERE_SEGMENTS};
+
+/// Corners of a box indexed by bits, bit 0 selects the maximum x, bit 1 y and bit 2 z
+const BOX_EDGES: [(usize, usize); 12] = [
+    (0, 1),
+    (2, 3),
+    (4, 5),
+    (6, 7),
+    (0, 2),
+    (1, 3),
+    (4, 6),
+    (5, 7),
+    (0, 4),
+    (1, 5),
+    (2, 6),
+    (3, 7),
+];
+
+impl DebugDraw {
+    pub fn new() -> Self {
+        Self {
+            batches: Vec::new(),
+        }
+    }
+
+    pub fn draw_line(
+        &mut self,
+        from: Point3<f32>,
+        to: Point3<f32>,
+        color: Vector3<f32>,
+        depth_test: bool,
+    ) {
+        // the direction is not normalized, lines are only drawn and never intersected
+        let line = Line::new(from, to - from, 1.0);
+        self.get_batch(color, depth_test).lines.push(line);
+    }
+
+    /// Axis aligned box from `min` to `max`
+    pub fn draw_aabb(
+        &mut self,
+        min: Point3<f32>,
+        max: Point3<f32>,
+        color: Vector3<f32>,
+        depth_test: bool,
+    ) {
+        let corners: Vec<Point3<f32>> = (0..8)
+            .map(|corner| {
+                Point3::new(
+                    if corner & 1 == 0 { min.x } else { max.x },
+                    if corner & 2 == 0 { min.y } else { max.y },
+                    if corner & 4 == 0 { min.z } else { max.z },
+                )
+            })
+            .collect();
+        self.draw_box(&corners, color, depth_test);
+    }
+
+    /// Sphere outlined by a circle around each axis
+    pub fn draw_sphere(
+        &mut self,
+        center: Point3<f32>,
+        radius: f32,
+        color: Vector3<f32>,
+        depth_test: bool,
+    ) {
+        let axes = [
+            (Vector3::unit_x(), Vector3::unit_y()),
+            (Vector3::unit_x(), Vector3::unit_z()),
+            (Vector3::unit_y(), Vector3::unit_z()),
+        ];
+        for (u, v) in axes {
+            self.draw_circle(center, u, v, radius, color, depth_test);
+        }
+    }
+
+    /// Circle around `center` in the plane spanned by the unit vectors `u` and `v`
+    pub fn draw_circle(
+        &mut self,
+        center: Point3<f32>,
+        u: Vector3<f32>,
+        v: Vector3<f32>,
+        radius: f32,
+        color: Vector3<f32>,
+        depth_test: bool,
+    ) {
+        let point = |segment: usize| {
+            let (sin, cos) = (segment as f32 / SPHERE_SEGMENTS as f32 * TAU).sin_cos();
+            center + (u * cos + v * sin) * radius
+        };
+        for segment in 0..SPHERE_SEGMENTS {
+            self.draw_line(point(segment), point(segment + 1), color, depth_test);
+        }
+    }
+
+    /// Volume `view_projection` maps into clip space, e.g. the frustum of a camera or the volume
+    /// of a shadow map
+    pub fn draw_frustum(
+        &mut self,
+        view_projection: &Matrix4<f32>,
+        color: Vector3<f32>,
+        depth_test: bool,
+    ) {
+        let Some(inverse) = view_projection.invert() else {
+            return;
+        };
+        let corners: Vec<Point3<f32>> = (0..8)
+            .map(|corner| {
+                let ndc = Vector4::new(
+                    if corner & 1 == 0 { -1.0 } else { 1.0 },
+                    if corner & 2 == 0 { -1.0 } else { 1.0 },
+                    if corner & 4 == 0 { -1.0 } else { 1.0 },
+                    1.0,
+                );
+                let world = inverse * ndc;
+                Point3::new(world.x, world.y, world.z) / world.w
+            })
+            .collect();
+        self.draw_box(&corners, color, depth_test);
+    }
+
+    /// Axes of `transform` from its origin, x in red, y in green and z in blue
+    pub fn draw_axes(&mut self, transform: &Matrix4<f32>, size: f32, depth_test: bool) {
+        let origin = Point3::new(transform.w.x, transform.w.y, transform.w.z);
+        let axes = [
+            (transform.x.truncate(), Vector3::new(1.0, 0.0, 0.0)),
+            (transform.y.truncate(), Vector3::new(0.0, 1.0, 0.0)),
+            (transform.z.truncate(), Vector3::new(0.0, 0.0, 1.0)),
+        ];
+        for (axis, color) in axes {
+            self.draw_line(origin, origin + axis * size, color, depth_test);
+        }
+    }
+
+    pub fn draw_chunk_bounds(
+        &mut self,
+        bounds: &ChunkBounds,
+        color: Vector3<f32>,
+        depth_test: bool,
+    ) {
+        self.draw_aabb(
+            Point3::new(
+                bounds.min.0 as f32,
+                bounds.min.1 as f32,
+                bounds.min.2 as f32,
+            ),
+            Point3::new(
+                bounds.max.0 as f32,
+                bounds.max.1 as f32,
+                bounds.max.2 as f32,
+            ),
+            color,
+            depth_test,
+        );
+    }
+
+    /// Removes the shapes drawn so far, called by the scene after rendering a frame
+    pub fn clear(&mut self) {
+        self.batches.clear();
+    }
+
+    /// Draws the shapes with the given view projection
+    pub fn render(&self, view_projection: &Matrix4<f32>) {
+        for batch in &self.batches {
+            LineRenderer::render_lines(
+                view_projection,
+                &batch.lines,
+                batch.color,
+                !batch.depth_test,
+            );
+        }
+    }
+
+    fn draw_box(&mut self, corners: &[Point3<f32>], color: Vector3<f32>, depth_test: bool) {
+        for (from, to) in BOX_EDGES {
+            self.draw_line(corners[from], corners[to], color, depth_test);
+        }
+    }
+
+    fn get_batch(&mut self, color: Vector3<f32>, depth_test: bool) -> &mut DebugBatch {
+        let index = match self
+            .batches
+            .iter()
+            .position(|batch| batch.color == color && batch.depth_test == depth_test)
+        {
+            Some(index) => index,
+            None => {
+                self.batches.push(DebugBatch {
+                    color,
+                    depth_test,
+                    lines: Vec::new(),
+                });
+                self.batches.len() - 1
+            }
+        };
+        &mut self.batches[index]
+    }
+}
+
+impl Default for DebugDraw {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RenderPass for DebugDrawPass {
+    fn get_name(&self) -> &str {
+        "Debug draw"
+    }
+
+    fn get_order(&self) -> i32 {
+        DEBUG_PASS_ORDER
+    }
+
+    fn render(&mut self, scene: &Scene, _: &Window, _: &mut RenderResources) {
+        let Some(camera) = scene.get_component::<CameraComponent>() else {
+            return;
+        };
+        scene.debug_draw.render(&camera.get_view_projection());
+    }
+}
@@ -0,0 +1,25 @@
+use cgmath::Vector3;
+
+use super::line::Line;
+
+mod debug_draw;
+
+/// Segments a circle of `DebugDraw::draw_circle` is made of
+const SPHERE_SEGMENTS: usize = 32;
+
+/// Shapes to draw for debugging, collected while updating and drawn by the `DebugDrawPass` of the
+/// scene once the frame is rendered. Components reach it through `Scene::debug_draw`, the shapes
+/// are cleared after every frame so they have to be drawn again each update
+pub struct DebugDraw {
+    batches: Vec<DebugBatch>,
+}
+
+/// Lines drawn with the same color and depth test in a single draw call
+struct DebugBatch {
+    color: Vector3<f32>,
+    depth_test: bool,
+    lines: Vec<Line>,
+}
+
+/// Draws the shapes of `Scene::debug_draw` on top of the frame
+pub struct DebugDrawPass;
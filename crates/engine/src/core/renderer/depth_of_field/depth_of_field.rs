@@ -0,0 +1,125 @@
+use cgmath::{InnerSpace, Matrix4, SquareMatrix};
+
+use crate::core::{
+    entity::component::camera_component::CameraComponent,
+    renderer::{
+        framebuffer::{ColorFrameBuffer, FrameBuffer},
+        screen_quad::ScreenQuad,
+        shader::Shader,
+        shader_manager::ShaderSource,
+    },
+    scene::Scene,
+    utils::DataSource,
+};
+
+use super::DepthOfField;
+
+/// How far clicking into the scene looks for something to focus on
+const MAX_FOCUS_DISTANCE: f32 = 1000.0;
+
+impl DepthOfField {
+    pub fn new(focus_distance: DataSource<f32>, aperture: DataSource<f32>) -> Self {
+        Self {
+            focus_distance,
+            aperture,
+            shader: Shader::load(
+                ScreenQuad::get_vertex_shader(),
+                ShaderSource::new(
+                    "renderer/depth_of_field/fragment.glsl",
+                    include_str!("fragment.glsl"),
+                ),
+            ),
+            screen_quad: ScreenQuad::new(),
+            target: None,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.aperture.read() > 0.0
+    }
+
+    /// Blurs `source` into the effect's own framebuffer and returns it, the framebuffer bound
+    /// before is bound again afterwards.
+    ///
+    /// `projection` has to be the one `source` was rendered with, `blur_scale` scales the
+    /// blur radius for frames rendered larger than the window.
+    pub fn render(
+        &mut self,
+        source: &ColorFrameBuffer,
+        projection: &Matrix4<f32>,
+        blur_scale: f32,
+    ) -> &ColorFrameBuffer {
+        let previous = FrameBuffer::get_bound_id();
+        let target =
+            ColorFrameBuffer::reuse(self.target.take(), source.get_width(), source.get_height());
+        let target = self.target.insert(target);
+        target.bind();
+        if let (Some(color), Some(depth)) = (source.get_color_texture(), source.get_depth_texture())
+        {
+            unsafe {
+                gl::ActiveTexture(gl::TEXTURE0);
+                color.bind();
+                gl::ActiveTexture(gl::TEXTURE1);
+                depth.bind();
+            }
+            self.shader.bind();
+            self.shader.set_uniform_1i("colorTexture", 0);
+            self.shader.set_uniform_1i("depthTexture", 1);
+            self.shader.set_uniform_mat4(
+                "inverseProjection",
+                &projection.invert().unwrap_or(Matrix4::identity()),
+            );
+            self.shader.set_uniform_2f(
+                "texelSize",
+                1.0 / source.get_width() as f32,
+                1.0 / source.get_height() as f32,
+            );
+            self.shader
+                .set_uniform_1f("focusDistance", self.focus_distance.read());
+            self.shader
+                .set_uniform_1f("aperture", self.aperture.read().max(0.0));
+            self.shader.set_uniform_1f("blurScale", blur_scale);
+            self.screen_quad.render(&self.shader);
+            unsafe {
+                gl::ActiveTexture(gl::TEXTURE1);
+                gl::BindTexture(gl::TEXTURE_2D, 0);
+                gl::ActiveTexture(gl::TEXTURE0);
+                gl::BindTexture(gl::TEXTURE_2D, 0);
+            }
+        }
+        FrameBuffer::bind_id(previous);
+        target
+    }
+
+    /// Focuses on the terrain under the cursor, returns false if there is nothing to focus on
+    pub fn focus_at_cursor(&self, scene: &Scene, window: &glfw::Window) -> bool {
+        let (x, y) = window.get_cursor_pos();
+        let (width, height) = window.get_size();
+        self.focus_at(
+            scene,
+            2.0 * x as f32 / width.max(1) as f32 - 1.0,
+            1.0 - 2.0 * y as f32 / height.max(1) as f32,
+        )
+    }
+
+    /// Focuses on the terrain at a point in normalized device coordinates by casting a ray into
+    /// the scene, returns false if nothing was hit
+    pub fn focus_at(&self, scene: &Scene, x: f32, y: f32) -> bool {
+        let Some(camera_component) = scene.get_component::<CameraComponent>() else {
+            return false;
+        };
+        let camera = camera_component.get_camera();
+        let direction = camera.get_ray_direction(camera_component.get_projection(), x, y);
+        let Some(distance) =
+            scene
+                .physics_engine
+                .cast_ray(camera.get_eye_position(), direction, MAX_FOCUS_DISTANCE)
+        else {
+            return false;
+        };
+        // the blur works with view space depth, not the distance along the ray
+        self.focus_distance
+            .write(distance * direction.dot(camera.get_direction()));
+        true
+    }
+}
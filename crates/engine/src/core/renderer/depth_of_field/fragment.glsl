@@ -0,0 +1,50 @@
+#version 460 core
+
+out vec4 FragColor;
+
+in vec2 texCoord;
+
+uniform sampler2D colorTexture;
+uniform sampler2D depthTexture;
+uniform mat4 inverseProjection;
+uniform vec2 texelSize;
+uniform float focusDistance;
+uniform float aperture;
+// frames rendered larger than the window blur further to look the same
+uniform float blurScale;
+
+const int SAMPLES = 48;
+const float GOLDEN_ANGLE = 2.39996323;
+const float MAX_BLUR_RADIUS = 12.0;
+
+float linearDepth(vec2 uv) {
+    float depth = texture(depthTexture, uv).r;
+    vec4 position = inverseProjection * vec4(0.0, 0.0, depth * 2.0 - 1.0, 1.0);
+    return -position.z / position.w;
+}
+
+// radius of the circle of confusion in pixels
+float circleOfConfusion(float depth) {
+    return clamp(abs(depth - focusDistance) / max(depth, 0.001) * aperture, 0.0, 1.0)
+        * MAX_BLUR_RADIUS * blurScale;
+}
+
+void main() {
+    vec3 color = texture(colorTexture, texCoord).rgb;
+    float radius = circleOfConfusion(linearDepth(texCoord));
+    if (radius > 0.5) {
+        // scatter as gather along a golden angle spiral, samples only count if their own blur reaches this pixel
+        vec3 sum = color;
+        float total = 1.0;
+        for (int i = 1; i < SAMPLES; i++) {
+            float distance = radius * sqrt(float(i) / float(SAMPLES));
+            float angle = float(i) * GOLDEN_ANGLE;
+            vec2 uv = texCoord + vec2(cos(angle), sin(angle)) * distance * texelSize;
+            float weight = clamp(circleOfConfusion(linearDepth(uv)) - distance + 1.0, 0.0, 1.0);
+            sum += texture(colorTexture, uv).rgb * weight;
+            total += weight;
+        }
+        color = sum / total;
+    }
+    FragColor = vec4(color, 1.0);
+}
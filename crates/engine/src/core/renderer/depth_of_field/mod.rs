@@ -0,0 +1,16 @@
+use crate::core::utils::DataSource;
+
+use super::{framebuffer::ColorFrameBuffer, screen_quad::ScreenQuad, shader::Shader};
+
+mod depth_of_field;
+
+/// Post effect blurring a rendered frame by each pixel's distance to the focus plane
+pub struct DepthOfField {
+    /// View space depth that stays sharp
+    pub focus_distance: DataSource<f32>,
+    /// Strength of the blur, 0 keeps everything sharp
+    pub aperture: DataSource<f32>,
+    shader: Shader,
+    screen_quad: ScreenQuad,
+    target: Option<ColorFrameBuffer>,
+}
@@ -0,0 +1,20 @@
+layout (std140, binding = 0) uniform Environment {
+    // rgb fog color, a fog density at the fog height
+    vec4 fogColorDensity;
+    // x fog height, y falloff of the density above it
+    vec4 fogParams;
+    // xyz camera position
+    vec4 environmentCamera;
+};
+
+// exponential height fog integrated along the view ray to the fragment
+vec3 applyFog(vec3 color, vec3 worldPosition) {
+    vec3 ray = worldPosition - environmentCamera.xyz;
+    float falloff = max(fogParams.y, 0.0001);
+    float density = fogColorDensity.a * exp(-falloff * (environmentCamera.y - fogParams.x));
+    float heightChange = falloff * ray.y;
+    // density integral along the ray divided by its length, it tends to 1 for flat rays
+    float integral = abs(heightChange) > 0.0001 ? (1.0 - exp(-heightChange)) / heightChange : 1.0;
+    float fog = 1.0 - exp(-density * integral * length(ray));
+    return mix(color, fogColorDensity.rgb, clamp(fog, 0.0, 1.0));
+}
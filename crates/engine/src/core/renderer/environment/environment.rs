@@ -0,0 +1,67 @@
+use std::ffi::c_void;
+
+use cgmath::Point3;
+use gl::types::GLsizeiptr;
+
+use super::{Environment, EnvironmentBuffer, GpuEnvironment, ENVIRONMENT_BUFFER_BINDING};
+
+impl Default for Environment {
+    fn default() -> Self {
+        Self {
+            fog_color: [0.3, 0.3, 0.5],
+            fog_density: 0.004,
+            fog_height: 50.0,
+            fog_height_falloff: 0.02,
+        }
+    }
+}
+
+impl EnvironmentBuffer {
+    pub fn new() -> Self {
+        let mut buffer = 0;
+        unsafe {
+            gl::GenBuffers(1, &mut buffer);
+        }
+        Self { buffer }
+    }
+
+    /// Uploads the environment as seen from `camera_position` and binds it for the shaders
+    pub fn upload(&self, environment: &Environment, camera_position: Point3<f32>) {
+        let [red, green, blue] = environment.fog_color;
+        let data = GpuEnvironment {
+            fog_color_density: [red, green, blue, environment.fog_density.max(0.0)],
+            fog_params: [
+                environment.fog_height,
+                environment.fog_height_falloff,
+                0.0,
+                0.0,
+            ],
+            camera_position: [camera_position.x, camera_position.y, camera_position.z, 1.0],
+        };
+        unsafe {
+            gl::BindBuffer(gl::UNIFORM_BUFFER, self.buffer);
+            gl::BufferData(
+                gl::UNIFORM_BUFFER,
+                std::mem::size_of::<GpuEnvironment>() as GLsizeiptr,
+                &data as *const GpuEnvironment as *const c_void,
+                gl::DYNAMIC_DRAW,
+            );
+            gl::BindBuffer(gl::UNIFORM_BUFFER, 0);
+            gl::BindBufferBase(gl::UNIFORM_BUFFER, ENVIRONMENT_BUFFER_BINDING, self.buffer);
+        }
+    }
+}
+
+impl Default for EnvironmentBuffer {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for EnvironmentBuffer {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteBuffers(1, &self.buffer);
+        }
+    }
+}
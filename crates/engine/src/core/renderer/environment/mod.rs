@@ -0,0 +1,43 @@
+use gl::types::GLuint;
+use serde::{Deserialize, Serialize};
+
+mod environment;
+
+/// Uniform buffer binding of the `Environment` block from `environment.glsl`
+pub const ENVIRONMENT_BUFFER_BINDING: GLuint = 0;
+const ENVIRONMENT_GLSL: &str = include_str!("environment.glsl");
+const ENVIRONMENT_INCLUDE: &str = "#include \"environment.glsl\"";
+
+/// Atmosphere of a scene, shared with the shaders through the `Environment` uniform block
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Environment {
+    /// Color distant surfaces fade into, should match the background
+    pub fog_color: [f32; 3],
+    /// Fog density at `fog_height`, 0 disables the fog
+    pub fog_density: f32,
+    /// World height the fog density is given at
+    pub fog_height: f32,
+    /// How fast the fog thins out above `fog_height`, per world unit
+    pub fog_height_falloff: f32,
+}
+
+/// Uniform buffer holding the `Environment` block, the scene uploads it every frame
+pub struct EnvironmentBuffer {
+    buffer: GLuint,
+}
+
+/// `Environment` block as laid out in std140
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+struct GpuEnvironment {
+    fog_color_density: [f32; 4],
+    fog_params: [f32; 4],
+    camera_position: [f32; 4],
+}
+
+/// Replaces the `#include "environment.glsl"` line of a shader source with the `Environment`
+/// block and `applyFog`
+pub fn include_environment(source: &str) -> String {
+    source.replace(ENVIRONMENT_INCLUDE, ENVIRONMENT_GLSL)
+}
@@ -4,6 +4,7 @@ pub struct FrameBuffer {
     id: u32,
     width: u32,
     height: u32,
+    color_texture: Option<Texture>,
     depth_texture: Option<Texture>,
 }
 
@@ -19,10 +20,28 @@ impl FrameBuffer {
             id,
             width,
             height,
+            color_texture: None,
             depth_texture: None,
         }
     }
 
+    pub fn append_color_texture(&mut self, texture: Texture) {
+        self.bind();
+        unsafe {
+            gl::FramebufferTexture2D(
+                gl::FRAMEBUFFER,
+                gl::COLOR_ATTACHMENT0,
+                gl::TEXTURE_2D,
+                texture.id,
+                0,
+            );
+            gl::DrawBuffer(gl::COLOR_ATTACHMENT0);
+            gl::ReadBuffer(gl::COLOR_ATTACHMENT0);
+        }
+        self.color_texture = Some(texture);
+        FrameBuffer::unbind();
+    }
+
     pub fn append_depth_texture(&mut self, texture: Texture) {
         self.bind();
         unsafe {
@@ -38,6 +57,20 @@ impl FrameBuffer {
         FrameBuffer::unbind();
     }
 
+    /// Attaches a depth texture owned by another framebuffer, so drawing is depth tested against its contents
+    pub fn attach_shared_depth_texture(&self, texture_id: u32) {
+        self.bind();
+        unsafe {
+            gl::FramebufferTexture2D(
+                gl::FRAMEBUFFER,
+                gl::DEPTH_ATTACHMENT,
+                gl::TEXTURE_2D,
+                texture_id,
+                0,
+            );
+        }
+    }
+
     pub fn bind(&self) {
         unsafe {
             gl::BindTexture(gl::TEXTURE_2D, 0);
@@ -55,6 +88,21 @@ impl FrameBuffer {
         }
     }
 
+    /// Id of the framebuffer currently bound for drawing, 0 is the window
+    pub fn get_bound_id() -> u32 {
+        let mut id = 0;
+        unsafe {
+            gl::GetIntegerv(gl::DRAW_FRAMEBUFFER_BINDING, &mut id);
+        }
+        id as u32
+    }
+
+    pub fn bind_id(id: u32) {
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, id);
+        }
+    }
+
     pub fn depth_only(&self) {
         self.bind();
         unsafe {
@@ -64,9 +112,25 @@ impl FrameBuffer {
         FrameBuffer::unbind();
     }
 
+    pub fn get_color_texture(&self) -> Option<&Texture> {
+        self.color_texture.as_ref()
+    }
+
     pub fn get_depth_texture(&self) -> Option<&Texture> {
         self.depth_texture.as_ref()
     }
+
+    pub fn get_id(&self) -> u32 {
+        self.id
+    }
+
+    pub fn get_width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn get_height(&self) -> u32 {
+        self.height
+    }
 }
 
 impl Drop for FrameBuffer {
@@ -97,3 +161,325 @@ impl ShadowFrameBuffer {
         self.0.get_depth_texture()
     }
 }
+
+/// Framebuffer with a color and a depth texture for passes that post process the rendered scene.
+/// HDR ones have a half float color texture, so colors are not clamped before tone mapping
+pub struct ColorFrameBuffer(pub FrameBuffer, bool);
+
+impl ColorFrameBuffer {
+    pub fn new(width: u32, height: u32) -> Self {
+        ColorFrameBuffer::create(width, height, false)
+    }
+
+    pub fn new_hdr(width: u32, height: u32) -> Self {
+        ColorFrameBuffer::create(width, height, true)
+    }
+
+    fn create(width: u32, height: u32, hdr: bool) -> Self {
+        let mut fbo = FrameBuffer::new(width, height);
+        let color_texture = Texture::new();
+        if hdr {
+            color_texture.set_as_hdr_texture(width, height);
+        } else {
+            color_texture.set_as_color_texture(width, height);
+        }
+        fbo.append_color_texture(color_texture);
+        let depth_texture = Texture::new();
+        depth_texture.set_as_depth_texture(width, height);
+        fbo.append_depth_texture(depth_texture);
+        Self(fbo, hdr)
+    }
+
+    /// Keeps `target` if it already has the given size, otherwise creates a new one
+    pub fn reuse(target: Option<ColorFrameBuffer>, width: u32, height: u32) -> Self {
+        match target {
+            Some(target)
+                if target.get_width() == width && target.get_height() == height && !target.1 =>
+            {
+                target
+            }
+            _ => ColorFrameBuffer::new(width, height),
+        }
+    }
+
+    /// Keeps `target` if it is an HDR framebuffer of the given size, otherwise creates a new one
+    pub fn reuse_hdr(target: Option<ColorFrameBuffer>, width: u32, height: u32) -> Self {
+        match target {
+            Some(target)
+                if target.get_width() == width && target.get_height() == height && target.1 =>
+            {
+                target
+            }
+            _ => ColorFrameBuffer::new_hdr(width, height),
+        }
+    }
+
+    pub fn is_hdr(&self) -> bool {
+        self.1
+    }
+
+    pub fn bind(&self) {
+        self.0.bind();
+    }
+
+    /// Reads the color attachment as tightly packed RGBA rows, starting at the bottom
+    pub fn read_pixels(&self) -> Vec<u8> {
+        let mut pixels = vec![0u8; (self.0.width * self.0.height * 4) as usize];
+        self.bind();
+        unsafe {
+            gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+            gl::ReadPixels(
+                0,
+                0,
+                self.0.width as i32,
+                self.0.height as i32,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                pixels.as_mut_ptr() as *mut _,
+            );
+        }
+        FrameBuffer::unbind();
+        pixels
+    }
+
+    pub fn get_color_texture(&self) -> Option<&Texture> {
+        self.0.get_color_texture()
+    }
+
+    pub fn get_depth_texture(&self) -> Option<&Texture> {
+        self.0.get_depth_texture()
+    }
+
+    pub fn get_id(&self) -> u32 {
+        self.0.get_id()
+    }
+
+    pub fn get_width(&self) -> u32 {
+        self.0.get_width()
+    }
+
+    pub fn get_height(&self) -> u32 {
+        self.0.get_height()
+    }
+}
+
+/// Framebuffer with a half float color texture and no depth texture of its own
+pub struct FloatFrameBuffer(pub FrameBuffer);
+
+impl FloatFrameBuffer {
+    pub fn new(width: u32, height: u32) -> Self {
+        let mut fbo = FrameBuffer::new(width, height);
+        let color_texture = Texture::new();
+        color_texture.set_as_float_texture(width, height);
+        fbo.append_color_texture(color_texture);
+        Self(fbo)
+    }
+
+    /// Keeps `target` if it already has the given size, otherwise creates a new one
+    pub fn reuse(target: Option<FloatFrameBuffer>, width: u32, height: u32) -> Self {
+        match target {
+            Some(target) if target.0.get_width() == width && target.0.get_height() == height => {
+                target
+            }
+            _ => FloatFrameBuffer::new(width, height),
+        }
+    }
+
+    pub fn bind(&self) {
+        self.0.bind();
+    }
+
+    pub fn get_color_texture(&self) -> Option<&Texture> {
+        self.0.get_color_texture()
+    }
+}
+
+/// Framebuffer the G-buffer pass renders into, with half float textures at attachment 1 and 2
+/// and its own depth texture. Scene shaders write their normals to output location 1 and their
+/// material to location 2, their color output is discarded
+pub struct GBufferFrameBuffer(pub FrameBuffer, Texture);
+
+impl GBufferFrameBuffer {
+    pub fn new(width: u32, height: u32) -> Self {
+        let mut fbo = FrameBuffer::new(width, height);
+        let normal_texture = Texture::new();
+        normal_texture.set_as_float_texture(width, height);
+        let material_texture = Texture::new();
+        material_texture.set_as_float_texture(width, height);
+        fbo.bind();
+        unsafe {
+            gl::FramebufferTexture2D(
+                gl::FRAMEBUFFER,
+                gl::COLOR_ATTACHMENT1,
+                gl::TEXTURE_2D,
+                normal_texture.id,
+                0,
+            );
+            gl::FramebufferTexture2D(
+                gl::FRAMEBUFFER,
+                gl::COLOR_ATTACHMENT2,
+                gl::TEXTURE_2D,
+                material_texture.id,
+                0,
+            );
+            gl::DrawBuffers(
+                3,
+                [gl::NONE, gl::COLOR_ATTACHMENT1, gl::COLOR_ATTACHMENT2].as_ptr(),
+            );
+            gl::ReadBuffer(gl::COLOR_ATTACHMENT1);
+        }
+        fbo.color_texture = Some(normal_texture);
+        FrameBuffer::unbind();
+        let depth_texture = Texture::new();
+        depth_texture.set_as_depth_texture(width, height);
+        fbo.append_depth_texture(depth_texture);
+        Self(fbo, material_texture)
+    }
+
+    /// Keeps `target` if it already has the given size, otherwise creates a new one
+    pub fn reuse(target: Option<GBufferFrameBuffer>, width: u32, height: u32) -> Self {
+        match target {
+            Some(target) if target.0.get_width() == width && target.0.get_height() == height => {
+                target
+            }
+            _ => GBufferFrameBuffer::new(width, height),
+        }
+    }
+
+    pub fn bind(&self) {
+        self.0.bind();
+    }
+
+    pub fn get_normal_texture(&self) -> Option<&Texture> {
+        self.0.get_color_texture()
+    }
+
+    pub fn get_material_texture(&self) -> &Texture {
+        &self.1
+    }
+
+    pub fn get_depth_texture(&self) -> Option<&Texture> {
+        self.0.get_depth_texture()
+    }
+}
+
+/// Framebuffer with multisampled color and depth renderbuffers, resolved into a `ColorFrameBuffer`
+/// of the same size before its contents are read
+pub struct MultisampleFrameBuffer {
+    framebuffer: FrameBuffer,
+    renderbuffers: [u32; 2],
+    samples: u32,
+    hdr: bool,
+}
+
+impl MultisampleFrameBuffer {
+    /// Framebuffer of the size and formats of `target`, the framebuffer it is resolved into
+    pub fn new(samples: u32, target: &ColorFrameBuffer) -> Self {
+        let (width, height) = (target.get_width(), target.get_height());
+        let color_format = target
+            .get_color_texture()
+            .map_or(gl::RGBA8, Texture::get_internal_format);
+        let depth_format = target
+            .get_depth_texture()
+            .map_or(gl::DEPTH_COMPONENT24, Texture::get_internal_format);
+        let framebuffer = FrameBuffer::new(width, height);
+        let mut renderbuffers = [0; 2];
+        unsafe {
+            gl::GenRenderbuffers(2, renderbuffers.as_mut_ptr());
+            for (renderbuffer, format, attachment) in [
+                (renderbuffers[0], color_format, gl::COLOR_ATTACHMENT0),
+                (renderbuffers[1], depth_format, gl::DEPTH_ATTACHMENT),
+            ] {
+                gl::BindRenderbuffer(gl::RENDERBUFFER, renderbuffer);
+                gl::RenderbufferStorageMultisample(
+                    gl::RENDERBUFFER,
+                    samples as i32,
+                    format,
+                    width as i32,
+                    height as i32,
+                );
+                gl::FramebufferRenderbuffer(
+                    gl::FRAMEBUFFER,
+                    attachment,
+                    gl::RENDERBUFFER,
+                    renderbuffer,
+                );
+            }
+            gl::BindRenderbuffer(gl::RENDERBUFFER, 0);
+            gl::DrawBuffer(gl::COLOR_ATTACHMENT0);
+            gl::ReadBuffer(gl::COLOR_ATTACHMENT0);
+        }
+        FrameBuffer::unbind();
+        Self {
+            framebuffer,
+            renderbuffers,
+            samples,
+            hdr: target.is_hdr(),
+        }
+    }
+
+    /// Keeps `previous` if it matches `target` and has the given samples, otherwise creates a
+    /// new one
+    pub fn reuse(
+        previous: Option<MultisampleFrameBuffer>,
+        samples: u32,
+        target: &ColorFrameBuffer,
+    ) -> Self {
+        match previous {
+            Some(previous)
+                if previous.framebuffer.get_width() == target.get_width()
+                    && previous.framebuffer.get_height() == target.get_height()
+                    && previous.samples == samples
+                    && previous.hdr == target.is_hdr() =>
+            {
+                previous
+            }
+            _ => MultisampleFrameBuffer::new(samples, target),
+        }
+    }
+
+    pub fn bind(&self) {
+        self.framebuffer.bind();
+    }
+
+    /// Averages the samples of the color and copies the depth into `target`
+    pub fn resolve(&self, target: &ColorFrameBuffer) {
+        let (width, height) = (
+            self.framebuffer.get_width() as i32,
+            self.framebuffer.get_height() as i32,
+        );
+        unsafe {
+            gl::BindFramebuffer(gl::READ_FRAMEBUFFER, self.framebuffer.get_id());
+            gl::BindFramebuffer(gl::DRAW_FRAMEBUFFER, target.get_id());
+            gl::BlitFramebuffer(
+                0,
+                0,
+                width,
+                height,
+                0,
+                0,
+                width,
+                height,
+                gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT,
+                gl::NEAREST,
+            );
+        }
+        FrameBuffer::unbind();
+    }
+
+    pub fn get_id(&self) -> u32 {
+        self.framebuffer.get_id()
+    }
+
+    pub fn get_samples(&self) -> u32 {
+        self.samples
+    }
+}
+
+impl Drop for MultisampleFrameBuffer {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteRenderbuffers(2, self.renderbuffers.as_ptr());
+        }
+    }
+}
@@ -0,0 +1,147 @@
+use std::{
+    ffi::c_void,
+    ptr, slice,
+    sync::atomic::{AtomicBool, AtomicU32, Ordering},
+};
+
+use gl::types::*;
+
+/// Severity of a driver debug message, from least to most severe
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DebugSeverity {
+    Notification,
+    Low,
+    Medium,
+    High,
+}
+
+static MIN_SEVERITY: AtomicU32 = AtomicU32::new(DebugSeverity::Low as u32);
+static ERROR_CHECKS: AtomicBool = AtomicBool::new(cfg!(debug_assertions));
+
+/// Forwards the driver's debug messages (KHR_debug) of at least `min_severity` to the `gl` log
+/// target. Messages are reported synchronously, so they are logged from inside the gl call that
+/// caused them. Returns false if the context does not support debug output
+pub fn enable_debug_output(min_severity: DebugSeverity) -> bool {
+    if !gl::DebugMessageCallback::is_loaded() {
+        return false;
+    }
+    set_min_severity(min_severity);
+    unsafe {
+        gl::Enable(gl::DEBUG_OUTPUT);
+        gl::Enable(gl::DEBUG_OUTPUT_SYNCHRONOUS);
+        gl::DebugMessageCallback(Some(log_debug_message), ptr::null());
+    }
+    true
+}
+
+pub fn disable_debug_output() {
+    if !gl::DebugMessageCallback::is_loaded() {
+        return;
+    }
+    unsafe {
+        gl::Disable(gl::DEBUG_OUTPUT);
+        gl::DebugMessageCallback(None, ptr::null());
+    }
+}
+
+/// Debug messages below `min_severity` are dropped
+pub fn set_min_severity(min_severity: DebugSeverity) {
+    MIN_SEVERITY.store(min_severity as u32, Ordering::Relaxed);
+}
+
+/// Turns the `check_errors` calls after draw calls and render passes on or off, they are on in
+/// debug builds
+pub fn set_error_checks(enabled: bool) {
+    ERROR_CHECKS.store(enabled, Ordering::Relaxed);
+}
+
+/// Logs every pending gl error as caused by `operation`, returns whether there were any
+pub fn check_errors(operation: &str) -> bool {
+    if !ERROR_CHECKS.load(Ordering::Relaxed) {
+        return false;
+    }
+    let mut failed = false;
+    loop {
+        let error = unsafe { gl::GetError() };
+        if error == gl::NO_ERROR {
+            return failed;
+        }
+        failed = true;
+        log::error!(target: "gl", "{} failed with {}", operation, get_error_name(error));
+    }
+}
+
+fn get_error_name(error: GLenum) -> &'static str {
+    match error {
+        gl::INVALID_ENUM => "GL_INVALID_ENUM",
+        gl::INVALID_VALUE => "GL_INVALID_VALUE",
+        gl::INVALID_OPERATION => "GL_INVALID_OPERATION",
+        gl::INVALID_FRAMEBUFFER_OPERATION => "GL_INVALID_FRAMEBUFFER_OPERATION",
+        gl::OUT_OF_MEMORY => "GL_OUT_OF_MEMORY",
+        gl::STACK_UNDERFLOW => "GL_STACK_UNDERFLOW",
+        gl::STACK_OVERFLOW => "GL_STACK_OVERFLOW",
+        _ => "an unknown error",
+    }
+}
+
+impl DebugSeverity {
+    fn from_gl(severity: GLenum) -> Self {
+        match severity {
+            gl::DEBUG_SEVERITY_HIGH => DebugSeverity::High,
+            gl::DEBUG_SEVERITY_MEDIUM => DebugSeverity::Medium,
+            gl::DEBUG_SEVERITY_LOW => DebugSeverity::Low,
+            _ => DebugSeverity::Notification,
+        }
+    }
+
+    fn get_level(&self) -> log::Level {
+        match self {
+            DebugSeverity::High => log::Level::Error,
+            DebugSeverity::Medium => log::Level::Warn,
+            DebugSeverity::Low => log::Level::Info,
+            DebugSeverity::Notification => log::Level::Debug,
+        }
+    }
+}
+
+extern "system" fn log_debug_message(
+    source: GLenum,
+    kind: GLenum,
+    id: GLuint,
+    severity: GLenum,
+    length: GLsizei,
+    message: *const GLchar,
+    _: *mut c_void,
+) {
+    let severity = DebugSeverity::from_gl(severity);
+    if (severity as u32) < MIN_SEVERITY.load(Ordering::Relaxed) || message.is_null() {
+        return;
+    }
+    let message = unsafe { slice::from_raw_parts(message as *const u8, length.max(0) as usize) };
+    let source = match source {
+        gl::DEBUG_SOURCE_API => "api",
+        gl::DEBUG_SOURCE_WINDOW_SYSTEM => "window system",
+        gl::DEBUG_SOURCE_SHADER_COMPILER => "shader compiler",
+        gl::DEBUG_SOURCE_THIRD_PARTY => "third party",
+        gl::DEBUG_SOURCE_APPLICATION => "application",
+        _ => "other",
+    };
+    let kind = match kind {
+        gl::DEBUG_TYPE_ERROR => "error",
+        gl::DEBUG_TYPE_DEPRECATED_BEHAVIOR => "deprecated behavior",
+        gl::DEBUG_TYPE_UNDEFINED_BEHAVIOR => "undefined behavior",
+        gl::DEBUG_TYPE_PORTABILITY => "portability",
+        gl::DEBUG_TYPE_PERFORMANCE => "performance",
+        gl::DEBUG_TYPE_MARKER => "marker",
+        _ => "other",
+    };
+    log::log!(
+        target: "gl",
+        severity.get_level(),
+        "{} {} {}: {}",
+        source,
+        kind,
+        id,
+        String::from_utf8_lossy(message).trim_end()
+    );
+}
@@ -0,0 +1,135 @@
+use crate::core::{
+    renderer::{
+        framebuffer::ColorFrameBuffer,
+        render_graph::{RenderPass, RenderResources, BLOOM_PASS_ORDER, FRAME_COLOR},
+        screen_quad::ScreenQuad,
+        shader::Shader,
+        shader_manager::ShaderSource,
+    },
+    scene::Scene,
+    window::Window,
+};
+
+use super::{BloomPass, BloomSettings};
+
+impl Default for BloomSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            threshold: 1.0,
+            intensity: 0.3,
+            iterations: 3,
+        }
+    }
+}
+
+impl BloomPass {
+    pub fn new() -> Self {
+        let shader =
+            |fragment: ShaderSource| Shader::load(ScreenQuad::get_vertex_shader(), fragment);
+        Self {
+            threshold_shader: shader(ShaderSource::new(
+                "renderer/hdr/bloom_threshold_fragment.glsl",
+                include_str!("bloom_threshold_fragment.glsl"),
+            )),
+            blur_shader: shader(ShaderSource::new(
+                "renderer/hdr/bloom_blur_fragment.glsl",
+                include_str!("bloom_blur_fragment.glsl"),
+            )),
+            composite_shader: shader(ShaderSource::new(
+                "renderer/hdr/bloom_composite_fragment.glsl",
+                include_str!("bloom_composite_fragment.glsl"),
+            )),
+            screen_quad: ScreenQuad::new(),
+            blur_targets: [None, None],
+            target: None,
+        }
+    }
+
+    /// Draws `texture` with `shader` into `target`, the shader's other uniforms have to be set
+    fn draw(&self, shader: &Shader, texture: u32, target: &ColorFrameBuffer) {
+        target.bind();
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, texture);
+        }
+        shader.set_uniform_1i("colorTexture", 0);
+        self.screen_quad.render(shader);
+    }
+}
+
+impl Default for BloomPass {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RenderPass for BloomPass {
+    fn get_name(&self) -> &str {
+        "Bloom"
+    }
+
+    fn get_order(&self) -> i32 {
+        BLOOM_PASS_ORDER
+    }
+
+    fn render(&mut self, scene: &Scene, _: &Window, resources: &mut RenderResources) {
+        let settings = &scene.config.bloom;
+        if !settings.enabled || settings.intensity <= 0.0 {
+            return;
+        }
+        let Some(color) = resources.get_texture(FRAME_COLOR) else {
+            return;
+        };
+
+        let (width, height) = resources.get_size();
+        let (blur_width, blur_height) = ((width / 2).max(1), (height / 2).max(1));
+        let [first, second] = &mut self.blur_targets;
+        let blur_targets = [
+            ColorFrameBuffer::reuse_hdr(first.take(), blur_width, blur_height),
+            ColorFrameBuffer::reuse_hdr(second.take(), blur_width, blur_height),
+        ];
+        let blur_textures = blur_targets
+            .each_ref()
+            .map(|target| target.get_color_texture().map_or(0, |texture| texture.id));
+
+        self.threshold_shader.bind();
+        self.threshold_shader
+            .set_uniform_1f("threshold", settings.threshold.max(0.0));
+        self.draw(&self.threshold_shader, color, &blur_targets[0]);
+        self.blur_shader.bind();
+        for _ in 0..settings.iterations.max(1) {
+            self.blur_shader
+                .set_uniform_2f("direction", 1.0 / blur_width as f32, 0.0);
+            self.draw(&self.blur_shader, blur_textures[0], &blur_targets[1]);
+            self.blur_shader
+                .set_uniform_2f("direction", 0.0, 1.0 / blur_height as f32);
+            self.draw(&self.blur_shader, blur_textures[1], &blur_targets[0]);
+        }
+
+        let target = ColorFrameBuffer::reuse_hdr(self.target.take(), width, height);
+        self.composite_shader.bind();
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE1);
+            gl::BindTexture(gl::TEXTURE_2D, blur_textures[0]);
+        }
+        self.composite_shader.set_uniform_1i("bloomTexture", 1);
+        self.composite_shader
+            .set_uniform_1f("intensity", settings.intensity);
+        self.draw(&self.composite_shader, color, &target);
+        unsafe {
+            for unit in (0..2).rev() {
+                gl::ActiveTexture(gl::TEXTURE0 + unit);
+                gl::BindTexture(gl::TEXTURE_2D, 0);
+            }
+        }
+
+        if let Some(texture) = target.get_color_texture() {
+            resources.set_texture(FRAME_COLOR, texture);
+        }
+        let [first, second] = blur_targets;
+        self.blur_targets = [Some(first), Some(second)];
+        self.target = Some(target);
+        resources.bind_target();
+    }
+}
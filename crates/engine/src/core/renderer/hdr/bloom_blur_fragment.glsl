@@ -0,0 +1,22 @@
+#version 460 core
+
+out vec4 FragColor;
+
+in vec2 texCoord;
+
+uniform sampler2D colorTexture;
+// one texel along the direction of the blur
+uniform vec2 direction;
+
+// 9 tap gaussian, the taps between two texels are merged into one linear sample
+const float OFFSETS[3] = float[](0.0, 1.3846153846, 3.2307692308);
+const float WEIGHTS[3] = float[](0.2270270270, 0.3162162162, 0.0702702703);
+
+void main() {
+    vec3 color = texture(colorTexture, texCoord).rgb * WEIGHTS[0];
+    for (int i = 1; i < 3; i++) {
+        color += texture(colorTexture, texCoord + direction * OFFSETS[i]).rgb * WEIGHTS[i];
+        color += texture(colorTexture, texCoord - direction * OFFSETS[i]).rgb * WEIGHTS[i];
+    }
+    FragColor = vec4(color, 1.0);
+}
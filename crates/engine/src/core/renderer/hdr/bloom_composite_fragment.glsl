@@ -0,0 +1,14 @@
+#version 460 core
+
+out vec4 FragColor;
+
+in vec2 texCoord;
+
+uniform sampler2D colorTexture;
+uniform sampler2D bloomTexture;
+uniform float intensity;
+
+void main() {
+    vec3 color = texture(colorTexture, texCoord).rgb;
+    FragColor = vec4(color + texture(bloomTexture, texCoord).rgb * intensity, 1.0);
+}
@@ -0,0 +1,16 @@
+#version 460 core
+
+out vec4 FragColor;
+
+in vec2 texCoord;
+
+uniform sampler2D colorTexture;
+uniform float threshold;
+
+void main() {
+    vec3 color = max(texture(colorTexture, texCoord).rgb, vec3(0.0));
+    float brightness = max(color.r, max(color.g, color.b));
+    // keeps the hue of the glow and fades it in above the threshold
+    float glow = max(brightness - threshold, 0.0) / max(brightness, 0.0001);
+    FragColor = vec4(color * glow, 1.0);
+}
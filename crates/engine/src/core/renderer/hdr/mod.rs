@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+
+use super::{framebuffer::ColorFrameBuffer, screen_quad::ScreenQuad, shader::Shader};
+
+mod bloom;
+mod tone_mapping;
+
+/// Curve the tone mapping pass maps the HDR colors with
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ToneMapOperator {
+    /// Filmic curve with a soft shoulder and slightly more contrast
+    #[default]
+    Aces,
+    /// `color / (1 + color)`, keeps the colors flatter
+    Reinhard,
+}
+
+/// Tone mapping settings, stored in the `EngineConfig`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ToneMappingSettings {
+    pub operator: ToneMapOperator,
+    /// Multiplies the colors before they are mapped, higher values brighten the frame
+    pub exposure: f32,
+}
+
+/// Bloom settings, stored in the `EngineConfig`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BloomSettings {
+    pub enabled: bool,
+    /// Brightness above which colors glow, 1 is white before the exposure
+    pub threshold: f32,
+    /// How strongly the glow is added to the frame
+    pub intensity: f32,
+    /// Horizontal and vertical blurs applied to the glow, more spread it further
+    pub iterations: u32,
+}
+
+/// Adds a glow around the parts of the HDR frame brighter than the threshold. They are extracted
+/// into a texture of half the size, blurred horizontally and vertically and added back to the frame
+pub struct BloomPass {
+    threshold_shader: Shader,
+    blur_shader: Shader,
+    composite_shader: Shader,
+    screen_quad: ScreenQuad,
+    /// Half size textures the blur ping-pongs between
+    blur_targets: [Option<ColorFrameBuffer>; 2],
+    target: Option<ColorFrameBuffer>,
+}
+
+/// Maps the HDR frame to colors between 0 and 1 with the exposure and operator of the
+/// `ToneMappingSettings`
+pub struct ToneMappingPass {
+    shader: Shader,
+    screen_quad: ScreenQuad,
+    target: Option<ColorFrameBuffer>,
+}
@@ -0,0 +1,90 @@
+use crate::core::{
+    renderer::{
+        framebuffer::ColorFrameBuffer,
+        render_graph::{RenderPass, RenderResources, FRAME_COLOR, TONE_MAPPING_PASS_ORDER},
+        screen_quad::ScreenQuad,
+        shader::Shader,
+        shader_manager::ShaderSource,
+    },
+    scene::Scene,
+    window::Window,
+};
+
+use super::{ToneMapOperator, ToneMappingPass, ToneMappingSettings};
+
+impl Default for ToneMappingSettings {
+    fn default() -> Self {
+        Self {
+            operator: ToneMapOperator::Aces,
+            exposure: 1.0,
+        }
+    }
+}
+
+impl ToneMappingPass {
+    pub fn new() -> Self {
+        Self {
+            shader: Shader::load(
+                ScreenQuad::get_vertex_shader(),
+                ShaderSource::new(
+                    "renderer/hdr/tone_mapping_fragment.glsl",
+                    include_str!("tone_mapping_fragment.glsl"),
+                ),
+            ),
+            screen_quad: ScreenQuad::new(),
+            target: None,
+        }
+    }
+}
+
+impl Default for ToneMappingPass {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RenderPass for ToneMappingPass {
+    fn get_name(&self) -> &str {
+        "Tone mapping"
+    }
+
+    fn get_order(&self) -> i32 {
+        TONE_MAPPING_PASS_ORDER
+    }
+
+    fn render(&mut self, scene: &Scene, _: &Window, resources: &mut RenderResources) {
+        let settings = &scene.config.tone_mapping;
+        let Some(color) = resources.get_texture(FRAME_COLOR) else {
+            return;
+        };
+
+        let (width, height) = resources.get_size();
+        let target = ColorFrameBuffer::reuse(self.target.take(), width, height);
+        target.bind();
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, color);
+        }
+        self.shader.bind();
+        self.shader.set_uniform_1i("colorTexture", 0);
+        self.shader
+            .set_uniform_1f("exposure", settings.exposure.max(0.0));
+        self.shader.set_uniform_1i(
+            "toneMapOperator",
+            match settings.operator {
+                ToneMapOperator::Aces => 0,
+                ToneMapOperator::Reinhard => 1,
+            },
+        );
+        self.screen_quad.render(&self.shader);
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+        }
+
+        if let Some(texture) = target.get_color_texture() {
+            resources.set_texture(FRAME_COLOR, texture);
+        }
+        self.target = Some(target);
+        resources.bind_target();
+    }
+}
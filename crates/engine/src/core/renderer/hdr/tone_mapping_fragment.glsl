@@ -0,0 +1,24 @@
+#version 460 core
+
+out vec4 FragColor;
+
+in vec2 texCoord;
+
+uniform sampler2D colorTexture;
+uniform float exposure;
+// 0 is ACES, 1 is Reinhard
+uniform int toneMapOperator;
+
+// fit of the ACES filmic curve by Krzysztof Narkowicz
+vec3 aces(vec3 color) {
+    return clamp((color * (2.51 * color + 0.03)) / (color * (2.43 * color + 0.59) + 0.14), 0.0, 1.0);
+}
+
+vec3 reinhard(vec3 color) {
+    return color / (1.0 + color);
+}
+
+void main() {
+    vec3 color = max(texture(colorTexture, texCoord).rgb, vec3(0.0)) * exposure;
+    FragColor = vec4(toneMapOperator == 1 ? reinhard(color) : aces(color), 1.0);
+}
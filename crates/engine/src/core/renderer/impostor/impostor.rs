@@ -0,0 +1,185 @@
+use std::f32::consts::TAU;
+
+use cgmath::{Matrix4, Point3, Vector3};
+use gl::types::{GLint, GLuint};
+
+use crate::core::renderer::framebuffer::FrameBuffer;
+
+use super::ImpostorAtlas;
+
+impl ImpostorAtlas {
+    /// Bakes the model inside `min`..`max` into `views` frames of `frame_size` pixels by calling
+    /// `draw` with the view projection of every frame. `draw` has to write the color to the first
+    /// and the model space normal, mapped to `0..1`, to the second color attachment.
+    ///
+    /// Has to be called on the thread owning the gl context
+    pub fn bake(
+        views: usize,
+        frame_size: (u32, u32),
+        min: Point3<f32>,
+        max: Point3<f32>,
+        draw: impl Fn(&Matrix4<f32>),
+    ) -> Self {
+        let (width, height) = (frame_size.0 as i32 * views as i32, frame_size.1 as i32);
+        let levels = (frame_size.0.min(frame_size.1) as f32).log2().floor() as i32 + 1;
+        let mut textures = [0; 2];
+        let mut framebuffer = 0;
+        let mut depth_buffer = 0;
+        unsafe {
+            gl::GenTextures(textures.len() as i32, textures.as_mut_ptr());
+            for texture in textures {
+                gl::BindTexture(gl::TEXTURE_2D, texture);
+                gl::TexStorage2D(gl::TEXTURE_2D, levels, gl::RGBA8, width, height);
+                gl::TexParameteri(
+                    gl::TEXTURE_2D,
+                    gl::TEXTURE_MIN_FILTER,
+                    gl::LINEAR_MIPMAP_LINEAR as GLint,
+                );
+                gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as GLint);
+                gl::TexParameteri(
+                    gl::TEXTURE_2D,
+                    gl::TEXTURE_WRAP_S,
+                    gl::CLAMP_TO_EDGE as GLint,
+                );
+                gl::TexParameteri(
+                    gl::TEXTURE_2D,
+                    gl::TEXTURE_WRAP_T,
+                    gl::CLAMP_TO_EDGE as GLint,
+                );
+            }
+            gl::GenRenderbuffers(1, &mut depth_buffer);
+            gl::BindRenderbuffer(gl::RENDERBUFFER, depth_buffer);
+            gl::RenderbufferStorage(gl::RENDERBUFFER, gl::DEPTH_COMPONENT24, width, height);
+            gl::BindRenderbuffer(gl::RENDERBUFFER, 0);
+
+            gl::GenFramebuffers(1, &mut framebuffer);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, framebuffer);
+            for (i, texture) in textures.iter().enumerate() {
+                gl::FramebufferTexture2D(
+                    gl::FRAMEBUFFER,
+                    gl::COLOR_ATTACHMENT0 + i as u32,
+                    gl::TEXTURE_2D,
+                    *texture,
+                    0,
+                );
+            }
+            gl::FramebufferRenderbuffer(
+                gl::FRAMEBUFFER,
+                gl::DEPTH_ATTACHMENT,
+                gl::RENDERBUFFER,
+                depth_buffer,
+            );
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+        }
+        let atlas = Self {
+            framebuffer,
+            depth_buffer,
+            albedo: textures[0],
+            normals: textures[1],
+            views,
+            frame_size,
+            min,
+            max,
+        };
+        atlas.render(draw);
+        atlas
+    }
+
+    fn render(&self, draw: impl Fn(&Matrix4<f32>)) {
+        let previous_framebuffer = FrameBuffer::get_bound_id();
+        let mut previous_viewport = [0; 4];
+        unsafe {
+            gl::GetIntegerv(gl::VIEWPORT, previous_viewport.as_mut_ptr());
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.framebuffer);
+            gl::DrawBuffers(2, [gl::COLOR_ATTACHMENT0, gl::COLOR_ATTACHMENT1].as_ptr());
+            gl::ClearBufferfv(gl::COLOR, 0, [0.0f32; 4].as_ptr());
+            // flat normals facing the viewer where nothing was drawn, so mipmaps blend sensibly
+            gl::ClearBufferfv(gl::COLOR, 1, [0.5f32, 0.5, 1.0, 0.0].as_ptr());
+            gl::Clear(gl::DEPTH_BUFFER_BIT);
+            gl::Enable(gl::DEPTH_TEST);
+            gl::Disable(gl::CULL_FACE);
+            gl::Disable(gl::BLEND);
+        }
+        let (frame_width, frame_height) = (self.frame_size.0 as i32, self.frame_size.1 as i32);
+        for view in 0..self.views {
+            unsafe {
+                gl::Viewport(view as i32 * frame_width, 0, frame_width, frame_height);
+            }
+            draw(&self.get_view_projection(view));
+        }
+        unsafe {
+            FrameBuffer::bind_id(previous_framebuffer);
+            gl::Viewport(
+                previous_viewport[0],
+                previous_viewport[1],
+                previous_viewport[2],
+                previous_viewport[3],
+            );
+            for texture in [self.albedo, self.normals] {
+                gl::BindTexture(gl::TEXTURE_2D, texture);
+                gl::GenerateMipmap(gl::TEXTURE_2D);
+            }
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+        }
+    }
+
+    /// Orthographic view projection of the frame `view`, which shows the model from the
+    /// direction `view` steps of a full turn counterclockwise from +x around the up axis
+    pub fn get_view_projection(&self, view: usize) -> Matrix4<f32> {
+        let radius = self.get_radius();
+        let distance = 2.0 * radius + 1.0;
+        let (sin, cos) = (view as f32 * TAU / self.views as f32).sin_cos();
+        let eye = Point3::new(cos * distance, 0.0, sin * distance);
+        let view_matrix = Matrix4::look_at_rh(eye, Point3::new(0.0, 0.0, 0.0), Vector3::unit_y());
+        cgmath::ortho(-radius, radius, self.min.y, self.max.y, 0.0, 2.0 * distance) * view_matrix
+    }
+
+    /// Half width of the frames, the distance of the model's furthest point from its up axis
+    pub fn get_radius(&self) -> f32 {
+        let x = self.min.x.abs().max(self.max.x.abs());
+        let z = self.min.z.abs().max(self.max.z.abs());
+        x.hypot(z)
+    }
+
+    pub fn get_views(&self) -> usize {
+        self.views
+    }
+
+    pub fn get_min(&self) -> Point3<f32> {
+        self.min
+    }
+
+    pub fn get_max(&self) -> Point3<f32> {
+        self.max
+    }
+
+    /// Binds the colors and the normals to the texture units `albedo_unit` and `normal_unit`
+    pub fn bind(&self, albedo_unit: u32, normal_unit: u32) {
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0 + albedo_unit);
+            gl::BindTexture(gl::TEXTURE_2D, self.albedo);
+            gl::ActiveTexture(gl::TEXTURE0 + normal_unit);
+            gl::BindTexture(gl::TEXTURE_2D, self.normals);
+            gl::ActiveTexture(gl::TEXTURE0);
+        }
+    }
+
+    pub fn get_albedo_texture(&self) -> GLuint {
+        self.albedo
+    }
+
+    pub fn get_normal_texture(&self) -> GLuint {
+        self.normals
+    }
+}
+
+impl Drop for ImpostorAtlas {
+    fn drop(&mut self) {
+        let textures = [self.albedo, self.normals];
+        unsafe {
+            gl::DeleteFramebuffers(1, &self.framebuffer);
+            gl::DeleteRenderbuffers(1, &self.depth_buffer);
+            gl::DeleteTextures(textures.len() as i32, textures.as_ptr());
+        }
+    }
+}
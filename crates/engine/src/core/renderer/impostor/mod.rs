@@ -0,0 +1,20 @@
+use cgmath::Point3;
+use gl::types::GLuint;
+
+mod impostor;
+
+/// Color and normals of a model baked from several directions around its up axis into one row
+/// of frames, drawn as camera facing quads in place of distant instances of the model
+pub struct ImpostorAtlas {
+    framebuffer: GLuint,
+    depth_buffer: GLuint,
+    /// Color in rgb and coverage in alpha
+    albedo: GLuint,
+    /// Model space normals mapped to `0..1`
+    normals: GLuint,
+    views: usize,
+    frame_size: (u32, u32),
+    /// Bounds of the baked model in model space
+    min: Point3<f32>,
+    max: Point3<f32>,
+}
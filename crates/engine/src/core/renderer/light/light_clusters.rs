@@ -0,0 +1,157 @@
+use cgmath::{InnerSpace, Matrix4, Point3, SquareMatrix, Transform, Vector3};
+
+/// Clusters along the width of the frame
+pub const CLUSTERS_X: usize = 16;
+/// Clusters along the height of the frame
+pub const CLUSTERS_Y: usize = 9;
+/// Depth slices, their depth grows exponentially from the near to the far plane
+pub const CLUSTERS_Z: usize = 24;
+const CLUSTER_COUNT: usize = CLUSTERS_X * CLUSTERS_Y * CLUSTERS_Z;
+
+/// Splits the view frustum into a grid of clusters and assigns each light to the clusters its
+/// range overlaps, so a fragment only has to look at the lights of its own cluster
+pub struct LightClusters {
+    projection: Option<Matrix4<f32>>,
+    near: f32,
+    far: f32,
+    /// View space bounds of each cluster, x fastest, then y, then depth
+    bounds: Vec<(Vector3<f32>, Vector3<f32>)>,
+    /// Offset into `indices` and light count of each cluster
+    ranges: Vec<[u32; 2]>,
+    indices: Vec<u32>,
+}
+
+impl LightClusters {
+    pub fn new() -> Self {
+        Self {
+            projection: None,
+            near: 0.1,
+            far: 1.0,
+            bounds: Vec::new(),
+            ranges: vec![[0, 0]; CLUSTER_COUNT],
+            indices: Vec::new(),
+        }
+    }
+
+    /// Recomputes the bounds of the clusters if the projection changed
+    pub fn update_bounds(&mut self, projection: Matrix4<f32>, near: f32, far: f32) {
+        if self.projection == Some(projection) && self.near == near && self.far == far {
+            return;
+        }
+        let Some(inverse) = projection.invert() else {
+            return;
+        };
+        self.projection = Some(projection);
+        self.near = near;
+        self.far = far;
+
+        // direction of the view ray through a point in normalized device coordinates, scaled to a depth of 1
+        let ray = |x: f32, y: f32| {
+            let point = inverse.transform_point(Point3::new(x, y, 0.5));
+            Vector3::new(point.x, point.y, point.z) / -point.z
+        };
+        self.bounds.clear();
+        for z in 0..CLUSTERS_Z {
+            let depths = [self.slice_depth(z), self.slice_depth(z + 1)];
+            for y in 0..CLUSTERS_Y {
+                let y0 = -1.0 + 2.0 * y as f32 / CLUSTERS_Y as f32;
+                let y1 = -1.0 + 2.0 * (y + 1) as f32 / CLUSTERS_Y as f32;
+                for x in 0..CLUSTERS_X {
+                    let x0 = -1.0 + 2.0 * x as f32 / CLUSTERS_X as f32;
+                    let x1 = -1.0 + 2.0 * (x + 1) as f32 / CLUSTERS_X as f32;
+                    let mut min = Vector3::new(f32::MAX, f32::MAX, f32::MAX);
+                    let mut max = Vector3::new(f32::MIN, f32::MIN, f32::MIN);
+                    for corner in [ray(x0, y0), ray(x1, y0), ray(x0, y1), ray(x1, y1)] {
+                        for depth in depths {
+                            let point = corner * depth;
+                            min = Vector3::new(
+                                min.x.min(point.x),
+                                min.y.min(point.y),
+                                min.z.min(point.z),
+                            );
+                            max = Vector3::new(
+                                max.x.max(point.x),
+                                max.y.max(point.y),
+                                max.z.max(point.z),
+                            );
+                        }
+                    }
+                    self.bounds.push((min, max));
+                }
+            }
+        }
+    }
+
+    /// Assigns the lights, given as view space position and range, to the clusters they reach
+    pub fn assign(&mut self, lights: &[(Point3<f32>, f32)]) {
+        let mut cluster_lights = vec![Vec::new(); CLUSTER_COUNT];
+        if !self.bounds.is_empty() {
+            for (i, (position, range)) in lights.iter().enumerate() {
+                let depth = -position.z;
+                if depth + range < self.near || depth - range > self.far {
+                    continue;
+                }
+                let first_slice = self.get_slice(depth - range);
+                let last_slice = self.get_slice(depth + range);
+                let slices = first_slice * CLUSTERS_X * CLUSTERS_Y
+                    ..(last_slice + 1) * CLUSTERS_X * CLUSTERS_Y;
+                for ((min, max), cluster) in self.bounds[slices.clone()]
+                    .iter()
+                    .zip(&mut cluster_lights[slices])
+                {
+                    if LightClusters::sphere_intersects_box(*position, *range, *min, *max) {
+                        cluster.push(i as u32);
+                    }
+                }
+            }
+        }
+        self.indices.clear();
+        for (range, lights) in self.ranges.iter_mut().zip(cluster_lights) {
+            *range = [self.indices.len() as u32, lights.len() as u32];
+            self.indices.extend(lights);
+        }
+    }
+
+    pub fn get_ranges(&self) -> &[[u32; 2]] {
+        &self.ranges
+    }
+
+    pub fn get_indices(&self) -> &[u32] {
+        &self.indices
+    }
+
+    /// View space depth of the near and far plane the clusters span
+    pub fn get_depth_range(&self) -> (f32, f32) {
+        (self.near, self.far)
+    }
+
+    fn slice_depth(&self, slice: usize) -> f32 {
+        self.near * (self.far / self.near).powf(slice as f32 / CLUSTERS_Z as f32)
+    }
+
+    fn get_slice(&self, depth: f32) -> usize {
+        let slice = (depth.max(self.near) / self.near).ln() / (self.far / self.near).ln()
+            * CLUSTERS_Z as f32;
+        (slice as usize).min(CLUSTERS_Z - 1)
+    }
+
+    fn sphere_intersects_box(
+        center: Point3<f32>,
+        radius: f32,
+        min: Vector3<f32>,
+        max: Vector3<f32>,
+    ) -> bool {
+        let closest = Vector3::new(
+            center.x.clamp(min.x, max.x),
+            center.y.clamp(min.y, max.y),
+            center.z.clamp(min.z, max.z),
+        );
+        (closest - Vector3::new(center.x, center.y, center.z)).magnitude2() <= radius * radius
+    }
+}
+
+impl Default for LightClusters {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,234 @@
+use std::ffi::c_void;
+
+use cgmath::{Angle, EuclideanSpace, InnerSpace, Matrix4, Point3, SquareMatrix, Transform};
+use gl::types::{GLsizeiptr, GLuint};
+
+use crate::core::{
+    entity::component::camera_component::CameraComponent,
+    renderer::render_graph::{RenderPass, RenderResources, LIGHT_PASS_ORDER},
+    scene::Scene,
+    window::Window,
+};
+
+use super::{
+    light_clusters::{LightClusters, CLUSTERS_X, CLUSTERS_Y, CLUSTERS_Z},
+    point_light::PointLightComponent,
+    spot_light::SpotLightComponent,
+};
+
+/// Shader storage binding the light buffer is bound to
+pub const LIGHT_BUFFER_BINDING: GLuint = 8;
+/// Shader storage binding of the offset and light count of each cluster
+pub const CLUSTER_BUFFER_BINDING: GLuint = 9;
+/// Shader storage binding of the light indices the clusters refer to
+pub const CLUSTER_LIGHTS_BUFFER_BINDING: GLuint = 10;
+/// Lights beyond this count are ignored
+pub const MAX_LIGHTS: usize = 1024;
+const LIGHTS_GLSL: &str = include_str!("lights.glsl");
+const LIGHTS_INCLUDE: &str = "#include \"lights.glsl\"";
+
+/// Start of the std430 light buffer, followed by the lights
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+struct GpuLightHeader {
+    /// Light count and the cluster counts along x, y and depth, which are 0 without a camera
+    info: [u32; 4],
+    /// Frame size and the depth range the clusters span
+    cluster_params: [f32; 4],
+    cluster_view: [[f32; 4]; 4],
+}
+
+/// Light as laid out in the std430 light buffer
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+struct GpuLight {
+    position_range: [f32; 4],
+    color: [f32; 4],
+    direction_outer: [f32; 4],
+    inner: [f32; 4],
+}
+
+/// Uploads the point and spot lights of the scene into a shader storage buffer, shaders
+/// read it through `pointLighting` from `lights.glsl`. The lights are sorted into clusters
+/// of the camera's view, so each fragment only shades with the lights reaching it
+pub struct LightPass {
+    buffer: GLuint,
+    cluster_buffer: GLuint,
+    cluster_lights_buffer: GLuint,
+    clusters: LightClusters,
+}
+
+impl LightPass {
+    pub fn new() -> Self {
+        let mut buffers = [0; 3];
+        unsafe {
+            gl::GenBuffers(3, buffers.as_mut_ptr());
+        }
+        Self {
+            buffer: buffers[0],
+            cluster_buffer: buffers[1],
+            cluster_lights_buffer: buffers[2],
+            clusters: LightClusters::new(),
+        }
+    }
+
+    fn collect_lights(scene: &Scene) -> Vec<GpuLight> {
+        let mut lights = Vec::new();
+        for (transform, light) in scene.get_components_with_transform::<PointLightComponent>() {
+            let position = transform.transform_point(Point3::origin());
+            let color = light.get_color() * light.get_intensity();
+            lights.push(GpuLight {
+                position_range: [position.x, position.y, position.z, light.get_range()],
+                color: [color.x, color.y, color.z, 0.0],
+                direction_outer: [0.0, -1.0, 0.0, -1.0],
+                inner: [-1.0; 4],
+            });
+        }
+        for (transform, light) in scene.get_components_with_transform::<SpotLightComponent>() {
+            let position = transform.transform_point(Point3::origin());
+            let direction = transform
+                .transform_vector(light.get_direction())
+                .normalize();
+            let color = light.get_color() * light.get_intensity();
+            lights.push(GpuLight {
+                position_range: [position.x, position.y, position.z, light.get_range()],
+                color: [color.x, color.y, color.z, 0.0],
+                direction_outer: [
+                    direction.x,
+                    direction.y,
+                    direction.z,
+                    light.get_outer_angle().cos(),
+                ],
+                inner: [light.get_inner_angle().cos(), 0.0, 0.0, 0.0],
+            });
+        }
+        lights.truncate(MAX_LIGHTS);
+        lights
+    }
+
+    /// Replaces the contents of a shader storage buffer and binds it, the buffer is never left
+    /// empty so it can always be bound
+    fn upload<T>(buffer: GLuint, binding: GLuint, data: &[T]) {
+        let size = std::mem::size_of_val(data);
+        unsafe {
+            gl::BindBuffer(gl::SHADER_STORAGE_BUFFER, buffer);
+            gl::BufferData(
+                gl::SHADER_STORAGE_BUFFER,
+                size.max(16) as GLsizeiptr,
+                std::ptr::null(),
+                gl::DYNAMIC_DRAW,
+            );
+            gl::BufferSubData(
+                gl::SHADER_STORAGE_BUFFER,
+                0,
+                size as GLsizeiptr,
+                data.as_ptr() as *const c_void,
+            );
+            gl::BindBuffer(gl::SHADER_STORAGE_BUFFER, 0);
+            gl::BindBufferBase(gl::SHADER_STORAGE_BUFFER, binding, buffer);
+        }
+    }
+}
+
+impl Default for LightPass {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for LightPass {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteBuffers(1, &self.buffer);
+            gl::DeleteBuffers(1, &self.cluster_buffer);
+            gl::DeleteBuffers(1, &self.cluster_lights_buffer);
+        }
+    }
+}
+
+impl RenderPass for LightPass {
+    fn get_name(&self) -> &str {
+        "Lights"
+    }
+
+    fn get_order(&self) -> i32 {
+        LIGHT_PASS_ORDER
+    }
+
+    fn render(&mut self, scene: &Scene, _: &Window, resources: &mut RenderResources) {
+        let lights = LightPass::collect_lights(scene);
+        let mut header = GpuLightHeader {
+            info: [lights.len() as u32, 0, 0, 0],
+            cluster_params: [0.0; 4],
+            cluster_view: Matrix4::identity().into(),
+        };
+        if let Some(camera) = scene.get_component::<CameraComponent>() {
+            let projection = camera.get_projection();
+            let view = camera.get_camera().get_matrix();
+            self.clusters.update_bounds(
+                projection.get_matrix(),
+                projection.get_znear(),
+                projection.get_zfar(),
+            );
+            let spheres: Vec<(Point3<f32>, f32)> = lights
+                .iter()
+                .map(|light| {
+                    let [x, y, z, range] = light.position_range;
+                    (view.transform_point(Point3::new(x, y, z)), range)
+                })
+                .collect();
+            self.clusters.assign(&spheres);
+            let (width, height) = resources.get_size();
+            let (near, far) = self.clusters.get_depth_range();
+            header.info[1..].copy_from_slice(&[
+                CLUSTERS_X as u32,
+                CLUSTERS_Y as u32,
+                CLUSTERS_Z as u32,
+            ]);
+            header.cluster_params = [width as f32, height as f32, near, far];
+            header.cluster_view = view.into();
+        }
+        let header_size = std::mem::size_of::<GpuLightHeader>();
+        let lights_size = std::mem::size_of_val(lights.as_slice());
+        unsafe {
+            gl::BindBuffer(gl::SHADER_STORAGE_BUFFER, self.buffer);
+            gl::BufferData(
+                gl::SHADER_STORAGE_BUFFER,
+                (header_size + lights_size) as GLsizeiptr,
+                std::ptr::null(),
+                gl::DYNAMIC_DRAW,
+            );
+            gl::BufferSubData(
+                gl::SHADER_STORAGE_BUFFER,
+                0,
+                header_size as GLsizeiptr,
+                &header as *const GpuLightHeader as *const c_void,
+            );
+            gl::BufferSubData(
+                gl::SHADER_STORAGE_BUFFER,
+                header_size as GLsizeiptr,
+                lights_size as GLsizeiptr,
+                lights.as_ptr() as *const c_void,
+            );
+            gl::BindBuffer(gl::SHADER_STORAGE_BUFFER, 0);
+            gl::BindBufferBase(gl::SHADER_STORAGE_BUFFER, LIGHT_BUFFER_BINDING, self.buffer);
+        }
+        LightPass::upload(
+            self.cluster_buffer,
+            CLUSTER_BUFFER_BINDING,
+            self.clusters.get_ranges(),
+        );
+        LightPass::upload(
+            self.cluster_lights_buffer,
+            CLUSTER_LIGHTS_BUFFER_BINDING,
+            self.clusters.get_indices(),
+        );
+    }
+}
+
+/// Replaces the `#include "lights.glsl"` line of a shader source with the light buffer
+/// declaration, the `pointLighting` function, the sky probe lookups `skyAmbient` and `skyReflection`
+/// and `screenAmbientOcclusion`
+pub fn include_lights(source: &str) -> String {
+    source.replace(LIGHTS_INCLUDE, LIGHTS_GLSL)
+}
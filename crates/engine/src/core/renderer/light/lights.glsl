@@ -0,0 +1,90 @@
+struct Light {
+    // xyz position, w range
+    vec4 positionRange;
+    // rgb color premultiplied by the intensity
+    vec4 color;
+    // xyz direction, w cosine of the outer cone angle, -1 for point lights
+    vec4 directionOuter;
+    // x cosine of the inner cone angle
+    vec4 inner;
+};
+
+layout (std430, binding = 8) readonly buffer LightBuffer {
+    // x light count, yzw clusters along x, y and depth, 0 if the lights are not clustered
+    uvec4 lightInfo;
+    // xy frame size, zw view space depth range of the clusters
+    vec4 clusterParams;
+    mat4 clusterView;
+    Light lights[];
+};
+
+layout (std430, binding = 9) readonly buffer ClusterBuffer {
+    // x offset into clusterLights, y light count
+    uvec2 clusters[];
+};
+
+layout (std430, binding = 10) readonly buffer ClusterLightBuffer {
+    uint clusterLights[];
+};
+
+layout (binding = 13) uniform sampler2D ambientOcclusionTexture;
+layout (binding = 14) uniform samplerCube skyIrradiance;
+layout (binding = 15) uniform samplerCube skySpecular;
+
+// screen space ambient occlusion of the fragment, 1 where nothing occludes it
+float screenAmbientOcclusion() {
+    return texture(ambientOcclusionTexture, gl_FragCoord.xy / vec2(textureSize(ambientOcclusionTexture, 0))).r;
+}
+
+// light the sky casts onto a surface facing `normal`
+vec3 skyAmbient(vec3 normal) {
+    return texture(skyIrradiance, normal).rgb;
+}
+
+// sky reflected in `direction`, blurred more the rougher the surface is
+vec3 skyReflection(vec3 direction, float roughness) {
+    float maxLevel = float(textureQueryLevels(skySpecular) - 1);
+    return textureLod(skySpecular, direction, roughness * maxLevel).rgb;
+}
+
+// cluster of the light grid the fragment at `position` lies in
+uint lightCluster(vec3 position, uvec3 clusterCount) {
+    vec2 tile = gl_FragCoord.xy / clusterParams.xy * vec2(clusterCount.xy);
+    float depth = max(-(clusterView * vec4(position, 1.0)).z, clusterParams.z);
+    float slice = log(depth / clusterParams.z) / log(clusterParams.w / clusterParams.z) * float(clusterCount.z);
+    uvec3 index = min(uvec3(max(vec3(tile, slice), 0.0)), clusterCount - 1);
+    return index.x + index.y * clusterCount.x + index.z * clusterCount.x * clusterCount.y;
+}
+
+// diffuse light the point and spot lights of the fragment's cluster add to a surface
+vec3 pointLighting(vec3 position, vec3 normal) {
+    vec3 result = vec3(0.0);
+    uvec3 clusterCount = lightInfo.yzw;
+    uint first = 0;
+    uint count = lightInfo.x;
+    if (clusterCount.x > 0) {
+        uint cluster = lightCluster(position, clusterCount);
+        first = clusters[cluster].x;
+        count = clusters[cluster].y;
+    }
+    for (uint i = 0; i < count; i++) {
+        Light light = lights[clusterCount.x > 0 ? clusterLights[first + i] : i];
+        vec3 toLight = light.positionRange.xyz - position;
+        float distance = length(toLight);
+        float range = light.positionRange.w;
+        if (distance >= range) {
+            continue;
+        }
+        vec3 direction = toLight / max(distance, 0.0001);
+        float diffuse = max(dot(normal, direction), 0.0);
+        // windowed inverse square falloff, reaches zero at the range
+        float window = clamp(1.0 - pow(distance / range, 4.0), 0.0, 1.0);
+        float attenuation = window * window / (distance * distance + 1.0);
+        float spot = 1.0;
+        if (light.directionOuter.w > -1.0) {
+            spot = smoothstep(light.directionOuter.w, light.inner.x, dot(-direction, light.directionOuter.xyz));
+        }
+        result += light.color.rgb * diffuse * attenuation * spot;
+    }
+    return result;
+}
@@ -1 +1,6 @@
+pub mod light_clusters;
+pub mod light_pass;
+pub mod point_light;
+pub mod sky_probe;
 pub mod skylight;
+pub mod spot_light;
@@ -0,0 +1,105 @@
+use cgmath::Vector3;
+use glfw::{Glfw, WindowEvent};
+
+use crate::core::{
+    entity::{
+        component::{Component, InspectableComponent, InspectorField, InspectorValue},
+        Entity,
+    },
+    event::EventCategory,
+    scene::Scene,
+};
+
+/// Light shining in all directions from its entity's position
+pub struct PointLightComponent {
+    color: Vector3<f32>,
+    intensity: f32,
+    range: f32,
+}
+
+impl PointLightComponent {
+    /// The light fades out until it reaches nothing at `range`
+    pub fn new<C: Into<Vector3<f32>>>(color: C, intensity: f32, range: f32) -> Self {
+        Self {
+            color: color.into(),
+            intensity,
+            range,
+        }
+    }
+
+    pub fn get_color(&self) -> Vector3<f32> {
+        self.color
+    }
+
+    pub fn set_color<C: Into<Vector3<f32>>>(&mut self, color: C) {
+        self.color = color.into();
+    }
+
+    pub fn get_intensity(&self) -> f32 {
+        self.intensity
+    }
+
+    pub fn set_intensity(&mut self, intensity: f32) {
+        self.intensity = intensity.max(0.0);
+    }
+
+    pub fn get_range(&self) -> f32 {
+        self.range
+    }
+
+    pub fn set_range(&mut self, range: f32) {
+        self.range = range.max(0.0);
+    }
+}
+
+impl InspectableComponent for PointLightComponent {
+    fn get_inspector_name(&self) -> &str {
+        "Point light"
+    }
+
+    fn get_fields(&self) -> Vec<InspectorField> {
+        vec![
+            InspectorField {
+                name: "Color",
+                value: InspectorValue::Vector3(self.color),
+            },
+            InspectorField {
+                name: "Intensity",
+                value: InspectorValue::Float(self.intensity),
+            },
+            InspectorField {
+                name: "Range",
+                value: InspectorValue::Float(self.range),
+            },
+        ]
+    }
+
+    fn set_field(&mut self, name: &str, value: InspectorValue) {
+        match (name, value) {
+            ("Color", InspectorValue::Vector3(color)) => self.set_color(color),
+            ("Intensity", InspectorValue::Float(intensity)) => self.set_intensity(intensity),
+            ("Range", InspectorValue::Float(range)) => self.set_range(range),
+            _ => {}
+        }
+    }
+}
+
+impl Component for PointLightComponent {
+    fn update(&mut self, _: &mut Scene, _: &mut Entity, _: f64) {}
+
+    fn get_event_priority(&self, _: EventCategory) -> Option<i32> {
+        None
+    }
+
+    fn handle_event(&mut self, _: &mut Glfw, _: &mut glfw::Window, _: &WindowEvent) -> bool {
+        false
+    }
+
+    fn as_inspectable(&self) -> Option<&dyn InspectableComponent> {
+        Some(self)
+    }
+
+    fn as_inspectable_mut(&mut self) -> Option<&mut dyn InspectableComponent> {
+        Some(self)
+    }
+}
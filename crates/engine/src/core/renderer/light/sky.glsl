@@ -0,0 +1,28 @@
+// direction through a texel of a cubemap face, `texCoord` from 0 to 1 over the face
+vec3 cubeDirection(int face, vec2 texCoord) {
+    vec2 uv = texCoord * 2.0 - 1.0;
+    vec3 direction;
+    if (face == 0) direction = vec3(1.0, -uv.y, -uv.x);
+    else if (face == 1) direction = vec3(-1.0, -uv.y, uv.x);
+    else if (face == 2) direction = vec3(uv.x, 1.0, uv.y);
+    else if (face == 3) direction = vec3(uv.x, -1.0, -uv.y);
+    else if (face == 4) direction = vec3(uv.x, -uv.y, 1.0);
+    else direction = vec3(-uv.x, -uv.y, -1.0);
+    return normalize(direction);
+}
+
+// radiance of the procedural sky in `direction` for the sun in `sunDirection`
+vec3 skyColor(vec3 direction, vec3 sunDirection) {
+    float day = smoothstep(-0.1, 0.25, sunDirection.y);
+    float sunset = 1.0 - smoothstep(0.0, 0.35, abs(sunDirection.y));
+    vec3 zenith = mix(vec3(0.01, 0.015, 0.04), vec3(0.25, 0.45, 0.85), day);
+    vec3 horizon = mix(vec3(0.03, 0.035, 0.06), vec3(0.7, 0.8, 0.95), day);
+    horizon = mix(horizon, vec3(0.95, 0.5, 0.25), sunset * 0.6);
+    vec3 color = mix(horizon, zenith, sqrt(max(direction.y, 0.0)));
+    // below the horizon only the light bounced off the ground arrives
+    color = mix(horizon * 0.3, color, smoothstep(-0.05, 0.05, direction.y));
+    float sun = max(dot(direction, sunDirection), 0.0);
+    vec3 sunColor = mix(vec3(1.0, 0.5, 0.2), vec3(1.0, 0.9, 0.7), day);
+    color += sunColor * (pow(sun, 8.0) * 0.3 + pow(sun, 512.0) * 4.0) * smoothstep(-0.1, 0.0, sunDirection.y);
+    return color;
+}
@@ -0,0 +1,13 @@
+#version 460 core
+in vec2 texCoord;
+
+out vec4 FragColor;
+
+uniform int face;
+uniform vec3 sunDirection;
+
+#include "sky.glsl"
+
+void main() {
+    FragColor = vec4(skyColor(cubeDirection(face, texCoord), normalize(sunDirection)), 1.0);
+}
@@ -0,0 +1,33 @@
+#version 460 core
+in vec2 texCoord;
+
+out vec4 FragColor;
+
+uniform int face;
+uniform samplerCube sky;
+
+#include "sky.glsl"
+
+const float PI = 3.14159265359;
+const float SAMPLE_DELTA = 0.1;
+
+// cosine weighted average of the sky over the hemisphere around the normal
+void main() {
+    vec3 normal = cubeDirection(face, texCoord);
+    vec3 up = abs(normal.y) < 0.999 ? vec3(0.0, 1.0, 0.0) : vec3(1.0, 0.0, 0.0);
+    vec3 right = normalize(cross(up, normal));
+    up = cross(normal, right);
+
+    vec3 irradiance = vec3(0.0);
+    float weight = 0.0;
+    for (float phi = 0.0; phi < 2.0 * PI; phi += SAMPLE_DELTA) {
+        for (float theta = 0.0; theta < 0.5 * PI; theta += SAMPLE_DELTA) {
+            vec3 tangent = vec3(sin(theta) * cos(phi), sin(theta) * sin(phi), cos(theta));
+            vec3 direction = tangent.x * right + tangent.y * up + tangent.z * normal;
+            float sampleWeight = cos(theta) * sin(theta);
+            irradiance += texture(sky, direction).rgb * sampleWeight;
+            weight += sampleWeight;
+        }
+    }
+    FragColor = vec4(irradiance / weight, 1.0);
+}
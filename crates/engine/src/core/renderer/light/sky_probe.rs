@@ -0,0 +1,235 @@
+use cgmath::{EuclideanSpace, InnerSpace, Vector3};
+use gl::types::{GLint, GLuint};
+
+use crate::core::{
+    renderer::{
+        render_graph::{RenderPass, RenderResources, SKY_PROBE_PASS_ORDER},
+        screen_quad::ScreenQuad,
+        shader::Shader,
+        shader_manager::ShaderSource,
+    },
+    scene::Scene,
+    window::Window,
+};
+
+use super::skylight::SkyLight;
+
+/// Texture unit the sky irradiance is bound to, read through `skyAmbient` from `lights.glsl`
+pub const SKY_IRRADIANCE_UNIT: u32 = 14;
+/// Texture unit the prefiltered sky is bound to, read through `skyReflection` from `lights.glsl`
+pub const SKY_SPECULAR_UNIT: u32 = 15;
+const SKY_SIZE: i32 = 64;
+/// Mip levels of the baked sky, the specular convolution samples the lower ones for wide lobes
+const SKY_LEVELS: i32 = 7;
+const IRRADIANCE_SIZE: i32 = 16;
+const SPECULAR_SIZE: i32 = 32;
+/// Mip levels of the specular probe, the roughness rises from 0 to 1 over them
+const SPECULAR_LEVELS: i32 = 5;
+/// Cosine of the angle the sun has to move by before the probes are baked again
+const REBAKE_THRESHOLD: f32 = 0.9995;
+const SKY_GLSL: &str = include_str!("sky.glsl");
+const SKY_INCLUDE: &str = "#include \"sky.glsl\"";
+
+/// Bakes the procedural sky for the direction of the skylight into a cubemap and convolves it
+/// into a diffuse irradiance and a prefiltered specular probe. The probes are baked again
+/// whenever the sun moved noticeably and are bound to `SKY_IRRADIANCE_UNIT` and `SKY_SPECULAR_UNIT`
+pub struct SkyProbePass {
+    bake_shader: Shader,
+    irradiance_shader: Shader,
+    specular_shader: Shader,
+    screen_quad: ScreenQuad,
+    framebuffer: GLuint,
+    sky: GLuint,
+    irradiance: GLuint,
+    specular: GLuint,
+    baked_sun_direction: Option<Vector3<f32>>,
+}
+
+impl SkyProbePass {
+    pub fn new() -> Self {
+        let vertex_source = ScreenQuad::get_vertex_shader();
+        let mut framebuffer = 0;
+        unsafe {
+            gl::Enable(gl::TEXTURE_CUBE_MAP_SEAMLESS);
+            gl::GenFramebuffers(1, &mut framebuffer);
+        }
+        Self {
+            bake_shader: Shader::load(
+                vertex_source,
+                ShaderSource::new(
+                    "renderer/light/sky_bake_fragment.glsl",
+                    include_str!("sky_bake_fragment.glsl"),
+                )
+                .preprocess(include_sky),
+            ),
+            irradiance_shader: Shader::load(
+                vertex_source,
+                ShaderSource::new(
+                    "renderer/light/sky_irradiance_fragment.glsl",
+                    include_str!("sky_irradiance_fragment.glsl"),
+                )
+                .preprocess(include_sky),
+            ),
+            specular_shader: Shader::load(
+                vertex_source,
+                ShaderSource::new(
+                    "renderer/light/sky_specular_fragment.glsl",
+                    include_str!("sky_specular_fragment.glsl"),
+                )
+                .preprocess(include_sky),
+            ),
+            screen_quad: ScreenQuad::new(),
+            framebuffer,
+            sky: SkyProbePass::create_cubemap(SKY_SIZE, SKY_LEVELS),
+            irradiance: SkyProbePass::create_cubemap(IRRADIANCE_SIZE, 1),
+            specular: SkyProbePass::create_cubemap(SPECULAR_SIZE, SPECULAR_LEVELS),
+            baked_sun_direction: None,
+        }
+    }
+
+    fn create_cubemap(size: i32, levels: i32) -> GLuint {
+        let mut texture = 0;
+        unsafe {
+            gl::GenTextures(1, &mut texture);
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, texture);
+            gl::TexStorage2D(gl::TEXTURE_CUBE_MAP, levels, gl::RGBA16F, size, size);
+            let min_filter = if levels > 1 {
+                gl::LINEAR_MIPMAP_LINEAR
+            } else {
+                gl::LINEAR
+            };
+            gl::TexParameteri(
+                gl::TEXTURE_CUBE_MAP,
+                gl::TEXTURE_MIN_FILTER,
+                min_filter as GLint,
+            );
+            gl::TexParameteri(
+                gl::TEXTURE_CUBE_MAP,
+                gl::TEXTURE_MAG_FILTER,
+                gl::LINEAR as GLint,
+            );
+            for wrap in [gl::TEXTURE_WRAP_S, gl::TEXTURE_WRAP_T, gl::TEXTURE_WRAP_R] {
+                gl::TexParameteri(gl::TEXTURE_CUBE_MAP, wrap, gl::CLAMP_TO_EDGE as GLint);
+            }
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, 0);
+        }
+        texture
+    }
+
+    fn bake(&self, sun_direction: Vector3<f32>) {
+        unsafe {
+            gl::BindFramebuffer(gl::FRAMEBUFFER, self.framebuffer);
+        }
+        self.bake_shader.bind();
+        self.bake_shader.set_uniform_3f(
+            "sunDirection",
+            sun_direction.x,
+            sun_direction.y,
+            sun_direction.z,
+        );
+        self.render_faces(&self.bake_shader, self.sky, 0, SKY_SIZE);
+
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, self.sky);
+            gl::GenerateMipmap(gl::TEXTURE_CUBE_MAP);
+        }
+        self.irradiance_shader.bind();
+        self.irradiance_shader.set_uniform_1i("sky", 0);
+        self.render_faces(&self.irradiance_shader, self.irradiance, 0, IRRADIANCE_SIZE);
+
+        self.specular_shader.bind();
+        self.specular_shader.set_uniform_1i("sky", 0);
+        self.specular_shader
+            .set_uniform_1f("skySize", SKY_SIZE as f32);
+        for level in 0..SPECULAR_LEVELS {
+            self.specular_shader
+                .set_uniform_1f("roughness", level as f32 / (SPECULAR_LEVELS - 1) as f32);
+            self.render_faces(
+                &self.specular_shader,
+                self.specular,
+                level,
+                (SPECULAR_SIZE >> level).max(1),
+            );
+        }
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, 0);
+        }
+    }
+
+    /// Draws `shader` into each face of the cubemap `texture`, the shader gets the face as `face`
+    fn render_faces(&self, shader: &Shader, texture: GLuint, level: i32, size: i32) {
+        unsafe {
+            gl::Viewport(0, 0, size, size);
+        }
+        for face in 0..6 {
+            unsafe {
+                gl::FramebufferTexture2D(
+                    gl::FRAMEBUFFER,
+                    gl::COLOR_ATTACHMENT0,
+                    gl::TEXTURE_CUBE_MAP_POSITIVE_X + face as u32,
+                    texture,
+                    level,
+                );
+            }
+            shader.set_uniform_1i("face", face);
+            self.screen_quad.render(shader);
+        }
+    }
+}
+
+impl Default for SkyProbePass {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for SkyProbePass {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteFramebuffers(1, &self.framebuffer);
+            gl::DeleteTextures(1, &self.sky);
+            gl::DeleteTextures(1, &self.irradiance);
+            gl::DeleteTextures(1, &self.specular);
+        }
+    }
+}
+
+impl RenderPass for SkyProbePass {
+    fn get_name(&self) -> &str {
+        "Sky probe"
+    }
+
+    fn get_order(&self) -> i32 {
+        SKY_PROBE_PASS_ORDER
+    }
+
+    fn render(&mut self, scene: &Scene, _: &Window, resources: &mut RenderResources) {
+        // without a skylight the sky is baked for the sun standing in the zenith
+        let sun_direction = scene
+            .get_component::<SkyLight>()
+            .map_or(Vector3::unit_y(), |skylight| {
+                skylight.get_position().to_vec().normalize()
+            });
+        let rebake = self
+            .baked_sun_direction
+            .is_none_or(|baked| baked.dot(sun_direction) < REBAKE_THRESHOLD);
+        if rebake {
+            self.bake(sun_direction);
+            self.baked_sun_direction = Some(sun_direction);
+            resources.bind_target();
+        }
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0 + SKY_IRRADIANCE_UNIT);
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, self.irradiance);
+            gl::ActiveTexture(gl::TEXTURE0 + SKY_SPECULAR_UNIT);
+            gl::BindTexture(gl::TEXTURE_CUBE_MAP, self.specular);
+            gl::ActiveTexture(gl::TEXTURE0);
+        }
+    }
+}
+
+/// Replaces the `#include "sky.glsl"` line of a shader source with the procedural sky functions
+fn include_sky(source: &str) -> String {
+    source.replace(SKY_INCLUDE, SKY_GLSL)
+}
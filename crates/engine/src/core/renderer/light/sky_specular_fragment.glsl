@@ -0,0 +1,65 @@
+#version 460 core
+in vec2 texCoord;
+
+out vec4 FragColor;
+
+uniform int face;
+uniform samplerCube sky;
+uniform float skySize;
+uniform float roughness;
+
+#include "sky.glsl"
+
+const float PI = 3.14159265359;
+const uint SAMPLE_COUNT = 64u;
+
+vec2 hammersley(uint i) {
+    uint bits = i;
+    bits = (bits << 16u) | (bits >> 16u);
+    bits = ((bits & 0x55555555u) << 1u) | ((bits & 0xAAAAAAAAu) >> 1u);
+    bits = ((bits & 0x33333333u) << 2u) | ((bits & 0xCCCCCCCCu) >> 2u);
+    bits = ((bits & 0x0F0F0F0Fu) << 4u) | ((bits & 0xF0F0F0F0u) >> 4u);
+    bits = ((bits & 0x00FF00FFu) << 8u) | ((bits & 0xFF00FF00u) >> 8u);
+    return vec2(float(i) / float(SAMPLE_COUNT), float(bits) * 2.3283064365386963e-10);
+}
+
+vec3 importanceSampleGGX(vec2 xi, vec3 normal, float a) {
+    float phi = 2.0 * PI * xi.x;
+    float cosTheta = sqrt((1.0 - xi.y) / (1.0 + (a * a - 1.0) * xi.y));
+    float sinTheta = sqrt(1.0 - cosTheta * cosTheta);
+    vec3 h = vec3(cos(phi) * sinTheta, sin(phi) * sinTheta, cosTheta);
+    vec3 up = abs(normal.y) < 0.999 ? vec3(0.0, 1.0, 0.0) : vec3(1.0, 0.0, 0.0);
+    vec3 tangent = normalize(cross(up, normal));
+    vec3 bitangent = cross(normal, tangent);
+    return normalize(tangent * h.x + bitangent * h.y + normal * h.z);
+}
+
+// sky convolved with a GGX lobe, assuming the view direction equals the reflection
+void main() {
+    vec3 normal = cubeDirection(face, texCoord);
+    if (roughness == 0.0) {
+        FragColor = vec4(texture(sky, normal).rgb, 1.0);
+        return;
+    }
+    float a = roughness * roughness;
+    float texelSolidAngle = 4.0 * PI / (6.0 * skySize * skySize);
+    vec3 color = vec3(0.0);
+    float weight = 0.0;
+    for (uint i = 0u; i < SAMPLE_COUNT; i++) {
+        vec3 h = importanceSampleGGX(hammersley(i), normal, a);
+        vec3 l = normalize(2.0 * dot(normal, h) * h - normal);
+        float nDotL = dot(normal, l);
+        if (nDotL <= 0.0) {
+            continue;
+        }
+        // sample a lower mip of the sky for unlikely directions to avoid fireflies
+        float nDotH = max(dot(normal, h), 0.0);
+        float d = a * a / (PI * pow(nDotH * nDotH * (a * a - 1.0) + 1.0, 2.0));
+        float pdf = d / 4.0 + 0.0001;
+        float sampleSolidAngle = 1.0 / (float(SAMPLE_COUNT) * pdf);
+        float level = 0.5 * log2(sampleSolidAngle / texelSolidAngle);
+        color += textureLod(sky, l, max(level, 0.0)).rgb * nDotL;
+        weight += nDotL;
+    }
+    FragColor = vec4(color / weight, 1.0);
+}
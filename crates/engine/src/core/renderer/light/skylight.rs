@@ -7,9 +7,13 @@ use glfw::{Glfw, WindowEvent};
 use crate::core::{
     camera::{Camera, Projection},
     entity::{
-        component::{camera_component::CameraComponent, Component},
+        component::{
+            camera_component::CameraComponent, Component, InspectableComponent, InspectorField,
+            InspectorValue,
+        },
         Entity,
     },
+    event::EventCategory,
     scene::Scene,
 };
 
@@ -53,12 +57,40 @@ impl SkyLight {
         self.position
     }
 
+    /// Moves the sun, for example with the time of day. The sky probes follow once it moved noticeably
+    pub fn set_position<P: Into<Point3<f32>>>(&mut self, position: P) {
+        self.position = position.into();
+    }
+
     pub fn get_projection(&self) -> Matrix4<f32> {
         let projection = self.shadow_box.light_projection * self.light_view;
         projection
     }
 }
 
+impl InspectableComponent for SkyLight {
+    fn get_inspector_name(&self) -> &str {
+        "Sky light"
+    }
+
+    fn get_fields(&self) -> Vec<InspectorField> {
+        vec![InspectorField {
+            name: "Direction",
+            value: InspectorValue::Vector3(-self.position.to_vec().normalize()),
+        }]
+    }
+
+    fn set_field(&mut self, name: &str, value: InspectorValue) {
+        if let ("Direction", InspectorValue::Vector3(direction)) = (name, value) {
+            // the sun keeps its distance, only the direction it shines from changes
+            if direction.magnitude2() > 0.0 {
+                let distance = self.position.to_vec().magnitude();
+                self.position = Point3::from_vec(-direction.normalize() * distance);
+            }
+        }
+    }
+}
+
 impl Component for SkyLight {
     fn update(&mut self, scene: &mut Scene, _: &mut Entity, _: f64) {
         if let Some(camera_component) = scene.get_component::<CameraComponent>() {
@@ -70,7 +102,21 @@ impl Component for SkyLight {
         }
     }
 
-    fn handle_event(&mut self, _: &mut Glfw, _: &mut glfw::Window, _: &WindowEvent) {}
+    fn get_event_priority(&self, _: EventCategory) -> Option<i32> {
+        None
+    }
+
+    fn handle_event(&mut self, _: &mut Glfw, _: &mut glfw::Window, _: &WindowEvent) -> bool {
+        false
+    }
+
+    fn as_inspectable(&self) -> Option<&dyn InspectableComponent> {
+        Some(self)
+    }
+
+    fn as_inspectable_mut(&mut self) -> Option<&mut dyn InspectableComponent> {
+        Some(self)
+    }
 }
 
 #[derive(Debug)]
@@ -0,0 +1,165 @@
+use cgmath::{Deg, InnerSpace, Vector3};
+use glfw::{Glfw, WindowEvent};
+
+use crate::core::{
+    entity::{
+        component::{Component, InspectableComponent, InspectorField, InspectorValue},
+        Entity,
+    },
+    event::EventCategory,
+    scene::Scene,
+};
+
+/// Light shining in a cone from its entity's position
+pub struct SpotLightComponent {
+    color: Vector3<f32>,
+    intensity: f32,
+    range: f32,
+    /// Direction in the entity's space, rotated with the entity
+    direction: Vector3<f32>,
+    inner_angle: Deg<f32>,
+    outer_angle: Deg<f32>,
+}
+
+impl SpotLightComponent {
+    /// Creates a light pointing down with a 30° cone that softens from 20° outwards
+    pub fn new<C: Into<Vector3<f32>>>(color: C, intensity: f32, range: f32) -> Self {
+        Self {
+            color: color.into(),
+            intensity,
+            range,
+            direction: -Vector3::unit_y(),
+            inner_angle: Deg(20.0),
+            outer_angle: Deg(30.0),
+        }
+    }
+
+    pub fn with_direction<D: Into<Vector3<f32>>>(mut self, direction: D) -> Self {
+        self.set_direction(direction);
+        self
+    }
+
+    /// Full intensity inside `inner_angle`, fading to nothing at `outer_angle`, both measured from the direction
+    pub fn with_cone(mut self, inner_angle: Deg<f32>, outer_angle: Deg<f32>) -> Self {
+        self.set_cone(inner_angle, outer_angle);
+        self
+    }
+
+    pub fn get_color(&self) -> Vector3<f32> {
+        self.color
+    }
+
+    pub fn set_color<C: Into<Vector3<f32>>>(&mut self, color: C) {
+        self.color = color.into();
+    }
+
+    pub fn get_intensity(&self) -> f32 {
+        self.intensity
+    }
+
+    pub fn set_intensity(&mut self, intensity: f32) {
+        self.intensity = intensity.max(0.0);
+    }
+
+    pub fn get_range(&self) -> f32 {
+        self.range
+    }
+
+    pub fn set_range(&mut self, range: f32) {
+        self.range = range.max(0.0);
+    }
+
+    pub fn get_direction(&self) -> Vector3<f32> {
+        self.direction
+    }
+
+    pub fn set_direction<D: Into<Vector3<f32>>>(&mut self, direction: D) {
+        self.direction = direction.into().normalize();
+    }
+
+    pub fn get_inner_angle(&self) -> Deg<f32> {
+        self.inner_angle
+    }
+
+    pub fn get_outer_angle(&self) -> Deg<f32> {
+        self.outer_angle
+    }
+
+    pub fn set_cone(&mut self, inner_angle: Deg<f32>, outer_angle: Deg<f32>) {
+        self.outer_angle = Deg(outer_angle.0.clamp(0.0, 90.0));
+        self.inner_angle = Deg(inner_angle.0.clamp(0.0, self.outer_angle.0));
+    }
+}
+
+impl InspectableComponent for SpotLightComponent {
+    fn get_inspector_name(&self) -> &str {
+        "Spot light"
+    }
+
+    fn get_fields(&self) -> Vec<InspectorField> {
+        vec![
+            InspectorField {
+                name: "Color",
+                value: InspectorValue::Vector3(self.color),
+            },
+            InspectorField {
+                name: "Intensity",
+                value: InspectorValue::Float(self.intensity),
+            },
+            InspectorField {
+                name: "Range",
+                value: InspectorValue::Float(self.range),
+            },
+            InspectorField {
+                name: "Direction",
+                value: InspectorValue::Vector3(self.get_direction()),
+            },
+            InspectorField {
+                name: "Inner angle",
+                value: InspectorValue::Float(self.get_inner_angle().0),
+            },
+            InspectorField {
+                name: "Outer angle",
+                value: InspectorValue::Float(self.get_outer_angle().0),
+            },
+        ]
+    }
+
+    fn set_field(&mut self, name: &str, value: InspectorValue) {
+        match (name, value) {
+            ("Color", InspectorValue::Vector3(color)) => self.set_color(color),
+            ("Intensity", InspectorValue::Float(intensity)) => self.set_intensity(intensity),
+            ("Range", InspectorValue::Float(range)) => self.set_range(range),
+            ("Direction", InspectorValue::Vector3(direction)) if direction.magnitude2() > 0.0 => {
+                self.set_direction(direction)
+            }
+            ("Inner angle", InspectorValue::Float(angle)) => {
+                self.set_cone(Deg(angle), self.get_outer_angle())
+            }
+            ("Outer angle", InspectorValue::Float(angle)) => {
+                self.set_cone(self.get_inner_angle(), Deg(angle))
+            }
+            _ => {}
+        }
+    }
+}
+
+impl Component for SpotLightComponent {
+    fn update(&mut self, _: &mut Scene, _: &mut Entity, _: f64) {}
+
+    fn get_event_priority(&self, _: EventCategory) -> Option<i32> {
+        None
+    }
+
+    fn handle_event(&mut self, _: &mut Glfw, _: &mut glfw::Window, _: &WindowEvent) -> bool {
+        false
+    }
+
+    fn as_inspectable(&self) -> Option<&dyn InspectableComponent> {
+        Some(self)
+    }
+
+    fn as_inspectable_mut(&mut self) -> Option<&mut dyn InspectableComponent> {
+        Some(self)
+    }
+}
@@ -1,9 +1,12 @@
-#version 330 core
+#version 460 core
 
 in vec3 fColor;
+in vec3 WorldPosition;
 
 out vec4 FragColor;
 
+#include "environment.glsl"
+
 void main() {
-   FragColor = vec4(fColor, 1.0);
+   FragColor = vec4(applyFog(fColor, WorldPosition), 1.0);
 }
\ No newline at end of file
@@ -1,7 +1,12 @@
 use cgmath::{Matrix4, Point3, Vector3};
-use gl::types::*;
 
-use super::{Line, LineRenderer, Shader};
+use crate::core::renderer::{
+    environment::include_environment,
+    shader::{DynamicVertexArray, VertexAttributes},
+    shader_manager::ShaderSource,
+};
+
+use super::{Line, LineRenderer, LineVertex, Shader};
 
 use lazy_static::lazy_static;
 use std::sync::Mutex;
@@ -22,32 +27,16 @@ impl Line {
 
 impl LineRenderer {
     fn new() -> Self {
-        let shader = Shader::new(include_str!("vertex.glsl"), include_str!("fragment.glsl"));
-
-        let mut vao = 0;
-        let mut vbo = 0;
-        unsafe {
-            gl::GenVertexArrays(1, &mut vao);
-            gl::GenBuffers(1, &mut vbo);
-
-            gl::BindVertexArray(vao);
-            gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+        let shader = Shader::load(
+            ShaderSource::new("renderer/line/vertex.glsl", include_str!("vertex.glsl")),
+            ShaderSource::new("renderer/line/fragment.glsl", include_str!("fragment.glsl"))
+                .preprocess(include_environment),
+        );
 
-            gl::VertexAttribPointer(
-                0,
-                3,
-                gl::FLOAT,
-                gl::FALSE,
-                3 * std::mem::size_of::<GLfloat>() as GLsizei,
-                std::ptr::null(),
-            );
-            gl::EnableVertexAttribArray(0);
-
-            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
-            gl::BindVertexArray(0);
+        Self {
+            shader,
+            vertex_array: DynamicVertexArray::new(),
         }
-
-        Self { shader, vao, vbo }
     }
 
     pub fn render(
@@ -56,46 +45,7 @@ impl LineRenderer {
         color: Vector3<f32>,
         always_on_top: bool,
     ) {
-        let renderer = RENDERER.lock().unwrap();
-        unsafe {
-            if always_on_top {
-                gl::Disable(gl::DEPTH_TEST);
-            } else {
-                gl::Enable(gl::DEPTH_TEST);
-            }
-            renderer.shader.bind();
-
-            renderer
-                .shader
-                .set_uniform_mat4("viewProjection", &view_projection);
-            renderer.shader.set_uniform_3fv("color", &color);
-
-            gl::BindVertexArray(renderer.vao);
-            gl::BindBuffer(gl::ARRAY_BUFFER, renderer.vbo);
-
-            let end = line.position + line.direction * line.length;
-            let lines = vec![
-                line.position.x,
-                line.position.y,
-                line.position.z,
-                end.x,
-                end.y,
-                end.z,
-            ];
-
-            gl::BufferData(
-                gl::ARRAY_BUFFER,
-                (lines.len() * std::mem::size_of::<GLfloat>()) as GLsizeiptr,
-                lines.as_ptr() as *const _,
-                gl::STATIC_DRAW,
-            );
-            gl::DrawArrays(gl::LINES, 0, (lines.len() / 3) as i32);
-
-            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
-            gl::BindVertexArray(0);
-            gl::UseProgram(0);
-            gl::Disable(gl::DEPTH_TEST);
-        }
+        LineRenderer::render_lines(view_projection, &vec![line.clone()], color, always_on_top);
     }
 
     pub fn render_lines(
@@ -104,46 +54,44 @@ impl LineRenderer {
         color: Vector3<f32>,
         always_on_top: bool,
     ) {
-        let renderer = RENDERER.lock().unwrap();
+        let mut renderer = RENDERER.lock().unwrap();
         unsafe {
             if always_on_top {
                 gl::Disable(gl::DEPTH_TEST);
             } else {
                 gl::Enable(gl::DEPTH_TEST);
             }
-            renderer.shader.bind();
-
-            renderer
-                .shader
-                .set_uniform_mat4("viewProjection", &view_projection);
-            renderer.shader.set_uniform_3fv("color", &color);
+        }
+        renderer.shader.bind();
 
-            gl::BindVertexArray(renderer.vao);
-            gl::BindBuffer(gl::ARRAY_BUFFER, renderer.vbo);
+        renderer
+            .shader
+            .set_uniform_mat4("viewProjection", view_projection);
+        renderer.shader.set_uniform_3fv("color", &color);
 
-            let mut lines_data = Vec::new();
-            for line in lines {
-                let end = line.position + line.direction * line.length;
-                lines_data.push(line.position.x);
-                lines_data.push(line.position.y);
-                lines_data.push(line.position.z);
-                lines_data.push(end.x);
-                lines_data.push(end.y);
-                lines_data.push(end.z);
-            }
+        let mut vertices = Vec::with_capacity(lines.len() * 2);
+        for line in lines {
+            let end = line.position + line.direction * line.length;
+            vertices.push(LineVertex {
+                position: [line.position.x, line.position.y, line.position.z],
+            });
+            vertices.push(LineVertex {
+                position: [end.x, end.y, end.z],
+            });
+        }
 
-            gl::BufferData(
-                gl::ARRAY_BUFFER,
-                (lines_data.len() * std::mem::size_of::<GLfloat>()) as GLsizeiptr,
-                lines_data.as_ptr() as *const _,
-                gl::STATIC_DRAW,
-            );
-            gl::DrawArrays(gl::LINES, 0, (lines_data.len() / 3) as i32);
+        renderer.vertex_array.buffer_data_dyn(&vertices, &None);
+        renderer.vertex_array.draw(gl::LINES);
 
-            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
-            gl::BindVertexArray(0);
+        unsafe {
             gl::UseProgram(0);
             gl::Disable(gl::DEPTH_TEST);
         }
     }
 }
+
+impl VertexAttributes for LineVertex {
+    fn get_vertex_attributes() -> Vec<(usize, gl::types::GLuint)> {
+        vec![(3, gl::FLOAT)]
+    }
+}
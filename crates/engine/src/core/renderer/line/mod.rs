@@ -1,7 +1,6 @@
 use cgmath::{Point3, Vector3};
-use gl::types::GLuint;
 
-use crate::core::renderer::shader::Shader;
+use crate::core::renderer::shader::{DynamicVertexArray, Shader};
 
 pub mod line;
 
@@ -14,6 +13,11 @@ pub struct Line {
 
 pub struct LineRenderer {
     shader: Shader,
-    vao: GLuint,
-    vbo: GLuint,
+    vertex_array: DynamicVertexArray<LineVertex>,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct LineVertex {
+    pub position: [f32; 3],
 }
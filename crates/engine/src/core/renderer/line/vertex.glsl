@@ -1,4 +1,4 @@
-#version 330 core
+#version 460 core
 
 layout (location = 0) in vec3 position;
 
@@ -6,8 +6,10 @@ uniform mat4 viewProjection;
 uniform vec3 color;
 
 out vec3 fColor;
+out vec3 WorldPosition;
 
 void main(){
    gl_Position = viewProjection * vec4(position, 1.0);
    fColor = color;
+   WorldPosition = position;
 }
\ No newline at end of file
@@ -0,0 +1,119 @@
+use std::{cell::Cell, mem::size_of, ptr};
+
+use gl::types::*;
+
+/// Regions of a mapped buffer, the cpu fills one while the gpu may still read the others
+const REGION_COUNT: usize = 3;
+/// Longest time to wait for the gpu to release a region before overwriting it anyway
+const FENCE_TIMEOUT_NS: GLuint64 = 1_000_000_000;
+
+/// Buffer that stays mapped for its whole lifetime (GL_ARB_buffer_storage), split into
+/// `REGION_COUNT` regions that are written in turn.
+///
+/// Every region is fenced after the draw calls reading it, so a write only waits for the gpu if
+/// it wraps around to a region that is still in use
+pub struct MappedBuffer<T> {
+    id: GLuint,
+    /// Capacity of one region in elements
+    region_length: usize,
+    pointer: *mut T,
+    fences: [Cell<GLsync>; REGION_COUNT],
+    current: usize,
+}
+
+// the mapping and the fences are only used on the thread owning the gl context
+unsafe impl<T: Send> Send for MappedBuffer<T> {}
+
+impl<T> MappedBuffer<T> {
+    /// Whether the context supports persistently mapped buffers
+    pub fn is_supported() -> bool {
+        gl::BufferStorage::is_loaded()
+    }
+
+    /// Creates a buffer whose regions hold at least `region_length` elements
+    pub fn new(region_length: usize) -> Self {
+        let region_length = region_length.max(1).next_power_of_two();
+        let flags = gl::MAP_WRITE_BIT | gl::MAP_PERSISTENT_BIT | gl::MAP_COHERENT_BIT;
+        let size = (region_length * REGION_COUNT * size_of::<T>()) as GLsizeiptr;
+        let mut id = 0;
+        // a neutral target, binding the element array buffer would change the bound vertex array
+        let pointer = unsafe {
+            gl::GenBuffers(1, &mut id);
+            gl::BindBuffer(gl::COPY_WRITE_BUFFER, id);
+            gl::BufferStorage(gl::COPY_WRITE_BUFFER, size, ptr::null(), flags);
+            let pointer = gl::MapBufferRange(gl::COPY_WRITE_BUFFER, 0, size, flags) as *mut T;
+            gl::BindBuffer(gl::COPY_WRITE_BUFFER, 0);
+            pointer
+        };
+        Self {
+            id,
+            region_length,
+            pointer,
+            fences: [
+                Cell::new(ptr::null()),
+                Cell::new(ptr::null()),
+                Cell::new(ptr::null()),
+            ],
+            current: 0,
+        }
+    }
+
+    pub fn get_id(&self) -> GLuint {
+        self.id
+    }
+
+    /// Copies `data` into the next region and returns the index of its first element in the
+    /// buffer, the buffer is recreated with larger regions if `data` does not fit
+    pub fn write(&mut self, data: &[T]) -> usize {
+        if data.len() > self.region_length {
+            *self = MappedBuffer::new(data.len());
+        }
+        self.current = (self.current + 1) % REGION_COUNT;
+        self.wait(self.current);
+        let first = self.current * self.region_length;
+        if !self.pointer.is_null() {
+            unsafe {
+                ptr::copy_nonoverlapping(data.as_ptr(), self.pointer.add(first), data.len());
+            }
+        }
+        first
+    }
+
+    /// Fences the current region, called after issuing the draw calls that read it
+    pub fn fence(&self) {
+        let fence = &self.fences[self.current];
+        unsafe {
+            if !fence.get().is_null() {
+                gl::DeleteSync(fence.get());
+            }
+            fence.set(gl::FenceSync(gl::SYNC_GPU_COMMANDS_COMPLETE, 0));
+        }
+    }
+
+    fn wait(&self, region: usize) {
+        let fence = self.fences[region].replace(ptr::null());
+        if fence.is_null() {
+            return;
+        }
+        unsafe {
+            gl::ClientWaitSync(fence, gl::SYNC_FLUSH_COMMANDS_BIT, FENCE_TIMEOUT_NS);
+            gl::DeleteSync(fence);
+        }
+    }
+}
+
+impl<T> Drop for MappedBuffer<T> {
+    fn drop(&mut self) {
+        unsafe {
+            for fence in &self.fences {
+                if !fence.get().is_null() {
+                    gl::DeleteSync(fence.get());
+                }
+            }
+            gl::BindBuffer(gl::COPY_WRITE_BUFFER, self.id);
+            gl::UnmapBuffer(gl::COPY_WRITE_BUFFER);
+            gl::BindBuffer(gl::COPY_WRITE_BUFFER, 0);
+            gl::DeleteBuffers(1, &self.id);
+        }
+    }
+}
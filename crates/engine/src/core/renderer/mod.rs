@@ -1,8 +1,25 @@
+pub mod debug_draw;
+pub mod depth_of_field;
+pub mod environment;
 pub mod framebuffer;
+pub mod gl_debug;
+pub mod hdr;
+pub mod impostor;
 pub mod light;
 pub mod line;
+pub mod mapped_buffer;
+pub mod motion_blur;
 pub mod plane;
+pub mod render_graph;
+pub mod render_settings;
+pub mod render_workers;
+pub mod screen_quad;
 pub mod shader;
+pub mod shader_manager;
+pub mod ssao;
+pub mod ssr;
 pub mod text;
 pub mod texture;
+pub mod transparency;
 pub mod ui;
+pub mod water;
@@ -0,0 +1,44 @@
+#version 460 core
+
+out vec4 FragColor;
+
+in vec2 texCoord;
+
+uniform sampler2D colorTexture;
+uniform sampler2D depthTexture;
+uniform sampler2D velocityTexture;
+uniform int hasVelocity;
+uniform mat4 inverseViewProjection;
+uniform mat4 previousViewProjection;
+uniform float intensity;
+uniform int samples;
+
+// longest blur in texture coordinates, keeps camera cuts from smearing the whole frame
+const float MAX_BLUR = 0.05;
+
+void main() {
+    // where the pixel was on screen last frame if only the camera moved
+    float depth = texture(depthTexture, texCoord).r;
+    vec4 position = inverseViewProjection * vec4(texCoord * 2.0 - 1.0, depth * 2.0 - 1.0, 1.0);
+    vec4 previous = previousViewProjection * (position / position.w);
+    vec2 velocity = texCoord - (previous.xy / previous.w * 0.5 + 0.5);
+    if (hasVelocity == 1) {
+        vec4 objectVelocity = texture(velocityTexture, texCoord);
+        if (objectVelocity.a > 0.0) {
+            velocity = objectVelocity.xy;
+        }
+    }
+    velocity *= intensity;
+    float blur = length(velocity);
+    if (blur > MAX_BLUR) {
+        velocity *= MAX_BLUR / blur;
+    }
+
+    int count = max(samples, 1);
+    vec3 color = vec3(0.0);
+    for (int i = 0; i < count; i++) {
+        float t = (float(i) + 0.5) / float(count) - 0.5;
+        color += texture(colorTexture, texCoord + velocity * t).rgb;
+    }
+    FragColor = vec4(color / float(count), 1.0);
+}
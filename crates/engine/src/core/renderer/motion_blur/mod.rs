@@ -0,0 +1,39 @@
+use cgmath::Matrix4;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    framebuffer::{ColorFrameBuffer, FloatFrameBuffer},
+    screen_quad::ScreenQuad,
+    shader::Shader,
+};
+
+mod motion_blur;
+mod velocity;
+
+/// Motion blur settings, stored in the `EngineConfig`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MotionBlurSettings {
+    pub enabled: bool,
+    /// Fraction of the motion between two frames the blur covers
+    pub intensity: f32,
+    /// Samples taken along the motion of each pixel
+    pub samples: u32,
+}
+
+/// Renders the screen space motion of models since the last frame into the `VELOCITY` texture,
+/// skinned meshes include the motion of their bones
+pub struct VelocityPass {
+    shader: Shader,
+    target: Option<FloatFrameBuffer>,
+    previous_view_projection: Option<Matrix4<f32>>,
+}
+
+/// Blurs the frame along the motion of the camera, reconstructed from the depth, and the
+/// motion of the objects in the `VELOCITY` texture
+pub struct MotionBlurPass {
+    shader: Shader,
+    screen_quad: ScreenQuad,
+    target: Option<ColorFrameBuffer>,
+    previous_view_projection: Option<Matrix4<f32>>,
+}
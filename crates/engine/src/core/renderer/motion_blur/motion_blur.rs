@@ -0,0 +1,123 @@
+use cgmath::{Matrix4, SquareMatrix};
+
+use crate::core::{
+    entity::component::camera_component::CameraComponent,
+    renderer::{
+        framebuffer::ColorFrameBuffer,
+        render_graph::{
+            RenderPass, RenderResources, FRAME_COLOR, FRAME_DEPTH, POST_PROCESS_PASS_ORDER,
+            VELOCITY,
+        },
+        screen_quad::ScreenQuad,
+        shader::Shader,
+        shader_manager::ShaderSource,
+    },
+    scene::Scene,
+    window::Window,
+};
+
+use super::{MotionBlurPass, MotionBlurSettings};
+
+impl Default for MotionBlurSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            intensity: 0.5,
+            samples: 12,
+        }
+    }
+}
+
+impl MotionBlurPass {
+    pub fn new() -> Self {
+        Self {
+            shader: Shader::load(
+                ScreenQuad::get_vertex_shader(),
+                ShaderSource::new(
+                    "renderer/motion_blur/fragment.glsl",
+                    include_str!("fragment.glsl"),
+                ),
+            ),
+            screen_quad: ScreenQuad::new(),
+            target: None,
+            previous_view_projection: None,
+        }
+    }
+}
+
+impl Default for MotionBlurPass {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RenderPass for MotionBlurPass {
+    fn get_name(&self) -> &str {
+        "Motion blur"
+    }
+
+    fn get_order(&self) -> i32 {
+        POST_PROCESS_PASS_ORDER
+    }
+
+    fn render(&mut self, scene: &Scene, _: &Window, resources: &mut RenderResources) {
+        let Some(camera) = scene.get_component::<CameraComponent>() else {
+            return;
+        };
+        let view_projection = camera.get_view_projection();
+        let previous_view_projection = self
+            .previous_view_projection
+            .replace(view_projection)
+            .unwrap_or(view_projection);
+
+        let settings = &scene.config.motion_blur;
+        if !settings.enabled || settings.intensity <= 0.0 {
+            return;
+        }
+        let (Some(color), Some(depth)) = (
+            resources.get_texture(FRAME_COLOR),
+            resources.get_texture(FRAME_DEPTH),
+        ) else {
+            return;
+        };
+
+        let (width, height) = resources.get_size();
+        let target = ColorFrameBuffer::reuse_hdr(self.target.take(), width, height);
+        target.bind();
+        let has_velocity = resources.bind_texture(VELOCITY, 2);
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE1);
+            gl::BindTexture(gl::TEXTURE_2D, depth);
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, color);
+        }
+        self.shader.bind();
+        self.shader.set_uniform_1i("colorTexture", 0);
+        self.shader.set_uniform_1i("depthTexture", 1);
+        self.shader.set_uniform_1i("velocityTexture", 2);
+        self.shader
+            .set_uniform_1i("hasVelocity", has_velocity as i32);
+        self.shader.set_uniform_mat4(
+            "inverseViewProjection",
+            &view_projection.invert().unwrap_or(Matrix4::identity()),
+        );
+        self.shader
+            .set_uniform_mat4("previousViewProjection", &previous_view_projection);
+        self.shader.set_uniform_1f("intensity", settings.intensity);
+        self.shader
+            .set_uniform_1i("samples", settings.samples.max(1) as i32);
+        self.screen_quad.render(&self.shader);
+        unsafe {
+            for unit in (0..3).rev() {
+                gl::ActiveTexture(gl::TEXTURE0 + unit);
+                gl::BindTexture(gl::TEXTURE_2D, 0);
+            }
+        }
+
+        if let Some(texture) = target.get_color_texture() {
+            resources.set_texture(FRAME_COLOR, texture);
+        }
+        self.target = Some(target);
+        resources.bind_target();
+    }
+}
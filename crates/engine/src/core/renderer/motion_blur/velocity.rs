@@ -0,0 +1,100 @@
+use crate::core::{
+    entity::component::{camera_component::CameraComponent, model_component::ModelComponent},
+    model::include_bone_palette,
+    renderer::{
+        framebuffer::FloatFrameBuffer,
+        render_graph::{RenderPass, RenderResources, FRAME_DEPTH, VELOCITY, VELOCITY_PASS_ORDER},
+        shader::Shader,
+        shader_manager::ShaderSource,
+    },
+    scene::Scene,
+    window::Window,
+};
+
+use super::VelocityPass;
+
+impl VelocityPass {
+    pub fn new() -> Self {
+        Self {
+            shader: Shader::load(
+                ShaderSource::new(
+                    "renderer/motion_blur/velocity_vertex.glsl",
+                    include_str!("velocity_vertex.glsl"),
+                )
+                .preprocess(include_bone_palette),
+                ShaderSource::new(
+                    "renderer/motion_blur/velocity_fragment.glsl",
+                    include_str!("velocity_fragment.glsl"),
+                ),
+            ),
+            target: None,
+            previous_view_projection: None,
+        }
+    }
+}
+
+impl Default for VelocityPass {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RenderPass for VelocityPass {
+    fn get_name(&self) -> &str {
+        "Velocity"
+    }
+
+    fn get_order(&self) -> i32 {
+        VELOCITY_PASS_ORDER
+    }
+
+    fn render(&mut self, scene: &Scene, _: &Window, resources: &mut RenderResources) {
+        let Some(camera) = scene.get_component::<CameraComponent>() else {
+            return;
+        };
+        let view_projection = camera.get_view_projection();
+        let previous_view_projection = self
+            .previous_view_projection
+            .replace(view_projection)
+            .unwrap_or(view_projection);
+        // the depth of the frame only exists while post processing
+        let Some(depth) = resources.get_texture(FRAME_DEPTH) else {
+            return;
+        };
+
+        let (width, height) = resources.get_size();
+        let target = FloatFrameBuffer::reuse(self.target.take(), width, height);
+        target.0.attach_shared_depth_texture(depth);
+        target.bind();
+        unsafe {
+            gl::ClearBufferfv(gl::COLOR, 0, [0.0f32; 4].as_ptr());
+            // only the visible surfaces of the models write their motion, pulled forward a bit
+            // as their depth is computed by a different shader than in the main pass
+            gl::DepthMask(gl::FALSE);
+            gl::DepthFunc(gl::LEQUAL);
+            gl::Enable(gl::POLYGON_OFFSET_FILL);
+            gl::PolygonOffset(-1.0, -1.0);
+        }
+        for (transform, model) in scene.get_components_with_transform::<ModelComponent>() {
+            if let Some(model) = model.get_model() {
+                model.render_velocity(
+                    &self.shader,
+                    &transform,
+                    &view_projection,
+                    &previous_view_projection,
+                );
+            }
+        }
+        unsafe {
+            gl::Disable(gl::POLYGON_OFFSET_FILL);
+            gl::DepthFunc(gl::LESS);
+            gl::DepthMask(gl::TRUE);
+        }
+
+        if let Some(texture) = target.get_color_texture() {
+            resources.set_texture(VELOCITY, texture);
+        }
+        self.target = Some(target);
+        resources.bind_target();
+    }
+}
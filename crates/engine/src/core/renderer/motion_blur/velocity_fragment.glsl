@@ -0,0 +1,12 @@
+#version 460 core
+
+in vec4 currentPosition;
+in vec4 previousPosition;
+
+out vec4 FragColor;
+
+void main() {
+    // motion in texture coordinates, alpha marks pixels covered by a moving object
+    vec2 velocity = (currentPosition.xy / currentPosition.w - previousPosition.xy / previousPosition.w) * 0.5;
+    FragColor = vec4(velocity, 0.0, 1.0);
+}
@@ -0,0 +1,34 @@
+#version 460 core
+
+const int MAX_WEIGHTS = 4;
+
+layout (location = 0) in vec3 position;
+layout (location = 3) in ivec4 boneIDs;
+layout (location = 4) in vec4 weights;
+
+out vec4 currentPosition;
+out vec4 previousPosition;
+
+uniform mat4 model;
+uniform mat4 previousModel;
+uniform mat4 viewProjection;
+uniform mat4 previousViewProjection;
+
+#include "bone_palette.glsl"
+
+void main()
+{
+    mat4 boneTransform = boneTransforms[boneIDs[0]] * weights[0];
+    mat4 previousBoneTransform = previousBoneTransforms[boneIDs[0]] * weights[0];
+    for (int i = 1; i < MAX_WEIGHTS; i++)
+    {
+        if (weights[i] == 0.0)
+            break;
+        boneTransform += boneTransforms[boneIDs[i]] * weights[i];
+        previousBoneTransform += previousBoneTransforms[boneIDs[i]] * weights[i];
+    }
+
+    currentPosition = viewProjection * model * (boneTransform * vec4(position, 1.0));
+    previousPosition = previousViewProjection * previousModel * (previousBoneTransform * vec4(position, 1.0));
+    gl_Position = currentPosition;
+}
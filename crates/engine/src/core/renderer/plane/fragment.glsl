@@ -9,6 +9,9 @@ out vec4 FragColor;
 uniform float borderThickness = 0.0;
 uniform vec4 borderRadius = vec4(0.0);
 uniform vec4 borderColor = vec4(1.0, 0.0, 0.0, 1.0);
+// set for planes showing a texture instead of their color
+uniform bool textured = false;
+uniform sampler2D image;
 
 float RectSDF(vec2 position, vec2 halfSize, vec4 radius)
 {
@@ -28,5 +31,6 @@ void main()
         discard;
     }
 
-    FragColor = mix(borderColor, vertex_color, blend);
+    vec4 color = textured ? texture(image, pos / rect_size.xy) : vertex_color;
+    FragColor = mix(borderColor, color, blend);
 }
\ No newline at end of file
@@ -1,3 +1,5 @@
+use gl::types::GLuint;
+
 use crate::core::renderer::shader::Shader;
 
 use super::{
@@ -20,6 +22,8 @@ pub struct Plane {
     pub border_thickness: f32,
     pub border_color: (f32, f32, f32, f32),
     pub border_radius: (f32, f32, f32, f32),
+    /// Texture drawn in place of the color, owned elsewhere
+    texture: Option<GLuint>,
     vertex_array: DynamicVertexArray<PlaneVertex>,
 }
 
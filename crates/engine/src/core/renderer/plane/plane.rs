@@ -1,6 +1,12 @@
+use gl::types::GLuint;
+
 use crate::core::renderer::{
     shader::{DynamicVertexArray, Shader, VertexAttributes},
-    ui::primitives::{Position, Size},
+    shader_manager::ShaderSource,
+    ui::{
+        primitives::{Position, Size},
+        UIRenderer,
+    },
 };
 
 use super::{Plane, PlaneBuilder, PlaneRenderer, PlaneVertex};
@@ -14,7 +20,13 @@ lazy_static! {
 impl PlaneRenderer {
     fn new(width: f32, height: f32) -> Self {
         Self {
-            shader: Shader::new(include_str!("vertex.glsl"), include_str!("fragment.glsl")),
+            shader: Shader::load(
+                ShaderSource::new("renderer/plane/vertex.glsl", include_str!("vertex.glsl")),
+                ShaderSource::new(
+                    "renderer/plane/fragment.glsl",
+                    include_str!("fragment.glsl"),
+                ),
+            ),
             width,
             height,
         }
@@ -25,7 +37,8 @@ impl PlaneRenderer {
 
         plane.vertex_array.bind();
         renderer.shader.bind();
-        let ortho = cgmath::ortho(0.0, renderer.width, renderer.height, 0.0, -100.0, 100.0);
+        let size = PlaneRenderer::get_scaled_size(&renderer);
+        let ortho = cgmath::ortho(0.0, size.width, size.height, 0.0, -100.0, 100.0);
         renderer.shader.set_uniform_mat4("projection", &ortho);
         renderer
             .shader
@@ -44,17 +57,20 @@ impl PlaneRenderer {
             plane.border_color.2,
             plane.border_color.3,
         );
+        renderer
+            .shader
+            .set_uniform_1i("textured", plane.texture.is_some() as i32);
         unsafe {
+            if let Some(texture) = plane.texture {
+                gl::ActiveTexture(gl::TEXTURE0);
+                gl::BindTexture(gl::TEXTURE_2D, texture);
+                renderer.shader.set_uniform_1i("image", 0);
+            }
             gl::Enable(gl::DEPTH_TEST);
             gl::Enable(gl::BLEND);
             gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
-            gl::DrawElements(
-                gl::TRIANGLES,
-                plane.vertex_array.get_element_count() as i32,
-                gl::UNSIGNED_INT,
-                std::ptr::null(),
-            );
         }
+        plane.vertex_array.draw(gl::TRIANGLES);
     }
 
     pub fn resize(width: u32, height: u32) {
@@ -63,6 +79,21 @@ impl PlaneRenderer {
         renderer.height = height as f32;
     }
 
+    /// Size of the framebuffer the planes are projected onto in UI units, which are
+    /// `UIRenderer::get_scale` pixels wide
+    pub fn get_size() -> Size {
+        let renderer = RENDERER.lock().unwrap();
+        PlaneRenderer::get_scaled_size(&renderer)
+    }
+
+    fn get_scaled_size(renderer: &PlaneRenderer) -> Size {
+        let scale = UIRenderer::get_scale();
+        Size {
+            width: renderer.width / scale,
+            height: renderer.height / scale,
+        }
+    }
+
     pub fn resize_from_event(event: &glfw::WindowEvent) {
         match event {
             glfw::WindowEvent::FramebufferSize(width, height) => {
@@ -147,6 +178,7 @@ impl Plane {
             border_thickness,
             border_color,
             border_radius,
+            texture: None,
             vertex_array,
         };
         let vertices = plane.get_vertices();
@@ -231,10 +263,16 @@ impl Plane {
         self.recalculate_vertices();
     }
 
+    /// Draws the texture with the gl id `texture` inside the plane's borders instead of its
+    /// color, its first row at the top. The texture has to outlive the plane
+    pub fn set_texture(&mut self, texture: Option<GLuint>) {
+        self.texture = texture;
+    }
+
     fn recalculate_vertices(&mut self) {
         let vertices = self.get_vertices();
         let indices: Vec<u32> = vec![0, 1, 2, 2, 3, 0];
-        self.vertex_array.buffer_data(&vertices, &Some(indices));
+        self.vertex_array.buffer_data_dyn(&vertices, &Some(indices));
     }
 }
 
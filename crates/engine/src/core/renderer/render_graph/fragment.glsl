@@ -0,0 +1,13 @@
+#version 460 core
+
+out vec4 FragColor;
+
+in vec2 texCoord;
+
+uniform sampler2D colorTexture;
+uniform sampler2D depthTexture;
+
+void main() {
+    FragColor = vec4(texture(colorTexture, texCoord).rgb, 1.0);
+    gl_FragDepth = texture(depthTexture, texCoord).r;
+}
@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+
+use gl::types::GLuint;
+
+use crate::core::{profiler::GpuTimer, scene::Scene, window::Window};
+
+use super::{
+    framebuffer::{
+        ColorFrameBuffer, GBufferFrameBuffer, MultisampleFrameBuffer, ShadowFrameBuffer,
+    },
+    screen_quad::ScreenQuad,
+    shader::Shader,
+    texture::TextureRenderer,
+    transparency::TransparencyQueue,
+};
+
+mod passes;
+mod render_graph;
+
+pub const LIGHT_PASS_ORDER: i32 = -200;
+pub const SKY_PROBE_PASS_ORDER: i32 = -150;
+pub const SHADOW_PASS_ORDER: i32 = -100;
+pub const GBUFFER_PASS_ORDER: i32 = -60;
+pub const SSAO_PASS_ORDER: i32 = -50;
+pub const MAIN_PASS_ORDER: i32 = 0;
+/// Renders the planar reflection of the water, which is drawn with the transparent draws
+pub const WATER_PASS_ORDER: i32 = 20;
+/// Blended draws of the passes and components, sorted back to front over the opaque geometry
+pub const TRANSPARENT_PASS_ORDER: i32 = 30;
+pub const VELOCITY_PASS_ORDER: i32 = 50;
+pub const POST_PROCESS_PASS_ORDER: i32 = 100;
+/// Glow around the brightest parts of the HDR frame, after the other post process passes
+pub const BLOOM_PASS_ORDER: i32 = 150;
+/// Maps the HDR frame to displayable colors, the last post process pass
+pub const TONE_MAPPING_PASS_ORDER: i32 = 190;
+pub const DEBUG_PASS_ORDER: i32 = 200;
+
+/// Name the shadow pass shares its depth texture under
+pub const SHADOW_MAP: &str = "shadow_map";
+/// Color of the frame while post processing, post process passes replace it with their output.
+/// It is HDR until the tone mapping pass replaces it
+pub const FRAME_COLOR: &str = "frame_color";
+/// Depth of the frame while post processing
+pub const FRAME_DEPTH: &str = "frame_depth";
+/// World space normals of the visible surfaces, shared by the G-buffer pass
+pub const GBUFFER_NORMALS: &str = "gbuffer_normals";
+/// Material of the visible surfaces, x is the roughness and y is 1 where the surface already
+/// reflects the sky probe itself
+pub const GBUFFER_MATERIAL: &str = "gbuffer_material";
+/// Depth of the visible surfaces, shared by the G-buffer pass along with the normals
+pub const GBUFFER_DEPTH: &str = "gbuffer_depth";
+/// Screen space ambient occlusion, 1 where nothing occludes
+pub const AMBIENT_OCCLUSION: &str = "ambient_occlusion";
+/// Screen space velocities of moving objects, shared by the velocity pass
+pub const VELOCITY: &str = "velocity";
+
+/// Step of rendering a frame, registered on a scene with `Scene::add_render_pass`
+pub trait RenderPass {
+    fn get_name(&self) -> &str;
+    /// Passes run from the lowest to the highest order, passes with the same order
+    /// run in the order they were added
+    fn get_order(&self) -> i32;
+    fn render(&mut self, scene: &Scene, window: &Window, resources: &mut RenderResources);
+    /// Adds the blended draws of the pass to the queue drawn at `TRANSPARENT_PASS_ORDER`, after
+    /// the passes before it rendered
+    fn queue_transparent<'a>(&'a self, _scene: &'a Scene, _queue: &mut TransparencyQueue<'a>) {}
+}
+
+/// Render passes of a scene in the order they run.
+///
+/// While passes between `POST_PROCESS_PASS_ORDER` and `DEBUG_PASS_ORDER` are registered the
+/// earlier passes render into an offscreen frame, which is copied into the target before the
+/// debug passes run. The frame is also rendered offscreen, at the size and with the samples of
+/// the `RenderSettings`, while they scale the resolution or turn on MSAA. A multisampled frame is
+/// resolved before the velocity pass, the first one reading the frame.
+///
+/// Before the first pass with an order of at least `TRANSPARENT_PASS_ORDER` the blended draws
+/// the passes and the components of the entities queue are drawn back to front.
+#[derive(Default)]
+pub struct RenderGraph {
+    passes: Vec<Box<dyn RenderPass>>,
+    frame: Option<ColorFrameBuffer>,
+    /// Rendered into before the frame while MSAA is on
+    multisample: Option<MultisampleFrameBuffer>,
+    presenter: Option<FramePresenter>,
+    /// Gpu timers of the passes by name, only used while profiling
+    timers: HashMap<String, GpuTimer>,
+}
+
+/// Copies the color and depth of the post processed frame into the target
+struct FramePresenter {
+    shader: Shader,
+    screen_quad: ScreenQuad,
+}
+
+/// Resources the passes of a frame share
+pub struct RenderResources {
+    /// Framebuffer bound when the frame started, passes binding their own framebuffer bind it again
+    target: u32,
+    width: u32,
+    height: u32,
+    textures: HashMap<String, GLuint>,
+}
+
+/// Renders the scene from the skylight into a depth texture shared as `SHADOW_MAP`
+pub struct ShadowPass {
+    framebuffer: ShadowFrameBuffer,
+}
+
+/// Renders the normals, materials and depth of the scene from the camera, shared as
+/// `GBUFFER_NORMALS`, `GBUFFER_MATERIAL` and `GBUFFER_DEPTH` for screen space effects
+pub struct GBufferPass {
+    target: Option<GBufferFrameBuffer>,
+}
+
+/// Renders the scene from the camera
+pub struct MainPass;
+
+/// Shows the shadow map in a corner of the screen
+pub struct ShadowMapPreviewPass {
+    texture_renderer: TextureRenderer,
+}
@@ -0,0 +1,164 @@
+use cgmath::{Matrix4, SquareMatrix};
+
+use crate::core::{
+    entity::component::camera_component::CameraComponent,
+    renderer::{
+        framebuffer::{GBufferFrameBuffer, ShadowFrameBuffer},
+        light::skylight::SkyLight,
+        texture::TextureRenderer,
+    },
+    scene::Scene,
+    window::Window,
+};
+
+use super::{
+    GBufferPass, MainPass, RenderPass, RenderResources, ShadowMapPreviewPass, ShadowPass,
+    DEBUG_PASS_ORDER, GBUFFER_DEPTH, GBUFFER_MATERIAL, GBUFFER_NORMALS, GBUFFER_PASS_ORDER,
+    MAIN_PASS_ORDER, SHADOW_MAP, SHADOW_PASS_ORDER,
+};
+
+impl ShadowPass {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            framebuffer: ShadowFrameBuffer::new(width, height),
+        }
+    }
+}
+
+impl RenderPass for ShadowPass {
+    fn get_name(&self) -> &str {
+        "Shadow"
+    }
+
+    fn get_order(&self) -> i32 {
+        SHADOW_PASS_ORDER
+    }
+
+    fn render(&mut self, scene: &Scene, window: &Window, resources: &mut RenderResources) {
+        if let Some(resolution) = scene.config.quality.shadow_resolution {
+            if resolution != self.framebuffer.0.get_width() {
+                self.framebuffer = ShadowFrameBuffer::new(resolution, resolution);
+            }
+        }
+        if let Some(texture) = self.framebuffer.get_depth_texture() {
+            resources.set_texture(SHADOW_MAP, texture);
+        }
+        let Some(skylight) = scene.get_component::<SkyLight>() else {
+            return;
+        };
+        let light_projection = skylight.get_projection();
+        self.framebuffer.bind();
+        window.clear_mask(gl::DEPTH_BUFFER_BIT);
+        for entity in scene.get_entities().iter() {
+            entity.render(scene, &light_projection, Matrix4::identity());
+        }
+        resources.bind_target();
+    }
+}
+
+impl GBufferPass {
+    pub fn new() -> Self {
+        Self { target: None }
+    }
+}
+
+impl Default for GBufferPass {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RenderPass for GBufferPass {
+    fn get_name(&self) -> &str {
+        "G-buffer"
+    }
+
+    fn get_order(&self) -> i32 {
+        GBUFFER_PASS_ORDER
+    }
+
+    fn render(&mut self, scene: &Scene, _: &Window, resources: &mut RenderResources) {
+        // rendering the scene a second time is only worth it for the effects reading the G-buffer
+        if !scene.config.ssao.enabled && !scene.config.ssr.enabled {
+            return;
+        }
+        let Some(camera) = scene.get_component::<CameraComponent>() else {
+            return;
+        };
+        let view_projection = camera.get_view_projection();
+        let (width, height) = resources.get_size();
+        let target = GBufferFrameBuffer::reuse(self.target.take(), width, height);
+        target.bind();
+        unsafe {
+            gl::ClearBufferfv(gl::COLOR, 1, [0.0f32; 4].as_ptr());
+            gl::ClearBufferfv(gl::COLOR, 2, [1.0f32, 0.0, 0.0, 1.0].as_ptr());
+            gl::Clear(gl::DEPTH_BUFFER_BIT);
+        }
+        // binding the framebuffer binds its depth texture, which must not be sampled while drawing into it
+        resources.bind_texture(SHADOW_MAP, 0);
+        for entity in scene.get_entities().iter() {
+            entity.render(scene, &view_projection, Matrix4::identity());
+        }
+
+        if let Some(texture) = target.get_normal_texture() {
+            resources.set_texture(GBUFFER_NORMALS, texture);
+        }
+        resources.set_texture(GBUFFER_MATERIAL, target.get_material_texture());
+        if let Some(texture) = target.get_depth_texture() {
+            resources.set_texture(GBUFFER_DEPTH, texture);
+        }
+        self.target = Some(target);
+        resources.bind_target();
+    }
+}
+
+impl RenderPass for MainPass {
+    fn get_name(&self) -> &str {
+        "Main"
+    }
+
+    fn get_order(&self) -> i32 {
+        MAIN_PASS_ORDER
+    }
+
+    fn render(&mut self, scene: &Scene, _: &Window, resources: &mut RenderResources) {
+        let Some(camera) = scene.get_component::<CameraComponent>() else {
+            return;
+        };
+        let view_projection = camera.get_view_projection();
+        resources.bind_texture(SHADOW_MAP, 0);
+        for entity in scene.get_entities().iter() {
+            entity.render(scene, &view_projection, Matrix4::identity());
+        }
+    }
+}
+
+impl ShadowMapPreviewPass {
+    pub fn new() -> Self {
+        Self {
+            texture_renderer: TextureRenderer::new(),
+        }
+    }
+}
+
+impl Default for ShadowMapPreviewPass {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RenderPass for ShadowMapPreviewPass {
+    fn get_name(&self) -> &str {
+        "Shadow map preview"
+    }
+
+    fn get_order(&self) -> i32 {
+        DEBUG_PASS_ORDER
+    }
+
+    fn render(&mut self, _: &Scene, _: &Window, resources: &mut RenderResources) {
+        if let Some(texture) = resources.get_texture(SHADOW_MAP) {
+            self.texture_renderer.render_id(texture);
+        }
+    }
+}
@@ -0,0 +1,283 @@
+use std::collections::HashMap;
+
+use cgmath::{Matrix4, SquareMatrix};
+use gl::types::GLuint;
+use log::Level;
+
+use crate::core::{
+    entity::component::camera_component::CameraComponent,
+    logger::TimingSpan,
+    profiler::{GpuTimer, Profiler},
+    renderer::{
+        framebuffer::{ColorFrameBuffer, FrameBuffer, MultisampleFrameBuffer},
+        gl_debug,
+        render_settings::RenderSettings,
+        screen_quad::ScreenQuad,
+        shader::Shader,
+        shader_manager::ShaderSource,
+        texture::Texture,
+        transparency::TransparencyQueue,
+    },
+    scene::Scene,
+    window::Window,
+};
+
+use super::{
+    FramePresenter, RenderGraph, RenderPass, RenderResources, DEBUG_PASS_ORDER, FRAME_COLOR,
+    FRAME_DEPTH, POST_PROCESS_PASS_ORDER, SHADOW_MAP, TRANSPARENT_PASS_ORDER, VELOCITY_PASS_ORDER,
+};
+
+/// Name the transparent draws are profiled under
+const TRANSPARENT_PASS_NAME: &str = "Transparent";
+
+impl RenderGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Inserts the pass after all passes with the same or a lower order
+    pub fn add_pass(&mut self, pass: Box<dyn RenderPass>) {
+        let order = pass.get_order();
+        let index = self
+            .passes
+            .partition_point(|other| other.get_order() <= order);
+        self.passes.insert(index, pass);
+    }
+
+    pub fn remove_pass(&mut self, name: &str) -> Option<Box<dyn RenderPass>> {
+        let index = self
+            .passes
+            .iter()
+            .position(|pass| pass.get_name() == name)?;
+        self.timers.remove(name);
+        Some(self.passes.remove(index))
+    }
+
+    pub fn has_pass(&self, name: &str) -> bool {
+        self.passes.iter().any(|pass| pass.get_name() == name)
+    }
+
+    /// Names of the passes in the order they run
+    pub fn get_pass_names(&self) -> Vec<&str> {
+        self.passes.iter().map(|pass| pass.get_name()).collect()
+    }
+
+    pub fn render(&mut self, scene: &Scene, window: &Window) {
+        let target = FrameBuffer::get_bound_id();
+        let settings = RenderSettings::get();
+        let (width, height) = settings.get_render_size(window.width, window.height);
+        let mut resources = RenderResources::new(target, width, height);
+        let post_processing = self
+            .passes
+            .iter()
+            .any(|pass| (POST_PROCESS_PASS_ORDER..DEBUG_PASS_ORDER).contains(&pass.get_order()));
+        // the frame is only rendered offscreen if it has to be read or stretched over the window
+        let offscreen = post_processing
+            || (width, height) != (window.width, window.height)
+            || settings.is_msaa_enabled();
+        if offscreen {
+            let frame = ColorFrameBuffer::reuse_hdr(self.frame.take(), width, height);
+            frame.bind();
+            window.clear_mask(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+            resources.target = frame.get_id();
+            if let (Some(color), Some(depth)) =
+                (frame.get_color_texture(), frame.get_depth_texture())
+            {
+                resources.set_texture(FRAME_COLOR, color);
+                resources.set_texture(FRAME_DEPTH, depth);
+            }
+            if settings.is_msaa_enabled() {
+                let multisample = MultisampleFrameBuffer::reuse(
+                    self.multisample.take(),
+                    settings.msaa_samples,
+                    &frame,
+                );
+                multisample.bind();
+                window.clear_mask(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+                resources.target = multisample.get_id();
+                self.multisample = Some(multisample);
+            }
+            self.frame = Some(frame);
+        }
+        if !settings.is_msaa_enabled() {
+            self.multisample = None;
+        }
+
+        let mut resolved = self.multisample.is_none();
+        let mut presented = !offscreen;
+        let mut transparent = false;
+        for index in 0..self.passes.len() {
+            let order = self.passes[index].get_order();
+            if !transparent && order >= TRANSPARENT_PASS_ORDER {
+                RenderGraph::render_transparent(&self.passes, &mut self.timers, scene, &resources);
+                transparent = true;
+            }
+            if !resolved && order >= VELOCITY_PASS_ORDER {
+                RenderGraph::resolve(&self.multisample, &self.frame, &mut resources);
+                resolved = true;
+            }
+            if !presented && order >= DEBUG_PASS_ORDER {
+                FramePresenter::present(&mut self.presenter, &mut resources, target, window);
+                presented = true;
+            }
+            let pass = &mut self.passes[index];
+            let _span = TimingSpan::new(Level::Trace, "render", pass.get_name());
+            let timer = Profiler::is_enabled().then(|| {
+                let timer = self.timers.entry(pass.get_name().to_string()).or_default();
+                timer.begin();
+                timer
+            });
+            pass.render(scene, window, &mut resources);
+            if let Some(milliseconds) = timer.and_then(GpuTimer::end) {
+                Profiler::record_gpu_time(pass.get_name(), milliseconds);
+            }
+            gl_debug::check_errors(pass.get_name());
+        }
+        if !transparent {
+            RenderGraph::render_transparent(&self.passes, &mut self.timers, scene, &resources);
+        }
+        if !resolved {
+            RenderGraph::resolve(&self.multisample, &self.frame, &mut resources);
+        }
+        if !presented {
+            FramePresenter::present(&mut self.presenter, &mut resources, target, window);
+        }
+    }
+
+    /// Draws the blended draws the passes and the components queue from the farthest to the
+    /// nearest, timed like a pass
+    fn render_transparent(
+        passes: &[Box<dyn RenderPass>],
+        timers: &mut HashMap<String, GpuTimer>,
+        scene: &Scene,
+        resources: &RenderResources,
+    ) {
+        let Some(camera) = scene.get_component::<CameraComponent>() else {
+            return;
+        };
+        let _span = TimingSpan::new(Level::Trace, "render", TRANSPARENT_PASS_NAME);
+        let timer = Profiler::is_enabled().then(|| {
+            let timer = timers.entry(TRANSPARENT_PASS_NAME.to_string()).or_default();
+            timer.begin();
+            timer
+        });
+        let view_projection = camera.get_view_projection();
+        let mut queue = TransparencyQueue::new(camera.get_camera().get_matrix());
+        for pass in passes {
+            pass.queue_transparent(scene, &mut queue);
+        }
+        for entity in scene.get_entities().iter() {
+            entity.queue_transparent(scene, &view_projection, Matrix4::identity(), &mut queue);
+        }
+        queue.render();
+        resources.bind_texture(SHADOW_MAP, 0);
+        if let Some(milliseconds) = timer.and_then(GpuTimer::end) {
+            Profiler::record_gpu_time(TRANSPARENT_PASS_NAME, milliseconds);
+        }
+        gl_debug::check_errors(TRANSPARENT_PASS_NAME);
+    }
+
+    /// Resolves the multisampled frame into the frame the later passes read and render into
+    fn resolve(
+        multisample: &Option<MultisampleFrameBuffer>,
+        frame: &Option<ColorFrameBuffer>,
+        resources: &mut RenderResources,
+    ) {
+        if let (Some(multisample), Some(frame)) = (multisample, frame) {
+            multisample.resolve(frame);
+            resources.target = frame.get_id();
+            resources.bind_target();
+        }
+    }
+}
+
+impl FramePresenter {
+    fn new() -> Self {
+        Self {
+            shader: Shader::load(
+                ScreenQuad::get_vertex_shader(),
+                ShaderSource::new(
+                    "renderer/render_graph/fragment.glsl",
+                    include_str!("fragment.glsl"),
+                ),
+            ),
+            screen_quad: ScreenQuad::new(),
+        }
+    }
+
+    /// Copies the frame into `target` and makes it the target of the remaining passes, stretching
+    /// it over the window if it was rendered at another resolution
+    fn present(
+        presenter: &mut Option<FramePresenter>,
+        resources: &mut RenderResources,
+        target: u32,
+        window: &Window,
+    ) {
+        resources.target = target;
+        resources.width = window.width;
+        resources.height = window.height;
+        resources.bind_target();
+        let presenter = presenter.get_or_insert_with(FramePresenter::new);
+        if resources.bind_texture(FRAME_COLOR, 0) && resources.bind_texture(FRAME_DEPTH, 1) {
+            presenter.shader.bind();
+            presenter.shader.set_uniform_1i("colorTexture", 0);
+            presenter.shader.set_uniform_1i("depthTexture", 1);
+            presenter.screen_quad.render_with_depth(&presenter.shader);
+        }
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE1);
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+        }
+    }
+}
+
+impl RenderResources {
+    fn new(target: u32, width: u32, height: u32) -> Self {
+        Self {
+            target,
+            width,
+            height,
+            textures: HashMap::new(),
+        }
+    }
+
+    pub fn get_target(&self) -> u32 {
+        self.target
+    }
+
+    /// Size of the frame in pixels
+    pub fn get_size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// Binds the framebuffer the frame is rendered into
+    pub fn bind_target(&self) {
+        FrameBuffer::bind_id(self.target);
+        unsafe {
+            gl::Viewport(0, 0, self.width as i32, self.height as i32);
+        }
+    }
+
+    /// Shares `texture` with the passes running later in the frame
+    pub fn set_texture(&mut self, name: &str, texture: &Texture) {
+        self.textures.insert(name.to_string(), texture.id);
+    }
+
+    pub fn get_texture(&self, name: &str) -> Option<GLuint> {
+        self.textures.get(name).copied()
+    }
+
+    /// Binds a shared texture to the texture unit, returns false if no pass shared it
+    pub fn bind_texture(&self, name: &str, unit: u32) -> bool {
+        let Some(texture) = self.get_texture(name) else {
+            return false;
+        };
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0 + unit);
+            gl::BindTexture(gl::TEXTURE_2D, texture);
+        }
+        true
+    }
+}
@@ -0,0 +1,39 @@
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+
+use crate::core::utils::DataSource;
+
+mod render_settings;
+
+/// Most samples per pixel of the multisampled frame
+pub const MAX_MSAA_SAMPLES: u32 = 8;
+/// Smallest and largest size of the frame relative to the window
+pub const MIN_RESOLUTION_SCALE: f32 = 0.25;
+pub const MAX_RESOLUTION_SCALE: f32 = 2.0;
+
+lazy_static! {
+    /// Shared with the settings UI, clamped whenever they are read
+    static ref MSAA_SAMPLES: DataSource<u32> = DataSource::new(4);
+    static ref VSYNC: DataSource<bool> = DataSource::new(true);
+    static ref RESOLUTION_SCALE: DataSource<f32> = DataSource::new(1.0);
+}
+
+/// Anti-aliasing, vsync and the internal resolution the scene is rendered at. The current
+/// settings are shared by the window and the render graphs of all scenes, see
+/// `RenderSettings::get` and `RenderSettings::set`, and stored in the `EngineConfig`.
+///
+/// The scene renders into an offscreen frame of the scaled size, multisampled when MSAA is on,
+/// which is stretched over the window when it is presented. The UI is drawn at the resolution of
+/// the window
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RenderSettings {
+    /// Samples per pixel, 0 or 1 turns MSAA off. Also requested for the window's own framebuffer
+    /// when it is created
+    pub msaa_samples: u32,
+    /// Waits for the display before showing a frame, hidden windows never wait
+    pub vsync: bool,
+    /// Size of the frame relative to the window, below 1 renders fewer pixels and above 1
+    /// supersamples
+    pub resolution_scale: f32,
+}
@@ -0,0 +1,89 @@
+use crate::core::utils::DataSource;
+
+use super::{
+    RenderSettings, MAX_MSAA_SAMPLES, MAX_RESOLUTION_SCALE, MIN_RESOLUTION_SCALE, MSAA_SAMPLES,
+    RESOLUTION_SCALE, VSYNC,
+};
+
+impl RenderSettings {
+    /// The current settings
+    pub fn get() -> RenderSettings {
+        RenderSettings {
+            msaa_samples: MSAA_SAMPLES.read(),
+            vsync: VSYNC.read(),
+            resolution_scale: RESOLUTION_SCALE.read(),
+        }
+        .clamped()
+    }
+
+    /// Replaces the current settings, the next frame renders with them
+    pub fn set(settings: RenderSettings) {
+        let settings = settings.clamped();
+        MSAA_SAMPLES.write(settings.msaa_samples);
+        VSYNC.write(settings.vsync);
+        RESOLUTION_SCALE.write(settings.resolution_scale);
+    }
+
+    pub fn toggle_vsync() {
+        VSYNC.write(!VSYNC.read());
+    }
+
+    /// Steps through no MSAA and 2, 4 and 8 samples
+    pub fn cycle_msaa_samples() {
+        let samples = match RenderSettings::get().msaa_samples {
+            samples if samples >= MAX_MSAA_SAMPLES => 0,
+            samples if samples < 2 => 2,
+            samples => samples * 2,
+        };
+        MSAA_SAMPLES.write(samples);
+    }
+
+    /// The samples for sliders to edit
+    pub fn get_msaa_samples_ref() -> DataSource<u32> {
+        MSAA_SAMPLES.clone()
+    }
+
+    /// The resolution scale for sliders to edit
+    pub fn get_resolution_scale_ref() -> DataSource<f32> {
+        RESOLUTION_SCALE.clone()
+    }
+
+    /// Whether the frame is multisampled
+    pub fn is_msaa_enabled(&self) -> bool {
+        self.msaa_samples > 1
+    }
+
+    /// Size of the frame for a window of `width` by `height` pixels
+    pub fn get_render_size(&self, width: u32, height: u32) -> (u32, u32) {
+        let scale = |size: u32| ((size as f32 * self.resolution_scale).round() as u32).max(1);
+        (scale(width), scale(height))
+    }
+
+    /// The settings within the supported ranges, the samples rounded down to a power of two
+    fn clamped(self) -> RenderSettings {
+        let msaa_samples = match self.msaa_samples.min(MAX_MSAA_SAMPLES) {
+            0 | 1 => 0,
+            samples => 1 << samples.ilog2(),
+        };
+        RenderSettings {
+            msaa_samples,
+            vsync: self.vsync,
+            resolution_scale: if self.resolution_scale.is_finite() {
+                self.resolution_scale
+                    .clamp(MIN_RESOLUTION_SCALE, MAX_RESOLUTION_SCALE)
+            } else {
+                1.0
+            },
+        }
+    }
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        Self {
+            msaa_samples: 4,
+            vsync: true,
+            resolution_scale: 1.0,
+        }
+    }
+}
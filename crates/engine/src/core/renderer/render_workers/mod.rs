@@ -0,0 +1,28 @@
+use std::{
+    sync::{mpsc, Mutex},
+    thread,
+};
+
+use lazy_static::lazy_static;
+
+mod render_workers;
+
+lazy_static! {
+    static ref WORKERS: RenderWorkers = RenderWorkers::spawn();
+}
+
+type Job = Box<dyn FnOnce() + Send>;
+
+/// Threads the visibility tests and draw recording of the terrain's chunk batches run on,
+/// started once and shared by every scene. The recorded draws are submitted by the thread owning
+/// the GL context. Entities are still culled with their `BoundsComponent` and drawn on that
+/// thread by the passes, components are not `Send`
+pub struct RenderWorkers {
+    jobs: Mutex<mpsc::Sender<Job>>,
+    count: usize,
+}
+
+/// Results of the jobs started with `RenderWorkers::record`, in the order of the jobs
+pub struct Recording<T> {
+    results: Vec<mpsc::Receiver<thread::Result<T>>>,
+}
@@ -0,0 +1,75 @@
+use std::{
+    panic::{self, AssertUnwindSafe},
+    sync::{mpsc, Arc, Mutex},
+    thread,
+};
+
+use super::{Job, Recording, RenderWorkers, WORKERS};
+
+impl RenderWorkers {
+    /// Number of worker threads, one less than the available parallelism so the render thread
+    /// keeps a core
+    pub fn get_count() -> usize {
+        WORKERS.count
+    }
+
+    /// Queues every job to run on the workers, `Recording::wait` returns their results
+    pub fn record<T: Send + 'static>(
+        jobs: impl IntoIterator<Item = impl FnOnce() -> T + Send + 'static>,
+    ) -> Recording<T> {
+        let sender = WORKERS.jobs.lock().unwrap();
+        let results = jobs
+            .into_iter()
+            .map(|job| {
+                let (result_sender, result) = mpsc::channel();
+                // the panic is resumed on the thread waiting for the result, the worker lives on
+                let _ = sender.send(Box::new(move || {
+                    let _ = result_sender.send(panic::catch_unwind(AssertUnwindSafe(job)));
+                }));
+                result
+            })
+            .collect();
+        Recording { results }
+    }
+
+    fn spawn() -> RenderWorkers {
+        let count = thread::available_parallelism()
+            .map_or(1, |count| count.get())
+            .saturating_sub(1)
+            .max(1);
+        let (sender, jobs) = mpsc::channel::<Job>();
+        let jobs = Arc::new(Mutex::new(jobs));
+        for i in 0..count {
+            let jobs = jobs.clone();
+            thread::Builder::new()
+                .name(format!("render worker {}", i))
+                .spawn(move || loop {
+                    let job = jobs.lock().unwrap().recv();
+                    match job {
+                        Ok(job) => job(),
+                        Err(_) => return,
+                    }
+                })
+                .expect("failed to start a render worker");
+        }
+        RenderWorkers {
+            jobs: Mutex::new(sender),
+            count,
+        }
+    }
+}
+
+impl<T> Recording<T> {
+    /// Blocks until every job finished and returns their results, a job that panicked panics
+    /// the calling thread with the same payload
+    pub fn wait(self) -> Vec<T> {
+        self.results
+            .into_iter()
+            .map(|result| match result.recv() {
+                Ok(Ok(value)) => value,
+                Ok(Err(payload)) => panic::resume_unwind(payload),
+                Err(_) => panic!("a render worker stopped before finishing its job"),
+            })
+            .collect()
+    }
+}
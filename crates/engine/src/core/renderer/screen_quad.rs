@@ -0,0 +1,100 @@
+use crate::core::profiler::Profiler;
+
+use super::{
+    gl_debug,
+    shader::{DynamicVertexArray, Shader, VertexAttributes},
+    shader_manager::ShaderSource,
+};
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct ScreenVertex {
+    pub position: [f32; 2],
+    pub tex_coord: [f32; 2],
+}
+
+/// Quad covering the whole viewport, used by full screen passes
+pub struct ScreenQuad {
+    vertex_array: DynamicVertexArray<ScreenVertex>,
+}
+
+impl ScreenQuad {
+    pub fn new() -> Self {
+        let vertex = |x: f32, y: f32| ScreenVertex {
+            position: [x, y],
+            tex_coord: [(x + 1.0) / 2.0, (y + 1.0) / 2.0],
+        };
+        let mut vertex_array = DynamicVertexArray::new();
+        vertex_array.buffer_data(
+            &vec![
+                vertex(-1.0, -1.0),
+                vertex(1.0, -1.0),
+                vertex(1.0, 1.0),
+                vertex(-1.0, 1.0),
+            ],
+            &Some(vec![0, 1, 2, 2, 3, 0]),
+        );
+        Self { vertex_array }
+    }
+
+    /// Vertex shader of the full screen passes, passes the quad's `texCoord` on to their
+    /// fragment shaders
+    pub fn get_vertex_shader() -> ShaderSource {
+        ShaderSource::new(
+            "renderer/screen_quad_vertex.glsl",
+            include_str!("screen_quad_vertex.glsl"),
+        )
+    }
+
+    /// Draws the quad with `shader`, its uniforms and textures have to be set beforehand
+    pub fn render(&self, shader: &Shader) {
+        unsafe {
+            gl::Disable(gl::DEPTH_TEST);
+        }
+        self.draw(shader);
+        unsafe {
+            gl::Enable(gl::DEPTH_TEST);
+        }
+    }
+
+    /// Draws the quad over everything while writing depth, for shaders setting `gl_FragDepth`
+    pub fn render_with_depth(&self, shader: &Shader) {
+        unsafe {
+            gl::Enable(gl::DEPTH_TEST);
+            gl::DepthFunc(gl::ALWAYS);
+            gl::DepthMask(gl::TRUE);
+        }
+        self.draw(shader);
+        unsafe {
+            gl::DepthFunc(gl::LESS);
+        }
+    }
+
+    fn draw(&self, shader: &Shader) {
+        shader.bind();
+        self.vertex_array.bind();
+        unsafe {
+            gl::DrawElements(
+                gl::TRIANGLES,
+                self.vertex_array.get_element_count() as i32,
+                gl::UNSIGNED_INT,
+                std::ptr::null(),
+            );
+        }
+        Profiler::count_draw_call();
+        gl_debug::check_errors("ScreenQuad::draw");
+        DynamicVertexArray::<ScreenVertex>::unbind();
+    }
+}
+
+impl VertexAttributes for ScreenVertex {
+    fn get_vertex_attributes() -> Vec<(usize, gl::types::GLuint)> {
+        vec![(2, gl::FLOAT), (2, gl::FLOAT)]
+    }
+}
+
+impl Default for ScreenQuad {
+    fn default() -> Self {
+        Self::new()
+    }
+}
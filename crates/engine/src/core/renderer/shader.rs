@@ -1,9 +1,68 @@
 use cgmath::{Array, Matrix};
 use gl::types::*;
-use std::{ffi::CString, ptr};
+use std::{
+    cell::{Ref, RefCell},
+    collections::{BTreeMap, HashMap, HashSet},
+    error::Error,
+    ffi::CString,
+    fmt, ptr,
+    sync::{
+        atomic::{AtomicBool, AtomicU32, Ordering},
+        Arc,
+    },
+};
+
+use super::{
+    gl_debug,
+    mapped_buffer::MappedBuffer,
+    shader_manager::{ShaderManager, ShaderSource},
+};
+use crate::core::profiler::Profiler;
+
+static VALIDATE_UNIFORMS: AtomicBool = AtomicBool::new(false);
 
 pub struct Shader {
-    pub id: GLuint,
+    /// Program id, replaced by the `ShaderManager` when a watched source changes on disk
+    id: Arc<AtomicU32>,
+    uniforms: RefCell<UniformCache>,
+}
+
+/// `#define`s a shader variant is compiled with, inserted right after the `#version` line.
+/// Sorted by name, so the same set of defines always gives the same variant
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct ShaderDefines {
+    /// Value per define, empty for flags
+    defines: BTreeMap<String, String>,
+}
+
+/// Variants of a vertex and fragment shader pair compiled with different `ShaderDefines`, so
+/// e.g. materials only pay for the features they use. A variant is compiled the first time it
+/// is requested and reused afterwards
+pub struct ShaderPermutations {
+    vertex_source: ShaderSource,
+    fragment_source: ShaderSource,
+    variants: RefCell<HashMap<ShaderDefines, Shader>>,
+}
+
+#[derive(Debug)]
+pub enum ShaderError {
+    UnknownUniform { program: GLuint, name: String },
+}
+
+/// Uniform locations of a linked program and the values last uploaded to them
+struct UniformCache {
+    program: GLuint,
+    locations: HashMap<String, GLint>,
+    values: HashMap<GLint, UniformValue>,
+    /// Missing uniforms that were already reported
+    reported: HashSet<String>,
+}
+
+/// Bits of a uniform value of up to 16 components
+#[derive(Clone, Copy, PartialEq)]
+struct UniformValue {
+    length: usize,
+    bits: [u32; 16],
 }
 
 pub struct DynamicVertexArray<T> {
@@ -12,6 +71,15 @@ pub struct DynamicVertexArray<T> {
     ebo: GLuint,
     current_vertex_data: Option<Vec<T>>,
     indices: Option<Vec<u32>>,
+    /// Buffer of per-instance attributes, created by the first `buffer_instance_data`
+    instance_vbo: Option<GLuint>,
+    instance_count: usize,
+    /// Persistently mapped buffers written by `buffer_data_dyn`
+    mapped_vertices: Option<MappedBuffer<T>>,
+    mapped_indices: Option<MappedBuffer<u32>>,
+    /// Where the current data starts in the mapped buffers
+    base_vertex: usize,
+    first_index: usize,
 }
 
 pub trait VertexAttributes {
@@ -20,155 +88,510 @@ pub trait VertexAttributes {
 
 impl Shader {
     pub fn new(vertex_source: &str, fragment_source: &str) -> Self {
-        Shader {
-            id: Shader::create_shader(vertex_source, fragment_source),
-        }
+        Shader::from_program(Arc::new(AtomicU32::new(Shader::create_shader(
+            vertex_source,
+            fragment_source,
+        ))))
+    }
+
+    pub fn new_compute(compute_source: &str) -> Self {
+        Shader::from_program(Arc::new(AtomicU32::new(Shader::create_compute_shader(
+            compute_source,
+        ))))
+    }
+
+    /// Shader whose sources are reloaded from the shader directory in debug builds
+    pub fn load(vertex_source: ShaderSource, fragment_source: ShaderSource) -> Self {
+        Shader::from_program(ShaderManager::load(vec![
+            (gl::VERTEX_SHADER, vertex_source),
+            (gl::FRAGMENT_SHADER, fragment_source),
+        ]))
+    }
+
+    /// Shader compiled with `defines`, reloaded from the shader directory in debug builds
+    pub fn load_with_defines(
+        vertex_source: ShaderSource,
+        fragment_source: ShaderSource,
+        defines: &ShaderDefines,
+    ) -> Self {
+        Shader::from_program(ShaderManager::load_with_defines(
+            vec![
+                (gl::VERTEX_SHADER, vertex_source),
+                (gl::FRAGMENT_SHADER, fragment_source),
+            ],
+            defines,
+        ))
+    }
+
+    /// Compute shader whose source is reloaded from the shader directory in debug builds
+    pub fn load_compute(compute_source: ShaderSource) -> Self {
+        Shader::from_program(ShaderManager::load(vec![(
+            gl::COMPUTE_SHADER,
+            compute_source,
+        )]))
+    }
+
+    fn from_program(id: Arc<AtomicU32>) -> Self {
+        let uniforms = RefCell::new(UniformCache::new(id.load(Ordering::Relaxed)));
+        Shader { id, uniforms }
+    }
+
+    pub fn get_id(&self) -> GLuint {
+        self.id.load(Ordering::Relaxed)
     }
 
     pub fn bind(&self) {
         unsafe {
-            gl::UseProgram(self.id);
+            gl::UseProgram(self.get_id());
         }
     }
 
-    pub fn set_uniform_mat4(&self, name: &str, matrix: &cgmath::Matrix4<f32>) {
+    /// Runs a compute shader with the given number of work groups and waits for its storage writes
+    pub fn dispatch(&self, groups_x: u32, groups_y: u32, groups_z: u32) {
         unsafe {
-            let name = CString::new(name).unwrap();
-            let location = gl::GetUniformLocation(self.id, name.as_ptr());
-            gl::UniformMatrix4fv(location, 1, gl::FALSE, matrix.as_ptr());
+            gl::UseProgram(self.get_id());
+            gl::DispatchCompute(groups_x, groups_y, groups_z);
+            gl::MemoryBarrier(gl::SHADER_STORAGE_BARRIER_BIT | gl::BUFFER_UPDATE_BARRIER_BIT);
+        }
+    }
+
+    /// Turns on reporting of uniforms that are set but do not exist in the program, each missing
+    /// uniform is reported once per shader. Off by default since the compiler drops unused
+    /// uniforms
+    pub fn set_uniform_validation(enabled: bool) {
+        VALIDATE_UNIFORMS.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Location of the uniform `name` in the program
+    pub fn get_uniform_location(&self, name: &str) -> Result<GLint, ShaderError> {
+        let program = self.get_id();
+        let mut cache = self.uniforms.borrow_mut();
+        if cache.program != program {
+            *cache = UniformCache::new(program);
+        }
+        let location = match cache.locations.get(name) {
+            Some(location) => *location,
+            // elements of arrays beyond the first are not listed as active uniforms
+            None => {
+                let location = CString::new(name)
+                    .map(|c_name| unsafe { gl::GetUniformLocation(program, c_name.as_ptr()) })
+                    .unwrap_or(-1);
+                cache.locations.insert(name.to_string(), location);
+                location
+            }
+        };
+        if location < 0 {
+            return Err(ShaderError::UnknownUniform {
+                program,
+                name: name.to_string(),
+            });
+        }
+        Ok(location)
+    }
+
+    pub fn set_uniform_mat4(&self, name: &str, matrix: &cgmath::Matrix4<f32>) {
+        let values: &[f32; 16] = matrix.as_ref();
+        if let Some(location) = self.prepare_uniform(name, &values.map(f32::to_bits)) {
+            unsafe {
+                gl::ProgramUniformMatrix4fv(self.get_id(), location, 1, gl::FALSE, matrix.as_ptr());
+            }
         }
     }
 
     pub fn set_uniform_mat4_array(&self, name: &str, matrices: &Vec<cgmath::Matrix4<f32>>) {
-        unsafe {
-            let name = CString::new(name).unwrap();
-            let location = gl::GetUniformLocation(self.id, name.as_ptr());
-            gl::UniformMatrix4fv(
-                location,
-                matrices.len() as i32,
-                gl::FALSE,
-                matrices.as_ptr() as *const f32,
-            );
+        if let Some(location) = self.locate_uniform(name) {
+            unsafe {
+                gl::ProgramUniformMatrix4fv(
+                    self.get_id(),
+                    location,
+                    matrices.len() as i32,
+                    gl::FALSE,
+                    matrices.as_ptr() as *const f32,
+                );
+            }
+        }
+    }
+
+    /// Uploads an array of `mat2x4`, each given as its two columns one after another
+    pub fn set_uniform_mat2x4_array(&self, name: &str, matrices: &[[f32; 8]]) {
+        if let Some(location) = self.locate_uniform(name) {
+            unsafe {
+                gl::ProgramUniformMatrix2x4fv(
+                    self.get_id(),
+                    location,
+                    matrices.len() as i32,
+                    gl::FALSE,
+                    matrices.as_ptr() as *const f32,
+                );
+            }
         }
     }
 
     pub fn set_uniform_1i(&self, name: &str, value: i32) {
-        unsafe {
-            let name = CString::new(name).unwrap();
-            let location = gl::GetUniformLocation(self.id, name.as_ptr());
-            gl::Uniform1i(location, value);
+        if let Some(location) = self.prepare_uniform(name, &[value as u32]) {
+            unsafe {
+                gl::ProgramUniform1i(self.get_id(), location, value);
+            }
         }
     }
 
     pub fn set_uniform_1f(&self, name: &str, value: f32) {
-        unsafe {
-            let name = CString::new(name).unwrap();
-            let location = gl::GetUniformLocation(self.id, name.as_ptr());
-            gl::Uniform1f(location, value);
+        if let Some(location) = self.prepare_uniform(name, &[value.to_bits()]) {
+            unsafe {
+                gl::ProgramUniform1f(self.get_id(), location, value);
+            }
+        }
+    }
+
+    pub fn set_uniform_2f(&self, name: &str, float1: f32, float2: f32) {
+        if let Some(location) = self.prepare_uniform(name, &[float1.to_bits(), float2.to_bits()]) {
+            unsafe {
+                gl::ProgramUniform2f(self.get_id(), location, float1, float2);
+            }
+        }
+    }
+
+    pub fn set_uniform_1ui(&self, name: &str, value: u32) {
+        if let Some(location) = self.prepare_uniform(name, &[value]) {
+            unsafe {
+                gl::ProgramUniform1ui(self.get_id(), location, value);
+            }
+        }
+    }
+
+    pub fn set_uniform_3ui(&self, name: &str, value1: u32, value2: u32, value3: u32) {
+        if let Some(location) = self.prepare_uniform(name, &[value1, value2, value3]) {
+            unsafe {
+                gl::ProgramUniform3ui(self.get_id(), location, value1, value2, value3);
+            }
         }
     }
 
     pub fn set_uniform_3f(&self, name: &str, float1: f32, float2: f32, float3: f32) {
-        unsafe {
-            let name = CString::new(name).unwrap();
-            let location = gl::GetUniformLocation(self.id, name.as_ptr());
-            gl::Uniform3f(location, float1, float2, float3);
+        let bits = [float1.to_bits(), float2.to_bits(), float3.to_bits()];
+        if let Some(location) = self.prepare_uniform(name, &bits) {
+            unsafe {
+                gl::ProgramUniform3f(self.get_id(), location, float1, float2, float3);
+            }
         }
     }
 
     pub fn set_uniform_4f(&self, name: &str, float1: f32, float2: f32, float3: f32, float4: f32) {
-        unsafe {
-            let name = CString::new(name).unwrap();
-            let location = gl::GetUniformLocation(self.id, name.as_ptr());
-            gl::Uniform4f(location, float1, float2, float3, float4);
+        let bits = [
+            float1.to_bits(),
+            float2.to_bits(),
+            float3.to_bits(),
+            float4.to_bits(),
+        ];
+        if let Some(location) = self.prepare_uniform(name, &bits) {
+            unsafe {
+                gl::ProgramUniform4f(self.get_id(), location, float1, float2, float3, float4);
+            }
+        }
+    }
+
+    pub fn set_uniform_3f_array(&self, name: &str, vectors: &[cgmath::Vector3<f32>]) {
+        if let Some(location) = self.locate_uniform(name) {
+            unsafe {
+                gl::ProgramUniform3fv(
+                    self.get_id(),
+                    location,
+                    vectors.len() as i32,
+                    vectors.as_ptr() as *const f32,
+                );
+            }
         }
     }
 
     pub fn set_uniform_3fv(&self, name: &str, value: &cgmath::Vector3<f32>) {
-        unsafe {
-            let name = CString::new(name).unwrap();
-            let location = gl::GetUniformLocation(self.id, name.as_ptr());
-            gl::Uniform3fv(location, 1, value.as_ptr());
+        let bits = [value.x.to_bits(), value.y.to_bits(), value.z.to_bits()];
+        if let Some(location) = self.prepare_uniform(name, &bits) {
+            unsafe {
+                gl::ProgramUniform3fv(self.get_id(), location, 1, value.as_ptr());
+            }
         }
     }
 
+    /// Location of the uniform `name`, `None` if it does not exist
+    fn locate_uniform(&self, name: &str) -> Option<GLint> {
+        match self.get_uniform_location(name) {
+            Ok(location) => Some(location),
+            Err(error) => {
+                if VALIDATE_UNIFORMS.load(Ordering::Relaxed)
+                    && self.uniforms.borrow_mut().reported.insert(name.to_string())
+                {
+                    log::warn!("{}", error);
+                }
+                None
+            }
+        }
+    }
+
+    /// Location to upload `value` to, `None` if the uniform does not exist or already holds
+    /// `value`. Values are compared by their bits
+    fn prepare_uniform(&self, name: &str, value: &[u32]) -> Option<GLint> {
+        let location = self.locate_uniform(name)?;
+        let mut bits = [0; 16];
+        bits[..value.len()].copy_from_slice(value);
+        let value = UniformValue {
+            length: value.len(),
+            bits,
+        };
+        let previous = self.uniforms.borrow_mut().values.insert(location, value);
+        (previous != Some(value)).then_some(location)
+    }
+
     pub fn create_shader(vertex_shader_source: &str, fragment_shader_source: &str) -> GLuint {
+        Shader::compile_program(&[
+            (gl::VERTEX_SHADER, vertex_shader_source),
+            (gl::FRAGMENT_SHADER, fragment_shader_source),
+        ])
+        .unwrap_or_else(|error| {
+            log::error!("{}", error);
+            0
+        })
+    }
+
+    pub fn create_compute_shader(compute_shader_source: &str) -> GLuint {
+        Shader::compile_program(&[(gl::COMPUTE_SHADER, compute_shader_source)]).unwrap_or_else(
+            |error| {
+                log::error!("{}", error);
+                0
+            },
+        )
+    }
+
+    /// Compiles the given stages and links them into a program, the error holds the info log of
+    /// the step that failed
+    pub fn compile_program(stages: &[(GLenum, &str)]) -> Result<GLuint, Box<dyn Error>> {
+        unsafe {
+            let program = gl::CreateProgram();
+            let mut shaders = Vec::new();
+            let mut result = Ok(program);
+            for (kind, source) in stages {
+                match Shader::compile_stage(*kind, source) {
+                    Ok(shader) => {
+                        gl::AttachShader(program, shader);
+                        shaders.push(shader);
+                    }
+                    Err(error) => {
+                        result = Err(error);
+                        break;
+                    }
+                }
+            }
+            if result.is_ok() {
+                gl::LinkProgram(program);
+                let mut success = gl::FALSE as GLint;
+                gl::GetProgramiv(program, gl::LINK_STATUS, &mut success);
+                if success != gl::TRUE as GLint {
+                    let log = Shader::get_info_log(program, gl::GetProgramInfoLog);
+                    result = Err(format!("Linking shaders failed\n{}", log).into());
+                }
+            }
+            // the shaders are linked into the program now and no longer necessary
+            for shader in shaders {
+                gl::DeleteShader(shader);
+            }
+            if result.is_err() {
+                gl::DeleteProgram(program);
+            }
+            result
+        }
+    }
+
+    fn compile_stage(kind: GLenum, source: &str) -> Result<GLuint, Box<dyn Error>> {
+        let source = CString::new(source.as_bytes())?;
         unsafe {
-            // 1. Compile vertex shader
-            let vertex_shader = gl::CreateShader(gl::VERTEX_SHADER);
-            let c_str_vert = CString::new(vertex_shader_source.as_bytes()).unwrap();
-            gl::ShaderSource(vertex_shader, 1, &c_str_vert.as_ptr(), ptr::null());
-            gl::CompileShader(vertex_shader);
+            let shader = gl::CreateShader(kind);
+            gl::ShaderSource(shader, 1, &source.as_ptr(), ptr::null());
+            gl::CompileShader(shader);
 
-            // 2. Check for vertex shader compilation errors
             let mut success = gl::FALSE as GLint;
-            let mut info_log = Vec::with_capacity(512);
-            info_log.set_len(512 - 1); // subtract 1 to skip the trailing null character
-            gl::GetShaderiv(vertex_shader, gl::COMPILE_STATUS, &mut success);
-            if success != gl::TRUE as GLint {
-                gl::GetShaderInfoLog(
-                    vertex_shader,
-                    512,
-                    ptr::null_mut(),
-                    info_log.as_mut_ptr() as *mut GLchar,
-                );
-                println!(
-                    "Vertex Shader Compilation failed\n{}",
-                    String::from_utf8_lossy(&info_log)
-                );
+            gl::GetShaderiv(shader, gl::COMPILE_STATUS, &mut success);
+            if success == gl::TRUE as GLint {
+                return Ok(shader);
             }
+            let log = Shader::get_info_log(shader, gl::GetShaderInfoLog);
+            gl::DeleteShader(shader);
+            let stage = match kind {
+                gl::VERTEX_SHADER => "Vertex",
+                gl::FRAGMENT_SHADER => "Fragment",
+                gl::COMPUTE_SHADER => "Compute",
+                _ => "Unknown",
+            };
+            Err(format!("{} Shader Compilation failed\n{}", stage, log).into())
+        }
+    }
 
-            // 3. Compile fragment shader
-            let fragment_shader = gl::CreateShader(gl::FRAGMENT_SHADER);
-            let c_str_frag = CString::new(fragment_shader_source.as_bytes()).unwrap();
-            gl::ShaderSource(fragment_shader, 1, &c_str_frag.as_ptr(), ptr::null());
-            gl::CompileShader(fragment_shader);
-
-            // 4. Check for fragment shader compilation errors
-            gl::GetShaderiv(fragment_shader, gl::COMPILE_STATUS, &mut success);
-            if success != gl::TRUE as GLint {
-                gl::GetShaderInfoLog(
-                    fragment_shader,
-                    512,
-                    ptr::null_mut(),
-                    info_log.as_mut_ptr() as *mut GLchar,
-                );
-                println!(
-                    "Fragment Shader Compilation failed\n{}",
-                    String::from_utf8_lossy(&info_log)
-                );
+    unsafe fn get_info_log(
+        id: GLuint,
+        get_log: unsafe fn(GLuint, GLsizei, *mut GLsizei, *mut GLchar),
+    ) -> String {
+        let mut info_log = vec![0u8; 1024];
+        let mut length = 0;
+        get_log(
+            id,
+            info_log.len() as GLsizei,
+            &mut length,
+            info_log.as_mut_ptr() as *mut GLchar,
+        );
+        info_log.truncate(length.max(0) as usize);
+        String::from_utf8_lossy(&info_log).into_owned()
+    }
+}
+
+impl ShaderDefines {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the flag `name`, defined without a value
+    pub fn with(mut self, name: &str) -> Self {
+        self.set(name, true);
+        self
+    }
+
+    /// Adds `name` defined as `value`
+    pub fn with_value(mut self, name: &str, value: impl ToString) -> Self {
+        self.defines.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    /// Adds or removes the flag `name`
+    pub fn set(&mut self, name: &str, enabled: bool) {
+        if enabled {
+            self.defines.insert(name.to_string(), String::new());
+        } else {
+            self.defines.remove(name);
+        }
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.defines.contains_key(name)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.defines.is_empty()
+    }
+
+    /// Inserts the defines after the `#version` line of `source`, or at its start if it has none
+    pub fn apply(&self, source: &str) -> String {
+        if self.defines.is_empty() {
+            return source.to_string();
+        }
+        let defines: String = self
+            .defines
+            .iter()
+            .map(|(name, value)| format!("#define {} {}\n", name, value))
+            .collect();
+        match source.find("#version") {
+            Some(start) => {
+                let end = source[start..]
+                    .find('\n')
+                    .map_or(source.len(), |offset| start + offset + 1);
+                let mut result = source[..end].to_string();
+                if !result.ends_with('\n') {
+                    result.push('\n');
+                }
+                result.push_str(&defines);
+                result.push_str(&source[end..]);
+                result
             }
+            None => defines + source,
+        }
+    }
+}
 
-            // 5. Link shaders
-            let shader_program = gl::CreateProgram();
-            gl::AttachShader(shader_program, vertex_shader);
-            gl::AttachShader(shader_program, fragment_shader);
-            gl::LinkProgram(shader_program);
-
-            // 6. Check for linking errors
-            gl::GetProgramiv(shader_program, gl::LINK_STATUS, &mut success);
-            if success != gl::TRUE as GLint {
-                gl::GetProgramInfoLog(
-                    shader_program,
-                    512,
-                    ptr::null_mut(),
-                    info_log.as_mut_ptr() as *mut GLchar,
-                );
-                println!(
-                    "Linking shaders failed\n{}",
-                    String::from_utf8_lossy(&info_log)
+impl fmt::Display for ShaderDefines {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<&str> = self.defines.keys().map(String::as_str).collect();
+        write!(f, "{}", names.join(" "))
+    }
+}
+
+impl ShaderPermutations {
+    pub fn new(vertex_source: ShaderSource, fragment_source: ShaderSource) -> Self {
+        Self {
+            vertex_source,
+            fragment_source,
+            variants: RefCell::new(HashMap::new()),
+        }
+    }
+
+    /// Variant compiled with `defines`, compiled now if it was not requested before
+    pub fn get(&self, defines: &ShaderDefines) -> Ref<'_, Shader> {
+        if !self.variants.borrow().contains_key(defines) {
+            let shader =
+                Shader::load_with_defines(self.vertex_source, self.fragment_source, defines);
+            self.variants.borrow_mut().insert(defines.clone(), shader);
+        }
+        Ref::map(self.variants.borrow(), |variants| &variants[defines])
+    }
+
+    /// Number of variants compiled so far
+    pub fn get_variant_count(&self) -> usize {
+        self.variants.borrow().len()
+    }
+}
+
+impl UniformCache {
+    /// Introspects the active uniforms of `program`, arrays are also stored without their `[0]`
+    fn new(program: GLuint) -> Self {
+        let mut locations = HashMap::new();
+        unsafe {
+            let mut count = 0;
+            let mut max_length = 0;
+            gl::GetProgramiv(program, gl::ACTIVE_UNIFORMS, &mut count);
+            gl::GetProgramiv(program, gl::ACTIVE_UNIFORM_MAX_LENGTH, &mut max_length);
+            let mut name = vec![0u8; max_length.max(1) as usize];
+            for index in 0..count.max(0) as GLuint {
+                let (mut length, mut size, mut kind) = (0, 0, 0);
+                gl::GetActiveUniform(
+                    program,
+                    index,
+                    name.len() as GLsizei,
+                    &mut length,
+                    &mut size,
+                    &mut kind,
+                    name.as_mut_ptr() as *mut GLchar,
                 );
+                let uniform = String::from_utf8_lossy(&name[..length.max(0) as usize]).into_owned();
+                let Ok(c_name) = CString::new(uniform.as_str()) else {
+                    continue;
+                };
+                // members of uniform blocks have no location
+                let location = gl::GetUniformLocation(program, c_name.as_ptr());
+                if location < 0 {
+                    continue;
+                }
+                if let Some(array) = uniform.strip_suffix("[0]") {
+                    locations.insert(array.to_string(), location);
+                }
+                locations.insert(uniform, location);
             }
+        }
+        Self {
+            program,
+            locations,
+            values: HashMap::new(),
+            reported: HashSet::new(),
+        }
+    }
+}
 
-            // 7. Delete the shaders as they're linked into our program now and no longer necessary
-            gl::DeleteShader(vertex_shader);
-            gl::DeleteShader(fragment_shader);
-
-            shader_program
+impl fmt::Display for ShaderError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ShaderError::UnknownUniform { program, name } => {
+                write!(f, "Program {} has no uniform named {}", program, name)
+            }
         }
     }
 }
 
+impl Error for ShaderError {}
+
 impl<T: VertexAttributes + Clone> DynamicVertexArray<T> {
     pub fn new() -> Self {
         let mut vao = 0;
@@ -185,16 +608,21 @@ impl<T: VertexAttributes + Clone> DynamicVertexArray<T> {
             ebo,
             current_vertex_data: None,
             indices: None,
+            instance_vbo: None,
+            instance_count: 0,
+            mapped_vertices: None,
+            mapped_indices: None,
+            base_vertex: 0,
+            first_index: 0,
         }
     }
 
-    pub fn buffer_data(&mut self, data: &Vec<T>, indices: &Option<Vec<u32>>) {
-        self.bind();
+    /// Points the attributes of `A`, starting at location `first`, into the bound array buffer
+    pub fn set_attribute_pointers<A: VertexAttributes>(first: GLuint, divisor: GLuint) {
+        let mut current_attrib = first;
+        let mut offset = 0;
         unsafe {
-            gl::BindBuffer(gl::ARRAY_BUFFER, self.vbo);
-            let mut current_attrib = 0;
-            let mut offset = 0;
-            for (size, gl_type) in T::get_vertex_attributes() {
+            for (size, gl_type) in A::get_vertex_attributes() {
                 gl::EnableVertexAttribArray(current_attrib);
                 match gl_type {
                     gl::FLOAT => {
@@ -203,7 +631,7 @@ impl<T: VertexAttributes + Clone> DynamicVertexArray<T> {
                             size as i32,
                             gl::FLOAT,
                             gl::FALSE,
-                            std::mem::size_of::<T>() as i32,
+                            std::mem::size_of::<A>() as i32,
                             offset as *const _,
                         );
                         offset += size * std::mem::size_of::<f32>();
@@ -213,15 +641,24 @@ impl<T: VertexAttributes + Clone> DynamicVertexArray<T> {
                             current_attrib,
                             size as i32,
                             gl::UNSIGNED_INT,
-                            std::mem::size_of::<T>() as i32,
+                            std::mem::size_of::<A>() as i32,
                             offset as *const _,
                         );
                         offset += size * std::mem::size_of::<u32>();
                     }
                     _ => {}
                 }
+                gl::VertexAttribDivisor(current_attrib, divisor);
                 current_attrib += 1;
             }
+        }
+    }
+
+    pub fn buffer_data(&mut self, data: &[T], indices: &Option<Vec<u32>>) {
+        self.bind();
+        unsafe {
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.vbo);
+            DynamicVertexArray::<T>::set_attribute_pointers::<T>(0, 0);
             gl::BufferData(
                 gl::ARRAY_BUFFER,
                 (data.len() * std::mem::size_of::<T>()) as GLsizeiptr,
@@ -243,6 +680,190 @@ impl<T: VertexAttributes + Clone> DynamicVertexArray<T> {
         }
         self.current_vertex_data = Some(data.to_vec());
         self.indices = indices.clone();
+        self.base_vertex = 0;
+        self.first_index = 0;
+    }
+
+    /// Uploads data that changes often, like text and UI planes. It is written into persistently
+    /// mapped buffers so the upload does not wait for draws still reading the previous data, without
+    /// buffer storage support the buffers are orphaned instead. The data has to be drawn with `draw`
+    pub fn buffer_data_dyn(&mut self, data: &[T], indices: &Option<Vec<u32>>) {
+        if MappedBuffer::<T>::is_supported() {
+            let vertices = self
+                .mapped_vertices
+                .get_or_insert_with(|| MappedBuffer::new(data.len()));
+            self.base_vertex = vertices.write(data);
+            let vertex_buffer = vertices.get_id();
+            self.bind();
+            unsafe {
+                gl::BindBuffer(gl::ARRAY_BUFFER, vertex_buffer);
+                DynamicVertexArray::<T>::set_attribute_pointers::<T>(0, 0);
+                if let Some(indices) = indices {
+                    let mapped_indices = self
+                        .mapped_indices
+                        .get_or_insert_with(|| MappedBuffer::new(indices.len()));
+                    self.first_index = mapped_indices.write(indices);
+                    gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, mapped_indices.get_id());
+                }
+                gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+                gl::BindVertexArray(0);
+            }
+        } else {
+            self.orphan_data(data, indices);
+        }
+        self.current_vertex_data = Some(data.to_vec());
+        self.indices = indices.clone();
+    }
+
+    /// Replaces the storage of the buffers before writing, so the driver hands out fresh memory
+    /// instead of waiting for draws that still read the old one
+    fn orphan_data(&mut self, data: &[T], indices: &Option<Vec<u32>>) {
+        self.bind();
+        unsafe {
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.vbo);
+            DynamicVertexArray::<T>::set_attribute_pointers::<T>(0, 0);
+            gl::BufferData(
+                gl::ARRAY_BUFFER,
+                std::mem::size_of_val(data) as GLsizeiptr,
+                ptr::null(),
+                gl::STREAM_DRAW,
+            );
+            gl::BufferSubData(
+                gl::ARRAY_BUFFER,
+                0,
+                std::mem::size_of_val(data) as GLsizeiptr,
+                data.as_ptr() as *const GLvoid,
+            );
+            if let Some(indices) = indices {
+                gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, self.ebo);
+                gl::BufferData(
+                    gl::ELEMENT_ARRAY_BUFFER,
+                    std::mem::size_of_val(indices.as_slice()) as GLsizeiptr,
+                    ptr::null(),
+                    gl::STREAM_DRAW,
+                );
+                gl::BufferSubData(
+                    gl::ELEMENT_ARRAY_BUFFER,
+                    0,
+                    std::mem::size_of_val(indices.as_slice()) as GLsizeiptr,
+                    indices.as_ptr() as *const GLvoid,
+                );
+            }
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+            gl::BindVertexArray(0);
+        }
+        self.base_vertex = 0;
+        self.first_index = 0;
+    }
+
+    /// Draws the buffered data as `mode` primitives and fences the mapped buffers it read
+    pub fn draw(&self, mode: GLenum) {
+        self.bind();
+        unsafe {
+            if self.indices.is_some() {
+                gl::DrawElementsBaseVertex(
+                    mode,
+                    self.get_element_count() as i32,
+                    gl::UNSIGNED_INT,
+                    (self.first_index * std::mem::size_of::<u32>()) as *const GLvoid,
+                    self.base_vertex as i32,
+                );
+            } else {
+                gl::DrawArrays(
+                    mode,
+                    self.base_vertex as i32,
+                    self.get_element_count() as i32,
+                );
+            }
+        }
+        Profiler::count_draw_call();
+        gl_debug::check_errors("DynamicVertexArray::draw");
+        if let Some(vertices) = &self.mapped_vertices {
+            vertices.fence();
+        }
+        if let Some(indices) = &self.mapped_indices {
+            indices.fence();
+        }
+        DynamicVertexArray::<T>::unbind();
+    }
+
+    /// Uploads one `I` per instance, its attributes follow the vertex attributes of `T` and
+    /// advance once per instance instead of once per vertex
+    pub fn buffer_instance_data<I: VertexAttributes>(&mut self, instances: &[I]) {
+        self.bind();
+        unsafe {
+            let vbo = *self.instance_vbo.get_or_insert_with(|| {
+                let mut vbo = 0;
+                gl::GenBuffers(1, &mut vbo);
+                vbo
+            });
+            gl::BindBuffer(gl::ARRAY_BUFFER, vbo);
+            DynamicVertexArray::<T>::set_attribute_pointers::<I>(
+                T::get_vertex_attributes().len() as GLuint,
+                1,
+            );
+            gl::BufferData(
+                gl::ARRAY_BUFFER,
+                std::mem::size_of_val(instances) as GLsizeiptr,
+                instances.as_ptr() as *const GLvoid,
+                gl::STATIC_DRAW,
+            );
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+            gl::BindVertexArray(0);
+        }
+        self.instance_count = instances.len();
+    }
+
+    pub fn get_instance_count(&self) -> usize {
+        self.instance_count
+    }
+
+    /// Buffer holding the per-instance attributes, None before `buffer_instance_data`
+    pub fn get_instance_buffer(&self) -> Option<GLuint> {
+        self.instance_vbo
+    }
+
+    /// Draws the buffered triangles with the `draw_count` `DrawArraysIndirectCommand`s in
+    /// `indirect_buffer`, whose instance counts are usually written by a compute shader
+    pub fn draw_indirect(&self, indirect_buffer: GLuint, draw_count: usize) {
+        self.bind();
+        unsafe {
+            gl::BindBuffer(gl::DRAW_INDIRECT_BUFFER, indirect_buffer);
+            gl::MultiDrawArraysIndirect(gl::TRIANGLES, ptr::null(), draw_count as i32, 0);
+            gl::BindBuffer(gl::DRAW_INDIRECT_BUFFER, 0);
+        }
+        Profiler::count_draw_call();
+        gl_debug::check_errors("DynamicVertexArray::draw_indirect");
+        DynamicVertexArray::<T>::unbind();
+    }
+
+    /// Draws every instance of the buffered triangles in a single draw call
+    pub fn draw_instanced(&self) {
+        if self.instance_count == 0 {
+            return;
+        }
+        self.bind();
+        unsafe {
+            if self.indices.is_some() {
+                gl::DrawElementsInstanced(
+                    gl::TRIANGLES,
+                    self.get_element_count() as i32,
+                    gl::UNSIGNED_INT,
+                    std::ptr::null(),
+                    self.instance_count as i32,
+                );
+            } else {
+                gl::DrawArraysInstanced(
+                    gl::TRIANGLES,
+                    0,
+                    self.get_element_count() as i32,
+                    self.instance_count as i32,
+                );
+            }
+        }
+        Profiler::count_draw_call();
+        gl_debug::check_errors("DynamicVertexArray::draw_instanced");
+        DynamicVertexArray::<T>::unbind();
     }
     pub fn get_element_count(&self) -> usize {
         if let Some(indices) = &self.indices {
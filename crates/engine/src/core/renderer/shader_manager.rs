@@ -0,0 +1,245 @@
+use std::{
+    error::Error,
+    fs,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc, Mutex, Weak,
+    },
+    time::{Duration, Instant, SystemTime},
+};
+
+use gl::types::*;
+use lazy_static::lazy_static;
+
+use crate::core::asset::AssetManager;
+
+use super::shader::{Shader, ShaderDefines};
+
+/// Shortest time between two checks of the watched files, textures and models are checked as
+/// often as shaders
+pub const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+lazy_static! {
+    static ref SHADER_MANAGER: Mutex<ShaderManager> = Mutex::new(ShaderManager::new());
+}
+
+/// Source of one shader stage. The embedded source is always compiled into the binary, in debug
+/// builds the file at `path` inside the shader directory replaces it if it exists
+#[derive(Clone, Copy)]
+pub struct ShaderSource {
+    path: &'static str,
+    embedded: &'static str,
+    preprocess: fn(&str) -> String,
+}
+
+/// Keeps track of the shaders created with `Shader::load` and recompiles them when their files
+/// change, so shaders can be edited while the engine is running.
+///
+/// Only the stage files themselves are watched, sources pulled in while preprocessing (like
+/// `lights.glsl`) stay embedded
+pub struct ShaderManager {
+    /// Replaces the `shaders` directory of the asset search paths when set
+    directory: Option<PathBuf>,
+    shaders: Vec<WatchedShader>,
+    last_poll: Instant,
+}
+
+struct WatchedShader {
+    stages: Vec<(GLenum, ShaderSource)>,
+    defines: ShaderDefines,
+    /// Modification times of the stage files, `None` for stages without a file
+    modified: Vec<Option<SystemTime>>,
+    program: Weak<AtomicU32>,
+}
+
+impl ShaderSource {
+    /// `path` is relative to the shader directory
+    pub fn new(path: &'static str, embedded: &'static str) -> Self {
+        Self {
+            path,
+            embedded,
+            preprocess: |source| source.to_string(),
+        }
+    }
+
+    /// Applied to the source before it is compiled, e.g. to resolve includes
+    pub fn preprocess(mut self, preprocess: fn(&str) -> String) -> Self {
+        self.preprocess = preprocess;
+        self
+    }
+}
+
+impl ShaderManager {
+    fn new() -> Self {
+        Self {
+            directory: None,
+            shaders: Vec::new(),
+            last_poll: Instant::now(),
+        }
+    }
+
+    /// Whether shader files are read and watched, only in debug builds
+    pub fn is_enabled() -> bool {
+        cfg!(debug_assertions)
+    }
+
+    /// Directory the shader files are read from instead of `shaders` in the asset search paths.
+    /// Only affects shaders loaded afterwards
+    pub fn set_directory(directory: impl Into<PathBuf>) {
+        SHADER_MANAGER.lock().unwrap().directory = Some(directory.into());
+    }
+
+    /// Compiles a program from `stages`, preferring the files in the shader directory and falling
+    /// back to the embedded sources if they fail to compile. The returned id is swapped for the
+    /// new program whenever a file changes
+    pub fn load(stages: Vec<(GLenum, ShaderSource)>) -> Arc<AtomicU32> {
+        Self::load_with_defines(stages, &ShaderDefines::new())
+    }
+
+    /// Like `load`, but every stage is compiled with `defines`. Reloads keep the defines
+    pub fn load_with_defines(
+        stages: Vec<(GLenum, ShaderSource)>,
+        defines: &ShaderDefines,
+    ) -> Arc<AtomicU32> {
+        if !Self::is_enabled() {
+            return Arc::new(AtomicU32::new(Self::compile_embedded(&stages, defines)));
+        }
+        let mut manager = SHADER_MANAGER.lock().unwrap();
+        let modified = manager.get_modified(&stages);
+        let id = if modified.iter().any(Option::is_some) {
+            manager.compile(&stages, defines).unwrap_or_else(|error| {
+                log::warn!(
+                    "Loading shader {} failed, using the embedded source\n{}",
+                    Self::get_name(&stages, defines),
+                    error
+                );
+                Self::compile_embedded(&stages, defines)
+            })
+        } else {
+            Self::compile_embedded(&stages, defines)
+        };
+        let program = Arc::new(AtomicU32::new(id));
+        manager.shaders.push(WatchedShader {
+            stages,
+            defines: defines.clone(),
+            modified,
+            program: Arc::downgrade(&program),
+        });
+        program
+    }
+
+    /// Recompiles the shaders whose files changed since the last call, a shader that fails to
+    /// compile keeps its previous program. Called once per frame, does nothing in release builds
+    pub fn poll() {
+        if !Self::is_enabled() {
+            return;
+        }
+        let mut manager = SHADER_MANAGER.lock().unwrap();
+        if manager.last_poll.elapsed() < POLL_INTERVAL {
+            return;
+        }
+        manager.last_poll = Instant::now();
+        manager
+            .shaders
+            .retain(|shader| shader.program.strong_count() > 0);
+
+        let mut shaders = std::mem::take(&mut manager.shaders);
+        for shader in &mut shaders {
+            let modified = manager.get_modified(&shader.stages);
+            if modified == shader.modified {
+                continue;
+            }
+            shader.modified = modified;
+            let Some(program) = shader.program.upgrade() else {
+                continue;
+            };
+            match manager.compile(&shader.stages, &shader.defines) {
+                Ok(id) => {
+                    let previous = program.swap(id, Ordering::Relaxed);
+                    unsafe {
+                        gl::DeleteProgram(previous);
+                    }
+                    log::info!(
+                        "Reloaded shader {}",
+                        Self::get_name(&shader.stages, &shader.defines)
+                    );
+                }
+                Err(error) => log::warn!(
+                    "Reloading shader {} failed, keeping the previous version\n{}",
+                    Self::get_name(&shader.stages, &shader.defines),
+                    error
+                ),
+            }
+        }
+        manager.shaders = shaders;
+    }
+
+    /// Compiles the stages from their files, stages without a file use the embedded source
+    fn compile(
+        &self,
+        stages: &[(GLenum, ShaderSource)],
+        defines: &ShaderDefines,
+    ) -> Result<GLuint, Box<dyn Error>> {
+        let sources: Vec<(GLenum, String)> = stages
+            .iter()
+            .map(|(kind, source)| {
+                let text = fs::read_to_string(self.get_file(source.path))
+                    .unwrap_or_else(|_| source.embedded.to_string());
+                (*kind, defines.apply(&(source.preprocess)(&text)))
+            })
+            .collect();
+        Self::compile_sources(&sources)
+    }
+
+    fn compile_embedded(stages: &[(GLenum, ShaderSource)], defines: &ShaderDefines) -> GLuint {
+        let sources: Vec<(GLenum, String)> = stages
+            .iter()
+            .map(|(kind, source)| (*kind, defines.apply(&(source.preprocess)(source.embedded))))
+            .collect();
+        Self::compile_sources(&sources).unwrap_or_else(|error| {
+            log::error!("{}", error);
+            0
+        })
+    }
+
+    fn compile_sources(sources: &[(GLenum, String)]) -> Result<GLuint, Box<dyn Error>> {
+        let sources: Vec<(GLenum, &str)> = sources
+            .iter()
+            .map(|(kind, source)| (*kind, source.as_str()))
+            .collect();
+        Shader::compile_program(&sources)
+    }
+
+    fn get_modified(&self, stages: &[(GLenum, ShaderSource)]) -> Vec<Option<SystemTime>> {
+        stages
+            .iter()
+            .map(|(_, source)| {
+                fs::metadata(self.get_file(source.path))
+                    .and_then(|metadata| metadata.modified())
+                    .ok()
+            })
+            .collect()
+    }
+
+    /// File of the stage file `path` in the shader directory
+    fn get_file(&self, path: &str) -> PathBuf {
+        match &self.directory {
+            Some(directory) => directory.join(path),
+            None => AssetManager::resolve(&format!("shaders/{}", path)),
+        }
+    }
+
+    fn get_name(stages: &[(GLenum, ShaderSource)], defines: &ShaderDefines) -> String {
+        let name = stages
+            .iter()
+            .map(|(_, source)| source.path)
+            .collect::<Vec<_>>()
+            .join(", ");
+        if defines.is_empty() {
+            name
+        } else {
+            format!("{} [{}]", name, defines)
+        }
+    }
+}
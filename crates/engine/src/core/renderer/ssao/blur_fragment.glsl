@@ -0,0 +1,18 @@
+#version 460 core
+in vec2 texCoord;
+
+out vec4 FragColor;
+
+uniform sampler2D ambientOcclusion;
+
+// averages the 4x4 block the kernel rotation repeats over
+void main() {
+    vec2 texelSize = 1.0 / vec2(textureSize(ambientOcclusion, 0));
+    float result = 0.0;
+    for (int x = -2; x < 2; x++) {
+        for (int y = -2; y < 2; y++) {
+            result += texture(ambientOcclusion, texCoord + vec2(x, y) * texelSize).r;
+        }
+    }
+    FragColor = vec4(vec3(result / 16.0), 1.0);
+}
@@ -0,0 +1,55 @@
+#version 460 core
+in vec2 texCoord;
+
+out vec4 FragColor;
+
+uniform sampler2D normalTexture;
+uniform sampler2D depthTexture;
+uniform mat4 projection;
+uniform mat4 inverseProjection;
+uniform mat4 view;
+uniform vec3 kernel[64];
+uniform int kernelSize;
+uniform float radius;
+uniform float bias;
+uniform float intensity;
+
+vec3 viewPosition(vec2 uv) {
+    float depth = texture(depthTexture, uv).r;
+    vec4 position = inverseProjection * vec4(vec3(uv, depth) * 2.0 - 1.0, 1.0);
+    return position.xyz / position.w;
+}
+
+void main() {
+    if (texture(depthTexture, texCoord).r >= 1.0) {
+        FragColor = vec4(1.0);
+        return;
+    }
+    vec3 position = viewPosition(texCoord);
+    vec3 normal = normalize(mat3(view) * texture(normalTexture, texCoord).xyz);
+
+    // rotate the kernel per pixel in a 4x4 pattern the blur removes again
+    ivec2 pixel = ivec2(gl_FragCoord.xy) % 4;
+    float angle = float(pixel.x * 4 + pixel.y) * 2.39996;
+    vec3 random = vec3(cos(angle), sin(angle), 0.0);
+    vec3 tangent = normalize(random - normal * dot(random, normal));
+    vec3 bitangent = cross(normal, tangent);
+    mat3 tbn = mat3(tangent, bitangent, normal);
+
+    float occlusion = 0.0;
+    int count = clamp(kernelSize, 1, 64);
+    for (int i = 0; i < count; i++) {
+        vec3 samplePosition = position + tbn * kernel[i] * radius;
+        vec4 offset = projection * vec4(samplePosition, 1.0);
+        vec2 uv = offset.xy / offset.w * 0.5 + 0.5;
+        if (uv.x < 0.0 || uv.x > 1.0 || uv.y < 0.0 || uv.y > 1.0) {
+            continue;
+        }
+        float sampleDepth = viewPosition(uv).z;
+        // occluders far in front of the pixel belong to other objects
+        float rangeCheck = smoothstep(0.0, 1.0, radius / abs(position.z - sampleDepth));
+        occlusion += (sampleDepth >= samplePosition.z + bias ? 1.0 : 0.0) * rangeCheck;
+    }
+    float ambientOcclusion = pow(1.0 - occlusion / float(count), intensity);
+    FragColor = vec4(vec3(ambientOcclusion), 1.0);
+}
@@ -0,0 +1,42 @@
+use cgmath::Vector3;
+use serde::{Deserialize, Serialize};
+
+use super::{
+    framebuffer::FloatFrameBuffer, screen_quad::ScreenQuad, shader::Shader, texture::Texture,
+};
+
+mod ssao;
+
+/// Texture unit the ambient occlusion is bound to, read through `screenAmbientOcclusion` from `lights.glsl`
+pub const AMBIENT_OCCLUSION_UNIT: u32 = 13;
+
+/// Screen space ambient occlusion settings, stored in the `EngineConfig`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SsaoSettings {
+    pub enabled: bool,
+    /// Samples in the hemisphere around each pixel, at most 64
+    pub samples: u32,
+    /// View space radius of the hemisphere in world units
+    pub radius: f32,
+    /// Depth difference below which samples don't occlude, hides acne on flat surfaces
+    pub bias: f32,
+    /// Exponent applied to the occlusion, higher values darken it
+    pub intensity: f32,
+    /// Blurs away the noise pattern of the rotated kernel
+    pub blur: bool,
+}
+
+/// Computes the ambient occlusion from the normals and depth of the G-buffer pass and binds it to
+/// `AMBIENT_OCCLUSION_UNIT`, where terrain and models darken their ambient light with it.
+/// While disabled a white texture is bound instead
+pub struct SsaoPass {
+    shader: Shader,
+    blur_shader: Shader,
+    screen_quad: ScreenQuad,
+    /// Offsets in the hemisphere around +z, closer to the center the earlier they come
+    kernel: Vec<Vector3<f32>>,
+    white: Texture,
+    target: Option<FloatFrameBuffer>,
+    blur_target: Option<FloatFrameBuffer>,
+}
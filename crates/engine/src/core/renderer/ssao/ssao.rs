@@ -0,0 +1,204 @@
+use cgmath::{InnerSpace, SquareMatrix, Vector3};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::core::{
+    entity::component::camera_component::CameraComponent,
+    renderer::{
+        framebuffer::FloatFrameBuffer,
+        render_graph::{
+            RenderPass, RenderResources, AMBIENT_OCCLUSION, GBUFFER_DEPTH, GBUFFER_NORMALS,
+            SSAO_PASS_ORDER,
+        },
+        screen_quad::ScreenQuad,
+        shader::Shader,
+        shader_manager::ShaderSource,
+        texture::Texture,
+    },
+    scene::Scene,
+    window::Window,
+};
+
+use super::{SsaoPass, SsaoSettings, AMBIENT_OCCLUSION_UNIT};
+
+const MAX_SAMPLES: u32 = 64;
+
+impl Default for SsaoSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            samples: 16,
+            radius: 0.5,
+            bias: 0.025,
+            intensity: 1.5,
+            blur: true,
+        }
+    }
+}
+
+impl SsaoPass {
+    pub fn new() -> Self {
+        let white = Texture::new();
+        white.load_from_data(1, 1, vec![255; 4]);
+        Self {
+            shader: Shader::load(
+                ScreenQuad::get_vertex_shader(),
+                ShaderSource::new("renderer/ssao/fragment.glsl", include_str!("fragment.glsl")),
+            ),
+            blur_shader: Shader::load(
+                ScreenQuad::get_vertex_shader(),
+                ShaderSource::new(
+                    "renderer/ssao/blur_fragment.glsl",
+                    include_str!("blur_fragment.glsl"),
+                ),
+            ),
+            screen_quad: ScreenQuad::new(),
+            kernel: Vec::new(),
+            white,
+            target: None,
+            blur_target: None,
+        }
+    }
+
+    /// Random offsets in the hemisphere around +z, denser towards the center
+    fn generate_kernel(samples: u32) -> Vec<Vector3<f32>> {
+        let mut rng = StdRng::seed_from_u64(0);
+        (0..samples)
+            .map(|i| {
+                let direction = Vector3::new(
+                    rng.gen_range(-1.0..1.0),
+                    rng.gen_range(-1.0..1.0),
+                    rng.gen_range(0.0..1.0),
+                )
+                .normalize();
+                let scale = i as f32 / samples as f32;
+                direction * rng.gen_range(0.0..1.0) * (0.1 + 0.9 * scale * scale)
+            })
+            .collect()
+    }
+
+    fn bind_ambient_occlusion(texture_id: u32) {
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0 + AMBIENT_OCCLUSION_UNIT);
+            gl::BindTexture(gl::TEXTURE_2D, texture_id);
+            gl::ActiveTexture(gl::TEXTURE0);
+        }
+    }
+
+    fn compute_ambient_occlusion(
+        &mut self,
+        settings: &SsaoSettings,
+        camera: &CameraComponent,
+        normals: u32,
+        depth: u32,
+        width: u32,
+        height: u32,
+    ) -> Option<()> {
+        let samples = settings.samples.clamp(1, MAX_SAMPLES);
+        if self.kernel.len() != samples as usize {
+            self.kernel = SsaoPass::generate_kernel(samples);
+        }
+        let projection = camera.get_projection().get_matrix();
+        let inverse_projection = projection.invert()?;
+
+        let target = FloatFrameBuffer::reuse(self.target.take(), width, height);
+        target.bind();
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0);
+            gl::BindTexture(gl::TEXTURE_2D, normals);
+            gl::ActiveTexture(gl::TEXTURE1);
+            gl::BindTexture(gl::TEXTURE_2D, depth);
+            gl::ActiveTexture(gl::TEXTURE0);
+        }
+        self.shader.bind();
+        self.shader.set_uniform_1i("normalTexture", 0);
+        self.shader.set_uniform_1i("depthTexture", 1);
+        self.shader.set_uniform_mat4("projection", &projection);
+        self.shader
+            .set_uniform_mat4("inverseProjection", &inverse_projection);
+        self.shader
+            .set_uniform_mat4("view", &camera.get_camera().get_matrix());
+        self.shader.set_uniform_3f_array("kernel", &self.kernel);
+        self.shader.set_uniform_1i("kernelSize", samples as i32);
+        self.shader.set_uniform_1f("radius", settings.radius);
+        self.shader.set_uniform_1f("bias", settings.bias);
+        self.shader.set_uniform_1f("intensity", settings.intensity);
+        self.screen_quad.render(&self.shader);
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE1);
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+            gl::ActiveTexture(gl::TEXTURE0);
+        }
+        let ambient_occlusion = target.get_color_texture()?.id;
+        self.target = Some(target);
+
+        if settings.blur {
+            let blur_target = FloatFrameBuffer::reuse(self.blur_target.take(), width, height);
+            blur_target.bind();
+            unsafe {
+                gl::BindTexture(gl::TEXTURE_2D, ambient_occlusion);
+            }
+            self.blur_shader.bind();
+            self.blur_shader.set_uniform_1i("ambientOcclusion", 0);
+            self.screen_quad.render(&self.blur_shader);
+            self.blur_target = Some(blur_target);
+        }
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+        }
+        Some(())
+    }
+}
+
+impl Default for SsaoPass {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RenderPass for SsaoPass {
+    fn get_name(&self) -> &str {
+        "SSAO"
+    }
+
+    fn get_order(&self) -> i32 {
+        SSAO_PASS_ORDER
+    }
+
+    fn render(&mut self, scene: &Scene, _: &Window, resources: &mut RenderResources) {
+        // last frame's occlusion is still bound, it must not be sampled while drawing into it again
+        SsaoPass::bind_ambient_occlusion(self.white.id);
+        let settings = &scene.config.ssao;
+        if !settings.enabled {
+            return;
+        }
+        let Some(camera) = scene.get_component::<CameraComponent>() else {
+            return;
+        };
+        let (Some(normals), Some(depth)) = (
+            resources.get_texture(GBUFFER_NORMALS),
+            resources.get_texture(GBUFFER_DEPTH),
+        ) else {
+            return;
+        };
+        let (width, height) = resources.get_size();
+
+        if self
+            .compute_ambient_occlusion(settings, camera, normals, depth, width, height)
+            .is_some()
+        {
+            let output = if settings.blur {
+                &self.blur_target
+            } else {
+                &self.target
+            };
+            if let Some(texture) = output
+                .as_ref()
+                .and_then(|target| target.get_color_texture())
+            {
+                resources.set_texture(AMBIENT_OCCLUSION, texture);
+                SsaoPass::bind_ambient_occlusion(texture.id);
+            }
+        }
+        resources.bind_target();
+    }
+}
@@ -0,0 +1,93 @@
+#version 460 core
+in vec2 texCoord;
+
+out vec4 FragColor;
+
+uniform sampler2D colorTexture;
+uniform sampler2D depthTexture;
+uniform sampler2D normalTexture;
+uniform sampler2D materialTexture;
+uniform mat4 projection;
+uniform mat4 inverseProjection;
+uniform mat4 view;
+uniform float maxRoughness;
+uniform float maxDistance;
+uniform int steps;
+uniform float thickness;
+
+#include "lights.glsl"
+
+const int REFINE_STEPS = 6;
+
+vec3 viewPosition(vec2 uv) {
+    float depth = texture(depthTexture, uv).r;
+    vec4 position = inverseProjection * vec4(vec3(uv, depth) * 2.0 - 1.0, 1.0);
+    return position.xyz / position.w;
+}
+
+vec2 project(vec3 position) {
+    vec4 clip = projection * vec4(position, 1.0);
+    return clip.xy / clip.w * 0.5 + 0.5;
+}
+
+bool onScreen(vec2 uv) {
+    return uv.x >= 0.0 && uv.x <= 1.0 && uv.y >= 0.0 && uv.y <= 1.0;
+}
+
+void main() {
+    vec4 color = texture(colorTexture, texCoord);
+    vec4 material = texture(materialTexture, texCoord);
+    float roughness = material.x;
+    if (texture(depthTexture, texCoord).r >= 1.0 || roughness > maxRoughness) {
+        FragColor = color;
+        return;
+    }
+    vec3 position = viewPosition(texCoord);
+    vec3 normal = normalize(mat3(view) * texture(normalTexture, texCoord).xyz);
+    vec3 viewDirection = normalize(position);
+    vec3 reflected = normalize(reflect(viewDirection, normal));
+    float fresnel = 0.02 + 0.98 * pow(1.0 - max(dot(-viewDirection, normal), 0.0), 5.0);
+    // rougher surfaces fade out towards the threshold instead of popping
+    float strength = fresnel * (1.0 - smoothstep(maxRoughness * 0.5, maxRoughness, roughness));
+
+    vec3 hitColor = vec3(0.0);
+    float hit = 0.0;
+    int count = max(steps, 1);
+    float stepLength = maxDistance / float(count);
+    for (int i = 1; i <= count; i++) {
+        vec3 rayPosition = position + reflected * stepLength * float(i);
+        if (rayPosition.z >= 0.0) {
+            break;
+        }
+        vec2 uv = project(rayPosition);
+        if (!onScreen(uv)) {
+            break;
+        }
+        float difference = viewPosition(uv).z - rayPosition.z;
+        if (difference > 0.0 && difference < thickness) {
+            // narrow the hit down between the last two steps
+            vec3 start = rayPosition - reflected * stepLength;
+            vec3 end = rayPosition;
+            for (int j = 0; j < REFINE_STEPS; j++) {
+                vec3 middle = (start + end) * 0.5;
+                if (viewPosition(project(middle)).z - middle.z > 0.0) {
+                    end = middle;
+                } else {
+                    start = middle;
+                }
+            }
+            uv = project(end);
+            // fade out towards the screen edges and the end of the ray
+            vec2 edge = smoothstep(0.0, 0.1, uv) * (1.0 - smoothstep(0.9, 1.0, uv));
+            hit = edge.x * edge.y * (1.0 - float(i) / float(count));
+            hitColor = texture(colorTexture, uv).rgb;
+            break;
+        }
+    }
+
+    vec3 probe = skyReflection(transpose(mat3(view)) * reflected, roughness);
+    // surfaces reflecting the sky probe in their own shader only get the screen space hits
+    vec3 missed = material.y > 0.5 ? color.rgb : mix(color.rgb, probe, strength);
+    vec3 reflection = mix(color.rgb, hitColor, strength);
+    FragColor = vec4(mix(missed, reflection, hit), color.a);
+}
@@ -0,0 +1,28 @@
+use serde::{Deserialize, Serialize};
+
+use super::{framebuffer::ColorFrameBuffer, screen_quad::ScreenQuad, shader::Shader};
+
+mod ssr;
+
+/// Screen space reflection settings, stored in the `EngineConfig`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SsrSettings {
+    pub enabled: bool,
+    /// Surfaces rougher than this don't reflect, smoother ones fade in towards 0
+    pub max_roughness: f32,
+    /// View space distance a reflected ray travels before it gives up
+    pub max_distance: f32,
+    /// Steps along the ray, before the hit is refined
+    pub steps: u32,
+    /// How far behind a surface the ray may be and still count as hitting it
+    pub thickness: f32,
+}
+
+/// Reflects the frame in smooth surfaces by marching the reflected rays through the G-buffer depth,
+/// rays leaving the screen or missing everything reflect the sky probe instead
+pub struct SsrPass {
+    shader: Shader,
+    screen_quad: ScreenQuad,
+    target: Option<ColorFrameBuffer>,
+}
@@ -0,0 +1,127 @@
+use cgmath::SquareMatrix;
+
+use crate::core::{
+    entity::component::camera_component::CameraComponent,
+    renderer::{
+        framebuffer::ColorFrameBuffer,
+        light::light_pass::include_lights,
+        render_graph::{
+            RenderPass, RenderResources, FRAME_COLOR, GBUFFER_DEPTH, GBUFFER_MATERIAL,
+            GBUFFER_NORMALS, POST_PROCESS_PASS_ORDER,
+        },
+        screen_quad::ScreenQuad,
+        shader::Shader,
+        shader_manager::ShaderSource,
+    },
+    scene::Scene,
+    window::Window,
+};
+
+use super::{SsrPass, SsrSettings};
+
+impl Default for SsrSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            max_roughness: 0.3,
+            max_distance: 30.0,
+            steps: 48,
+            thickness: 0.5,
+        }
+    }
+}
+
+impl SsrPass {
+    pub fn new() -> Self {
+        Self {
+            shader: Shader::load(
+                ScreenQuad::get_vertex_shader(),
+                ShaderSource::new("renderer/ssr/fragment.glsl", include_str!("fragment.glsl"))
+                    .preprocess(include_lights),
+            ),
+            screen_quad: ScreenQuad::new(),
+            target: None,
+        }
+    }
+}
+
+impl Default for SsrPass {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RenderPass for SsrPass {
+    fn get_name(&self) -> &str {
+        "SSR"
+    }
+
+    fn get_order(&self) -> i32 {
+        POST_PROCESS_PASS_ORDER
+    }
+
+    fn render(&mut self, scene: &Scene, _: &Window, resources: &mut RenderResources) {
+        let settings = &scene.config.ssr;
+        if !settings.enabled {
+            return;
+        }
+        let Some(camera) = scene.get_component::<CameraComponent>() else {
+            return;
+        };
+        let textures = [
+            FRAME_COLOR,
+            GBUFFER_DEPTH,
+            GBUFFER_NORMALS,
+            GBUFFER_MATERIAL,
+        ]
+        .map(|name| resources.get_texture(name));
+        let [Some(color), Some(depth), Some(normals), Some(material)] = textures else {
+            return;
+        };
+        let projection = camera.get_projection().get_matrix();
+        let Some(inverse_projection) = projection.invert() else {
+            return;
+        };
+
+        let (width, height) = resources.get_size();
+        let target = ColorFrameBuffer::reuse_hdr(self.target.take(), width, height);
+        target.bind();
+        unsafe {
+            for (unit, texture) in [color, depth, normals, material].into_iter().enumerate() {
+                gl::ActiveTexture(gl::TEXTURE0 + unit as u32);
+                gl::BindTexture(gl::TEXTURE_2D, texture);
+            }
+            gl::ActiveTexture(gl::TEXTURE0);
+        }
+        self.shader.bind();
+        self.shader.set_uniform_1i("colorTexture", 0);
+        self.shader.set_uniform_1i("depthTexture", 1);
+        self.shader.set_uniform_1i("normalTexture", 2);
+        self.shader.set_uniform_1i("materialTexture", 3);
+        self.shader.set_uniform_mat4("projection", &projection);
+        self.shader
+            .set_uniform_mat4("inverseProjection", &inverse_projection);
+        self.shader
+            .set_uniform_mat4("view", &camera.get_camera().get_matrix());
+        self.shader
+            .set_uniform_1f("maxRoughness", settings.max_roughness);
+        self.shader
+            .set_uniform_1f("maxDistance", settings.max_distance);
+        self.shader
+            .set_uniform_1i("steps", settings.steps.max(1) as i32);
+        self.shader.set_uniform_1f("thickness", settings.thickness);
+        self.screen_quad.render(&self.shader);
+        unsafe {
+            for unit in (0..4).rev() {
+                gl::ActiveTexture(gl::TEXTURE0 + unit);
+                gl::BindTexture(gl::TEXTURE_2D, 0);
+            }
+        }
+
+        if let Some(texture) = target.get_color_texture() {
+            resources.set_texture(FRAME_COLOR, texture);
+        }
+        self.target = Some(target);
+        resources.bind_target();
+    }
+}
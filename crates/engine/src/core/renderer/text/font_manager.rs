@@ -0,0 +1,154 @@
+use std::{collections::HashMap, error::Error, path::Path, sync::Mutex};
+
+use lazy_static::lazy_static;
+
+use crate::core::asset::AssetManager;
+
+use super::{FontId, FontManager, Fonts};
+
+/// Family and weight the built in font is registered with
+const ROBOTO_MONO: (&str, u16) = ("Roboto Mono", 400);
+
+lazy_static! {
+    static ref FONT_MANAGER: Mutex<FontManager> = Mutex::new(FontManager::new());
+}
+
+impl FontManager {
+    fn new() -> Self {
+        let mut manager = Self {
+            fonts: Vec::new(),
+            families: HashMap::new(),
+        };
+        let roboto_mono = rusttype::Font::try_from_bytes(include_bytes!("RobotoMono.ttf"))
+            .expect("the built in font is valid");
+        manager.add(ROBOTO_MONO.0, ROBOTO_MONO.1, roboto_mono);
+        manager
+    }
+
+    fn add(&mut self, family: &str, weight: u16, font: rusttype::Font<'static>) -> FontId {
+        let id = FontId(self.fonts.len());
+        self.fonts.push(font);
+        let weights = self.families.entry(family.to_owned()).or_default();
+        weights.retain(|(existing, _)| *existing != weight);
+        weights.push((weight, id));
+        id
+    }
+
+    /// Loads the TrueType or OpenType font at `path`, looked up like other assets, as the
+    /// `weight` of `family`, replacing a font loaded for the same family and weight before
+    pub fn load(family: &str, weight: u16, path: &str) -> Result<Fonts, Box<dyn Error>> {
+        FontManager::load_from_bytes(family, weight, AssetManager::read(path)?)
+            .map_err(|err| format!("Failed to load font {}: {}", path, err).into())
+    }
+
+    pub fn load_from_bytes(
+        family: &str,
+        weight: u16,
+        data: Vec<u8>,
+    ) -> Result<Fonts, Box<dyn Error>> {
+        let font = rusttype::Font::try_from_vec(data).ok_or("not a TrueType or OpenType font")?;
+        let id = FONT_MANAGER.lock().unwrap().add(family, weight, font);
+        Ok(Fonts::from_id(id))
+    }
+
+    /// Loads every `.ttf` and `.otf` file in the directory `path`, looked up like other assets.
+    /// The family and weight are taken from the file name, e.g. `Inter-SemiBold.ttf` is the 600
+    /// weight of `Inter`
+    pub fn load_directory(path: &str) -> Result<Vec<Fonts>, Box<dyn Error>> {
+        let mut fonts = Vec::new();
+        for entry in std::fs::read_dir(AssetManager::resolve(path))? {
+            let path = entry?.path();
+            let extension = path.extension().and_then(|e| e.to_str());
+            if !matches!(extension, Some("ttf" | "otf")) {
+                continue;
+            }
+            let (family, weight) = FontManager::parse_file_name(&path);
+            fonts.push(FontManager::load(&family, weight, &path.to_string_lossy())?);
+        }
+        Ok(fonts)
+    }
+
+    /// Family and weight of a font file named `Family-Weight`, regular if the weight is missing
+    fn parse_file_name(path: &Path) -> (String, u16) {
+        let stem = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let Some((family, style)) = stem.rsplit_once('-') else {
+            return (stem, 400);
+        };
+        let weight = match style.to_lowercase().trim_end_matches("italic") {
+            "thin" | "hairline" => 100,
+            "extralight" | "ultralight" => 200,
+            "light" => 300,
+            "" | "regular" | "book" => 400,
+            "medium" => 500,
+            "semibold" | "demibold" => 600,
+            "bold" => 700,
+            "extrabold" | "ultrabold" => 800,
+            "black" | "heavy" => 900,
+            _ => return (stem, 400),
+        };
+        (family.to_owned(), weight)
+    }
+
+    /// Font of `family` whose weight is closest to `weight`, None if no font of the family was
+    /// loaded
+    pub fn get_font(family: &str, weight: u16) -> Option<Fonts> {
+        let manager = FONT_MANAGER.lock().unwrap();
+        manager
+            .families
+            .get(family)?
+            .iter()
+            .min_by_key(|(existing, _)| existing.abs_diff(weight))
+            .map(|(_, id)| Fonts::from_id(*id))
+    }
+
+    /// Names of the loaded font families
+    pub fn get_families() -> Vec<String> {
+        let manager = FONT_MANAGER.lock().unwrap();
+        let mut families: Vec<String> = manager.families.keys().cloned().collect();
+        families.sort();
+        families
+    }
+
+    /// The font to draw `c` of a text in `font` with, the first loaded font that has a glyph for
+    /// it if `font` has none
+    pub(super) fn get_for_char(font: Fonts, c: char) -> (Fonts, rusttype::Font<'static>) {
+        let manager = FONT_MANAGER.lock().unwrap();
+        let preferred = &manager.fonts[font.get_id().0];
+        if preferred.glyph(c).id().0 != 0 {
+            return (font, preferred.clone());
+        }
+        manager
+            .fonts
+            .iter()
+            .enumerate()
+            .find(|(_, fallback)| fallback.glyph(c).id().0 != 0)
+            .map_or((font, preferred.clone()), |(id, fallback)| {
+                (Fonts::from_id(FontId(id)), fallback.clone())
+            })
+    }
+
+    /// The rusttype font of `font`, cheap to clone as the font data is shared
+    pub(super) fn get(font: Fonts) -> rusttype::Font<'static> {
+        let manager = FONT_MANAGER.lock().unwrap();
+        manager.fonts[font.get_id().0].clone()
+    }
+}
+
+impl Fonts {
+    fn from_id(id: FontId) -> Self {
+        match id {
+            FontId(0) => Fonts::RobotoMono,
+            id => Fonts::Custom(id),
+        }
+    }
+
+    pub(super) fn get_id(&self) -> FontId {
+        match self {
+            Fonts::RobotoMono => FontId(0),
+            Fonts::Custom(id) => *id,
+        }
+    }
+}
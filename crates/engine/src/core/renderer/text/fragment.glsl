@@ -8,5 +8,5 @@ in vec4 v_color;
 out vec4 f_color;
 
 void main() {
-    f_color = v_color * vec4(1.0, 1.0, 1.0, texture(texture0, v_tex_coords).r);
+    f_color = v_color * vec4(1.0, 1.0, 1.0, texture(texture0, v_tex_coords / vec2(textureSize(texture0, 0))).r);
 }
\ No newline at end of file
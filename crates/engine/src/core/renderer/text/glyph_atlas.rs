@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+
+use rusttype::{point, PositionedGlyph, Rect};
+
+use super::{AtlasGlyph, FontManager, Fonts, GlyphAtlas, GlyphKey, Texture};
+
+const INITIAL_SIZE: u32 = 512;
+/// The atlas is cleared instead of growing beyond this size
+const MAX_SIZE: u32 = 4096;
+/// Empty pixels around every glyph so linear filtering does not bleed into its neighbours
+const PADDING: u32 = 1;
+
+impl GlyphAtlas {
+    pub(super) fn new() -> Self {
+        Self {
+            texture: Texture::new(INITIAL_SIZE as i32, INITIAL_SIZE as i32),
+            size: INITIAL_SIZE,
+            glyphs: HashMap::new(),
+            cursor: (0, 0),
+            row_height: 0,
+            generation: 0,
+        }
+    }
+
+    /// Places of `glyphs` in the atlas, rasterizing the ones that are not in it yet.
+    /// If the atlas has to be cleared on the way, the glyphs are placed again so all of them
+    /// belong to the current generation
+    pub(super) fn get_glyphs(
+        &mut self,
+        glyphs: &[(Fonts, PositionedGlyph<'static>)],
+    ) -> Vec<Option<AtlasGlyph>> {
+        let generation = self.generation;
+        let placed = self.place_glyphs(glyphs);
+        if self.generation == generation {
+            return placed;
+        }
+        // glyphs that don't fit into an empty atlas are left out
+        self.place_glyphs(glyphs)
+    }
+
+    fn place_glyphs(
+        &mut self,
+        glyphs: &[(Fonts, PositionedGlyph<'static>)],
+    ) -> Vec<Option<AtlasGlyph>> {
+        glyphs
+            .iter()
+            .map(|(font, glyph)| {
+                let scale = glyph.scale();
+                let key = GlyphKey {
+                    font: font.get_id(),
+                    glyph: glyph.id().0,
+                    scale: (scale.x.to_bits(), scale.y.to_bits()),
+                };
+                if let Some(placed) = self.glyphs.get(&key) {
+                    return *placed;
+                }
+                let glyph = FontManager::get(*font)
+                    .glyph(glyph.id())
+                    .scaled(scale)
+                    .positioned(point(0.0, 0.0));
+                let placed = self.rasterize(&glyph);
+                self.glyphs.insert(key, placed);
+                placed
+            })
+            .collect()
+    }
+
+    fn rasterize(&mut self, glyph: &PositionedGlyph<'static>) -> Option<AtlasGlyph> {
+        let bounds = glyph.pixel_bounding_box()?;
+        let (width, height) = (bounds.width() as u32, bounds.height() as u32);
+        let (x, y) = self.allocate(width, height)?;
+        let mut pixels = vec![0u8; (width * height) as usize];
+        glyph.draw(|px, py, coverage| {
+            pixels[(py * width + px) as usize] = (coverage * 255.0) as u8;
+        });
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D, self.texture.id);
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+            gl::TexSubImage2D(
+                gl::TEXTURE_2D,
+                0,
+                x as i32,
+                y as i32,
+                width as i32,
+                height as i32,
+                gl::RED,
+                gl::UNSIGNED_BYTE,
+                pixels.as_ptr() as *const std::ffi::c_void,
+            );
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 4);
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+        }
+        Some(AtlasGlyph {
+            rect: Rect {
+                min: point(x, y),
+                max: point(x + width, y + height),
+            },
+        })
+    }
+
+    /// Finds room for a `width` by `height` glyph in the rows of the atlas, growing or clearing
+    /// the atlas if there is none. None if the glyph is larger than the atlas can get
+    fn allocate(&mut self, width: u32, height: u32) -> Option<(u32, u32)> {
+        let (padded_width, padded_height) = (width + PADDING, height + PADDING);
+        if padded_width > MAX_SIZE || padded_height > MAX_SIZE {
+            return None;
+        }
+        loop {
+            if self.cursor.0 + padded_width > self.size {
+                // start a new row below the current one
+                self.cursor = (0, self.cursor.1 + self.row_height);
+                self.row_height = 0;
+            }
+            if self.cursor.1 + padded_height <= self.size {
+                let position = self.cursor;
+                self.cursor.0 += padded_width;
+                self.row_height = self.row_height.max(padded_height);
+                return Some(position);
+            }
+            if self.size < MAX_SIZE {
+                self.grow();
+            } else {
+                self.clear();
+            }
+        }
+    }
+
+    /// Doubles the size of the atlas, the glyphs keep their pixel coordinates
+    fn grow(&mut self) {
+        let size = self.size * 2;
+        let texture = Texture::new(size as i32, size as i32);
+        unsafe {
+            gl::CopyImageSubData(
+                self.texture.id,
+                gl::TEXTURE_2D,
+                0,
+                0,
+                0,
+                0,
+                texture.id,
+                gl::TEXTURE_2D,
+                0,
+                0,
+                0,
+                0,
+                self.size as i32,
+                self.size as i32,
+                1,
+            );
+        }
+        log::debug!("Glyph atlas grew to {}x{}", size, size);
+        self.texture = texture;
+        self.size = size;
+    }
+
+    /// Removes all glyphs, texts rebuild their meshes for the new generation
+    fn clear(&mut self) {
+        log::debug!("Glyph atlas is full, clearing it");
+        unsafe {
+            gl::ClearTexImage(
+                self.texture.id,
+                0,
+                gl::RED,
+                gl::UNSIGNED_BYTE,
+                std::ptr::null(),
+            );
+        }
+        self.glyphs.clear();
+        self.cursor = (0, 0);
+        self.row_height = 0;
+        self.generation += 1;
+    }
+
+    pub(super) fn get_generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub(super) fn bind(&self) {
+        self.texture.bind();
+    }
+}
@@ -1,42 +1,118 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+};
+
 use gl::types::GLuint;
-use rusttype::{gpu_cache::Cache, PositionedGlyph};
+use rusttype::{PositionedGlyph, Rect};
 
 use crate::core::renderer::shader::Shader;
 
 use super::shader::DynamicVertexArray;
 
+mod font_manager;
+mod glyph_atlas;
 pub mod text;
 
-pub struct Font {
-    font: rusttype::Font<'static>,
-}
-
+/// Font of a text, the built in Roboto Mono or one loaded by the `FontManager`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum Fonts {
     RobotoMono,
+    Custom(FontId),
+}
+
+/// Horizontal alignment of the lines of a text within its maximum width, or within its widest
+/// line if it has none
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TextAlignment {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+/// Index of a font loaded by the `FontManager`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FontId(usize);
+
+/// Fonts loaded at runtime, grouped by family and weight so UI elements can ask for e.g. the
+/// bold variant of a family
+pub struct FontManager {
+    fonts: Vec<rusttype::Font<'static>>,
+    /// Weights of every family, from 100 for thin to 900 for black, with their fonts
+    families: HashMap<String, Vec<(u16, FontId)>>,
 }
 
 pub struct TextRenderer {
-    cache: Cache<'static>,
     shader: Shader,
-    texture_buffer: Texture,
+    atlas: GlyphAtlas,
     pub width: u32,
     height: u32,
 }
 
+/// Texture holding the rasterized glyphs of all texts. Grows when it fills up and is only
+/// cleared once it can't grow any further, which invalidates the meshes of all texts
+struct GlyphAtlas {
+    texture: Texture,
+    /// Width and height of the texture
+    size: u32,
+    /// Placement of every rasterized glyph, None for glyphs without pixels like spaces
+    glyphs: HashMap<GlyphKey, Option<AtlasGlyph>>,
+    /// Next free position of the row glyphs are added to and the height of that row
+    cursor: (u32, u32),
+    row_height: u32,
+    /// Incremented whenever the atlas is cleared
+    generation: u64,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct GlyphKey {
+    font: FontId,
+    glyph: u16,
+    /// Bits of the horizontal and vertical scale
+    scale: (u32, u32),
+}
+
+#[derive(Clone, Copy)]
+struct AtlasGlyph {
+    /// Pixels of the glyph in the atlas
+    rect: Rect<u32>,
+}
+
 pub struct Text {
     pub content: String,
     font: Fonts,
     size: f32,
-    pub glyphs: Vec<PositionedGlyph<'static>>,
+    /// Lines are wrapped between words to stay within this width, the width of the window if None
+    max_width: Option<f32>,
+    alignment: TextAlignment,
+    /// Glyphs with the font they are taken from, which differs from the text's font for
+    /// characters the font has no glyph for
+    glyphs: Vec<(Fonts, PositionedGlyph<'static>)>,
+    /// UI scale the glyphs were rasterized for, they are laid out in framebuffer pixels and
+    /// scaled back to UI units for the mesh
+    ui_scale: f32,
     dirty: bool,
     x: i32,
     y: i32,
     z: i32,
-    pub mesh: TextMesh,
+    /// Rebuilt while rendering when the atlas was cleared since it was built
+    mesh: RefCell<TextMesh>,
+    /// Generation of the atlas the mesh was built with
+    atlas_generation: Cell<u64>,
     pub max_x: i32,
     pub max_y: i32,
 }
 
+/// Glyphs of a piece of text laid out on a single line from the origin
+#[derive(Default)]
+struct ShapedRun {
+    glyphs: Vec<(Fonts, PositionedGlyph<'static>)>,
+    /// Horizontal offset of the caret in front of every character
+    carets: Vec<f32>,
+    width: f32,
+}
+
 pub struct TextMesh {
     pub vertex_array: DynamicVertexArray<TextVertex>,
     vertices: Vec<TextVertex>,
@@ -46,6 +122,7 @@ pub struct TextMesh {
 #[repr(C)]
 pub struct TextVertex {
     position: (f32, f32, f32),
+    /// Pixel coordinates in the atlas, which stay valid when the atlas grows
     texture_coords: (f32, f32),
 }
 
@@ -1,51 +1,41 @@
-use rusttype::gpu_cache::Cache;
-use rusttype::{point, PositionedGlyph, Rect, Scale};
+use std::cell::{Cell, RefCell};
+
+use rusttype::{point, PositionedGlyph, Scale};
+use unicode_segmentation::UnicodeSegmentation;
 
 use crate::core::renderer::shader::{DynamicVertexArray, VertexAttributes};
-use crate::core::renderer::text::Fonts;
-use crate::core::renderer::ui::primitives::Position;
+use crate::core::renderer::shader_manager::ShaderSource;
+use crate::core::renderer::text::{Fonts, TextAlignment};
+use crate::core::renderer::ui::{primitives::Position, UIRenderer};
 
-use super::{Font, Shader, Text, TextMesh, TextRenderer, TextVertex, Texture};
+use super::{
+    AtlasGlyph, FontManager, GlyphAtlas, Shader, ShapedRun, Text, TextMesh, TextRenderer,
+    TextVertex, Texture,
+};
 
 use lazy_static::lazy_static;
-use std::sync::{Mutex, OnceLock};
+use std::sync::Mutex;
 
 lazy_static! {
     static ref RENDERER: Mutex<TextRenderer> = Mutex::new(TextRenderer::new(1280, 720));
 }
 
-impl Font {
-    fn new(font_data: &'static [u8]) -> Self {
-        Font {
-            font: rusttype::Font::try_from_bytes(font_data).unwrap(),
-        }
-    }
-}
-
-impl Fonts {
-    fn get(&self) -> &Font {
-        static ROBOTO_MONO: OnceLock<Font> = OnceLock::new();
-
-        match self {
-            Fonts::RobotoMono => {
-                ROBOTO_MONO.get_or_init(|| Font::new(include_bytes!("RobotoMono.ttf")))
-            }
-        }
-    }
-}
-
 impl Text {
     pub fn new(font: Fonts, x: i32, y: i32, z: i32, size: f32, content: String) -> Text {
         let mut text = Text {
             content,
             font,
             size,
+            max_width: None,
+            alignment: TextAlignment::default(),
             glyphs: Vec::new(),
+            ui_scale: UIRenderer::get_scale(),
             dirty: true,
             x,
             y,
             z,
-            mesh: TextMesh::new(),
+            mesh: RefCell::new(TextMesh::new()),
+            atlas_generation: Cell::new(0),
             max_x: x,
             max_y: y,
         };
@@ -54,12 +44,15 @@ impl Text {
     }
 
     pub fn render(&self) -> (i32, i32) {
+        if self.atlas_generation.get() != TextRenderer::get_atlas_generation() {
+            self.update_mesh();
+        }
         TextRenderer::render(self)
     }
 
     pub fn render_at(&mut self, position: Position) -> (i32, i32) {
         let (x, y, z) = (position.x as i32, position.y as i32, position.z as i32);
-        if self.x == x && self.y == y && self.z == z {
+        if self.x == x && self.y == y && self.z == z && self.ui_scale == UIRenderer::get_scale() {
             return self.render();
         }
         self.x = x;
@@ -78,6 +71,39 @@ impl Text {
         self.layout(TextRenderer::get_size().0);
     }
 
+    /// Switches to `font`, e.g. one returned by `FontManager::get_font`
+    pub fn set_font(&mut self, font: Fonts) {
+        if self.font == font {
+            return;
+        }
+        self.font = font;
+        self.dirty = true;
+        self.layout(TextRenderer::get_size().0);
+    }
+
+    pub fn get_font(&self) -> Fonts {
+        self.font
+    }
+
+    /// Wraps the lines between words to stay within `max_width`, or within the window if None
+    pub fn set_max_width(&mut self, max_width: Option<f32>) {
+        if self.max_width == max_width {
+            return;
+        }
+        self.max_width = max_width;
+        self.dirty = true;
+        self.layout(TextRenderer::get_size().0);
+    }
+
+    pub fn set_alignment(&mut self, alignment: TextAlignment) {
+        if self.alignment == alignment {
+            return;
+        }
+        self.alignment = alignment;
+        self.dirty = true;
+        self.layout(TextRenderer::get_size().0);
+    }
+
     pub fn set_z_index(&mut self, z_index: f32) {
         if self.z == z_index as i32 {
             return;
@@ -87,114 +113,235 @@ impl Text {
         self.layout(TextRenderer::get_size().0);
     }
 
+    /// Horizontal offset of the caret in front of every character of the content and behind
+    /// the last one, for content laid out on a single line
+    pub fn get_caret_offsets(&self) -> Vec<f32> {
+        let mut run = self.shape_run(Scale::uniform(self.size), &self.content);
+        run.carets.push(run.width);
+        run.carets
+    }
+
     fn layout(&mut self, width: u32) {
+        let ui_scale = UIRenderer::get_scale();
+        if self.ui_scale != ui_scale {
+            self.ui_scale = ui_scale;
+            self.dirty = true;
+        }
         if self.dirty {
-            self.glyphs = self.layout_text(Scale::uniform(self.size), width, &self.content);
+            let scale = Scale::uniform(self.size * ui_scale);
+            self.glyphs = self.layout_text(scale, width, &self.content);
+            for bounds in self
+                .glyphs
+                .iter()
+                .filter_map(|(_, g)| g.pixel_bounding_box())
+            {
+                self.max_x = self
+                    .max_x
+                    .max((bounds.max.x as f32 / ui_scale).ceil() as i32);
+                self.max_y = self
+                    .max_y
+                    .max((bounds.max.y as f32 / ui_scale).ceil() as i32);
+            }
             self.dirty = false;
         }
         self.update_mesh();
     }
 
-    fn update_mesh(&mut self) {
+    fn update_mesh(&self) {
+        let (placed, generation) = TextRenderer::get_glyphs(&self.glyphs);
+        let z = self.z as f32;
         let vertices: Vec<TextVertex> = self
             .glyphs
             .iter()
-            .filter_map(|g| TextRenderer::rect_for(0, g.clone()))
-            .flat_map(|(uv_rect, screen_rect)| {
-                if self.max_x < screen_rect.max.x as i32 {
-                    self.max_x = screen_rect.max.x as i32;
-                }
-                if self.max_y < screen_rect.max.y as i32 {
-                    self.max_y = screen_rect.max.y as i32;
-                }
-                let gl_rect = Rect {
-                    min: point(
-                        screen_rect.min.x as f32 + self.x as f32,
-                        screen_rect.min.y as f32 + self.y as f32,
-                    ),
-                    max: point(
-                        screen_rect.max.x as f32 + self.x as f32,
-                        screen_rect.max.y as f32 + self.y as f32,
-                    ),
-                };
+            .zip(placed)
+            .filter_map(|((_, glyph), placed)| Some((glyph.pixel_bounding_box()?, placed?)))
+            .flat_map(|(bounds, AtlasGlyph { rect })| {
+                // the glyph keeps the pixel size it was rasterized with
+                let min = point(
+                    bounds.min.x as f32 / self.ui_scale + self.x as f32,
+                    bounds.min.y as f32 / self.ui_scale + self.y as f32,
+                );
+                let max = point(
+                    min.x + rect.width() as f32 / self.ui_scale,
+                    min.y + rect.height() as f32 / self.ui_scale,
+                );
+                let (uv_min, uv_max) = (
+                    point(rect.min.x as f32, rect.min.y as f32),
+                    point(rect.max.x as f32, rect.max.y as f32),
+                );
                 vec![
                     TextVertex {
-                        position: (gl_rect.min.x, gl_rect.max.y, self.z as f32),
-                        texture_coords: (uv_rect.min.x, uv_rect.max.y),
+                        position: (min.x, max.y, z),
+                        texture_coords: (uv_min.x, uv_max.y),
                     },
                     TextVertex {
-                        position: (gl_rect.min.x, gl_rect.min.y, self.z as f32),
-                        texture_coords: (uv_rect.min.x, uv_rect.min.y),
+                        position: (min.x, min.y, z),
+                        texture_coords: (uv_min.x, uv_min.y),
                     },
                     TextVertex {
-                        position: (gl_rect.max.x, gl_rect.min.y, self.z as f32),
-                        texture_coords: (uv_rect.max.x, uv_rect.min.y),
+                        position: (max.x, min.y, z),
+                        texture_coords: (uv_max.x, uv_min.y),
                     },
                     TextVertex {
-                        position: (gl_rect.max.x, gl_rect.min.y, self.z as f32),
-                        texture_coords: (uv_rect.max.x, uv_rect.min.y),
+                        position: (max.x, min.y, z),
+                        texture_coords: (uv_max.x, uv_min.y),
                     },
                     TextVertex {
-                        position: (gl_rect.max.x, gl_rect.max.y, self.z as f32),
-                        texture_coords: (uv_rect.max.x, uv_rect.max.y),
+                        position: (max.x, max.y, z),
+                        texture_coords: (uv_max.x, uv_max.y),
                     },
                     TextVertex {
-                        position: (gl_rect.min.x, gl_rect.max.y, self.z as f32),
-                        texture_coords: (uv_rect.min.x, uv_rect.max.y),
+                        position: (min.x, max.y, z),
+                        texture_coords: (uv_min.x, uv_max.y),
                     },
                 ]
             })
             .collect();
-        self.mesh.update_vertices(vertices);
+        self.mesh.borrow_mut().update_vertices(vertices);
+        self.atlas_generation.set(generation);
     }
 
-    fn layout_text<'a>(&self, scale: Scale, width: u32, text: &str) -> Vec<PositionedGlyph<'a>> {
-        let font = &self.font.get().font;
-        let mut result = Vec::new();
-        let v_metrics = font.v_metrics(scale);
+    /// Lays out `text` in framebuffer pixels in lines of at most `width`, or the maximum width of
+    /// the text if it has one. Lines are broken at line feeds and carriage returns, and wrapped between words unless
+    /// a single word is too wide, which is wrapped between characters
+    fn layout_text(
+        &self,
+        scale: Scale,
+        width: u32,
+        text: &str,
+    ) -> Vec<(Fonts, PositionedGlyph<'static>)> {
+        let v_metrics = FontManager::get(self.font).v_metrics(scale);
         let advance_height = v_metrics.ascent - v_metrics.descent + v_metrics.line_gap;
-        let mut caret = point(0.0, v_metrics.ascent);
-        let mut last_glyph_id = None;
-        for c in text.chars() {
-            if c.is_control() {
-                match c {
-                    '\r' => {
-                        caret = point(0.0, caret.y + advance_height);
+        let max_width = self
+            .max_width
+            .map_or(width as f32, |max_width| max_width * self.ui_scale);
+
+        // glyphs of every line relative to its start, with the width up to its last word
+        let mut lines = Vec::new();
+        for paragraph in text.split("\r\n").flat_map(|line| line.split(['\n', '\r'])) {
+            let mut line = Vec::new();
+            let (mut caret, mut line_width) = (0.0, 0.0);
+            let mut wrapped = false;
+            for word in paragraph.split_word_bounds() {
+                let is_space = word.chars().all(char::is_whitespace);
+                let run = self.shape_run(scale, word);
+                let runs = if !is_space && run.width > max_width {
+                    word.graphemes(true)
+                        .map(|grapheme| self.shape_run(scale, grapheme))
+                        .collect()
+                } else {
+                    vec![run]
+                };
+                for run in runs {
+                    if !is_space && line_width > 0.0 && caret + run.width > max_width {
+                        lines.push((std::mem::take(&mut line), line_width));
+                        (caret, line_width) = (0.0, 0.0);
+                        wrapped = true;
+                    }
+                    // the space a line was wrapped at is not carried over to the next one
+                    if is_space && wrapped && caret == 0.0 {
+                        continue;
+                    }
+                    for (font, mut glyph) in run.glyphs {
+                        let position = glyph.position();
+                        glyph.set_position(point(position.x + caret, position.y));
+                        line.push((font, glyph));
+                    }
+                    caret += run.width;
+                    if !is_space {
+                        line_width = caret;
                     }
-                    '\n' => {}
-                    _ => {}
                 }
-                continue;
             }
-            let base_glyph = font.glyph(c);
-            if let Some(id) = last_glyph_id.take() {
-                caret.x += font.pair_kerning(scale, id, base_glyph.id());
+            lines.push((line, line_width));
+        }
+
+        let align_width = self
+            .max_width
+            .map(|max_width| max_width * self.ui_scale)
+            .unwrap_or_else(|| lines.iter().map(|(_, width)| *width).fold(0.0, f32::max));
+        let mut result = Vec::new();
+        for (i, (line, line_width)) in lines.into_iter().enumerate() {
+            let x = match self.alignment {
+                TextAlignment::Left => 0.0,
+                TextAlignment::Center => ((align_width - line_width) / 2.0).max(0.0),
+                TextAlignment::Right => (align_width - line_width).max(0.0),
+            };
+            let y = v_metrics.ascent + i as f32 * advance_height;
+            for (font, mut glyph) in line {
+                let position = glyph.position();
+                glyph.set_position(point(position.x + x, position.y + y));
+                result.push((font, glyph));
+            }
+        }
+        result
+    }
+
+    /// Lays out `run` on a single line. Combining characters don't advance the caret and are
+    /// centered over the character they belong to, characters missing from the text's font are
+    /// taken from the first loaded font that has them
+    fn shape_run(&self, scale: Scale, run: &str) -> ShapedRun {
+        let mut shaped = ShapedRun::default();
+        let mut last_glyph = None;
+        for grapheme in run.graphemes(true) {
+            let mut chars = grapheme.chars();
+            let Some(base) = chars.next() else {
+                continue;
+            };
+            shaped.carets.push(shaped.width);
+            if base.is_control() {
+                shaped.carets.extend(chars.map(|_| shaped.width));
+                continue;
             }
-            last_glyph_id = Some(base_glyph.id());
-            let mut glyph = base_glyph.scaled(scale).positioned(caret);
-            if let Some(bb) = glyph.pixel_bounding_box() {
-                if bb.max.x > width as i32 {
-                    caret = point(0.0, caret.y + advance_height);
-                    glyph.set_position(caret);
-                    last_glyph_id = None;
+            let (base_font, font) = FontManager::get_for_char(self.font, base);
+            let glyph = font.glyph(base);
+            if let Some((last_font, id)) = last_glyph.take() {
+                if last_font == base_font {
+                    shaped.width += font.pair_kerning(scale, id, glyph.id());
                 }
             }
-            caret.x += glyph.unpositioned().h_metrics().advance_width;
-            result.push(glyph);
+            last_glyph = Some((base_font, glyph.id()));
+            let glyph = glyph.scaled(scale);
+            let advance = glyph.h_metrics().advance_width;
+            let base_center = ShapedRun::get_center(&glyph, advance);
+            let caret = shaped.width;
+            shaped
+                .glyphs
+                .push((base_font, glyph.positioned(point(caret, 0.0))));
+            shaped.width += advance;
+            for mark in chars {
+                shaped.carets.push(shaped.width);
+                let (mark_font, font) = FontManager::get_for_char(self.font, mark);
+                let glyph = font.glyph(mark).scaled(scale);
+                let mark_center = ShapedRun::get_center(&glyph, glyph.h_metrics().advance_width);
+                let x = caret + base_center - mark_center;
+                shaped
+                    .glyphs
+                    .push((mark_font, glyph.positioned(point(x, 0.0))));
+            }
         }
-        result
+        shaped
+    }
+}
+
+impl ShapedRun {
+    /// Horizontal center of the outline of `glyph`, or of its advance if it has no outline
+    fn get_center(glyph: &rusttype::ScaledGlyph<'static>, advance: f32) -> f32 {
+        glyph
+            .exact_bounding_box()
+            .map_or(advance / 2.0, |bounds| (bounds.min.x + bounds.max.x) / 2.0)
     }
 }
 
 impl TextRenderer {
     fn new(width: u32, height: u32) -> TextRenderer {
-        let cache: Cache<'static> = Cache::builder().dimensions(1024, 1024).build();
-
-        let shader = Shader::new(include_str!("vertex.glsl"), include_str!("fragment.glsl"));
+        let shader = Shader::load(
+            ShaderSource::new("renderer/text/vertex.glsl", include_str!("vertex.glsl")),
+            ShaderSource::new("renderer/text/fragment.glsl", include_str!("fragment.glsl")),
+        );
         TextRenderer {
-            cache,
             shader,
-            texture_buffer: Texture::new(1024, 1024),
+            atlas: GlyphAtlas::new(),
             width,
             height,
         }
@@ -208,8 +355,7 @@ impl TextRenderer {
         let mut polygon_mode = 0;
         unsafe {
             gl::ActiveTexture(gl::TEXTURE0);
-            renderer.texture_buffer.bind();
-            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+            renderer.atlas.bind();
 
             gl::GetIntegerv(gl::POLYGON_MODE, &mut polygon_mode);
             if polygon_mode != gl::FILL as i32 {
@@ -217,14 +363,16 @@ impl TextRenderer {
             }
         }
 
-        text.mesh.vertex_array.bind();
+        let mesh = text.mesh.borrow();
+        mesh.vertex_array.bind();
 
         // set shader uniforms
         renderer.shader.bind();
+        let scale = UIRenderer::get_scale();
         let projection = cgmath::ortho(
             0.0,
-            renderer.width as f32,
-            renderer.height as f32,
+            renderer.width as f32 / scale,
+            renderer.height as f32 / scale,
             0.0,
             -100.0,
             100.0,
@@ -239,18 +387,13 @@ impl TextRenderer {
             gl::Enable(gl::BLEND);
             gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
             renderer.shader.set_uniform_1i("texture0", 0);
-            gl::DrawArrays(
-                gl::TRIANGLES,
-                0,
-                text.mesh.vertex_array.get_element_count() as i32,
-            );
+            mesh.vertex_array.draw(gl::TRIANGLES);
 
             // cleanup
             gl::BindTexture(gl::TEXTURE_2D, 0);
             gl::BindBuffer(gl::ARRAY_BUFFER, 0);
             gl::BindVertexArray(0);
             gl::Disable(gl::BLEND);
-            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 4);
 
             if polygon_mode != gl::FILL as i32 {
                 gl::PolygonMode(gl::FRONT_AND_BACK, polygon_mode as u32);
@@ -279,31 +422,17 @@ impl TextRenderer {
         (renderer.width, renderer.height)
     }
 
-    pub fn rect_for(
-        font_id: usize,
-        glyph: PositionedGlyph<'static>,
-    ) -> Option<(Rect<f32>, Rect<i32>)> {
+    /// Places `glyphs` in the atlas, along with the generation of the atlas they
+    /// belong to
+    fn get_glyphs(glyphs: &[(Fonts, PositionedGlyph<'static>)]) -> (Vec<Option<AtlasGlyph>>, u64) {
         let mut renderer = RENDERER.lock().unwrap();
-        renderer.cache.queue_glyph(0, glyph.clone());
-        unsafe {
-            gl::ActiveTexture(gl::TEXTURE0);
-            renderer.texture_buffer.bind();
-            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
-        }
-        let _ = renderer.cache.cache_queued(|rect, data| unsafe {
-            gl::TexSubImage2D(
-                gl::TEXTURE_2D,
-                0,
-                rect.min.x as i32,
-                rect.min.y as i32,
-                rect.width() as i32,
-                rect.height() as i32,
-                gl::RED,
-                gl::UNSIGNED_BYTE,
-                data.as_ptr() as *const std::ffi::c_void,
-            );
-        });
-        renderer.cache.rect_for(font_id, &glyph).ok().flatten()
+        let placed = renderer.atlas.get_glyphs(glyphs);
+        (placed, renderer.atlas.get_generation())
+    }
+
+    fn get_atlas_generation() -> u64 {
+        let renderer = RENDERER.lock().unwrap();
+        renderer.atlas.get_generation()
     }
 }
 
@@ -317,7 +446,7 @@ impl TextMesh {
 
     fn update_vertices(&mut self, vertices: Vec<TextVertex>) {
         self.vertices = vertices;
-        self.vertex_array.buffer_data(&self.vertices, &None);
+        self.vertex_array.buffer_data_dyn(&self.vertices, &None);
     }
 }
 
@@ -328,7 +457,7 @@ impl VertexAttributes for TextVertex {
 }
 
 impl Texture {
-    pub fn new(width: i32, height: i32) -> Texture {
+    pub(super) fn new(width: i32, height: i32) -> Texture {
         let mut texture_buffer = 0;
         let data = vec![0u8; width as usize * height as usize];
         unsafe {
@@ -356,7 +485,7 @@ impl Texture {
         Texture { id: texture_buffer }
     }
 
-    pub fn bind(&self) {
+    pub(super) fn bind(&self) {
         unsafe {
             gl::BindTexture(gl::TEXTURE_2D, self.id);
         }
@@ -1,6 +1,12 @@
-use std::path::Path;
+use std::{error::Error, path::Path};
 
-use gl::types::{GLint, GLsizei, GLsizeiptr, GLvoid};
+use gl::types::{GLint, GLsizei, GLsizeiptr, GLuint, GLvoid};
+
+use crate::core::{
+    asset::Asset,
+    profiler::Profiler,
+    renderer::{gl_debug, shader_manager::ShaderSource},
+};
 
 use super::{Shader, Texture, TextureRenderer};
 
@@ -49,12 +55,85 @@ impl Texture {
         }
     }
 
+    /// Allocates an empty RGBA texture to render into
+    pub fn set_as_color_texture(&self, width: u32, height: u32) {
+        self.bind();
+        unsafe {
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
+            gl::TexImage2D(
+                gl::TEXTURE_2D,
+                0,
+                gl::RGBA8 as GLint,
+                width as GLsizei,
+                height as GLsizei,
+                0,
+                gl::RGBA,
+                gl::UNSIGNED_BYTE,
+                std::ptr::null(),
+            );
+        }
+        Texture::unbind();
+    }
+
+    /// Allocates an empty RGBA half float texture for colors brighter than white, filtered like
+    /// `set_as_color_texture`
+    pub fn set_as_hdr_texture(&self, width: u32, height: u32) {
+        self.bind();
+        unsafe {
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
+            gl::TexImage2D(
+                gl::TEXTURE_2D,
+                0,
+                gl::RGBA16F as GLint,
+                width as GLsizei,
+                height as GLsizei,
+                0,
+                gl::RGBA,
+                gl::FLOAT,
+                std::ptr::null(),
+            );
+        }
+        Texture::unbind();
+    }
+
+    /// Allocates an empty RGBA half float texture for data like velocities, it is not filtered
+    pub fn set_as_float_texture(&self, width: u32, height: u32) {
+        self.bind();
+        unsafe {
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::NEAREST as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_S, gl::CLAMP_TO_EDGE as i32);
+            gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_WRAP_T, gl::CLAMP_TO_EDGE as i32);
+            gl::TexImage2D(
+                gl::TEXTURE_2D,
+                0,
+                gl::RGBA16F as GLint,
+                width as GLsizei,
+                height as GLsizei,
+                0,
+                gl::RGBA,
+                gl::FLOAT,
+                std::ptr::null(),
+            );
+        }
+        Texture::unbind();
+    }
+
     pub fn load_from_file(&self, path: &Path) {
+        self.try_load_from_file(path).expect("Image not found");
+    }
+
+    /// Uploads the image file at `path`, the texture keeps its previous image if the file can not
+    /// be read
+    pub fn try_load_from_file(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        let img = image::open(path)?.flipv().to_rgba8();
         self.bind();
-        let img = image::open(path)
-            .expect("Image not found")
-            .flipv()
-            .to_rgba8();
         unsafe {
             gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
             gl::TexParameteri(gl::TEXTURE_2D, gl::TEXTURE_MAG_FILTER, gl::NEAREST as i32);
@@ -73,6 +152,7 @@ impl Texture {
             );
         }
         Texture::unbind();
+        Ok(())
     }
 
     pub fn load_from_data(&self, width: u32, height: u32, data: Vec<u8>) {
@@ -97,6 +177,18 @@ impl Texture {
         Texture::unbind();
     }
 
+    /// Format the driver allocated the texture with, e.g. the depth format picked for
+    /// `set_as_depth_texture`
+    pub fn get_internal_format(&self) -> u32 {
+        let mut format = 0;
+        self.bind();
+        unsafe {
+            gl::GetTexLevelParameteriv(gl::TEXTURE_2D, 0, gl::TEXTURE_INTERNAL_FORMAT, &mut format);
+        }
+        Texture::unbind();
+        format as u32
+    }
+
     pub fn bind(&self) {
         unsafe {
             gl::BindTexture(gl::TEXTURE_2D, self.id);
@@ -110,6 +202,23 @@ impl Texture {
     }
 }
 
+/// Image files, uploaded again into the same texture when the file changes
+impl Asset for Texture {
+    fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        let texture = Texture::new();
+        texture.try_load_from_file(path)?;
+        Ok(texture)
+    }
+
+    fn is_reloadable() -> bool {
+        true
+    }
+
+    fn reload(&self, path: &Path) -> Result<(), Box<dyn Error>> {
+        self.try_load_from_file(path)
+    }
+}
+
 impl Drop for Texture {
     fn drop(&mut self) {
         unsafe {
@@ -120,11 +229,22 @@ impl Drop for Texture {
 
 impl TextureRenderer {
     pub fn new() -> Self {
-        let shader = Shader::new(include_str!("vertex.glsl"), include_str!("fragment.glsl"));
+        let shader = Shader::load(
+            ShaderSource::new("renderer/texture/vertex.glsl", include_str!("vertex.glsl")),
+            ShaderSource::new(
+                "renderer/texture/fragment.glsl",
+                include_str!("fragment.glsl"),
+            ),
+        );
         Self { shader }
     }
 
     pub fn render(&self, texture: &Texture) {
+        self.render_id(texture.id);
+    }
+
+    /// Renders a texture by its gl id, for textures owned elsewhere
+    pub fn render_id(&self, texture_id: GLuint) {
         #[rustfmt::skip]
         let vertices: Vec<f32> = vec![
             0.5, 0.5, 0.0, 0.0,
@@ -175,12 +295,14 @@ impl TextureRenderer {
             );
             gl::EnableVertexAttribArray(1);
             gl::ActiveTexture(gl::TEXTURE0);
-            texture.bind();
+            gl::BindTexture(gl::TEXTURE_2D, texture_id);
             self.shader.bind();
             gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
             gl::Enable(gl::BLEND);
             gl::Disable(gl::DEPTH_TEST);
             gl::DrawElements(gl::TRIANGLES, 6, gl::UNSIGNED_INT, std::ptr::null());
+            Profiler::count_draw_call();
+            gl_debug::check_errors("TextureRenderer::render");
             gl::Disable(gl::BLEND);
             gl::DeleteBuffers(1, &vbo);
             gl::DeleteBuffers(1, &ebo);
@@ -0,0 +1,17 @@
+use cgmath::Matrix4;
+
+mod transparency;
+
+/// Blended draws of a frame, drawn back to front after the opaque geometry with depth writes
+/// off. Passes add theirs with `RenderPass::queue_transparent` and components with
+/// `Component::queue_transparent`, the render graph draws them at `TRANSPARENT_PASS_ORDER`
+pub struct TransparencyQueue<'a> {
+    view: Matrix4<f32>,
+    draws: Vec<TransparentDraw<'a>>,
+}
+
+/// Draw call of a `TransparencyQueue`, sorted by its distance in front of the camera
+struct TransparentDraw<'a> {
+    depth: f32,
+    draw: Box<dyn FnOnce() + 'a>,
+}
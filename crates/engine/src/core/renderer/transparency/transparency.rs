@@ -0,0 +1,59 @@
+use cgmath::{EuclideanSpace, Matrix4, Point3};
+
+use super::{TransparencyQueue, TransparentDraw};
+
+impl<'a> TransparencyQueue<'a> {
+    /// Queue sorting by the view space depth of the camera with the view matrix `view`
+    pub fn new(view: Matrix4<f32>) -> Self {
+        Self {
+            view,
+            draws: Vec::new(),
+        }
+    }
+
+    /// Queues `draw` for geometry around the world space `position`. The draw binds its own
+    /// shader and textures, the blend state is already set
+    pub fn push<F: FnOnce() + 'a>(&mut self, position: Point3<f32>, draw: F) {
+        let depth = -(self.view * position.to_homogeneous()).z;
+        self.push_at_depth(depth, draw);
+    }
+
+    /// Queues `draw` at a view space depth, e.g. `f32::INFINITY` for geometry behind everything
+    /// else like the ocean
+    pub fn push_at_depth<F: FnOnce() + 'a>(&mut self, depth: f32, draw: F) {
+        self.draws.push(TransparentDraw {
+            depth,
+            draw: Box::new(draw),
+        });
+    }
+
+    pub fn len(&self) -> usize {
+        self.draws.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.draws.is_empty()
+    }
+
+    /// Runs the draws from the farthest to the nearest, draws at the same depth in the order they
+    /// were queued
+    pub fn render(mut self) {
+        if self.draws.is_empty() {
+            return;
+        }
+        self.draws
+            .sort_by(|first, second| second.depth.total_cmp(&first.depth));
+        unsafe {
+            gl::Enable(gl::BLEND);
+            gl::BlendFunc(gl::SRC_ALPHA, gl::ONE_MINUS_SRC_ALPHA);
+            gl::DepthMask(gl::FALSE);
+        }
+        for draw in self.draws {
+            (draw.draw)();
+        }
+        unsafe {
+            gl::DepthMask(gl::TRUE);
+            gl::Disable(gl::BLEND);
+        }
+    }
+}
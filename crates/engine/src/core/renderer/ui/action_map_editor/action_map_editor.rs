@@ -0,0 +1,230 @@
+use glfw::{Action, MouseButton, WindowEvent};
+
+use crate::core::{
+    input::{ActionMap, Binding, Input},
+    renderer::{
+        plane::{PlaneBuilder, PlaneRenderer},
+        text::{Fonts, Text},
+        ui::{
+            primitives::{Position, Region},
+            Offset, Size, UIElement, UIElementHandle, UIWindow,
+        },
+    },
+    scene::Scene,
+};
+
+use super::{ActionMapEditor, ActionMapEditorBuilder, ChangeHandler};
+
+const ROW_HEIGHT: f32 = 22.0;
+/// Horizontal position of the bindings in a row
+const BINDINGS_X: f32 = 180.0;
+
+impl ActionMapEditor {
+    pub fn new(position: Position, size: Size, on_change: Option<ChangeHandler>) -> Self {
+        Self {
+            position,
+            size,
+            offset: Offset::default(),
+            background: PlaneBuilder::new()
+                .position(position)
+                .size(size)
+                .color((0.1, 0.1, 0.1, 0.8))
+                .border_radius_uniform(5.0)
+                .build(),
+            highlight: PlaneBuilder::new()
+                .position(position)
+                .size(Size {
+                    width: size.width,
+                    height: ROW_HEIGHT,
+                })
+                .color((0.2, 0.3, 0.5, 1.0))
+                .build(),
+            rows: Vec::new(),
+            listening: None,
+            on_change,
+        }
+    }
+
+    fn get_bindings_label(&self, action: &str, actions: &ActionMap) -> String {
+        if self.listening.as_deref() == Some(action) {
+            return "Press a key...".to_string();
+        }
+        let bindings = actions.get_bindings(action);
+        if bindings.is_empty() {
+            return "-".to_string();
+        }
+        bindings
+            .iter()
+            .map(Binding::get_name)
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Applies new bindings of `action` and reports the changed map
+    fn set_bindings(&self, action: &str, bindings: Vec<Binding>) {
+        let mut actions = Input::get_action_map();
+        actions.set_bindings(action, bindings);
+        Input::set_action_map(actions.clone());
+        if let Some(on_change) = &self.on_change {
+            on_change(&actions);
+        }
+    }
+}
+
+impl UIElement for ActionMapEditor {
+    fn render(&mut self, _: &mut Scene) {
+        let origin = &self.position + &self.offset;
+        PlaneRenderer::render(&self.background);
+        let actions = Input::get_action_map();
+        let names = actions.get_actions();
+        let row_count = names
+            .len()
+            .min((self.size.height / ROW_HEIGHT).floor() as usize);
+        while self.rows.len() < row_count {
+            self.rows.push((
+                Text::new(Fonts::RobotoMono, 0, 0, 0, 14.0, String::new()),
+                Text::new(Fonts::RobotoMono, 0, 0, 0, 14.0, String::new()),
+            ));
+        }
+        if let Some(row) = names
+            .iter()
+            .position(|name| Some(*name) == self.listening.as_deref())
+            .filter(|row| *row < row_count)
+        {
+            self.highlight
+                .set_position(&origin + (0.0, row as f32 * ROW_HEIGHT, 1.0));
+            PlaneRenderer::render(&self.highlight);
+        }
+        for (index, name) in names.iter().take(row_count).enumerate() {
+            let label = self.get_bindings_label(name, &actions);
+            let (name_text, bindings_text) = &mut self.rows[index];
+            let y = index as f32 * ROW_HEIGHT + 3.0;
+            name_text.set_content(name);
+            name_text.render_at(&origin + (5.0, y, 2.0));
+            bindings_text.set_content(&label);
+            bindings_text.render_at(&origin + (BINDINGS_X, y, 2.0));
+        }
+    }
+
+    fn handle_events(
+        &mut self,
+        _: &mut Scene,
+        window: &mut dyn UIWindow,
+        _: &mut glfw::Glfw,
+        event: &WindowEvent,
+    ) -> bool {
+        if let Some(action) = self.listening.clone() {
+            let Some((binding, state)) = Binding::from_event(event) else {
+                return false;
+            };
+            if state == Action::Press {
+                self.listening = None;
+                self.set_bindings(&action, vec![binding]);
+            }
+            return true;
+        }
+        let WindowEvent::MouseButton(button, Action::Press, _) = event else {
+            return false;
+        };
+        let (x, y) = window.get_cursor_pos();
+        let (x, y) = (x as f32, y as f32);
+        if !Region::new_with_offset(self.position, self.size, self.offset).contains(x, y) {
+            return false;
+        }
+        let origin = &self.position + &self.offset;
+        let row = ((y - origin.y) / ROW_HEIGHT) as usize;
+        let actions = Input::get_action_map();
+        let Some(action) = actions
+            .get_actions()
+            .get(row)
+            .map(|action| action.to_string())
+        else {
+            return true;
+        };
+        match button {
+            MouseButton::Button1 => self.listening = Some(action),
+            MouseButton::Button2 => {
+                let defaults = ActionMap::default().get_bindings(&action).to_vec();
+                self.set_bindings(&action, defaults);
+            }
+            _ => {}
+        }
+        true
+    }
+
+    fn add_children(&mut self, _: Vec<(Option<UIElementHandle>, Box<dyn UIElement>)>) {
+        panic!("ActionMapEditor cannot have children");
+    }
+
+    fn set_offset(&mut self, offset: Offset) {
+        self.offset = offset;
+        self.background.set_position(&self.position + &self.offset);
+    }
+
+    fn get_size(&self) -> &Size {
+        &self.size
+    }
+
+    fn contains_child(&self, _: &UIElementHandle) -> bool {
+        false
+    }
+
+    fn get_offset(&self) -> &Offset {
+        &self.offset
+    }
+
+    fn add_child_to(
+        &mut self,
+        _: UIElementHandle,
+        _: Option<UIElementHandle>,
+        _: Box<dyn UIElement>,
+    ) {
+        panic!("ActionMapEditor cannot have children");
+    }
+
+    fn set_z_index(&mut self, z_index: f32) {
+        self.position.z = z_index;
+        self.background.set_z_index(z_index);
+        self.highlight.set_z_index(z_index + 1.0);
+    }
+}
+
+impl ActionMapEditorBuilder {
+    pub fn new() -> Self {
+        Self {
+            position: Position::default(),
+            size: Size {
+                width: 340.0,
+                height: ActionMap::default().get_actions().len() as f32 * ROW_HEIGHT,
+            },
+            on_change: None,
+        }
+    }
+
+    pub fn position(mut self, x: f32, y: f32) -> Self {
+        self.position = Position { x, y, z: 0.0 };
+        self
+    }
+
+    /// Actions beyond the height are cut off, e.g. to put the editor in a scroll container
+    pub fn size(mut self, width: f32, height: f32) -> Self {
+        self.size = Size { width, height };
+        self
+    }
+
+    /// Called with the whole map after an action was rebound, e.g. to save it
+    pub fn on_change(mut self, on_change: ChangeHandler) -> Self {
+        self.on_change = Some(on_change);
+        self
+    }
+
+    pub fn build(self) -> ActionMapEditor {
+        ActionMapEditor::new(self.position, self.size, self.on_change)
+    }
+}
+
+impl Default for ActionMapEditorBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,34 @@
+use crate::core::{
+    input::ActionMap,
+    renderer::{plane::Plane, text::Text},
+};
+
+use super::{primitives::Position, Offset, Size};
+
+pub mod action_map_editor;
+
+/// Called with the whole map after an action was rebound
+pub type ChangeHandler = Box<dyn Fn(&ActionMap)>;
+
+/// The actions of the `ActionMap` in use with their bindings. Clicking a row waits for the next
+/// key or mouse button and binds the action to it, right clicking restores the action's
+/// default bindings
+pub struct ActionMapEditor {
+    position: Position,
+    size: Size,
+    offset: Offset,
+    background: Plane,
+    /// Marks the row waiting for its binding
+    highlight: Plane,
+    /// Action name and bindings per row
+    rows: Vec<(Text, Text)>,
+    /// Action waiting for its new binding
+    listening: Option<String>,
+    on_change: Option<ChangeHandler>,
+}
+
+pub struct ActionMapEditorBuilder {
+    position: Position,
+    size: Size,
+    on_change: Option<ChangeHandler>,
+}
@@ -0,0 +1,301 @@
+use std::{
+    cell::{Cell, RefCell},
+    fs,
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use crate::core::{
+    renderer::ui::{
+        drag::{DragPayload, Draggable},
+        list_view::ListViewBuilder,
+        primitives::Position,
+        Offset, Size, UIElement, UIElementHandle, UIWindow,
+    },
+    scene::Scene,
+};
+
+use super::{
+    asset_row::ROW_HEIGHT, AssetBrowser, AssetBrowserBuilder, AssetEntry, AssetHandler, AssetKind,
+    AssetRow, Thumbnails, ASSET_PAYLOAD,
+};
+
+/// How often the directory is listed again to pick up added and removed files
+const SCAN_INTERVAL: Duration = Duration::from_secs(1);
+/// Longest time in seconds between the clicks of a double-click
+const DOUBLE_CLICK_TIME: f64 = 0.4;
+
+impl AssetBrowser {
+    pub fn new(root: &Path, position: Position, size: Size) -> Self {
+        let entries = Rc::new(RefCell::new(AssetBrowser::scan(root)));
+        let thumbnails = Rc::new(RefCell::new(Thumbnails::new()));
+        let item_count = {
+            let entries = entries.clone();
+            Box::new(move || entries.borrow().len())
+        };
+        let row_width = Rc::new(Cell::new(size.width));
+        let build_row = {
+            let entries = entries.clone();
+            let thumbnails = thumbnails.clone();
+            let row_width = row_width.clone();
+            // previews are only created for the rows that are shown
+            Box::new(move |index: usize| -> Box<dyn UIElement> {
+                let entry = entries.borrow()[index].clone();
+                thumbnails.borrow_mut().request(&entry);
+                let payload = DragPayload::new(ASSET_PAYLOAD, &entry.path.to_string_lossy());
+                let row = AssetRow::new(entry, thumbnails.clone(), row_width.get());
+                Box::new(Draggable::new(payload, Box::new(row)))
+            })
+        };
+        Self {
+            root: root.to_path_buf(),
+            list: ListViewBuilder::new(item_count, build_row)
+                .position(position.x, position.y)
+                .size(size.width, size.height)
+                .row_height(ROW_HEIGHT)
+                .build(),
+            entries,
+            thumbnails,
+            row_width,
+            on_open: None,
+            last_click: None,
+            last_scan: Instant::now(),
+        }
+    }
+
+    pub fn on_open(&mut self, on_open: AssetHandler) {
+        self.on_open = Some(on_open);
+    }
+
+    /// Lists the directory again, the rows are rebuilt if files were added or removed
+    pub fn refresh(&mut self) {
+        self.last_scan = Instant::now();
+        let entries = AssetBrowser::scan(&self.root);
+        let changed = {
+            let current = self.entries.borrow();
+            entries.len() != current.len()
+                || entries
+                    .iter()
+                    .zip(current.iter())
+                    .any(|(entry, current)| entry.path != current.path)
+        };
+        if changed {
+            self.thumbnails.borrow_mut().retain(&entries);
+            *self.entries.borrow_mut() = entries;
+            self.list.refresh();
+        }
+    }
+
+    /// Asset of the selected row, if any
+    pub fn get_selected(&self) -> Option<AssetEntry> {
+        let index = self.list.get_selected()?;
+        self.entries.borrow().get(index).cloned()
+    }
+
+    pub fn get_entries(&self) -> Vec<AssetEntry> {
+        self.entries.borrow().clone()
+    }
+
+    fn open(&self, scene: &mut Scene, index: usize) {
+        let Some(entry) = self.entries.borrow().get(index).cloned() else {
+            return;
+        };
+        if let Some(on_open) = &self.on_open {
+            on_open(scene, &entry);
+        }
+    }
+
+    /// Files below `root` sorted by their path, hidden files and unfinished saves are skipped
+    fn scan(root: &Path) -> Vec<AssetEntry> {
+        let mut paths = Vec::new();
+        AssetBrowser::collect_files(root, &mut paths);
+        paths.sort();
+        paths
+            .into_iter()
+            .map(|path| AssetEntry {
+                name: path
+                    .strip_prefix(root)
+                    .unwrap_or(&path)
+                    .to_string_lossy()
+                    .replace('\\', "/"),
+                kind: AssetKind::from_path(&path),
+                path,
+            })
+            .collect()
+    }
+
+    fn collect_files(directory: &Path, paths: &mut Vec<PathBuf>) {
+        let Ok(dir_entries) = fs::read_dir(directory) else {
+            return;
+        };
+        for dir_entry in dir_entries.flatten() {
+            let path = dir_entry.path();
+            let is_hidden = dir_entry.file_name().to_string_lossy().starts_with('.');
+            if is_hidden || path.extension().is_some_and(|extension| extension == "tmp") {
+                continue;
+            }
+            if path.is_dir() {
+                AssetBrowser::collect_files(&path, paths);
+            } else {
+                paths.push(path);
+            }
+        }
+    }
+}
+
+impl AssetKind {
+    /// Kind of the file at `path`, files below an `animations` directory are animations
+    pub fn from_path(path: &Path) -> Self {
+        if path
+            .components()
+            .any(|component| component.as_os_str() == "animations")
+        {
+            return AssetKind::Animation;
+        }
+        let extension = path
+            .extension()
+            .map(|extension| extension.to_string_lossy().to_lowercase());
+        match extension.as_deref() {
+            Some("fbx" | "gltf" | "glb" | "obj" | "dae") => AssetKind::Model,
+            Some("png" | "jpg" | "jpeg" | "tga" | "bmp") => AssetKind::Texture,
+            Some("glsl" | "vert" | "frag") => AssetKind::Shader,
+            Some("ron" | "json" | "toml") => AssetKind::Config,
+            _ => AssetKind::Other,
+        }
+    }
+
+    /// Short label shown in place of a preview
+    pub fn get_label(&self) -> &'static str {
+        match self {
+            AssetKind::Model => "MDL",
+            AssetKind::Texture => "IMG",
+            AssetKind::Animation => "ANM",
+            AssetKind::Shader => "SHD",
+            AssetKind::Config => "CFG",
+            AssetKind::Other => "---",
+        }
+    }
+
+    pub fn get_color(&self) -> (f32, f32, f32, f32) {
+        match self {
+            AssetKind::Model => (0.3, 0.5, 0.8, 1.0),
+            AssetKind::Texture => (0.3, 0.7, 0.4, 1.0),
+            AssetKind::Animation => (0.8, 0.6, 0.2, 1.0),
+            AssetKind::Shader => (0.7, 0.3, 0.7, 1.0),
+            AssetKind::Config => (0.5, 0.5, 0.5, 1.0),
+            AssetKind::Other => (0.3, 0.3, 0.3, 1.0),
+        }
+    }
+}
+
+impl UIElement for AssetBrowser {
+    fn render(&mut self, scene: &mut Scene) {
+        if self.last_scan.elapsed() >= SCAN_INTERVAL {
+            self.refresh();
+        }
+        self.thumbnails.borrow_mut().update();
+        self.list.render(scene);
+    }
+
+    fn handle_events(
+        &mut self,
+        scene: &mut Scene,
+        window: &mut dyn UIWindow,
+        glfw: &mut glfw::Glfw,
+        event: &glfw::WindowEvent,
+    ) -> bool {
+        // the rows take the press to start dragging, so clicks are counted before the list
+        // sees them
+        if let glfw::WindowEvent::MouseButton(glfw::MouseButton::Button1, glfw::Action::Press, _) =
+            event
+        {
+            let (x, y) = window.get_cursor_pos();
+            if let Some(index) = self.list.get_row_at(x as f32, y as f32) {
+                let time = glfw.get_time();
+                let is_double_click = self.last_click.is_some_and(|(row, clicked_at)| {
+                    row == index && time - clicked_at <= DOUBLE_CLICK_TIME
+                });
+                self.list.set_selected(Some(index));
+                if is_double_click {
+                    self.last_click = None;
+                    self.open(scene, index);
+                    return true;
+                }
+                self.last_click = Some((index, time));
+            }
+        }
+        self.list.handle_events(scene, window, glfw, event)
+    }
+
+    fn add_children(&mut self, _: Vec<(Option<UIElementHandle>, Box<dyn UIElement>)>) {
+        panic!("AssetBrowser builds its rows from the listed files");
+    }
+
+    fn add_child_to(
+        &mut self,
+        _: UIElementHandle,
+        _: Option<UIElementHandle>,
+        _: Box<dyn UIElement>,
+    ) {
+        panic!("AssetBrowser builds its rows from the listed files");
+    }
+
+    fn contains_child(&self, _: &UIElementHandle) -> bool {
+        false
+    }
+
+    fn get_offset(&self) -> &Offset {
+        self.list.get_offset()
+    }
+
+    fn set_offset(&mut self, offset: Offset) {
+        self.list.set_offset(offset);
+    }
+
+    fn get_size(&self) -> &Size {
+        self.list.get_size()
+    }
+
+    fn set_size(&mut self, size: Size) {
+        self.row_width.set(size.width);
+        self.list.set_size(size);
+    }
+
+    fn set_z_index(&mut self, z_index: f32) {
+        self.list.set_z_index(z_index);
+    }
+}
+
+impl AssetBrowserBuilder {
+    pub fn new(root: &str) -> Self {
+        Self {
+            root: PathBuf::from(root),
+            position: Position::default(),
+            size: Size::default(),
+            on_open: None,
+        }
+    }
+
+    pub fn position(mut self, x: f32, y: f32) -> Self {
+        self.position = Position { x, y, z: 0.0 };
+        self
+    }
+
+    pub fn size(mut self, width: f32, height: f32) -> Self {
+        self.size = Size { width, height };
+        self
+    }
+
+    /// Called with the asset of every row the user double-clicks
+    pub fn on_open(mut self, on_open: AssetHandler) -> Self {
+        self.on_open = Some(on_open);
+        self
+    }
+
+    pub fn build(self) -> AssetBrowser {
+        let mut asset_browser = AssetBrowser::new(&self.root, self.position, self.size);
+        asset_browser.on_open = self.on_open;
+        asset_browser
+    }
+}
@@ -0,0 +1,111 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::core::{
+    renderer::{
+        plane::{PlaneBuilder, PlaneRenderer},
+        ui::{
+            primitives::Position, text::Text, Offset, Size, UIElement, UIElementHandle, UIWindow,
+        },
+    },
+    scene::Scene,
+};
+
+use super::{AssetEntry, AssetRow, Thumbnails};
+
+/// Height of the rows, the icons are square and fill it but for a margin
+pub(super) const ROW_HEIGHT: f32 = 40.0;
+const ICON_MARGIN: f32 = 4.0;
+const FONT_SIZE: f32 = 14.0;
+
+impl AssetRow {
+    pub(super) fn new(entry: AssetEntry, thumbnails: Rc<RefCell<Thumbnails>>, width: f32) -> Self {
+        let icon_size = ROW_HEIGHT - 2.0 * ICON_MARGIN;
+        Self {
+            icon: PlaneBuilder::new()
+                .size(Size {
+                    width: icon_size,
+                    height: icon_size,
+                })
+                .color(entry.kind.get_color())
+                .border_radius_uniform(3.0)
+                .build(),
+            label: Text::new(entry.kind.get_label().to_string(), FONT_SIZE),
+            name: Text::new(entry.name.clone(), FONT_SIZE),
+            entry,
+            thumbnails,
+            offset: Offset::default(),
+            z: 0.0,
+            size: Size {
+                width,
+                height: ROW_HEIGHT,
+            },
+        }
+    }
+}
+
+impl UIElement for AssetRow {
+    fn render(&mut self, scene: &mut Scene) {
+        let texture = self.thumbnails.borrow().get(&self.entry.path);
+        self.icon.set_texture(texture);
+        PlaneRenderer::render(&self.icon);
+        if texture.is_none() {
+            self.label.render(scene);
+        }
+        self.name.render(scene);
+    }
+
+    fn handle_events(
+        &mut self,
+        _: &mut Scene,
+        _: &mut dyn UIWindow,
+        _: &mut glfw::Glfw,
+        _: &glfw::WindowEvent,
+    ) -> bool {
+        false
+    }
+
+    fn add_children(&mut self, _: Vec<(Option<UIElementHandle>, Box<dyn UIElement>)>) {
+        panic!("AssetRow cannot have children");
+    }
+
+    fn add_child_to(
+        &mut self,
+        _: UIElementHandle,
+        _: Option<UIElementHandle>,
+        _: Box<dyn UIElement>,
+    ) {
+        panic!("AssetRow cannot have children");
+    }
+
+    fn contains_child(&self, _: &UIElementHandle) -> bool {
+        false
+    }
+
+    fn get_offset(&self) -> &Offset {
+        &self.offset
+    }
+
+    fn set_offset(&mut self, offset: Offset) {
+        self.offset = offset;
+        self.icon.set_position(Position {
+            x: offset.x + ICON_MARGIN,
+            y: offset.y + ICON_MARGIN,
+            z: self.z,
+        });
+        self.label
+            .set_offset(offset + (ICON_MARGIN, ROW_HEIGHT / 2.0 - FONT_SIZE / 2.0 - 2.0));
+        self.name
+            .set_offset(offset + (ROW_HEIGHT, ROW_HEIGHT / 2.0 - FONT_SIZE / 2.0 - 2.0));
+    }
+
+    fn get_size(&self) -> &Size {
+        &self.size
+    }
+
+    fn set_z_index(&mut self, z_index: f32) {
+        self.z = z_index;
+        self.icon.set_z_index(z_index);
+        self.label.set_z_index(z_index + 1.0);
+        self.name.set_z_index(z_index);
+    }
+}
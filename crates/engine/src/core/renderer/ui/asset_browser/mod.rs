@@ -0,0 +1,101 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::{HashMap, VecDeque},
+    path::PathBuf,
+    rc::Rc,
+    time::Instant,
+};
+
+use crate::core::{
+    model::ModelHandle,
+    renderer::{framebuffer::ColorFrameBuffer, plane::Plane, texture::Texture},
+    scene::Scene,
+};
+
+use super::{list_view::ListView, primitives::Position, text::Text, Offset, Size};
+
+pub mod asset_browser;
+mod asset_row;
+mod thumbnails;
+
+/// Kind of the drag payloads of the asset browser's rows, their value is the asset's path
+pub const ASSET_PAYLOAD: &str = "asset";
+
+/// Kind of an asset file, decided by its extension
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AssetKind {
+    Model,
+    Texture,
+    Animation,
+    Shader,
+    Config,
+    Other,
+}
+
+/// File listed by the `AssetBrowser`
+#[derive(Clone, Debug)]
+pub struct AssetEntry {
+    /// Path of the file including the browsed directory, e.g. `assets/models/tree.glb`
+    pub path: PathBuf,
+    /// Path relative to the browsed directory
+    pub name: String,
+    pub kind: AssetKind,
+}
+
+/// Called with the asset the user double-clicked
+pub type AssetHandler = Box<dyn Fn(&mut Scene, &AssetEntry)>;
+
+/// Lists the files below a directory with an icon for their kind, or a preview for textures
+/// and models. The list follows the files as they are added or removed.
+///
+/// Rows can be dragged into containers accepting `ASSET_PAYLOAD` or into the scene, see
+/// `UIRenderer::on_scene_drop`, double-clicking a row calls the open handler
+pub struct AssetBrowser {
+    root: PathBuf,
+    entries: Rc<RefCell<Vec<AssetEntry>>>,
+    thumbnails: Rc<RefCell<Thumbnails>>,
+    /// Width the rows are built with, follows the size of the list
+    row_width: Rc<Cell<f32>>,
+    list: ListView,
+    on_open: Option<AssetHandler>,
+    /// Row and glfw time of the last click, to detect double-clicks
+    last_click: Option<(usize, f64)>,
+    last_scan: Instant,
+}
+
+pub struct AssetBrowserBuilder {
+    root: PathBuf,
+    position: Position,
+    size: Size,
+    on_open: Option<AssetHandler>,
+}
+
+/// Previews of the listed textures and models by path, created for the rows that are shown
+struct Thumbnails {
+    previews: HashMap<PathBuf, Preview>,
+    /// Previews still to start, one is started per frame
+    queue: VecDeque<AssetEntry>,
+}
+
+enum Preview {
+    Queued,
+    Loading(ModelHandle),
+    /// Scaled down texture
+    Image(Texture),
+    /// Model rendered from above at an angle
+    Rendered(ColorFrameBuffer),
+    Unavailable,
+}
+
+/// Row of the asset browser showing the icon or preview and the name of an asset
+struct AssetRow {
+    entry: AssetEntry,
+    thumbnails: Rc<RefCell<Thumbnails>>,
+    offset: Offset,
+    z: f32,
+    size: Size,
+    icon: Plane,
+    /// Shown on the icon while there is no preview
+    label: Text,
+    name: Text,
+}
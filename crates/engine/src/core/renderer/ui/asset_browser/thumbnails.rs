@@ -0,0 +1,143 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    path::Path,
+};
+
+use cgmath::{InnerSpace, Matrix4, Point3, Vector3};
+use gl::types::GLuint;
+
+use crate::core::{
+    model::{Model, ModelBuilder},
+    renderer::{
+        framebuffer::{ColorFrameBuffer, FrameBuffer},
+        texture::Texture,
+    },
+};
+
+use super::{AssetEntry, AssetKind, Preview, Thumbnails};
+
+/// Width and height of the previews in pixels
+pub(super) const THUMBNAIL_SIZE: u32 = 64;
+/// Models are loaded by `ModelData::load` relative to this directory
+const MODELS_DIRECTORY: &str = "assets/models";
+
+impl Thumbnails {
+    pub(super) fn new() -> Self {
+        Self {
+            previews: HashMap::new(),
+            queue: VecDeque::new(),
+        }
+    }
+
+    /// Queues a preview of the asset if it is a texture or model without one
+    pub(super) fn request(&mut self, entry: &AssetEntry) {
+        if !matches!(entry.kind, AssetKind::Model | AssetKind::Texture)
+            || self.previews.contains_key(&entry.path)
+        {
+            return;
+        }
+        self.previews.insert(entry.path.clone(), Preview::Queued);
+        self.queue.push_back(entry.clone());
+    }
+
+    /// Gl id of the texture with the preview of the asset at `path`, if it is ready
+    pub(super) fn get(&self, path: &Path) -> Option<GLuint> {
+        match self.previews.get(path)? {
+            Preview::Image(texture) => Some(texture.id),
+            Preview::Rendered(framebuffer) => framebuffer.get_color_texture().map(|t| t.id),
+            _ => None,
+        }
+    }
+
+    /// Drops the previews of assets that are no longer listed
+    pub(super) fn retain(&mut self, entries: &[AssetEntry]) {
+        self.previews
+            .retain(|path, _| entries.iter().any(|entry| &entry.path == path));
+        self.queue
+            .retain(|queued| entries.iter().any(|entry| entry.path == queued.path));
+    }
+
+    /// Starts the next queued preview and renders the models that finished loading
+    pub(super) fn update(&mut self) {
+        if let Some(entry) = self.queue.pop_front() {
+            let preview = match entry.kind {
+                AssetKind::Texture => Thumbnails::load_image(&entry.path),
+                AssetKind::Model => match entry.path.strip_prefix(MODELS_DIRECTORY) {
+                    Ok(path) => {
+                        Preview::Loading(ModelBuilder::new(&path.to_string_lossy()).build_async())
+                    }
+                    // the model loader only reads below the models directory
+                    Err(_) => Preview::Unavailable,
+                },
+                _ => Preview::Unavailable,
+            };
+            self.previews.insert(entry.path, preview);
+        }
+
+        for preview in self.previews.values_mut() {
+            let Preview::Loading(handle) = preview else {
+                continue;
+            };
+            if let Some(model) = handle.poll() {
+                *preview = Preview::Rendered(Thumbnails::render_model(model));
+            } else if !handle.is_loading() {
+                *preview = Preview::Unavailable;
+            }
+        }
+    }
+
+    fn load_image(path: &Path) -> Preview {
+        match image::open(path) {
+            Ok(image) => {
+                let image = image.thumbnail(THUMBNAIL_SIZE, THUMBNAIL_SIZE).to_rgba8();
+                let texture = Texture::new();
+                texture.load_from_data(image.width(), image.height(), image.into_raw());
+                Preview::Image(texture)
+            }
+            Err(err) => {
+                log::warn!("Failed to load preview of {}: {}", path.display(), err);
+                Preview::Unavailable
+            }
+        }
+    }
+
+    /// Renders the model from the front and above into a framebuffer of its own
+    fn render_model(model: &Model) -> ColorFrameBuffer {
+        let framebuffer = ColorFrameBuffer::new(THUMBNAIL_SIZE, THUMBNAIL_SIZE);
+        let (min, max) = model.get_bounds();
+        let center = Point3::new(
+            (min.x + max.x) / 2.0,
+            (min.y + max.y) / 2.0,
+            (min.z + max.z) / 2.0,
+        );
+        let radius = ((max - min).magnitude() / 2.0).max(0.01);
+        let eye = center + Vector3::new(0.6, 0.5, 1.0).normalize() * radius * 2.5;
+        let view = Matrix4::look_at_rh(eye, center, Vector3::unit_y());
+        // flipped vertically so the first row of the texture is the top of the image, like
+        // the rows of a loaded image
+        let projection = Matrix4::from_nonuniform_scale(1.0, -1.0, 1.0)
+            * cgmath::perspective(cgmath::Deg(30.0), 1.0, radius * 0.5, radius * 5.0);
+
+        let previous_framebuffer = FrameBuffer::get_bound_id();
+        let mut previous_viewport = [0; 4];
+        unsafe {
+            gl::GetIntegerv(gl::VIEWPORT, previous_viewport.as_mut_ptr());
+        }
+        framebuffer.bind();
+        unsafe {
+            gl::ClearBufferfv(gl::COLOR, 0, [0.25f32, 0.25, 0.25, 1.0].as_ptr());
+            gl::Clear(gl::DEPTH_BUFFER_BIT);
+        }
+        model.render(&eye, &Matrix4::from_scale(1.0), &(projection * view));
+        unsafe {
+            FrameBuffer::bind_id(previous_framebuffer);
+            gl::Viewport(
+                previous_viewport[0],
+                previous_viewport[1],
+                previous_viewport[2],
+                previous_viewport[3],
+            );
+        }
+        framebuffer
+    }
+}
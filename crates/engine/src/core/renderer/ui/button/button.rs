@@ -5,7 +5,7 @@ use crate::core::{
         plane::{PlaneBuilder, PlaneRenderer},
         ui::{
             primitives::{Position, Region},
-            Offset, Size, UIElement, UIElementHandle,
+            Offset, Size, UIElement, UIElementHandle, UIWindow,
         },
     },
     scene::Scene,
@@ -32,7 +32,7 @@ impl UIElement for Button {
     fn handle_events(
         &mut self,
         scene: &mut Scene,
-        window: &mut glfw::Window,
+        window: &mut dyn UIWindow,
         _: &mut glfw::Glfw,
         event: &glfw::WindowEvent,
     ) -> bool {
@@ -50,7 +50,7 @@ impl UIElement for Button {
             glfw::WindowEvent::CursorPos(x, y) => {
                 if region.contains(*x as f32, *y as f32) {
                     if !self.is_hovering {
-                        window.set_cursor(Some(glfw::Cursor::standard(glfw::StandardCursor::Hand)));
+                        window.set_cursor(Some(glfw::StandardCursor::Hand));
                         self.is_hovering = true;
                         self.plane.set_color((0.3, 0.4, 0.6, 1.0));
                     }
@@ -3,11 +3,17 @@ use std::collections::BTreeMap;
 use crate::core::{
     renderer::{
         plane::{PlaneBuilder, PlaneRenderer},
-        ui::{primitives::Position, Offset, Size, UIElement, UIElementHandle},
+        ui::{
+            drag::{DragPayload, Draggable, DropFilter, DropHandler},
+            primitives::{Position, Region},
+            Offset, Size, UIElement, UIElementHandle, UIWindow,
+        },
     },
     scene::Scene,
 };
 
+const DROP_HIGHLIGHT_COLOR: (f32, f32, f32, f32) = (0.3, 0.6, 0.3, 0.3);
+
 use super::{Container, ContainerBuilder, Direction};
 
 impl Container {
@@ -25,7 +31,12 @@ impl Container {
                 .border_color((0.0, 0.0, 0.0, 0.0))
                 .build(),
             with_end_gap: true,
+            min_size: Size::default(),
             direction: Direction::Vertical,
+            accepts: None,
+            on_drop: None,
+            drop_pending: false,
+            drop_hovered: false,
         }
     }
 
@@ -33,6 +44,35 @@ impl Container {
         self.with_end_gap = with_end_gap;
     }
 
+    /// Lets dragged elements whose payload passes `accepts` be dropped into the container
+    pub fn accept_drops(&mut self, accepts: DropFilter) {
+        self.accepts = Some(accepts);
+    }
+
+    /// Called with the payload of every element dropped into the container
+    pub fn on_drop(&mut self, on_drop: DropHandler) {
+        self.on_drop = Some(on_drop);
+    }
+
+    /// Payload of the dragged element if it is above the container and accepted by it
+    fn get_acceptable_drop(&self, x: f32, y: f32) -> Option<DragPayload> {
+        let accepts = self.accepts.as_ref()?;
+        let payload = Draggable::get_dragged_payload()?;
+        let region = Region::new_with_offset(self.position, self.size, self.offset);
+        (region.contains(x, y) && accepts(&payload)).then_some(payload)
+    }
+
+    fn set_drop_hovered(&mut self, hovered: bool) {
+        if self.drop_hovered != hovered {
+            self.drop_hovered = hovered;
+            self.plane.set_color(if hovered {
+                DROP_HIGHLIGHT_COLOR
+            } else {
+                (0.0, 0.0, 0.0, 0.0)
+            });
+        }
+    }
+
     pub fn set_position(&mut self, position: Position) {
         self.position = position;
         self.plane.set_position(&self.position + &self.offset);
@@ -60,6 +100,7 @@ impl UIElement for Container {
                 if !self.with_end_gap {
                     self.size.width -= self.gap;
                 }
+                self.size.width = self.size.width.max(self.min_size.width);
             }
             Direction::Vertical => {
                 let mut y_offset = self.gap;
@@ -75,6 +116,7 @@ impl UIElement for Container {
                 if !self.with_end_gap {
                     self.size.height -= self.gap;
                 }
+                self.size.height = self.size.height.max(self.min_size.height);
             }
         }
     }
@@ -103,7 +145,7 @@ impl UIElement for Container {
     fn handle_events(
         &mut self,
         scene: &mut Scene,
-        window: &mut glfw::Window,
+        window: &mut dyn UIWindow,
         glfw: &mut glfw::Glfw,
         event: &glfw::WindowEvent,
     ) -> bool {
@@ -113,7 +155,39 @@ impl UIElement for Container {
                 return true;
             }
         }
-        false
+        if self.accepts.is_none() {
+            return false;
+        }
+        match event {
+            glfw::WindowEvent::MouseButton(glfw::MouseButton::Button1, glfw::Action::Press, _) => {
+                // a drop that was never delivered must not catch the next one
+                self.drop_pending = false;
+                false
+            }
+            glfw::WindowEvent::CursorPos(x, y) => {
+                let hovered = self.get_acceptable_drop(*x as f32, *y as f32).is_some();
+                self.set_drop_hovered(hovered);
+                false
+            }
+            glfw::WindowEvent::MouseButton(
+                glfw::MouseButton::Button1,
+                glfw::Action::Release,
+                _,
+            ) => {
+                self.set_drop_hovered(false);
+                let (x, y) = window.get_cursor_pos();
+                let Some(payload) = self.get_acceptable_drop(x as f32, y as f32) else {
+                    return false;
+                };
+                self.drop_pending = true;
+                Draggable::accept_drop();
+                if let Some(on_drop) = &self.on_drop {
+                    on_drop(scene, &payload);
+                }
+                true
+            }
+            _ => false,
+        }
     }
 
     fn add_children(&mut self, children: Vec<(Option<UIElementHandle>, Box<dyn UIElement>)>) {
@@ -147,6 +221,12 @@ impl UIElement for Container {
         &self.size
     }
 
+    fn set_size(&mut self, size: Size) {
+        self.min_size = size;
+        self.size = size;
+        self.plane.set_size(size);
+    }
+
     fn contains_child(&self, handle: &UIElementHandle) -> bool {
         if self.children.contains_key(handle) {
             return true;
@@ -188,6 +268,40 @@ impl UIElement for Container {
             child.set_z_index(z_index + 1.0);
         }
     }
+
+    fn take_dragged(&mut self) -> Option<(UIElementHandle, Box<dyn UIElement>)> {
+        if let Some(handle) = self
+            .children
+            .iter()
+            .find(|(_, child)| child.is_dragging())
+            .map(|(handle, _)| *handle)
+        {
+            return self.children.remove(&handle).map(|child| (handle, child));
+        }
+        self.children
+            .values_mut()
+            .find_map(|child| child.take_dragged())
+    }
+
+    fn receive_drop(
+        &mut self,
+        handle: UIElementHandle,
+        element: Box<dyn UIElement>,
+    ) -> Option<Box<dyn UIElement>> {
+        if self.drop_pending {
+            self.drop_pending = false;
+            self.add_children(vec![(Some(handle), element)]);
+            return None;
+        }
+        let mut element = element;
+        for child in self.children.values_mut() {
+            match child.receive_drop(handle, element) {
+                Some(rejected) => element = rejected,
+                None => return None,
+            }
+        }
+        Some(element)
+    }
 }
 
 impl ContainerBuilder {
@@ -198,6 +312,8 @@ impl ContainerBuilder {
             children: Vec::new(),
             with_end_gap: true,
             direction: Direction::Vertical,
+            accepts: None,
+            on_drop: None,
         }
     }
 
@@ -226,10 +342,24 @@ impl ContainerBuilder {
         self
     }
 
+    /// Lets dragged elements whose payload passes `accepts` be dropped into the container
+    pub fn accept_drops(mut self, accepts: DropFilter) -> Self {
+        self.accepts = Some(accepts);
+        self
+    }
+
+    /// Called with the payload of every element dropped into the container
+    pub fn on_drop(mut self, on_drop: DropHandler) -> Self {
+        self.on_drop = Some(on_drop);
+        self
+    }
+
     pub fn build(self) -> Container {
         let mut container = Container::new(self.position, self.size);
         container.with_end_gap = self.with_end_gap;
         container.direction = self.direction;
+        container.accepts = self.accepts;
+        container.on_drop = self.on_drop;
         container.add_children(self.children);
         container
     }
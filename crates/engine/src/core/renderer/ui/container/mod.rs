@@ -2,7 +2,11 @@ use std::collections::BTreeMap;
 
 use crate::core::renderer::plane::Plane;
 
-use super::{primitives::Position, Offset, Size, UIElement, UIElementHandle};
+use super::{
+    drag::{DropFilter, DropHandler},
+    primitives::Position,
+    Offset, Size, UIElement, UIElementHandle,
+};
 
 pub mod container;
 
@@ -21,6 +25,15 @@ pub struct Container {
     direction: Direction,
 
     with_end_gap: bool,
+    /// Size given by a layout, the container grows past it along its direction when its
+    /// children need more room
+    min_size: Size,
+    /// Decides which dragged elements may be dropped into the container, drops are ignored without it
+    accepts: Option<DropFilter>,
+    on_drop: Option<DropHandler>,
+    /// An element was dropped on the container and is about to be moved into it
+    drop_pending: bool,
+    drop_hovered: bool,
 }
 
 pub struct ContainerBuilder {
@@ -29,4 +42,6 @@ pub struct ContainerBuilder {
     children: Vec<(Option<UIElementHandle>, Box<dyn UIElement>)>,
     with_end_gap: bool,
     direction: Direction,
+    accepts: Option<DropFilter>,
+    on_drop: Option<DropHandler>,
 }
@@ -0,0 +1,256 @@
+use std::{collections::HashMap, error::Error, fs, path::Path, sync::Mutex};
+
+use glfw::{PixelImage, StandardCursor};
+use image::{Rgba, RgbaImage};
+use lazy_static::lazy_static;
+
+use crate::core::renderer::{
+    plane::{PlaneBuilder, PlaneRenderer},
+    texture::Texture,
+    ui::primitives::{Position, Size},
+};
+
+use super::{CursorFileEntry, CursorImage, CursorTheme, CursorThemeFile, SoftwareCursor};
+
+/// The software cursor is drawn above the rest of the UI, including dragged elements
+const CURSOR_Z_INDEX: f32 = 99.0;
+/// Size of the fallback arrow in pixels
+const ARROW_WIDTH: u32 = 12;
+const ARROW_HEIGHT: u32 = 18;
+
+lazy_static! {
+    static ref CURSOR: Mutex<CursorState> = Mutex::new(CursorState {
+        theme: CursorTheme::default(),
+        theme_version: 0,
+        shape: None,
+        applied: None,
+        software: false,
+    });
+}
+
+struct CursorState {
+    theme: CursorTheme,
+    /// Incremented whenever the theme is replaced
+    theme_version: u64,
+    /// Shape the UI asked for last, `None` for the default arrow
+    shape: Option<StandardCursor>,
+    /// Shape and theme version last applied to the window
+    applied: Option<(Option<StandardCursor>, u64)>,
+    software: bool,
+}
+
+impl CursorTheme {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads a theme saved with `save` from the RON file at `path`
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<CursorTheme, Box<dyn Error>> {
+        let path = path.as_ref();
+        let file: CursorThemeFile = ron::from_str(&fs::read_to_string(path)?)?;
+        let directory = path.parent().unwrap_or(Path::new(""));
+        let mut theme = CursorTheme::new();
+        for (shape, entry) in file.cursors {
+            let image = image::open(directory.join(&entry.path))?.to_rgba8();
+            theme = theme.with_image(shape, image, entry.hotspot);
+        }
+        Ok(theme)
+    }
+
+    /// Writes the theme to `path` as RON, the images are written as PNGs next to it
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        let path = path.as_ref();
+        let directory = path.parent().unwrap_or(Path::new(""));
+        let mut file = CursorThemeFile {
+            cursors: Default::default(),
+        };
+        for (shape, image) in &self.images {
+            let image_path = format!("{:?}.png", shape).to_lowercase();
+            image.image.save(directory.join(&image_path))?;
+            file.cursors.insert(
+                *shape,
+                CursorFileEntry {
+                    path: image_path,
+                    hotspot: image.hotspot,
+                },
+            );
+        }
+        fs::write(
+            path,
+            ron::ser::to_string_pretty(&file, ron::ser::PrettyConfig::default())?,
+        )?;
+        Ok(())
+    }
+
+    /// Uses `image` for `shape`, `hotspot` is the pixel at the cursor position
+    pub fn with_image(
+        mut self,
+        shape: StandardCursor,
+        image: RgbaImage,
+        hotspot: (u32, u32),
+    ) -> Self {
+        let hotspot = (
+            hotspot.0.min(image.width().saturating_sub(1)),
+            hotspot.1.min(image.height().saturating_sub(1)),
+        );
+        self.images.insert(shape, CursorImage { image, hotspot });
+        self
+    }
+
+    pub fn get_image(&self, shape: StandardCursor) -> Option<&CursorImage> {
+        self.images.get(&shape)
+    }
+
+    /// Replaces the theme of the UI, the window's cursor changes with the next `apply`
+    pub fn set(theme: CursorTheme) {
+        let mut cursor = CURSOR.lock().unwrap();
+        cursor.theme = theme;
+        cursor.theme_version += 1;
+    }
+
+    /// Shape the UI asked for last, `None` for the default arrow
+    pub fn get_shape() -> Option<StandardCursor> {
+        CURSOR.lock().unwrap().shape
+    }
+
+    /// Shows `shape` on `window`, with the theme's image if it has one. `None` shows the
+    /// default arrow. Only calls into glfw when the shape or the theme changed
+    pub fn apply(window: &mut glfw::Window, shape: Option<StandardCursor>) {
+        let mut cursor = CURSOR.lock().unwrap();
+        cursor.shape = shape;
+        let applied = Some((shape, cursor.theme_version));
+        if cursor.applied == applied {
+            return;
+        }
+        cursor.applied = applied;
+        let image = cursor
+            .theme
+            .get_image(shape.unwrap_or(StandardCursor::Arrow));
+        let glfw_cursor = match (image, shape) {
+            (Some(image), _) => Some(glfw::Cursor::create_from_pixels(
+                image.to_pixel_image(),
+                image.hotspot.0,
+                image.hotspot.1,
+            )),
+            (None, Some(shape)) => Some(glfw::Cursor::standard(shape)),
+            (None, None) => None,
+        };
+        window.set_cursor(glfw_cursor);
+    }
+}
+
+impl CursorImage {
+    pub fn get_image(&self) -> &RgbaImage {
+        &self.image
+    }
+
+    pub fn get_hotspot(&self) -> (u32, u32) {
+        self.hotspot
+    }
+
+    fn to_pixel_image(&self) -> PixelImage {
+        PixelImage {
+            width: self.image.width(),
+            height: self.image.height(),
+            // glfw reads the pixels as rgba bytes
+            pixels: self
+                .image
+                .pixels()
+                .map(|pixel| u32::from_ne_bytes(pixel.0))
+                .collect(),
+        }
+    }
+}
+
+impl SoftwareCursor {
+    pub fn new() -> Self {
+        let mut plane = PlaneBuilder::new().build();
+        plane.set_z_index(CURSOR_Z_INDEX);
+        Self {
+            plane,
+            textures: HashMap::new(),
+            theme_version: 0,
+            position: (0.0, 0.0),
+        }
+    }
+
+    /// Draws the cursor from the next frame on, the application hides the system cursor itself
+    pub fn set_enabled(enabled: bool) {
+        CURSOR.lock().unwrap().software = enabled;
+    }
+
+    pub fn is_enabled() -> bool {
+        CURSOR.lock().unwrap().software
+    }
+
+    /// Moves the cursor to `position` in UI units, kept inside the window of `window_size`
+    pub fn set_position(&mut self, position: (f32, f32), window_size: Size) {
+        self.position = (
+            position.0.clamp(0.0, window_size.width),
+            position.1.clamp(0.0, window_size.height),
+        );
+    }
+
+    /// Draws the image of the current shape at the cursor position
+    pub fn render(&mut self) {
+        let key = {
+            let cursor = CURSOR.lock().unwrap();
+            if cursor.theme_version != self.theme_version {
+                self.textures.clear();
+                self.theme_version = cursor.theme_version;
+            }
+            let shape = cursor.shape.unwrap_or(StandardCursor::Arrow);
+            let image = cursor.theme.get_image(shape);
+            let key = image.map(|_| shape);
+            self.textures.entry(key).or_insert_with(|| {
+                let image = image.cloned().unwrap_or_else(|| CursorImage {
+                    image: SoftwareCursor::create_arrow(),
+                    hotspot: (0, 0),
+                });
+                let texture = Texture::new();
+                texture.load_from_data(
+                    image.image.width(),
+                    image.image.height(),
+                    image.image.as_raw().clone(),
+                );
+                (texture, image)
+            });
+            key
+        };
+        let Some((texture, image)) = self.textures.get(&key) else {
+            return;
+        };
+        self.plane.set_position(Position {
+            x: self.position.0 - image.hotspot.0 as f32,
+            y: self.position.1 - image.hotspot.1 as f32,
+            z: CURSOR_Z_INDEX,
+        });
+        self.plane.set_size(Size {
+            width: image.image.width() as f32,
+            height: image.image.height() as f32,
+        });
+        self.plane.set_texture(Some(texture.id));
+        PlaneRenderer::render(&self.plane);
+    }
+
+    /// White arrow with a black outline pointing to the top left corner
+    fn create_arrow() -> RgbaImage {
+        let inside = |x: i32, y: i32| {
+            // the tip is at the top left, the tail leaves the bottom at a slant
+            x >= 0 && y >= 0 && y < ARROW_HEIGHT as i32 && x <= y * 2 / 3 && x + y / 3 < 12
+        };
+        RgbaImage::from_fn(ARROW_WIDTH, ARROW_HEIGHT, |x, y| {
+            let (x, y) = (x as i32, y as i32);
+            if !inside(x, y) {
+                Rgba([0, 0, 0, 0])
+            } else if [(-1, 0), (1, 0), (0, -1), (0, 1)]
+                .iter()
+                .all(|(dx, dy)| inside(x + dx, y + dy))
+            {
+                Rgba([255, 255, 255, 255])
+            } else {
+                Rgba([0, 0, 0, 255])
+            }
+        })
+    }
+}
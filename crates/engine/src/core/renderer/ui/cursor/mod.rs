@@ -0,0 +1,49 @@
+use std::collections::{BTreeMap, HashMap};
+
+use glfw::StandardCursor;
+use image::RgbaImage;
+use serde::{Deserialize, Serialize};
+
+use crate::core::renderer::{plane::Plane, texture::Texture};
+
+pub mod cursor;
+
+/// Images replacing the standard cursor shapes the UI switches between, shapes without an
+/// image keep the system's cursor. Applied to the window with `CursorTheme::apply`, and drawn
+/// by the `SoftwareCursor` while it is enabled
+#[derive(Clone, Default)]
+pub struct CursorTheme {
+    images: HashMap<StandardCursor, CursorImage>,
+}
+
+#[derive(Clone)]
+pub struct CursorImage {
+    image: RgbaImage,
+    /// Pixel of the image at the cursor position, from the top left corner
+    hotspot: (u32, u32),
+}
+
+/// Cursor theme as stored in a RON file, with the image paths relative to the file
+#[derive(Serialize, Deserialize)]
+struct CursorThemeFile {
+    cursors: BTreeMap<StandardCursor, CursorFileEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct CursorFileEntry {
+    path: String,
+    hotspot: (u32, u32),
+}
+
+/// Cursor drawn by the `UIRenderer` on top of the UI while `SoftwareCursor::set_enabled` is
+/// set, for when the system cursor is hidden, e.g. while the camera captures it but a UI is
+/// open. Draws the theme's image of the current shape, or a plain arrow if it has none
+pub struct SoftwareCursor {
+    plane: Plane,
+    /// Uploaded images per shape of the theme, `None` for the fallback arrow
+    textures: HashMap<Option<StandardCursor>, (Texture, CursorImage)>,
+    /// Theme version the textures were uploaded for
+    theme_version: u64,
+    /// Cursor position in UI units
+    position: (f32, f32),
+}
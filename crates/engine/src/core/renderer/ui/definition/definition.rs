@@ -0,0 +1,208 @@
+use std::{
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use crate::core::{
+    renderer::ui::{primitives::Layout, UIElement, UIRenderer, UI},
+    scene::Scene,
+    utils::DataSource,
+};
+
+use super::{UIBindings, UIDefinition, UIDefinitionFile};
+
+/// Shortest time between two checks of the file
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+impl UIBindings {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Binds `DragValue`s named `name` to `value`
+    pub fn number(mut self, name: &str, value: DataSource<f32>) -> Self {
+        self.numbers.insert(name.to_owned(), value);
+        self
+    }
+
+    /// Binds `Input`s named `name` to `value`
+    pub fn text(mut self, name: &str, value: DataSource<String>) -> Self {
+        self.texts.insert(name.to_owned(), value);
+        self
+    }
+
+    /// Runs `action` when a `Button` with the action `name` is clicked
+    pub fn action<F: Fn(&mut Scene) + 'static>(mut self, name: &str, action: F) -> Self {
+        self.actions.insert(name.to_owned(), Rc::new(action));
+        self
+    }
+
+    /// Builds the element described by `definition`. Names without a binding are shown as a
+    /// text, so a typo in the file is visible right away
+    pub fn build(&self, definition: &UIDefinition) -> Box<dyn UIElement> {
+        match definition {
+            UIDefinition::Panel {
+                title,
+                position,
+                size,
+                collapsible,
+                children,
+                ..
+            } => {
+                let (position, size, collapsible) = (*position, *size, *collapsible);
+                let children = self.build_children(children);
+                UI::panel(title, move |mut builder| {
+                    builder = builder.position(position.0, position.1, 0.0);
+                    if let Some((width, height)) = size {
+                        builder = builder.size(width, height);
+                    }
+                    if collapsible {
+                        builder = builder.collapsible();
+                    }
+                    for child in children {
+                        builder = builder.add_child(None, child);
+                    }
+                    builder
+                })
+            }
+            UIDefinition::Container { children } => {
+                let children = self.build_children(children);
+                UI::container(move |mut builder| {
+                    for child in children {
+                        builder = builder.add_child(None, child);
+                    }
+                    builder
+                })
+            }
+            UIDefinition::Text { text, size } => UI::text(text, *size, |text| text),
+            UIDefinition::Button {
+                label,
+                action,
+                size,
+            } => {
+                let Some(action) = self.actions.get(action).cloned() else {
+                    return Self::missing("action", action);
+                };
+                let size = *size;
+                UI::button(
+                    label,
+                    Box::new(move |scene| action(scene)),
+                    move |builder| match size {
+                        Some((width, height)) => builder.size(width, height),
+                        None => builder,
+                    },
+                )
+            }
+            UIDefinition::DragValue {
+                value,
+                speed,
+                range,
+            } => {
+                let Some(value) = self.numbers.get(value) else {
+                    return Self::missing("number", value);
+                };
+                let (speed, range) = (*speed, *range);
+                UI::drag_value(value.clone(), move |mut builder| {
+                    if let Some(speed) = speed {
+                        builder = builder.speed(speed);
+                    }
+                    if let Some((min, max)) = range {
+                        builder = builder.range(min, max);
+                    }
+                    builder
+                })
+            }
+            UIDefinition::Input { value, size } => {
+                let Some(value) = self.texts.get(value) else {
+                    return Self::missing("text", value);
+                };
+                let size = *size;
+                UI::input(value.clone(), move |builder| match size {
+                    Some((width, height)) => builder.size(width, height),
+                    None => builder,
+                })
+            }
+        }
+    }
+
+    fn build_children(&self, children: &[UIDefinition]) -> Vec<Box<dyn UIElement>> {
+        children.iter().map(|child| self.build(child)).collect()
+    }
+
+    fn missing(kind: &str, name: &str) -> Box<dyn UIElement> {
+        log::warn!("The UI definition uses the unbound {} '{}'", kind, name);
+        UI::text(&format!("unbound {} '{}'", kind, name), 16.0, |text| text)
+    }
+}
+
+impl UIDefinitionFile {
+    /// Watches the file at `path`, nothing is built until the first `poll`
+    pub fn new<P: AsRef<Path>>(path: P, bindings: UIBindings) -> Self {
+        Self {
+            path: PathBuf::from(path.as_ref()),
+            bindings,
+            modified: None,
+            last_poll: None,
+            handles: Vec::new(),
+        }
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Vec<UIDefinition>, Box<dyn Error>> {
+        let content = fs::read_to_string(path)?;
+        Ok(ron::from_str(&content)?)
+    }
+
+    /// Rebuilds the elements in `ui` if the file changed since the last call, returns whether it
+    /// did. Called once per frame
+    pub fn poll(&mut self, ui: &mut UIRenderer) -> bool {
+        if self
+            .last_poll
+            .is_some_and(|last_poll| last_poll.elapsed() < POLL_INTERVAL)
+        {
+            return false;
+        }
+        self.last_poll = Some(Instant::now());
+        let modified = fs::metadata(&self.path)
+            .and_then(|metadata| metadata.modified())
+            .ok();
+        if modified.is_none() || modified == self.modified {
+            return false;
+        }
+        self.modified = modified;
+        match Self::load(&self.path) {
+            Ok(definitions) => {
+                self.rebuild(ui, &definitions);
+                log::info!("Loaded UI definition {}", self.path.display());
+                true
+            }
+            Err(error) => {
+                log::warn!(
+                    "Loading UI definition {} failed, keeping the previous version\n{}",
+                    self.path.display(),
+                    error
+                );
+                false
+            }
+        }
+    }
+
+    fn rebuild(&mut self, ui: &mut UIRenderer, definitions: &[UIDefinition]) {
+        for handle in self.handles.drain(..) {
+            ui.remove(&handle);
+        }
+        for definition in definitions {
+            let element = self.bindings.build(definition);
+            let handle = match definition {
+                UIDefinition::Panel {
+                    anchor: Some(anchor),
+                    ..
+                } => ui.add_with_layout(element, Layout::new(*anchor)),
+                _ => ui.add(element),
+            };
+            self.handles.push(handle);
+        }
+    }
+}
@@ -0,0 +1,88 @@
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    rc::Rc,
+    time::{Instant, SystemTime},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::{scene::Scene, utils::DataSource};
+
+use super::primitives::{Anchor, UIElementHandle};
+
+mod definition;
+
+/// Declarative description of a UI element, see `UIDefinitionFile`. Values and buttons refer to
+/// the names they were registered with in `UIBindings`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub enum UIDefinition {
+    Panel {
+        title: String,
+        #[serde(default)]
+        position: (f32, f32),
+        #[serde(default)]
+        size: Option<(f32, f32)>,
+        #[serde(default)]
+        collapsible: bool,
+        /// Window point a top level panel stays anchored to when the window is resized
+        #[serde(default)]
+        anchor: Option<Anchor>,
+        #[serde(default)]
+        children: Vec<UIDefinition>,
+    },
+    Container {
+        #[serde(default)]
+        children: Vec<UIDefinition>,
+    },
+    Text {
+        text: String,
+        #[serde(default = "default_text_size")]
+        size: f32,
+    },
+    Button {
+        label: String,
+        action: String,
+        #[serde(default)]
+        size: Option<(f32, f32)>,
+    },
+    DragValue {
+        value: String,
+        #[serde(default)]
+        speed: Option<f64>,
+        #[serde(default)]
+        range: Option<(f64, f64)>,
+    },
+    Input {
+        value: String,
+        #[serde(default)]
+        size: Option<(f32, f32)>,
+    },
+}
+
+fn default_text_size() -> f32 {
+    16.0
+}
+
+/// Action a button of a `UIDefinition` runs when it is clicked
+pub type UIAction = Rc<dyn Fn(&mut Scene)>;
+
+/// Named values and actions the elements of a `UIDefinition` bind to
+#[derive(Default)]
+pub struct UIBindings {
+    numbers: HashMap<String, DataSource<f32>>,
+    texts: HashMap<String, DataSource<String>>,
+    actions: HashMap<String, UIAction>,
+}
+
+/// UI built from a RON file with a list of `UIDefinition`s, rebuilt whenever the file changes
+/// so debug UIs can be edited while the engine is running. A file that fails to parse keeps the
+/// previous UI, a missing one shows nothing
+pub struct UIDefinitionFile {
+    path: PathBuf,
+    bindings: UIBindings,
+    modified: Option<SystemTime>,
+    last_poll: Option<Instant>,
+    /// Top level elements the file added to the `UIRenderer`
+    handles: Vec<UIElementHandle>,
+}
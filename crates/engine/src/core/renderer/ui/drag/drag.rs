@@ -0,0 +1,205 @@
+use std::sync::Mutex;
+
+use lazy_static::lazy_static;
+
+use crate::core::{
+    renderer::{
+        plane::{PlaneBuilder, PlaneRenderer},
+        ui::{
+            primitives::{Position, Region},
+            Offset, Size, UIElement, UIElementHandle, UIWindow,
+        },
+    },
+    scene::Scene,
+};
+
+use super::{DragPayload, DragState, Draggable};
+
+/// Distance in pixels the cursor has to move with the button held before a drag starts
+const DRAG_THRESHOLD: f32 = 4.0;
+/// The dragged element is drawn above the rest of the UI
+const DRAG_Z_INDEX: f32 = 90.0;
+
+lazy_static! {
+    static ref DRAG: Mutex<Option<DragState>> = Mutex::new(None);
+}
+
+impl DragPayload {
+    pub fn new(kind: &str, value: &str) -> Self {
+        Self {
+            kind: kind.to_string(),
+            value: value.to_string(),
+        }
+    }
+}
+
+impl Draggable {
+    pub fn new(payload: DragPayload, child: Box<dyn UIElement>) -> Self {
+        Self {
+            id: UIElementHandle::new(),
+            payload,
+            offset: *child.get_offset(),
+            z_index: 0.0,
+            grab: None,
+            ghost: PlaneBuilder::new()
+                .size(*child.get_size())
+                .color((0.3, 0.4, 0.6, 0.5))
+                .border_radius_uniform(5.0)
+                .build(),
+            child,
+        }
+    }
+
+    /// Payload of the element being dragged, if any
+    pub fn get_dragged_payload() -> Option<DragPayload> {
+        DRAG.lock()
+            .unwrap()
+            .as_ref()
+            .map(|state| state.payload.clone())
+    }
+
+    /// Marks the drag in progress as accepted, the element is moved to the container that set
+    /// a pending drop once the mouse button is released
+    pub fn accept_drop() {
+        if let Some(state) = DRAG.lock().unwrap().as_mut() {
+            state.accepted = true;
+        }
+    }
+
+    /// Ends the drag in progress, returns whether it was dropped on an accepting container
+    pub fn finish_drag() -> bool {
+        DRAG.lock()
+            .unwrap()
+            .take()
+            .is_some_and(|state| state.accepted)
+    }
+}
+
+impl UIElement for Draggable {
+    fn render(&mut self, scene: &mut Scene) {
+        let cursor = DRAG
+            .lock()
+            .unwrap()
+            .as_ref()
+            .filter(|state| state.source == self.id)
+            .map(|state| state.cursor);
+        let (Some((x, y)), Some((grab_x, grab_y))) = (cursor, self.grab) else {
+            self.child.render(scene);
+            return;
+        };
+        // the element leaves its place and follows the cursor on top of the ghost plane
+        let ghost_offset = Offset {
+            x: x - grab_x,
+            y: y - grab_y,
+        };
+        self.ghost.set_size(*self.child.get_size());
+        self.ghost.set_position(Position {
+            x: ghost_offset.x,
+            y: ghost_offset.y,
+            z: DRAG_Z_INDEX,
+        });
+        PlaneRenderer::render(&self.ghost);
+        self.child.set_z_index(DRAG_Z_INDEX + 1.0);
+        self.child.set_offset(ghost_offset);
+        self.child.render(scene);
+        self.child.set_offset(self.offset);
+        self.child.set_z_index(self.z_index);
+    }
+
+    fn handle_events(
+        &mut self,
+        scene: &mut Scene,
+        window: &mut dyn UIWindow,
+        glfw: &mut glfw::Glfw,
+        event: &glfw::WindowEvent,
+    ) -> bool {
+        if !self.is_dragging() && self.child.handle_events(scene, window, glfw, event) {
+            return true;
+        }
+        let region = Region::new_with_offset(Position::default(), *self.get_size(), self.offset);
+        match event {
+            glfw::WindowEvent::MouseButton(glfw::MouseButton::Button1, glfw::Action::Press, _) => {
+                let (x, y) = window.get_cursor_pos();
+                let (x, y) = (x as f32, y as f32);
+                if region.contains(x, y) {
+                    self.grab = Some((x - self.offset.x, y - self.offset.y));
+                    return true;
+                }
+                false
+            }
+            glfw::WindowEvent::MouseButton(
+                glfw::MouseButton::Button1,
+                glfw::Action::Release,
+                _,
+            ) => {
+                self.grab = None;
+                false
+            }
+            glfw::WindowEvent::CursorPos(x, y) => {
+                let (x, y) = (*x as f32, *y as f32);
+                let mut drag = DRAG.lock().unwrap();
+                match drag.as_mut() {
+                    Some(state) if state.source == self.id => state.cursor = (x, y),
+                    None => {
+                        if let Some((grab_x, grab_y)) = self.grab {
+                            let (dx, dy) = (x - self.offset.x - grab_x, y - self.offset.y - grab_y);
+                            if dx * dx + dy * dy > DRAG_THRESHOLD * DRAG_THRESHOLD {
+                                *drag = Some(DragState {
+                                    source: self.id,
+                                    payload: self.payload.clone(),
+                                    cursor: (x, y),
+                                    accepted: false,
+                                });
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+                false
+            }
+            _ => false,
+        }
+    }
+
+    fn add_children(&mut self, children: Vec<(Option<UIElementHandle>, Box<dyn UIElement>)>) {
+        self.child.add_children(children);
+    }
+
+    fn add_child_to(
+        &mut self,
+        parent: UIElementHandle,
+        id: Option<UIElementHandle>,
+        element: Box<dyn UIElement>,
+    ) {
+        self.child.add_child_to(parent, id, element);
+    }
+
+    fn contains_child(&self, handle: &UIElementHandle) -> bool {
+        self.child.contains_child(handle)
+    }
+
+    fn get_offset(&self) -> &Offset {
+        &self.offset
+    }
+
+    fn set_offset(&mut self, offset: Offset) {
+        self.offset = offset;
+        self.child.set_offset(offset);
+    }
+
+    fn get_size(&self) -> &Size {
+        self.child.get_size()
+    }
+
+    fn set_z_index(&mut self, z_index: f32) {
+        self.z_index = z_index;
+        self.child.set_z_index(z_index);
+    }
+
+    fn is_dragging(&self) -> bool {
+        DRAG.lock()
+            .unwrap()
+            .as_ref()
+            .is_some_and(|state| state.source == self.id)
+    }
+}
@@ -0,0 +1,46 @@
+use crate::core::{renderer::plane::Plane, scene::Scene};
+
+use super::{Offset, UIElement, UIElementHandle};
+
+pub mod drag;
+
+/// Data carried by a dragged element, containers decide by it whether they accept the drop
+#[derive(Clone, Debug, PartialEq)]
+pub struct DragPayload {
+    pub kind: String,
+    pub value: String,
+}
+
+/// Decides whether a container accepts a dragged payload
+pub type DropFilter = Box<dyn Fn(&DragPayload) -> bool>;
+
+/// Called when a payload is dropped into a container
+pub type DropHandler = Box<dyn Fn(&mut Scene, &DragPayload)>;
+
+/// Called when a payload is dropped outside of the UI with the cursor in normalized device
+/// coordinates, as `Camera::get_ray_direction` takes them
+pub type SceneDropHandler = Box<dyn Fn(&mut Scene, &DragPayload, (f32, f32))>;
+
+/// Wraps an element so it can be dragged into containers that accept its payload.
+///
+/// While dragged the element follows the cursor over a ghost plane, when it is dropped on an
+/// accepting container it is moved there, otherwise it returns to where it was
+pub struct Draggable {
+    id: UIElementHandle,
+    payload: DragPayload,
+    child: Box<dyn UIElement>,
+    offset: Offset,
+    z_index: f32,
+    /// Cursor position relative to the element while the mouse button is held on it
+    grab: Option<(f32, f32)>,
+    ghost: Plane,
+}
+
+/// The drag in progress, shared between the dragged element and the containers below the cursor
+struct DragState {
+    source: UIElementHandle,
+    payload: DragPayload,
+    cursor: (f32, f32),
+    /// Set by the container the element was dropped on
+    accepted: bool,
+}
@@ -0,0 +1,320 @@
+use crate::core::{
+    renderer::{
+        plane::{PlaneBuilder, PlaneRenderer},
+        text::{Fonts, Text},
+        ui::{
+            primitives::{Position, Region},
+            Offset, Size, UIElement, UIElementHandle, UIWindow,
+        },
+    },
+    scene::Scene,
+    utils::DataSource,
+};
+
+use super::{DragNumber, DragValue, DragValueBuilder};
+
+/// Distance the cursor has to move before a press turns into a drag
+const DRAG_THRESHOLD: f32 = 2.0;
+const COARSE_FACTOR: f64 = 10.0;
+const FINE_FACTOR: f64 = 0.1;
+
+macro_rules! impl_drag_number {
+    ($($number:ty),*) => {
+        $(
+            impl DragNumber for $number {
+                fn to_f64(&self) -> f64 {
+                    *self as f64
+                }
+
+                fn from_f64(value: f64) -> Self {
+                    value.round() as $number
+                }
+            }
+        )*
+    };
+}
+
+impl_drag_number!(i32, i64, u32, u64, usize);
+
+impl DragNumber for f32 {
+    fn to_f64(&self) -> f64 {
+        *self as f64
+    }
+
+    fn from_f64(value: f64) -> Self {
+        value as f32
+    }
+}
+
+impl DragNumber for f64 {
+    fn to_f64(&self) -> f64 {
+        *self
+    }
+
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+}
+
+impl<T: DragNumber> DragValue<T> {
+    pub fn new(position: Position, size: Size, data_source: DataSource<T>) -> Self {
+        Self {
+            position,
+            size,
+            offset: Offset::default(),
+            data_source,
+            speed: 0.1,
+            precision: 2,
+            range: None,
+            drag: None,
+            dragged: false,
+            is_hovering: false,
+            editing: None,
+            text: Text::new(Fonts::RobotoMono, 0, 0, 0, 16.0, String::new()),
+            plane: PlaneBuilder::new()
+                .position(position)
+                .size(size)
+                .color((0.2, 0.2, 0.2, 1.0))
+                .border_radius_uniform(5.0)
+                .border_thickness(1.0)
+                .build(),
+        }
+    }
+
+    fn format_value(&self, value: f64) -> String {
+        format!("{:.*}", self.precision, value)
+    }
+
+    /// Clamps `value` to the range, rounds it to the precision and writes it to the data source
+    fn write_value(&self, value: f64) {
+        let value = match self.range {
+            Some((min, max)) => value.clamp(min, max),
+            None => value,
+        };
+        let scale = 10f64.powi(self.precision as i32);
+        self.data_source
+            .write(T::from_f64((value * scale).round() / scale));
+    }
+
+    /// Speed factor of the held modifier keys
+    fn get_modifier(window: &dyn UIWindow) -> f64 {
+        let pressed = |key| window.get_key(key) == glfw::Action::Press;
+        if pressed(glfw::Key::LeftShift) || pressed(glfw::Key::RightShift) {
+            COARSE_FACTOR
+        } else if pressed(glfw::Key::LeftControl)
+            || pressed(glfw::Key::RightControl)
+            || pressed(glfw::Key::LeftAlt)
+            || pressed(glfw::Key::RightAlt)
+        {
+            FINE_FACTOR
+        } else {
+            1.0
+        }
+    }
+
+    fn set_highlighted(&mut self, highlighted: bool) {
+        self.plane.set_color(if highlighted {
+            (0.3, 0.3, 0.3, 1.0)
+        } else {
+            (0.2, 0.2, 0.2, 1.0)
+        });
+    }
+
+    /// Leaves text mode, writing the typed value if it is a number
+    fn commit(&mut self) {
+        if let Some(content) = self.editing.take() {
+            if let Ok(value) = content.trim().parse::<f64>() {
+                self.write_value(value);
+            }
+        }
+        self.set_highlighted(self.is_hovering);
+    }
+}
+
+impl<T: DragNumber> UIElement for DragValue<T> {
+    fn render(&mut self, _: &mut Scene) {
+        PlaneRenderer::render(&self.plane);
+        let content = match &self.editing {
+            Some(content) => format!("{}_", content),
+            None => self.format_value(self.data_source.read().to_f64()),
+        };
+        self.text.set_content(&content);
+        self.text
+            .render_at(&(&self.position + &self.offset) + (5.0, 2.0, 1.0));
+    }
+
+    fn handle_events(
+        &mut self,
+        _: &mut Scene,
+        window: &mut dyn UIWindow,
+        _: &mut glfw::Glfw,
+        event: &glfw::WindowEvent,
+    ) -> bool {
+        let region = Region::new_with_offset(self.position, self.size, self.offset);
+        match event {
+            glfw::WindowEvent::MouseButton(glfw::MouseButton::Button1, glfw::Action::Press, _) => {
+                let (x, y) = window.get_cursor_pos();
+                if !region.contains(x as f32, y as f32) {
+                    if self.editing.is_some() {
+                        self.commit();
+                    }
+                    return false;
+                }
+                if self.editing.is_none() {
+                    self.drag = Some((x as f32, self.data_source.read().to_f64()));
+                    self.dragged = false;
+                }
+                true
+            }
+            glfw::WindowEvent::MouseButton(
+                glfw::MouseButton::Button1,
+                glfw::Action::Release,
+                _,
+            ) => {
+                if self.drag.take().is_none() {
+                    return false;
+                }
+                if !self.dragged {
+                    self.editing = Some(self.format_value(self.data_source.read().to_f64()));
+                    self.set_highlighted(true);
+                    window.set_cursor(Some(glfw::StandardCursor::IBeam));
+                }
+                true
+            }
+            glfw::WindowEvent::CursorPos(x, y) => {
+                let (x, y) = (*x as f32, *y as f32);
+                if let Some((last_x, value)) = self.drag {
+                    if !self.dragged && (x - last_x).abs() < DRAG_THRESHOLD {
+                        return true;
+                    }
+                    self.dragged = true;
+                    let value =
+                        value + (x - last_x) as f64 * self.speed * Self::get_modifier(window);
+                    self.drag = Some((x, value));
+                    self.write_value(value);
+                    return true;
+                }
+                let hovering = region.contains(x, y);
+                if hovering != self.is_hovering {
+                    self.is_hovering = hovering;
+                    if self.editing.is_none() {
+                        self.set_highlighted(hovering);
+                        window.set_cursor(hovering.then_some(glfw::StandardCursor::HResize));
+                    }
+                }
+                false
+            }
+            glfw::WindowEvent::Char(character) => {
+                let Some(content) = &mut self.editing else {
+                    return false;
+                };
+                if character.is_ascii_digit() || matches!(character, '.' | '-' | 'e') {
+                    content.push(*character);
+                }
+                true
+            }
+            glfw::WindowEvent::Key(key, _, glfw::Action::Press | glfw::Action::Repeat, _) => {
+                let Some(content) = &mut self.editing else {
+                    return false;
+                };
+                match key {
+                    glfw::Key::Backspace => {
+                        content.pop();
+                    }
+                    glfw::Key::Enter | glfw::Key::KpEnter => self.commit(),
+                    glfw::Key::Escape => {
+                        self.editing = None;
+                        self.set_highlighted(self.is_hovering);
+                    }
+                    _ => {}
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn add_children(&mut self, _: Vec<(Option<UIElementHandle>, Box<dyn UIElement>)>) {
+        panic!("DragValue cannot have children");
+    }
+
+    fn set_offset(&mut self, offset: Offset) {
+        self.offset = offset;
+        self.plane.set_position(&self.position + &self.offset);
+    }
+
+    fn get_size(&self) -> &Size {
+        &self.size
+    }
+
+    fn contains_child(&self, _: &UIElementHandle) -> bool {
+        false
+    }
+
+    fn get_offset(&self) -> &Offset {
+        &self.offset
+    }
+
+    fn add_child_to(
+        &mut self,
+        _: UIElementHandle,
+        _: Option<UIElementHandle>,
+        _: Box<dyn UIElement>,
+    ) {
+        panic!("DragValue cannot have children");
+    }
+
+    fn set_z_index(&mut self, z_index: f32) {
+        self.position.z = z_index;
+        self.plane.set_z_index(z_index);
+        self.text.set_z_index(z_index + 1.0);
+    }
+}
+
+impl<T: DragNumber> DragValueBuilder<T> {
+    pub fn new(data_source: DataSource<T>) -> Self {
+        Self {
+            position: Position::default(),
+            size: Size::default(),
+            data_source,
+            speed: 0.1,
+            precision: 2,
+            range: None,
+        }
+    }
+
+    pub fn position(mut self, x: f32, y: f32) -> Self {
+        self.position = Position { x, y, z: 0.0 };
+        self
+    }
+
+    pub fn size(mut self, width: f32, height: f32) -> Self {
+        self.size = Size { width, height };
+        self
+    }
+
+    /// Change of the value per pixel the cursor is dragged
+    pub fn speed(mut self, speed: f64) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Decimal places the value is rounded to
+    pub fn precision(mut self, precision: usize) -> Self {
+        self.precision = precision;
+        self
+    }
+
+    pub fn range(mut self, min: f64, max: f64) -> Self {
+        self.range = Some((min, max));
+        self
+    }
+
+    pub fn build(self) -> DragValue<T> {
+        let mut drag_value = DragValue::new(self.position, self.size, self.data_source);
+        drag_value.speed = self.speed;
+        drag_value.precision = self.precision;
+        drag_value.range = self.range;
+        drag_value
+    }
+}
@@ -0,0 +1,50 @@
+use std::str::FromStr;
+
+use crate::core::{
+    renderer::{plane::Plane, text::Text},
+    utils::DataSource,
+};
+
+use super::{primitives::Position, Offset, Size};
+
+pub mod drag_value;
+
+/// Numbers a `DragValue` can edit
+pub trait DragNumber: Clone + ToString + FromStr {
+    fn to_f64(&self) -> f64;
+    fn from_f64(value: f64) -> Self;
+}
+
+/// Number field that is adjusted by dragging the mouse horizontally across it, or typed into
+/// after a click without dragging.
+///
+/// Holding shift while dragging changes the value ten times faster, holding control or alt ten
+/// times slower
+pub struct DragValue<T: DragNumber> {
+    position: Position,
+    size: Size,
+    offset: Offset,
+    data_source: DataSource<T>,
+    /// Change of the value per dragged pixel
+    speed: f64,
+    /// Decimal places the value is rounded to
+    precision: usize,
+    range: Option<(f64, f64)>,
+    /// Cursor x and the unrounded value while the mouse button is held on the field
+    drag: Option<(f32, f64)>,
+    dragged: bool,
+    is_hovering: bool,
+    /// Text typed into the field, the field is in text mode while it is set
+    editing: Option<String>,
+    text: Text,
+    plane: Plane,
+}
+
+pub struct DragValueBuilder<T: DragNumber> {
+    position: Position,
+    size: Size,
+    data_source: DataSource<T>,
+    speed: f64,
+    precision: usize,
+    range: Option<(f64, f64)>,
+}
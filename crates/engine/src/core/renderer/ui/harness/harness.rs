@@ -0,0 +1,234 @@
+use glfw::{Action, Context, Key, Modifiers, MouseButton, StandardCursor, WindowEvent};
+
+use crate::core::{
+    determinism::Determinism,
+    renderer::{
+        plane::PlaneRenderer,
+        text::TextRenderer,
+        ui::{UIElement, UIElementHandle, UIRenderer, UIWindow},
+    },
+    scene::Scene,
+};
+
+use super::{TestWindow, UITestHarness};
+
+/// Cursor moves sent between the start and the end of a drag
+const DRAG_STEPS: usize = 10;
+/// Size of the hidden window, tests start with this framebuffer size
+const WINDOW_WIDTH: u32 = 1280;
+const WINDOW_HEIGHT: u32 = 720;
+
+impl UIWindow for TestWindow {
+    fn get_cursor_pos(&self) -> (f64, f64) {
+        self.cursor_pos
+    }
+
+    fn get_key(&self, key: Key) -> Action {
+        if self.pressed_keys.contains(&key) {
+            Action::Press
+        } else {
+            Action::Release
+        }
+    }
+
+    fn set_cursor(&mut self, cursor: Option<StandardCursor>) {
+        self.cursor = cursor;
+    }
+
+    fn get_clipboard_string(&self) -> Option<String> {
+        self.clipboard.clone()
+    }
+
+    fn set_clipboard_string(&mut self, content: &str) {
+        self.clipboard = Some(content.to_owned());
+    }
+}
+
+impl TestWindow {
+    fn new() -> Self {
+        Self {
+            cursor_pos: (0.0, 0.0),
+            pressed_keys: Vec::new(),
+            cursor: None,
+            clipboard: None,
+        }
+    }
+
+    /// Modifier flags of the held keys, as glfw reports them with key and mouse events
+    fn get_modifiers(&self) -> Modifiers {
+        let mut modifiers = Modifiers::empty();
+        for key in &self.pressed_keys {
+            modifiers |= match key {
+                Key::LeftShift | Key::RightShift => Modifiers::Shift,
+                Key::LeftControl | Key::RightControl => Modifiers::Control,
+                Key::LeftAlt | Key::RightAlt => Modifiers::Alt,
+                Key::LeftSuper | Key::RightSuper => Modifiers::Super,
+                _ => Modifiers::empty(),
+            };
+        }
+        modifiers
+    }
+}
+
+impl UITestHarness {
+    pub fn new() -> Self {
+        let mut glfw = glfw::init(glfw::fail_on_errors)
+            .expect("the ui test harness needs a display to create its gl context");
+        glfw.window_hint(glfw::WindowHint::Visible(false));
+        let (mut context, _) = glfw
+            .create_window(
+                WINDOW_WIDTH,
+                WINDOW_HEIGHT,
+                "UI test harness",
+                glfw::WindowMode::Windowed,
+            )
+            .expect("the ui test harness could not create its window");
+        context.make_current();
+        gl::load_with(|symbol| context.get_proc_address(symbol) as *const _);
+        // element handles are generated, seeding them keeps the order of the elements stable
+        Determinism::enable(0, 1.0 / 60.0);
+        PlaneRenderer::resize(WINDOW_WIDTH, WINDOW_HEIGHT);
+        TextRenderer::resize(WINDOW_WIDTH, WINDOW_HEIGHT);
+
+        Self {
+            scene: Scene::new(),
+            ui: UIRenderer::new(),
+            window: TestWindow::new(),
+            glfw,
+            _context: context,
+        }
+    }
+
+    /// Removes all elements, releases the cursor and keys and resets the UI scale
+    pub fn reset(&mut self) {
+        UIRenderer::set_scale(1.0);
+        PlaneRenderer::resize(WINDOW_WIDTH, WINDOW_HEIGHT);
+        TextRenderer::resize(WINDOW_WIDTH, WINDOW_HEIGHT);
+        self.ui = UIRenderer::new();
+        self.window = TestWindow::new();
+    }
+
+    pub fn add(&mut self, element: Box<dyn UIElement>) -> UIElementHandle {
+        self.ui.add(element)
+    }
+
+    /// Renders the elements once, for elements that lay out their children while rendering
+    pub fn render(&mut self) {
+        self.ui.render(&mut self.scene);
+    }
+
+    /// Sends `event` to the elements, returns whether one of them handled it
+    pub fn send(&mut self, event: WindowEvent) -> bool {
+        self.ui
+            .handle_events(&mut self.scene, &mut self.window, &mut self.glfw, &event)
+    }
+
+    /// Resizes the framebuffer the way the application does, then sends the resize event
+    pub fn resize(&mut self, width: u32, height: u32) {
+        PlaneRenderer::resize(width, height);
+        TextRenderer::resize(width, height);
+        self.send(WindowEvent::FramebufferSize(width as i32, height as i32));
+    }
+
+    pub fn move_cursor(&mut self, x: f64, y: f64) -> bool {
+        self.window.cursor_pos = (x, y);
+        self.send(WindowEvent::CursorPos(x, y))
+    }
+
+    pub fn press_mouse(&mut self, x: f64, y: f64) -> bool {
+        self.move_cursor(x, y);
+        let modifiers = self.window.get_modifiers();
+        self.send(WindowEvent::MouseButton(
+            MouseButton::Button1,
+            Action::Press,
+            modifiers,
+        ))
+    }
+
+    pub fn release_mouse(&mut self, x: f64, y: f64) -> bool {
+        self.move_cursor(x, y);
+        let modifiers = self.window.get_modifiers();
+        self.send(WindowEvent::MouseButton(
+            MouseButton::Button1,
+            Action::Release,
+            modifiers,
+        ))
+    }
+
+    /// Presses and releases the left mouse button at `x`, `y`, returns whether the press was
+    /// handled
+    pub fn click(&mut self, x: f64, y: f64) -> bool {
+        let handled = self.press_mouse(x, y);
+        self.release_mouse(x, y);
+        handled
+    }
+
+    /// Presses the left mouse button at `from`, moves the cursor to `to` in even steps and
+    /// releases it there
+    pub fn drag(&mut self, from: (f64, f64), to: (f64, f64)) -> bool {
+        let handled = self.press_mouse(from.0, from.1);
+        for step in 1..=DRAG_STEPS {
+            let progress = step as f64 / DRAG_STEPS as f64;
+            self.move_cursor(
+                from.0 + (to.0 - from.0) * progress,
+                from.1 + (to.1 - from.1) * progress,
+            );
+        }
+        self.release_mouse(to.0, to.1);
+        handled
+    }
+
+    pub fn scroll(&mut self, x: f64, y: f64, amount: f64) -> bool {
+        self.move_cursor(x, y);
+        self.send(WindowEvent::Scroll(0.0, amount))
+    }
+
+    /// Presses and releases `key`, returns whether the press was handled
+    pub fn press_key(&mut self, key: Key) -> bool {
+        let handled = self.hold_key(key);
+        self.release_key(key);
+        handled
+    }
+
+    /// Keeps `key` pressed until `release_key`, e.g. for modifiers
+    pub fn hold_key(&mut self, key: Key) -> bool {
+        if !self.window.pressed_keys.contains(&key) {
+            self.window.pressed_keys.push(key);
+        }
+        let modifiers = self.window.get_modifiers();
+        self.send(WindowEvent::Key(key, 0, Action::Press, modifiers))
+    }
+
+    pub fn release_key(&mut self, key: Key) -> bool {
+        self.window.pressed_keys.retain(|pressed| *pressed != key);
+        let modifiers = self.window.get_modifiers();
+        self.send(WindowEvent::Key(key, 0, Action::Release, modifiers))
+    }
+
+    /// Sends a char event for every character of `text`
+    pub fn type_text(&mut self, text: &str) {
+        for character in text.chars() {
+            self.send(WindowEvent::Char(character));
+        }
+    }
+
+    /// Cursor shape the elements set last, `None` for the default arrow
+    pub fn get_cursor(&self) -> Option<StandardCursor> {
+        self.window.cursor
+    }
+
+    /// Content the elements copied last, or that was set with `set_clipboard`
+    pub fn get_clipboard(&self) -> Option<String> {
+        self.window.clipboard.clone()
+    }
+
+    pub fn set_clipboard(&mut self, content: &str) {
+        self.window.clipboard = Some(content.to_owned());
+    }
+}
+
+impl Default for UITestHarness {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,31 @@
+use glfw::{Key, StandardCursor};
+
+use crate::core::scene::Scene;
+
+use super::UIRenderer;
+
+pub mod harness;
+
+/// Runs UI elements without user input: synthetic clicks, key presses and typed text are sent
+/// through a `UIRenderer` and the test asserts on the state they leave behind.
+///
+/// The elements still need a gl context for their planes and texts, so the harness creates a
+/// hidden window (a display or xvfb is required). The renderers of the UI are shared per process,
+/// create one harness and `reset` it between tests instead of creating more
+pub struct UITestHarness {
+    pub scene: Scene,
+    pub ui: UIRenderer,
+    window: TestWindow,
+    glfw: glfw::Glfw,
+    /// Hidden window that owns the gl context
+    _context: glfw::PWindow,
+}
+
+/// Cursor and keyboard state of the harness, changed only by the synthetic events
+pub struct TestWindow {
+    cursor_pos: (f64, f64),
+    pressed_keys: Vec<Key>,
+    cursor: Option<StandardCursor>,
+    /// Clipboard of the harness, the system clipboard is left alone
+    clipboard: Option<String>,
+}
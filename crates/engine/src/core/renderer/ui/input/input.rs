@@ -7,7 +7,7 @@ use crate::core::{
         text::{Fonts, Text},
         ui::{
             primitives::{Position, Region},
-            Offset, Size, UIElement, UIElementHandle,
+            Offset, Size, UIElement, UIElementHandle, UIWindow,
         },
     },
     scene::Scene,
@@ -16,6 +16,10 @@ use crate::core::{
 
 use super::{Input, InputBuilder};
 
+/// Space between the border of the field and the content
+const PADDING: f32 = 5.0;
+const CARET_WIDTH: f32 = 1.0;
+
 impl<T: Clone + ToString + FromStr> UIElement for Input<T> {
     fn render(&mut self, _: &mut Scene) {
         PlaneRenderer::render(&self.plane);
@@ -44,8 +48,25 @@ impl<T: Clone + ToString + FromStr> UIElement for Input<T> {
                 self.content = data_source.to_string();
             }
             self.text.set_content(&self.content);
-            self.text
-                .render_at(&(&self.position + &self.offset) + (5.0, 2.0, 1.0));
+            self.caret = self.caret.min(self.content.chars().count());
+            self.scroll_to_caret();
+            let offsets = self.text.get_caret_offsets();
+            let origin = &(&self.position + &self.offset) + (PADDING - self.scroll, 0.0);
+            if let Some((start, end)) = self.get_selection().filter(|_| self.is_focused) {
+                self.selection_plane
+                    .set_position(&origin + (offsets[start], 3.0, 1.0));
+                self.selection_plane.set_size(Size {
+                    width: offsets[end] - offsets[start],
+                    height: self.size.height - 6.0,
+                });
+                PlaneRenderer::render(&self.selection_plane);
+            }
+            self.text.render_at(&origin + (0.0, 2.0, 1.0));
+            if self.is_focused {
+                self.caret_plane
+                    .set_position(&origin + (offsets[self.caret], 3.0, 2.0));
+                PlaneRenderer::render(&self.caret_plane);
+            }
             gl::Disable(gl::STENCIL_TEST);
             gl::StencilMask(0xFF);
             gl::StencilFunc(gl::ALWAYS, 0, 0xFF);
@@ -55,13 +76,17 @@ impl<T: Clone + ToString + FromStr> UIElement for Input<T> {
     fn handle_events(
         &mut self,
         _: &mut Scene,
-        window: &mut glfw::Window,
+        window: &mut dyn UIWindow,
         _: &mut glfw::Glfw,
         event: &glfw::WindowEvent,
     ) -> bool {
         let region = Region::new_with_offset(self.position, self.size, self.offset);
         match event {
-            glfw::WindowEvent::MouseButton(glfw::MouseButton::Button1, glfw::Action::Press, _) => {
+            glfw::WindowEvent::MouseButton(
+                glfw::MouseButton::Button1,
+                glfw::Action::Press,
+                modifiers,
+            ) => {
                 let (x, y) = window.get_cursor_pos();
                 let (x, y) = (x as f32, y as f32);
                 if region.contains(x, y) {
@@ -70,22 +95,38 @@ impl<T: Clone + ToString + FromStr> UIElement for Input<T> {
                         self.plane.set_color((0.3, 0.3, 0.3, 1.0));
                         self.stencil_plane.set_color((0.3, 0.3, 0.3, 1.0));
                     }
+                    let caret = self.get_caret_at(x);
+                    self.move_caret(caret, modifiers.contains(glfw::Modifiers::Shift));
+                    self.is_selecting = true;
                     return true;
                 } else if self.is_focused {
                     self.is_focused = false;
+                    self.selection_anchor = None;
                     self.plane.set_color((0.2, 0.2, 0.2, 1.0));
                     self.stencil_plane.set_color((0.2, 0.2, 0.2, 1.0));
                 }
                 false
             }
+            glfw::WindowEvent::MouseButton(
+                glfw::MouseButton::Button1,
+                glfw::Action::Release,
+                _,
+            ) if self.is_selecting => {
+                self.is_selecting = false;
+                true
+            }
+            glfw::WindowEvent::CursorPos(x, _) if self.is_selecting => {
+                let caret = self.get_caret_at(*x as f32);
+                self.move_caret(caret, true);
+                true
+            }
             glfw::WindowEvent::CursorPos(x, y) => {
                 if region.contains(*x as f32, *y as f32) {
                     if !self.is_hovering {
                         self.is_hovering = true;
                         self.plane.set_color((0.3, 0.3, 0.3, 1.0));
                         self.stencil_plane.set_color((0.3, 0.3, 0.3, 1.0));
-                        window
-                            .set_cursor(Some(glfw::Cursor::standard(glfw::StandardCursor::IBeam)));
+                        window.set_cursor(Some(glfw::StandardCursor::IBeam));
                     }
                 } else if self.is_hovering {
                     window.set_cursor(None);
@@ -99,31 +140,19 @@ impl<T: Clone + ToString + FromStr> UIElement for Input<T> {
             }
             glfw::WindowEvent::Char(character) => {
                 if self.is_focused {
-                    self.content.push(*character);
-                    if let Some(data_source) = &self.data_source {
-                        data_source.write_from_string(self.content.clone());
-                    }
+                    self.insert(&character.to_string());
                     return true;
                 }
                 false
             }
             glfw::WindowEvent::Key(
-                glfw::Key::Backspace,
+                key,
                 _,
                 glfw::Action::Press | glfw::Action::Repeat,
-                _,
+                modifiers,
             ) => {
                 if self.is_focused {
-                    self.content.pop();
-                    if let Some(data_source) = &mut self.data_source {
-                        data_source.write_from_string(self.content.clone());
-                    }
-                    return true;
-                }
-                false
-            }
-            glfw::WindowEvent::Key(_, _, glfw::Action::Press | glfw::Action::Repeat, _) => {
-                if self.is_focused {
+                    self.handle_key(window, *key, *modifiers);
                     return true;
                 }
                 false
@@ -168,7 +197,9 @@ impl<T: Clone + ToString + FromStr> UIElement for Input<T> {
         self.position.z = z_index;
         self.plane.set_z_index(z_index);
         self.stencil_plane.set_z_index(z_index + 1.0);
+        self.selection_plane.set_z_index(z_index + 1.0);
         self.text.set_z_index(z_index + 1.0);
+        self.caret_plane.set_z_index(z_index + 2.0);
     }
 }
 
@@ -202,9 +233,175 @@ impl<T: Clone + ToString> Input<T> {
                     height: size.height,
                 })
                 .build(),
+            caret_plane: PlaneBuilder::new()
+                .position(position)
+                .size(Size {
+                    width: CARET_WIDTH,
+                    height: size.height - 6.0,
+                })
+                .color((0.9, 0.9, 0.9, 1.0))
+                .build(),
+            selection_plane: PlaneBuilder::new()
+                .position(position)
+                .color((0.25, 0.4, 0.65, 1.0))
+                .build(),
+            caret: content.to_string().chars().count(),
+            selection_anchor: None,
+            is_selecting: false,
+            scroll: 0.0,
             data_source,
         }
     }
+
+    /// Characters in front of the caret
+    pub fn get_caret(&self) -> usize {
+        self.caret
+    }
+
+    /// First and last character of the selection, `None` if nothing is selected
+    pub fn get_selection(&self) -> Option<(usize, usize)> {
+        let anchor = self.selection_anchor?;
+        match anchor.cmp(&self.caret) {
+            std::cmp::Ordering::Less => Some((anchor, self.caret)),
+            std::cmp::Ordering::Greater => Some((self.caret, anchor)),
+            std::cmp::Ordering::Equal => None,
+        }
+    }
+
+    pub fn get_selected_text(&self) -> Option<String> {
+        let (start, end) = self.get_selection()?;
+        Some(self.content.chars().skip(start).take(end - start).collect())
+    }
+
+    /// Moves the caret in front of the character `caret`, extending the selection from where
+    /// the caret was if `select` is set
+    fn move_caret(&mut self, caret: usize, select: bool) {
+        if select {
+            self.selection_anchor.get_or_insert(self.caret);
+        } else {
+            self.selection_anchor = None;
+        }
+        self.caret = caret.min(self.content.chars().count());
+    }
+
+    /// Caret position closest to the window x coordinate `x`
+    fn get_caret_at(&self, x: f32) -> usize {
+        let x = x - self.offset.x - self.position.x - PADDING + self.scroll;
+        let offsets = self.text.get_caret_offsets();
+        (0..offsets.len())
+            .min_by(|a, b| (offsets[*a] - x).abs().total_cmp(&(offsets[*b] - x).abs()))
+            .unwrap_or(0)
+    }
+
+    /// Scrolls the content horizontally until the caret is inside the field
+    fn scroll_to_caret(&mut self) {
+        let offsets = self.text.get_caret_offsets();
+        // the stencil plane is 12 pixels narrower than the field
+        let width = (self.size.width - 12.0 - 2.0 * PADDING).max(0.0);
+        let caret = offsets[self.caret.min(offsets.len() - 1)];
+        let content_width = offsets.last().copied().unwrap_or_default();
+        if caret - self.scroll > width {
+            self.scroll = caret - width;
+        } else if caret < self.scroll {
+            self.scroll = caret;
+        }
+        // no empty space behind the content while it is wider than the field
+        self.scroll = self.scroll.min((content_width - width).max(0.0)).max(0.0);
+    }
+
+    fn get_byte_index(&self, caret: usize) -> usize {
+        self.content
+            .char_indices()
+            .nth(caret)
+            .map_or(self.content.len(), |(index, _)| index)
+    }
+}
+
+impl<T: Clone + ToString + FromStr> Input<T> {
+    /// Replaces the selection, or inserts at the caret if nothing is selected
+    fn insert(&mut self, text: &str) {
+        if let Some((start, end)) = self.get_selection() {
+            let range = self.get_byte_index(start)..self.get_byte_index(end);
+            self.content.replace_range(range, "");
+            self.caret = start;
+        }
+        self.selection_anchor = None;
+        let index = self.get_byte_index(self.caret);
+        self.content.insert_str(index, text);
+        self.caret += text.chars().count();
+        self.write_content();
+    }
+
+    /// Removes the selection, or the character in front of the caret for `backwards` and the
+    /// one behind it otherwise
+    fn delete(&mut self, backwards: bool) {
+        if self.get_selection().is_none() {
+            let length = self.content.chars().count();
+            if backwards && self.caret > 0 {
+                self.move_caret(self.caret - 1, true);
+            } else if !backwards && self.caret < length {
+                self.move_caret(self.caret + 1, true);
+            } else {
+                return;
+            }
+        }
+        self.insert("");
+    }
+
+    fn write_content(&self) {
+        if let Some(data_source) = &self.data_source {
+            data_source.write_from_string(self.content.clone());
+        }
+    }
+
+    fn handle_key(
+        &mut self,
+        window: &mut dyn UIWindow,
+        key: glfw::Key,
+        modifiers: glfw::Modifiers,
+    ) {
+        let select = modifiers.contains(glfw::Modifiers::Shift);
+        // ctrl on most platforms and cmd on macOS
+        let shortcut = modifiers.intersects(glfw::Modifiers::Control | glfw::Modifiers::Super);
+        let length = self.content.chars().count();
+        match key {
+            glfw::Key::Backspace => self.delete(true),
+            glfw::Key::Delete => self.delete(false),
+            glfw::Key::Left => match self.get_selection() {
+                Some((start, _)) if !select => self.move_caret(start, false),
+                _ => self.move_caret(self.caret.saturating_sub(1), select),
+            },
+            glfw::Key::Right => match self.get_selection() {
+                Some((_, end)) if !select => self.move_caret(end, false),
+                _ => self.move_caret(self.caret + 1, select),
+            },
+            glfw::Key::Home => self.move_caret(0, select),
+            glfw::Key::End => self.move_caret(length, select),
+            glfw::Key::A if shortcut => {
+                self.selection_anchor = Some(0);
+                self.caret = length;
+            }
+            glfw::Key::C if shortcut => {
+                if let Some(selected) = self.get_selected_text() {
+                    window.set_clipboard_string(&selected);
+                }
+            }
+            glfw::Key::X if shortcut => {
+                if let Some(selected) = self.get_selected_text() {
+                    window.set_clipboard_string(&selected);
+                    self.insert("");
+                }
+            }
+            glfw::Key::V if shortcut => {
+                if let Some(clipboard) = window.get_clipboard_string() {
+                    // the field holds a single line
+                    let line: String = clipboard.chars().filter(|c| !c.is_control()).collect();
+                    self.insert(&line);
+                }
+            }
+            _ => {}
+        }
+    }
 }
 
 impl<T: Clone + ToString> InputBuilder<T> {
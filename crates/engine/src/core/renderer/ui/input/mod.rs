@@ -17,6 +17,16 @@ pub struct Input<T: Clone + ToString> {
     text: Text,
     plane: Plane,
     stencil_plane: Plane,
+    caret_plane: Plane,
+    selection_plane: Plane,
+    /// Characters in front of the caret
+    caret: usize,
+    /// Position the selection was started at, the selection spans from it to the caret
+    selection_anchor: Option<usize>,
+    /// Whether the mouse button pressed in the field is still held, moving selects
+    is_selecting: bool,
+    /// Pixels the content is moved to the left so the caret stays inside the field
+    scroll: f32,
     data_source: Option<DataSource<T>>,
 }
 
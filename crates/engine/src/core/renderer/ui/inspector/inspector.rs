@@ -0,0 +1,438 @@
+use std::{cell::RefCell, rc::Rc};
+
+use cgmath::{EuclideanSpace, Point3, Vector3};
+
+use crate::core::{
+    entity::{
+        component::{InspectorField, InspectorValue},
+        Entity, EntityHandle,
+    },
+    renderer::{
+        plane::{PlaneBuilder, PlaneRenderer},
+        text::{Fonts, Text},
+        ui::{
+            drag_value::DragValueBuilder,
+            list_view::ListViewBuilder,
+            primitives::{Position, Region},
+            Offset, Size, UIElement, UIElementHandle, UIWindow, UI,
+        },
+    },
+    scene::Scene,
+    utils::DataSource,
+};
+
+use super::{Inspector, InspectorBuilder, InspectorRow, InspectorRowField, InspectorTarget};
+
+const PADDING: f32 = 5.0;
+const ROW_HEIGHT: f32 = 26.0;
+const LIST_ROW_HEIGHT: f32 = 20.0;
+/// Share of the height the entity list takes, the component fields are below it
+const LIST_SHARE: f32 = 0.4;
+const LABEL_WIDTH: f32 = 110.0;
+const FONT_SIZE: f32 = 16.0;
+
+impl Inspector {
+    pub fn new(position: Position, size: Size) -> Self {
+        let entities: Rc<RefCell<Vec<(EntityHandle, String)>>> = Rc::new(RefCell::new(Vec::new()));
+        let item_entities = entities.clone();
+        let row_entities = entities.clone();
+        let list_view = ListViewBuilder::new(
+            Box::new(move || item_entities.borrow().len()),
+            Box::new(move |index| -> Box<dyn UIElement> {
+                let name = row_entities
+                    .borrow()
+                    .get(index)
+                    .map_or(String::new(), |(_, name)| name.clone());
+                UI::text(&name, FONT_SIZE, |text| text)
+            }),
+        )
+        .position(position.x + PADDING, position.y + PADDING)
+        .size(
+            size.width - 2.0 * PADDING,
+            (size.height * LIST_SHARE).floor(),
+        )
+        .row_height(LIST_ROW_HEIGHT)
+        .build();
+        Self {
+            position,
+            size,
+            offset: Offset::default(),
+            entities,
+            selected: None,
+            list_view,
+            layout: None,
+            rows: Vec::new(),
+            plane: PlaneBuilder::new()
+                .position(position)
+                .size(size)
+                .color((0.1, 0.1, 0.1, 0.9))
+                .border_radius_uniform(5.0)
+                .border_thickness(1.0)
+                .build(),
+        }
+    }
+
+    pub fn get_selected(&self) -> Option<EntityHandle> {
+        self.selected
+    }
+
+    fn get_entity_index(&self, id: &EntityHandle) -> Option<usize> {
+        self.entities
+            .borrow()
+            .iter()
+            .position(|(handle, _)| handle == id)
+    }
+
+    /// Lists `entity` and its children, children indented below their parent
+    fn collect_entities(entity: &Entity, depth: usize, entities: &mut Vec<(EntityHandle, String)>) {
+        entities.push((
+            entity.id,
+            format!("{}{}", "  ".repeat(depth), entity.get_name()),
+        ));
+        for child in entity.get_children() {
+            Inspector::collect_entities(child, depth + 1, entities);
+        }
+    }
+
+    /// Syncs the selection of the list with `Scene::selection` and updates the list to the
+    /// entities of the scene, the selection follows its entity when the order changes
+    fn update_entities(&mut self, scene: &mut Scene) {
+        let listed = self
+            .list_view
+            .get_selected()
+            .and_then(|index| self.entities.borrow().get(index).map(|(id, _)| *id));
+        // a click in the list selects in the scene, otherwise the list follows the scene
+        if listed != self.selected {
+            scene.selection.set(listed);
+        }
+        self.selected = scene.selection.get();
+
+        let mut entities = Vec::new();
+        for entity in scene.get_entities() {
+            Inspector::collect_entities(entity, 0, &mut entities);
+        }
+        if *self.entities.borrow() != entities {
+            *self.entities.borrow_mut() = entities;
+            self.list_view.refresh();
+        }
+        let index = self.selected.and_then(|id| self.get_entity_index(&id));
+        if index.is_none() && self.selected.is_some() {
+            self.selected = None;
+            scene.selection.clear();
+        }
+        if index != self.list_view.get_selected() {
+            self.list_view.set_selected(index);
+        }
+    }
+
+    /// Rebuilds the rows when the selected entity or its components changed, otherwise writes
+    /// edited values to the entity and shows the current values of the others
+    fn update_rows(&mut self, scene: &mut Scene) {
+        let Some(entity) = self.selected.and_then(|id| scene.get_entity(&id)) else {
+            self.rows.clear();
+            self.layout = None;
+            return;
+        };
+        let id = entity.id;
+        let position = entity.get_position();
+        let components: Vec<(String, Vec<InspectorField>)> = entity
+            .get_inspectable_components()
+            .iter()
+            .map(|component| {
+                (
+                    component.get_inspector_name().to_string(),
+                    component.get_fields(),
+                )
+            })
+            .collect();
+        let layout = (
+            id,
+            components
+                .iter()
+                .map(|(name, fields)| {
+                    (
+                        name.clone(),
+                        fields.iter().map(|field| field.name).collect(),
+                    )
+                })
+                .collect(),
+        );
+        if self.layout.as_ref() != Some(&layout) {
+            self.build_rows(position, &components);
+            self.layout = Some(layout);
+            return;
+        }
+
+        for row in self.rows.iter_mut() {
+            let Some(field) = &mut row.field else {
+                continue;
+            };
+            let current = match field.target {
+                InspectorTarget::Position => InspectorValue::Vector3(position.to_vec()),
+                InspectorTarget::Component(index, name) => components[index]
+                    .1
+                    .iter()
+                    .find(|component_field| component_field.name == name)
+                    .map_or(field.value, |component_field| component_field.value),
+            };
+            let edited = field.read_value();
+            if edited != field.value {
+                field.value = edited;
+                Inspector::apply(scene, &id, field.target, edited);
+            } else if current != field.value {
+                field.write_value(current);
+            }
+        }
+    }
+
+    fn apply(scene: &mut Scene, id: &EntityHandle, target: InspectorTarget, value: InspectorValue) {
+        match (target, value) {
+            (InspectorTarget::Position, InspectorValue::Vector3(position)) => {
+                scene.set_entity_position(id, Point3::from_vec(position));
+            }
+            (InspectorTarget::Component(index, name), value) => {
+                if let Some(component) = scene
+                    .get_entity_mut(id)
+                    .and_then(|entity| entity.get_inspectable_component_mut(index))
+                {
+                    component.set_field(name, value);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn build_rows(&mut self, position: Point3<f32>, components: &[(String, Vec<InspectorField>)]) {
+        self.rows.clear();
+        self.add_row("Transform", None);
+        self.add_row(
+            "  Position",
+            Some((
+                InspectorTarget::Position,
+                InspectorValue::Vector3(position.to_vec()),
+            )),
+        );
+        for (index, (name, fields)) in components.iter().enumerate() {
+            self.add_row(name, None);
+            for field in fields {
+                self.add_row(
+                    &format!("  {}", field.name),
+                    Some((InspectorTarget::Component(index, field.name), field.value)),
+                );
+            }
+        }
+    }
+
+    fn add_row(&mut self, label: &str, field: Option<(InspectorTarget, InspectorValue)>) {
+        let y = self.get_fields_top() + self.rows.len() as f32 * ROW_HEIGHT;
+        let field = field.map(|(target, value)| {
+            let count = match value {
+                InspectorValue::Float(_) => 1,
+                InspectorValue::Vector3(_) => 3,
+            };
+            let width = (self.size.width - LABEL_WIDTH - PADDING) / count as f32;
+            let data_sources: Vec<DataSource<f32>> =
+                (0..count).map(|_| DataSource::new(0.0)).collect();
+            let drag_values = data_sources
+                .iter()
+                .enumerate()
+                .map(|(index, data_source)| {
+                    let mut drag_value = DragValueBuilder::new(data_source.clone())
+                        .position(
+                            self.position.x + LABEL_WIDTH + index as f32 * width,
+                            y + 2.0,
+                        )
+                        .size(width - PADDING, ROW_HEIGHT - 4.0)
+                        .build();
+                    drag_value.set_offset(self.offset);
+                    drag_value.set_z_index(self.position.z + 1.0);
+                    drag_value
+                })
+                .collect();
+            let mut field = InspectorRowField {
+                target,
+                value,
+                data_sources,
+                drag_values,
+            };
+            field.write_value(value);
+            field
+        });
+        self.rows.push(InspectorRow {
+            label: Text::new(Fonts::RobotoMono, 0, 0, 0, FONT_SIZE, label.to_string()),
+            field,
+        });
+    }
+
+    /// Y of the first field row, below the entity list
+    fn get_fields_top(&self) -> f32 {
+        self.position.y + 2.0 * PADDING + (self.size.height * LIST_SHARE).floor()
+    }
+
+    /// Number of rows that fit below the entity list, the others are cut off
+    fn get_visible_row_count(&self) -> usize {
+        let height = self.position.y + self.size.height - self.get_fields_top();
+        (height / ROW_HEIGHT).max(0.0) as usize
+    }
+}
+
+impl InspectorRowField {
+    /// Value of the data sources, which the user may have edited
+    fn read_value(&self) -> InspectorValue {
+        match self.value {
+            InspectorValue::Float(_) => InspectorValue::Float(self.data_sources[0].read()),
+            InspectorValue::Vector3(_) => InspectorValue::Vector3(Vector3::new(
+                self.data_sources[0].read(),
+                self.data_sources[1].read(),
+                self.data_sources[2].read(),
+            )),
+        }
+    }
+
+    fn write_value(&mut self, value: InspectorValue) {
+        self.value = value;
+        match value {
+            InspectorValue::Float(value) => self.data_sources[0].write(value),
+            InspectorValue::Vector3(value) => {
+                for (data_source, value) in
+                    self.data_sources.iter().zip([value.x, value.y, value.z])
+                {
+                    data_source.write(value);
+                }
+            }
+        }
+    }
+}
+
+impl UIElement for Inspector {
+    fn render(&mut self, scene: &mut Scene) {
+        self.update_entities(scene);
+        self.update_rows(scene);
+
+        PlaneRenderer::render(&self.plane);
+        self.list_view.render(scene);
+        let origin = &self.position + &self.offset;
+        let top = self.get_fields_top() - self.position.y;
+        let visible_rows = self.get_visible_row_count();
+        for (index, row) in self.rows.iter_mut().take(visible_rows).enumerate() {
+            row.label
+                .render_at(&origin + (PADDING, top + index as f32 * ROW_HEIGHT + 4.0, 1.0));
+            if let Some(field) = &mut row.field {
+                for drag_value in field.drag_values.iter_mut() {
+                    drag_value.render(scene);
+                }
+            }
+        }
+    }
+
+    fn handle_events(
+        &mut self,
+        scene: &mut Scene,
+        window: &mut dyn UIWindow,
+        glfw: &mut glfw::Glfw,
+        event: &glfw::WindowEvent,
+    ) -> bool {
+        if self.list_view.handle_events(scene, window, glfw, event) {
+            return true;
+        }
+        let visible_rows = self.get_visible_row_count();
+        for row in self.rows.iter_mut().take(visible_rows) {
+            if let Some(field) = &mut row.field {
+                for drag_value in field.drag_values.iter_mut() {
+                    if drag_value.handle_events(scene, window, glfw, event) {
+                        return true;
+                    }
+                }
+            }
+        }
+        if let glfw::WindowEvent::MouseButton(_, glfw::Action::Press, _) = event {
+            let (x, y) = window.get_cursor_pos();
+            return Region::new_with_offset(self.position, self.size, self.offset)
+                .contains(x as f32, y as f32);
+        }
+        false
+    }
+
+    fn add_children(&mut self, _: Vec<(Option<UIElementHandle>, Box<dyn UIElement>)>) {
+        panic!("Inspector cannot have children");
+    }
+
+    fn set_offset(&mut self, offset: Offset) {
+        self.offset = offset;
+        self.plane.set_position(&self.position + &self.offset);
+        self.list_view.set_offset(offset);
+        for row in self.rows.iter_mut() {
+            if let Some(field) = &mut row.field {
+                for drag_value in field.drag_values.iter_mut() {
+                    drag_value.set_offset(offset);
+                }
+            }
+        }
+    }
+
+    fn get_size(&self) -> &Size {
+        &self.size
+    }
+
+    fn contains_child(&self, _: &UIElementHandle) -> bool {
+        false
+    }
+
+    fn get_offset(&self) -> &Offset {
+        &self.offset
+    }
+
+    fn add_child_to(
+        &mut self,
+        _: UIElementHandle,
+        _: Option<UIElementHandle>,
+        _: Box<dyn UIElement>,
+    ) {
+        panic!("Inspector cannot have children");
+    }
+
+    fn set_z_index(&mut self, z_index: f32) {
+        self.position.z = z_index;
+        self.plane.set_z_index(z_index);
+        self.list_view.set_z_index(z_index + 1.0);
+        for row in self.rows.iter_mut() {
+            if let Some(field) = &mut row.field {
+                for drag_value in field.drag_values.iter_mut() {
+                    drag_value.set_z_index(z_index + 1.0);
+                }
+            }
+        }
+    }
+}
+
+impl InspectorBuilder {
+    pub fn new() -> Self {
+        Self {
+            position: Position::default(),
+            size: Size {
+                width: 340.0,
+                height: 480.0,
+            },
+        }
+    }
+
+    pub fn position(mut self, x: f32, y: f32) -> Self {
+        self.position = Position { x, y, z: 0.0 };
+        self
+    }
+
+    pub fn size(mut self, width: f32, height: f32) -> Self {
+        self.size = Size { width, height };
+        self
+    }
+
+    pub fn build(self) -> Inspector {
+        Inspector::new(self.position, self.size)
+    }
+}
+
+impl Default for InspectorBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
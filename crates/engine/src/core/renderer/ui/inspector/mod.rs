@@ -0,0 +1,62 @@
+use std::{cell::RefCell, rc::Rc};
+
+use crate::core::{
+    entity::{component::InspectorValue, EntityHandle},
+    renderer::{plane::Plane, text::Text},
+    utils::DataSource,
+};
+
+use super::{drag_value::DragValue, list_view::ListView, primitives::Position, Offset, Size};
+
+pub mod inspector;
+
+/// Panel that lists the entities of the scene and shows the components of the selected one by
+/// name, with its position and the fields of its `InspectableComponent`s as editable values.
+/// The selection is shared with `Scene::selection`, so entities clicked in the scene are shown.
+///
+/// The values follow the components every frame, so changes made by the game show up while a
+/// value the user drags or types is written back to the component
+pub struct Inspector {
+    position: Position,
+    size: Size,
+    offset: Offset,
+    /// Handle and name, indented by the depth in the hierarchy, of every entity in the order
+    /// they are listed, shared with the row builder of the list
+    entities: Rc<RefCell<Vec<(EntityHandle, String)>>>,
+    selected: Option<EntityHandle>,
+    list_view: ListView,
+    /// Selected entity and the component and field names the rows were built for
+    layout: Option<(EntityHandle, InspectorLayout)>,
+    rows: Vec<InspectorRow>,
+    plane: Plane,
+}
+
+/// Names of the inspectable components with the names of their fields
+type InspectorLayout = Vec<(String, Vec<&'static str>)>;
+
+/// Line of the inspector, a component name or a field with its values
+struct InspectorRow {
+    label: Text,
+    field: Option<InspectorRowField>,
+}
+
+struct InspectorRowField {
+    target: InspectorTarget,
+    /// Value last written to the data sources, they differ from it after the user edited them
+    value: InspectorValue,
+    data_sources: Vec<DataSource<f32>>,
+    drag_values: Vec<DragValue<f32>>,
+}
+
+/// What an inspector field edits
+#[derive(Clone, Copy)]
+enum InspectorTarget {
+    Position,
+    /// Field of the component at an index of `Entity::get_inspectable_components`
+    Component(usize, &'static str),
+}
+
+pub struct InspectorBuilder {
+    position: Position,
+    size: Size,
+}
@@ -0,0 +1,366 @@
+use std::collections::BTreeMap;
+
+use crate::core::{
+    renderer::{
+        plane::{PlaneBuilder, PlaneRenderer},
+        ui::{
+            primitives::{Position, Region},
+            Offset, Size, UIElement, UIElementHandle, UIWindow,
+        },
+    },
+    scene::Scene,
+};
+
+use super::{ListView, ListViewBuilder, RowBuilder, SelectHandler};
+
+/// Rows scrolled per step of the mouse wheel
+const SCROLL_ROWS: f64 = 3.0;
+const SCROLLBAR_WIDTH: f32 = 4.0;
+const MIN_SCROLLBAR_HEIGHT: f32 = 10.0;
+
+impl ListView {
+    pub fn new(
+        position: Position,
+        size: Size,
+        row_height: f32,
+        item_count: Box<dyn Fn() -> usize>,
+        build_row: RowBuilder,
+    ) -> Self {
+        let row_height = row_height.max(1.0);
+        Self {
+            position,
+            size,
+            offset: Offset::default(),
+            row_height,
+            item_count,
+            build_row,
+            on_select: None,
+            scroll: 0,
+            selected: None,
+            is_focused: false,
+            rows: BTreeMap::new(),
+            plane: PlaneBuilder::new()
+                .position(position)
+                .size(size)
+                .color((0.15, 0.15, 0.15, 1.0))
+                .border_radius_uniform(5.0)
+                .border_thickness(1.0)
+                .build(),
+            selection_plane: PlaneBuilder::new()
+                .position(position)
+                .size(Size {
+                    width: size.width - SCROLLBAR_WIDTH,
+                    height: row_height,
+                })
+                .color((0.2, 0.3, 0.5, 1.0))
+                .build(),
+            scrollbar_plane: PlaneBuilder::new()
+                .position(position)
+                .size(Size {
+                    width: SCROLLBAR_WIDTH,
+                    height: size.height,
+                })
+                .color((0.4, 0.4, 0.4, 1.0))
+                .border_radius_uniform(2.0)
+                .build(),
+        }
+    }
+
+    pub fn on_select(&mut self, on_select: SelectHandler) {
+        self.on_select = Some(on_select);
+    }
+
+    /// Drops the built rows so they are rebuilt from the current items
+    pub fn refresh(&mut self) {
+        self.rows.clear();
+    }
+
+    pub fn get_selected(&self) -> Option<usize> {
+        self.selected
+    }
+
+    /// Selects the row at `index` without calling the select handler
+    pub fn set_selected(&mut self, index: Option<usize>) {
+        self.selected = index;
+        if let Some(index) = index {
+            self.scroll_to(index);
+        }
+    }
+
+    /// Scrolls the least amount needed to show the row at `index`
+    pub fn scroll_to(&mut self, index: usize) {
+        let visible_rows = self.get_visible_row_count();
+        if index < self.scroll {
+            self.scroll = index;
+        } else if index >= self.scroll + visible_rows {
+            self.scroll = index + 1 - visible_rows;
+        }
+    }
+
+    /// Index of the item whose row is at the cursor position `x`, `y`, if any
+    pub fn get_row_at(&self, x: f32, y: f32) -> Option<usize> {
+        let region = Region::new_with_offset(self.position, self.size, self.offset);
+        if !region.contains(x, y) {
+            return None;
+        }
+        let row = (y - self.position.y - self.offset.y) / self.row_height;
+        let index = self.scroll + row.max(0.0) as usize;
+        (index < (self.item_count)()).then_some(index)
+    }
+
+    /// Number of rows that fit into the viewport
+    fn get_visible_row_count(&self) -> usize {
+        ((self.size.height / self.row_height).floor() as usize).max(1)
+    }
+
+    fn get_max_scroll(&self, item_count: usize) -> usize {
+        item_count.saturating_sub(self.get_visible_row_count())
+    }
+
+    fn select(&mut self, scene: &mut Scene, index: usize) {
+        self.set_selected(Some(index));
+        if let Some(on_select) = &self.on_select {
+            on_select(scene, index);
+        }
+    }
+
+    /// Moves the selection by `delta` rows, the first row is selected if there was no selection
+    fn move_selection(&mut self, scene: &mut Scene, delta: isize) {
+        let item_count = (self.item_count)();
+        if item_count == 0 {
+            return;
+        }
+        let target = match self.selected {
+            Some(selected) => {
+                (selected as isize + delta).clamp(0, item_count as isize - 1) as usize
+            }
+            None => 0,
+        };
+        if self.selected != Some(target) {
+            self.select(scene, target);
+        }
+    }
+
+    fn get_row_offset(&self, index: usize) -> Offset {
+        &self.offset + &self.position + (0.0, (index - self.scroll) as f32 * self.row_height)
+    }
+
+    fn update_scrollbar(&mut self, item_count: usize) {
+        let visible_rows = self.get_visible_row_count();
+        let height = (self.size.height * visible_rows as f32 / item_count as f32)
+            .clamp(MIN_SCROLLBAR_HEIGHT, self.size.height);
+        let progress = self.scroll as f32 / self.get_max_scroll(item_count).max(1) as f32;
+        self.scrollbar_plane.set_size(Size {
+            width: SCROLLBAR_WIDTH,
+            height,
+        });
+        self.scrollbar_plane.set_position(
+            &(&self.position + &self.offset)
+                + (
+                    self.size.width - SCROLLBAR_WIDTH,
+                    (self.size.height - height) * progress,
+                ),
+        );
+    }
+}
+
+impl UIElement for ListView {
+    fn render(&mut self, scene: &mut Scene) {
+        let item_count = (self.item_count)();
+        if let Some(selected) = self.selected {
+            if selected >= item_count {
+                self.selected = item_count.checked_sub(1);
+            }
+        }
+        self.scroll = self.scroll.min(self.get_max_scroll(item_count));
+        let visible = self.scroll..(self.scroll + self.get_visible_row_count()).min(item_count);
+        self.rows.retain(|index, _| visible.contains(index));
+
+        PlaneRenderer::render(&self.plane);
+        if let Some(selected) = self.selected.filter(|selected| visible.contains(selected)) {
+            self.selection_plane
+                .set_position(Position::from(self.get_row_offset(selected)));
+            self.selection_plane.set_z_index(self.position.z + 1.0);
+            PlaneRenderer::render(&self.selection_plane);
+        }
+        for index in visible {
+            let offset = self.get_row_offset(index);
+            let row = self.rows.entry(index).or_insert_with(|| {
+                let mut row = (self.build_row)(index);
+                row.set_z_index(self.position.z + 2.0);
+                row
+            });
+            if offset != *row.get_offset() {
+                row.set_offset(offset);
+            }
+            row.render(scene);
+        }
+        if item_count > self.get_visible_row_count() {
+            self.update_scrollbar(item_count);
+            PlaneRenderer::render(&self.scrollbar_plane);
+        }
+    }
+
+    fn handle_events(
+        &mut self,
+        scene: &mut Scene,
+        window: &mut dyn UIWindow,
+        glfw: &mut glfw::Glfw,
+        event: &glfw::WindowEvent,
+    ) -> bool {
+        for row in self.rows.values_mut() {
+            if row.handle_events(scene, window, glfw, event) {
+                return true;
+            }
+        }
+        let region = Region::new_with_offset(self.position, self.size, self.offset);
+        match event {
+            glfw::WindowEvent::Scroll(_, y) => {
+                let (x, cursor_y) = window.get_cursor_pos();
+                if !region.contains(x as f32, cursor_y as f32) {
+                    return false;
+                }
+                let rows = (-y * SCROLL_ROWS).round() as isize;
+                let max_scroll = self.get_max_scroll((self.item_count)());
+                self.scroll = (self.scroll as isize + rows).clamp(0, max_scroll as isize) as usize;
+                true
+            }
+            glfw::WindowEvent::MouseButton(glfw::MouseButton::Button1, glfw::Action::Press, _) => {
+                let (x, y) = window.get_cursor_pos();
+                let (x, y) = (x as f32, y as f32);
+                if !region.contains(x, y) {
+                    self.is_focused = false;
+                    return false;
+                }
+                self.is_focused = true;
+                if let Some(index) = self.get_row_at(x, y) {
+                    self.select(scene, index);
+                }
+                true
+            }
+            glfw::WindowEvent::Key(key, _, glfw::Action::Press | glfw::Action::Repeat, _)
+                if self.is_focused =>
+            {
+                let page = self.get_visible_row_count() as isize;
+                match key {
+                    glfw::Key::Up => self.move_selection(scene, -1),
+                    glfw::Key::Down => self.move_selection(scene, 1),
+                    glfw::Key::PageUp => self.move_selection(scene, -page),
+                    glfw::Key::PageDown => self.move_selection(scene, page),
+                    glfw::Key::Home => self.move_selection(scene, isize::MIN / 2),
+                    glfw::Key::End => self.move_selection(scene, isize::MAX / 2),
+                    _ => return false,
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn add_children(&mut self, _: Vec<(Option<UIElementHandle>, Box<dyn UIElement>)>) {
+        panic!("ListView builds its rows from the row builder");
+    }
+
+    fn set_offset(&mut self, offset: Offset) {
+        self.offset = offset;
+        self.plane.set_position(&self.position + &self.offset);
+        let offsets: Vec<(usize, Offset)> = self
+            .rows
+            .keys()
+            .map(|index| (*index, self.get_row_offset(*index)))
+            .collect();
+        for (index, offset) in offsets {
+            if let Some(row) = self.rows.get_mut(&index) {
+                row.set_offset(offset);
+            }
+        }
+    }
+
+    fn get_size(&self) -> &Size {
+        &self.size
+    }
+
+    fn set_size(&mut self, size: Size) {
+        self.size = size;
+        self.plane.set_size(size);
+        self.selection_plane.set_size(Size {
+            width: size.width - SCROLLBAR_WIDTH,
+            height: self.row_height,
+        });
+        // the rows are built for the width of the list
+        self.refresh();
+    }
+
+    fn contains_child(&self, _: &UIElementHandle) -> bool {
+        false
+    }
+
+    fn get_offset(&self) -> &Offset {
+        &self.offset
+    }
+
+    fn add_child_to(
+        &mut self,
+        _: UIElementHandle,
+        _: Option<UIElementHandle>,
+        _: Box<dyn UIElement>,
+    ) {
+        panic!("ListView builds its rows from the row builder");
+    }
+
+    fn set_z_index(&mut self, z_index: f32) {
+        self.position.z = z_index;
+        self.plane.set_z_index(z_index);
+        self.selection_plane.set_z_index(z_index + 1.0);
+        self.scrollbar_plane.set_z_index(z_index + 1.0);
+        for row in self.rows.values_mut() {
+            row.set_z_index(z_index + 2.0);
+        }
+    }
+}
+
+impl ListViewBuilder {
+    pub fn new(item_count: Box<dyn Fn() -> usize>, build_row: RowBuilder) -> Self {
+        Self {
+            position: Position::default(),
+            size: Size::default(),
+            row_height: 20.0,
+            item_count,
+            build_row,
+            on_select: None,
+        }
+    }
+
+    pub fn position(mut self, x: f32, y: f32) -> Self {
+        self.position = Position { x, y, z: 0.0 };
+        self
+    }
+
+    pub fn size(mut self, width: f32, height: f32) -> Self {
+        self.size = Size { width, height };
+        self
+    }
+
+    pub fn row_height(mut self, row_height: f32) -> Self {
+        self.row_height = row_height;
+        self
+    }
+
+    /// Called with the index of every row the user selects by clicking or with the keyboard
+    pub fn on_select(mut self, on_select: SelectHandler) -> Self {
+        self.on_select = Some(on_select);
+        self
+    }
+
+    pub fn build(self) -> ListView {
+        let mut list_view = ListView::new(
+            self.position,
+            self.size,
+            self.row_height,
+            self.item_count,
+            self.build_row,
+        );
+        list_view.on_select = self.on_select;
+        list_view
+    }
+}
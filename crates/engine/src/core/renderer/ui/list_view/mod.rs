@@ -0,0 +1,45 @@
+use std::collections::BTreeMap;
+
+use crate::core::{renderer::plane::Plane, scene::Scene};
+
+use super::{primitives::Position, Offset, Size, UIElement};
+
+pub mod list_view;
+
+/// Builds the element shown for the row at the given index
+pub type RowBuilder = Box<dyn Fn(usize) -> Box<dyn UIElement>>;
+
+/// Called with the index of the row that was selected
+pub type SelectHandler = Box<dyn Fn(&mut Scene, usize)>;
+
+/// List of rows with a fixed height that only builds and draws the rows inside its viewport,
+/// so it stays fast with thousands of items.
+///
+/// Rows are built on demand by the row builder and dropped again once they scroll out of view,
+/// `refresh` rebuilds the visible rows after the underlying items changed
+pub struct ListView {
+    position: Position,
+    size: Size,
+    offset: Offset,
+    row_height: f32,
+    item_count: Box<dyn Fn() -> usize>,
+    build_row: RowBuilder,
+    on_select: Option<SelectHandler>,
+    /// Index of the first row in the viewport
+    scroll: usize,
+    selected: Option<usize>,
+    is_focused: bool,
+    rows: BTreeMap<usize, Box<dyn UIElement>>,
+    plane: Plane,
+    selection_plane: Plane,
+    scrollbar_plane: Plane,
+}
+
+pub struct ListViewBuilder {
+    position: Position,
+    size: Size,
+    row_height: f32,
+    item_count: Box<dyn Fn() -> usize>,
+    build_row: RowBuilder,
+    on_select: Option<SelectHandler>,
+}
@@ -1,23 +1,61 @@
 use std::collections::BTreeMap;
 
-use glfw::{Glfw, Window, WindowEvent};
-use primitives::{Offset, Size, UIElementHandle};
+use cursor::SoftwareCursor;
+use drag::SceneDropHandler;
+use glfw::{Action, Glfw, Key, StandardCursor, WindowEvent};
+use primitives::{Layout, Offset, Size, UIElementHandle};
 
 use crate::core::scene::Scene;
 
+pub mod action_map_editor;
+pub mod asset_browser;
 pub mod button;
 pub mod container;
+pub mod cursor;
+pub mod definition;
+pub mod drag;
+pub mod drag_value;
+pub mod harness;
 pub mod input;
+pub mod inspector;
+pub mod list_view;
 pub mod panel;
 pub mod popup;
 pub mod primitives;
+pub mod profiler_graph;
+pub mod scroll_container;
+pub mod tabs;
 pub mod text;
 pub mod ui;
 
 pub struct UI {}
 
+/// Window state UI elements read while handling events, implemented by the glfw window and by
+/// the `UITestHarness`
+pub trait UIWindow {
+    fn get_cursor_pos(&self) -> (f64, f64);
+    fn get_key(&self, key: Key) -> Action;
+    /// Shows the standard cursor, or the default arrow for `None`. The glfw window shows the
+    /// image of the `CursorTheme` instead if it has one for the shape
+    fn set_cursor(&mut self, cursor: Option<StandardCursor>);
+    /// Framebuffer pixels per unit of the cursor position, above 1 on displays where the
+    /// framebuffer is larger than the window like retina displays
+    fn get_pixel_ratio(&self) -> f64 {
+        1.0
+    }
+    fn get_clipboard_string(&self) -> Option<String>;
+    fn set_clipboard_string(&mut self, content: &str);
+}
+
 pub struct UIRenderer {
     children: BTreeMap<UIElementHandle, Box<dyn UIElement>>,
+    /// Placement of the top level elements that follow the window size, with the window size
+    /// their relative size was last applied for
+    layouts: BTreeMap<UIElementHandle, (Layout, Option<Size>)>,
+    /// Framebuffer size the layouts are applied for
+    window_size: Size,
+    on_scene_drop: Option<SceneDropHandler>,
+    cursor: SoftwareCursor,
 }
 
 pub trait UIElement {
@@ -25,7 +63,7 @@ pub trait UIElement {
     fn handle_events(
         &mut self,
         scene: &mut Scene,
-        window: &mut Window,
+        window: &mut dyn UIWindow,
         glfw: &mut Glfw,
         event: &WindowEvent,
     ) -> bool;
@@ -41,4 +79,26 @@ pub trait UIElement {
     fn set_offset(&mut self, offset: Offset);
     fn get_size(&self) -> &Size;
     fn set_z_index(&mut self, z_index: f32);
+    /// Resizes the element for a `Layout` with a relative size, elements with a fixed size
+    /// ignore it
+    fn set_size(&mut self, size: Size) {
+        let _ = size;
+    }
+    /// Whether the element is being dragged
+    fn is_dragging(&self) -> bool {
+        false
+    }
+    /// Removes the element being dragged from the children of this element or its descendants
+    fn take_dragged(&mut self) -> Option<(UIElementHandle, Box<dyn UIElement>)> {
+        None
+    }
+    /// Adds a dropped element to the container with a pending drop, hands it back if there is none
+    fn receive_drop(
+        &mut self,
+        handle: UIElementHandle,
+        element: Box<dyn UIElement>,
+    ) -> Option<Box<dyn UIElement>> {
+        let _ = handle;
+        Some(element)
+    }
 }
@@ -5,7 +5,7 @@ use crate::core::{
         ui::{
             container::{ContainerBuilder, Direction},
             primitives::{Position, Region},
-            Offset, Size, UIElement, UIElementHandle,
+            Offset, Size, UIElement, UIElementHandle, UIWindow,
         },
     },
     scene::Scene,
@@ -19,9 +19,9 @@ impl UIElement for Panel {
         if !self.collapsible || self.is_open {
             let content_size = self.content.get_size();
             self.header_plane.border_radius = (0.0, 5.0, 0.0, 5.0);
-            self.set_size(content_size + (0.0, 20.0));
+            self.resize(content_size + (0.0, 20.0));
         } else if self.collapsible && !self.is_open {
-            self.set_size(Size {
+            self.resize(Size {
                 width: self.size.width,
                 height: if self.has_controls { 24.0 } else { 20.0 },
             });
@@ -47,7 +47,7 @@ impl UIElement for Panel {
     fn handle_events(
         &mut self,
         scene: &mut Scene,
-        window: &mut glfw::Window,
+        window: &mut dyn UIWindow,
         glfw: &mut glfw::Glfw,
         event: &glfw::WindowEvent,
     ) -> bool {
@@ -111,7 +111,7 @@ impl UIElement for Panel {
                 );
                 if region.contains(x, y) {
                     if !self.is_hovering {
-                        window.set_cursor(Some(glfw::Cursor::standard(glfw::StandardCursor::Hand)));
+                        window.set_cursor(Some(glfw::StandardCursor::Hand));
                         self.is_hovering = true;
                         self.header_plane.set_color((0.3, 0.4, 0.6, 1.0));
                     }
@@ -123,8 +123,9 @@ impl UIElement for Panel {
                 if self.dragging {
                     // Update panel position while dragging
                     if let Some(position) = self.drag_start {
-                        self.position.x += x - position.x - self.offset.x;
-                        self.position.y += y - position.y - self.offset.y;
+                        // the offset stays, e.g. the one a layout anchors the panel with
+                        self.position.x += x - position.x;
+                        self.position.y += y - position.y;
                         self.drag_start = Some(Position {
                             x,
                             y,
@@ -163,6 +164,19 @@ impl UIElement for Panel {
         &self.size
     }
 
+    fn set_size(&mut self, size: Size) {
+        self.content.set_size(Size {
+            width: size.width,
+            height: (size.height - 20.0).max(0.0),
+        });
+        self.controls.set_position(Position {
+            x: size.width - self.controls.get_size().width - 2.5,
+            y: -2.0,
+            z: self.position.z + 1.0,
+        });
+        self.resize(size);
+    }
+
     fn contains_child(&self, handle: &UIElementHandle) -> bool {
         self.content.contains_child(handle)
     }
@@ -197,6 +211,18 @@ impl UIElement for Panel {
         self.content.set_z_index(z_index + 1.0);
         self.controls.set_z_index(z_index + 3.0);
     }
+
+    fn take_dragged(&mut self) -> Option<(UIElementHandle, Box<dyn UIElement>)> {
+        self.content.take_dragged()
+    }
+
+    fn receive_drop(
+        &mut self,
+        handle: UIElementHandle,
+        element: Box<dyn UIElement>,
+    ) -> Option<Box<dyn UIElement>> {
+        self.content.receive_drop(handle, element)
+    }
 }
 
 impl Panel {
@@ -253,7 +279,8 @@ impl Panel {
         panel
     }
 
-    pub fn set_size(&mut self, size: Size) {
+    /// Resizes the background and header to `size`, the content keeps its size
+    fn resize(&mut self, size: Size) {
         self.size = size;
         self.plane.set_size(size);
         self.header_plane.set_size(Size {
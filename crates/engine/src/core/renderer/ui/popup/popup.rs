@@ -1,7 +1,7 @@
 use crate::core::{
     renderer::{
         plane::{PlaneBuilder, PlaneRenderer},
-        ui::{panel::PanelBuilder, Offset, Size, UIElement, UIElementHandle, UI},
+        ui::{panel::PanelBuilder, Offset, Size, UIElement, UIElementHandle, UIWindow, UI},
     },
     scene::Scene,
     utils::DataSource,
@@ -48,7 +48,7 @@ impl UIElement for Popup {
     fn handle_events(
         &mut self,
         scene: &mut Scene,
-        window: &mut glfw::Window,
+        window: &mut dyn UIWindow,
         glfw: &mut glfw::Glfw,
         event: &glfw::WindowEvent,
     ) -> bool {
@@ -88,4 +88,16 @@ impl UIElement for Popup {
     fn set_z_index(&mut self, z_index: f32) {
         self.panel.set_z_index(z_index);
     }
+
+    fn take_dragged(&mut self) -> Option<(UIElementHandle, Box<dyn UIElement>)> {
+        self.panel.take_dragged()
+    }
+
+    fn receive_drop(
+        &mut self,
+        handle: UIElementHandle,
+        element: Box<dyn UIElement>,
+    ) -> Option<Box<dyn UIElement>> {
+        self.panel.receive_drop(handle, element)
+    }
 }
@@ -0,0 +1,50 @@
+use super::{Anchor, Layout, Offset, Size};
+
+impl Anchor {
+    /// Position of the anchor on a rectangle as shares of its width and height
+    pub fn get_factors(&self) -> (f32, f32) {
+        match self {
+            Anchor::TopLeft => (0.0, 0.0),
+            Anchor::Top => (0.5, 0.0),
+            Anchor::TopRight => (1.0, 0.0),
+            Anchor::Left => (0.0, 0.5),
+            Anchor::Center => (0.5, 0.5),
+            Anchor::Right => (1.0, 0.5),
+            Anchor::BottomLeft => (0.0, 1.0),
+            Anchor::Bottom => (0.5, 1.0),
+            Anchor::BottomRight => (1.0, 1.0),
+        }
+    }
+}
+
+impl Layout {
+    pub fn new(anchor: Anchor) -> Self {
+        Self {
+            anchor,
+            relative_size: None,
+        }
+    }
+
+    /// Resizes the element to `width` and `height` times the size of the window
+    pub fn relative_size(mut self, width: f32, height: f32) -> Self {
+        self.relative_size = Some((width, height));
+        self
+    }
+
+    /// Size the element is given in a window of `window_size`, None if it keeps its own
+    pub fn get_size(&self, window_size: Size) -> Option<Size> {
+        self.relative_size.map(|(width, height)| Size {
+            width: window_size.width * width,
+            height: window_size.height * height,
+        })
+    }
+
+    /// Offset that places an element of `size` at the anchor of a window of `window_size`
+    pub fn get_offset(&self, window_size: Size, size: Size) -> Offset {
+        let (x, y) = self.anchor.get_factors();
+        Offset {
+            x: (window_size.width - size.width) * x,
+            y: (window_size.height - size.height) * y,
+        }
+    }
+}
@@ -1,7 +1,10 @@
 use std::cmp::Ordering;
 
-use rand::Rng;
+use serde::{Deserialize, Serialize};
 
+use crate::core::determinism::Determinism;
+
+mod layout;
 mod offset;
 mod position;
 mod region;
@@ -13,7 +16,7 @@ pub struct UIElementHandle(u64);
 impl UIElementHandle {
     pub fn new() -> Self {
         Self {
-            0: rand::thread_rng().gen::<u64>(),
+            0: Determinism::next_id(),
         }
     }
     pub fn from(id: u64) -> Self {
@@ -51,3 +54,31 @@ pub struct Region {
     pub position: Position,
     pub size: Size,
 }
+
+/// Point of the window a top level element is anchored to, the same point of the element is
+/// placed on it
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Anchor {
+    #[default]
+    TopLeft,
+    Top,
+    TopRight,
+    Left,
+    Center,
+    Right,
+    BottomLeft,
+    Bottom,
+    BottomRight,
+}
+
+/// Placement of a top level element relative to the window, see `UIRenderer::set_layout`.
+///
+/// The position the element was built with is kept as its distance from the anchor, so an
+/// element anchored to the bottom right is moved in by building it at a negative position
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Layout {
+    pub anchor: Anchor,
+    /// Shares of the window's width and height the element is resized to, None keeps the size
+    /// it has. Panels and containers still grow past it when their content needs more room
+    pub relative_size: Option<(f32, f32)>,
+}
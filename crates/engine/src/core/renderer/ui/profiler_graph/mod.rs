@@ -0,0 +1,29 @@
+use crate::core::renderer::{plane::Plane, text::Text};
+
+use super::{primitives::Position, Offset, Size};
+
+pub mod profiler_graph;
+
+/// Graph of the frame times the `Profiler` recorded, with the update and render time, draw
+/// calls, chunk throughput and gpu pass times of the latest frames below it.
+///
+/// Shows nothing useful until profiling is enabled with `Profiler::set_enabled`
+pub struct ProfilerGraph {
+    position: Position,
+    size: Size,
+    offset: Offset,
+    /// Frame time in seconds at the top of the graph, longer frames are cut off
+    max_frame_time: f64,
+    background: Plane,
+    /// One bar per frame, the newest on the right
+    bars: Vec<Plane>,
+    /// Marks the frame time of 60 fps
+    budget_line: Plane,
+    lines: Vec<Text>,
+}
+
+pub struct ProfilerGraphBuilder {
+    position: Position,
+    size: Size,
+    max_frame_time: f64,
+}
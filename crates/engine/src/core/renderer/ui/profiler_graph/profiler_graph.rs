@@ -0,0 +1,224 @@
+use crate::core::{
+    profiler::Profiler,
+    renderer::{
+        plane::{PlaneBuilder, PlaneRenderer},
+        text::{Fonts, Text},
+        ui::{primitives::Position, Offset, Size, UIElement, UIElementHandle, UIWindow},
+    },
+    scene::Scene,
+};
+
+use super::{ProfilerGraph, ProfilerGraphBuilder};
+
+/// Height of the bar graph, the text lines are below it
+const GRAPH_HEIGHT: f32 = 80.0;
+const BAR_WIDTH: f32 = 2.0;
+const LINE_HEIGHT: f32 = 18.0;
+/// Text lines below the graph, the first three are the frame summary and the rest gpu passes
+const LINE_COUNT: usize = 11;
+const FRAME_BUDGET: f64 = 1.0 / 60.0;
+
+impl ProfilerGraph {
+    pub fn new(position: Position, size: Size, max_frame_time: f64) -> Self {
+        let bar_count = (size.width / BAR_WIDTH) as usize;
+        Self {
+            position,
+            size,
+            offset: Offset::default(),
+            max_frame_time,
+            background: PlaneBuilder::new()
+                .position(position)
+                .size(size)
+                .color((0.1, 0.1, 0.1, 0.8))
+                .border_radius_uniform(5.0)
+                .build(),
+            bars: (0..bar_count)
+                .map(|_| {
+                    PlaneBuilder::new()
+                        .position(position)
+                        .size(Size::default())
+                        .build()
+                })
+                .collect(),
+            budget_line: PlaneBuilder::new()
+                .position(position)
+                .size(Size {
+                    width: size.width,
+                    height: 1.0,
+                })
+                .color((1.0, 1.0, 1.0, 0.4))
+                .build(),
+            lines: (0..LINE_COUNT)
+                .map(|_| Text::new(Fonts::RobotoMono, 0, 0, 0, 14.0, String::new()))
+                .collect(),
+        }
+    }
+
+    fn get_bar_color(frame_time: f64) -> (f32, f32, f32, f32) {
+        if frame_time <= FRAME_BUDGET {
+            (0.3, 0.8, 0.3, 1.0)
+        } else if frame_time <= FRAME_BUDGET * 2.0 {
+            (0.9, 0.8, 0.2, 1.0)
+        } else {
+            (0.9, 0.3, 0.2, 1.0)
+        }
+    }
+
+    fn update_bars(&mut self, origin: Position) {
+        let frame_times = Profiler::get_frame_times();
+        let skipped = frame_times.len().saturating_sub(self.bars.len());
+        let first_bar = self.bars.len() - (frame_times.len() - skipped);
+        for (index, bar) in self.bars.iter_mut().enumerate() {
+            let Some(frame_time) = index
+                .checked_sub(first_bar)
+                .and_then(|frame| frame_times.get(skipped + frame))
+            else {
+                bar.set_size(Size::default());
+                continue;
+            };
+            let height = (frame_time / self.max_frame_time).min(1.0) as f32 * GRAPH_HEIGHT;
+            bar.set_color(ProfilerGraph::get_bar_color(*frame_time));
+            bar.set_position(&origin + (index as f32 * BAR_WIDTH, GRAPH_HEIGHT - height, 1.0));
+            bar.set_size(Size {
+                width: BAR_WIDTH,
+                height,
+            });
+        }
+    }
+
+    fn get_lines() -> Vec<String> {
+        let frame_times = Profiler::get_frame_times();
+        let average = frame_times.iter().sum::<f64>() / frame_times.len().max(1) as f64;
+        let longest = frame_times.iter().cloned().fold(0.0, f64::max);
+        let mut lines = vec![
+            format!(
+                "Frame {:.2}ms (max {:.2}ms)",
+                average * 1000.0,
+                longest * 1000.0
+            ),
+            format!(
+                "Update {:.2}ms  Render {:.2}ms",
+                Profiler::get_average_scope_time("update") * 1000.0,
+                Profiler::get_average_scope_time("render") * 1000.0
+            ),
+        ];
+        let Some(latest) = Profiler::get_latest() else {
+            return lines;
+        };
+        lines.push(format!(
+            "Draw calls {}  Chunks {:.1}/s",
+            latest.draw_calls,
+            Profiler::get_chunk_throughput()
+        ));
+        for (name, milliseconds) in &latest.gpu_passes {
+            lines.push(format!("  {} {:.2}ms", name, milliseconds));
+        }
+        lines
+    }
+}
+
+impl UIElement for ProfilerGraph {
+    fn render(&mut self, _: &mut Scene) {
+        let origin = &self.position + &self.offset;
+        PlaneRenderer::render(&self.background);
+        self.update_bars(origin);
+        for bar in &self.bars {
+            PlaneRenderer::render(bar);
+        }
+        let budget = (FRAME_BUDGET / self.max_frame_time).min(1.0) as f32 * GRAPH_HEIGHT;
+        self.budget_line
+            .set_position(&origin + (0.0, GRAPH_HEIGHT - budget, 2.0));
+        PlaneRenderer::render(&self.budget_line);
+
+        let contents = ProfilerGraph::get_lines();
+        for (index, line) in self.lines.iter_mut().enumerate() {
+            line.set_content(contents.get(index).map_or("", String::as_str));
+            line.render_at(&origin + (5.0, GRAPH_HEIGHT + 4.0 + index as f32 * LINE_HEIGHT, 1.0));
+        }
+    }
+
+    fn handle_events(
+        &mut self,
+        _: &mut Scene,
+        _: &mut dyn UIWindow,
+        _: &mut glfw::Glfw,
+        _: &glfw::WindowEvent,
+    ) -> bool {
+        false
+    }
+
+    fn add_children(&mut self, _: Vec<(Option<UIElementHandle>, Box<dyn UIElement>)>) {
+        panic!("ProfilerGraph cannot have children");
+    }
+
+    fn set_offset(&mut self, offset: Offset) {
+        self.offset = offset;
+        self.background.set_position(&self.position + &self.offset);
+    }
+
+    fn get_size(&self) -> &Size {
+        &self.size
+    }
+
+    fn contains_child(&self, _: &UIElementHandle) -> bool {
+        false
+    }
+
+    fn get_offset(&self) -> &Offset {
+        &self.offset
+    }
+
+    fn add_child_to(
+        &mut self,
+        _: UIElementHandle,
+        _: Option<UIElementHandle>,
+        _: Box<dyn UIElement>,
+    ) {
+        panic!("ProfilerGraph cannot have children");
+    }
+
+    fn set_z_index(&mut self, z_index: f32) {
+        self.position.z = z_index;
+        self.background.set_z_index(z_index);
+    }
+}
+
+impl ProfilerGraphBuilder {
+    pub fn new() -> Self {
+        Self {
+            position: Position::default(),
+            size: Size {
+                width: 240.0,
+                height: GRAPH_HEIGHT + 8.0 + LINE_COUNT as f32 * LINE_HEIGHT,
+            },
+            max_frame_time: FRAME_BUDGET * 3.0,
+        }
+    }
+
+    pub fn position(mut self, x: f32, y: f32) -> Self {
+        self.position = Position { x, y, z: 0.0 };
+        self
+    }
+
+    /// The graph shows one frame per 2 pixels of the width
+    pub fn size(mut self, width: f32, height: f32) -> Self {
+        self.size = Size { width, height };
+        self
+    }
+
+    /// Frame time in seconds at the top of the graph, three times the 60 fps budget by default
+    pub fn max_frame_time(mut self, max_frame_time: f64) -> Self {
+        self.max_frame_time = max_frame_time;
+        self
+    }
+
+    pub fn build(self) -> ProfilerGraph {
+        ProfilerGraph::new(self.position, self.size, self.max_frame_time)
+    }
+}
+
+impl Default for ProfilerGraphBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,29 @@
+use crate::core::renderer::plane::Plane;
+
+use super::{container::Container, primitives::Position, Offset, Size, UIElement, UIElementHandle};
+
+pub mod scroll_container;
+
+/// Fixed size viewport onto a vertical list of children that is scrolled with the mouse wheel
+/// or by dragging its scrollbar.
+///
+/// Children are clipped to the viewport with the scissor test and only receive clicks inside
+/// it, scroll containers can be nested
+pub struct ScrollContainer {
+    position: Position,
+    size: Size,
+    offset: Offset,
+    content: Container,
+    /// Distance the content is scrolled up by
+    scroll: f32,
+    /// Distance of the cursor from the top of the scrollbar while it is dragged
+    scrollbar_grab: Option<f32>,
+    plane: Plane,
+    scrollbar_plane: Plane,
+}
+
+pub struct ScrollContainerBuilder {
+    position: Position,
+    size: Size,
+    children: Vec<(Option<UIElementHandle>, Box<dyn UIElement>)>,
+}
@@ -0,0 +1,324 @@
+use std::sync::Mutex;
+
+use lazy_static::lazy_static;
+
+use crate::core::{
+    renderer::{
+        plane::{PlaneBuilder, PlaneRenderer},
+        ui::{
+            container::Container,
+            primitives::{Position, Region},
+            Offset, Size, UIElement, UIElementHandle, UIRenderer, UIWindow,
+        },
+    },
+    scene::Scene,
+};
+
+use super::{ScrollContainer, ScrollContainerBuilder};
+
+/// Pixels scrolled per step of the mouse wheel
+const SCROLL_SPEED: f32 = 30.0;
+const SCROLLBAR_WIDTH: f32 = 6.0;
+const MIN_SCROLLBAR_HEIGHT: f32 = 16.0;
+
+lazy_static! {
+    /// Window rectangles of the scroll containers being rendered as (left, top, right, bottom),
+    /// nested containers are clipped to the ones around them
+    static ref CLIP_STACK: Mutex<Vec<(f32, f32, f32, f32)>> = Mutex::new(Vec::new());
+}
+
+impl ScrollContainer {
+    pub fn new(position: Position, size: Size) -> Self {
+        let mut content = Container::new(
+            Position::default(),
+            Size {
+                width: size.width - SCROLLBAR_WIDTH,
+                height: 0.0,
+            },
+        );
+        content.set_z_index(position.z + 1.0);
+        Self {
+            position,
+            size,
+            offset: Offset::default(),
+            content,
+            scroll: 0.0,
+            scrollbar_grab: None,
+            plane: PlaneBuilder::new()
+                .position(position)
+                .size(size)
+                .color((0.0, 0.0, 0.0, 0.0))
+                .border_color((0.0, 0.0, 0.0, 0.0))
+                .build(),
+            scrollbar_plane: PlaneBuilder::new()
+                .position(position)
+                .size(Size {
+                    width: SCROLLBAR_WIDTH,
+                    height: size.height,
+                })
+                .color((0.4, 0.4, 0.4, 1.0))
+                .border_radius_uniform(3.0)
+                .build(),
+        }
+    }
+
+    pub fn get_scroll(&self) -> f32 {
+        self.scroll
+    }
+
+    /// Scrolls the content up by `scroll` pixels, limited to the height of the content
+    pub fn set_scroll(&mut self, scroll: f32) {
+        self.scroll = scroll.clamp(0.0, self.get_max_scroll());
+        self.content.set_offset(self.get_content_offset());
+    }
+
+    fn get_max_scroll(&self) -> f32 {
+        (self.content.get_size().height - self.size.height).max(0.0)
+    }
+
+    fn get_content_offset(&self) -> Offset {
+        &self.offset + &self.position + (0.0, -self.scroll)
+    }
+
+    /// Top of the scrollbar relative to the viewport and its height
+    fn get_scrollbar(&self) -> (f32, f32) {
+        let height = (self.size.height * self.size.height / self.content.get_size().height)
+            .clamp(MIN_SCROLLBAR_HEIGHT, self.size.height);
+        let progress = self.scroll / self.get_max_scroll().max(1.0);
+        ((self.size.height - height) * progress, height)
+    }
+
+    fn get_scrollbar_region(&self) -> Region {
+        let (top, height) = self.get_scrollbar();
+        Region::new_with_offset(
+            &self.position + (self.size.width - SCROLLBAR_WIDTH, top),
+            Size {
+                width: SCROLLBAR_WIDTH,
+                height,
+            },
+            self.offset,
+        )
+    }
+
+    /// Restricts rendering to the viewport intersected with the viewports around it
+    fn push_clip(&self) {
+        let left = self.offset.x + self.position.x;
+        let top = self.offset.y + self.position.y;
+        let mut clip_stack = CLIP_STACK.lock().unwrap();
+        let mut clip = (left, top, left + self.size.width, top + self.size.height);
+        if let Some(parent) = clip_stack.last() {
+            clip = (
+                clip.0.max(parent.0),
+                clip.1.max(parent.1),
+                clip.2.min(parent.2),
+                clip.3.min(parent.3),
+            );
+        }
+        clip_stack.push(clip);
+        ScrollContainer::apply_clip(clip);
+    }
+
+    fn pop_clip(&self) {
+        let mut clip_stack = CLIP_STACK.lock().unwrap();
+        clip_stack.pop();
+        match clip_stack.last() {
+            Some(clip) => ScrollContainer::apply_clip(*clip),
+            None => unsafe {
+                gl::Disable(gl::SCISSOR_TEST);
+            },
+        }
+    }
+
+    fn apply_clip((left, top, right, bottom): (f32, f32, f32, f32)) {
+        // the scissor box is in framebuffer pixels from the bottom left corner
+        let window = PlaneRenderer::get_size();
+        let scale = UIRenderer::get_scale();
+        unsafe {
+            gl::Enable(gl::SCISSOR_TEST);
+            gl::Scissor(
+                (left * scale).floor() as i32,
+                ((window.height - bottom) * scale).floor() as i32,
+                ((right - left).max(0.0) * scale).ceil() as i32,
+                ((bottom - top).max(0.0) * scale).ceil() as i32,
+            );
+        }
+    }
+}
+
+impl UIElement for ScrollContainer {
+    fn render(&mut self, scene: &mut Scene) {
+        self.scroll = self.scroll.min(self.get_max_scroll());
+        let content_offset = self.get_content_offset();
+        if content_offset != *self.content.get_offset() {
+            self.content.set_offset(content_offset);
+        }
+        PlaneRenderer::render(&self.plane);
+        self.push_clip();
+        self.content.render(scene);
+        self.pop_clip();
+        if self.get_max_scroll() > 0.0 {
+            let (top, height) = self.get_scrollbar();
+            self.scrollbar_plane.set_size(Size {
+                width: SCROLLBAR_WIDTH,
+                height,
+            });
+            self.scrollbar_plane.set_position(
+                &(&self.position + &self.offset) + (self.size.width - SCROLLBAR_WIDTH, top),
+            );
+            PlaneRenderer::render(&self.scrollbar_plane);
+        }
+    }
+
+    fn handle_events(
+        &mut self,
+        scene: &mut Scene,
+        window: &mut dyn UIWindow,
+        glfw: &mut glfw::Glfw,
+        event: &glfw::WindowEvent,
+    ) -> bool {
+        let region = Region::new_with_offset(self.position, self.size, self.offset);
+        let (x, y) = window.get_cursor_pos();
+        let (x, y) = (x as f32, y as f32);
+        match event {
+            glfw::WindowEvent::MouseButton(glfw::MouseButton::Button1, glfw::Action::Press, _) => {
+                // hidden children must not be clicked
+                if !region.contains(x, y) {
+                    return false;
+                }
+                if self.get_max_scroll() > 0.0 && self.get_scrollbar_region().contains(x, y) {
+                    let (top, _) = self.get_scrollbar();
+                    self.scrollbar_grab = Some(y - self.offset.y - self.position.y - top);
+                    return true;
+                }
+                self.content.handle_events(scene, window, glfw, event)
+            }
+            glfw::WindowEvent::MouseButton(
+                glfw::MouseButton::Button1,
+                glfw::Action::Release,
+                _,
+            ) if self.scrollbar_grab.is_some() => {
+                self.scrollbar_grab = None;
+                true
+            }
+            glfw::WindowEvent::CursorPos(_, cursor_y) if self.scrollbar_grab.is_some() => {
+                let grab = self.scrollbar_grab.unwrap_or_default();
+                let (_, height) = self.get_scrollbar();
+                let top = *cursor_y as f32 - self.offset.y - self.position.y - grab;
+                let progress = top / (self.size.height - height).max(1.0);
+                self.set_scroll(progress * self.get_max_scroll());
+                true
+            }
+            glfw::WindowEvent::Scroll(_, amount) => {
+                if !region.contains(x, y) {
+                    return false;
+                }
+                // nested scroll containers and lists scroll first
+                if self.content.handle_events(scene, window, glfw, event) {
+                    return true;
+                }
+                if self.get_max_scroll() <= 0.0 {
+                    return false;
+                }
+                self.set_scroll(self.scroll - *amount as f32 * SCROLL_SPEED);
+                true
+            }
+            _ => self.content.handle_events(scene, window, glfw, event),
+        }
+    }
+
+    fn add_children(&mut self, children: Vec<(Option<UIElementHandle>, Box<dyn UIElement>)>) {
+        self.content.add_children(children);
+    }
+
+    fn add_child_to(
+        &mut self,
+        parent: UIElementHandle,
+        id: Option<UIElementHandle>,
+        element: Box<dyn UIElement>,
+    ) {
+        self.content.add_child_to(parent, id, element);
+    }
+
+    fn contains_child(&self, handle: &UIElementHandle) -> bool {
+        self.content.contains_child(handle)
+    }
+
+    fn get_offset(&self) -> &Offset {
+        &self.offset
+    }
+
+    fn set_offset(&mut self, offset: Offset) {
+        self.offset = offset;
+        self.plane.set_position(&self.position + &self.offset);
+        self.content.set_offset(self.get_content_offset());
+    }
+
+    fn get_size(&self) -> &Size {
+        &self.size
+    }
+
+    fn set_size(&mut self, size: Size) {
+        self.size = size;
+        self.plane.set_size(size);
+        self.content.set_size(Size {
+            width: size.width - SCROLLBAR_WIDTH,
+            height: 0.0,
+        });
+    }
+
+    fn set_z_index(&mut self, z_index: f32) {
+        self.position.z = z_index;
+        self.plane.set_z_index(z_index);
+        self.scrollbar_plane.set_z_index(z_index + 2.0);
+        self.content.set_z_index(z_index + 1.0);
+    }
+
+    fn take_dragged(&mut self) -> Option<(UIElementHandle, Box<dyn UIElement>)> {
+        self.content.take_dragged()
+    }
+
+    fn receive_drop(
+        &mut self,
+        handle: UIElementHandle,
+        element: Box<dyn UIElement>,
+    ) -> Option<Box<dyn UIElement>> {
+        self.content.receive_drop(handle, element)
+    }
+}
+
+impl ScrollContainerBuilder {
+    pub fn new() -> Self {
+        Self {
+            position: Position::default(),
+            size: Size::default(),
+            children: Vec::new(),
+        }
+    }
+
+    pub fn position(mut self, x: f32, y: f32, z: f32) -> Self {
+        self.position = Position { x, y, z };
+        self
+    }
+
+    pub fn size(mut self, width: f32, height: f32) -> Self {
+        self.size = Size { width, height };
+        self
+    }
+
+    pub fn add_child(mut self, handle: Option<UIElementHandle>, child: Box<dyn UIElement>) -> Self {
+        self.children.push((handle, child));
+        self
+    }
+
+    pub fn build(self) -> ScrollContainer {
+        let mut scroll_container = ScrollContainer::new(self.position, self.size);
+        scroll_container.add_children(self.children);
+        scroll_container
+    }
+}
+
+impl Default for ScrollContainerBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
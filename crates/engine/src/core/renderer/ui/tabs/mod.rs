@@ -0,0 +1,52 @@
+use crate::core::renderer::{plane::Plane, text::Text};
+
+use super::{primitives::Position, Offset, Size, UIElement, UIElementHandle};
+
+pub mod tabs;
+
+/// Tab bar above the content of the active tab, so several tools can share one panel.
+///
+/// Tabs that do not fit into the bar are reached with the arrows at its ends or by scrolling
+/// over it, closeable tabs are removed with the cross next to their title
+pub struct Tabs {
+    position: Position,
+    size: Size,
+    offset: Offset,
+    tabs: Vec<Tab>,
+    active: usize,
+    /// Index of the first tab shown in the bar
+    scroll: usize,
+    bar_plane: Plane,
+    previous_plane: Plane,
+    next_plane: Plane,
+    previous_text: Text,
+    next_text: Text,
+}
+
+struct Tab {
+    handle: UIElementHandle,
+    title: String,
+    closeable: bool,
+    content: Box<dyn UIElement>,
+    /// Width in the bar, measured when the title is rendered
+    width: f32,
+    /// Horizontal position in the bar the plane was last moved to
+    x: f32,
+    plane: Plane,
+    text: Text,
+    close_text: Text,
+}
+
+pub struct TabsBuilder {
+    position: Position,
+    size: Size,
+    tabs: Vec<PendingTab>,
+}
+
+/// Tab added to the builder, created once the tabs are built
+struct PendingTab {
+    handle: Option<UIElementHandle>,
+    title: String,
+    content: Box<dyn UIElement>,
+    closeable: bool,
+}
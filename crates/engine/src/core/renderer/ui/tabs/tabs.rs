@@ -0,0 +1,451 @@
+use crate::core::{
+    renderer::{
+        plane::{PlaneBuilder, PlaneRenderer},
+        text::{Fonts, Text},
+        ui::{
+            primitives::{Position, Region},
+            Offset, Size, UIElement, UIElementHandle, UIWindow,
+        },
+    },
+    scene::Scene,
+};
+
+use super::{PendingTab, Tab, Tabs, TabsBuilder};
+
+const TAB_BAR_HEIGHT: f32 = 22.0;
+const TAB_PADDING: f32 = 8.0;
+const CLOSE_WIDTH: f32 = 14.0;
+const ARROW_WIDTH: f32 = 18.0;
+const TITLE_SIZE: f32 = 16.0;
+const BAR_COLOR: (f32, f32, f32, f32) = (0.1, 0.1, 0.1, 1.0);
+const TAB_COLOR: (f32, f32, f32, f32) = (0.15, 0.15, 0.15, 1.0);
+const ACTIVE_TAB_COLOR: (f32, f32, f32, f32) = (0.2, 0.3, 0.5, 1.0);
+
+impl Tab {
+    fn new(
+        handle: UIElementHandle,
+        title: &str,
+        closeable: bool,
+        content: Box<dyn UIElement>,
+    ) -> Self {
+        // initial estimate, corrected once the title is rendered
+        let width = title.len() as f32 * TITLE_SIZE * 0.6
+            + 2.0 * TAB_PADDING
+            + if closeable { CLOSE_WIDTH } else { 0.0 };
+        Self {
+            handle,
+            title: title.to_string(),
+            closeable,
+            content,
+            width,
+            x: f32::NAN,
+            plane: PlaneBuilder::new()
+                .size(Size {
+                    width,
+                    height: TAB_BAR_HEIGHT,
+                })
+                .color(TAB_COLOR)
+                .border_radius((3.0, 0.0, 3.0, 0.0))
+                .build(),
+            text: Text::new(Fonts::RobotoMono, 0, 0, 0, TITLE_SIZE, title.to_string()),
+            close_text: Text::new(Fonts::RobotoMono, 0, 0, 0, TITLE_SIZE, "x".to_string()),
+        }
+    }
+}
+
+impl Tabs {
+    pub fn new(position: Position, size: Size) -> Self {
+        let arrow = PlaneBuilder::new()
+            .size(Size {
+                width: ARROW_WIDTH,
+                height: TAB_BAR_HEIGHT,
+            })
+            .color(TAB_COLOR);
+        let mut tabs = Self {
+            position,
+            size,
+            offset: Offset::default(),
+            tabs: Vec::new(),
+            active: 0,
+            scroll: 0,
+            bar_plane: PlaneBuilder::new()
+                .position(position)
+                .size(Size {
+                    width: size.width,
+                    height: TAB_BAR_HEIGHT,
+                })
+                .color(BAR_COLOR)
+                .build(),
+            previous_plane: arrow.build(),
+            next_plane: arrow.build(),
+            previous_text: Text::new(Fonts::RobotoMono, 0, 0, 0, TITLE_SIZE, "<".to_string()),
+            next_text: Text::new(Fonts::RobotoMono, 0, 0, 0, TITLE_SIZE, ">".to_string()),
+        };
+        tabs.set_z_index(position.z);
+        tabs
+    }
+
+    /// Appends a tab showing `content`, the first tab becomes active
+    pub fn add_tab(
+        &mut self,
+        handle: Option<UIElementHandle>,
+        title: &str,
+        mut content: Box<dyn UIElement>,
+        closeable: bool,
+    ) -> UIElementHandle {
+        let handle = handle.unwrap_or(UIElementHandle::new());
+        content.set_z_index(self.position.z + 1.0);
+        content.set_offset(self.get_content_offset());
+        let mut tab = Tab::new(handle, title, closeable, content);
+        tab.plane.set_z_index(self.position.z + 1.0);
+        self.tabs.push(tab);
+        self.update_tab_colors();
+        handle
+    }
+
+    /// Removes the tab with `handle` and returns its content
+    pub fn close_tab(&mut self, handle: &UIElementHandle) -> Option<Box<dyn UIElement>> {
+        let index = self.tabs.iter().position(|tab| tab.handle == *handle)?;
+        let tab = self.tabs.remove(index);
+        if index < self.active || self.active >= self.tabs.len() {
+            self.active = self.active.saturating_sub(1);
+        }
+        self.scroll = self.scroll.min(self.tabs.len().saturating_sub(1));
+        self.update_tab_colors();
+        Some(tab.content)
+    }
+
+    pub fn get_active(&self) -> Option<UIElementHandle> {
+        self.tabs.get(self.active).map(|tab| tab.handle)
+    }
+
+    pub fn get_active_title(&self) -> Option<&str> {
+        self.tabs.get(self.active).map(|tab| tab.title.as_str())
+    }
+
+    pub fn set_active(&mut self, handle: &UIElementHandle) {
+        if let Some(index) = self.tabs.iter().position(|tab| tab.handle == *handle) {
+            self.activate(index);
+        }
+    }
+
+    fn activate(&mut self, index: usize) {
+        self.active = index;
+        self.update_tab_colors();
+        if index < self.scroll {
+            self.scroll = index;
+        }
+        while self.scroll < index && !self.layout().iter().any(|(i, _)| *i == index) {
+            self.scroll += 1;
+        }
+    }
+
+    fn update_tab_colors(&mut self) {
+        for (index, tab) in self.tabs.iter_mut().enumerate() {
+            tab.plane.set_color(if index == self.active {
+                ACTIVE_TAB_COLOR
+            } else {
+                TAB_COLOR
+            });
+        }
+    }
+
+    fn get_content_offset(&self) -> Offset {
+        &self.offset + &self.position + (0.0, TAB_BAR_HEIGHT)
+    }
+
+    fn is_overflowing(&self) -> bool {
+        self.tabs.iter().map(|tab| tab.width).sum::<f32>() > self.size.width
+    }
+
+    /// Tabs that fit into the bar starting at the scrolled to tab, with their horizontal position
+    fn layout(&self) -> Vec<(usize, f32)> {
+        let (start, end) = if self.is_overflowing() {
+            (ARROW_WIDTH, self.size.width - ARROW_WIDTH)
+        } else {
+            (0.0, self.size.width)
+        };
+        let mut x = start;
+        let mut layout = Vec::new();
+        for (index, tab) in self.tabs.iter().enumerate().skip(self.scroll) {
+            // the first tab is always shown, even if it is wider than the bar
+            if x + tab.width > end && x > start {
+                break;
+            }
+            layout.push((index, x));
+            x += tab.width;
+        }
+        layout
+    }
+
+    fn scroll_by(&mut self, delta: isize) {
+        let max_scroll = self.tabs.len().saturating_sub(1) as isize;
+        self.scroll = (self.scroll as isize + delta).clamp(0, max_scroll) as usize;
+    }
+
+    fn render_arrows(&mut self, origin: Position) {
+        PlaneRenderer::render(&self.previous_plane);
+        PlaneRenderer::render(&self.next_plane);
+        self.previous_text.render_at(&origin + (5.0, 2.0, 2.0));
+        self.next_text
+            .render_at(&origin + (self.size.width - ARROW_WIDTH + 5.0, 2.0, 2.0));
+    }
+}
+
+impl UIElement for Tabs {
+    fn render(&mut self, scene: &mut Scene) {
+        let origin = &self.position + &self.offset;
+        PlaneRenderer::render(&self.bar_plane);
+        for (index, x) in self.layout() {
+            let tab = &mut self.tabs[index];
+            if tab.x != x {
+                tab.x = x;
+                tab.plane.set_position(&origin + (x, 0.0, 1.0));
+            }
+            PlaneRenderer::render(&tab.plane);
+            let (title_width, _) = tab.text.render_at(&origin + (x + TAB_PADDING, 2.0, 2.0));
+            let mut width = title_width as f32 + 2.0 * TAB_PADDING;
+            if tab.closeable {
+                tab.close_text
+                    .render_at(&origin + (x + width - TAB_PADDING / 2.0, 2.0, 2.0));
+                width += CLOSE_WIDTH;
+            }
+            if tab.width != width {
+                tab.width = width;
+                tab.plane.set_size(Size {
+                    width,
+                    height: TAB_BAR_HEIGHT,
+                });
+            }
+        }
+        if self.is_overflowing() {
+            self.render_arrows(origin);
+        }
+
+        let content_offset = self.get_content_offset();
+        let mut content_height = 0.0;
+        if let Some(tab) = self.tabs.get_mut(self.active) {
+            if *tab.content.get_offset() != content_offset {
+                tab.content.set_offset(content_offset);
+            }
+            tab.content.render(scene);
+            content_height = tab.content.get_size().height;
+        }
+        self.size.height = TAB_BAR_HEIGHT + content_height;
+    }
+
+    fn handle_events(
+        &mut self,
+        scene: &mut Scene,
+        window: &mut dyn UIWindow,
+        glfw: &mut glfw::Glfw,
+        event: &glfw::WindowEvent,
+    ) -> bool {
+        if let Some(tab) = self.tabs.get_mut(self.active) {
+            if tab.content.handle_events(scene, window, glfw, event) {
+                return true;
+            }
+        }
+        let bar = Region::new_with_offset(
+            self.position,
+            Size {
+                width: self.size.width,
+                height: TAB_BAR_HEIGHT,
+            },
+            self.offset,
+        );
+        match event {
+            glfw::WindowEvent::MouseButton(glfw::MouseButton::Button1, glfw::Action::Press, _) => {
+                let (x, y) = window.get_cursor_pos();
+                if !bar.contains(x as f32, y as f32) {
+                    return false;
+                }
+                let x = x as f32 - self.position.x - self.offset.x;
+                if self.is_overflowing() {
+                    if x < ARROW_WIDTH {
+                        self.scroll_by(-1);
+                        return true;
+                    }
+                    if x > self.size.width - ARROW_WIDTH {
+                        self.scroll_by(1);
+                        return true;
+                    }
+                }
+                let clicked = self
+                    .layout()
+                    .into_iter()
+                    .find(|(index, tab_x)| x >= *tab_x && x < tab_x + self.tabs[*index].width);
+                if let Some((index, tab_x)) = clicked {
+                    let tab = &self.tabs[index];
+                    if tab.closeable && x >= tab_x + tab.width - CLOSE_WIDTH - TAB_PADDING {
+                        let handle = tab.handle;
+                        self.close_tab(&handle);
+                    } else {
+                        self.activate(index);
+                    }
+                }
+                true
+            }
+            glfw::WindowEvent::Scroll(_, y) => {
+                let (x, cursor_y) = window.get_cursor_pos();
+                if !bar.contains(x as f32, cursor_y as f32) || *y == 0.0 {
+                    return false;
+                }
+                self.scroll_by(-y.signum() as isize);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Adds the children to the content of the active tab
+    fn add_children(&mut self, children: Vec<(Option<UIElementHandle>, Box<dyn UIElement>)>) {
+        if let Some(tab) = self.tabs.get_mut(self.active) {
+            tab.content.add_children(children);
+        }
+    }
+
+    fn add_child_to(
+        &mut self,
+        parent: UIElementHandle,
+        id: Option<UIElementHandle>,
+        element: Box<dyn UIElement>,
+    ) {
+        if let Some(tab) = self.tabs.iter_mut().find(|tab| tab.handle == parent) {
+            tab.content.add_children(vec![(id, element)]);
+            return;
+        }
+        if let Some(tab) = self
+            .tabs
+            .iter_mut()
+            .find(|tab| tab.content.contains_child(&parent))
+        {
+            tab.content.add_child_to(parent, id, element);
+        }
+    }
+
+    fn contains_child(&self, handle: &UIElementHandle) -> bool {
+        self.tabs
+            .iter()
+            .any(|tab| tab.handle == *handle || tab.content.contains_child(handle))
+    }
+
+    fn get_offset(&self) -> &Offset {
+        &self.offset
+    }
+
+    fn set_offset(&mut self, offset: Offset) {
+        self.offset = offset;
+        let origin = &self.position + &self.offset;
+        self.bar_plane.set_position(origin);
+        self.previous_plane.set_position(&origin + (0.0, 0.0, 1.0));
+        self.next_plane
+            .set_position(&origin + (self.size.width - ARROW_WIDTH, 0.0, 1.0));
+        for tab in &mut self.tabs {
+            // moved into place on the next render
+            tab.x = f32::NAN;
+        }
+        let content_offset = self.get_content_offset();
+        if let Some(tab) = self.tabs.get_mut(self.active) {
+            tab.content.set_offset(content_offset);
+        }
+    }
+
+    fn get_size(&self) -> &Size {
+        &self.size
+    }
+
+    fn set_z_index(&mut self, z_index: f32) {
+        self.position.z = z_index;
+        self.bar_plane.set_z_index(z_index);
+        self.previous_plane.set_z_index(z_index + 1.0);
+        self.next_plane.set_z_index(z_index + 1.0);
+        self.previous_text.set_z_index(z_index + 2.0);
+        self.next_text.set_z_index(z_index + 2.0);
+        for tab in &mut self.tabs {
+            tab.plane.set_z_index(z_index + 1.0);
+            tab.content.set_z_index(z_index + 1.0);
+        }
+    }
+
+    fn take_dragged(&mut self) -> Option<(UIElementHandle, Box<dyn UIElement>)> {
+        self.tabs.get_mut(self.active)?.content.take_dragged()
+    }
+
+    fn receive_drop(
+        &mut self,
+        handle: UIElementHandle,
+        element: Box<dyn UIElement>,
+    ) -> Option<Box<dyn UIElement>> {
+        match self.tabs.get_mut(self.active) {
+            Some(tab) => tab.content.receive_drop(handle, element),
+            None => Some(element),
+        }
+    }
+}
+
+impl TabsBuilder {
+    pub fn new() -> Self {
+        Self {
+            position: Position::default(),
+            size: Size::default(),
+            tabs: Vec::new(),
+        }
+    }
+
+    pub fn position(mut self, x: f32, y: f32, z: f32) -> Self {
+        self.position = Position { x, y, z };
+        self
+    }
+
+    pub fn size(mut self, width: f32, height: f32) -> Self {
+        self.size = Size { width, height };
+        self
+    }
+
+    pub fn add_tab(
+        mut self,
+        handle: Option<UIElementHandle>,
+        title: &str,
+        content: Box<dyn UIElement>,
+    ) -> Self {
+        self.tabs.push(PendingTab {
+            handle,
+            title: title.to_string(),
+            content,
+            closeable: false,
+        });
+        self
+    }
+
+    /// Adds a tab that can be closed with the cross next to its title
+    pub fn add_closeable_tab(
+        mut self,
+        handle: Option<UIElementHandle>,
+        title: &str,
+        content: Box<dyn UIElement>,
+    ) -> Self {
+        self.tabs.push(PendingTab {
+            handle,
+            title: title.to_string(),
+            content,
+            closeable: true,
+        });
+        self
+    }
+
+    pub fn build(self) -> Tabs {
+        let mut tabs = Tabs::new(self.position, self.size);
+        for tab in self.tabs {
+            tabs.add_tab(tab.handle, &tab.title, tab.content, tab.closeable);
+        }
+        tabs.set_offset(Offset::default());
+        tabs
+    }
+}
+
+impl Default for TabsBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
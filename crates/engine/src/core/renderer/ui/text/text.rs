@@ -1,7 +1,7 @@
 use crate::core::{
     renderer::{
-        text::Fonts,
-        ui::{primitives::Position, Offset, Size, UIElement, UIElementHandle},
+        text::{Fonts, TextAlignment},
+        ui::{primitives::Position, Offset, Size, UIElement, UIElementHandle, UIWindow},
     },
     scene::Scene,
 };
@@ -28,6 +28,23 @@ impl Text {
             z: 0.0,
         }
     }
+
+    /// Renders the text in `font`, e.g. `FontManager::get_font("Inter", 700)` for bold Inter
+    pub fn font(mut self, font: Fonts) -> Self {
+        self.text.set_font(font);
+        self
+    }
+
+    /// Wraps the text between words to stay within `max_width`
+    pub fn max_width(mut self, max_width: f32) -> Self {
+        self.text.set_max_width(Some(max_width));
+        self
+    }
+
+    pub fn alignment(mut self, alignment: TextAlignment) -> Self {
+        self.text.set_alignment(alignment);
+        self
+    }
 }
 
 impl UIElement for Text {
@@ -49,7 +66,7 @@ impl UIElement for Text {
     fn handle_events(
         &mut self,
         _: &mut Scene,
-        _window: &mut glfw::Window,
+        _window: &mut dyn UIWindow,
         _: &mut glfw::Glfw,
         _event: &glfw::WindowEvent,
     ) -> bool {
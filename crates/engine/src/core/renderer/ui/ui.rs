@@ -1,32 +1,204 @@
-use std::{collections::BTreeMap, str::FromStr};
+use std::{collections::BTreeMap, str::FromStr, sync::Mutex};
 
-use glfw::{Glfw, WindowEvent};
+use glfw::{Action, GamepadButton, Glfw, Key, Modifiers, MouseButton, WindowEvent};
+use lazy_static::lazy_static;
 
-use crate::core::{scene::Scene, utils::DataSource};
+use crate::core::{
+    input::GamepadEvent, renderer::plane::PlaneRenderer, scene::Scene, utils::DataSource,
+};
 
 use super::{
+    action_map_editor::{ActionMapEditor, ActionMapEditorBuilder},
+    asset_browser::{AssetBrowser, AssetBrowserBuilder},
     button::{Button, ButtonBuilder},
     container::{Container, ContainerBuilder},
+    cursor::{CursorTheme, SoftwareCursor},
+    drag::{DragPayload, Draggable, SceneDropHandler},
+    drag_value::{DragNumber, DragValue, DragValueBuilder},
     input::{Input, InputBuilder},
+    inspector::{Inspector, InspectorBuilder},
+    list_view::{ListView, ListViewBuilder, RowBuilder},
     panel::{Panel, PanelBuilder},
     popup::Popup,
+    primitives::Layout,
+    profiler_graph::{ProfilerGraph, ProfilerGraphBuilder},
+    scroll_container::{ScrollContainer, ScrollContainerBuilder},
+    tabs::{Tabs, TabsBuilder},
     text::Text,
-    UIElement, UIElementHandle, UIRenderer, UI,
+    Size, UIElement, UIElementHandle, UIRenderer, UIWindow, UI,
 };
 
+lazy_static! {
+    /// Content scale of the window and the scale set with `UIRenderer::set_scale`
+    static ref SCALE: Mutex<(f32, f32)> = Mutex::new((1.0, 1.0));
+}
+
+/// Window the cursor positions of are converted into UI units
+struct ScaledWindow<'a> {
+    window: &'a mut dyn UIWindow,
+    /// UI units per unit of the cursor position
+    factor: f64,
+}
+
+impl UIWindow for glfw::Window {
+    fn get_cursor_pos(&self) -> (f64, f64) {
+        glfw::Window::get_cursor_pos(self)
+    }
+
+    fn get_key(&self, key: glfw::Key) -> glfw::Action {
+        glfw::Window::get_key(self, key)
+    }
+
+    fn set_cursor(&mut self, cursor: Option<glfw::StandardCursor>) {
+        CursorTheme::apply(self, cursor);
+    }
+
+    fn get_clipboard_string(&self) -> Option<String> {
+        glfw::Window::get_clipboard_string(self)
+    }
+
+    fn get_pixel_ratio(&self) -> f64 {
+        let (framebuffer_width, _) = self.get_framebuffer_size();
+        let (width, _) = self.get_size();
+        if width > 0 {
+            framebuffer_width as f64 / width as f64
+        } else {
+            1.0
+        }
+    }
+
+    fn set_clipboard_string(&mut self, content: &str) {
+        glfw::Window::set_clipboard_string(self, content);
+    }
+}
+
+impl UIWindow for ScaledWindow<'_> {
+    fn get_cursor_pos(&self) -> (f64, f64) {
+        let (x, y) = self.window.get_cursor_pos();
+        (x * self.factor, y * self.factor)
+    }
+
+    fn get_key(&self, key: glfw::Key) -> glfw::Action {
+        self.window.get_key(key)
+    }
+
+    fn set_cursor(&mut self, cursor: Option<glfw::StandardCursor>) {
+        self.window.set_cursor(cursor);
+    }
+
+    fn get_clipboard_string(&self) -> Option<String> {
+        self.window.get_clipboard_string()
+    }
+
+    fn set_clipboard_string(&mut self, content: &str) {
+        self.window.set_clipboard_string(content);
+    }
+}
+
 impl UIRenderer {
     pub fn new() -> Self {
         Self {
             children: BTreeMap::new(),
+            layouts: BTreeMap::new(),
+            window_size: PlaneRenderer::get_size(),
+            on_scene_drop: None,
+            cursor: SoftwareCursor::new(),
         }
     }
 
+    /// Called with the payloads of drags that end outside of the UI, e.g. to place a dragged
+    /// asset in the scene
+    pub fn on_scene_drop(&mut self, on_scene_drop: SceneDropHandler) {
+        self.on_scene_drop = Some(on_scene_drop);
+    }
+
     pub fn add(&mut self, element: Box<dyn UIElement>) -> UIElementHandle {
         let handle = UIElementHandle::new();
         self.children.insert(handle, element);
         handle
     }
 
+    /// Adds `element` placed by `layout`, see `set_layout`
+    pub fn add_with_layout(
+        &mut self,
+        element: Box<dyn UIElement>,
+        layout: Layout,
+    ) -> UIElementHandle {
+        let handle = self.add(element);
+        self.set_layout(handle, layout);
+        handle
+    }
+
+    /// Anchors the top level element `handle` to the window and sizes it relative to it, the
+    /// element is moved along whenever the window is resized
+    pub fn set_layout(&mut self, handle: UIElementHandle, layout: Layout) {
+        if self.children.contains_key(&handle) {
+            self.layouts.insert(handle, (layout, None));
+            self.reflow();
+        }
+    }
+
+    /// Lets the top level element `handle` keep its place when the window is resized
+    pub fn remove_layout(&mut self, handle: &UIElementHandle) {
+        self.layouts.remove(handle);
+    }
+
+    /// Reflows the anchored elements for a framebuffer of `width` x `height`
+    pub fn resize(&mut self, width: u32, height: u32) {
+        let scale = UIRenderer::get_scale();
+        self.window_size = Size {
+            width: width as f32 / scale,
+            height: height as f32 / scale,
+        };
+        self.reflow();
+    }
+
+    /// Scales the whole UI by `scale` on top of the content scale of the window. Positions and
+    /// sizes of the elements stay in UI units, which are `get_scale` framebuffer pixels wide
+    pub fn set_scale(scale: f32) {
+        SCALE.lock().unwrap().1 = scale.max(f32::EPSILON);
+    }
+
+    /// Sets the content scale of the monitor the window is on, called by the application when
+    /// the window is created and whenever the content scale changes
+    pub fn set_content_scale(scale: f32) {
+        SCALE.lock().unwrap().0 = scale.max(f32::EPSILON);
+    }
+
+    /// Framebuffer pixels per UI unit, the content scale times the scale set with `set_scale`
+    pub fn get_scale() -> f32 {
+        let (content_scale, scale) = *SCALE.lock().unwrap();
+        content_scale * scale
+    }
+
+    /// Applies the layouts for the current window size. Sizes are only applied once per window
+    /// size, while the anchoring is checked every frame as panels grow with their content
+    fn reflow(&mut self) {
+        self.layouts
+            .retain(|handle, _| self.children.contains_key(handle));
+        for (handle, (layout, applied_for)) in self.layouts.iter_mut() {
+            let Some(element) = self.children.get_mut(handle) else {
+                continue;
+            };
+            if *applied_for != Some(self.window_size) {
+                if let Some(size) = layout.get_size(self.window_size) {
+                    element.set_size(size);
+                }
+                *applied_for = Some(self.window_size);
+            }
+            let offset = layout.get_offset(self.window_size, *element.get_size());
+            if *element.get_offset() != offset {
+                element.set_offset(offset);
+            }
+        }
+    }
+
+    /// Removes the top level element `handle` along with its layout
+    pub fn remove(&mut self, handle: &UIElementHandle) -> Option<Box<dyn UIElement>> {
+        self.layouts.remove(handle);
+        self.children.remove(handle)
+    }
+
     pub fn insert(&mut self, key: UIElementHandle, element: Box<dyn UIElement>) {
         self.children.insert(key, element);
     }
@@ -50,24 +222,124 @@ impl UIRenderer {
     }
 
     pub fn render(&mut self, scene: &mut Scene) {
+        // the window size in UI units changes with the scale as well
+        self.window_size = PlaneRenderer::get_size();
+        self.reflow();
         for (_, child) in &mut self.children {
             child.render(scene);
         }
+        if SoftwareCursor::is_enabled() {
+            self.cursor.render();
+        }
     }
 
     pub fn handle_events(
         &mut self,
         scene: &mut Scene,
-        window: &mut glfw::Window,
+        window: &mut dyn UIWindow,
         glfw: &mut Glfw,
         event: &WindowEvent,
     ) -> bool {
+        if let WindowEvent::FramebufferSize(width, height) = event {
+            self.resize(*width as u32, *height as u32);
+        }
+        // elements hit test in UI units
+        let factor = window.get_pixel_ratio() / UIRenderer::get_scale() as f64;
+        let event = &match event {
+            WindowEvent::CursorPos(x, y) => WindowEvent::CursorPos(x * factor, y * factor),
+            event => event.clone(),
+        };
+        if let WindowEvent::CursorPos(x, y) = event {
+            self.cursor
+                .set_position((*x as f32, *y as f32), self.window_size);
+        }
+        let window = &mut ScaledWindow { window, factor };
+        let mut handled = false;
         for (_, child) in &mut self.children {
             if child.handle_events(scene, window, glfw, event) {
-                return true;
+                handled = true;
+                break;
             }
         }
-        false
+        if let glfw::WindowEvent::MouseButton(
+            glfw::MouseButton::Button1,
+            glfw::Action::Release,
+            _,
+        ) = event
+        {
+            let payload = Draggable::get_dragged_payload();
+            if Draggable::finish_drag() {
+                self.move_dropped();
+            } else if let (Some(payload), Some(on_scene_drop)) = (payload, &self.on_scene_drop) {
+                if !handled {
+                    let (x, y) = window.get_cursor_pos();
+                    let cursor = (
+                        2.0 * x as f32 / self.window_size.width - 1.0,
+                        1.0 - 2.0 * y as f32 / self.window_size.height,
+                    );
+                    on_scene_drop(scene, &payload, cursor);
+                }
+            }
+        }
+        handled
+    }
+
+    /// Navigates the UI with a gamepad, the d-pad acts as the arrow keys, A clicks at the cursor
+    /// and B as escape
+    pub fn handle_gamepad_event(
+        &mut self,
+        scene: &mut Scene,
+        window: &mut dyn UIWindow,
+        glfw: &mut Glfw,
+        event: &GamepadEvent,
+    ) -> bool {
+        let (button, action) = match event {
+            GamepadEvent::ButtonPressed { button, .. } => (button, Action::Press),
+            GamepadEvent::ButtonReleased { button, .. } => (button, Action::Release),
+            _ => return false,
+        };
+        let key = match button {
+            GamepadButton::ButtonDpadUp => Key::Up,
+            GamepadButton::ButtonDpadDown => Key::Down,
+            GamepadButton::ButtonDpadLeft => Key::Left,
+            GamepadButton::ButtonDpadRight => Key::Right,
+            GamepadButton::ButtonB => Key::Escape,
+            GamepadButton::ButtonA => {
+                let event =
+                    WindowEvent::MouseButton(MouseButton::Button1, action, Modifiers::empty());
+                return self.handle_events(scene, window, glfw, &event);
+            }
+            _ => return false,
+        };
+        let event = WindowEvent::Key(key, 0, action, Modifiers::empty());
+        self.handle_events(scene, window, glfw, &event)
+    }
+
+    /// Moves the dragged element into the container it was dropped on
+    fn move_dropped(&mut self) {
+        let dragged = match self
+            .children
+            .iter()
+            .find(|(_, child)| child.is_dragging())
+            .map(|(handle, _)| *handle)
+        {
+            Some(handle) => self.children.remove(&handle).map(|child| (handle, child)),
+            None => self
+                .children
+                .values_mut()
+                .find_map(|child| child.take_dragged()),
+        };
+        let Some((handle, mut element)) = dragged else {
+            return;
+        };
+        for child in self.children.values_mut() {
+            match child.receive_drop(handle, element) {
+                Some(rejected) => element = rejected,
+                None => return,
+            }
+        }
+        // the container vanished while dragging, the element stays on the top level
+        self.children.insert(handle, element);
     }
 
     pub fn contains_key(&self, key: &UIElementHandle) -> bool {
@@ -125,6 +397,20 @@ impl UI {
         Box::new(builder.build())
     }
 
+    /// Number field bound to `data_source` that is adjusted by dragging or typed into
+    pub fn drag_value<T: DragNumber, InitFn>(
+        data_source: DataSource<T>,
+        init_fn: InitFn,
+    ) -> Box<DragValue<T>>
+    where
+        InitFn: FnOnce(DragValueBuilder<T>) -> DragValueBuilder<T> + 'static,
+    {
+        let mut builder = DragValueBuilder::new(data_source);
+        builder = builder.size(100.0, 26.0);
+        builder = init_fn(builder);
+        Box::new(builder.build())
+    }
+
     pub fn button<InitFn>(
         text: &str,
         on_click: Box<dyn Fn(&mut Scene)>,
@@ -161,6 +447,89 @@ impl UI {
         Box::new(builder.build())
     }
 
+    /// Viewport that scrolls its children vertically once they outgrow it
+    pub fn scroll_container<InitFn>(init_fn: InitFn) -> Box<ScrollContainer>
+    where
+        InitFn: FnOnce(ScrollContainerBuilder) -> ScrollContainerBuilder + 'static,
+    {
+        let mut builder = ScrollContainerBuilder::new();
+        builder = builder.size(200.0, 200.0);
+        builder = init_fn(builder);
+        Box::new(builder.build())
+    }
+
+    /// List that only builds the rows of the `item_count()` items that are scrolled into view
+    pub fn list_view<InitFn>(
+        item_count: Box<dyn Fn() -> usize>,
+        build_row: RowBuilder,
+        init_fn: InitFn,
+    ) -> Box<ListView>
+    where
+        InitFn: FnOnce(ListViewBuilder) -> ListViewBuilder + 'static,
+    {
+        let mut builder = ListViewBuilder::new(item_count, build_row);
+        builder = builder.size(200.0, 200.0);
+        builder = init_fn(builder);
+        Box::new(builder.build())
+    }
+
+    /// Files below the directory `root` with previews, see `AssetBrowser`
+    pub fn asset_browser<InitFn>(root: &str, init_fn: InitFn) -> Box<AssetBrowser>
+    where
+        InitFn: FnOnce(AssetBrowserBuilder) -> AssetBrowserBuilder + 'static,
+    {
+        let mut builder = AssetBrowserBuilder::new(root);
+        builder = builder.size(250.0, 300.0);
+        builder = init_fn(builder);
+        Box::new(builder.build())
+    }
+
+    /// Frame time graph and timings of the `Profiler`
+    pub fn profiler_graph<InitFn>(init_fn: InitFn) -> Box<ProfilerGraph>
+    where
+        InitFn: FnOnce(ProfilerGraphBuilder) -> ProfilerGraphBuilder + 'static,
+    {
+        let mut builder = ProfilerGraphBuilder::new();
+        builder = init_fn(builder);
+        Box::new(builder.build())
+    }
+
+    /// Actions of the `ActionMap` in use with their bindings, click a row to rebind it
+    pub fn action_map_editor<InitFn>(init_fn: InitFn) -> Box<ActionMapEditor>
+    where
+        InitFn: FnOnce(ActionMapEditorBuilder) -> ActionMapEditorBuilder + 'static,
+    {
+        let mut builder = ActionMapEditorBuilder::new();
+        builder = init_fn(builder);
+        Box::new(builder.build())
+    }
+
+    /// Entity list of the scene with the editable fields of the selected entity's components
+    pub fn inspector<InitFn>(init_fn: InitFn) -> Box<Inspector>
+    where
+        InitFn: FnOnce(InspectorBuilder) -> InspectorBuilder + 'static,
+    {
+        let mut builder = InspectorBuilder::new();
+        builder = init_fn(builder);
+        Box::new(builder.build())
+    }
+
+    /// Tab bar that switches between the contents of its tabs
+    pub fn tabs<InitFn>(init_fn: InitFn) -> Box<Tabs>
+    where
+        InitFn: FnOnce(TabsBuilder) -> TabsBuilder + 'static,
+    {
+        let mut builder = TabsBuilder::new();
+        builder = builder.size(300.0, 200.0);
+        builder = init_fn(builder);
+        Box::new(builder.build())
+    }
+
+    /// Makes `child` draggable into containers that accept `payload`
+    pub fn draggable(payload: DragPayload, child: Box<dyn UIElement>) -> Box<Draggable> {
+        Box::new(Draggable::new(payload, child))
+    }
+
     pub fn popup(
         title: &str,
         close_ref: DataSource<bool>,
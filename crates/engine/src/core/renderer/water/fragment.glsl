@@ -0,0 +1,55 @@
+#version 460 core
+
+in vec3 WorldPosition;
+
+layout (location = 0) out vec4 FragColor;
+
+uniform sampler2D waveMap;
+uniform sampler2D reflectionTexture;
+// 1 while drawing the ocean with a planar reflection rendered for the sea level
+uniform int planarReflection;
+uniform vec2 screenSize;
+uniform vec3 cameraPosition;
+uniform vec3 sunDirection;
+uniform float time;
+uniform vec3 waterColor;
+uniform float opacity;
+uniform float waveScale;
+uniform float waveSpeed;
+uniform float waveStrength;
+
+#include "lights.glsl"
+#include "environment.glsl"
+
+const float REFLECTION_DISTORTION = 0.02;
+
+// two layers of the wave map scrolling in different directions, the map stores the normal with y up
+vec3 waveNormal() {
+    vec2 coords = WorldPosition.xz / waveScale;
+    float offset = time * waveSpeed;
+    vec3 first = texture(waveMap, coords + vec2(offset, offset * 0.6)).rgb * 2.0 - 1.0;
+    vec3 second = texture(waveMap, coords * 0.7 + vec2(-offset * 0.8, offset * 0.3)).rgb * 2.0 - 1.0;
+    vec3 normal = first + second;
+    return normalize(vec3(normal.x * waveStrength, normal.y, normal.z * waveStrength));
+}
+
+void main() {
+    vec3 normal = waveNormal();
+    vec3 view = normalize(WorldPosition - cameraPosition);
+    vec3 reflected = reflect(view, normal);
+    float fresnel = 0.02 + 0.98 * pow(1.0 - max(dot(-view, normal), 0.0), 5.0);
+
+    vec3 reflection = skyReflection(reflected, 0.05);
+    if (planarReflection == 1) {
+        vec2 coords = gl_FragCoord.xy / screenSize + normal.xz * REFLECTION_DISTORTION;
+        vec4 scene = texture(reflectionTexture, clamp(coords, 0.001, 0.999));
+        // the reflection is cleared transparent, where nothing was drawn the sky shows
+        reflection = mix(reflection, scene.rgb, scene.a);
+    }
+
+    float diffuse = max(dot(normal, sunDirection), 0.0);
+    vec3 body = waterColor * (skyAmbient(normal) + diffuse) + waterColor * pointLighting(WorldPosition, normal);
+    vec3 specular = vec3(pow(max(dot(reflected, sunDirection), 0.0), 256.0)) * 2.0;
+    vec3 color = mix(body, reflection, fresnel) + specular;
+    FragColor = vec4(applyFog(color, WorldPosition), mix(opacity, 1.0, fresnel));
+}
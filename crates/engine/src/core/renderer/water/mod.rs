@@ -0,0 +1,52 @@
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+use super::{
+    framebuffer::ColorFrameBuffer, shader::DynamicVertexArray, shader::Shader, texture::Texture,
+};
+
+mod water;
+
+/// Water rendering settings, stored in the `EngineConfig`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WaterSettings {
+    pub enabled: bool,
+    /// Draws an ocean plane at `SEA_LEVEL` around the camera, rivers and lakes are drawn either way
+    pub ocean: bool,
+    /// Renders the scene mirrored at the sea level for the ocean to reflect, otherwise it only
+    /// reflects the sky probe like rivers and lakes do
+    pub planar_reflections: bool,
+    /// Color of the water seen from straight above
+    pub color: [f32; 3],
+    /// Alpha of the water seen from straight above, it turns opaque towards grazing angles
+    pub opacity: f32,
+    /// World size the wave normal map repeats over
+    pub wave_scale: f32,
+    /// Speed the waves scroll with, in wave map repeats per second
+    pub wave_speed: f32,
+    /// How far the waves tilt the surface normal
+    pub wave_strength: f32,
+}
+
+/// Queues the ocean and the rivers and lakes of the terrain chunks as transparent draws, blended
+/// over the opaque geometry with animated normal map waves and a Fresnel weighted reflection.
+/// The pass itself only renders the planar reflection
+pub struct WaterPass {
+    shader: Shader,
+    ocean: DynamicVertexArray<WaterVertex>,
+    wave_map: Texture,
+    reflection: Option<ColorFrameBuffer>,
+    /// Whether the ocean reflects the planar reflection rendered this frame
+    planar_reflection: bool,
+    /// Size of the frame the water is drawn into
+    screen_size: (u32, u32),
+    start: Instant,
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+struct WaterVertex {
+    position: [f32; 3],
+}
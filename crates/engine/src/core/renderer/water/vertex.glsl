@@ -0,0 +1,15 @@
+#version 460 core
+
+layout (location = 0) in vec3 position;
+
+out vec3 WorldPosition;
+
+uniform mat4 model;
+uniform mat4 viewProjection;
+
+void main()
+{
+    vec4 worldPosition = model * vec4(position, 1.0);
+    gl_Position = viewProjection * worldPosition;
+    WorldPosition = worldPosition.xyz;
+}
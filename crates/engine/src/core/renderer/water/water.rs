@@ -0,0 +1,365 @@
+use std::{f32::consts::TAU, time::Instant};
+
+use cgmath::{EuclideanSpace, InnerSpace, Matrix, Matrix4, Point3, SquareMatrix, Vector3, Vector4};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    core::{
+        entity::component::camera_component::CameraComponent,
+        profiler::Profiler,
+        renderer::{
+            environment::include_environment,
+            framebuffer::ColorFrameBuffer,
+            gl_debug,
+            light::{light_pass::include_lights, skylight::SkyLight},
+            render_graph::{RenderPass, RenderResources, SHADOW_MAP, WATER_PASS_ORDER},
+            shader::{DynamicVertexArray, Shader, VertexAttributes},
+            shader_manager::ShaderSource,
+            texture::Texture,
+            transparency::TransparencyQueue,
+        },
+        scene::Scene,
+        view_frustum::ViewFrustum,
+        window::Window,
+    },
+    terrain::{
+        dual_contouring::DualContouringChunk, hydrology::SEA_LEVEL, Chunk, CHUNK_RADIUS, CHUNK_SIZE,
+    },
+};
+
+use super::{WaterPass, WaterSettings, WaterVertex};
+
+const WAVE_MAP_SIZE: u32 = 128;
+const WAVE_COUNT: usize = 24;
+/// Highest frequency of the waves in the wave map, in repeats over the map
+const WAVE_MAX_FREQUENCY: i32 = 6;
+/// Scales the summed wave slopes into the normals of the wave map
+const WAVE_MAP_SLOPE: f32 = 0.05;
+
+impl Default for WaterSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            ocean: true,
+            planar_reflections: false,
+            color: [0.05, 0.2, 0.3],
+            opacity: 0.6,
+            wave_scale: 16.0,
+            wave_speed: 0.03,
+            wave_strength: 0.5,
+        }
+    }
+}
+
+impl WaterPass {
+    pub fn new() -> Self {
+        let mut ocean = DynamicVertexArray::new();
+        ocean.buffer_data(
+            &vec![
+                WaterVertex {
+                    position: [-1.0, 0.0, -1.0],
+                },
+                WaterVertex {
+                    position: [1.0, 0.0, -1.0],
+                },
+                WaterVertex {
+                    position: [1.0, 0.0, 1.0],
+                },
+                WaterVertex {
+                    position: [-1.0, 0.0, 1.0],
+                },
+            ],
+            &Some(vec![0, 1, 2, 2, 3, 0]),
+        );
+        Self {
+            shader: Shader::load(
+                ShaderSource::new("renderer/water/vertex.glsl", include_str!("vertex.glsl")),
+                ShaderSource::new(
+                    "renderer/water/fragment.glsl",
+                    include_str!("fragment.glsl"),
+                )
+                .preprocess(|source| include_lights(&include_environment(source))),
+            ),
+            ocean,
+            wave_map: WaterPass::generate_wave_map(),
+            reflection: None,
+            planar_reflection: false,
+            screen_size: (0, 0),
+            start: Instant::now(),
+        }
+    }
+
+    /// Normal map of a sum of waves with whole frequencies, so it tiles seamlessly
+    fn generate_wave_map() -> Texture {
+        let mut rng = StdRng::seed_from_u64(0);
+        let waves: Vec<(f32, f32, f32, f32)> = (0..WAVE_COUNT)
+            .map(|_| {
+                let x = rng.gen_range(-WAVE_MAX_FREQUENCY..=WAVE_MAX_FREQUENCY) as f32;
+                let z = rng.gen_range(-WAVE_MAX_FREQUENCY..=WAVE_MAX_FREQUENCY) as f32;
+                let amplitude = 1.0 / (1.0 + (x * x + z * z).sqrt());
+                (x, z, amplitude, rng.gen_range(0.0..TAU))
+            })
+            .collect();
+        let mut data = Vec::with_capacity((WAVE_MAP_SIZE * WAVE_MAP_SIZE * 4) as usize);
+        for v in 0..WAVE_MAP_SIZE {
+            for u in 0..WAVE_MAP_SIZE {
+                let (u, v) = (
+                    u as f32 / WAVE_MAP_SIZE as f32,
+                    v as f32 / WAVE_MAP_SIZE as f32,
+                );
+                let (mut slope_x, mut slope_z) = (0.0, 0.0);
+                for (x, z, amplitude, phase) in &waves {
+                    let slope = amplitude * TAU * (TAU * (x * u + z * v) + phase).cos();
+                    slope_x += slope * x;
+                    slope_z += slope * z;
+                }
+                let normal =
+                    Vector3::new(-slope_x * WAVE_MAP_SLOPE, 1.0, -slope_z * WAVE_MAP_SLOPE)
+                        .normalize();
+                data.extend(
+                    [normal.x, normal.y, normal.z].map(|c| ((c * 0.5 + 0.5) * 255.0) as u8),
+                );
+                data.push(255);
+            }
+        }
+        let texture = Texture::new();
+        texture.load_from_data(WAVE_MAP_SIZE, WAVE_MAP_SIZE, data);
+        texture
+    }
+
+    /// Renders the scene mirrored at the sea level into `reflection`, everything below the sea
+    /// level is clipped by moving the near plane of the projection onto the water
+    fn render_reflection(
+        &mut self,
+        scene: &Scene,
+        window: &Window,
+        resources: &mut RenderResources,
+        camera: &CameraComponent,
+    ) -> Option<()> {
+        let mirror = Matrix4::from_translation(Vector3::new(0.0, 2.0 * SEA_LEVEL, 0.0))
+            * Matrix4::from_nonuniform_scale(1.0, -1.0, 1.0);
+        let view = camera.get_camera().get_matrix() * mirror;
+        let plane = view.invert()?.transpose() * Vector4::new(0.0, 1.0, 0.0, -SEA_LEVEL);
+        let projection =
+            WaterPass::oblique_projection(camera.get_projection().get_matrix(), plane)?;
+
+        let (width, height) = resources.get_size();
+        let target = ColorFrameBuffer::reuse_hdr(
+            self.reflection.take(),
+            (width / 2).max(1),
+            (height / 2).max(1),
+        );
+        target.bind();
+        window.clear(
+            (0.0, 0.0, 0.0, 0.0),
+            gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT,
+        );
+        resources.bind_texture(SHADOW_MAP, 0);
+        // mirroring flips the winding of every triangle
+        unsafe {
+            gl::FrontFace(gl::CW);
+        }
+        for entity in scene.get_entities().iter() {
+            entity.render(scene, &(projection * view), Matrix4::identity());
+        }
+        unsafe {
+            gl::FrontFace(gl::CCW);
+        }
+        self.reflection = Some(target);
+        resources.bind_target();
+        Some(())
+    }
+
+    /// Replaces the near plane of `projection` with the view space `plane`, keeping its positive side
+    fn oblique_projection(projection: Matrix4<f32>, plane: Vector4<f32>) -> Option<Matrix4<f32>> {
+        let corner =
+            projection.invert()? * Vector4::new(plane.x.signum(), plane.y.signum(), 1.0, 1.0);
+        let clip = plane * (2.0 / plane.dot(corner));
+        let mut oblique = projection;
+        oblique.x.z = clip.x - oblique.x.w;
+        oblique.y.z = clip.y - oblique.y.w;
+        oblique.z.z = clip.z - oblique.z.w;
+        oblique.w.z = clip.w - oblique.w.w;
+        Some(oblique)
+    }
+
+    fn set_uniforms(
+        &self,
+        settings: &WaterSettings,
+        camera: &CameraComponent,
+        scene: &Scene,
+        (width, height): (u32, u32),
+    ) {
+        let eye = camera.get_camera().get_eye_position();
+        // without a skylight the sun stands in the zenith
+        let sun_direction = scene
+            .get_component::<SkyLight>()
+            .map_or(Vector3::unit_y(), |skylight| {
+                skylight.get_position().to_vec().normalize()
+            });
+        let [red, green, blue] = settings.color;
+        self.shader.bind();
+        self.shader.set_uniform_1i("waveMap", 0);
+        self.shader.set_uniform_1i("reflectionTexture", 1);
+        self.shader
+            .set_uniform_mat4("viewProjection", &camera.get_view_projection());
+        self.shader
+            .set_uniform_2f("screenSize", width as f32, height as f32);
+        self.shader
+            .set_uniform_3f("cameraPosition", eye.x, eye.y, eye.z);
+        self.shader.set_uniform_3f(
+            "sunDirection",
+            sun_direction.x,
+            sun_direction.y,
+            sun_direction.z,
+        );
+        self.shader
+            .set_uniform_1f("time", self.start.elapsed().as_secs_f32());
+        self.shader.set_uniform_3f("waterColor", red, green, blue);
+        self.shader.set_uniform_1f("opacity", settings.opacity);
+        self.shader
+            .set_uniform_1f("waveScale", settings.wave_scale.max(0.01));
+        self.shader.set_uniform_1f("waveSpeed", settings.wave_speed);
+        self.shader
+            .set_uniform_1f("waveStrength", settings.wave_strength);
+    }
+
+    /// Binds the shader with its uniforms and textures for a transparent draw
+    fn bind(
+        &self,
+        settings: &WaterSettings,
+        camera: &CameraComponent,
+        scene: &Scene,
+        planar_reflection: bool,
+    ) {
+        self.set_uniforms(settings, camera, scene, self.screen_size);
+        self.shader
+            .set_uniform_1i("planarReflection", planar_reflection as i32);
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE1);
+            gl::BindTexture(
+                gl::TEXTURE_2D,
+                self.reflection
+                    .as_ref()
+                    .and_then(|target| target.get_color_texture())
+                    .map_or(0, |texture| texture.id),
+            );
+            gl::ActiveTexture(gl::TEXTURE0);
+            self.wave_map.bind();
+            gl::Enable(gl::DEPTH_TEST);
+            gl::Disable(gl::CULL_FACE);
+        }
+    }
+
+    fn unbind() {
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE1);
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+            gl::ActiveTexture(gl::TEXTURE0);
+        }
+    }
+}
+
+impl Default for WaterPass {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RenderPass for WaterPass {
+    fn get_name(&self) -> &str {
+        "Water"
+    }
+
+    fn get_order(&self) -> i32 {
+        WATER_PASS_ORDER
+    }
+
+    fn render(&mut self, scene: &Scene, window: &Window, resources: &mut RenderResources) {
+        let settings = &scene.config.water;
+        self.planar_reflection = false;
+        self.screen_size = resources.get_size();
+        if !settings.enabled {
+            return;
+        }
+        let Some(camera) = scene.get_component::<CameraComponent>() else {
+            return;
+        };
+        let eye = camera.get_camera().get_eye_position();
+        self.planar_reflection = settings.ocean
+            && settings.planar_reflections
+            && eye.y > SEA_LEVEL
+            && self
+                .render_reflection(scene, window, resources, camera)
+                .is_some();
+    }
+
+    fn queue_transparent<'a>(&'a self, scene: &'a Scene, queue: &mut TransparencyQueue<'a>) {
+        let settings = &scene.config.water;
+        if !settings.enabled {
+            return;
+        }
+        let Some(camera) = scene.get_component::<CameraComponent>() else {
+            return;
+        };
+
+        if settings.ocean {
+            let eye = camera.get_camera().get_eye_position();
+            // the ocean ends where the terrain stops being loaded
+            let radius =
+                ((CHUNK_RADIUS * CHUNK_SIZE) as f32).min(camera.get_projection().get_zfar());
+            let model = Matrix4::from_translation(Vector3::new(eye.x, SEA_LEVEL, eye.z))
+                * Matrix4::from_nonuniform_scale(radius, 1.0, radius);
+            // the ocean stretches to the horizon, so it is drawn before everything else
+            queue.push_at_depth(f32::INFINITY, move || {
+                self.bind(settings, camera, scene, self.planar_reflection);
+                self.shader.set_uniform_mat4("model", &model);
+                self.ocean.bind();
+                unsafe {
+                    gl::DrawElements(
+                        gl::TRIANGLES,
+                        self.ocean.get_element_count() as i32,
+                        gl::UNSIGNED_INT,
+                        std::ptr::null(),
+                    );
+                }
+                Profiler::count_draw_call();
+                gl_debug::check_errors("WaterPass::queue_transparent");
+                DynamicVertexArray::<WaterVertex>::unbind();
+                WaterPass::unbind();
+            });
+        }
+
+        for entity in scene.get_entities_with_component::<DualContouringChunk>() {
+            let Some(chunk) = entity.get_component::<DualContouringChunk>() else {
+                continue;
+            };
+            let bounds = chunk.get_bounds();
+            let center = Point3::new(
+                (bounds.min.0 + bounds.max.0) as f32 / 2.0,
+                (bounds.min.1 + bounds.max.1) as f32 / 2.0,
+                (bounds.min.2 + bounds.max.2) as f32 / 2.0,
+            );
+            if !chunk.has_water()
+                || !ViewFrustum::is_bounds_in_frustum(
+                    camera.get_projection(),
+                    camera.get_camera(),
+                    bounds,
+                )
+            {
+                continue;
+            }
+            queue.push(center, move || {
+                self.bind(settings, camera, scene, false);
+                chunk.render_water(&self.shader, &Matrix4::identity());
+                WaterPass::unbind();
+            });
+        }
+    }
+}
+
+impl VertexAttributes for WaterVertex {
+    fn get_vertex_attributes() -> Vec<(usize, gl::types::GLuint)> {
+        vec![(3, gl::FLOAT)]
+    }
+}
@@ -0,0 +1,45 @@
+use std::{
+    path::PathBuf,
+    sync::{mpsc, Mutex},
+    time::Duration,
+};
+
+use lazy_static::lazy_static;
+
+mod save_worker;
+
+/// How long the worker waits for more writes after the first one before writing the batch
+const BATCH_DELAY: Duration = Duration::from_millis(200);
+/// Files written since the last sync are synced to the disk at least this often
+const SYNC_INTERVAL: Duration = Duration::from_secs(5);
+
+lazy_static! {
+    static ref WORKER: Mutex<SaveWorker> = Mutex::new(SaveWorker::spawn());
+}
+
+/// Writes save files on a background thread so saving never blocks a frame. Writes to the same
+/// file are coalesced, only the latest contents queued for a path are written. Files are
+/// replaced atomically and synced to the disk periodically and on `SaveWorker::flush`, which
+/// `Application` calls after detaching its layers
+pub struct SaveWorker {
+    sender: mpsc::Sender<SaveRequest>,
+    subscribers: Vec<mpsc::Sender<SaveEvent>>,
+    /// Results the worker reported that are not yet passed on to the subscribers
+    results: mpsc::Receiver<SaveEvent>,
+}
+
+enum SaveRequest {
+    Write {
+        path: PathBuf,
+        contents: Vec<u8>,
+    },
+    /// Writes and syncs everything queued before, then answers on the sender
+    Flush(mpsc::Sender<()>),
+}
+
+/// Outcome of a write, sent to the receivers returned by `SaveWorker::subscribe`
+#[derive(Clone, Debug)]
+pub enum SaveEvent {
+    Saved { path: PathBuf },
+    Failed { path: PathBuf, error: String },
+}
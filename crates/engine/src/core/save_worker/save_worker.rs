@@ -0,0 +1,165 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::mpsc,
+    time::Instant,
+};
+
+use super::{SaveEvent, SaveRequest, SaveWorker, BATCH_DELAY, SYNC_INTERVAL, WORKER};
+
+impl SaveWorker {
+    /// Queues `contents` to replace the file at `path`, replacing contents queued for it before
+    /// that were not written yet
+    pub fn write<P: AsRef<Path>>(path: P, contents: Vec<u8>) {
+        let path = path.as_ref().to_path_buf();
+        let _ = WORKER
+            .lock()
+            .unwrap()
+            .sender
+            .send(SaveRequest::Write { path, contents });
+    }
+
+    /// Blocks until everything queued so far is written and synced to the disk
+    pub fn flush() {
+        let (sender, receiver) = mpsc::channel();
+        if WORKER
+            .lock()
+            .unwrap()
+            .sender
+            .send(SaveRequest::Flush(sender))
+            .is_ok()
+        {
+            let _ = receiver.recv();
+        }
+        SaveWorker::dispatch();
+    }
+
+    /// Receives the outcome of every write from now on, see `SaveWorker::dispatch`
+    pub fn subscribe() -> mpsc::Receiver<SaveEvent> {
+        let (sender, receiver) = mpsc::channel();
+        WORKER.lock().unwrap().subscribers.push(sender);
+        receiver
+    }
+
+    /// Passes the outcomes of the finished writes on to the subscribers, called every frame by
+    /// `Application`
+    pub fn dispatch() {
+        let mut worker = WORKER.lock().unwrap();
+        let events: Vec<SaveEvent> = worker.results.try_iter().collect();
+        for event in events {
+            worker
+                .subscribers
+                .retain(|subscriber| subscriber.send(event.clone()).is_ok());
+        }
+    }
+
+    pub(super) fn spawn() -> SaveWorker {
+        let (sender, requests) = mpsc::channel();
+        let (results_sender, results) = mpsc::channel();
+        let _ = std::thread::spawn(move || SaveWorker::run(requests, results_sender));
+        SaveWorker {
+            sender,
+            subscribers: Vec::new(),
+            results,
+        }
+    }
+
+    fn run(requests: mpsc::Receiver<SaveRequest>, results: mpsc::Sender<SaveEvent>) {
+        let mut pending = BTreeMap::<PathBuf, Vec<u8>>::new();
+        let mut unsynced = BTreeSet::<PathBuf>::new();
+        let mut last_sync = Instant::now();
+        // the batch is written once this passes, writes arriving until then join it
+        let mut deadline: Option<Instant> = None;
+        loop {
+            let request = match deadline {
+                Some(deadline) => {
+                    requests.recv_timeout(deadline.saturating_duration_since(Instant::now()))
+                }
+                None if unsynced.is_empty() => requests
+                    .recv()
+                    .map_err(|_| mpsc::RecvTimeoutError::Disconnected),
+                None => requests.recv_timeout(SYNC_INTERVAL.saturating_sub(last_sync.elapsed())),
+            };
+            match request {
+                Ok(SaveRequest::Write { path, contents }) => {
+                    pending.insert(path, contents);
+                    deadline.get_or_insert_with(|| Instant::now() + BATCH_DELAY);
+                }
+                Ok(SaveRequest::Flush(done)) => {
+                    SaveWorker::write_pending(&mut pending, &mut unsynced, &results);
+                    SaveWorker::sync(&mut unsynced);
+                    last_sync = Instant::now();
+                    deadline = None;
+                    let _ = done.send(());
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => {}
+                Err(mpsc::RecvTimeoutError::Disconnected) => {
+                    SaveWorker::write_pending(&mut pending, &mut unsynced, &results);
+                    SaveWorker::sync(&mut unsynced);
+                    return;
+                }
+            }
+            if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+                SaveWorker::write_pending(&mut pending, &mut unsynced, &results);
+                deadline = None;
+            }
+            if !unsynced.is_empty() && last_sync.elapsed() >= SYNC_INTERVAL {
+                SaveWorker::sync(&mut unsynced);
+                last_sync = Instant::now();
+            }
+        }
+    }
+
+    fn write_pending(
+        pending: &mut BTreeMap<PathBuf, Vec<u8>>,
+        unsynced: &mut BTreeSet<PathBuf>,
+        results: &mpsc::Sender<SaveEvent>,
+    ) {
+        for (path, contents) in std::mem::take(pending) {
+            let event = match SaveWorker::replace(&path, &contents) {
+                Ok(()) => {
+                    unsynced.insert(path.clone());
+                    SaveEvent::Saved { path }
+                }
+                Err(err) => {
+                    log::error!("Failed to save {}: {}", path.display(), err);
+                    SaveEvent::Failed {
+                        path,
+                        error: err.to_string(),
+                    }
+                }
+            };
+            let _ = results.send(event);
+        }
+    }
+
+    /// Writes `contents` next to `path` and moves it over the file, so a crash while writing
+    /// keeps the previous save intact
+    fn replace(path: &Path, contents: &[u8]) -> Result<(), Box<dyn std::error::Error>> {
+        let mut temporary = path.as_os_str().to_owned();
+        temporary.push(".tmp");
+        let mut file = File::create(&temporary)?;
+        file.write_all(contents)?;
+        std::fs::rename(&temporary, path)?;
+        Ok(())
+    }
+
+    fn sync(unsynced: &mut BTreeSet<PathBuf>) {
+        for path in std::mem::take(unsynced) {
+            if let Err(err) = File::open(&path).and_then(|file| file.sync_all()) {
+                log::warn!("Failed to sync {}: {}", path.display(), err);
+            }
+        }
+    }
+}
+
+impl SaveEvent {
+    /// File the write was for
+    pub fn get_path(&self) -> &Path {
+        match self {
+            SaveEvent::Saved { path } | SaveEvent::Failed { path, .. } => path,
+        }
+    }
+}
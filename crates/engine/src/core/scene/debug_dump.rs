@@ -0,0 +1,181 @@
+use std::{error::Error, fmt::Write, fs, path::Path};
+
+use cgmath::{Deg, Matrix4, SquareMatrix};
+
+use crate::{
+    core::{
+        build_info::BuildInfo,
+        entity::{component::camera_component::CameraComponent, Entity},
+        logger::Logger,
+        profiler::Profiler,
+    },
+    terrain::{dual_contouring::DualContouringChunk, Terrain},
+};
+
+use super::Scene;
+
+/// Latest log records included in a dump
+const DUMP_LOG_LINES: usize = 20;
+/// Cpu scopes of the latest frame included in a dump, the slowest first
+const DUMP_SCOPES: usize = 10;
+
+impl Scene {
+    /// Human readable summary of the build and gpu, the entities with their components, the
+    /// loaded chunks, the renderer statistics and the latest log records, meant to be pasted into
+    /// bug reports
+    pub fn debug_dump(&self) -> String {
+        let mut dump = String::new();
+        Scene::dump_build(&mut dump);
+        self.dump_entities(&mut dump);
+        self.dump_terrain(&mut dump);
+        self.dump_renderer(&mut dump);
+        Scene::dump_log(&mut dump);
+        dump
+    }
+
+    /// Writes `debug_dump` to the file at `path`
+    pub fn write_debug_dump<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        fs::write(path, self.debug_dump())?;
+        Ok(())
+    }
+
+    fn dump_build(dump: &mut String) {
+        let _ = writeln!(dump, "== Build ==");
+        for line in BuildInfo::get_lines() {
+            let _ = writeln!(dump, "{}", line);
+        }
+    }
+
+    fn dump_entities(&self, dump: &mut String) {
+        let _ = writeln!(dump, "\n== Scene ==");
+        if let Some(camera_component) = self.get_component::<CameraComponent>() {
+            let camera = camera_component.get_camera();
+            let position = camera.get_position();
+            let _ = writeln!(
+                dump,
+                "camera: ({:.2}, {:.2}, {:.2}) yaw {:.1} pitch {:.1}",
+                position.x,
+                position.y,
+                position.z,
+                Deg::from(camera.get_yaw()).0,
+                Deg::from(camera.get_pitch()).0
+            );
+        }
+        let _ = writeln!(
+            dump,
+            "time: {}scale {:.2}",
+            if self.time_control.is_paused() {
+                "paused, "
+            } else {
+                ""
+            },
+            self.time_control.get_time_scale()
+        );
+        let _ = writeln!(dump, "entities: {}", self.entities.len());
+        for entity in &self.entities {
+            Scene::dump_entity(dump, entity, Matrix4::identity(), 1);
+        }
+    }
+
+    /// One line for `entity` and its components, followed by its children indented one level
+    /// deeper
+    fn dump_entity(dump: &mut String, entity: &Entity, parent: Matrix4<f32>, depth: usize) {
+        let transform = entity.find_world_transform(&entity.id, parent).unwrap();
+        let indent = "  ".repeat(depth);
+        let _ = write!(
+            dump,
+            "{}- {:?} \"{}\" at ({:.2}, {:.2}, {:.2})",
+            indent,
+            entity.id,
+            entity.get_name(),
+            transform.w.x,
+            transform.w.y,
+            transform.w.z
+        );
+        if !entity.get_tags().is_empty() {
+            let _ = write!(dump, " [{}]", entity.get_tags().join(", "));
+        }
+        let _ = writeln!(dump);
+        let inspectable: Vec<&str> = entity
+            .get_inspectable_components()
+            .iter()
+            .map(|component| component.get_inspector_name())
+            .collect();
+        let others = entity.component_count() - inspectable.len();
+        if !inspectable.is_empty() || others > 0 {
+            let _ = write!(dump, "{}  components: {}", indent, inspectable.join(", "));
+            if others > 0 {
+                let separator = if inspectable.is_empty() { "" } else { ", " };
+                let _ = write!(dump, "{}{} other", separator, others);
+            }
+            let _ = writeln!(dump);
+        }
+        for child in entity.get_children() {
+            Scene::dump_entity(dump, child, transform, depth + 1);
+        }
+    }
+
+    fn dump_terrain(&self, dump: &mut String) {
+        for terrain_entity in self.get_entities_with_component::<Terrain<DualContouringChunk>>() {
+            let terrain = terrain_entity
+                .get_own_component::<Terrain<DualContouringChunk>>()
+                .unwrap();
+            let chunks = terrain.get_chunks();
+            let _ = writeln!(dump, "\n== Terrain {:?} ==", terrain_entity.id);
+            let _ = writeln!(
+                dump,
+                "loaded chunks: {}, waiting for upload: {}, triangles: {}, version: {}",
+                chunks.len(),
+                terrain.get_pending_upload_count(),
+                terrain.get_triangle_count(terrain_entity),
+                terrain.get_version()
+            );
+            if let (Some(min), Some(max)) = (
+                chunks.iter().map(|chunk| chunk.coord).min(),
+                chunks.iter().map(|chunk| chunk.coord).max(),
+            ) {
+                let _ = writeln!(dump, "chunk coordinates: {:?} to {:?}", min, max);
+            }
+        }
+    }
+
+    fn dump_renderer(&self, dump: &mut String) {
+        let _ = writeln!(dump, "\n== Renderer ==");
+        let _ = writeln!(
+            dump,
+            "passes: {}",
+            self.render_graph.get_pass_names().join(", ")
+        );
+        let Some(frame) = Profiler::get_latest() else {
+            let _ = writeln!(dump, "no frame statistics, the profiler is disabled");
+            return;
+        };
+        let _ = writeln!(
+            dump,
+            "frame: {:.2}ms, draw calls: {}, chunks generated: {}",
+            frame.frame_time * 1000.0,
+            frame.draw_calls,
+            frame.chunks_generated
+        );
+        for (pass, milliseconds) in &frame.gpu_passes {
+            let _ = writeln!(dump, "  gpu {}: {:.3}ms", pass, milliseconds);
+        }
+        let mut scopes = frame.scopes.clone();
+        scopes.sort_by_key(|(_, duration)| std::cmp::Reverse(*duration));
+        for (scope, duration) in scopes.iter().take(DUMP_SCOPES) {
+            let _ = writeln!(
+                dump,
+                "  cpu {}: {:.3}ms",
+                scope,
+                duration.as_secs_f64() * 1000.0
+            );
+        }
+    }
+
+    fn dump_log(dump: &mut String) {
+        let _ = writeln!(dump, "\n== Log ==");
+        for record in Logger::get_records(DUMP_LOG_LINES) {
+            let _ = writeln!(dump, "{}", record);
+        }
+    }
+}
@@ -1,14 +1,35 @@
 use super::{
+    config::EngineConfig,
     entity::Entity,
+    event::EventBus,
     physics::physics_engine::PhysicsEngine,
-    renderer::{framebuffer::ShadowFrameBuffer, texture::TextureRenderer},
+    quality::AutoQuality,
+    renderer::{
+        debug_draw::DebugDraw,
+        environment::{Environment, EnvironmentBuffer},
+        render_graph::RenderGraph,
+    },
+    selection::Selection,
+    time_control::TimeControl,
 };
 
+mod debug_dump;
 mod scene;
 
 pub struct Scene {
     entities: Vec<Entity>,
     pub physics_engine: PhysicsEngine,
-    shadow_fbo: Option<ShadowFrameBuffer>,
-    texture_renderer: TextureRenderer,
+    pub config: EngineConfig,
+    /// Fog the shaders read from the `Environment` uniform block
+    pub environment: Environment,
+    /// Lines, boxes and spheres to draw on top of the next frame
+    pub debug_draw: DebugDraw,
+    /// Entity selected in the scene or the inspector
+    pub selection: Selection,
+    /// Pauses, steps and scales the time this scene updates with
+    pub time_control: TimeControl,
+    environment_buffer: EnvironmentBuffer,
+    render_graph: RenderGraph,
+    auto_quality: AutoQuality,
+    events: EventBus,
 }
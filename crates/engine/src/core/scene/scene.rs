@@ -1,88 +1,175 @@
-use cgmath::{Matrix4, SquareMatrix};
+use cgmath::{EuclideanSpace, Matrix4, Point3, Quaternion, SquareMatrix};
 use glfw::{Glfw, WindowEvent};
 
-use crate::core::{
-    entity::{
-        component::{camera_component::CameraComponent, Component},
-        Entity, EntityHandle,
+use crate::{
+    core::{
+        camera::controller::CameraControllerType,
+        config::EngineConfig,
+        entity::{
+            component::{
+                camera_component::CameraComponent, model_component::ModelComponent, Component,
+            },
+            Entity, EntityFilter, EntityHandle,
+        },
+        event::{EventBus, EventCategory},
+        physics::physics_engine::PhysicsEngine,
+        quality::AutoQuality,
+        renderer::{
+            debug_draw::{DebugDraw, DebugDrawPass},
+            environment::{Environment, EnvironmentBuffer},
+            hdr::{BloomPass, ToneMappingPass},
+            light::{light_pass::LightPass, sky_probe::SkyProbePass},
+            render_graph::{
+                GBufferPass, MainPass, RenderGraph, RenderPass, ShadowMapPreviewPass, ShadowPass,
+            },
+            ssao::SsaoPass,
+            water::WaterPass,
+        },
+        scene_file::SceneFile,
+        selection::Selection,
+        time_control::TimeControl,
+        window::Window,
     },
-    physics::physics_engine::PhysicsEngine,
-    renderer::{
-        framebuffer::{FrameBuffer, ShadowFrameBuffer},
-        light::skylight::SkyLight,
-        texture::TextureRenderer,
-    },
-    window::Window,
+    profile_scope,
+    terrain::voxel::OreXRayPass,
 };
 
 use super::Scene;
 
 impl Scene {
     pub fn new() -> Self {
+        let mut render_graph = RenderGraph::new();
+        render_graph.add_pass(Box::new(LightPass::new()));
+        render_graph.add_pass(Box::new(SkyProbePass::new()));
+        render_graph.add_pass(Box::new(GBufferPass::new()));
+        render_graph.add_pass(Box::new(SsaoPass::new()));
+        render_graph.add_pass(Box::new(MainPass));
+        render_graph.add_pass(Box::new(WaterPass::new()));
+        render_graph.add_pass(Box::new(BloomPass::new()));
+        render_graph.add_pass(Box::new(ToneMappingPass::new()));
+        render_graph.add_pass(Box::new(DebugDrawPass));
+        render_graph.add_pass(Box::new(OreXRayPass));
         Scene {
             entities: Vec::new(),
             physics_engine: PhysicsEngine::new(),
-            shadow_fbo: None,
-            texture_renderer: TextureRenderer::new(),
+            config: EngineConfig::default(),
+            environment: Environment::default(),
+            debug_draw: DebugDraw::new(),
+            selection: Selection::new(),
+            time_control: TimeControl::new(),
+            environment_buffer: EnvironmentBuffer::new(),
+            render_graph,
+            auto_quality: AutoQuality::new(),
+            events: EventBus::new(),
         }
     }
 
+    /// New scene with the entities of the scene file at `path`
+    pub fn from_file(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut scene = Scene::new();
+        scene.load_file(path)?;
+        Ok(scene)
+    }
+
     pub fn add_shadow_map(&mut self, width: u32, height: u32) {
-        self.shadow_fbo = Some(ShadowFrameBuffer::new(width, height));
+        self.add_render_pass(Box::new(ShadowPass::new(width, height)));
+        self.add_render_pass(Box::new(ShadowMapPreviewPass::new()));
+    }
+
+    pub fn add_render_pass(&mut self, pass: Box<dyn RenderPass>) {
+        self.render_graph.add_pass(pass);
+    }
+
+    pub fn remove_render_pass(&mut self, name: &str) -> Option<Box<dyn RenderPass>> {
+        self.render_graph.remove_pass(name)
     }
 
+    pub fn has_render_pass(&self, name: &str) -> bool {
+        self.render_graph.has_pass(name)
+    }
+
+    /// Advances the scene by `delta_time` real seconds, scaled and paused by its `TimeControl`
     pub fn update(&mut self, delta_time: f64) {
-        self.physics_engine.update();
+        profile_scope!("update");
+        self.auto_quality.update(
+            &self.config.auto_quality,
+            &mut self.config.quality,
+            delta_time,
+        );
+        let scaled_delta_time = self.time_control.advance(delta_time);
+        if scaled_delta_time.is_some() {
+            self.physics_engine
+                .set_time_scale(self.time_control.get_time_scale());
+            self.physics_engine.update();
+            for event in self.physics_engine.take_collision_events() {
+                self.events.emit(event);
+            }
+        }
         for i in 0..self.entities.len() {
             let mut entity = self.entities.remove(i);
-            entity.update(self, delta_time);
+            entity.update_with_time(self, scaled_delta_time, delta_time);
             self.entities.insert(i, entity);
         }
+        self.events.flush();
     }
 
-    pub fn render(&self, window: &Window) {
-        let parent_transform = Matrix4::identity();
-
-        // Shadow Pass
-        if let Some(shadow_fbo) = &self.shadow_fbo {
-            if let Some(skylight) = self.get_component::<SkyLight>() {
-                let light_projection = skylight.get_projection();
-                shadow_fbo.bind();
-                window.clear_mask(gl::DEPTH_BUFFER_BIT);
-                for entity in self.entities.iter() {
-                    entity.render(self, &light_projection, parent_transform);
-                }
-                FrameBuffer::unbind();
-                window.reset_viewport();
-            }
+    /// Only updates the components of type `T`, the rest of the scene stays paused
+    pub fn update_component<T: Component>(&mut self, delta_time: f64) {
+        for i in 0..self.entities.len() {
+            let mut entity = self.entities.remove(i);
+            entity.update_component::<T>(self, delta_time);
+            self.entities.insert(i, entity);
         }
+        self.events.flush();
+    }
 
-        // Render Pass
-        if let Some(camera) = self.get_component::<CameraComponent>() {
-            let view_projection = camera.get_view_projection();
-            if let Some(shadow_fbo) = &self.shadow_fbo {
-                if let Some(texture) = &shadow_fbo.get_depth_texture() {
-                    unsafe {
-                        gl::ActiveTexture(gl::TEXTURE0);
-                    }
-                    texture.bind();
-                }
-            }
-            for entity in self.entities.iter() {
-                entity.render(self, &view_projection, parent_transform);
-            }
-        }
+    /// Queues `event` for the subscribers of its type, they receive it at the end of the
+    /// current or next `update`
+    pub fn emit<T: Clone + 'static>(&mut self, event: T) {
+        self.events.emit(event);
+    }
 
-        // Render Shadow Map
-        if let Some(shadow_fbo) = &self.shadow_fbo {
-            if let Some(texture) = &shadow_fbo.get_depth_texture() {
-                self.texture_renderer.render(texture);
-            }
+    /// Returns a receiver for the events of type `T` emitted from now on, e.g. `ChunkModified`
+    /// or rapier's `CollisionEvent`. Dropping it unsubscribes it
+    pub fn subscribe<T: Clone + 'static>(&mut self) -> std::sync::mpsc::Receiver<T> {
+        self.events.subscribe()
+    }
+
+    /// Runs the render passes into the framebuffer bound before
+    pub fn render(&mut self, window: &Window) {
+        profile_scope!("render");
+        let camera_position = self
+            .get_component::<CameraComponent>()
+            .map_or(Point3::origin(), |camera| {
+                camera.get_camera().get_eye_position()
+            });
+        // the workers record the terrain's chunk draws while the passes before the ones
+        // submitting them render
+        for entity in self.entities.iter() {
+            entity.record(self);
         }
+        self.environment_buffer
+            .upload(&self.environment, camera_position);
+        // passes get the scene while the graph is borrowed mutably, so it is taken out meanwhile
+        let mut render_graph = std::mem::take(&mut self.render_graph);
+        render_graph.render(self, window);
+        self.render_graph = render_graph;
+        self.debug_draw.clear();
     }
 
-    pub fn add_entity(&mut self, entity: Entity) {
+    /// Adds `entity` to the scene, the returned handle finds it again with `get_entity`
+    pub fn add_entity(&mut self, entity: Entity) -> EntityHandle {
+        let id = entity.id;
         self.entities.push(entity);
+        id
+    }
+
+    /// Adds the entities of the scene file at `path`, returns the handles of the top level ones
+    pub fn load_file(
+        &mut self,
+        path: &str,
+    ) -> Result<Vec<EntityHandle>, Box<dyn std::error::Error>> {
+        SceneFile::load(path)?.spawn(self)
     }
 
     pub fn handle_event(
@@ -90,9 +177,21 @@ impl Scene {
         glfw: &mut Glfw,
         window: &mut glfw::Window,
         event: &WindowEvent,
-    ) {
+    ) -> bool {
+        let category = EventCategory::from(event);
+        let mut handlers = Vec::new();
         for entity in self.entities.iter_mut() {
-            entity.handle_event(glfw, window, event);
+            entity.collect_event_handlers(category, &mut handlers);
+        }
+        Entity::dispatch_event(handlers, glfw, window, event)
+    }
+
+    /// Swaps the controller of the scene's camera, the settings of the previous controller are kept in the config
+    pub fn set_camera_controller(&mut self, controller_type: CameraControllerType) {
+        let mut settings = self.config.camera.clone();
+        if let Some(camera) = self.get_component_mut::<CameraComponent>() {
+            camera.switch_camera_controller(controller_type, &mut settings);
+            self.config.camera = settings;
         }
     }
 
@@ -133,17 +232,87 @@ impl Scene {
     //     components
     // }
 
+    /// Components of type `T` in the scene with the world transforms of their entities
+    pub fn get_components_with_transform<T>(&self) -> Vec<(Matrix4<f32>, &T)>
+    where
+        T: Component,
+    {
+        let mut components = Vec::new();
+        for entity in self.entities.iter() {
+            entity.collect_components(Matrix4::identity(), &mut components);
+        }
+        components
+    }
+
     pub fn get_entities_with_component<T>(&self) -> Vec<&Entity>
     where
         T: Component,
     {
+        self.query_entities(&EntityFilter::has_component::<T>())
+    }
+
+    /// Handles of the entities in the scene, children included, that `predicate` holds for
+    pub fn query<F>(&self, predicate: F) -> Vec<EntityHandle>
+    where
+        F: Fn(&Entity) -> bool,
+    {
+        self.query_filter(&EntityFilter::new(predicate))
+    }
+
+    /// Handles of the entities in the scene, children included, that match `filter`, e.g.
+    /// `EntityFilter::has_component::<ModelComponent>().and(EntityFilter::has_tag("enemy"))`
+    pub fn query_filter(&self, filter: &EntityFilter) -> Vec<EntityHandle> {
+        self.query_entities(filter)
+            .iter()
+            .map(|entity| entity.id)
+            .collect()
+    }
+
+    /// Entities in the scene, children included, that match `filter`
+    pub fn query_entities(&self, filter: &EntityFilter) -> Vec<&Entity> {
         let mut entities = Vec::new();
         for entity in self.entities.iter() {
-            entities.extend(entity.get_with_own_component::<T>());
+            entity.collect_matching(filter, &mut entities);
         }
         entities
     }
 
+    /// Moves the entity `id`, children included, together with its rigid body
+    pub fn set_entity_position(&mut self, id: &EntityHandle, position: Point3<f32>) {
+        let Some(index) = self
+            .entities
+            .iter()
+            .position(|entity| entity.id == *id || entity.get_child(id).is_some())
+        else {
+            return;
+        };
+        let mut entity = self.entities.remove(index);
+        if entity.id == *id {
+            entity.set_position(self, position);
+        } else if let Some(child) = entity.get_child_mut(id) {
+            child.set_position(self, position);
+        }
+        self.entities.insert(index, entity);
+    }
+
+    /// Rotates the entity `id`, children included, together with its rigid body
+    pub fn set_entity_rotation(&mut self, id: &EntityHandle, rotation: Quaternion<f32>) {
+        let Some(index) = self
+            .entities
+            .iter()
+            .position(|entity| entity.id == *id || entity.get_child(id).is_some())
+        else {
+            return;
+        };
+        let mut entity = self.entities.remove(index);
+        if entity.id == *id {
+            entity.set_rotation(self, rotation);
+        } else if let Some(child) = entity.get_child_mut(id) {
+            child.set_rotation(self, rotation);
+        }
+        self.entities.insert(index, entity);
+    }
+
     pub fn get_entities(&self) -> &Vec<Entity> {
         &self.entities
     }
@@ -160,6 +329,24 @@ impl Scene {
         None
     }
 
+    /// World transform of the entity `id`, including the transforms of its parents
+    pub fn get_world_transform(&self, id: &EntityHandle) -> Option<Matrix4<f32>> {
+        self.entities
+            .iter()
+            .find_map(|entity| entity.find_world_transform(id, Matrix4::identity()))
+    }
+
+    /// World transform of the bone `bone` of the model of the entity `id` in its current pose.
+    /// Entities being updated are not part of the scene, so their own components and children
+    /// have to use `Model::get_bone_world_transform`
+    pub fn get_bone_world_transform(&self, id: &EntityHandle, bone: &str) -> Option<Matrix4<f32>> {
+        let model = self
+            .get_entity(id)?
+            .get_own_component::<ModelComponent>()?
+            .get_model()?;
+        model.get_bone_world_transform(bone, &self.get_world_transform(id)?)
+    }
+
     pub fn get_entity_mut(&mut self, id: &EntityHandle) -> Option<&mut Entity> {
         for entity in self.entities.iter_mut() {
             if entity.id == *id {
@@ -0,0 +1,301 @@
+use std::{collections::HashMap, error::Error, path::Path, sync::Arc};
+
+use cgmath::Deg;
+
+use crate::{
+    core::{
+        camera::{Camera, Projection},
+        entity::{
+            component::{
+                animation_component::AnimationComponent,
+                bone_attachment_component::BoneAttachmentComponent,
+                camera_component::CameraComponent,
+                character_controller_component::CharacterControllerComponent,
+                debug_component::DebugController, model_component::ModelComponent, Component,
+            },
+            Entity,
+        },
+        model::{
+            animation_graph::{AnimationGraph, BlendSpace, State},
+            Animation, ModelBuilder, SkinningMethod,
+        },
+        renderer::light::{
+            point_light::PointLightComponent, skylight::SkyLight, spot_light::SpotLightComponent,
+        },
+        scene::Scene,
+        selection::SelectionController,
+    },
+    player::Player,
+    terrain::{
+        deltas::ChunkDeltas, dual_contouring::DualContouringChunk,
+        marching_cubes::MarchingCubesChunk, migration::WorldMigration, voxel::VoxelChunk,
+        world_gen::WorldGenConfig, Chunk, Terrain,
+    },
+};
+
+use super::{
+    AnimationSetParams, BoneAttachmentParams, CameraParams, CharacterControllerParams,
+    ComponentConstructor, ComponentParams, ComponentRegistry, LightParams, ModelParams,
+    PlayerParams, SkyLightParams, TerrainParams,
+};
+
+/// Size the projection of a camera starts with, it follows the framebuffer once it is resized
+const DEFAULT_VIEWPORT: (u32, u32) = (1280, 720);
+
+type Constructor = fn(&mut Scene, &mut Entity, &ComponentParams) -> Result<(), Box<dyn Error>>;
+
+impl ComponentRegistry {
+    /// Constructors of the engine's components
+    pub(super) fn get_builtins() -> HashMap<String, Arc<ComponentConstructor>> {
+        let builtins: [(&str, Constructor); 11] = [
+            ("camera", create_camera),
+            ("terrain", create_terrain),
+            ("model", create_model),
+            ("player", create_player),
+            ("skylight", create_skylight),
+            ("point_light", create_point_light),
+            ("spot_light", create_spot_light),
+            ("bone_attachment", create_bone_attachment),
+            ("debug_controller", |_, entity, _| {
+                entity.add_component(DebugController::new());
+                Ok(())
+            }),
+            ("selection_controller", |_, entity, _| {
+                entity.add_component(SelectionController::new());
+                Ok(())
+            }),
+            ("character_controller", create_character_controller),
+        ];
+        builtins
+            .into_iter()
+            .map(|(name, constructor)| {
+                let constructor: Arc<ComponentConstructor> = Arc::new(constructor);
+                (name.to_string(), constructor)
+            })
+            .collect()
+    }
+}
+
+fn create_camera(
+    scene: &mut Scene,
+    entity: &mut Entity,
+    params: &ComponentParams,
+) -> Result<(), Box<dyn Error>> {
+    let params: CameraParams = params.parse()?;
+    let mut camera = Camera::new(params.position, Deg(params.yaw), Deg(params.pitch));
+    camera.set_relative_position(params.relative_position);
+    let projection = Projection::new(
+        DEFAULT_VIEWPORT.0,
+        DEFAULT_VIEWPORT.1,
+        Deg(params.fovy),
+        params.znear,
+        params.zfar,
+    );
+    let camera_controller = scene.config.camera.create_controller();
+    entity.add_component(CameraComponent::new(camera, projection, camera_controller));
+    Ok(())
+}
+
+fn create_terrain(
+    _: &mut Scene,
+    entity: &mut Entity,
+    params: &ComponentParams,
+) -> Result<(), Box<dyn Error>> {
+    let params: TerrainParams = params.parse()?;
+    match params.chunk.as_str() {
+        "dual_contouring" => add_terrain::<DualContouringChunk>(entity, &params),
+        "marching_cubes" => add_terrain::<MarchingCubesChunk>(entity, &params),
+        "voxel" => add_terrain::<VoxelChunk>(entity, &params),
+        chunk => Err(format!("Unknown chunk type: {}", chunk).into()),
+    }
+}
+
+/// Adds the terrain with the config and saved changes of `params`, saves of an older generator
+/// are migrated and written back
+fn add_terrain<T: Chunk + Component + Send + 'static>(
+    entity: &mut Entity,
+    params: &TerrainParams,
+) -> Result<(), Box<dyn Error>> {
+    let mut config = match (&params.config, params.seed) {
+        (Some(path), _) if Path::new(path).exists() => WorldGenConfig::load(path)?,
+        (_, Some(seed)) => WorldGenConfig::default().with_seed(seed),
+        _ => WorldGenConfig::default(),
+    };
+    let mut deltas = match &params.deltas {
+        Some(path) if Path::new(path).exists() => ChunkDeltas::load(path)?,
+        _ => ChunkDeltas::new(),
+    };
+    let migrated = WorldMigration::new().migrate(&mut config, &mut deltas);
+    let mut terrain = Terrain::<T>::new(config);
+    terrain.set_deltas(deltas);
+    if let Some(path) = &params.deltas {
+        terrain.set_save_path(path);
+        if migrated {
+            terrain.save();
+        }
+    }
+    entity.add_component(terrain);
+    Ok(())
+}
+
+fn create_animation_graph(params: &AnimationSetParams) -> Result<AnimationGraph, Box<dyn Error>> {
+    let mut blend_space = match params.inputs.as_slice() {
+        [input] => BlendSpace::new_1d(input),
+        [x_input, y_input] => BlendSpace::new_2d(x_input, y_input),
+        _ => return Err("An animation set needs one or two inputs".into()),
+    };
+    let mut animation_graph = AnimationGraph::new();
+    for input in &params.inputs {
+        animation_graph.add_input(input, 0.0);
+    }
+    for clip in &params.clips {
+        blend_space.add_clip(Animation::from_file(&clip.name, &clip.file)?, clip.position);
+    }
+    let mut state = State::new("default");
+    state.set_blend_space(blend_space);
+    animation_graph.set_default_state(state);
+    Ok(animation_graph)
+}
+
+fn create_model(
+    _: &mut Scene,
+    entity: &mut Entity,
+    params: &ComponentParams,
+) -> Result<(), Box<dyn Error>> {
+    let params: ModelParams = params.parse()?;
+    if params.path.is_empty() {
+        return Err("The model has no path".into());
+    }
+    if let Some(animations) = &params.animations {
+        entity.add_component(AnimationComponent::new(create_animation_graph(animations)?));
+    }
+    let mut builder = ModelBuilder::new(&params.path);
+    if params.dual_quaternion_skinning {
+        builder = builder.with_skinning(SkinningMethod::DualQuaternion);
+    }
+    entity.add_component(ModelComponent::loading(builder.build_async()));
+    Ok(())
+}
+
+fn create_player(
+    scene: &mut Scene,
+    entity: &mut Entity,
+    params: &ComponentParams,
+) -> Result<(), Box<dyn Error>> {
+    let params: PlayerParams = params.parse()?;
+    Player::attach(scene, entity, create_animation_graph(&params.animations)?);
+    Ok(())
+}
+
+fn create_character_controller(
+    _: &mut Scene,
+    entity: &mut Entity,
+    params: &ComponentParams,
+) -> Result<(), Box<dyn Error>> {
+    let params: CharacterControllerParams = params.parse()?;
+    entity.add_component(CharacterControllerComponent::new(
+        params.radius,
+        params.height,
+    ));
+    Ok(())
+}
+
+fn create_skylight(
+    _: &mut Scene,
+    entity: &mut Entity,
+    params: &ComponentParams,
+) -> Result<(), Box<dyn Error>> {
+    let params: SkyLightParams = params.parse()?;
+    entity.add_component(SkyLight::new(params.position));
+    Ok(())
+}
+
+fn create_point_light(
+    _: &mut Scene,
+    entity: &mut Entity,
+    params: &ComponentParams,
+) -> Result<(), Box<dyn Error>> {
+    let params: LightParams = params.parse()?;
+    entity.add_component(PointLightComponent::new(
+        params.color,
+        params.intensity,
+        params.range,
+    ));
+    Ok(())
+}
+
+fn create_spot_light(
+    _: &mut Scene,
+    entity: &mut Entity,
+    params: &ComponentParams,
+) -> Result<(), Box<dyn Error>> {
+    let params: LightParams = params.parse()?;
+    entity.add_component(SpotLightComponent::new(
+        params.color,
+        params.intensity,
+        params.range,
+    ));
+    Ok(())
+}
+
+fn create_bone_attachment(
+    _: &mut Scene,
+    entity: &mut Entity,
+    params: &ComponentParams,
+) -> Result<(), Box<dyn Error>> {
+    let params: BoneAttachmentParams = params.parse()?;
+    entity.add_component(BoneAttachmentComponent::new(&params.bone));
+    Ok(())
+}
+
+impl Default for CameraParams {
+    fn default() -> Self {
+        Self {
+            position: (0.0, 0.0, 0.0),
+            relative_position: (0.0, 0.0, 0.0),
+            yaw: -90.0,
+            pitch: 0.0,
+            fovy: 45.0,
+            znear: 0.1,
+            zfar: 100.0,
+        }
+    }
+}
+
+impl Default for TerrainParams {
+    fn default() -> Self {
+        Self {
+            chunk: "dual_contouring".to_string(),
+            config: None,
+            seed: None,
+            deltas: None,
+        }
+    }
+}
+
+impl Default for CharacterControllerParams {
+    fn default() -> Self {
+        Self {
+            radius: 0.35,
+            height: 1.8,
+        }
+    }
+}
+
+impl Default for SkyLightParams {
+    fn default() -> Self {
+        Self {
+            position: (10.0, 600.0, 10.0),
+        }
+    }
+}
+
+impl Default for LightParams {
+    fn default() -> Self {
+        Self {
+            color: (1.0, 1.0, 1.0),
+            intensity: 1.0,
+            range: 10.0,
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use std::error::Error;
+
+use serde::{Deserialize, Serialize};
+
+use super::{entity::Entity, scene::Scene};
+
+mod components;
+mod scene_file;
+
+/// Entities of a scene as a RON file, loaded with `Scene::from_file` or `Scene::load_file`.
+///
+/// ```ron
+/// SceneFile(
+///     entities: [
+///         (
+///             name: "terrain",
+///             components: [
+///                 (type: "terrain", params: (chunk: "voxel", seed: Some(7))),
+///             ],
+///             children: [
+///                 (name: "lamp", position: (0.0, 60.0, 0.0), components: [
+///                     (type: "point_light", params: (color: (1.0, 0.8, 0.5), range: 8.0)),
+///                 ]),
+///             ],
+///         ),
+///     ],
+/// )
+/// ```
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct SceneFile {
+    #[serde(default)]
+    pub entities: Vec<EntityDefinition>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct EntityDefinition {
+    pub name: String,
+    #[serde(default)]
+    pub position: (f32, f32, f32),
+    /// Euler angles in degrees
+    #[serde(default)]
+    pub rotation: (f32, f32, f32),
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Created in order after the entity is moved to its position
+    #[serde(default)]
+    pub components: Vec<ComponentDefinition>,
+    #[serde(default)]
+    pub children: Vec<EntityDefinition>,
+}
+
+/// Component created by the constructor registered as `type` in the `ComponentRegistry`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ComponentDefinition {
+    #[serde(rename = "type")]
+    pub kind: String,
+    #[serde(default)]
+    pub params: ComponentParams,
+}
+
+/// Parameters of a `ComponentDefinition`, read by its constructor with `parse`. Left out they
+/// are `()`
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ComponentParams(ron::Value);
+
+/// Adds the components of one type with their parameters to an entity, e.g. several for a
+/// player. The entity is already at its position but not yet in the scene
+pub type ComponentConstructor =
+    dyn Fn(&mut Scene, &mut Entity, &ComponentParams) -> Result<(), Box<dyn Error>> + Send + Sync;
+
+/// Constructors of the component types scene files can use by name. The engine's components are
+/// registered from the start, user layers add their own with `register`
+pub struct ComponentRegistry;
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+struct CameraParams {
+    position: (f32, f32, f32),
+    relative_position: (f32, f32, f32),
+    /// Degrees
+    yaw: f32,
+    pitch: f32,
+    fovy: f32,
+    znear: f32,
+    zfar: f32,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+struct TerrainParams {
+    /// Chunk type, "dual_contouring", "marching_cubes" or "voxel"
+    chunk: String,
+    /// World generation config file, the default config is used if it does not exist
+    config: Option<String>,
+    /// Seed of the default config used if there is no config file
+    seed: Option<u64>,
+    /// File the changes to the world are loaded from and saved to
+    deltas: Option<String>,
+}
+
+/// Clips of a blend space over one or two inputs, the default state of the animation graph
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+struct AnimationSetParams {
+    inputs: Vec<String>,
+    clips: Vec<ClipParams>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+struct ClipParams {
+    name: String,
+    file: String,
+    /// Values of the inputs the clip plays at, the second one is ignored for a single input
+    #[serde(default)]
+    position: (f32, f32),
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+struct ModelParams {
+    path: String,
+    dual_quaternion_skinning: bool,
+    animations: Option<AnimationSetParams>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+struct PlayerParams {
+    animations: AnimationSetParams,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+struct SkyLightParams {
+    position: (f32, f32, f32),
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+struct LightParams {
+    color: (f32, f32, f32),
+    intensity: f32,
+    range: f32,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+#[serde(default)]
+struct CharacterControllerParams {
+    radius: f32,
+    /// Height of the capsule including its caps
+    height: f32,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default)]
+struct BoneAttachmentParams {
+    bone: String,
+}
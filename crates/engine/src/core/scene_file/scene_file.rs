@@ -0,0 +1,145 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    sync::{Arc, RwLock},
+};
+
+use cgmath::{Deg, Euler, Quaternion};
+use lazy_static::lazy_static;
+use serde::de::DeserializeOwned;
+
+use crate::core::{
+    entity::{Entity, EntityHandle},
+    scene::Scene,
+};
+
+use super::{
+    ComponentConstructor, ComponentParams, ComponentRegistry, EntityDefinition, SceneFile,
+};
+
+lazy_static! {
+    static ref COMPONENT_CONSTRUCTORS: RwLock<HashMap<String, Arc<ComponentConstructor>>> =
+        RwLock::new(ComponentRegistry::get_builtins());
+}
+
+impl SceneFile {
+    pub fn load(path: &str) -> Result<SceneFile, Box<dyn Error>> {
+        let source = std::fs::read_to_string(path)?;
+        SceneFile::from_ron(&source)
+    }
+
+    pub fn from_ron(source: &str) -> Result<SceneFile, Box<dyn Error>> {
+        Ok(ron::from_str(source)?)
+    }
+
+    /// Creates the entities and adds them to `scene`, returns the handles of the top level ones.
+    /// The entities are only added once all of them were created
+    pub fn spawn(&self, scene: &mut Scene) -> Result<Vec<EntityHandle>, Box<dyn Error>> {
+        let entities = self
+            .entities
+            .iter()
+            .map(|definition| definition.create(scene))
+            .collect::<Result<Vec<Entity>, Box<dyn Error>>>()?;
+        Ok(entities
+            .into_iter()
+            .map(|entity| scene.add_entity(entity))
+            .collect())
+    }
+}
+
+impl EntityDefinition {
+    /// Creates the entity with its components and children
+    pub fn create(&self, scene: &mut Scene) -> Result<Entity, Box<dyn Error>> {
+        let mut entity = Entity::new(&self.name);
+        entity.set_position(scene, self.position);
+        if self.rotation != (0.0, 0.0, 0.0) {
+            let (x, y, z) = self.rotation;
+            entity.set_rotation(scene, Quaternion::from(Euler::new(Deg(x), Deg(y), Deg(z))));
+        }
+        for tag in &self.tags {
+            entity.add_tag(tag);
+        }
+        for component in &self.components {
+            ComponentRegistry::create(&component.kind, scene, &mut entity, &component.params)
+                .map_err(|err| {
+                    format!(
+                        "Failed to create the {} component of {}: {}",
+                        component.kind, self.name, err
+                    )
+                })?;
+        }
+        for child in &self.children {
+            entity.add_child(child.create(scene)?);
+        }
+        Ok(entity)
+    }
+}
+
+impl ComponentParams {
+    /// The parameters as `T`, all of them default if they were left out
+    pub fn parse<T: DeserializeOwned + Default>(&self) -> Result<T, Box<dyn Error>> {
+        match &self.0 {
+            ron::Value::Unit => Ok(T::default()),
+            value => Ok(value.clone().into_rust()?),
+        }
+    }
+}
+
+impl Default for ComponentParams {
+    fn default() -> Self {
+        Self(ron::Value::Unit)
+    }
+}
+
+impl ComponentRegistry {
+    /// Makes the component type `name` available to scene files, replacing a constructor
+    /// registered with the same name
+    pub fn register<F>(name: &str, constructor: F)
+    where
+        F: Fn(&mut Scene, &mut Entity, &ComponentParams) -> Result<(), Box<dyn Error>>
+            + Send
+            + Sync
+            + 'static,
+    {
+        COMPONENT_CONSTRUCTORS
+            .write()
+            .unwrap()
+            .insert(name.to_string(), Arc::new(constructor));
+    }
+
+    /// Removes the component type `name`, returns false if there is none
+    pub fn unregister(name: &str) -> bool {
+        COMPONENT_CONSTRUCTORS
+            .write()
+            .unwrap()
+            .remove(name)
+            .is_some()
+    }
+
+    /// Names of the registered component types in alphabetical order
+    pub fn get_names() -> Vec<String> {
+        let mut names: Vec<String> = COMPONENT_CONSTRUCTORS
+            .read()
+            .unwrap()
+            .keys()
+            .cloned()
+            .collect();
+        names.sort();
+        names
+    }
+
+    /// Runs the constructor of the component type `name` for `entity`
+    pub fn create(
+        name: &str,
+        scene: &mut Scene,
+        entity: &mut Entity,
+        params: &ComponentParams,
+    ) -> Result<(), Box<dyn Error>> {
+        // the lock is released before the constructor runs, so it may register types itself
+        let constructor = COMPONENT_CONSTRUCTORS.read().unwrap().get(name).cloned();
+        match constructor {
+            Some(constructor) => constructor(scene, entity, params),
+            None => Err(format!("Unknown component type: {}", name).into()),
+        }
+    }
+}
@@ -0,0 +1,354 @@
+use cgmath::{
+    EuclideanSpace, InnerSpace, Matrix4, Point3, Quaternion, Rad, Rotation3, SquareMatrix, Vector3,
+    Vector4,
+};
+use glfw::{Action, CursorMode, Glfw, WindowEvent};
+
+use crate::core::{
+    entity::{
+        component::{
+            bounds_component::BoundsComponent, camera_component::CameraComponent, Component,
+        },
+        Entity,
+    },
+    event::{EventCategory, DEFAULT_EVENT_PRIORITY},
+    input::Input,
+    scene::Scene,
+};
+
+use super::{GizmoDrag, GizmoMode, GizmoView, Selection, SelectionController};
+
+/// Clicks reach the gizmo before the handlers with the default priority, so grabbing a handle
+/// does not also dig or place
+const SELECTION_EVENT_PRIORITY: i32 = 50;
+/// Size of the gizmo per unit of distance to the camera
+const GIZMO_SCALE: f32 = 0.15;
+/// Distance from a handle that still grabs it, relative to the size of the gizmo
+const HANDLE_TOLERANCE: f32 = 0.08;
+/// Length and width of the arrow heads, relative to the size of the gizmo
+const ARROW_HEAD_LENGTH: f32 = 0.15;
+const ARROW_HEAD_WIDTH: f32 = 0.06;
+const SELECTED_COLOR: Vector3<f32> = Vector3::new(1.0, 0.8, 0.1);
+
+impl SelectionController {
+    pub fn new() -> Self {
+        Self {
+            mode: GizmoMode::default(),
+            cursor: None,
+            pending_pick: None,
+            view: None,
+            drag: None,
+        }
+    }
+
+    pub fn get_mode(&self) -> GizmoMode {
+        self.mode
+    }
+
+    pub fn set_mode(&mut self, mode: GizmoMode) {
+        self.mode = mode;
+        self.drag = None;
+    }
+
+    /// Whether a handle of the gizmo is being dragged
+    pub fn is_dragging(&self) -> bool {
+        self.drag.is_some()
+    }
+
+    /// Unit vector of the world axis `axis`
+    fn get_axis(axis: usize) -> Vector3<f32> {
+        match axis {
+            0 => Vector3::unit_x(),
+            1 => Vector3::unit_y(),
+            _ => Vector3::unit_z(),
+        }
+    }
+
+    /// Vectors spanning the plane around `axis`, ordered so that positive angles rotate around
+    /// the axis in the same direction as `Quaternion::from_axis_angle`
+    fn get_plane(axis: usize) -> (Vector3<f32>, Vector3<f32>) {
+        (
+            SelectionController::get_axis((axis + 1) % 3),
+            SelectionController::get_axis((axis + 2) % 3),
+        )
+    }
+
+    /// Ray from the eye through the point `cursor` in normalized device coordinates
+    fn get_ray(
+        eye: Point3<f32>,
+        inverse_view_projection: &Matrix4<f32>,
+        cursor: (f32, f32),
+    ) -> (Point3<f32>, Vector3<f32>) {
+        let far = inverse_view_projection * Vector4::new(cursor.0, cursor.1, 1.0, 1.0);
+        let far = Point3::new(far.x, far.y, far.z) / far.w;
+        (eye, (far - eye).normalize())
+    }
+
+    /// Position along the axis through `origin` closest to the ray and the distance of the ray
+    /// to that point, None if the ray is parallel to the axis
+    fn intersect_axis(
+        origin: Point3<f32>,
+        axis: Vector3<f32>,
+        ray: (Point3<f32>, Vector3<f32>),
+    ) -> Option<(f32, f32)> {
+        let (ray_origin, ray_direction) = ray;
+        let alignment = axis.dot(ray_direction);
+        let denominator = 1.0 - alignment * alignment;
+        if denominator < f32::EPSILON {
+            return None;
+        }
+        let offset = origin - ray_origin;
+        let along_axis = offset.dot(axis);
+        let along_ray = offset.dot(ray_direction);
+        let position = (alignment * along_ray - along_axis) / denominator;
+        let ray_position = (along_ray - alignment * along_axis) / denominator;
+        if ray_position < 0.0 {
+            return None;
+        }
+        let distance =
+            ((origin + axis * position) - (ray_origin + ray_direction * ray_position)).magnitude();
+        Some((position, distance))
+    }
+
+    /// Angle around the axis through `origin` where the ray hits the plane around the axis and
+    /// the distance of that point to `origin`
+    fn intersect_ring(
+        origin: Point3<f32>,
+        axis: usize,
+        ray: (Point3<f32>, Vector3<f32>),
+    ) -> Option<(f32, f32)> {
+        let (ray_origin, ray_direction) = ray;
+        let normal = SelectionController::get_axis(axis);
+        let denominator = ray_direction.dot(normal);
+        if denominator.abs() < f32::EPSILON {
+            return None;
+        }
+        let distance = (origin - ray_origin).dot(normal) / denominator;
+        if distance < 0.0 {
+            return None;
+        }
+        let offset = ray_origin + ray_direction * distance - origin;
+        let (u, v) = SelectionController::get_plane(axis);
+        Some((offset.dot(v).atan2(offset.dot(u)), offset.magnitude()))
+    }
+
+    /// Axis of the handle the ray hits and where it grabs it, the closest one if several are hit
+    fn pick_handle(
+        &self,
+        view: &GizmoView,
+        ray: (Point3<f32>, Vector3<f32>),
+    ) -> Option<(usize, f32)> {
+        let tolerance = view.size * HANDLE_TOLERANCE;
+        let mut closest: Option<(f32, usize, f32)> = None;
+        for axis in 0..3 {
+            let hit = match self.mode {
+                GizmoMode::Translate => SelectionController::intersect_axis(
+                    view.origin,
+                    SelectionController::get_axis(axis),
+                    ray,
+                )
+                .filter(|(position, _)| (0.0..=view.size).contains(position)),
+                GizmoMode::Rotate => SelectionController::intersect_ring(view.origin, axis, ray)
+                    .map(|(angle, radius)| (angle, (radius - view.size).abs())),
+            };
+            if let Some((start, distance)) = hit {
+                if distance < tolerance && closest.map_or(true, |(closest, ..)| distance < closest)
+                {
+                    closest = Some((distance, axis, start));
+                }
+            }
+        }
+        closest.map(|(_, axis, start)| (axis, start))
+    }
+
+    /// Moves or rotates the dragged entity to follow the ray
+    fn apply_drag(&self, scene: &mut Scene, drag: &GizmoDrag, ray: (Point3<f32>, Vector3<f32>)) {
+        let axis = SelectionController::get_axis(drag.axis);
+        match self.mode {
+            GizmoMode::Translate => {
+                if let Some((position, _)) =
+                    SelectionController::intersect_axis(drag.origin, axis, ray)
+                {
+                    let position = drag.position + axis * (position - drag.start);
+                    scene.set_entity_position(&drag.entity, position);
+                }
+            }
+            GizmoMode::Rotate => {
+                if let Some((angle, _)) =
+                    SelectionController::intersect_ring(drag.origin, drag.axis, ray)
+                {
+                    let rotation =
+                        Quaternion::from_axis_angle(axis, Rad(angle - drag.start)) * drag.rotation;
+                    scene.set_entity_rotation(&drag.entity, rotation);
+                }
+            }
+        }
+    }
+
+    fn draw(&self, scene: &mut Scene, view: &GizmoView) {
+        let bounds = scene
+            .get_entity(&view.entity)
+            .and_then(|entity| entity.get_own_component::<BoundsComponent>())
+            .zip(scene.get_world_transform(&view.entity))
+            .map(|(bounds, transform)| bounds.get_world_bounds(&transform));
+        if let Some((min, max)) = bounds {
+            scene.debug_draw.draw_aabb(min, max, SELECTED_COLOR, true);
+        }
+
+        let dragged = self.drag.as_ref().map(|drag| drag.axis);
+        for axis in 0..3 {
+            let direction = SelectionController::get_axis(axis);
+            let color = if dragged == Some(axis) {
+                SELECTED_COLOR
+            } else {
+                direction
+            };
+            let (u, v) = SelectionController::get_plane(axis);
+            match self.mode {
+                GizmoMode::Translate => {
+                    let tip = view.origin + direction * view.size;
+                    let base = tip - direction * view.size * ARROW_HEAD_LENGTH;
+                    let width = u * view.size * ARROW_HEAD_WIDTH;
+                    scene.debug_draw.draw_line(view.origin, tip, color, false);
+                    scene.debug_draw.draw_line(tip, base + width, color, false);
+                    scene.debug_draw.draw_line(tip, base - width, color, false);
+                }
+                GizmoMode::Rotate => {
+                    scene
+                        .debug_draw
+                        .draw_circle(view.origin, u, v, view.size, color, false);
+                }
+            }
+        }
+    }
+}
+
+impl Default for SelectionController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Component for SelectionController {
+    fn update(&mut self, scene: &mut Scene, _: &mut Entity, _: f64) {
+        let Some(camera) = scene.get_component::<CameraComponent>() else {
+            self.view = None;
+            return;
+        };
+        let eye = camera.get_camera().get_eye_position();
+        let inverse_view_projection = camera
+            .get_view_projection()
+            .invert()
+            .unwrap_or(Matrix4::identity());
+
+        if let Some(cursor) = self.pending_pick.take() {
+            let (origin, direction) =
+                SelectionController::get_ray(eye, &inverse_view_projection, cursor);
+            let picked = Selection::pick(scene, origin, direction);
+            scene.selection.set(picked);
+        }
+        // selecting another entity ends the drag
+        if self
+            .drag
+            .as_ref()
+            .is_some_and(|drag| !scene.selection.is_selected(&drag.entity))
+        {
+            self.drag = None;
+        }
+        if let (Some(drag), Some(cursor)) = (&self.drag, self.cursor) {
+            let ray = SelectionController::get_ray(eye, &inverse_view_projection, cursor);
+            self.apply_drag(scene, drag, ray);
+        }
+
+        self.view = scene.selection.get().and_then(|id| {
+            let entity = scene.get_entity(&id)?;
+            let transform = scene.get_world_transform(&id)?;
+            let origin = Point3::from_vec(transform.w.truncate());
+            Some(GizmoView {
+                eye,
+                inverse_view_projection,
+                entity: id,
+                origin,
+                position: entity.get_position(),
+                rotation: entity.get_rotation(),
+                size: (origin - eye).magnitude() * GIZMO_SCALE,
+            })
+        });
+        if self.view.is_none() {
+            self.drag = None;
+        }
+        if let Some(view) = self.view.take() {
+            self.draw(scene, &view);
+            self.view = Some(view);
+        }
+    }
+
+    fn is_realtime(&self) -> bool {
+        true
+    }
+
+    fn get_event_priority(&self, category: EventCategory) -> Option<i32> {
+        match category {
+            EventCategory::Mouse => Some(SELECTION_EVENT_PRIORITY),
+            EventCategory::Keyboard => Some(DEFAULT_EVENT_PRIORITY),
+            EventCategory::Window | EventCategory::Gamepad => None,
+        }
+    }
+
+    fn handle_event(
+        &mut self,
+        _: &mut Glfw,
+        window: &mut glfw::Window,
+        event: &WindowEvent,
+    ) -> bool {
+        if let WindowEvent::CursorPos(x, y) = event {
+            let (width, height) = window.get_size();
+            self.cursor = (window.get_cursor_mode() == CursorMode::Normal
+                && width > 0
+                && height > 0)
+                .then(|| {
+                    (
+                        *x as f32 / width as f32 * 2.0 - 1.0,
+                        1.0 - *y as f32 / height as f32 * 2.0,
+                    )
+                });
+            return false;
+        }
+        if Input::is_action_pressed("toggle_gizmo_mode", event) {
+            self.set_mode(match self.mode {
+                GizmoMode::Translate => GizmoMode::Rotate,
+                GizmoMode::Rotate => GizmoMode::Translate,
+            });
+            return true;
+        }
+        match Input::get_action("select", event) {
+            Some(Action::Press) => {
+                let Some(cursor) = self.cursor else {
+                    return false;
+                };
+                if let Some(view) = &self.view {
+                    let ray = SelectionController::get_ray(
+                        view.eye,
+                        &view.inverse_view_projection,
+                        cursor,
+                    );
+                    if let Some((axis, start)) = self.pick_handle(view, ray) {
+                        self.drag = Some(GizmoDrag {
+                            entity: view.entity,
+                            axis,
+                            origin: view.origin,
+                            start,
+                            position: view.position,
+                            rotation: view.rotation,
+                        });
+                        return true;
+                    }
+                }
+                self.pending_pick = Some(cursor);
+                false
+            }
+            Some(Action::Release) => self.drag.take().is_some(),
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use cgmath::{Matrix4, Point3, Quaternion};
+
+use super::entity::EntityHandle;
+
+mod gizmo;
+mod selection;
+
+/// Entity the user selected by clicking it in the scene or in the entity list of the `Inspector`,
+/// both read and write it through `Scene::selection`
+#[derive(Debug, Default)]
+pub struct Selection {
+    selected: Option<EntityHandle>,
+}
+
+/// What dragging a handle of the gizmo does to the selected entity
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum GizmoMode {
+    /// Arrows that move the entity along the world axes
+    #[default]
+    Translate,
+    /// Rings that rotate the entity around the world axes
+    Rotate,
+}
+
+/// Selects the entity with bounds under the cursor with the "select" action and draws a transform
+/// gizmo on the selected entity through `Scene::debug_draw`. "toggle_gizmo_mode" switches between
+/// moving and rotating. Clicks only select while the cursor is visible, a captured cursor is
+/// left to the camera and the terrain tools
+pub struct SelectionController {
+    mode: GizmoMode,
+    /// Cursor position in normalized device coordinates, None while the cursor is captured
+    cursor: Option<(f32, f32)>,
+    /// Click to pick an entity at on the next update
+    pending_pick: Option<(f32, f32)>,
+    /// Camera and gizmo of the last update, clicks on a handle are detected while handling the
+    /// event so they do not reach the handlers below
+    view: Option<GizmoView>,
+    drag: Option<GizmoDrag>,
+}
+
+struct GizmoView {
+    eye: Point3<f32>,
+    inverse_view_projection: Matrix4<f32>,
+    entity: EntityHandle,
+    /// World position of the entity, where the gizmo is drawn
+    origin: Point3<f32>,
+    position: Point3<f32>,
+    rotation: Quaternion<f32>,
+    /// Length of the arrows and radius of the rings, grows with the distance to the camera so
+    /// the gizmo keeps its size on screen
+    size: f32,
+}
+
+/// Handle the user is dragging with the transform of the entity when the drag started
+struct GizmoDrag {
+    entity: EntityHandle,
+    /// Index of the world axis, 0 for x, 1 for y and 2 for z
+    axis: usize,
+    origin: Point3<f32>,
+    /// Position along the axis or angle around it where the handle was grabbed
+    start: f32,
+    position: Point3<f32>,
+    rotation: Quaternion<f32>,
+}
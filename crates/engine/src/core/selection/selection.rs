@@ -0,0 +1,83 @@
+use cgmath::{EuclideanSpace, Point3, Vector3};
+
+use crate::core::{
+    entity::{component::bounds_component::BoundsComponent, EntityHandle},
+    scene::Scene,
+};
+
+use super::Selection;
+
+impl Selection {
+    pub fn new() -> Self {
+        Self { selected: None }
+    }
+
+    pub fn get(&self) -> Option<EntityHandle> {
+        self.selected
+    }
+
+    pub fn set(&mut self, selected: Option<EntityHandle>) {
+        self.selected = selected;
+    }
+
+    pub fn clear(&mut self) {
+        self.selected = None;
+    }
+
+    pub fn is_selected(&self, id: &EntityHandle) -> bool {
+        self.selected.as_ref() == Some(id)
+    }
+
+    /// Closest entity whose world space bounds the ray from `origin` along `direction` hits,
+    /// entities without a `BoundsComponent` can not be picked
+    pub fn pick(
+        scene: &Scene,
+        origin: Point3<f32>,
+        direction: Vector3<f32>,
+    ) -> Option<EntityHandle> {
+        let mut closest: Option<(f32, EntityHandle)> = None;
+        for entity in scene.get_entities_with_component::<BoundsComponent>() {
+            let (Some(bounds), Some(transform)) = (
+                entity.get_own_component::<BoundsComponent>(),
+                scene.get_world_transform(&entity.id),
+            ) else {
+                continue;
+            };
+            let (min, max) = bounds.get_world_bounds(&transform);
+            if let Some(distance) = Selection::intersect_box(origin, direction, min, max) {
+                if closest.map_or(true, |(closest, _)| distance < closest) {
+                    closest = Some((distance, entity.id));
+                }
+            }
+        }
+        closest.map(|(_, id)| id)
+    }
+
+    /// Distance along the ray to where it enters the box, 0 if it starts inside
+    fn intersect_box(
+        origin: Point3<f32>,
+        direction: Vector3<f32>,
+        min: Point3<f32>,
+        max: Point3<f32>,
+    ) -> Option<f32> {
+        let mut near = 0.0f32;
+        let mut far = f32::MAX;
+        let origin = origin.to_vec();
+        for axis in 0..3 {
+            if direction[axis].abs() < f32::EPSILON {
+                if origin[axis] < min[axis] || origin[axis] > max[axis] {
+                    return None;
+                }
+                continue;
+            }
+            let first = (min[axis] - origin[axis]) / direction[axis];
+            let second = (max[axis] - origin[axis]) / direction[axis];
+            near = near.max(first.min(second));
+            far = far.min(first.max(second));
+            if near > far {
+                return None;
+            }
+        }
+        Some(near)
+    }
+}
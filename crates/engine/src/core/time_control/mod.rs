@@ -0,0 +1,19 @@
+use super::utils::DataSource;
+
+mod time_control;
+
+/// Slowest and fastest time scale, see `TimeControl::set_time_scale`
+pub const MIN_TIME_SCALE: f32 = 0.1;
+pub const MAX_TIME_SCALE: f32 = 4.0;
+
+/// Pauses, single-steps and slows down or speeds up the simulation of a scene for debugging.
+/// The scene updates its components with the scaled time, components following the player's
+/// input like the camera keep the real frame time and update while paused, see
+/// `Component::is_realtime`
+pub struct TimeControl {
+    paused: bool,
+    /// Updates still to run while paused
+    steps: u32,
+    /// Shared with the sliders bound to it, clamped whenever it is read
+    time_scale: DataSource<f32>,
+}
@@ -0,0 +1,66 @@
+use crate::core::utils::DataSource;
+
+use super::{TimeControl, MAX_TIME_SCALE, MIN_TIME_SCALE};
+
+impl TimeControl {
+    pub fn new() -> Self {
+        Self {
+            paused: false,
+            steps: 0,
+            time_scale: DataSource::new(1.0),
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+        self.steps = 0;
+    }
+
+    pub fn toggle_paused(&mut self) {
+        self.set_paused(!self.paused);
+    }
+
+    /// Runs a single update while paused, pauses first if the simulation is running
+    pub fn step(&mut self) {
+        self.paused = true;
+        self.steps += 1;
+    }
+
+    pub fn get_time_scale(&self) -> f32 {
+        self.time_scale.read().clamp(MIN_TIME_SCALE, MAX_TIME_SCALE)
+    }
+
+    /// Factor the simulation time runs faster than real time, clamped to `MIN_TIME_SCALE` to
+    /// `MAX_TIME_SCALE`
+    pub fn set_time_scale(&mut self, time_scale: f32) {
+        self.time_scale
+            .write(time_scale.clamp(MIN_TIME_SCALE, MAX_TIME_SCALE));
+    }
+
+    /// The time scale for sliders to edit
+    pub fn get_time_scale_ref(&self) -> DataSource<f32> {
+        self.time_scale.clone()
+    }
+
+    /// Seconds the simulation advances for a frame of `delta_time` real seconds, None while it
+    /// is paused. Called once per update of the scene owning it
+    pub fn advance(&mut self, delta_time: f64) -> Option<f64> {
+        if self.paused {
+            if self.steps == 0 {
+                return None;
+            }
+            self.steps -= 1;
+        }
+        Some(delta_time * self.get_time_scale() as f64)
+    }
+}
+
+impl Default for TimeControl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
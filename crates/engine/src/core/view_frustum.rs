@@ -1,4 +1,4 @@
-use cgmath::{InnerSpace, Vector4};
+use cgmath::{InnerSpace, Matrix, Matrix4, Point3, Vector4};
 
 use crate::terrain::{ChunkBounds, CHUNK_SIZE};
 
@@ -11,16 +11,29 @@ impl ViewFrustum {
         projection: &Projection,
         camera: &Camera,
         bounds: ChunkBounds,
+    ) -> bool {
+        ViewFrustum::is_bounds_in_view(
+            &(projection.get_matrix() * camera.get_matrix()),
+            camera.get_position(),
+            &bounds,
+        )
+    }
+
+    /// Like `is_bounds_in_frustum` for the view of `view_projection` from `position`, which
+    /// can be tested away from the camera, e.g. on the `RenderWorkers`
+    pub fn is_bounds_in_view(
+        view_projection: &Matrix4<f32>,
+        position: Point3<f32>,
+        bounds: &ChunkBounds,
     ) -> bool {
         let mut result = false;
 
         // check if bounds are close to camera
-        let distance = (camera.get_position() - bounds.center()).magnitude();
+        let distance = (position - bounds.center()).magnitude();
         if distance < CHUNK_SIZE as f32 * 0.75 {
             return true;
         }
 
-        let view_projection = projection.get_matrix() * camera.get_matrix();
         let clip: [Vector4<f32>; 8] = [
             Vector4::new(
                 bounds.min.0 as f32,
@@ -73,7 +86,7 @@ impl ViewFrustum {
         ];
 
         for point in clip {
-            let point = view_projection * point;
+            let point = *view_projection * point;
             if point.x <= point.w
                 && point.x >= -point.w
                 && point.y <= point.w
@@ -88,4 +101,35 @@ impl ViewFrustum {
 
         result
     }
+
+    /// Tests the world space box from `min` to `max` against the planes of the frustum of
+    /// `view_projection`, boxes only rejected when they lie fully outside one of the planes
+    pub fn is_box_in_frustum(
+        view_projection: &Matrix4<f32>,
+        min: Point3<f32>,
+        max: Point3<f32>,
+    ) -> bool {
+        for plane in ViewFrustum::get_planes(view_projection) {
+            // the corner furthest along the plane's normal is the last one to leave it
+            let corner = Vector4::new(
+                if plane.x > 0.0 { max.x } else { min.x },
+                if plane.y > 0.0 { max.y } else { min.y },
+                if plane.z > 0.0 { max.z } else { min.z },
+                1.0,
+            );
+            if plane.dot(corner) < 0.0 {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Planes of the frustum of `view_projection` in world space as `(normal, distance)`, with
+    /// unit normals pointing into the frustum
+    pub fn get_planes(view_projection: &Matrix4<f32>) -> [Vector4<f32>; 6] {
+        let w = view_projection.row(3);
+        let [x, y, z] = [0, 1, 2].map(|i| view_projection.row(i));
+        [w + x, w - x, w + y, w - y, w + z, w - z]
+            .map(|plane| plane / plane.truncate().magnitude().max(f32::EPSILON))
+    }
 }
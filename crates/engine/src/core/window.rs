@@ -1,33 +1,68 @@
 use glfw::{Context, GlfwReceiver};
 
+use super::{
+    build_info::{BuildInfo, GpuInfo},
+    input::{GamepadEvent, Input},
+    renderer::{
+        gl_debug::{self, DebugSeverity},
+        render_settings::RenderSettings,
+    },
+};
+
 pub struct Window {
     window: glfw::PWindow,
     glfw: glfw::Glfw,
     events: GlfwReceiver<(f64, glfw::WindowEvent)>,
     pub width: u32,
     pub height: u32,
+    visible: bool,
+    /// Whether buffer swaps currently wait for the display
+    vsync: bool,
 }
 
 impl Window {
     pub fn new(width: u32, height: u32, title: &str) -> Self {
+        Self::create(width, height, title, true)
+    }
+
+    /// Window that is never shown, it only owns the gl context. Buffer swaps don't wait for the
+    /// display, so frames run as fast as they are processed
+    pub fn new_hidden(width: u32, height: u32, title: &str) -> Self {
+        Self::create(width, height, title, false)
+    }
+
+    fn create(width: u32, height: u32, title: &str, visible: bool) -> Self {
         let mut glfw = glfw::init(glfw::log_errors).unwrap_or_else(|err| {
-            eprintln!("Fehler bei der GLFW-Initialisierung: {}", err);
+            log::error!("Fehler bei der GLFW-Initialisierung: {}", err);
             std::process::exit(1);
         });
 
-        glfw.window_hint(glfw::WindowHint::Samples(Some(8)));
+        let settings = RenderSettings::get();
+        // the scene renders into its own multisampled frame, these samples smooth the UI
+        glfw.window_hint(glfw::WindowHint::Samples(
+            settings.is_msaa_enabled().then_some(settings.msaa_samples),
+        ));
+        glfw.window_hint(glfw::WindowHint::OpenGlDebugContext(cfg!(debug_assertions)));
+        glfw.window_hint(glfw::WindowHint::Visible(visible));
 
         let (mut window, events) = glfw
             .create_window(width, height, title, glfw::WindowMode::Windowed)
             .expect("Fenster konnte nicht erstellt werden");
 
         window.make_current();
+        let vsync = visible && settings.vsync;
+        glfw.set_swap_interval(if vsync {
+            glfw::SwapInterval::Sync(1)
+        } else {
+            glfw::SwapInterval::None
+        });
         window.set_key_polling(true);
         window.set_mouse_button_polling(true);
         window.set_scroll_polling(true);
         window.set_cursor_pos_polling(true);
         window.set_framebuffer_size_polling(true);
         window.set_char_polling(true);
+        window.set_content_scale_polling(true);
         // window.set_cursor_mode(glfw::CursorMode::Disabled);
         window.set_cursor_pos(0.0, 0.0);
 
@@ -35,13 +70,43 @@ impl Window {
         unsafe {
             gl::Enable(gl::MULTISAMPLE);
         }
+        if cfg!(debug_assertions) && !gl_debug::enable_debug_output(DebugSeverity::Low) {
+            log::warn!(target: "gl", "The OpenGL context does not support debug output");
+        }
+        if let Some(gpu) = GpuInfo::get() {
+            log::info!(
+                target: "gl",
+                "ferrite {} on {} ({}), OpenGL {}",
+                BuildInfo::get(),
+                gpu.renderer,
+                gpu.vendor,
+                gpu.version
+            );
+        }
 
+        // on high DPI displays the framebuffer can be larger than the requested window size
+        let (width, height) = window.get_framebuffer_size();
         Self {
             window,
             glfw,
             events,
-            width,
-            height,
+            width: width as u32,
+            height: height as u32,
+            visible,
+            vsync,
+        }
+    }
+
+    /// Turns vsync on or off if `settings` changed it, the samples only apply to new windows
+    pub fn apply_render_settings(&mut self, settings: &RenderSettings) {
+        let vsync = self.visible && settings.vsync;
+        if vsync != self.vsync {
+            self.glfw.set_swap_interval(if vsync {
+                glfw::SwapInterval::Sync(1)
+            } else {
+                glfw::SwapInterval::None
+            });
+            self.vsync = vsync;
         }
     }
 
@@ -75,6 +140,16 @@ impl Window {
         }
     }
 
+    /// Polls the gamepads and passes their connections and button changes to `event_handler`
+    pub fn handle_gamepad_events<F>(&mut self, mut event_handler: F)
+    where
+        F: FnMut(&mut glfw::Window, &mut glfw::Glfw, GamepadEvent),
+    {
+        for event in Input::poll(&self.glfw) {
+            event_handler(&mut self.window, &mut self.glfw, event);
+        }
+    }
+
     pub fn should_close(&mut self) -> bool {
         self.window.should_close()
     }
@@ -94,6 +169,11 @@ impl Window {
         delta_time
     }
 
+    /// Ratio of the DPI of the monitor the window is on to the platform's default DPI
+    pub fn get_content_scale(&self) -> f32 {
+        self.window.get_content_scale().0
+    }
+
     pub fn reset_viewport(&self) {
         unsafe {
             gl::Viewport(0, 0, self.width as i32, self.height as i32);
@@ -1,3 +1,5 @@
 pub mod core;
 pub mod player;
+/// Types most games need, `use ferrite::prelude::*;` imports them without their module paths
+pub mod prelude;
 pub mod terrain;
@@ -7,6 +7,5 @@ pub struct PlayerController {
     backward: f32,
     left: f32,
     right: f32,
-
-    dirty: bool,
+    running: bool,
 }
@@ -1,22 +1,30 @@
-use cgmath::{Point3, Vector3, Zero};
-use glfw::{Action, Glfw, Key, WindowEvent};
-use rapier3d::prelude::{ColliderBuilder, RigidBodyType};
+use cgmath::{Point3, Vector3};
+use glfw::{Action, Glfw, WindowEvent};
+use rapier3d::prelude::RigidBodyType;
 
 use crate::core::{
     entity::{
         component::{
-            animation_component::AnimationComponent, camera_component::CameraComponent,
-            model_component::ModelComponent, Component,
+            animation_component::AnimationComponent, bounds_component::BoundsComponent,
+            camera_component::CameraComponent,
+            character_controller_component::CharacterControllerComponent,
+            model_component::ModelComponent, Component, InspectableComponent,
         },
         Entity,
     },
-    model::{animation_graph::AnimationGraph, ModelBuilder},
+    event::{EventCategory, DEFAULT_EVENT_PRIORITY},
+    input::Input,
+    model::{animation_graph::AnimationGraph, ModelBuilder, SkinningMethod},
     physics::rigidbody::RigidBody,
     scene::Scene,
 };
 
 use super::{Player, PlayerController};
 
+/// Radius and height of the capsule the player collides with
+const PLAYER_RADIUS: f32 = 0.35;
+const PLAYER_HEIGHT: f32 = 1.8;
+
 impl Player {
     pub fn new<P: Into<Point3<f32>>>(
         scene: &mut Scene,
@@ -25,25 +33,35 @@ impl Player {
     ) -> Result<Entity, Box<dyn std::error::Error>> {
         let mut entity = Entity::new("player");
         entity.set_position(scene, position);
+        Player::attach(scene, &mut entity, animation_graph);
+        Ok(entity)
+    }
+
+    /// Adds the model, collider and controllers of the player to an entity that is already at
+    /// its position
+    pub fn attach(scene: &mut Scene, entity: &mut Entity, animation_graph: AnimationGraph) {
+        entity.add_tag("player");
 
-        let mut model = ModelBuilder::new("Mannequin.fbx")?.build();
-        model.init();
+        // the player stands in as its bounds until the model is loaded
+        let model = ModelBuilder::new("Mannequin.fbx")
+            .with_skinning(SkinningMethod::DualQuaternion)
+            .build_async();
 
         let animation_component = AnimationComponent::new(animation_graph);
 
-        let collider = ColliderBuilder::ball(1.0).build();
+        let character = CharacterControllerComponent::new(PLAYER_RADIUS, PLAYER_HEIGHT);
 
         entity.add_component(animation_component);
         entity.add_component(RigidBody::new(
             RigidBodyType::KinematicPositionBased,
             scene,
-            &entity,
-            Some(collider),
+            entity,
+            Some(character.get_collider()),
         ));
-        entity.add_component(ModelComponent::new(model));
+        entity.add_component(ModelComponent::loading(model));
+        entity.add_component(BoundsComponent::new((-0.5, 0.0, -0.5), (0.5, 2.0, 0.5)));
         entity.add_component(PlayerController::new());
-
-        Ok(entity)
+        entity.add_component(character);
     }
 }
 
@@ -54,82 +72,73 @@ impl PlayerController {
             backward: 0.0,
             left: 0.0,
             right: 0.0,
-            dirty: false,
+            running: false,
         }
     }
 }
 
+impl Default for PlayerController {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl InspectableComponent for PlayerController {
+    fn get_inspector_name(&self) -> &str {
+        "Player controller"
+    }
+}
+
 impl Component for PlayerController {
     fn update(&mut self, scene: &mut Scene, entity: &mut Entity, _: f64) {
-        let mut position_delta: Vector3<f32> = Vector3::zero();
-        if let Some(animation_component) = entity.get_component_mut::<AnimationComponent>() {
-            if self.dirty {
-                animation_component.set_input("forward", self.forward);
-                animation_component.set_input("backward", self.backward);
-                animation_component.set_input("left", self.left);
-                animation_component.set_input("right", self.right);
-            }
-        }
-        if let Some(model_component) = entity.get_component_mut::<ModelComponent>() {
-            let model = model_component.get_model_mut();
-            position_delta += model.reset_position();
-        }
-        entity.set_position(scene, entity.get_position() + position_delta);
         let camera = scene
             .get_component_mut::<CameraComponent>()
             .unwrap()
             .get_camera_mut();
+        // the keys walk relative to where the camera looks
+        let (sin_yaw, cos_yaw) = camera.get_yaw().0.sin_cos();
+        let forward = Vector3::new(cos_yaw, 0.0, sin_yaw);
+        let right = Vector3::new(-sin_yaw, 0.0, cos_yaw);
+        let direction = forward * (self.forward - self.backward) + right * (self.right - self.left);
+        if let Some(character) = entity.get_component_mut::<CharacterControllerComponent>() {
+            character.set_movement(direction, self.running);
+        }
         camera.set_position(entity.get_position());
-        self.dirty = false;
     }
 
-    fn handle_event(&mut self, _: &mut Glfw, _: &mut glfw::Window, event: &WindowEvent) {
-        match event {
-            glfw::WindowEvent::Key(Key::W, _, action, _) => match action {
-                &Action::Press => {
-                    self.forward = 1.0;
-                    self.dirty = true;
-                }
-                &Action::Release => {
-                    self.forward = 0.0;
-                    self.dirty = true;
-                }
-                _ => {}
-            },
-            glfw::WindowEvent::Key(Key::S, _, action, _) => match action {
-                &Action::Press => {
-                    self.backward = 1.0;
-                    self.dirty = true;
-                }
-                &Action::Release => {
-                    self.backward = 0.0;
-                    self.dirty = true;
-                }
-                _ => {}
-            },
-            glfw::WindowEvent::Key(Key::A, _, action, _) => match action {
-                &Action::Press => {
-                    self.left = 1.0;
-                    self.dirty = true;
-                }
-                &Action::Release => {
-                    self.left = 0.0;
-                    self.dirty = true;
-                }
-                _ => {}
-            },
-            glfw::WindowEvent::Key(Key::D, _, action, _) => match action {
-                &Action::Press => {
-                    self.right = 1.0;
-                    self.dirty = true;
-                }
-                &Action::Release => {
-                    self.right = 0.0;
-                    self.dirty = true;
-                }
+    fn get_event_priority(&self, category: EventCategory) -> Option<i32> {
+        match category {
+            EventCategory::Keyboard => Some(DEFAULT_EVENT_PRIORITY),
+            _ => None,
+        }
+    }
+
+    fn handle_event(&mut self, _: &mut Glfw, _: &mut glfw::Window, event: &WindowEvent) -> bool {
+        for (action, amount) in [
+            ("walk_forward", &mut self.forward),
+            ("walk_backward", &mut self.backward),
+            ("walk_left", &mut self.left),
+            ("walk_right", &mut self.right),
+        ] {
+            match Input::get_action(action, event) {
+                Some(Action::Press) => *amount = 1.0,
+                Some(Action::Release) => *amount = 0.0,
                 _ => {}
-            },
+            }
+        }
+        match Input::get_action("run", event) {
+            Some(Action::Press) => self.running = true,
+            Some(Action::Release) => self.running = false,
             _ => {}
         }
+        false
+    }
+
+    fn as_inspectable(&self) -> Option<&dyn InspectableComponent> {
+        Some(self)
+    }
+
+    fn as_inspectable_mut(&mut self) -> Option<&mut dyn InspectableComponent> {
+        Some(self)
     }
 }
@@ -0,0 +1,32 @@
+pub use crate::core::{
+    application::{Application, Layer},
+    asset::{Asset, AssetHandle, AssetManager, Assets},
+    camera::{controller::CameraControllerType, Camera, Projection},
+    config::EngineConfig,
+    entity::{
+        component::{
+            camera_component::CameraComponent, model_component::ModelComponent, Component,
+            InspectableComponent,
+        },
+        ChangeTracker, ComponentHandle, Entity, EntityFilter, EntityHandle,
+    },
+    event::EventCategory,
+    model::{
+        Animation, Material, MaterialManager, MaterialSlot, Model, ModelBuilder, ModelHandle,
+        ModelMaterial, SkeletonLod,
+    },
+    physics::rigidbody::RigidBody,
+    renderer::{
+        light::skylight::SkyLight,
+        render_settings::RenderSettings,
+        text::{FontManager, Fonts, TextAlignment},
+        ui::{primitives::UIElementHandle, UIElement, UIRenderer, UI},
+    },
+    scene::Scene,
+    scene_file::{ComponentParams, ComponentRegistry, SceneFile},
+    selection::{Selection, SelectionController},
+    utils::DataSource,
+    window::Window,
+};
+pub use crate::player::Player;
+pub use crate::terrain::{world_gen::WorldGenConfig, Terrain};
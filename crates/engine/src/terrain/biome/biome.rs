@@ -0,0 +1,179 @@
+use libnoise::prelude::*;
+
+use crate::terrain::{surface::SurfaceMaterial, world_gen::WorldGenConfig};
+
+use super::{Biome, BiomeMap, BiomeSample, BiomeType, MAX_SURFACE_HEIGHT};
+
+const MIN_WEIGHT: f32 = 0.01;
+const NOISE_OFFSET: f64 = 16777216.0;
+
+impl Biome {
+    pub fn defaults() -> Vec<Biome> {
+        vec![
+            Biome {
+                biome_type: BiomeType::Plains,
+                temperature: 0.0,
+                humidity: 0.0,
+                base_height: 40.0,
+                height_amplitude: 50.0,
+                height_frequency: 0.003,
+                height_octaves: 4,
+                surface_material: SurfaceMaterial::Grass,
+                surface_color: [0.0, 0.5, 0.1],
+                surface_block: 1,
+                cave_density: 0.02,
+            },
+            Biome {
+                biome_type: BiomeType::Forest,
+                temperature: 0.2,
+                humidity: 0.7,
+                base_height: 45.0,
+                height_amplitude: 50.0,
+                height_frequency: 0.004,
+                height_octaves: 6,
+                surface_material: SurfaceMaterial::Grass,
+                surface_color: [0.05, 0.38, 0.08],
+                surface_block: 1,
+                cave_density: 0.03,
+            },
+            Biome {
+                biome_type: BiomeType::Desert,
+                temperature: 0.8,
+                humidity: -0.7,
+                base_height: 48.0,
+                height_amplitude: 25.0,
+                height_frequency: 0.002,
+                height_octaves: 3,
+                surface_material: SurfaceMaterial::Sand,
+                surface_color: [0.76, 0.698, 0.502],
+                surface_block: 2,
+                cave_density: 0.01,
+            },
+            Biome {
+                biome_type: BiomeType::Tundra,
+                temperature: -0.8,
+                humidity: 0.0,
+                base_height: 50.0,
+                height_amplitude: 30.0,
+                height_frequency: 0.003,
+                height_octaves: 4,
+                surface_material: SurfaceMaterial::Snow,
+                surface_color: [0.85, 0.88, 0.9],
+                surface_block: 2,
+                cave_density: 0.02,
+            },
+            Biome {
+                biome_type: BiomeType::Mountains,
+                temperature: -0.2,
+                humidity: -0.5,
+                base_height: 40.0,
+                height_amplitude: 72.0,
+                height_frequency: 0.004,
+                height_octaves: 6,
+                surface_material: SurfaceMaterial::Rock,
+                surface_color: [0.5, 0.5, 0.5],
+                surface_block: 2,
+                cave_density: 0.06,
+            },
+        ]
+    }
+}
+
+impl BiomeMap {
+    pub fn new(config: &WorldGenConfig) -> Self {
+        let seed = config.seed;
+        let biomes = config.biomes.clone();
+        let height_noise = biomes
+            .iter()
+            .map(|biome| {
+                Source::perlin(seed).scale([biome.height_frequency; 2]).fbm(
+                    biome.height_octaves,
+                    1.0,
+                    2.0,
+                    0.5,
+                )
+            })
+            .collect();
+        Self {
+            temperature: Source::perlin(seed.wrapping_add(1)).scale([config.climate_frequency; 2]),
+            humidity: Source::perlin(seed.wrapping_add(2)).scale([config.climate_frequency; 2]),
+            biomes,
+            blend_width: config.biome_blend_width,
+            height_noise,
+            max_height: if config.generator_version >= 2 {
+                MAX_SURFACE_HEIGHT
+            } else {
+                f32::INFINITY
+            },
+        }
+    }
+
+    pub fn get_biomes(&self) -> &Vec<Biome> {
+        &self.biomes
+    }
+
+    /// Returns the climate `(temperature, humidity)` at the world column `(x, z)`
+    pub fn get_climate(&self, x: f64, z: f64) -> (f32, f32) {
+        let point = [x + NOISE_OFFSET, z + NOISE_OFFSET];
+        (
+            self.temperature.sample(point) as f32,
+            self.humidity.sample(point) as f32,
+        )
+    }
+
+    pub fn sample(&self, x: f64, z: f64) -> BiomeSample {
+        let (temperature, humidity) = self.get_climate(x, z);
+
+        // gaussian falloff in climate space keeps the transitions between biomes smooth
+        let mut weights: Vec<(usize, f32)> = self
+            .biomes
+            .iter()
+            .enumerate()
+            .map(|(i, biome)| {
+                let distance =
+                    (biome.temperature - temperature).powi(2) + (biome.humidity - humidity).powi(2);
+                (i, (-distance / (self.blend_width * self.blend_width)).exp())
+            })
+            .collect();
+        let total: f32 = weights.iter().map(|(_, weight)| weight).sum();
+        for (_, weight) in &mut weights {
+            *weight /= total;
+        }
+        weights.retain(|(_, weight)| *weight >= MIN_WEIGHT);
+        let total: f32 = weights.iter().map(|(_, weight)| weight).sum();
+
+        let mut height = 0.0;
+        let mut surface_color = [0.0; 3];
+        let mut cave_density = 0.0;
+        let mut dominant = (0, 0.0);
+        for (i, weight) in &mut weights {
+            *weight /= total;
+            let biome = &self.biomes[*i];
+            let noise =
+                (1.0 + self.height_noise[*i].sample([x + NOISE_OFFSET, z + NOISE_OFFSET])) / 2.0;
+            height += (biome.base_height + noise as f32 * biome.height_amplitude) * *weight;
+            for (color, biome_color) in surface_color.iter_mut().zip(biome.surface_color) {
+                *color += biome_color * *weight;
+            }
+            cave_density += biome.cave_density * *weight;
+            if *weight > dominant.1 {
+                dominant = (*i, *weight);
+            }
+        }
+
+        let dominant = &self.biomes[dominant.0];
+        BiomeSample {
+            biome_type: dominant.biome_type,
+            // biomes loaded from a config may reach above the chunk
+            height: height.min(self.max_height),
+            surface_material: dominant.surface_material,
+            surface_color,
+            surface_block: dominant.surface_block,
+            cave_density,
+            weights: weights
+                .iter()
+                .map(|(i, weight)| (self.biomes[*i].biome_type, *weight))
+                .collect(),
+        }
+    }
+}
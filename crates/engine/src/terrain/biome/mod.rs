@@ -0,0 +1,65 @@
+use libnoise::{Fbm, Perlin, Scale};
+use serde::{Deserialize, Serialize};
+
+use super::{surface::SurfaceMaterial, CHUNK_SIZE_FLOAT};
+
+mod biome;
+
+/// Highest the terrain surface reaches. Chunks are a single `CHUNK_SIZE` high, so higher terrain
+/// would be cut flat at the top, the rest leaves room for trees and structures on the peaks
+pub const MAX_SURFACE_HEIGHT: f32 = CHUNK_SIZE_FLOAT - 16.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum BiomeType {
+    Plains,
+    Forest,
+    Desert,
+    Tundra,
+    Mountains,
+}
+
+/// Generation parameters of a single biome
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Biome {
+    pub biome_type: BiomeType,
+    /// Position of the biome in climate space, both in the range `-1.0..=1.0`
+    pub temperature: f32,
+    pub humidity: f32,
+    /// Height of the lowest point of the biome in world units
+    pub base_height: f32,
+    /// Difference between the lowest and highest point of the biome in world units, the surface
+    /// is clamped to `MAX_SURFACE_HEIGHT`
+    pub height_amplitude: f32,
+    pub height_frequency: f64,
+    pub height_octaves: u32,
+    pub surface_material: SurfaceMaterial,
+    pub surface_color: [f32; 3],
+    /// Block type used for the top layer of voxel terrain
+    pub surface_block: u32,
+    /// Fraction of the underground that is hollowed out by caves
+    pub cave_density: f32,
+}
+
+/// 2D map selecting biomes by temperature and humidity noise
+pub struct BiomeMap {
+    temperature: Scale<2, Perlin<2>>,
+    humidity: Scale<2, Perlin<2>>,
+    biomes: Vec<Biome>,
+    blend_width: f32,
+    height_noise: Vec<Fbm<2, Scale<2, Perlin<2>>>>,
+    /// `MAX_SURFACE_HEIGHT`, unclamped for worlds of generator version 1
+    max_height: f32,
+}
+
+/// Biome parameters at a single world column, blended between neighbouring biomes
+#[derive(Clone, Debug)]
+pub struct BiomeSample {
+    /// Biome with the highest weight
+    pub biome_type: BiomeType,
+    pub height: f32,
+    pub surface_material: SurfaceMaterial,
+    pub surface_color: [f32; 3],
+    pub surface_block: u32,
+    pub cave_density: f32,
+    pub weights: Vec<(BiomeType, f32)>,
+}
@@ -0,0 +1,233 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::RwLock,
+};
+
+use lazy_static::lazy_static;
+
+use crate::terrain::voxel::TORCH_BLOCK;
+
+use super::{ChunkLight, LightEmitters, MAX_LIGHT_LEVEL};
+
+lazy_static! {
+    static ref EMITTERS: RwLock<HashMap<u32, u8>> =
+        RwLock::new(HashMap::from([(TORCH_BLOCK, MAX_LIGHT_LEVEL - 1)]));
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Channel {
+    Sky,
+    Block,
+}
+
+impl Channel {
+    fn get(self, value: u8) -> u8 {
+        match self {
+            Channel::Sky => value >> 4,
+            Channel::Block => value & 0x0f,
+        }
+    }
+
+    fn set(self, value: &mut u8, level: u8) {
+        *value = match self {
+            Channel::Sky => (*value & 0x0f) | (level << 4),
+            Channel::Block => (*value & 0xf0) | level,
+        };
+    }
+}
+
+impl LightEmitters {
+    /// Makes blocks of `type_id` emit light of `level`, at most `MAX_LIGHT_LEVEL`
+    pub fn register(type_id: u32, level: u8) {
+        EMITTERS
+            .write()
+            .unwrap()
+            .insert(type_id, level.min(MAX_LIGHT_LEVEL));
+    }
+
+    pub fn unregister(type_id: u32) {
+        EMITTERS.write().unwrap().remove(&type_id);
+    }
+
+    /// Light level blocks of `type_id` emit, 0 for blocks that do not emit any
+    pub fn get(type_id: u32) -> u8 {
+        EMITTERS.read().unwrap().get(&type_id).copied().unwrap_or(0)
+    }
+
+    /// Copy of the emitters for filling a whole chunk without locking them for every block
+    pub(crate) fn get_all() -> HashMap<u32, u8> {
+        EMITTERS.read().unwrap().clone()
+    }
+}
+
+impl ChunkLight {
+    /// Lights a `size` wide chunk from `is_opaque` and `get_emission`, which are called with the
+    /// chunk space position of every block. Above the chunk is treated as open sky
+    pub fn propagate<O, E>(size: usize, is_opaque: O, get_emission: E) -> Self
+    where
+        O: Fn(usize, usize, usize) -> bool,
+        E: Fn(usize, usize, usize) -> u8,
+    {
+        let mut light = Self {
+            size,
+            data: vec![0; size * size * size],
+        };
+        let mut sky = VecDeque::new();
+        let mut block = VecDeque::new();
+        for x in 0..size {
+            for z in 0..size {
+                for y in (0..size).rev() {
+                    if is_opaque(x, y, z) {
+                        break;
+                    }
+                    let index = light.index(x, y, z);
+                    Channel::Sky.set(&mut light.data[index], MAX_LIGHT_LEVEL);
+                    sky.push_back(index);
+                }
+            }
+        }
+        for x in 0..size {
+            for y in 0..size {
+                for z in 0..size {
+                    let emission = get_emission(x, y, z);
+                    if emission > 0 {
+                        let index = light.index(x, y, z);
+                        Channel::Block.set(&mut light.data[index], emission);
+                        block.push_back(index);
+                    }
+                }
+            }
+        }
+        light.spread(Channel::Sky, sky, &is_opaque);
+        light.spread(Channel::Block, block, &is_opaque);
+        light
+    }
+
+    /// Relights the chunk after the blocks at the chunk space positions `changed` were placed or
+    /// removed. Light that came from or through the changed blocks is removed first and the
+    /// darkened area is then refilled from the light around it, so only the affected blocks are
+    /// visited
+    pub fn update<O, E>(&mut self, changed: &[(usize, usize, usize)], is_opaque: O, get_emission: E)
+    where
+        O: Fn(usize, usize, usize) -> bool,
+        E: Fn(usize, usize, usize) -> u8,
+    {
+        for channel in [Channel::Sky, Channel::Block] {
+            let mut removal = VecDeque::new();
+            let mut relight = VecDeque::new();
+            for &(x, y, z) in changed {
+                let index = self.index(x, y, z);
+                let level = channel.get(self.data[index]);
+                if level > 0 {
+                    channel.set(&mut self.data[index], 0);
+                    removal.push_back((index, level));
+                }
+            }
+            while let Some((index, level)) = removal.pop_front() {
+                for (neighbour, down) in self.get_neighbours(index) {
+                    let neighbour_level = channel.get(self.data[neighbour]);
+                    if neighbour_level == 0 {
+                        continue;
+                    }
+                    let lit_from_here = neighbour_level < level
+                        || channel == Channel::Sky && down && level == MAX_LIGHT_LEVEL;
+                    if lit_from_here {
+                        channel.set(&mut self.data[neighbour], 0);
+                        removal.push_back((neighbour, neighbour_level));
+                    } else {
+                        relight.push_back(neighbour);
+                    }
+                }
+            }
+            for &(x, y, z) in changed {
+                let index = self.index(x, y, z);
+                let seed = match channel {
+                    Channel::Sky if y == self.size - 1 && !is_opaque(x, y, z) => MAX_LIGHT_LEVEL,
+                    Channel::Sky => 0,
+                    Channel::Block => get_emission(x, y, z),
+                };
+                if seed > channel.get(self.data[index]) {
+                    channel.set(&mut self.data[index], seed);
+                    relight.push_back(index);
+                }
+                // a removed block lets the light of its neighbours in
+                for (neighbour, _) in self.get_neighbours(index) {
+                    if channel.get(self.data[neighbour]) > 0 {
+                        relight.push_back(neighbour);
+                    }
+                }
+            }
+            self.spread(channel, relight, &is_opaque);
+        }
+    }
+
+    /// Skylight and block light at the chunk space position, 0 to `MAX_LIGHT_LEVEL`. Positions
+    /// outside of the chunk are open sky since light does not cross chunk borders
+    pub fn get(&self, x: i32, y: i32, z: i32) -> (u8, u8) {
+        let size = self.size as i32;
+        if x < 0 || y < 0 || z < 0 || x >= size || y >= size || z >= size {
+            return (MAX_LIGHT_LEVEL, 0);
+        }
+        let value = self.data[self.index(x as usize, y as usize, z as usize)];
+        (Channel::Sky.get(value), Channel::Block.get(value))
+    }
+
+    /// Flood fills `channel` from the blocks in `queue` into the transparent blocks around them
+    fn spread<O: Fn(usize, usize, usize) -> bool>(
+        &mut self,
+        channel: Channel,
+        mut queue: VecDeque<usize>,
+        is_opaque: &O,
+    ) {
+        while let Some(index) = queue.pop_front() {
+            let level = channel.get(self.data[index]);
+            if level <= 1 {
+                continue;
+            }
+            for (neighbour, down) in self.get_neighbours(index) {
+                let (x, y, z) = self.position(neighbour);
+                if is_opaque(x, y, z) {
+                    continue;
+                }
+                let neighbour_level = if channel == Channel::Sky && down && level == MAX_LIGHT_LEVEL
+                {
+                    MAX_LIGHT_LEVEL
+                } else {
+                    level - 1
+                };
+                if channel.get(self.data[neighbour]) < neighbour_level {
+                    channel.set(&mut self.data[neighbour], neighbour_level);
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+    }
+
+    /// Indices of the blocks next to `index` inside the chunk, with whether they lie below it
+    fn get_neighbours(&self, index: usize) -> impl Iterator<Item = (usize, bool)> {
+        let (x, y, z) = self.position(index);
+        let size = self.size;
+        [
+            (x > 0).then(|| (index - 1, false)),
+            (x + 1 < size).then(|| (index + 1, false)),
+            (y > 0).then(|| (index - size, true)),
+            (y + 1 < size).then(|| (index + size, false)),
+            (z > 0).then(|| (index - size * size, false)),
+            (z + 1 < size).then(|| (index + size * size, false)),
+        ]
+        .into_iter()
+        .flatten()
+    }
+
+    fn index(&self, x: usize, y: usize, z: usize) -> usize {
+        x + self.size * (y + self.size * z)
+    }
+
+    fn position(&self, index: usize) -> (usize, usize, usize) {
+        (
+            index % self.size,
+            index / self.size % self.size,
+            index / (self.size * self.size),
+        )
+    }
+}
@@ -0,0 +1,19 @@
+mod block_light;
+
+/// Brightest light level, of open sky and of the strongest light sources
+pub const MAX_LIGHT_LEVEL: u8 = 15;
+
+/// Sky and block light of every block of a chunk, flood filled from the open sky and from light
+/// emitting blocks. Light drops by one level per block, except skylight which travels straight
+/// down without losing any. Light does not cross chunk borders
+#[derive(Default)]
+pub struct ChunkLight {
+    /// Number of blocks along every axis
+    size: usize,
+    /// Skylight in the high and block light in the low nibble of every block, x varies fastest
+    data: Vec<u8>,
+}
+
+/// Light levels block types emit, shared by all chunks. Changes only affect chunks generated or
+/// edited afterwards
+pub struct LightEmitters;
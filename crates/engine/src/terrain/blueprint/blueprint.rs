@@ -0,0 +1,250 @@
+use std::{collections::BTreeMap, error::Error, fs, path::Path, sync::Mutex};
+
+use lazy_static::lazy_static;
+
+use crate::{
+    core::entity::{component::Component, Entity},
+    terrain::{Chunk, Terrain},
+};
+
+use super::{Blueprint, BlueprintEditor, BlueprintLibrary};
+
+lazy_static! {
+    static ref BLUEPRINT_LIBRARY: Mutex<BlueprintLibrary> = Mutex::new(BlueprintLibrary {
+        blueprints: BTreeMap::new(),
+    });
+}
+
+impl Blueprint {
+    /// Copies the blocks between the world block positions `min` and `max`, both included.
+    /// `anchor` is a world block position as well, `get_block` returns 0 for air
+    pub fn capture(
+        name: &str,
+        min: (i32, i32, i32),
+        max: (i32, i32, i32),
+        anchor: (i32, i32, i32),
+        get_block: impl Fn((i32, i32, i32)) -> u32,
+    ) -> Self {
+        let (min, max) = (
+            (min.0.min(max.0), min.1.min(max.1), min.2.min(max.2)),
+            (min.0.max(max.0), min.1.max(max.1), min.2.max(max.2)),
+        );
+        let size = (
+            (max.0 - min.0 + 1) as u32,
+            (max.1 - min.1 + 1) as u32,
+            (max.2 - min.2 + 1) as u32,
+        );
+        let mut blocks = Vec::with_capacity((size.0 * size.1 * size.2) as usize);
+        for y in min.1..=max.1 {
+            for z in min.2..=max.2 {
+                for x in min.0..=max.0 {
+                    blocks.push(get_block((x, y, z)));
+                }
+            }
+        }
+        Self {
+            name: name.to_string(),
+            size,
+            anchor: (anchor.0 - min.0, anchor.1 - min.1, anchor.2 - min.2),
+            blocks,
+        }
+    }
+
+    /// Loads a blueprint saved with `save` from the RON file at `path`
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Blueprint, Box<dyn Error>> {
+        let blueprint: Blueprint = ron::from_str(&fs::read_to_string(path)?)?;
+        let (width, height, depth) = blueprint.size;
+        if blueprint.blocks.len() != (width * height * depth) as usize {
+            return Err(format!(
+                "blueprint {} has {} blocks instead of {}x{}x{}",
+                blueprint.name,
+                blueprint.blocks.len(),
+                width,
+                height,
+                depth
+            )
+            .into());
+        }
+        Ok(blueprint)
+    }
+
+    /// Writes the blueprint to `path` as RON
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Box<dyn Error>> {
+        let source = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())?;
+        fs::write(path, source)?;
+        Ok(())
+    }
+
+    pub fn get_size(&self) -> (u32, u32, u32) {
+        self.size
+    }
+
+    pub fn get_anchor(&self) -> (i32, i32, i32) {
+        self.anchor
+    }
+
+    /// Minimum and maximum world block position covered when the anchor is placed at `position`
+    pub fn get_bounds(&self, position: (i32, i32, i32)) -> ((i32, i32, i32), (i32, i32, i32)) {
+        let min = (
+            position.0 - self.anchor.0,
+            position.1 - self.anchor.1,
+            position.2 - self.anchor.2,
+        );
+        (
+            min,
+            (
+                min.0 + self.size.0 as i32 - 1,
+                min.1 + self.size.1 as i32 - 1,
+                min.2 + self.size.2 as i32 - 1,
+            ),
+        )
+    }
+
+    /// Type of the block at `position` relative to the minimum corner, None outside the region
+    pub fn get_block(&self, position: (u32, u32, u32)) -> Option<u32> {
+        let (width, height, depth) = self.size;
+        let (x, y, z) = position;
+        if x >= width || y >= height || z >= depth {
+            return None;
+        }
+        Some(self.blocks[(x + z * width + y * width * depth) as usize])
+    }
+
+    /// World block positions and types of all blocks when the anchor is placed at `position`
+    pub fn get_placed_blocks(
+        &self,
+        position: (i32, i32, i32),
+    ) -> impl Iterator<Item = ((i32, i32, i32), u32)> + '_ {
+        let (width, _, depth) = self.size;
+        let (origin, _) = self.get_bounds(position);
+        self.blocks.iter().enumerate().map(move |(index, block)| {
+            let index = index as u32;
+            let (x, z, y) = (
+                index % width,
+                index / width % depth,
+                index / (width * depth),
+            );
+            (
+                (
+                    origin.0 + x as i32,
+                    origin.1 + y as i32,
+                    origin.2 + z as i32,
+                ),
+                *block,
+            )
+        })
+    }
+}
+
+impl BlueprintLibrary {
+    /// Adds `blueprint` under its name, replacing a blueprint registered with the same name
+    pub fn register(blueprint: Blueprint) {
+        BLUEPRINT_LIBRARY
+            .lock()
+            .unwrap()
+            .blueprints
+            .insert(blueprint.name.clone(), blueprint);
+    }
+
+    pub fn unregister(name: &str) -> Option<Blueprint> {
+        BLUEPRINT_LIBRARY.lock().unwrap().blueprints.remove(name)
+    }
+
+    pub fn get(name: &str) -> Option<Blueprint> {
+        BLUEPRINT_LIBRARY
+            .lock()
+            .unwrap()
+            .blueprints
+            .get(name)
+            .cloned()
+    }
+
+    /// Names of the registered blueprints in alphabetical order
+    pub fn get_names() -> Vec<String> {
+        BLUEPRINT_LIBRARY
+            .lock()
+            .unwrap()
+            .blueprints
+            .keys()
+            .cloned()
+            .collect()
+    }
+
+    /// Registers every `.ron` file in `path`, files that fail to load are logged and skipped.
+    /// Returns the number of registered blueprints
+    pub fn load_directory<P: AsRef<Path>>(path: P) -> Result<usize, Box<dyn Error>> {
+        let mut count = 0;
+        for entry in fs::read_dir(path)? {
+            let path = entry?.path();
+            if path.extension().and_then(|e| e.to_str()) != Some("ron") {
+                continue;
+            }
+            match Blueprint::load(&path) {
+                Ok(blueprint) => {
+                    BlueprintLibrary::register(blueprint);
+                    count += 1;
+                }
+                Err(err) => log::warn!("Failed to load blueprint {}: {}", path.display(), err),
+            }
+        }
+        Ok(count)
+    }
+}
+
+impl BlueprintEditor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the first and the second corner of the selection in turns
+    pub fn select_corner(&mut self, position: (i32, i32, i32)) {
+        self.corners[self.next_corner] = Some(position);
+        self.next_corner = (self.next_corner + 1) % 2;
+    }
+
+    pub fn set_anchor(&mut self, position: (i32, i32, i32)) {
+        self.anchor = Some(position);
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Minimum and maximum corner of the selection once both corners are set
+    pub fn get_selection(&self) -> Option<((i32, i32, i32), (i32, i32, i32))> {
+        let [Some(a), Some(b)] = self.corners else {
+            return None;
+        };
+        Some((
+            (a.0.min(b.0), a.1.min(b.1), a.2.min(b.2)),
+            (a.0.max(b.0), a.1.max(b.1), a.2.max(b.2)),
+        ))
+    }
+
+    pub fn get_anchor(&self) -> Option<(i32, i32, i32)> {
+        let (min, max) = self.get_selection()?;
+        Some(
+            self.anchor
+                .unwrap_or(((min.0 + max.0) / 2, min.1, (min.2 + max.2) / 2)),
+        )
+    }
+
+    /// Captures the selected blocks of `terrain` as the blueprint `name`, writes it to
+    /// `directory` and registers it. `entity` is the terrain's entity
+    pub fn save<T: Chunk + Component + Send + 'static, P: AsRef<Path>>(
+        &self,
+        terrain: &Terrain<T>,
+        entity: &Entity,
+        name: &str,
+        directory: P,
+    ) -> Result<Blueprint, Box<dyn Error>> {
+        let (Some((min, max)), Some(anchor)) = (self.get_selection(), self.get_anchor()) else {
+            return Err("select both corners of the blueprint first".into());
+        };
+        let blueprint = terrain.capture_blueprint(entity, name, min, max, anchor);
+        fs::create_dir_all(&directory)?;
+        blueprint.save(directory.as_ref().join(format!("{}.ron", name)))?;
+        BlueprintLibrary::register(blueprint.clone());
+        Ok(blueprint)
+    }
+}
@@ -0,0 +1,35 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+mod blueprint;
+
+/// Region of blocks saved as a named structure template. Placing it puts its anchor block at the
+/// target position, air inside the region is placed as well so interiors stay hollow
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Blueprint {
+    pub name: String,
+    /// Blocks along x, y and z
+    size: (u32, u32, u32),
+    /// Block that ends up at the placement position, relative to the minimum corner
+    anchor: (i32, i32, i32),
+    /// Block types with x changing fastest, then z, then y. 0 is air
+    blocks: Vec<u32>,
+}
+
+/// Named blueprints the structure generation picks from and the blueprint editor places, shared
+/// by all terrains. See `BlueprintLibrary::register`
+pub struct BlueprintLibrary {
+    blueprints: BTreeMap<String, Blueprint>,
+}
+
+/// Selection of the blueprint editor mode: two corners spanning the region to capture and the
+/// block the blueprint is anchored at. See `BlueprintEditor::save`
+#[derive(Clone, Debug, Default)]
+pub struct BlueprintEditor {
+    corners: [Option<(i32, i32, i32)>; 2],
+    /// Corner the next call to `select_corner` sets
+    next_corner: usize,
+    /// Defaults to the bottom center of the selection
+    anchor: Option<(i32, i32, i32)>,
+}
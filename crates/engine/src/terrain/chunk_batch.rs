@@ -0,0 +1,308 @@
+use std::{marker::PhantomData, mem::size_of, sync::Arc};
+
+use cgmath::Point3;
+use gl::types::GLuint;
+
+use crate::core::{
+    profiler::Profiler,
+    renderer::{
+        gl_debug,
+        render_workers::RenderWorkers,
+        shader::{DynamicVertexArray, Shader, VertexAttributes},
+    },
+};
+
+use super::{
+    BatchRegion, ChunkBatch, ChunkBounds, ChunkDrawList, ChunkMesh, ChunkRecording,
+    DrawElementsIndirectCommand,
+};
+
+/// Storage buffer binding of the chunk origins, indexed with `gl_DrawID` in the chunk shaders.
+/// The w component of an origin holds the level of detail of the chunk
+const CHUNK_ORIGIN_BINDING: GLuint = 4;
+const INITIAL_VERTEX_CAPACITY: usize = 1 << 16;
+const INITIAL_INDEX_CAPACITY: usize = 1 << 18;
+/// Chunks a worker tests at least while recording, handing a job to a worker costs about as
+/// much as testing a few dozen chunks
+const MIN_REGIONS_PER_WORKER: usize = 32;
+
+impl<T: VertexAttributes + Clone> ChunkBatch<T> {
+    pub fn new() -> Self {
+        let mut vertex_array = 0;
+        let mut indirect_buffer = 0;
+        let mut origin_buffer = 0;
+        unsafe {
+            gl::GenVertexArrays(1, &mut vertex_array);
+            gl::GenBuffers(1, &mut indirect_buffer);
+            gl::GenBuffers(1, &mut origin_buffer);
+        }
+        let mut batch = Self {
+            vertex_array,
+            vertex_buffer: 0,
+            index_buffer: 0,
+            indirect_buffer,
+            origin_buffer,
+            vertex_capacity: 0,
+            index_capacity: 0,
+            vertex_count: 0,
+            index_count: 0,
+            regions: Arc::new(Vec::new()),
+            vertex: PhantomData,
+        };
+        batch.reserve(INITIAL_VERTEX_CAPACITY, INITIAL_INDEX_CAPACITY);
+        batch
+    }
+
+    /// Appends the mesh of the chunk at `origin`, a chunk that was inserted before is replaced
+    /// without reclaiming the space of its old mesh
+    pub fn insert(
+        &mut self,
+        origin: Point3<f32>,
+        bounds: ChunkBounds,
+        lod: usize,
+        mesh: &ChunkMesh<T>,
+    ) {
+        // only copies the regions while workers still hold an earlier recording's
+        let regions = Arc::make_mut(&mut self.regions);
+        regions.retain(|region| region.origin != origin);
+        let indices = match &mesh.indices {
+            Some(indices) => indices.clone(),
+            None => (0..mesh.vertices.len() as u32).collect(),
+        };
+        if indices.is_empty() {
+            return;
+        }
+        self.reserve(
+            self.vertex_count + mesh.vertices.len(),
+            self.index_count + indices.len(),
+        );
+        unsafe {
+            gl::BindBuffer(gl::ARRAY_BUFFER, self.vertex_buffer);
+            gl::BufferSubData(
+                gl::ARRAY_BUFFER,
+                (self.vertex_count * size_of::<T>()) as isize,
+                (mesh.vertices.len() * size_of::<T>()) as isize,
+                mesh.vertices.as_ptr() as *const _,
+            );
+            gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+            // the element array binding belongs to whichever vertex array is bound
+            gl::BindBuffer(gl::COPY_WRITE_BUFFER, self.index_buffer);
+            gl::BufferSubData(
+                gl::COPY_WRITE_BUFFER,
+                (self.index_count * size_of::<u32>()) as isize,
+                (indices.len() * size_of::<u32>()) as isize,
+                indices.as_ptr() as *const _,
+            );
+            gl::BindBuffer(gl::COPY_WRITE_BUFFER, 0);
+        }
+        regions.push(BatchRegion {
+            origin,
+            bounds,
+            lod,
+            first_index: self.index_count as u32,
+            index_count: indices.len() as u32,
+            base_vertex: self.vertex_count as i32,
+        });
+        self.vertex_count += mesh.vertices.len();
+        self.index_count += indices.len();
+    }
+
+    /// Starts recording the draws of the chunks whose bounds pass `is_visible` on the
+    /// `RenderWorkers`, large batches are split between several of them. The draws stay in the
+    /// order of the batch
+    pub fn record(
+        &self,
+        is_visible: impl Fn(&ChunkBounds) -> bool + Send + Sync + 'static,
+    ) -> ChunkRecording
+    where
+        T: 'static,
+    {
+        let workers = RenderWorkers::get_count()
+            .min(self.regions.len() / MIN_REGIONS_PER_WORKER)
+            .max(1);
+        let regions_per_worker = self.regions.len().div_ceil(workers).max(1);
+        let is_visible = Arc::new(is_visible);
+        let jobs = (0..self.regions.len())
+            .step_by(regions_per_worker)
+            .map(|start| {
+                let regions = self.regions.clone();
+                let is_visible = is_visible.clone();
+                let end = (start + regions_per_worker).min(regions.len());
+                move || ChunkBatch::<T>::record_regions(&regions[start..end], &*is_visible)
+            });
+        ChunkRecording(RenderWorkers::record(jobs))
+    }
+
+    fn record_regions(
+        regions: &[BatchRegion],
+        is_visible: &impl Fn(&ChunkBounds) -> bool,
+    ) -> ChunkDrawList {
+        let mut draw_list = ChunkDrawList::default();
+        for region in regions.iter().filter(|region| is_visible(&region.bounds)) {
+            draw_list.commands.push(DrawElementsIndirectCommand {
+                count: region.index_count,
+                instance_count: 1,
+                first_index: region.first_index,
+                base_vertex: region.base_vertex,
+                base_instance: 0,
+            });
+            // the level of detail is only read to tint the chunks
+            draw_list.origins.push([
+                region.origin.x,
+                region.origin.y,
+                region.origin.z,
+                region.lod as f32,
+            ]);
+        }
+        draw_list
+    }
+
+    /// Draws `draw_list` with one indirect multidraw, `shader` has to be bound and offset its
+    /// vertices by the chunk origins while `batched` is set
+    pub fn submit(&self, shader: &Shader, draw_list: &ChunkDrawList) {
+        let ChunkDrawList { commands, origins } = draw_list;
+        if commands.is_empty() {
+            return;
+        }
+        shader.set_uniform_1i("batched", 1);
+        unsafe {
+            gl::BindBuffer(gl::DRAW_INDIRECT_BUFFER, self.indirect_buffer);
+            gl::BufferData(
+                gl::DRAW_INDIRECT_BUFFER,
+                (commands.len() * size_of::<DrawElementsIndirectCommand>()) as isize,
+                commands.as_ptr() as *const _,
+                gl::STREAM_DRAW,
+            );
+            gl::BindBuffer(gl::SHADER_STORAGE_BUFFER, self.origin_buffer);
+            gl::BufferData(
+                gl::SHADER_STORAGE_BUFFER,
+                (origins.len() * size_of::<[f32; 4]>()) as isize,
+                origins.as_ptr() as *const _,
+                gl::STREAM_DRAW,
+            );
+            gl::BindBufferBase(
+                gl::SHADER_STORAGE_BUFFER,
+                CHUNK_ORIGIN_BINDING,
+                self.origin_buffer,
+            );
+            gl::BindVertexArray(self.vertex_array);
+            gl::MultiDrawElementsIndirect(
+                gl::TRIANGLES,
+                gl::UNSIGNED_INT,
+                std::ptr::null(),
+                commands.len() as i32,
+                0,
+            );
+            Profiler::count_draw_call();
+            gl_debug::check_errors("ChunkBatch::render");
+            gl::BindVertexArray(0);
+            gl::BindBuffer(gl::DRAW_INDIRECT_BUFFER, 0);
+            gl::BindBuffer(gl::SHADER_STORAGE_BUFFER, 0);
+        }
+        shader.set_uniform_1i("batched", 0);
+    }
+
+    /// Number of chunks in the batch
+    pub fn get_chunk_count(&self) -> usize {
+        self.regions.len()
+    }
+
+    /// Grows the buffers to hold at least `vertices` vertices and `indices` indices, keeping
+    /// what they already contain
+    fn reserve(&mut self, vertices: usize, indices: usize) {
+        if vertices > self.vertex_capacity {
+            let capacity = vertices.max(self.vertex_capacity * 2);
+            self.vertex_buffer = ChunkBatch::<T>::grow_buffer(
+                self.vertex_buffer,
+                self.vertex_count * size_of::<T>(),
+                capacity * size_of::<T>(),
+            );
+            self.vertex_capacity = capacity;
+            unsafe {
+                gl::BindVertexArray(self.vertex_array);
+                gl::BindBuffer(gl::ARRAY_BUFFER, self.vertex_buffer);
+                DynamicVertexArray::<T>::set_attribute_pointers::<T>(0, 0);
+                gl::BindVertexArray(0);
+                gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+            }
+        }
+        if indices > self.index_capacity {
+            let capacity = indices.max(self.index_capacity * 2);
+            self.index_buffer = ChunkBatch::<T>::grow_buffer(
+                self.index_buffer,
+                self.index_count * size_of::<u32>(),
+                capacity * size_of::<u32>(),
+            );
+            self.index_capacity = capacity;
+            unsafe {
+                gl::BindVertexArray(self.vertex_array);
+                gl::BindBuffer(gl::ELEMENT_ARRAY_BUFFER, self.index_buffer);
+                gl::BindVertexArray(0);
+            }
+        }
+    }
+
+    /// Creates a buffer of `size` bytes holding the first `used` bytes of `buffer`, which is deleted
+    fn grow_buffer(buffer: GLuint, used: usize, size: usize) -> GLuint {
+        let mut grown = 0;
+        unsafe {
+            gl::GenBuffers(1, &mut grown);
+            gl::BindBuffer(gl::COPY_WRITE_BUFFER, grown);
+            gl::BufferData(
+                gl::COPY_WRITE_BUFFER,
+                size as isize,
+                std::ptr::null(),
+                gl::STATIC_DRAW,
+            );
+            if used > 0 {
+                gl::BindBuffer(gl::COPY_READ_BUFFER, buffer);
+                gl::CopyBufferSubData(
+                    gl::COPY_READ_BUFFER,
+                    gl::COPY_WRITE_BUFFER,
+                    0,
+                    0,
+                    used as isize,
+                );
+                gl::BindBuffer(gl::COPY_READ_BUFFER, 0);
+            }
+            gl::BindBuffer(gl::COPY_WRITE_BUFFER, 0);
+            if buffer != 0 {
+                gl::DeleteBuffers(1, &buffer);
+            }
+        }
+        grown
+    }
+}
+
+impl ChunkRecording {
+    /// Blocks until the workers recorded every draw, panics if one of them panicked
+    pub fn wait(self) -> ChunkDrawList {
+        let mut draw_list = ChunkDrawList::default();
+        for list in self.0.wait() {
+            draw_list.commands.extend(list.commands);
+            draw_list.origins.extend(list.origins);
+        }
+        draw_list
+    }
+}
+
+impl<T: VertexAttributes + Clone> Default for ChunkBatch<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: VertexAttributes> Drop for ChunkBatch<T> {
+    fn drop(&mut self) {
+        let buffers = [
+            self.vertex_buffer,
+            self.index_buffer,
+            self.indirect_buffer,
+            self.origin_buffer,
+        ];
+        unsafe {
+            gl::DeleteBuffers(buffers.len() as i32, buffers.as_ptr());
+            gl::DeleteVertexArrays(1, &self.vertex_array);
+        }
+    }
+}
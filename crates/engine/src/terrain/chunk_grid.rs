@@ -0,0 +1,196 @@
+use cgmath::Point3;
+
+use super::{ChunkCoord, ChunkGrid, LoadedChunk, CHUNK_SIZE, CHUNK_SIZE_FLOAT};
+
+/// Offsets of the six chunks sharing a face with a chunk
+const FACE_NEIGHBORS: [(i32, i32, i32); 6] = [
+    (-1, 0, 0),
+    (1, 0, 0),
+    (0, -1, 0),
+    (0, 1, 0),
+    (0, 0, -1),
+    (0, 0, 1),
+];
+
+impl ChunkCoord {
+    pub fn new(x: i32, y: i32, z: i32) -> Self {
+        Self { x, y, z }
+    }
+
+    /// Coordinate of the chunk containing the world `position`
+    pub fn from_world(position: Point3<f32>) -> Self {
+        Self {
+            x: (position.x / CHUNK_SIZE_FLOAT).floor() as i32,
+            y: (position.y / CHUNK_SIZE_FLOAT).floor() as i32,
+            z: (position.z / CHUNK_SIZE_FLOAT).floor() as i32,
+        }
+    }
+
+    /// Coordinate of the chunk containing the world block `position`
+    pub fn from_block(position: (i32, i32, i32)) -> Self {
+        let size = CHUNK_SIZE as i32;
+        Self {
+            x: position.0.div_euclid(size),
+            y: position.1.div_euclid(size),
+            z: position.2.div_euclid(size),
+        }
+    }
+
+    /// Coordinate of the chunk whose origin is at the world `position`, as returned by
+    /// `Chunk::get_position`
+    pub fn from_origin(position: Point3<f32>) -> Self {
+        Self {
+            x: (position.x / CHUNK_SIZE_FLOAT).round() as i32,
+            y: (position.y / CHUNK_SIZE_FLOAT).round() as i32,
+            z: (position.z / CHUNK_SIZE_FLOAT).round() as i32,
+        }
+    }
+
+    /// World position of the chunk's minimum corner
+    pub fn get_origin(&self) -> Point3<f32> {
+        Point3::new(
+            self.x as f32 * CHUNK_SIZE_FLOAT,
+            self.y as f32 * CHUNK_SIZE_FLOAT,
+            self.z as f32 * CHUNK_SIZE_FLOAT,
+        )
+    }
+
+    /// Level of detail the terrain loads the chunk with, the ring around the origin it lies in
+    pub fn get_lod(&self) -> usize {
+        self.x.unsigned_abs().max(self.z.unsigned_abs()) as usize
+    }
+
+    pub fn offset(&self, (x, y, z): (i32, i32, i32)) -> Self {
+        Self {
+            x: self.x + x,
+            y: self.y + y,
+            z: self.z + z,
+        }
+    }
+
+    /// Number of chunks between the two coordinates along the axis they are furthest apart on
+    pub fn distance(&self, other: &ChunkCoord) -> i32 {
+        (self.x - other.x)
+            .abs()
+            .max((self.y - other.y).abs())
+            .max((self.z - other.z).abs())
+    }
+}
+
+impl From<(i32, i32, i32)> for ChunkCoord {
+    fn from((x, y, z): (i32, i32, i32)) -> Self {
+        Self { x, y, z }
+    }
+}
+
+impl From<ChunkCoord> for (i32, i32, i32) {
+    fn from(coord: ChunkCoord) -> Self {
+        (coord.x, coord.y, coord.z)
+    }
+}
+
+impl ChunkGrid {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds `chunk` at its coordinate, returns false and keeps the grid as it is if a chunk is
+    /// already loaded there
+    pub fn insert(&mut self, chunk: LoadedChunk) -> bool {
+        if self.indices.contains_key(&chunk.coord) {
+            return false;
+        }
+        self.indices.insert(chunk.coord, self.chunks.len());
+        self.chunks.push(chunk);
+        true
+    }
+
+    /// Removes the chunk at `coord`, the last inserted chunk takes its place in the iteration
+    /// order
+    pub fn remove(&mut self, coord: &ChunkCoord) -> Option<LoadedChunk> {
+        let index = self.indices.remove(coord)?;
+        let chunk = self.chunks.swap_remove(index);
+        if let Some(moved) = self.chunks.get(index) {
+            self.indices.insert(moved.coord, index);
+        }
+        Some(chunk)
+    }
+
+    pub fn get(&self, coord: &ChunkCoord) -> Option<&LoadedChunk> {
+        self.indices.get(coord).map(|index| &self.chunks[*index])
+    }
+
+    pub fn get_mut(&mut self, coord: &ChunkCoord) -> Option<&mut LoadedChunk> {
+        self.indices
+            .get(coord)
+            .map(|index| &mut self.chunks[*index])
+    }
+
+    pub fn contains(&self, coord: &ChunkCoord) -> bool {
+        self.indices.contains_key(coord)
+    }
+
+    /// Loaded chunk containing the world `position`
+    pub fn get_at(&self, position: Point3<f32>) -> Option<&LoadedChunk> {
+        self.get(&ChunkCoord::from_world(position))
+    }
+
+    /// Loaded chunk `offset` chunks away from `coord`
+    pub fn get_neighbor(
+        &self,
+        coord: &ChunkCoord,
+        offset: (i32, i32, i32),
+    ) -> Option<&LoadedChunk> {
+        self.get(&coord.offset(offset))
+    }
+
+    /// Loaded chunks sharing a face with the chunk at `coord`
+    pub fn get_face_neighbors<'a>(
+        &'a self,
+        coord: &'a ChunkCoord,
+    ) -> impl Iterator<Item = &'a LoadedChunk> + 'a {
+        FACE_NEIGHBORS
+            .iter()
+            .filter_map(move |offset| self.get_neighbor(coord, *offset))
+    }
+
+    /// Loaded chunks at most `radius` chunks away from `center` along every axis. Looks up the
+    /// cells around `center` for small radii and filters the loaded chunks for large ones
+    pub fn iter_in_radius(
+        &self,
+        center: ChunkCoord,
+        radius: i32,
+    ) -> Box<dyn Iterator<Item = &LoadedChunk> + '_> {
+        let side = (2 * radius.max(0) + 1) as usize;
+        if side * side * side < self.chunks.len() {
+            let cells = (-radius..=radius).flat_map(move |x| {
+                (-radius..=radius)
+                    .flat_map(move |y| (-radius..=radius).map(move |z| center.offset((x, y, z))))
+            });
+            Box::new(cells.filter_map(|coord| self.get(&coord)))
+        } else {
+            Box::new(
+                self.chunks
+                    .iter()
+                    .filter(move |chunk| chunk.coord.distance(&center) <= radius),
+            )
+        }
+    }
+
+    /// Chunks in the order they were inserted
+    pub fn iter(&self) -> std::slice::Iter<'_, LoadedChunk> {
+        self.chunks.iter()
+    }
+
+    pub fn as_slice(&self) -> &[LoadedChunk] {
+        &self.chunks
+    }
+
+    pub fn len(&self) -> usize {
+        self.chunks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chunks.is_empty()
+    }
+}
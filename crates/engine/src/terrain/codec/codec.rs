@@ -0,0 +1,319 @@
+use std::{collections::BTreeMap, error::Error, fmt};
+
+use super::{
+    BlockRuns, ByteReader, ByteWriter, CodecError, PayloadKind, SparseDeltas, CODEC_VERSION,
+};
+
+impl PayloadKind {
+    fn from_u8(value: u8) -> Option<Self> {
+        match value {
+            1 => Some(PayloadKind::Blocks),
+            2 => Some(PayloadKind::SdfDeltas),
+            _ => None,
+        }
+    }
+
+    /// Starts an encoded chunk holding this kind of payload
+    pub fn write_header(self, writer: &mut ByteWriter) {
+        writer.write_u8(CODEC_VERSION);
+        writer.write_u8(self as u8);
+    }
+
+    /// Reads the header written by `write_header` and checks that it announces `self`
+    pub fn read_header(self, reader: &mut ByteReader) -> Result<(), CodecError> {
+        let version = reader.read_u8()?;
+        if version != CODEC_VERSION {
+            return Err(CodecError::UnsupportedVersion(version));
+        }
+        let kind = reader.read_u8()?;
+        match PayloadKind::from_u8(kind) {
+            Some(payload) if payload == self => Ok(()),
+            _ => Err(CodecError::UnexpectedPayload(kind)),
+        }
+    }
+}
+
+impl BlockRuns {
+    /// Compresses `blocks`, ordered x fastest, then z, then y
+    pub fn encode(blocks: impl IntoIterator<Item = u32>) -> Self {
+        let mut palette: Vec<u32> = Vec::new();
+        let mut runs: Vec<(u32, u32)> = Vec::new();
+        for block in blocks {
+            let index = match palette.iter().position(|entry| *entry == block) {
+                Some(index) => index as u32,
+                None => {
+                    palette.push(block);
+                    palette.len() as u32 - 1
+                }
+            };
+            match runs.last_mut() {
+                Some((last, length)) if *last == index => *length += 1,
+                _ => runs.push((index, 1)),
+            }
+        }
+        Self { palette, runs }
+    }
+
+    /// Block types in the order they were encoded
+    pub fn decode(&self) -> impl Iterator<Item = u32> + '_ {
+        self.runs.iter().flat_map(|(index, length)| {
+            std::iter::repeat(self.palette[*index as usize]).take(*length as usize)
+        })
+    }
+
+    /// Number of blocks
+    pub fn len(&self) -> usize {
+        self.runs.iter().map(|(_, length)| *length as usize).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.runs.is_empty()
+    }
+
+    pub fn get_palette(&self) -> &[u32] {
+        &self.palette
+    }
+
+    pub fn get_run_count(&self) -> usize {
+        self.runs.len()
+    }
+
+    pub fn write(&self, writer: &mut ByteWriter) {
+        writer.write_varint(self.palette.len() as u64);
+        for block in &self.palette {
+            writer.write_varint(*block as u64);
+        }
+        writer.write_varint(self.runs.len() as u64);
+        for (index, length) in &self.runs {
+            writer.write_varint(*index as u64);
+            writer.write_varint(*length as u64);
+        }
+    }
+
+    /// Reads runs written by `write` that cover exactly `length` blocks
+    pub fn read(reader: &mut ByteReader, length: usize) -> Result<Self, CodecError> {
+        let palette_length = reader.read_length()?;
+        let palette = (0..palette_length)
+            .map(|_| reader.read_u32())
+            .collect::<Result<Vec<_>, _>>()?;
+        let run_count = reader.read_length()?;
+        let mut runs = Vec::with_capacity(run_count);
+        let mut total = 0;
+        for _ in 0..run_count {
+            let index = reader.read_u32()?;
+            let run = reader.read_u32()?;
+            if index as usize >= palette.len() {
+                return Err(CodecError::Invalid("palette index out of range"));
+            }
+            total += run as usize;
+            if total > length {
+                return Err(CodecError::Invalid("runs longer than the chunk"));
+            }
+            runs.push((index, run));
+        }
+        if total != length {
+            return Err(CodecError::Invalid("runs shorter than the chunk"));
+        }
+        Ok(Self { palette, runs })
+    }
+}
+
+impl SparseDeltas {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the edited density of the sample at `index`
+    pub fn set(&mut self, index: u32, density: f32) {
+        self.values.insert(index, density);
+    }
+
+    /// Drops the edit of the sample at `index`, e.g. when it was edited back to its generated
+    /// density
+    pub fn remove(&mut self, index: u32) {
+        self.values.remove(&index);
+    }
+
+    pub fn get(&self, index: u32) -> Option<f32> {
+        self.values.get(&index).copied()
+    }
+
+    /// Edited samples ordered by their index
+    pub fn iter(&self) -> impl Iterator<Item = (u32, f32)> + '_ {
+        self.values
+            .iter()
+            .map(|(index, density)| (*index, *density))
+    }
+
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Writes the samples as gaps between their indices followed by their densities, nearby
+    /// edits take a byte for the index
+    pub fn write(&self, writer: &mut ByteWriter) {
+        writer.write_varint(self.values.len() as u64);
+        let mut previous = 0;
+        for (index, density) in &self.values {
+            writer.write_varint((index - previous) as u64);
+            writer.write_f32(*density);
+            previous = *index;
+        }
+    }
+
+    /// Reads edits written by `write` of a chunk with `sample_count` samples
+    pub fn read(reader: &mut ByteReader, sample_count: usize) -> Result<Self, CodecError> {
+        let count = reader.read_length()?;
+        let mut values = BTreeMap::new();
+        let mut index: u64 = 0;
+        for _ in 0..count {
+            index += reader.read_varint()?;
+            if index >= sample_count as u64 {
+                return Err(CodecError::Invalid("sample index out of range"));
+            }
+            values.insert(index as u32, reader.read_f32()?);
+        }
+        Ok(Self { values })
+    }
+}
+
+impl ByteWriter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn write_u8(&mut self, value: u8) {
+        self.bytes.push(value);
+    }
+
+    /// Writes `value` in 7 bit groups, least significant first, with the high bit set on all but
+    /// the last byte
+    pub fn write_varint(&mut self, mut value: u64) {
+        loop {
+            let byte = (value & 0x7f) as u8;
+            value >>= 7;
+            if value == 0 {
+                self.bytes.push(byte);
+                return;
+            }
+            self.bytes.push(byte | 0x80);
+        }
+    }
+
+    /// Writes `value` zigzag encoded, so small negative values stay short
+    pub fn write_i32(&mut self, value: i32) {
+        self.write_varint(((value << 1) ^ (value >> 31)) as u32 as u64);
+    }
+
+    pub fn write_f32(&mut self, value: f32) {
+        self.bytes.extend_from_slice(&value.to_le_bytes());
+    }
+
+    /// Writes the length of `bytes` followed by them
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        self.write_varint(bytes.len() as u64);
+        self.bytes.extend_from_slice(bytes);
+    }
+
+    pub fn write_string(&mut self, value: &str) {
+        self.write_bytes(value.as_bytes());
+    }
+
+    pub fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+impl<'a> ByteReader<'a> {
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, position: 0 }
+    }
+
+    pub fn read_u8(&mut self) -> Result<u8, CodecError> {
+        let byte = *self
+            .bytes
+            .get(self.position)
+            .ok_or(CodecError::UnexpectedEnd)?;
+        self.position += 1;
+        Ok(byte)
+    }
+
+    pub fn read_varint(&mut self) -> Result<u64, CodecError> {
+        let mut value = 0;
+        for shift in (0..64).step_by(7) {
+            let byte = self.read_u8()?;
+            value |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(CodecError::Invalid("varint longer than 64 bits"))
+    }
+
+    pub fn read_u32(&mut self) -> Result<u32, CodecError> {
+        u32::try_from(self.read_varint()?).map_err(|_| CodecError::Invalid("value exceeds 32 bits"))
+    }
+
+    pub fn read_i32(&mut self) -> Result<i32, CodecError> {
+        let value = self.read_u32()?;
+        Ok((value >> 1) as i32 ^ -((value & 1) as i32))
+    }
+
+    pub fn read_bytes(&mut self) -> Result<&'a [u8], CodecError> {
+        let length = self.read_length()?;
+        let bytes = &self.bytes[self.position..self.position + length];
+        self.position += length;
+        Ok(bytes)
+    }
+
+    pub fn read_string(&mut self) -> Result<String, CodecError> {
+        String::from_utf8(self.read_bytes()?.to_vec())
+            .map_err(|_| CodecError::Invalid("string is not utf-8"))
+    }
+
+    pub fn read_f32(&mut self) -> Result<f32, CodecError> {
+        let bytes = self
+            .bytes
+            .get(self.position..self.position + 4)
+            .ok_or(CodecError::UnexpectedEnd)?;
+        self.position += 4;
+        Ok(f32::from_le_bytes(bytes.try_into().unwrap()))
+    }
+
+    /// Number of bytes left to read
+    pub fn remaining(&self) -> usize {
+        self.bytes.len() - self.position
+    }
+
+    /// Reads the length of a list, which can not exceed the bytes left since every element takes
+    /// at least one
+    fn read_length(&mut self) -> Result<usize, CodecError> {
+        let length = self.read_varint()?;
+        if length > self.remaining() as u64 {
+            return Err(CodecError::UnexpectedEnd);
+        }
+        Ok(length as usize)
+    }
+}
+
+impl fmt::Display for CodecError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CodecError::UnexpectedEnd => write!(f, "Encoded chunk ends unexpectedly"),
+            CodecError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported chunk codec version {}", version)
+            }
+            CodecError::UnexpectedPayload(kind) => {
+                write!(f, "Unexpected chunk payload kind {}", kind)
+            }
+            CodecError::Invalid(reason) => write!(f, "Invalid encoded chunk: {}", reason),
+            CodecError::Unsupported => write!(f, "The chunk type can not be deserialized"),
+        }
+    }
+}
+
+impl Error for CodecError {}
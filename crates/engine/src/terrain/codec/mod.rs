@@ -0,0 +1,84 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs::File,
+    path::PathBuf,
+};
+
+mod codec;
+mod region;
+
+/// Version written in front of every encoded chunk
+pub const CODEC_VERSION: u8 = 1;
+/// Number of chunks along every axis of a region file
+pub const REGION_SIZE: i32 = 8;
+/// Region files are allocated in sectors of this many bytes, the index takes the first one
+pub const SECTOR_SIZE: usize = 4096;
+
+/// What an encoded chunk contains, stored after `CODEC_VERSION`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum PayloadKind {
+    /// `BlockRuns` of the block types followed by those of the resources
+    Blocks = 1,
+    /// `SparseDeltas` of the density samples edited since generation
+    SdfDeltas = 2,
+}
+
+#[derive(Debug)]
+pub enum CodecError {
+    /// The data ended in the middle of a value
+    UnexpectedEnd,
+    UnsupportedVersion(u8),
+    UnexpectedPayload(u8),
+    /// A value is out of range, e.g. a palette index past the palette or runs longer than the
+    /// chunk
+    Invalid(&'static str),
+    /// The chunk type has no serialized form and is regenerated from the seed instead
+    Unsupported,
+}
+
+/// Block types of a chunk as a palette of the distinct types and runs of palette indices, a
+/// 128³ chunk of air over stone shrinks to a handful of bytes. Blocks are ordered x fastest,
+/// then z, then y, so the runs follow the horizontal layers of the terrain
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlockRuns {
+    palette: Vec<u32>,
+    /// Palette index and length of every run
+    runs: Vec<(u32, u32)>,
+}
+
+/// Density samples of a signed distance field chunk that differ from the generated ones, keyed by
+/// their index in the chunk's sample grid
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SparseDeltas {
+    values: BTreeMap<u32, f32>,
+}
+
+/// Appends varints and fixed size values to a byte buffer
+#[derive(Default)]
+pub struct ByteWriter {
+    bytes: Vec<u8>,
+}
+
+pub struct ByteReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+/// Encoded chunks of `REGION_SIZE`³ neighbouring chunks in a single file, similar to Anvil's
+/// region files. The first sector holds the index with the first sector and byte length of every
+/// chunk, chunks take as many whole sectors as they need and are moved to the end of the file
+/// when they outgrow them
+pub struct RegionFile {
+    file: File,
+    /// First sector and length in bytes per chunk in the region, 0 sectors for missing chunks
+    index: Vec<(u32, u32)>,
+    /// Whether each sector of the file is taken by the index or a chunk
+    used: Vec<bool>,
+}
+
+/// Region files of a world directory, opened when a chunk in them is first loaded or saved
+pub struct RegionStore {
+    directory: PathBuf,
+    regions: HashMap<(i32, i32, i32), RegionFile>,
+}
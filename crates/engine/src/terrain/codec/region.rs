@@ -0,0 +1,232 @@
+use std::{
+    collections::HashMap,
+    error::Error,
+    fs::{self, OpenOptions},
+    io::{Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+use crate::terrain::ChunkCoord;
+
+use super::{RegionFile, RegionStore, REGION_SIZE, SECTOR_SIZE};
+
+/// Number of chunks in a region
+const REGION_CHUNKS: usize = (REGION_SIZE * REGION_SIZE * REGION_SIZE) as usize;
+/// Bytes of an index entry, the first sector and the length of a chunk
+const INDEX_ENTRY_SIZE: usize = 8;
+
+impl RegionFile {
+    /// Opens the region file at `path`, creating an empty one if it does not exist
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Box<dyn Error>> {
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(path)?;
+        let mut index = vec![(0, 0); REGION_CHUNKS];
+        if file.metadata()?.len() < SECTOR_SIZE as u64 {
+            file.set_len(SECTOR_SIZE as u64)?;
+        } else {
+            let mut header = vec![0u8; REGION_CHUNKS * INDEX_ENTRY_SIZE];
+            file.read_exact(&mut header)?;
+            for (entry, bytes) in index.iter_mut().zip(header.chunks(INDEX_ENTRY_SIZE)) {
+                *entry = (
+                    u32::from_le_bytes(bytes[0..4].try_into().unwrap()),
+                    u32::from_le_bytes(bytes[4..8].try_into().unwrap()),
+                );
+            }
+        }
+        let sectors = file.metadata()?.len().div_ceil(SECTOR_SIZE as u64) as usize;
+        let mut used = vec![false; sectors];
+        used[0] = true;
+        for &(first, length) in &index {
+            if first == 0 {
+                continue;
+            }
+            let end = first as usize + RegionFile::get_sector_count(length);
+            if end > used.len() {
+                return Err(format!("Region chunk at sector {} exceeds the file", first).into());
+            }
+            used[first as usize..end].fill(true);
+        }
+        Ok(Self { file, index, used })
+    }
+
+    /// Region containing the chunk at `coord`
+    pub fn get_region(coord: &ChunkCoord) -> (i32, i32, i32) {
+        (
+            coord.x.div_euclid(REGION_SIZE),
+            coord.y.div_euclid(REGION_SIZE),
+            coord.z.div_euclid(REGION_SIZE),
+        )
+    }
+
+    /// File name of the region, e.g. "r.0.0.-1.region"
+    pub fn get_file_name(region: (i32, i32, i32)) -> String {
+        format!("r.{}.{}.{}.region", region.0, region.1, region.2)
+    }
+
+    /// Encoded chunk at `coord`, None if the region has none. `coord` has to lie in this region
+    pub fn read(&mut self, coord: &ChunkCoord) -> Result<Option<Vec<u8>>, Box<dyn Error>> {
+        let (first, length) = self.index[RegionFile::get_index(coord)];
+        if first == 0 {
+            return Ok(None);
+        }
+        let mut data = vec![0u8; length as usize];
+        self.file
+            .seek(SeekFrom::Start(first as u64 * SECTOR_SIZE as u64))?;
+        self.file.read_exact(&mut data)?;
+        Ok(Some(data))
+    }
+
+    /// Stores the encoded chunk at `coord`, in its old sectors if it still fits into them
+    pub fn write(&mut self, coord: &ChunkCoord, data: &[u8]) -> Result<(), Box<dyn Error>> {
+        let slot = RegionFile::get_index(coord);
+        let sectors = RegionFile::get_sector_count(data.len() as u32);
+        let (first, length) = self.index[slot];
+        let first = if first != 0 && RegionFile::get_sector_count(length) >= sectors {
+            let old = first as usize + RegionFile::get_sector_count(length);
+            self.used[first as usize + sectors..old].fill(false);
+            first as usize
+        } else {
+            if first != 0 {
+                let old = first as usize + RegionFile::get_sector_count(length);
+                self.used[first as usize..old].fill(false);
+            }
+            self.allocate(sectors)
+        };
+        self.used[first..first + sectors].fill(true);
+        self.file
+            .seek(SeekFrom::Start((first * SECTOR_SIZE) as u64))?;
+        self.file.write_all(data)?;
+        // pad to whole sectors so the next chunk appended at the end starts on a sector
+        let padding = sectors * SECTOR_SIZE - data.len();
+        self.file.write_all(&vec![0u8; padding])?;
+        self.write_index(slot, (first as u32, data.len() as u32))
+    }
+
+    /// Frees the sectors of the chunk at `coord`, the file is not shrunk
+    pub fn remove(&mut self, coord: &ChunkCoord) -> Result<(), Box<dyn Error>> {
+        let slot = RegionFile::get_index(coord);
+        let (first, length) = self.index[slot];
+        if first == 0 {
+            return Ok(());
+        }
+        let end = first as usize + RegionFile::get_sector_count(length);
+        self.used[first as usize..end].fill(false);
+        self.write_index(slot, (0, 0))
+    }
+
+    pub fn contains(&self, coord: &ChunkCoord) -> bool {
+        self.index[RegionFile::get_index(coord)].0 != 0
+    }
+
+    /// Number of chunks stored in the region
+    pub fn get_chunk_count(&self) -> usize {
+        self.index.iter().filter(|(first, _)| *first != 0).count()
+    }
+
+    pub fn flush(&mut self) -> Result<(), Box<dyn Error>> {
+        self.file.sync_data()?;
+        Ok(())
+    }
+
+    /// First sector of a free run of `sectors` sectors, past the end of the file if there is none
+    fn allocate(&mut self, sectors: usize) -> usize {
+        let mut start = 1;
+        while start + sectors <= self.used.len() {
+            match self.used[start..start + sectors]
+                .iter()
+                .position(|used| *used)
+            {
+                Some(offset) => start += offset + 1,
+                None => return start,
+            }
+        }
+        let first = self.used.len();
+        self.used.resize(first + sectors, false);
+        first
+    }
+
+    fn write_index(&mut self, slot: usize, entry: (u32, u32)) -> Result<(), Box<dyn Error>> {
+        self.index[slot] = entry;
+        let mut bytes = [0u8; INDEX_ENTRY_SIZE];
+        bytes[0..4].copy_from_slice(&entry.0.to_le_bytes());
+        bytes[4..8].copy_from_slice(&entry.1.to_le_bytes());
+        self.file
+            .seek(SeekFrom::Start((slot * INDEX_ENTRY_SIZE) as u64))?;
+        self.file.write_all(&bytes)?;
+        Ok(())
+    }
+
+    /// Slot of the chunk at `coord` in the index, x varies fastest
+    fn get_index(coord: &ChunkCoord) -> usize {
+        let (x, y, z) = (
+            coord.x.rem_euclid(REGION_SIZE),
+            coord.y.rem_euclid(REGION_SIZE),
+            coord.z.rem_euclid(REGION_SIZE),
+        );
+        (x + REGION_SIZE * (y + REGION_SIZE * z)) as usize
+    }
+
+    fn get_sector_count(length: u32) -> usize {
+        (length as usize).div_ceil(SECTOR_SIZE).max(1)
+    }
+}
+
+impl RegionStore {
+    /// Stores the region files in `directory`, which is created with the first saved chunk
+    pub fn new<P: AsRef<Path>>(directory: P) -> Self {
+        Self {
+            directory: directory.as_ref().to_path_buf(),
+            regions: HashMap::new(),
+        }
+    }
+
+    /// Encoded chunk at `coord`, None if it was never saved
+    pub fn load(&mut self, coord: &ChunkCoord) -> Result<Option<Vec<u8>>, Box<dyn Error>> {
+        let region = RegionFile::get_region(coord);
+        if !self.regions.contains_key(&region) && !self.get_path(region).exists() {
+            return Ok(None);
+        }
+        self.get_region_file(region)?.read(coord)
+    }
+
+    pub fn save(&mut self, coord: &ChunkCoord, data: &[u8]) -> Result<(), Box<dyn Error>> {
+        fs::create_dir_all(&self.directory)?;
+        self.get_region_file(RegionFile::get_region(coord))?
+            .write(coord, data)
+    }
+
+    /// Writes the open region files to disk
+    pub fn flush(&mut self) -> Result<(), Box<dyn Error>> {
+        for region in self.regions.values_mut() {
+            region.flush()?;
+        }
+        Ok(())
+    }
+
+    fn get_path(&self, region: (i32, i32, i32)) -> PathBuf {
+        self.directory.join(RegionFile::get_file_name(region))
+    }
+
+    fn get_region_file(
+        &mut self,
+        region: (i32, i32, i32),
+    ) -> Result<&mut RegionFile, Box<dyn Error>> {
+        if !self.regions.contains_key(&region) {
+            let file = RegionFile::open(self.get_path(region))?;
+            self.regions.insert(region, file);
+        }
+        Ok(self.regions.get_mut(&region).unwrap())
+    }
+}
+
+impl Drop for RegionStore {
+    fn drop(&mut self) {
+        if let Err(err) = self.flush() {
+            log::error!(target: "terrain", "Failed to flush the region files: {}", err);
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use std::{collections::BTreeMap, path::Path};
+
+use cgmath::Point3;
+
+use crate::{
+    core::save_worker::SaveWorker,
+    terrain::{structures::StructureBlock, ChunkCoord},
+};
+
+use super::{ChunkDelta, ChunkDeltas, PlacedBlueprint};
+
+impl ChunkDeltas {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads the deltas saved with `save` from the RON file at `path`
+    pub fn load(path: &str) -> Result<ChunkDeltas, Box<dyn std::error::Error>> {
+        let source = std::fs::read_to_string(path)?;
+        Ok(ron::from_str(&source)?)
+    }
+
+    /// Writes the deltas to `path` as RON
+    pub fn save(&self, path: &str) -> Result<(), Box<dyn std::error::Error>> {
+        std::fs::write(path, self.to_ron()?)?;
+        Ok(())
+    }
+
+    /// Queues the deltas to be written to `path` as RON by the `SaveWorker` without blocking
+    pub fn save_in_background(&self, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+        SaveWorker::write(path, self.to_ron()?.into_bytes());
+        Ok(())
+    }
+
+    fn to_ron(&self) -> Result<String, ron::Error> {
+        ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+    }
+
+    /// Changes to the chunk at the world position `chunk_position`, if it was changed
+    pub fn get(&self, chunk_position: Point3<f32>) -> Option<&ChunkDelta> {
+        self.chunks.get(&ChunkDeltas::get_key(chunk_position))
+    }
+
+    /// Records that the prop `id` of the chunk at `chunk_position` was removed, returns false
+    /// if it was already removed
+    pub fn remove_prop(&mut self, chunk_position: Point3<f32>, id: u32) -> bool {
+        self.chunks
+            .entry(ChunkDeltas::get_key(chunk_position))
+            .or_default()
+            .removed_props
+            .insert(id)
+    }
+
+    pub fn is_prop_removed(&self, chunk_position: Point3<f32>, id: u32) -> bool {
+        self.get(chunk_position)
+            .is_some_and(|delta| delta.removed_props.contains(&id))
+    }
+
+    /// Records blocks the player set at world block positions, returns false if none of them
+    /// changed the saved deltas
+    pub fn set_blocks(&mut self, blocks: &[StructureBlock]) -> bool {
+        let mut changed = false;
+        for &(position, block) in blocks {
+            let key = ChunkCoord::from_block(position).into();
+            let previous = self
+                .chunks
+                .entry(key)
+                .or_default()
+                .blocks
+                .insert(position, block);
+            changed |= previous != Some(block);
+        }
+        changed
+    }
+
+    /// Records that the blueprint `name` was placed with its anchor at the world block `position`
+    pub fn add_blueprint(&mut self, name: &str, position: (i32, i32, i32)) {
+        self.blueprints.push(PlacedBlueprint {
+            name: name.to_string(),
+            position,
+        });
+    }
+
+    /// Placed blueprints in the order they were placed
+    pub fn get_blueprints(&self) -> &[PlacedBlueprint] {
+        &self.blueprints
+    }
+
+    pub fn get_generator_version(&self) -> u32 {
+        self.generator_version
+    }
+
+    pub fn set_generator_version(&mut self, version: u32) {
+        self.generator_version = version;
+    }
+
+    /// Changes per chunk by the chunk's grid position
+    pub fn get_chunks_mut(&mut self) -> impl Iterator<Item = (&(i32, i32, i32), &mut ChunkDelta)> {
+        self.chunks.iter_mut()
+    }
+
+    /// Chunks pinned to an older generator version by their grid position
+    pub fn get_pinned_chunks(&self) -> BTreeMap<(i32, i32, i32), u32> {
+        self.chunks
+            .iter()
+            .filter_map(|(key, delta)| delta.generator_version.map(|version| (*key, version)))
+            .collect()
+    }
+
+    /// Number of chunks with changes
+    pub fn get_chunk_count(&self) -> usize {
+        self.chunks.len()
+    }
+
+    fn get_key(chunk_position: Point3<f32>) -> (i32, i32, i32) {
+        ChunkCoord::from_origin(chunk_position).into()
+    }
+}
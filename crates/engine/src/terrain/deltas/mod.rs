@@ -0,0 +1,57 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Deserialize, Serialize};
+
+use super::world_gen::GENERATOR_VERSION;
+
+mod deltas;
+
+/// Changes the player made to the generated chunks, saved next to the world so regenerating a
+/// chunk from its seed does not undo them
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChunkDeltas {
+    /// Generator version the world was saved with, saves from before it was recorded come from
+    /// the first one
+    #[serde(default = "first_generator_version")]
+    generator_version: u32,
+    /// Changes per chunk, keyed by the chunk's grid position
+    chunks: BTreeMap<(i32, i32, i32), ChunkDelta>,
+    /// Blueprints placed into the world in the order they were placed, applied to every chunk
+    /// they overlap as it is uploaded
+    blueprints: Vec<PlacedBlueprint>,
+}
+
+/// Blueprint from the `BlueprintLibrary` placed with its anchor at a world block position
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PlacedBlueprint {
+    pub name: String,
+    pub position: (i32, i32, i32),
+}
+
+/// Changes to a single chunk
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ChunkDelta {
+    /// Scattered props that were removed, by their index in the chunk's scattered instances
+    pub removed_props: BTreeSet<u32>,
+    /// Older generator version the chunk keeps being generated with, see `WorldMigration`
+    pub generator_version: Option<u32>,
+    /// Blocks dug or placed by the player by their world block position, 0 for removed ones.
+    /// Removing a block also removes its ore, so mined veins stay mined
+    pub blocks: BTreeMap<(i32, i32, i32), u32>,
+}
+
+fn first_generator_version() -> u32 {
+    1
+}
+
+impl Default for ChunkDeltas {
+    fn default() -> Self {
+        Self {
+            generator_version: GENERATOR_VERSION,
+            chunks: BTreeMap::new(),
+            blueprints: Vec::new(),
+        }
+    }
+}
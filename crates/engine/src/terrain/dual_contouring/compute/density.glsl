@@ -0,0 +1,85 @@
+#version 460 core
+
+layout (local_size_x = 8, local_size_y = 8, local_size_z = 8) in;
+
+// carved surface height and cave density of every column, indexed by x * size + z
+layout (std430, binding = 0) readonly buffer Columns {
+    vec2 columns[];
+};
+layout (std430, binding = 1) readonly buffer Permutation {
+    uint permutation[];
+};
+layout (std430, binding = 2) writeonly buffer Density {
+    float density[];
+};
+
+uniform uint size;
+uniform uint scaleFactor;
+uniform float caveFrequency;
+uniform float caveThreshold;
+// the cave noise origin is split into a lattice cell and an offset inside of it to keep
+// the precision that f32 lacks for the large offsets used by the cpu generator
+uniform uvec3 noiseCell;
+uniform vec3 noiseOffset;
+
+const vec3 GRADIENTS[8] = vec3[](
+    vec3(-1.0, -1.0, -1.0),
+    vec3(-1.0, -1.0, 1.0),
+    vec3(-1.0, 1.0, -1.0),
+    vec3(-1.0, 1.0, 1.0),
+    vec3(1.0, -1.0, -1.0),
+    vec3(1.0, -1.0, 1.0),
+    vec3(1.0, 1.0, -1.0),
+    vec3(1.0, 1.0, 1.0)
+);
+
+uint hash(uvec3 cell) {
+    return permutation[cell.z + permutation[cell.y + permutation[cell.x]]];
+}
+
+float contribution(uvec3 cell, vec3 offset) {
+    return dot(GRADIENTS[hash(cell) % 8], offset);
+}
+
+// same perlin noise as the cpu generator, see libnoise's noise3d
+float perlin(vec3 point) {
+    vec3 lattice = floor(point);
+    vec3 d = point - lattice;
+    vec3 s = d * d * (3.0 - 2.0 * d);
+    uvec3 cell = (noiseCell + uvec3(ivec3(lattice))) % 256;
+    float n000 = contribution(cell, d);
+    float n001 = contribution(cell + uvec3(0, 0, 1), d - vec3(0.0, 0.0, 1.0));
+    float n010 = contribution(cell + uvec3(0, 1, 0), d - vec3(0.0, 1.0, 0.0));
+    float n011 = contribution(cell + uvec3(0, 1, 1), d - vec3(0.0, 1.0, 1.0));
+    float n100 = contribution(cell + uvec3(1, 0, 0), d - vec3(1.0, 0.0, 0.0));
+    float n101 = contribution(cell + uvec3(1, 0, 1), d - vec3(1.0, 0.0, 1.0));
+    float n110 = contribution(cell + uvec3(1, 1, 0), d - vec3(1.0, 1.0, 0.0));
+    float n111 = contribution(cell + uvec3(1, 1, 1), d - vec3(1.0, 1.0, 1.0));
+    float xn00 = mix(n000, n100, s.x);
+    float xn01 = mix(n001, n101, s.x);
+    float xn10 = mix(n010, n110, s.x);
+    float xn11 = mix(n011, n111, s.x);
+    float yn0 = mix(xn00, xn10, s.y);
+    float yn1 = mix(xn01, xn11, s.y);
+    return mix(yn0, yn1, s.z) * 0.6666666666666666;
+}
+
+void main() {
+    uvec3 id = gl_GlobalInvocationID;
+    if (id.x >= size || id.y >= size || id.z >= size) {
+        return;
+    }
+    vec3 local = vec3(id * scaleFactor);
+    vec2 column = columns[id.x * size + id.z];
+
+    float heightIso = 1.0 - ((column.x + 1.0) / (1.0 + local.y));
+    float maxCaveIso = 1.0 - (caveThreshold - column.y * 2.0);
+    // caves can't reach above the terrain surface, skip the noise there
+    if (heightIso >= maxCaveIso) {
+        density[id.x + id.y * size + id.z * size * size] = heightIso;
+        return;
+    }
+    float cave = (1.0 + perlin(noiseOffset + local * caveFrequency)) / 2.0;
+    float caveIso = cave - (caveThreshold - column.y * 2.0);
+    density[id.x + id.y * size + id.z * size * size] = max(heightIso, caveIso);
+}
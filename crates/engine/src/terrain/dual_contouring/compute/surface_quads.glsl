@@ -0,0 +1,99 @@
+#version 460 core
+
+layout (local_size_x = 8, local_size_y = 8, local_size_z = 8) in;
+
+struct SurfaceVertex {
+    vec4 position;
+    vec4 normal;
+};
+
+layout (std430, binding = 2) readonly buffer Density {
+    float density[];
+};
+layout (std430, binding = 3) readonly buffer StrideToIndex {
+    uint strideToIndex[];
+};
+layout (std430, binding = 4) readonly buffer Vertices {
+    SurfaceVertex vertices[];
+};
+layout (std430, binding = 5) writeonly buffer Indices {
+    uint indices[];
+};
+layout (std430, binding = 6) buffer Counters {
+    uint vertexCount;
+    uint indexCount;
+};
+
+uniform uint size;
+uniform uint maxIndices;
+
+const uint NULL_VERTEX = 0xFFFFFFFFu;
+
+// emits the quad between the four cells around the edge from p1 to p2 if the surface crosses it
+void makeQuad(uint p1, uint p2, uint axisB, uint axisC) {
+    bool negative1 = density[p1] < 0.0;
+    bool negative2 = density[p2] < 0.0;
+    if (negative1 == negative2) {
+        return;
+    }
+    bool negativeFace = !negative1;
+
+    uint v1 = strideToIndex[p1];
+    uint v2 = strideToIndex[p1 - axisB];
+    uint v3 = strideToIndex[p1 - axisC];
+    uint v4 = strideToIndex[p1 - axisB - axisC];
+    if (v1 == NULL_VERTEX || v2 == NULL_VERTEX || v3 == NULL_VERTEX || v4 == NULL_VERTEX) {
+        return;
+    }
+    vec3 pos1 = vertices[v1].position.xyz;
+    vec3 pos2 = vertices[v2].position.xyz;
+    vec3 pos3 = vertices[v3].position.xyz;
+    vec3 pos4 = vertices[v4].position.xyz;
+
+    // split the quad along the shorter diagonal
+    uint quad[6];
+    vec3 diagonal1 = pos1 - pos4;
+    vec3 diagonal2 = pos2 - pos3;
+    if (dot(diagonal1, diagonal1) < dot(diagonal2, diagonal2)) {
+        if (negativeFace) {
+            quad = uint[](v1, v4, v2, v1, v3, v4);
+        } else {
+            quad = uint[](v1, v2, v4, v1, v4, v3);
+        }
+    } else if (negativeFace) {
+        quad = uint[](v2, v3, v4, v2, v1, v3);
+    } else {
+        quad = uint[](v2, v4, v3, v2, v3, v1);
+    }
+
+    uint index = atomicAdd(indexCount, 6);
+    if (index + 6 > maxIndices) {
+        return;
+    }
+    for (uint i = 0; i < 6; i++) {
+        indices[index + i] = quad[i];
+    }
+}
+
+void main() {
+    uvec3 id = gl_GlobalInvocationID;
+    if (id.x >= size - 1 || id.y >= size - 1 || id.z >= size - 1) {
+        return;
+    }
+    uint stride = id.x + id.y * size + id.z * size * size;
+    if (strideToIndex[stride] == NULL_VERTEX) {
+        return;
+    }
+    uint strideX = 1;
+    uint strideY = size;
+    uint strideZ = size * size;
+    if (id.y != 0 && id.z != 0 && id.x != size - 2) {
+        makeQuad(stride, stride + strideX, strideY, strideZ);
+    }
+    if (id.x != 0 && id.z != 0 && id.y != size - 2) {
+        makeQuad(stride, stride + strideY, strideZ, strideX);
+    }
+    if (id.x != 0 && id.y != 0 && id.z != size - 2) {
+        makeQuad(stride, stride + strideZ, strideX, strideY);
+    }
+}
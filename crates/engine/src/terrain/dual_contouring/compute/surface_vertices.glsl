@@ -0,0 +1,115 @@
+#version 460 core
+
+layout (local_size_x = 8, local_size_y = 8, local_size_z = 8) in;
+
+struct SurfaceVertex {
+    // w holds the curvature of the density field at the vertex
+    vec4 position;
+    vec4 normal;
+};
+
+layout (std430, binding = 2) readonly buffer Density {
+    float density[];
+};
+layout (std430, binding = 3) writeonly buffer StrideToIndex {
+    uint strideToIndex[];
+};
+layout (std430, binding = 4) writeonly buffer Vertices {
+    SurfaceVertex vertices[];
+};
+layout (std430, binding = 6) buffer Counters {
+    uint vertexCount;
+    uint indexCount;
+};
+
+uniform uint size;
+uniform uint maxVertices;
+
+const uint NULL_VERTEX = 0xFFFFFFFFu;
+
+const uvec2 EDGES[12] = uvec2[](
+    uvec2(0, 1), uvec2(0, 2), uvec2(0, 4),
+    uvec2(1, 3), uvec2(1, 5), uvec2(2, 3),
+    uvec2(2, 6), uvec2(3, 7), uvec2(4, 5),
+    uvec2(4, 6), uvec2(5, 7), uvec2(6, 7)
+);
+
+float at(uint x, uint y, uint z) {
+    return density[x + y * size + z * size * size];
+}
+
+vec3 corner(uint i) {
+    return vec3(float(i & 1u), float((i >> 1) & 1u), float((i >> 2) & 1u));
+}
+
+// gradient of the trilinear interpolation of the corner values at s, same as fast-surface-nets
+vec3 gradient(float d[8], vec3 s) {
+    vec3 d00 = vec3(d[1], d[2], d[4]) - vec3(d[0], d[0], d[0]);
+    vec3 d10 = vec3(d[5], d[3], d[6]) - vec3(d[4], d[1], d[2]);
+    vec3 d01 = vec3(d[3], d[6], d[5]) - vec3(d[2], d[4], d[1]);
+    vec3 d11 = vec3(d[7], d[7], d[7]) - vec3(d[6], d[5], d[3]);
+    vec3 n = vec3(1.0) - s;
+    return n.yzx * n.zxy * d00 + n.yzx * s.zxy * d10 + s.yzx * n.zxy * d01 + s.yzx * s.zxy * d11;
+}
+
+// laplacian of the density field divided by the length of its gradient
+float curvature(vec3 position) {
+    uvec3 p = uvec3(clamp(round(position), vec3(1.0), vec3(float(size - 2))));
+    float center = at(p.x, p.y, p.z);
+    float left = at(p.x - 1, p.y, p.z);
+    float right = at(p.x + 1, p.y, p.z);
+    float down = at(p.x, p.y - 1, p.z);
+    float up = at(p.x, p.y + 1, p.z);
+    float back = at(p.x, p.y, p.z - 1);
+    float front = at(p.x, p.y, p.z + 1);
+    float laplacian = left + right + down + up + back + front - 6.0 * center;
+    float g = length(vec3(right - left, up - down, front - back)) / 2.0;
+    return g > 1.1920929e-7 ? laplacian / g : 0.0;
+}
+
+void main() {
+    uvec3 id = gl_GlobalInvocationID;
+    if (id.x >= size || id.y >= size || id.z >= size) {
+        return;
+    }
+    uint stride = id.x + id.y * size + id.z * size * size;
+    if (id.x >= size - 1 || id.y >= size - 1 || id.z >= size - 1) {
+        strideToIndex[stride] = NULL_VERTEX;
+        return;
+    }
+
+    float d[8];
+    uint negative = 0;
+    for (uint i = 0; i < 8; i++) {
+        d[i] = at(id.x + (i & 1u), id.y + ((i >> 1) & 1u), id.z + ((i >> 2) & 1u));
+        if (d[i] < 0.0) {
+            negative++;
+        }
+    }
+    if (negative == 0 || negative == 8) {
+        strideToIndex[stride] = NULL_VERTEX;
+        return;
+    }
+
+    vec3 sum = vec3(0.0);
+    uint count = 0;
+    for (uint i = 0; i < 12; i++) {
+        float d1 = d[EDGES[i].x];
+        float d2 = d[EDGES[i].y];
+        if ((d1 < 0.0) != (d2 < 0.0)) {
+            float t = d1 / (d1 - d2);
+            sum += (1.0 - t) * corner(EDGES[i].x) + t * corner(EDGES[i].y);
+            count++;
+        }
+    }
+    vec3 s = sum / float(count);
+    vec3 position = vec3(id) + s;
+
+    uint index = atomicAdd(vertexCount, 1);
+    if (index >= maxVertices) {
+        strideToIndex[stride] = NULL_VERTEX;
+        return;
+    }
+    strideToIndex[stride] = index;
+    vertices[index] = SurfaceVertex(vec4(position, curvature(position)), vec4(gradient(d, s), 0.0));
+}
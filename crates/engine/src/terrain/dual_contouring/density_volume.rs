@@ -0,0 +1,62 @@
+use fast_surface_nets::{
+    ndshape::{AbstractShape, RuntimeShape},
+    surface_nets, SurfaceNetsBuffer,
+};
+
+use super::DensityVolume;
+
+impl DensityVolume {
+    /// Samples `density` at every point of a `size`³ grid
+    pub fn new<F: Fn(u32, u32, u32) -> f32>(size: u32, density: F) -> Self {
+        let shape = RuntimeShape::<u32, 3>::new([size, size, size]);
+        let values = (0..size * size * size)
+            .map(|i| {
+                let [x, y, z] = shape.delinearize(i);
+                density(x, y, z)
+            })
+            .collect();
+        Self {
+            shape,
+            size,
+            values,
+        }
+    }
+
+    pub fn get(&self, x: u32, y: u32, z: u32) -> f32 {
+        self.values[self.shape.linearize([x, y, z]) as usize]
+    }
+
+    pub fn surface_nets(&self) -> SurfaceNetsBuffer {
+        let mut buffer = SurfaceNetsBuffer::default();
+        surface_nets(
+            &self.values,
+            &self.shape,
+            [0; 3],
+            [self.size - 1; 3],
+            &mut buffer,
+        );
+        buffer
+    }
+
+    /// Approximates the curvature of the iso surface at a mesh vertex as the
+    /// laplacian of the density field divided by the length of its gradient
+    pub fn get_curvature_at(&self, vertex: [f32; 3]) -> f32 {
+        let size = self.size;
+        let x = (vertex[0].round() as u32).clamp(1, size - 2);
+        let y = (vertex[1].round() as u32).clamp(1, size - 2);
+        let z = (vertex[2].round() as u32).clamp(1, size - 2);
+
+        let center = self.get(x, y, z);
+        let (left, right) = (self.get(x - 1, y, z), self.get(x + 1, y, z));
+        let (down, up) = (self.get(x, y - 1, z), self.get(x, y + 1, z));
+        let (back, front) = (self.get(x, y, z - 1), self.get(x, y, z + 1));
+        let laplacian = left + right + down + up + back + front - 6.0 * center;
+        let gradient =
+            ((right - left).powi(2) + (up - down).powi(2) + (front - back).powi(2)).sqrt() / 2.0;
+        if gradient > f32::EPSILON {
+            laplacian / gradient
+        } else {
+            0.0
+        }
+    }
+}
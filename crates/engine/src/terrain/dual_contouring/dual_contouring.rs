@@ -1,117 +1,497 @@
 use core::panic;
+use std::{
+    collections::{HashMap, HashSet},
+    sync::Arc,
+};
 
-use cgmath::{Matrix4, Point3, Vector3};
+use cgmath::{InnerSpace, Matrix4, Point3, Vector3};
 use gl::types::GLuint;
-use glfw::{Glfw, MouseButton, WindowEvent};
+use glfw::{Glfw, WindowEvent};
 use libnoise::prelude::*;
 
 use crate::{
     core::{
-        entity::{component::Component, Entity},
-        renderer::{line::Line, shader::VertexAttributes, texture::Texture},
+        entity::{
+            component::{Component, InspectableComponent},
+            Entity,
+        },
+        event::EventCategory,
+        renderer::{
+            environment::include_environment,
+            light::light_pass::include_lights,
+            shader::{Shader, VertexAttributes},
+            shader_manager::ShaderSource,
+            texture::Texture,
+        },
         scene::Scene,
     },
-    terrain::{Chunk, ChunkBounds, Terrain, CHUNK_SIZE, CHUNK_SIZE_FLOAT, USE_LOD},
+    terrain::{
+        biome::BiomeMap,
+        hydrology::Hydrology,
+        surface::{SurfaceMaterial, SurfaceRules, SurfaceSample},
+        vegetation::{ScatterTriangle, VegetationInstance},
+        world_gen::WorldGenConfig,
+        Chunk, ChunkBounds, ChunkCoord, Terrain, CHUNK_SIZE, CHUNK_SIZE_FLOAT,
+    },
 };
 
-use fast_surface_nets::{
-    ndshape::{AbstractShape, RuntimeShape},
-    {surface_nets, SurfaceNetsBuffer},
+use super::{
+    ChunkMesh, Column, DensityParameters, DensityVolume, DualContouringChunk, GpuMesher,
+    HorizonMap, Vertex,
 };
 
-use super::{ChunkMesh, DualContouringChunk, Vertex};
+const WATER_CELL_SIZE: usize = 4;
+/// Depth of the LOD seam skirts in cells of the coarser of the two chunks
+const SKIRT_DEPTH: f32 = 2.0;
 
 impl DualContouringChunk {
-    fn get_density_at(&self, (x, y, z): (usize, usize, usize)) -> f32 {
+    fn get_density_at(&self, (x, y, z): (usize, usize, usize), column: &Column) -> f32 {
+        let height_iso = 1.0 - ((column.height + 1.0) / (1.0 + y as f32));
+        // the cave noise is at most 1, above that the terrain is air no matter what the caves do
+        let max_cave_iso = 1.0 - (self.cave_threshold - column.biome.cave_density * 2.0);
+        if height_iso >= max_cave_iso {
+            return height_iso;
+        }
+
+        let world = [
+            (self.position.0 * CHUNK_SIZE_FLOAT) as f64 + x as f64,
+            (self.position.1 * CHUNK_SIZE_FLOAT) as f64 + y as f64,
+            (self.position.2 * CHUNK_SIZE_FLOAT) as f64 + z as f64,
+        ];
+        height_iso.max(self.get_cave_iso(world, column.biome.cave_density))
+    }
+
+    /// Cave noise at the world position relative to the threshold, positive inside caves
+    pub(super) fn get_cave_iso(&self, world: [f64; 3], cave_density: f32) -> f32 {
         let offset: f64 = 16777216.0;
-        let sample_point = (
-            (self.position.0 * CHUNK_SIZE_FLOAT) as f64 + x as f64 + offset,
-            (self.position.1 * CHUNK_SIZE_FLOAT) as f64 + y as f64 + offset,
-            (self.position.2 * CHUNK_SIZE_FLOAT) as f64 + z as f64 + offset,
-        );
-
-        let noise = ((1.0 + self.noise.sample([sample_point.0, sample_point.2])) / 2.0) as f32;
-        let _iso = ((1.0
+        let cave = ((1.0
             + self
                 .cave
-                .sample([sample_point.0, sample_point.1, sample_point.2]))
+                .sample([world[0] + offset, world[1] + offset, world[2] + offset]))
             / 2.0) as f32;
-        let height_iso = 1.0 - ((noise) / ((1.0 + y as f32) / CHUNK_SIZE_FLOAT));
-        height_iso
+        cave - (self.cave_threshold - cave_density * 2.0)
     }
 
-    fn generate_mesh(&self) -> ChunkMesh<Vertex> {
-        let mut vertices = Vec::<Vertex>::new();
-        let mut indices = Vec::<u32>::new();
+    /// Height of the uncarved terrain surface at the world column `(x, z)`
+    fn get_surface_height(&self, x: f64, z: f64) -> f32 {
+        self.biome_map.sample(x, z).height
+    }
+
+    fn generate_columns(&self) -> Vec<Column> {
+        let size = self.chunk_size + 2;
+        let scale_factor = CHUNK_SIZE / self.chunk_size;
+        let mut columns = Vec::with_capacity(size * size);
+        for x in 0..size {
+            for z in 0..size {
+                let world_x =
+                    (self.position.0 * CHUNK_SIZE_FLOAT) as f64 + (x * scale_factor) as f64;
+                let world_z =
+                    (self.position.2 * CHUNK_SIZE_FLOAT) as f64 + (z * scale_factor) as f64;
+                let biome = self.biome_map.sample(world_x, world_z);
+                let water = self
+                    .hydrology
+                    .sample(world_x, world_z, &|x, z| self.get_surface_height(x, z));
+                columns.push(Column {
+                    height: biome.height - water.carve_depth,
+                    biome,
+                });
+            }
+        }
+        columns
+    }
+
+    fn generate_density(&self, columns: &[Column]) -> DensityVolume {
         let size = (self.chunk_size + 2) as u32;
         let scale_factor = CHUNK_SIZE / self.chunk_size;
-        let shape = RuntimeShape::<u32, 3>::new([size, size, size]);
-        let mut sdf = vec![0.0; (size * size * size) as usize];
-        for i in 0..sdf.len() {
-            let [x, y, z] = shape.delinearize(i as u32);
-            sdf[i as usize] = self.get_density_at((
-                x as usize * scale_factor,
-                y as usize * scale_factor,
-                z as usize * scale_factor,
-            ));
-        }
-        let mut buffer = SurfaceNetsBuffer::default();
-        surface_nets(&sdf, &shape, [0; 3], [size as u32 - 1; 3], &mut buffer);
-        for (i, vertex) in buffer.positions.into_iter().enumerate() {
-            let normal = buffer.normals[i];
-            vertices.push(Vertex {
-                position: [
-                    vertex[0] * scale_factor as f32,
-                    vertex[1] * scale_factor as f32,
-                    vertex[2] * scale_factor as f32,
+        DensityVolume::new(size, |x, y, z| {
+            self.get_density_at(
+                (
+                    x as usize * scale_factor,
+                    y as usize * scale_factor,
+                    z as usize * scale_factor,
+                ),
+                &columns[(x * size + z) as usize],
+            )
+        })
+    }
+
+    fn generate_mesh(&self, columns: &[Column]) -> ChunkMesh<Vertex> {
+        let density = self.generate_density(columns);
+        let buffer = density.surface_nets();
+        let curvatures: Vec<f32> = buffer
+            .positions
+            .iter()
+            .map(|vertex| density.get_curvature_at(*vertex))
+            .collect();
+        self.build_mesh(
+            columns,
+            &buffer.positions,
+            &buffer.normals,
+            &curvatures,
+            buffer.indices,
+        )
+    }
+
+    /// Meshes the chunk with compute shaders, falls back to the cpu if the gpu buffers overflow
+    fn generate_gpu_mesh(&self, columns: &[Column]) -> ChunkMesh<Vertex> {
+        let size = (self.chunk_size + 2) as u32;
+        let surface = GpuMesher::mesh(&DensityParameters {
+            seed: self.seed,
+            size,
+            scale_factor: (CHUNK_SIZE / self.chunk_size) as u32,
+            cave_frequency: self.cave_frequency,
+            cave_threshold: self.cave_threshold,
+            origin: [
+                (self.position.0 * CHUNK_SIZE_FLOAT) as f64,
+                (self.position.1 * CHUNK_SIZE_FLOAT) as f64,
+                (self.position.2 * CHUNK_SIZE_FLOAT) as f64,
+            ],
+            columns: columns
+                .iter()
+                .map(|column| [column.height, column.biome.cave_density])
+                .collect(),
+        });
+        match surface {
+            Some(surface) => self.build_mesh(
+                columns,
+                &surface.positions,
+                &surface.normals,
+                &surface.curvatures,
+                surface.indices,
+            ),
+            None => {
+                log::debug!(
+                    target: "terrain",
+                    "Chunk {:?} overflowed the gpu buffers, meshing it on the cpu",
+                    self.position
+                );
+                self.generate_mesh(columns)
+            }
+        }
+    }
+
+    /// Applies the surface rules to the vertices produced by surface nets
+    fn build_mesh(
+        &self,
+        columns: &[Column],
+        positions: &[[f32; 3]],
+        normals: &[[f32; 3]],
+        curvatures: &[f32],
+        indices: Vec<u32>,
+    ) -> ChunkMesh<Vertex> {
+        let size = self.chunk_size + 2;
+        let scale_factor = CHUNK_SIZE / self.chunk_size;
+        let mut vertices = Vec::<Vertex>::with_capacity(positions.len());
+        for (i, vertex) in positions.iter().enumerate() {
+            let normal = normals[i];
+            let position = [
+                vertex[0] * scale_factor as f32,
+                vertex[1] * scale_factor as f32,
+                vertex[2] * scale_factor as f32,
+            ];
+            let sample = SurfaceSample::new(
+                [
+                    position[0] + self.position.0 * CHUNK_SIZE_FLOAT,
+                    position[1] + self.position.1 * CHUNK_SIZE_FLOAT,
+                    position[2] + self.position.2 * CHUNK_SIZE_FLOAT,
                 ],
                 normal,
-                color: [0.0, 0.5, 0.1],
+                curvatures[i] / scale_factor as f32,
+            );
+            let column = &columns[(vertex[0].round() as usize).min(size - 1) * size
+                + (vertex[2].round() as usize).min(size - 1)];
+            let sample =
+                sample.with_biome(column.biome.surface_material, column.biome.surface_color);
+            let surface = self.surface_rules.evaluate(&sample);
+            let horizon = self
+                .horizon_map
+                .as_ref()
+                .map_or([0; 2], |horizon_map| horizon_map.trace(sample.position));
+            let interior = if self.interior_darkening {
+                self.get_interior(columns, sample.position, normal)
+            } else {
+                0.0
+            };
+            vertices.push(Vertex {
+                position,
+                normal,
+                color: sample.get_color(surface.material),
+                material: surface.material as u32,
+                vegetation: surface.vegetation,
+                horizon,
+                interior,
             });
         }
-        for index in buffer.indices {
-            indices.push(index);
-        }
+        let mut indices = indices;
+        self.add_skirts(&mut vertices, &mut indices);
         ChunkMesh::new(vertices, Some(indices))
     }
 
-    fn calculate_chunk_size(lod: usize) -> usize {
-        let lod = std::cmp::max(
+    /// Scatters grass, rocks and trees over the triangles of `mesh`
+    fn scatter_vegetation(
+        &self,
+        columns: &[Column],
+        mesh: &ChunkMesh<Vertex>,
+    ) -> Vec<VegetationInstance> {
+        let Some(indices) = &mesh.indices else {
+            return Vec::new();
+        };
+        let size = self.chunk_size + 2;
+        let scale_factor = (CHUNK_SIZE / self.chunk_size) as f32;
+        let origin = [
+            self.position.0 * CHUNK_SIZE_FLOAT,
+            self.position.1 * CHUNK_SIZE_FLOAT,
+            self.position.2 * CHUNK_SIZE_FLOAT,
+        ];
+        let triangles = indices.chunks_exact(3).map(|triangle| {
+            let vertices = [0, 1, 2].map(|i| &mesh.vertices[triangle[i] as usize]);
+            let [x, _, z] = vertices[0].position;
+            let column = &columns[((x / scale_factor).round() as usize).min(size - 1) * size
+                + ((z / scale_factor).round() as usize).min(size - 1)];
+            ScatterTriangle {
+                corners: vertices.map(|vertex| {
+                    [
+                        vertex.position[0] + origin[0],
+                        vertex.position[1] + origin[1],
+                        vertex.position[2] + origin[2],
+                    ]
+                }),
+                color: vertices[0].color,
+                vegetation: vertices.iter().map(|vertex| vertex.vegetation).sum::<f32>() / 3.0,
+                biome: column.biome.biome_type,
+            }
+        });
+        // chunks keep their vegetation no matter in which order they are generated
+        let seed = self.seed
+            ^ (self.position.0 as i64 as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15)
+            ^ (self.position.2 as i64 as u64).wrapping_mul(0xc2b2_ae3d_27d4_eb4f);
+        VegetationInstance::scatter(seed, triangles)
+    }
+
+    /// Border of the chunk the open edge from `a` to `b` lies on as an index into
+    /// `neighbor_chunk_sizes`, None for edges on the top or bottom of the chunk
+    fn get_border(&self, a: [f32; 3], b: [f32; 3]) -> Option<usize> {
+        let scale_factor = (CHUNK_SIZE / self.chunk_size) as f32;
+        // the vertices of the outermost cells lie within one cell of the border
+        let x = (a[0] + b[0]) / 2.0 / scale_factor;
+        let z = (a[2] + b[2]) / 2.0 / scale_factor;
+        let high = self.chunk_size as f32;
+        [x < 1.0, x > high, z < 1.0, z > high]
+            .into_iter()
+            .enumerate()
+            .find(|(border, on_border)| {
+                *on_border && self.neighbor_chunk_sizes[*border] != self.chunk_size
+            })
+            .map(|(border, _)| border)
+    }
+
+    /// Hangs a skirt below every open edge on the borders towards neighbouring chunks of another
+    /// level of detail, so the gaps between their surfaces are covered
+    fn add_skirts(&self, vertices: &mut Vec<Vertex>, indices: &mut Vec<u32>) {
+        if self.neighbor_chunk_sizes == [self.chunk_size; 4] {
+            return;
+        }
+        // edges used by a single triangle are on the border of the mesh
+        let mut open_edges = HashSet::new();
+        for triangle in indices.chunks_exact(3) {
+            for (a, b) in [
+                (triangle[0], triangle[1]),
+                (triangle[1], triangle[2]),
+                (triangle[2], triangle[0]),
+            ] {
+                if !open_edges.remove(&(b, a)) {
+                    open_edges.insert((a, b));
+                }
+            }
+        }
+
+        let mut skirt_vertices = HashMap::new();
+        let mut get_skirt_vertex = |vertices: &mut Vec<Vertex>, index: u32, depth: f32| {
+            *skirt_vertices.entry(index).or_insert_with(|| {
+                let mut vertex = vertices[index as usize];
+                let normal = Vector3::from(vertex.normal);
+                let offset = if normal.magnitude2() > f32::EPSILON {
+                    normal.normalize() * depth
+                } else {
+                    Vector3::unit_y() * depth
+                };
+                vertex.position = (Vector3::from(vertex.position) - offset).into();
+                vertices.push(vertex);
+                (vertices.len() - 1) as u32
+            })
+        };
+        for (a, b) in open_edges {
+            let Some(border) =
+                self.get_border(vertices[a as usize].position, vertices[b as usize].position)
+            else {
+                continue;
+            };
+            // deep enough to cover a cell of the coarser of the two chunks
+            let coarser = self.chunk_size.min(self.neighbor_chunk_sizes[border]);
+            let depth = SKIRT_DEPTH * (CHUNK_SIZE / coarser) as f32;
+            let skirt_a = get_skirt_vertex(vertices, a, depth);
+            let skirt_b = get_skirt_vertex(vertices, b, depth);
+            // wound against the border edge so the skirt faces the same way as the surface
+            indices.extend_from_slice(&[b, a, skirt_a, b, skirt_a, skirt_b]);
+        }
+    }
+
+    /// Builds flat water surfaces for every column where a river or lake sits above the terrain
+    fn generate_water_mesh(&self) -> Option<ChunkMesh<Vertex>> {
+        let mut vertices = Vec::<Vertex>::new();
+        let cells = CHUNK_SIZE / WATER_CELL_SIZE;
+        let cell_size = WATER_CELL_SIZE as f32;
+        for i in 0..cells {
+            for j in 0..cells {
+                let (x, z) = ((i * WATER_CELL_SIZE) as f32, (j * WATER_CELL_SIZE) as f32);
+                let world_x = (self.position.0 * CHUNK_SIZE_FLOAT + x + cell_size / 2.0) as f64;
+                let world_z = (self.position.2 * CHUNK_SIZE_FLOAT + z + cell_size / 2.0) as f64;
+                let surface = self.get_surface_height(world_x, world_z);
+                let sample = self
+                    .hydrology
+                    .sample(world_x, world_z, &|x, z| self.get_surface_height(x, z));
+                let Some(water_level) = sample.water_level else {
+                    continue;
+                };
+                if water_level <= surface - sample.carve_depth {
+                    continue;
+                }
+                let y = water_level - self.position.1 * CHUNK_SIZE_FLOAT;
+                let corners = [
+                    [x, y, z],
+                    [x, y, z + cell_size],
+                    [x + cell_size, y, z + cell_size],
+                    [x + cell_size, y, z],
+                ];
+                for corner in [0, 1, 2, 0, 2, 3] {
+                    vertices.push(Vertex {
+                        position: corners[corner],
+                        normal: [0.0, 1.0, 0.0],
+                        color: [0.1, 0.3, 0.8],
+                        material: SurfaceMaterial::Seabed as u32,
+                        vegetation: 0.0,
+                        horizon: [0; 2],
+                        interior: 0.0,
+                    });
+                }
+            }
+        }
+        if vertices.is_empty() {
+            None
+        } else {
+            Some(ChunkMesh::new(vertices, None))
+        }
+    }
+
+    /// Draws the rivers and lakes of the chunk with the bound `shader`, the water pass renders
+    /// them after the opaque geometry
+    pub fn has_water(&self) -> bool {
+        self.water_mesh.is_some()
+    }
+
+    pub fn render_water(&self, shader: &Shader, parent_transform: &Matrix4<f32>) {
+        if let Some(water_mesh) = &self.water_mesh {
+            let transform = parent_transform
+                * Matrix4::from_translation(Vector3::new(
+                    self.position.0 * CHUNK_SIZE_FLOAT,
+                    self.position.1 * CHUNK_SIZE_FLOAT,
+                    self.position.2 * CHUNK_SIZE_FLOAT,
+                ));
+            water_mesh.render(shader, &transform, None);
+        }
+    }
+
+    fn calculate_chunk_size(lod: usize, level_of_detail: bool) -> usize {
+        if !level_of_detail {
+            return CHUNK_SIZE;
+        }
+        std::cmp::max(
             8,
             std::cmp::min(
                 CHUNK_SIZE,
                 CHUNK_SIZE / 2usize.pow(if lod > 0 { (lod - 1) as u32 } else { 0 }),
             ),
-        );
-        if USE_LOD {
-            lod
-        } else {
-            CHUNK_SIZE
-        }
+        )
     }
 }
 
 impl Chunk for DualContouringChunk {
-    fn new(seed: u64, position: (f32, f32, f32), lod: usize) -> Self {
-        let noise = Source::perlin(seed).scale([0.003; 2]).fbm(6, 1.0, 2.0, 0.5);
-        let cave = Source::perlin(seed).scale([0.1; 3]);
+    type Vertex = Vertex;
+
+    fn new(config: &WorldGenConfig, position: (f32, f32, f32), lod: usize) -> Self {
+        let mut chunk = DualContouringChunk::new_deferred(config, position, lod);
+        let columns = chunk.columns.take().unwrap();
+        let mesh = chunk.generate_mesh(&columns);
+        chunk.vegetation = chunk.scatter_vegetation(&columns, &mesh);
+        chunk.mesh = Some(mesh);
+        // the horizons are baked into the vertices
+        chunk.horizon_map = None;
+        chunk
+    }
+
+    fn new_deferred(config: &WorldGenConfig, position: (f32, f32, f32), lod: usize) -> Self {
+        let cave = Source::perlin(config.seed).scale([config.cave_frequency; 3]);
         let mut chunk = Self {
             position,
+            seed: config.seed,
             cave,
-            noise,
-            chunk_size: DualContouringChunk::calculate_chunk_size(lod),
+            cave_frequency: config.cave_frequency,
+            cave_threshold: config.cave_threshold,
+            biome_map: BiomeMap::new(config),
+            hydrology: Hydrology::new(config.seed),
+            surface_rules: SurfaceRules::default(),
+            chunk_size: DualContouringChunk::calculate_chunk_size(lod, config.level_of_detail),
+            neighbor_chunk_sizes: [(-1, 0), (1, 0), (0, -1), (0, 1)].map(|(x, z)| {
+                let neighbor = ChunkCoord::new(position.0 as i32 + x, 0, position.2 as i32 + z);
+                DualContouringChunk::calculate_chunk_size(
+                    neighbor.get_lod(),
+                    config.level_of_detail,
+                )
+            }),
+            lod,
+            columns: None,
+            horizon_map: None,
+            interior_darkening: config.interior_darkening,
             mesh: None,
+            water_mesh: None,
+            vegetation: Vec::new(),
         };
-        chunk.mesh = Some(chunk.generate_mesh());
+        chunk.water_mesh = chunk.generate_water_mesh();
+        if config.sun_occlusion {
+            let origin = [position.0 * CHUNK_SIZE_FLOAT, position.2 * CHUNK_SIZE_FLOAT];
+            chunk.horizon_map = Some(HorizonMap::new(origin, CHUNK_SIZE_FLOAT, |x, z| {
+                chunk.get_surface_height(x, z)
+            }));
+        }
+        // columns only need the cpu, so they are generated on the loader thread already
+        chunk.columns = Some(chunk.generate_columns());
         chunk
     }
 
+    fn generate_gpu_mesh(&mut self) {
+        if self.mesh.is_some() {
+            return;
+        }
+        let columns = self
+            .columns
+            .take()
+            .unwrap_or_else(|| self.generate_columns());
+        let mesh = DualContouringChunk::generate_gpu_mesh(self, &columns);
+        self.vegetation = self.scatter_vegetation(&columns, &mesh);
+        self.mesh = Some(mesh);
+        self.horizon_map = None;
+    }
+
     fn buffer_data(&mut self) {
-        if let Some(mesh) = &mut self.mesh {
-            mesh.buffer_data();
+        if let Some(water_mesh) = &mut self.water_mesh {
+            water_mesh.buffer_data();
         }
     }
 
+    fn get_mesh(&self) -> Option<&ChunkMesh<Vertex>> {
+        self.mesh.as_ref()
+    }
+
     fn get_bounds(&self) -> ChunkBounds {
         ChunkBounds {
             min: (
@@ -127,10 +507,6 @@ impl Chunk for DualContouringChunk {
         }
     }
 
-    fn process_line(&mut self, _: &Line, _: &MouseButton) -> bool {
-        false
-    }
-
     fn get_position(&self) -> Point3<f32> {
         Point3::new(
             self.position.0 * CHUNK_SIZE_FLOAT,
@@ -139,17 +515,36 @@ impl Chunk for DualContouringChunk {
         )
     }
 
-    fn get_shader_source() -> (String, String) {
+    fn get_shader_source() -> (ShaderSource, ShaderSource) {
         (
-            include_str!("vertex.glsl").to_string(),
-            include_str!("fragment.glsl").to_string(),
+            ShaderSource::new(
+                "terrain/dual_contouring/vertex.glsl",
+                include_str!("vertex.glsl"),
+            ),
+            ShaderSource::new(
+                "terrain/dual_contouring/fragment.glsl",
+                include_str!("fragment.glsl"),
+            )
+            .preprocess(|source| include_lights(&include_environment(source))),
         )
     }
 
-    fn get_textures() -> Vec<Texture> {
+    fn get_textures() -> Vec<Arc<Texture>> {
         Vec::new()
     }
 
+    fn get_buffer_size(&self) -> usize {
+        self.mesh.as_ref().map_or(0, |mesh| mesh.get_buffer_size())
+            + self
+                .water_mesh
+                .as_ref()
+                .map_or(0, |mesh| mesh.get_buffer_size())
+    }
+
+    fn get_vegetation(&self) -> Vec<VegetationInstance> {
+        self.vegetation.clone()
+    }
+
     fn get_triangle_count(&self) -> usize {
         if let Some(mesh) = &self.mesh {
             mesh.get_triangle_count()
@@ -169,6 +564,17 @@ impl Chunk for DualContouringChunk {
         }
     }
 
+    fn get_normals(&self) -> Vec<[f32; 3]> {
+        match &self.mesh {
+            Some(mesh) => mesh.vertices.iter().map(|v| v.normal).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    fn get_lod(&self) -> usize {
+        self.lod
+    }
+
     fn get_indices(&self) -> Vec<[u32; 3]> {
         if let Some(mesh) = &self.mesh {
             if let Some(indices) = &mesh.indices {
@@ -179,49 +585,45 @@ impl Chunk for DualContouringChunk {
     }
 }
 
+impl InspectableComponent for DualContouringChunk {
+    fn get_inspector_name(&self) -> &str {
+        "Chunk"
+    }
+}
+
 impl Component for DualContouringChunk {
     fn update(&mut self, _: &mut Scene, _: &mut Entity, _: f64) {}
 
-    fn render(
-        &self,
-        scene: &Scene,
-        _: &Entity,
-        view_projection: &Matrix4<f32>,
-        parent_transform: &Matrix4<f32>,
-    ) {
-        if let Some(terrain) = scene.get_component::<Terrain<DualContouringChunk>>() {
-            let shader = terrain.get_shader();
-            if let Some(mesh) = &self.mesh {
-                if !mesh.is_buffered() {
-                    panic!("Mesh is not buffered");
-                }
-                shader.bind();
-                shader.set_uniform_mat4("viewProjection", &view_projection);
-                unsafe {
-                    gl::Enable(gl::CULL_FACE);
-                }
-                mesh.render(
-                    &shader,
-                    &(parent_transform
-                        * Matrix4::from_translation(Vector3::new(
-                            self.position.0 * CHUNK_SIZE_FLOAT,
-                            self.position.1 * CHUNK_SIZE_FLOAT,
-                            self.position.2 * CHUNK_SIZE_FLOAT,
-                        ))),
-                    None,
-                );
-                unsafe {
-                    gl::Disable(gl::CULL_FACE);
-                }
-            }
-        }
+    /// The terrain mesh is drawn by the terrain's batch and the water by the water pass
+    fn render(&self, _: &Scene, _: &Entity, _: &Matrix4<f32>, _: &Matrix4<f32>) {}
+
+    fn get_event_priority(&self, _: EventCategory) -> Option<i32> {
+        None
     }
 
-    fn handle_event(&mut self, _: &mut Glfw, _: &mut glfw::Window, _: &WindowEvent) {}
+    fn handle_event(&mut self, _: &mut Glfw, _: &mut glfw::Window, _: &WindowEvent) -> bool {
+        false
+    }
+
+    fn as_inspectable(&self) -> Option<&dyn InspectableComponent> {
+        Some(self)
+    }
+
+    fn as_inspectable_mut(&mut self) -> Option<&mut dyn InspectableComponent> {
+        Some(self)
+    }
 }
 
 impl VertexAttributes for Vertex {
     fn get_vertex_attributes() -> Vec<(usize, GLuint)> {
-        vec![(3, gl::FLOAT), (3, gl::FLOAT), (3, gl::FLOAT)]
+        vec![
+            (3, gl::FLOAT),
+            (3, gl::FLOAT),
+            (3, gl::FLOAT),
+            (1, gl::UNSIGNED_INT),
+            (1, gl::FLOAT),
+            (2, gl::UNSIGNED_INT),
+            (1, gl::FLOAT),
+        ]
     }
 }
@@ -4,11 +4,26 @@ in vec3 Color;
 in vec3 Normal;
 in vec3 toLightVector;
 in vec4 fragPosLightSpace;
+in vec3 WorldPosition;
+// 0 where the surrounding terrain hides the sun, independent of the shadow map's range
+in float SunVisibility;
+// how enclosed by terrain the fragment is, caves don't get the sky's ambient light
+in float Interior;
 
-out vec4 FragColor;
+layout (location = 0) out vec4 FragColor;
+// world space normal for the SSAO G-buffer
+layout (location = 1) out vec4 FragNormal;
+// x roughness, y 0 as the terrain doesn't reflect the sky probe itself
+layout (location = 2) out vec4 FragMaterial;
 
 uniform sampler2D shadowMap;
 
+// share of the sky ambient light that remains where the terrain hides the whole sky
+const float INTERIOR_AMBIENT = 0.1;
+
+#include "lights.glsl"
+#include "environment.glsl"
+
 float ShadowCalculation(vec4 fragPosLightSpace, vec3 toLightVector, vec3 normal) {
     vec3 projCoords = fragPosLightSpace.xyz / fragPosLightSpace.w;
     projCoords = projCoords * 0.5 + 0.5;
@@ -40,6 +55,10 @@ void main() {
     float intensity = dot(normal, unitToLightVector);
     float brightness = max(intensity, 0.5);
     vec3 diffuse = brightness * vec3(1.0);
-    float shadow = ShadowCalculation(fragPosLightSpace, unitToLightVector, normal);
-    FragColor = vec4((0.5 + (1.0 - shadow) * diffuse) * Color, 1.0);
+    float shadow = max(ShadowCalculation(fragPosLightSpace, unitToLightVector, normal), 1.0 - SunVisibility);
+    float ambient = mix(1.0, INTERIOR_AMBIENT, Interior);
+    vec3 lighting = skyAmbient(normal) * ambient * screenAmbientOcclusion() + (1.0 - shadow) * diffuse + pointLighting(WorldPosition, normal);
+    FragColor = vec4(applyFog(lighting * Color, WorldPosition), 1.0);
+    FragNormal = vec4(normal, 1.0);
+    FragMaterial = vec4(1.0, 0.0, 0.0, 1.0);
 }
\ No newline at end of file
@@ -0,0 +1,206 @@
+use std::{ffi::c_void, sync::Mutex};
+
+use gl::types::{GLsizeiptr, GLuint};
+use lazy_static::lazy_static;
+use rand::{seq::SliceRandom, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+
+use crate::core::renderer::{shader::Shader, shader_manager::ShaderSource};
+
+use super::{DensityParameters, GpuMesher, GpuSurface};
+
+const WORK_GROUP_SIZE: u32 = 8;
+const PERMUTATION_TABLE_SIZE: u32 = 256;
+/// Offset the cpu generator adds to every noise sample point
+const NOISE_OFFSET: f64 = 16777216.0;
+/// Vertices reserved per grid layer, surfaces are rarely more than a few layers thick
+const VERTICES_PER_LAYER: u32 = 12;
+const INDICES_PER_VERTEX: u32 = 18;
+const NULL_VERTEX: u32 = u32::MAX;
+
+const COLUMNS: usize = 0;
+const PERMUTATION: usize = 1;
+const DENSITY: usize = 2;
+const STRIDE_TO_INDEX: usize = 3;
+const VERTICES: usize = 4;
+const INDICES: usize = 5;
+const COUNTERS: usize = 6;
+
+lazy_static! {
+    static ref MESHER: Mutex<Option<GpuMesher>> = Mutex::new(None);
+}
+
+impl GpuMesher {
+    fn new() -> Self {
+        let mut buffers = [0; 7];
+        unsafe {
+            gl::GenBuffers(buffers.len() as i32, buffers.as_mut_ptr());
+        }
+        Self {
+            density_shader: Shader::load_compute(ShaderSource::new(
+                "terrain/dual_contouring/compute/density.glsl",
+                include_str!("compute/density.glsl"),
+            )),
+            vertices_shader: Shader::load_compute(ShaderSource::new(
+                "terrain/dual_contouring/compute/surface_vertices.glsl",
+                include_str!("compute/surface_vertices.glsl"),
+            )),
+            quads_shader: Shader::load_compute(ShaderSource::new(
+                "terrain/dual_contouring/compute/surface_quads.glsl",
+                include_str!("compute/surface_quads.glsl"),
+            )),
+            buffers,
+            size: 0,
+            permutation_seed: None,
+        }
+    }
+
+    /// Meshes a chunk on the gpu, has to be called on the thread owning the gl context.
+    ///
+    /// Returns `None` if the surface did not fit into the gpu buffers.
+    pub(super) fn mesh(parameters: &DensityParameters) -> Option<GpuSurface> {
+        let mut mesher = MESHER.lock().unwrap();
+        mesher.get_or_insert_with(GpuMesher::new).run(parameters)
+    }
+
+    fn run(&mut self, parameters: &DensityParameters) -> Option<GpuSurface> {
+        let size = parameters.size;
+        let max_vertices = size * size * VERTICES_PER_LAYER;
+        let max_indices = max_vertices * INDICES_PER_VERTEX;
+        if self.size != size {
+            self.allocate(size, max_vertices, max_indices);
+        }
+        if self.permutation_seed != Some(parameters.seed) {
+            self.upload(PERMUTATION, &GpuMesher::permutation_table(parameters.seed));
+            self.permutation_seed = Some(parameters.seed);
+        }
+        self.upload(COLUMNS, &parameters.columns);
+        self.upload(COUNTERS, &[0u32, 0u32]);
+        unsafe {
+            for (binding, buffer) in self.buffers.iter().enumerate() {
+                gl::BindBufferBase(gl::SHADER_STORAGE_BUFFER, binding as GLuint, *buffer);
+            }
+        }
+
+        // split the noise origin into its lattice cell and the offset inside the cell
+        let noise_origin = parameters
+            .origin
+            .map(|o| (o + NOISE_OFFSET) * parameters.cave_frequency);
+        let groups = size.div_ceil(WORK_GROUP_SIZE);
+
+        self.density_shader.bind();
+        self.density_shader.set_uniform_1ui("size", size);
+        self.density_shader
+            .set_uniform_1ui("scaleFactor", parameters.scale_factor);
+        self.density_shader
+            .set_uniform_1f("caveFrequency", parameters.cave_frequency as f32);
+        self.density_shader
+            .set_uniform_1f("caveThreshold", parameters.cave_threshold);
+        let cell = noise_origin.map(|o| o.floor().rem_euclid(PERMUTATION_TABLE_SIZE as f64) as u32);
+        self.density_shader
+            .set_uniform_3ui("noiseCell", cell[0], cell[1], cell[2]);
+        self.density_shader.set_uniform_3f(
+            "noiseOffset",
+            (noise_origin[0] - noise_origin[0].floor()) as f32,
+            (noise_origin[1] - noise_origin[1].floor()) as f32,
+            (noise_origin[2] - noise_origin[2].floor()) as f32,
+        );
+        self.density_shader.dispatch(groups, groups, groups);
+
+        self.vertices_shader.bind();
+        self.vertices_shader.set_uniform_1ui("size", size);
+        self.vertices_shader
+            .set_uniform_1ui("maxVertices", max_vertices);
+        self.vertices_shader.dispatch(groups, groups, groups);
+
+        self.quads_shader.bind();
+        self.quads_shader.set_uniform_1ui("size", size);
+        self.quads_shader.set_uniform_1ui("maxIndices", max_indices);
+        self.quads_shader.dispatch(groups, groups, groups);
+
+        let counters: Vec<u32> = self.read(COUNTERS, 2);
+        let (vertex_count, index_count) = (counters[0], counters[1]);
+        if vertex_count > max_vertices || index_count > max_indices {
+            return None;
+        }
+
+        // each vertex is a vec4 position with the curvature in w followed by a vec4 normal
+        let vertex_data: Vec<[f32; 8]> = self.read(VERTICES, vertex_count as usize);
+        let indices: Vec<u32> = self.read(INDICES, index_count as usize);
+        if indices.contains(&NULL_VERTEX) {
+            return None;
+        }
+        Some(GpuSurface {
+            positions: vertex_data.iter().map(|v| [v[0], v[1], v[2]]).collect(),
+            normals: vertex_data.iter().map(|v| [v[4], v[5], v[6]]).collect(),
+            curvatures: vertex_data.iter().map(|v| v[3]).collect(),
+            indices,
+        })
+    }
+
+    /// Same permutation table libnoise builds for its perlin noise with `seed`
+    fn permutation_table(seed: u64) -> Vec<u32> {
+        let mut table: Vec<u32> = (0..PERMUTATION_TABLE_SIZE).collect();
+        table.shuffle(&mut ChaCha12Rng::seed_from_u64(seed));
+        table.extend_from_within(..);
+        table
+    }
+
+    fn allocate(&mut self, size: u32, max_vertices: u32, max_indices: u32) {
+        let cells = (size * size * size) as usize;
+        let mut sizes = [0; 7];
+        sizes[COLUMNS] = (size * size) as usize * 2 * size_of::<f32>();
+        sizes[PERMUTATION] = 2 * PERMUTATION_TABLE_SIZE as usize * size_of::<u32>();
+        sizes[DENSITY] = cells * size_of::<f32>();
+        sizes[STRIDE_TO_INDEX] = cells * size_of::<u32>();
+        sizes[VERTICES] = max_vertices as usize * 8 * size_of::<f32>();
+        sizes[INDICES] = max_indices as usize * size_of::<u32>();
+        sizes[COUNTERS] = 2 * size_of::<u32>();
+        unsafe {
+            for (buffer, bytes) in self.buffers.iter().zip(sizes) {
+                gl::BindBuffer(gl::SHADER_STORAGE_BUFFER, *buffer);
+                gl::BufferData(
+                    gl::SHADER_STORAGE_BUFFER,
+                    bytes as GLsizeiptr,
+                    std::ptr::null(),
+                    gl::DYNAMIC_COPY,
+                );
+            }
+            gl::BindBuffer(gl::SHADER_STORAGE_BUFFER, 0);
+        }
+        self.size = size;
+        // the permutation buffer was reallocated as well
+        self.permutation_seed = None;
+    }
+
+    fn upload<T>(&self, buffer: usize, data: &[T]) {
+        unsafe {
+            gl::BindBuffer(gl::SHADER_STORAGE_BUFFER, self.buffers[buffer]);
+            gl::BufferSubData(
+                gl::SHADER_STORAGE_BUFFER,
+                0,
+                std::mem::size_of_val(data) as GLsizeiptr,
+                data.as_ptr() as *const c_void,
+            );
+            gl::BindBuffer(gl::SHADER_STORAGE_BUFFER, 0);
+        }
+    }
+
+    fn read<T: Clone + Default>(&self, buffer: usize, count: usize) -> Vec<T> {
+        let mut data = vec![T::default(); count];
+        if count == 0 {
+            return data;
+        }
+        unsafe {
+            gl::BindBuffer(gl::SHADER_STORAGE_BUFFER, self.buffers[buffer]);
+            gl::GetBufferSubData(
+                gl::SHADER_STORAGE_BUFFER,
+                0,
+                std::mem::size_of_val(data.as_slice()) as GLsizeiptr,
+                data.as_mut_ptr() as *mut c_void,
+            );
+            gl::BindBuffer(gl::SHADER_STORAGE_BUFFER, 0);
+        }
+        data
+    }
+}
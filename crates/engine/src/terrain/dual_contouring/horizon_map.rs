@@ -0,0 +1,75 @@
+use std::f32::consts::TAU;
+
+use super::HorizonMap;
+
+/// Distance between the grid points of the height grid
+const SPACING: f32 = 4.0;
+/// Distance around the chunk the horizon is traced to, terrain further away casts no shadow
+const RANGE: f32 = 64.0;
+/// Distances along every direction the horizon is sampled at, denser close to the vertex
+const SAMPLE_DISTANCES: [f32; 9] = [2.0, 4.0, 8.0, 12.0, 16.0, 24.0, 32.0, 48.0, 64.0];
+/// Directions the horizon is traced in, must match `vertex.glsl`
+const HORIZON_DIRECTIONS: usize = 8;
+/// Height above the surface the horizon is traced from, keeps the coarse grid from shadowing
+/// the surface it was sampled from
+const BIAS: f32 = 1.0;
+
+impl HorizonMap {
+    /// Samples `height` on a grid covering the `chunk_size` wide chunk at `origin` and the
+    /// traced range around it
+    pub fn new<F: Fn(f64, f64) -> f32>(origin: [f32; 2], chunk_size: f32, height: F) -> Self {
+        let origin = [origin[0] - RANGE, origin[1] - RANGE];
+        let size = ((chunk_size + 2.0 * RANGE) / SPACING).ceil() as usize + 1;
+        let mut heights = Vec::with_capacity(size * size);
+        for x in 0..size {
+            for z in 0..size {
+                heights.push(height(
+                    (origin[0] + x as f32 * SPACING) as f64,
+                    (origin[1] + z as f32 * SPACING) as f64,
+                ));
+            }
+        }
+        Self {
+            origin,
+            size,
+            heights,
+        }
+    }
+
+    /// Bilinearly interpolated surface height at the world column `(x, z)`
+    fn get_height(&self, x: f32, z: f32) -> f32 {
+        let max = (self.size - 1) as f32;
+        let x = ((x - self.origin[0]) / SPACING).clamp(0.0, max);
+        let z = ((z - self.origin[1]) / SPACING).clamp(0.0, max);
+        let (x0, z0) = (
+            (x.floor() as usize).min(self.size - 2),
+            (z.floor() as usize).min(self.size - 2),
+        );
+        let (fx, fz) = (x - x0 as f32, z - z0 as f32);
+        let get = |x: usize, z: usize| self.heights[x * self.size + z];
+        let near = get(x0, z0) * (1.0 - fz) + get(x0, z0 + 1) * fz;
+        let far = get(x0 + 1, z0) * (1.0 - fz) + get(x0 + 1, z0 + 1) * fz;
+        near * (1.0 - fx) + far * fx
+    }
+
+    /// Sine of the highest elevation the terrain reaches around the world `position`, in
+    /// `HORIZON_DIRECTIONS` directions counterclockwise from +x and quantized to a byte each
+    pub fn trace(&self, position: [f32; 3]) -> [u32; 2] {
+        let mut horizon = [0u32; 2];
+        for direction in 0..HORIZON_DIRECTIONS {
+            let (sin, cos) = (direction as f32 * TAU / HORIZON_DIRECTIONS as f32).sin_cos();
+            let mut max_sine: f32 = 0.0;
+            for distance in SAMPLE_DISTANCES {
+                let height =
+                    self.get_height(position[0] + cos * distance, position[2] + sin * distance);
+                let elevation = height - position[1] - BIAS;
+                if elevation > 0.0 {
+                    max_sine = max_sine.max(elevation / elevation.hypot(distance));
+                }
+            }
+            let byte = (max_sine.min(1.0) * 255.0).round() as u32;
+            horizon[direction / 4] |= byte << ((direction % 4) * 8);
+        }
+        horizon
+    }
+}
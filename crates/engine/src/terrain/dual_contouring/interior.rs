@@ -0,0 +1,95 @@
+use crate::terrain::CHUNK_SIZE_FLOAT;
+
+use super::{Column, DualContouringChunk};
+
+/// Rays traced per vertex, straight up and evenly around the vertex at 45 degrees
+const INTERIOR_RAYS: usize = 7;
+const INTERIOR_RAY_STEPS: usize = 8;
+/// Length of the first step of a ray, every following step is `INTERIOR_STEP_GROWTH` times longer
+const INTERIOR_FIRST_STEP: f32 = 1.0;
+const INTERIOR_STEP_GROWTH: f32 = 1.5;
+/// Vertices this close below the surface height count as outdoors without tracing
+const SURFACE_TOLERANCE: f32 = 1.0;
+
+impl DualContouringChunk {
+    /// How enclosed the world `position` is, from 0 outdoors to 1 where no ray towards the sky
+    /// gets out of the terrain. Caves only hollow out the terrain below the surface, so only
+    /// vertices below it are traced and a ray sees the sky once it rises above the surface
+    pub(super) fn get_interior(
+        &self,
+        columns: &[Column],
+        position: [f32; 3],
+        normal: [f32; 3],
+    ) -> f32 {
+        let (height, _) = self.sample_column(columns, position[0], position[2]);
+        if position[1] >= height - SURFACE_TOLERANCE {
+            return 0.0;
+        }
+        // start off the surface so the rays don't hit the rock the vertex lies on
+        let origin = [
+            position[0] + normal[0] * 0.5,
+            position[1] + normal[1] * 0.5,
+            position[2] + normal[2] * 0.5,
+        ];
+        let open = (0..INTERIOR_RAYS)
+            .filter(|ray| self.reaches_sky(columns, origin, DualContouringChunk::get_ray(*ray)))
+            .count();
+        1.0 - open as f32 / INTERIOR_RAYS as f32
+    }
+
+    fn get_ray(ray: usize) -> [f32; 3] {
+        if ray == 0 {
+            return [0.0, 1.0, 0.0];
+        }
+        let angle = (ray - 1) as f32 / (INTERIOR_RAYS - 1) as f32 * std::f32::consts::TAU;
+        let horizontal = std::f32::consts::FRAC_1_SQRT_2;
+        [
+            angle.cos() * horizontal,
+            std::f32::consts::FRAC_1_SQRT_2,
+            angle.sin() * horizontal,
+        ]
+    }
+
+    /// Whether the ray rises above the surface before it hits rock
+    fn reaches_sky(&self, columns: &[Column], origin: [f32; 3], direction: [f32; 3]) -> bool {
+        let mut distance = 0.0;
+        let mut step = INTERIOR_FIRST_STEP;
+        for _ in 0..INTERIOR_RAY_STEPS {
+            distance += step;
+            step *= INTERIOR_STEP_GROWTH;
+            let point = [
+                origin[0] + direction[0] * distance,
+                origin[1] + direction[1] * distance,
+                origin[2] + direction[2] * distance,
+            ];
+            let (height, cave_density) = self.sample_column(columns, point[0], point[2]);
+            if point[1] > height {
+                return true;
+            }
+            let world = [point[0] as f64, point[1] as f64, point[2] as f64];
+            if self.get_cave_iso(world, cave_density) <= 0.0 {
+                return false;
+            }
+        }
+        false
+    }
+
+    /// Carved surface height and cave density at the world column `(x, z)`, from the chunk's
+    /// columns where it has them
+    fn sample_column(&self, columns: &[Column], x: f32, z: f32) -> (f32, f32) {
+        let size = self.chunk_size + 2;
+        let scale_factor = (CHUNK_SIZE_FLOAT as usize / self.chunk_size) as f32;
+        let local_x = ((x - self.position.0 * CHUNK_SIZE_FLOAT) / scale_factor).round();
+        let local_z = ((z - self.position.2 * CHUNK_SIZE_FLOAT) / scale_factor).round();
+        if local_x >= 0.0
+            && local_z >= 0.0
+            && (local_x as usize) < size
+            && (local_z as usize) < size
+        {
+            let column = &columns[local_x as usize * size + local_z as usize];
+            return (column.height, column.biome.cave_density);
+        }
+        let biome = self.biome_map.sample(x as f64, z as f64);
+        (biome.height, biome.cave_density)
+    }
+}
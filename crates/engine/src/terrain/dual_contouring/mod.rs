@@ -1,15 +1,49 @@
+mod density_volume;
 pub mod dual_contouring;
+mod gpu_mesher;
+mod horizon_map;
+mod interior;
 
-use libnoise::{Fbm, Perlin, Scale};
+use fast_surface_nets::ndshape::RuntimeShape;
+use gl::types::GLuint;
+use libnoise::{Perlin, Scale};
 
-use crate::terrain::ChunkMesh;
+use crate::{
+    core::renderer::shader::Shader,
+    terrain::{
+        biome::{BiomeMap, BiomeSample},
+        hydrology::Hydrology,
+        surface::SurfaceRules,
+        vegetation::VegetationInstance,
+        ChunkMesh,
+    },
+};
 
 pub struct DualContouringChunk {
     position: (f32, f32, f32),
+    seed: u64,
     cave: Scale<3, Perlin<3>>,
-    noise: Fbm<2, Scale<2, Perlin<2>>>,
+    cave_frequency: f64,
+    cave_threshold: f32,
+    biome_map: BiomeMap,
+    hydrology: Hydrology,
+    surface_rules: SurfaceRules,
     chunk_size: usize,
+    /// Chunk sizes of the neighbours towards -x, +x, -z and +z, the borders towards neighbours of
+    /// another size get skirts
+    neighbor_chunk_sizes: [usize; 4],
+    lod: usize,
+    /// Columns generated on the loader thread for chunks that are meshed on the gpu later
+    columns: Option<Vec<Column>>,
+    /// Surface heights around the chunk the sun occlusion of the vertices is traced against,
+    /// None if `WorldGenConfig::sun_occlusion` is off
+    horizon_map: Option<HorizonMap>,
+    /// Whether the vertices trace their `interior`, see `WorldGenConfig::interior_darkening`
+    interior_darkening: bool,
     mesh: Option<ChunkMesh<Vertex>>,
+    water_mesh: Option<ChunkMesh<Vertex>>,
+    /// Scattered once the chunk is meshed, turned into a `VegetationComponent` on upload
+    vegetation: Vec<VegetationInstance>,
 }
 
 #[derive(Clone, Copy)]
@@ -18,4 +52,66 @@ pub struct Vertex {
     position: [f32; 3],
     normal: [f32; 3],
     color: [f32; 3],
+    material: u32,
+    vegetation: f32,
+    /// Sine of the elevation of the horizon in 8 directions around the vertex, one byte each
+    horizon: [u32; 2],
+    /// How enclosed by terrain the vertex is, 0 outdoors and 1 deep in a cave, darkens the sky
+    /// ambient light
+    interior: f32,
+}
+
+struct Column {
+    /// Surface height after rivers have been carved into it
+    height: f32,
+    biome: BiomeSample,
+}
+
+/// Density field of a chunk sampled once on the chunk's grid, shared by
+/// surface nets and the curvature estimation instead of re-evaluating the noise
+struct DensityVolume {
+    shape: RuntimeShape<u32, 3>,
+    size: u32,
+    values: Vec<f32>,
+}
+
+/// Heights of the terrain surface on a coarse grid around a chunk. Caves only hollow out the
+/// terrain below the surface, so the surface is the upper envelope of the density field and
+/// the sun is hidden from a point exactly when a ray towards it passes below these heights
+struct HorizonMap {
+    /// World x and z of the first grid point
+    origin: [f32; 2],
+    size: usize,
+    heights: Vec<f32>,
+}
+
+/// Compute shader pipeline evaluating the density field and running surface nets on the gpu
+pub struct GpuMesher {
+    density_shader: Shader,
+    vertices_shader: Shader,
+    quads_shader: Shader,
+    buffers: [GLuint; 7],
+    /// Grid size the buffers are currently allocated for
+    size: u32,
+    permutation_seed: Option<u64>,
+}
+
+struct DensityParameters {
+    seed: u64,
+    size: u32,
+    scale_factor: u32,
+    cave_frequency: f64,
+    cave_threshold: f32,
+    /// World position of the chunk's minimum corner
+    origin: [f64; 3],
+    /// Carved surface height and cave density of every column
+    columns: Vec<[f32; 2]>,
+}
+
+/// Surface nets output read back from the gpu
+struct GpuSurface {
+    positions: Vec<[f32; 3]>,
+    normals: Vec<[f32; 3]>,
+    curvatures: Vec<f32>,
+    indices: Vec<u32>,
 }
@@ -3,33 +3,67 @@
 layout (location = 0) in vec3 position;
 layout (location = 1) in vec3 normals;
 layout (location = 2) in vec3 color;
+layout (location = 3) in uint material;
+layout (location = 4) in float vegetation;
+// sine of the horizon elevation in 8 directions counterclockwise from +x, one byte each
+layout (location = 5) in uvec2 horizon;
+// 0 outdoors, 1 where the surrounding terrain hides the whole sky
+layout (location = 6) in float interior;
 
 out vec3 Normal;
 out vec3 Color;
 out vec3 toLightVector;
 out vec4 fragPosLightSpace;
+out vec3 WorldPosition;
+out float SunVisibility;
+out float Interior;
 
 uniform vec3 lightPosition;
 uniform mat4 model;
+// set while the terrain draws its chunk batch, each draw is offset by the origin of its chunk
+uniform bool batched;
 uniform mat4 viewProjection;
+
+layout (std430, binding = 4) readonly buffer ChunkOrigins {
+    vec4 chunkOrigins[];
+};
+// set while the debug controller tints the chunks by their level of detail, which the batch
+// stores in the w component of the chunk origins
+uniform bool lodColoring;
+
+vec3 lodTint(uint lod) {
+    const vec3 tints[4] = vec3[](vec3(1.0, 0.4, 0.4), vec3(0.4, 1.0, 0.4), vec3(0.4, 0.6, 1.0), vec3(1.0, 1.0, 0.4));
+    return tints[lod % 4u];
+}
 uniform mat4 lightProjection;
+// set when the chunks were generated with sun occlusion and their vertices carry horizons
+uniform bool sunOcclusion;
+
+const float HORIZON_SOFTNESS = 0.08;
+
+float getHorizon(uint direction) {
+    return float((horizon[direction / 4u] >> ((direction % 4u) * 8u)) & 0xFFu) / 255.0;
+}
+
+// how much of the sun in the normalized direction rises above the terrain around the vertex,
+// blended over a band of elevations for soft shadow edges
+float sunVisibility(vec3 toSun) {
+    float azimuth = mod(atan(toSun.z, toSun.x) / 6.28318530718 * 8.0, 8.0);
+    uint first = uint(azimuth) % 8u;
+    float horizonSine = mix(getHorizon(first), getHorizon((first + 1u) % 8u), fract(azimuth));
+    return smoothstep(horizonSine - HORIZON_SOFTNESS, horizonSine + HORIZON_SOFTNESS, toSun.y);
+}
 
 void main()
 {
-    vec4 worldPosition = model * vec4(position, 1.0);
+    vec3 localPosition = batched ? position + chunkOrigins[gl_DrawID].xyz : position;
+    vec4 worldPosition = model * vec4(localPosition, 1.0);
     gl_Position = viewProjection * worldPosition;
     Normal = normalize(normals);
-    if(position.y < 50.0) {
-        Color = vec3(0.1, 0.2, 0.8);
-    } else if(position.y < 51.0) {
-        Color = vec3(0.76078431, 0.69803921, 0.50196078);
-    } else if(position.y > 90.0) {
-        Color = vec3(0.95, 0.95, 0.95);
-    } else if(position.y > 80.0) {
-        Color = vec3(0.5, 0.5, 0.5);
-    } else {
-        Color = color;
-    }
+    Color = batched && lodColoring ? color * lodTint(uint(chunkOrigins[gl_DrawID].w)) : color;
     fragPosLightSpace = lightProjection * worldPosition;
     toLightVector = lightPosition - worldPosition.xyz;
+    SunVisibility = sunOcclusion ? sunVisibility(normalize(toLightVector)) : 1.0;
+    WorldPosition = worldPosition.xyz;
+    Interior = interior;
 }
\ No newline at end of file
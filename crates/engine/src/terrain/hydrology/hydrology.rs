@@ -0,0 +1,299 @@
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap, HashSet},
+    sync::{Arc, Mutex},
+};
+
+use lazy_static::lazy_static;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use super::{Hydrology, HydrologyRegion, WaterSample, SEA_LEVEL};
+
+const REGION_SIZE: f64 = 512.0;
+const REGION_MARGIN: f64 = 384.0;
+const CELL_SIZE: f64 = 4.0;
+const GRID_CELLS: usize = ((REGION_SIZE + 2.0 * REGION_MARGIN) / CELL_SIZE) as usize;
+const MARGIN_CELLS: usize = (REGION_MARGIN / CELL_SIZE) as usize;
+const REGION_CELLS: usize = (REGION_SIZE / CELL_SIZE) as usize;
+
+const SPRINGS_PER_REGION: usize = 4;
+const SPRING_MIN_HEIGHT: f32 = 70.0;
+const MAX_RIVER_STEPS: usize = 64;
+const RIVER_DEPTH: f32 = 3.0;
+const RIVER_WATER_DEPTH: f32 = 1.5;
+const RIVER_MIN_RADIUS: f32 = 1.0;
+const RIVER_RADIUS_GROWTH: f32 = 0.03;
+const MAX_LAKE_CELLS: usize = 2048;
+/// Regions kept generated, the least recently sampled ones are dropped beyond that. Each one
+/// takes about 1.6MB, the chunks within load range and their neighbours touch up to 6x6 regions
+const MAX_CACHED_REGIONS: usize = 36;
+
+lazy_static! {
+    static ref REGIONS: Mutex<RegionCache> = Mutex::new(RegionCache::default());
+}
+
+/// Generated regions by seed and region coordinate with the tick they were last sampled at
+#[derive(Default)]
+struct RegionCache {
+    regions: HashMap<(u64, i32, i32), (Arc<HydrologyRegion>, u64)>,
+    tick: u64,
+}
+
+impl Hydrology {
+    pub fn new(seed: u64) -> Self {
+        Self { seed }
+    }
+
+    /// Samples rivers and lakes at the world column `(x, z)`.
+    ///
+    /// `height` has to return the unmodified terrain surface height for a world
+    /// column and must be the same function for every call with the same seed.
+    pub fn sample(&self, x: f64, z: f64, height: &dyn Fn(f64, f64) -> f32) -> WaterSample {
+        let region_x = (x / REGION_SIZE).floor() as i32;
+        let region_z = (z / REGION_SIZE).floor() as i32;
+        let mut sample = WaterSample::default();
+        for dx in -1..=1 {
+            for dz in -1..=1 {
+                let region = self.get_region(region_x + dx, region_z + dz, height);
+                if let Some(region_sample) = region.sample(x, z) {
+                    sample.carve_depth = sample.carve_depth.max(region_sample.carve_depth);
+                    sample.water_level = match (sample.water_level, region_sample.water_level) {
+                        (Some(a), Some(b)) => Some(a.max(b)),
+                        (a, b) => a.or(b),
+                    };
+                }
+            }
+        }
+        sample
+    }
+
+    fn get_region(
+        &self,
+        region_x: i32,
+        region_z: i32,
+        height: &dyn Fn(f64, f64) -> f32,
+    ) -> Arc<HydrologyRegion> {
+        let key = (self.seed, region_x, region_z);
+        if let Some(region) = REGIONS.lock().unwrap().get(&key) {
+            return region;
+        }
+        // generate without holding the lock, other chunk loaders might need different regions
+        let region = Arc::new(HydrologyRegion::generate(
+            self.seed, region_x, region_z, height,
+        ));
+        REGIONS.lock().unwrap().insert(key, region)
+    }
+}
+
+impl RegionCache {
+    fn get(&mut self, key: &(u64, i32, i32)) -> Option<Arc<HydrologyRegion>> {
+        self.tick += 1;
+        let (region, last_used) = self.regions.get_mut(key)?;
+        *last_used = self.tick;
+        Some(region.clone())
+    }
+
+    /// Adds the region unless another loader generated it first, returns the cached one
+    fn insert(
+        &mut self,
+        key: (u64, i32, i32),
+        region: Arc<HydrologyRegion>,
+    ) -> Arc<HydrologyRegion> {
+        self.tick += 1;
+        let tick = self.tick;
+        let (region, last_used) = self.regions.entry(key).or_insert((region, tick));
+        *last_used = tick;
+        let region = region.clone();
+        while self.regions.len() > MAX_CACHED_REGIONS {
+            let Some(oldest) = self
+                .regions
+                .iter()
+                .min_by_key(|(_, (_, last_used))| *last_used)
+                .map(|(key, _)| *key)
+            else {
+                break;
+            };
+            self.regions.remove(&oldest);
+        }
+        region
+    }
+}
+
+impl HydrologyRegion {
+    fn generate(seed: u64, region_x: i32, region_z: i32, height: &dyn Fn(f64, f64) -> f32) -> Self {
+        let origin = (
+            region_x as f64 * REGION_SIZE - REGION_MARGIN,
+            region_z as f64 * REGION_SIZE - REGION_MARGIN,
+        );
+        let mut heights = vec![0.0; GRID_CELLS * GRID_CELLS];
+        for z in 0..GRID_CELLS {
+            for x in 0..GRID_CELLS {
+                heights[z * GRID_CELLS + x] = height(
+                    origin.0 + x as f64 * CELL_SIZE,
+                    origin.1 + z as f64 * CELL_SIZE,
+                );
+            }
+        }
+        let mut region = Self {
+            origin,
+            heights,
+            carve: vec![0.0; GRID_CELLS * GRID_CELLS],
+            water: vec![None; GRID_CELLS * GRID_CELLS],
+        };
+
+        let region_seed = seed
+            ^ (region_x as i64 as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+            ^ (region_z as i64 as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F);
+        let mut rng = StdRng::seed_from_u64(region_seed);
+        for _ in 0..SPRINGS_PER_REGION {
+            // springs always lie inside the region itself, the margin is only for the river to flow into
+            let x = MARGIN_CELLS + rng.gen_range(0..REGION_CELLS);
+            let z = MARGIN_CELLS + rng.gen_range(0..REGION_CELLS);
+            let spring = z * GRID_CELLS + x;
+            if region.heights[spring] >= SPRING_MIN_HEIGHT {
+                region.trace_river(spring);
+            }
+        }
+        region
+    }
+
+    fn sample(&self, x: f64, z: f64) -> Option<WaterSample> {
+        let grid_x = (x - self.origin.0) / CELL_SIZE;
+        let grid_z = (z - self.origin.1) / CELL_SIZE;
+        if grid_x < 0.0
+            || grid_z < 0.0
+            || grid_x >= (GRID_CELLS - 1) as f64
+            || grid_z >= (GRID_CELLS - 1) as f64
+        {
+            return None;
+        }
+        let (cell_x, cell_z) = (grid_x.floor() as usize, grid_z.floor() as usize);
+        let (fx, fz) = (
+            (grid_x - cell_x as f64) as f32,
+            (grid_z - cell_z as f64) as f32,
+        );
+        let index = cell_z * GRID_CELLS + cell_x;
+        let carve_top = self.carve[index] * (1.0 - fx) + self.carve[index + 1] * fx;
+        let carve_bottom =
+            self.carve[index + GRID_CELLS] * (1.0 - fx) + self.carve[index + GRID_CELLS + 1] * fx;
+
+        let nearest = grid_z.round() as usize * GRID_CELLS + grid_x.round() as usize;
+        Some(WaterSample {
+            carve_depth: carve_top * (1.0 - fz) + carve_bottom * fz,
+            water_level: self.water[nearest],
+        })
+    }
+
+    /// Follows the steepest descent from `spring` until the river reaches the sea,
+    /// leaves the region or ends in a basin that does not overflow.
+    fn trace_river(&mut self, spring: usize) {
+        let mut current = spring;
+        let mut visited = HashSet::new();
+        for step in 0..MAX_RIVER_STEPS {
+            let height = self.heights[current];
+            if height <= SEA_LEVEL || !visited.insert(current) {
+                return;
+            }
+            let radius = RIVER_MIN_RADIUS + step as f32 * RIVER_RADIUS_GROWTH;
+            self.carve_river(current, radius, height - RIVER_DEPTH + RIVER_WATER_DEPTH);
+
+            let next = self
+                .neighbours(current)
+                .into_iter()
+                .filter(|neighbour| self.heights[*neighbour] < height)
+                .min_by(|a, b| self.heights[*a].total_cmp(&self.heights[*b]));
+            current = match next {
+                Some(next) => next,
+                None => match self.fill_lake(current) {
+                    Some(spill) => spill,
+                    None => return,
+                },
+            };
+        }
+    }
+
+    fn carve_river(&mut self, center: usize, radius: f32, water_level: f32) {
+        let (center_x, center_z) = ((center % GRID_CELLS) as i32, (center / GRID_CELLS) as i32);
+        let reach = radius.ceil() as i32;
+        for dz in -reach..=reach {
+            for dx in -reach..=reach {
+                let (x, z) = (center_x + dx, center_z + dz);
+                if x < 0 || z < 0 || x >= GRID_CELLS as i32 || z >= GRID_CELLS as i32 {
+                    continue;
+                }
+                let distance = ((dx * dx + dz * dz) as f32).sqrt();
+                if distance > radius {
+                    continue;
+                }
+                let index = z as usize * GRID_CELLS + x as usize;
+                // smooth falloff so the banks blend into the surrounding terrain
+                let falloff = 1.0 - (distance / radius).powi(2);
+                self.carve[index] = self.carve[index].max(RIVER_DEPTH * falloff);
+                self.water[index] =
+                    Some(self.water[index].map_or(water_level, |w| w.max(water_level)));
+            }
+        }
+    }
+
+    /// Fills the basin around `start` with a priority flood and returns the cell
+    /// where the lake spills over, if it does before reaching its maximum size.
+    fn fill_lake(&mut self, start: usize) -> Option<usize> {
+        // heights are never negative, so their bit patterns sort the same way as the values
+        let mut open = BinaryHeap::new();
+        let mut visited = HashSet::new();
+        let mut lake = Vec::new();
+        let mut level = self.heights[start];
+        let mut spill = None;
+        open.push(Reverse((self.heights[start].max(0.0).to_bits(), start)));
+        visited.insert(start);
+        while let Some(Reverse((_, index))) = open.pop() {
+            let height = self.heights[index];
+            if height < level {
+                spill = Some(index);
+                break;
+            }
+            if lake.len() >= MAX_LAKE_CELLS || self.is_edge(index) {
+                break;
+            }
+            level = height;
+            lake.push(index);
+            for neighbour in self.neighbours(index) {
+                if visited.insert(neighbour) {
+                    open.push(Reverse((
+                        self.heights[neighbour].max(0.0).to_bits(),
+                        neighbour,
+                    )));
+                }
+            }
+        }
+        if level <= SEA_LEVEL {
+            return spill;
+        }
+        for index in lake {
+            self.water[index] = Some(self.water[index].map_or(level, |w| w.max(level)));
+        }
+        spill
+    }
+
+    fn is_edge(&self, index: usize) -> bool {
+        let (x, z) = (index % GRID_CELLS, index / GRID_CELLS);
+        x == 0 || z == 0 || x == GRID_CELLS - 1 || z == GRID_CELLS - 1
+    }
+
+    fn neighbours(&self, index: usize) -> Vec<usize> {
+        let (x, z) = ((index % GRID_CELLS) as i32, (index / GRID_CELLS) as i32);
+        let mut neighbours = Vec::with_capacity(8);
+        for dz in -1..=1 {
+            for dx in -1..=1 {
+                if dx == 0 && dz == 0 {
+                    continue;
+                }
+                let (nx, nz) = (x + dx, z + dz);
+                if nx >= 0 && nz >= 0 && nx < GRID_CELLS as i32 && nz < GRID_CELLS as i32 {
+                    neighbours.push(nz as usize * GRID_CELLS + nx as usize);
+                }
+            }
+        }
+        neighbours
+    }
+}
@@ -0,0 +1,25 @@
+mod hydrology;
+
+/// Height below which terrain is considered ocean. Rivers end here.
+pub const SEA_LEVEL: f32 = 50.0;
+
+/// Rivers and lakes are generated per region so that every chunk overlapping
+/// a region sees the same water, independent of the order chunks are loaded in.
+pub struct Hydrology {
+    seed: u64,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct WaterSample {
+    /// How far the terrain surface is lowered by a river bed at this column
+    pub carve_depth: f32,
+    /// Height of the water surface at this column, if any
+    pub water_level: Option<f32>,
+}
+
+struct HydrologyRegion {
+    origin: (f64, f64),
+    heights: Vec<f32>,
+    carve: Vec<f32>,
+    water: Vec<Option<f32>>,
+}
@@ -0,0 +1,272 @@
+use std::f32::consts::{FRAC_1_SQRT_2, TAU};
+
+use cgmath::Point3;
+use gl::types::GLint;
+
+use crate::terrain::{CHUNK_SIZE, CHUNK_SIZE_FLOAT};
+
+use super::{ChunkLightmap, Lightmap, LIGHTMAP_SCALE};
+
+/// Directions above the horizon the sky is traced in
+const SKY_DIRECTIONS: usize = 8;
+/// Texels along a sky direction are skipped once less light than this passes through
+const MIN_TRANSMITTANCE: f32 = 0.01;
+
+impl ChunkLightmap {
+    /// Bakes the lightmap of a `chunk_size` wide chunk from `is_solid`, which is called with the
+    /// chunk space position of every block. Below the chunk is treated as solid and above it as
+    /// open sky, neighbouring chunks are not taken into account
+    pub fn bake<F: Fn(usize, usize, usize) -> bool>(chunk_size: usize, is_solid: F) -> Self {
+        let size = chunk_size / LIGHTMAP_SCALE;
+        let index = |x: usize, y: usize, z: usize| x + size * (y + size * z);
+        let block_weight = 1.0 / (LIGHTMAP_SCALE * LIGHTMAP_SCALE * LIGHTMAP_SCALE) as f32;
+
+        // fraction of solid blocks in every texel
+        let mut density = vec![0.0f32; size * size * size];
+        for x in 0..size * LIGHTMAP_SCALE {
+            for y in 0..size * LIGHTMAP_SCALE {
+                for z in 0..size * LIGHTMAP_SCALE {
+                    if is_solid(x, y, z) {
+                        density
+                            [index(x / LIGHTMAP_SCALE, y / LIGHTMAP_SCALE, z / LIGHTMAP_SCALE)] +=
+                            block_weight;
+                    }
+                }
+            }
+        }
+        // texels beside the chunk repeat its border
+        let get_density = |x: i32, y: i32, z: i32| {
+            if y < 0 {
+                1.0
+            } else if y >= size as i32 {
+                0.0
+            } else {
+                let max = size as i32 - 1;
+                density[index(
+                    x.clamp(0, max) as usize,
+                    y as usize,
+                    z.clamp(0, max) as usize,
+                )]
+            }
+        };
+
+        let mut directions = vec![([0.0, 1.0, 0.0], 1.0)];
+        for direction in 0..SKY_DIRECTIONS {
+            let (sin, cos) = (direction as f32 * TAU / SKY_DIRECTIONS as f32).sin_cos();
+            // 45 degrees above the horizon, weighted by the cosine to the zenith
+            directions.push((
+                [cos * FRAC_1_SQRT_2, FRAC_1_SQRT_2, sin * FRAC_1_SQRT_2],
+                FRAC_1_SQRT_2,
+            ));
+        }
+        let total_weight: f32 = directions.iter().map(|(_, weight)| weight).sum();
+
+        let mut data = vec![[0u8; 2]; size * size * size];
+        for x in 0..size as i32 {
+            for y in 0..size as i32 {
+                for z in 0..size as i32 {
+                    let mut occlusion = 0.0;
+                    for dx in -1..=1 {
+                        for dy in -1..=1 {
+                            for dz in -1..=1 {
+                                occlusion += get_density(x + dx, y + dy, z + dz);
+                            }
+                        }
+                    }
+                    // open ground, where a third of the neighbourhood is solid, is unoccluded
+                    let ambient = (1.5 * (1.0 - occlusion / 27.0)).clamp(0.0, 1.0);
+
+                    let mut sky = 0.0;
+                    for (direction, weight) in &directions {
+                        let mut transmittance = 1.0;
+                        let mut distance = 1.0;
+                        while transmittance > MIN_TRANSMITTANCE {
+                            let sample = [
+                                x as f32 + 0.5 + direction[0] * distance,
+                                y as f32 + 0.5 + direction[1] * distance,
+                                z as f32 + 0.5 + direction[2] * distance,
+                            ];
+                            // light reaches the texel once the trace leaves the chunk
+                            if sample[1] >= size as f32
+                                || sample[0] < 0.0
+                                || sample[2] < 0.0
+                                || sample[0] >= size as f32
+                                || sample[2] >= size as f32
+                            {
+                                break;
+                            }
+                            transmittance *= 1.0
+                                - get_density(sample[0] as i32, sample[1] as i32, sample[2] as i32);
+                            distance += 1.0;
+                        }
+                        sky += transmittance * weight;
+                    }
+                    data[index(x as usize, y as usize, z as usize)] = [
+                        (ambient * 255.0) as u8,
+                        (sky / total_weight * 255.0).clamp(0.0, 255.0) as u8,
+                    ];
+                }
+            }
+        }
+
+        // surfaces are sampled from both sides, solid texels take the light of the open texels
+        // next to them so faces are not darkened by the ground behind them
+        let baked = data.clone();
+        for x in 0..size {
+            for y in 0..size {
+                for z in 0..size {
+                    if density[index(x, y, z)] < 1.0 {
+                        continue;
+                    }
+                    let mut light = [0u8; 2];
+                    for (dx, dy, dz) in [
+                        (-1, 0, 0),
+                        (1, 0, 0),
+                        (0, -1, 0),
+                        (0, 1, 0),
+                        (0, 0, -1),
+                        (0, 0, 1),
+                    ] {
+                        let (nx, ny, nz) = (x as i32 + dx, y as i32 + dy, z as i32 + dz);
+                        if [nx, ny, nz].iter().any(|n| *n < 0 || *n >= size as i32) {
+                            continue;
+                        }
+                        let neighbour = index(nx as usize, ny as usize, nz as usize);
+                        if density[neighbour] < 1.0 {
+                            light[0] = light[0].max(baked[neighbour][0]);
+                            light[1] = light[1].max(baked[neighbour][1]);
+                        }
+                    }
+                    data[index(x, y, z)] = light;
+                }
+            }
+        }
+
+        Self { size, data }
+    }
+
+    pub fn get_size(&self) -> usize {
+        self.size
+    }
+
+    /// Ambient occlusion and sky visibility of the texel at `(x, y, z)`, 1 for open
+    pub fn get(&self, x: usize, y: usize, z: usize) -> (f32, f32) {
+        let [ambient, sky] = self.data[x + self.size * (y + self.size * z)];
+        (ambient as f32 / 255.0, sky as f32 / 255.0)
+    }
+}
+
+impl Lightmap {
+    /// Creates a lightmap covering the chunks up to `chunk_radius` around the origin at height 0,
+    /// the texels of chunks that were not baked yet are open
+    pub fn new(chunk_radius: usize) -> Self {
+        let chunk_texels = CHUNK_SIZE / LIGHTMAP_SCALE;
+        let chunks = 2 * chunk_radius + 1;
+        let size = [chunks * chunk_texels, chunk_texels, chunks * chunk_texels];
+        let origin = Point3::new(
+            -(chunk_radius as f32) * CHUNK_SIZE_FLOAT,
+            0.0,
+            -(chunk_radius as f32) * CHUNK_SIZE_FLOAT,
+        );
+        let mut texture = 0;
+        let open: [u8; 2] = [255, 255];
+        unsafe {
+            gl::GenTextures(1, &mut texture);
+            gl::BindTexture(gl::TEXTURE_3D, texture);
+            gl::TexStorage3D(
+                gl::TEXTURE_3D,
+                1,
+                gl::RG8,
+                size[0] as i32,
+                size[1] as i32,
+                size[2] as i32,
+            );
+            gl::TexParameteri(gl::TEXTURE_3D, gl::TEXTURE_MIN_FILTER, gl::LINEAR as GLint);
+            gl::TexParameteri(gl::TEXTURE_3D, gl::TEXTURE_MAG_FILTER, gl::LINEAR as GLint);
+            for wrap in [gl::TEXTURE_WRAP_S, gl::TEXTURE_WRAP_T, gl::TEXTURE_WRAP_R] {
+                gl::TexParameteri(gl::TEXTURE_3D, wrap, gl::CLAMP_TO_EDGE as GLint);
+            }
+            gl::ClearTexImage(
+                texture,
+                0,
+                gl::RG,
+                gl::UNSIGNED_BYTE,
+                open.as_ptr() as *const _,
+            );
+            gl::BindTexture(gl::TEXTURE_3D, 0);
+        }
+        Self {
+            texture,
+            origin,
+            size,
+        }
+    }
+
+    /// Uploads the lightmap of the chunk at the world position `chunk_position`, chunks outside
+    /// of the covered area are ignored
+    pub fn insert(&self, chunk_position: Point3<f32>, lightmap: &ChunkLightmap) {
+        let offset = [
+            (chunk_position.x - self.origin.x) / LIGHTMAP_SCALE as f32,
+            (chunk_position.y - self.origin.y) / LIGHTMAP_SCALE as f32,
+            (chunk_position.z - self.origin.z) / LIGHTMAP_SCALE as f32,
+        ];
+        if offset
+            .iter()
+            .zip(self.size)
+            .any(|(offset, size)| *offset < 0.0 || *offset as usize + lightmap.size > size)
+        {
+            return;
+        }
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_3D, self.texture);
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+            gl::TexSubImage3D(
+                gl::TEXTURE_3D,
+                0,
+                offset[0] as i32,
+                offset[1] as i32,
+                offset[2] as i32,
+                lightmap.size as i32,
+                lightmap.size as i32,
+                lightmap.size as i32,
+                gl::RG,
+                gl::UNSIGNED_BYTE,
+                lightmap.data.as_ptr() as *const _,
+            );
+            gl::PixelStorei(gl::UNPACK_ALIGNMENT, 4);
+            gl::BindTexture(gl::TEXTURE_3D, 0);
+        }
+    }
+
+    pub fn bind(&self, unit: u32) {
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0 + unit);
+            gl::BindTexture(gl::TEXTURE_3D, self.texture);
+        }
+    }
+
+    pub fn unbind(&self, unit: u32) {
+        unsafe {
+            gl::ActiveTexture(gl::TEXTURE0 + unit);
+            gl::BindTexture(gl::TEXTURE_3D, 0);
+        }
+    }
+
+    /// World position of the corner of the first texel
+    pub fn get_origin(&self) -> Point3<f32> {
+        self.origin
+    }
+
+    /// World size of the covered area
+    pub fn get_extent(&self) -> [f32; 3] {
+        self.size.map(|size| (size * LIGHTMAP_SCALE) as f32)
+    }
+}
+
+impl Drop for Lightmap {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteTextures(1, &self.texture);
+        }
+    }
+}
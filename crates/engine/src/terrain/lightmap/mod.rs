@@ -0,0 +1,25 @@
+use cgmath::Point3;
+use gl::types::GLuint;
+
+mod lightmap;
+
+/// Number of blocks along every axis of a lightmap texel
+pub const LIGHTMAP_SCALE: usize = 4;
+
+/// Ambient occlusion and sky visibility of a chunk, baked on the loader threads at one texel per
+/// `LIGHTMAP_SCALE` blocks
+pub struct ChunkLightmap {
+    /// Number of texels along every axis
+    size: usize,
+    /// Occlusion and sky visibility of every texel, 0 for dark and 255 for open, x varies fastest
+    data: Vec<[u8; 2]>,
+}
+
+/// 3D texture holding the baked lightmaps of all chunks around the origin, sampled in world space
+/// by the terrain shader since the chunks are drawn in a single batch
+pub struct Lightmap {
+    texture: GLuint,
+    origin: Point3<f32>,
+    /// Number of texels along every axis
+    size: [usize; 3],
+}
@@ -3,9 +3,12 @@
 in vec3 Color;
 in vec3 Normal;
 in vec3 toLightVector;
+in vec3 WorldPosition;
 
 out vec4 FragColor;
 
+#include "environment.glsl"
+
 void main() {
     vec3 unitNormal = normalize(Normal);
     vec3 normal = unitNormal;
@@ -16,5 +19,5 @@ void main() {
     float intensity = dot(normal, unitToLightVector);
     float brightness = max(intensity, 0.5);
     vec3 diffuse = brightness * vec3(1.0);
-    FragColor = vec4(Color * diffuse, 1.0);
+    FragColor = vec4(applyFog(Color * diffuse, WorldPosition), 1.0);
 }
\ No newline at end of file
@@ -1,18 +1,26 @@
 use core::panic;
+use std::sync::Arc;
 
 use cgmath::{InnerSpace, Matrix4, Point3, Vector3, Zero};
 use gl::types::GLuint;
-use glfw::{Glfw, MouseButton, WindowEvent};
+use glfw::{Glfw, WindowEvent};
 use libnoise::prelude::*;
 use ndarray::ArrayBase;
 
 use crate::{
     core::{
-        entity::{component::Component, Entity},
-        renderer::{line::Line, shader::VertexAttributes, texture::Texture},
+        entity::{
+            component::{Component, InspectableComponent},
+            Entity,
+        },
+        event::EventCategory,
+        renderer::{
+            environment::include_environment, shader::VertexAttributes,
+            shader_manager::ShaderSource, texture::Texture,
+        },
         scene::Scene,
     },
-    terrain::{Chunk, ChunkBounds, Terrain, CHUNK_SIZE_FLOAT},
+    terrain::{world_gen::WorldGenConfig, Chunk, ChunkBounds, Terrain, CHUNK_SIZE_FLOAT},
 };
 
 use super::{ChunkMesh, MarchingCubesChunk, Vertex, CHUNK_SIZE, EDGES, POINTS, TRIANGULATIONS};
@@ -20,7 +28,7 @@ use super::{ChunkMesh, MarchingCubesChunk, Vertex, CHUNK_SIZE, EDGES, POINTS, TR
 impl MarchingCubesChunk {
     fn generate_mesh(&self) -> ChunkMesh<Vertex> {
         let mut vertices = Vec::<Vertex>::new();
-        let isovalue = 0.3;
+        let isovalue = self.iso_value;
         for z in 0..CHUNK_SIZE {
             for y in 0..CHUNK_SIZE {
                 for x in 0..CHUNK_SIZE {
@@ -129,11 +137,20 @@ impl MarchingCubesChunk {
 }
 
 impl Chunk for MarchingCubesChunk {
-    fn new(seed: u64, position: (f32, f32, f32), _: usize) -> Self {
-        let generator = Source::perlin(seed).scale([0.003; 2]);
-        let hills = Source::perlin(seed).scale([0.01; 2]);
-        let tiny_hills = Source::perlin(seed).scale([0.1; 2]);
-        let cave = Source::perlin(seed).scale([0.1; 3]);
+    type Vertex = Vertex;
+
+    fn new(config: &WorldGenConfig, position: (f32, f32, f32), _: usize) -> Self {
+        let height_layers: Vec<_> = config
+            .height_layers
+            .iter()
+            .map(|layer| {
+                (
+                    Source::perlin(config.seed).scale([layer.frequency; 2]),
+                    layer.amplitude,
+                )
+            })
+            .collect();
+        let cave = Source::perlin(config.seed).scale([config.cave_frequency; 3]);
         let offset: f64 = 16777216.0;
         let blocks: ArrayBase<ndarray::OwnedRepr<f32>, ndarray::Dim<[usize; 3]>> =
             ArrayBase::from_shape_fn(
@@ -145,15 +162,13 @@ impl Chunk for MarchingCubesChunk {
                         (position.2 * CHUNK_SIZE as f32) as f64 + z as f64 + offset,
                     );
 
-                    let noise_value =
-                        (1.0 + generator.sample([sample_point.0, sample_point.2])) / 2.0;
-                    let hills_value =
-                        (1.0 + hills.sample([sample_point.0, sample_point.2])) / 2.0 * 0.2;
-                    let tiny_hills_value =
-                        (1.0 + tiny_hills.sample([sample_point.0, sample_point.2])) / 2.0 * 0.01;
-                    if ((noise_value + hills_value + tiny_hills_value) * CHUNK_SIZE as f64)
-                        < y as f64
-                    {
+                    let height: f64 = height_layers
+                        .iter()
+                        .map(|(noise, amplitude)| {
+                            (1.0 + noise.sample([sample_point.0, sample_point.2])) / 2.0 * amplitude
+                        })
+                        .sum();
+                    if (height * CHUNK_SIZE as f64) < y as f64 {
                         return 0.0;
                     }
                     (1.0 + cave.sample([sample_point.0, sample_point.1, sample_point.2]) as f32)
@@ -163,16 +178,17 @@ impl Chunk for MarchingCubesChunk {
         let mut chunk = Self {
             position,
             blocks,
+            iso_value: config.iso_value,
             mesh: None,
         };
         chunk.mesh = Some(chunk.generate_mesh());
         chunk
     }
 
-    fn buffer_data(&mut self) {
-        if let Some(mesh) = &mut self.mesh {
-            mesh.buffer_data();
-        }
+    fn buffer_data(&mut self) {}
+
+    fn get_mesh(&self) -> Option<&ChunkMesh<Vertex>> {
+        self.mesh.as_ref()
     }
 
     fn get_bounds(&self) -> ChunkBounds {
@@ -190,10 +206,6 @@ impl Chunk for MarchingCubesChunk {
         }
     }
 
-    fn process_line(&mut self, _: &Line, _: &MouseButton) -> bool {
-        false
-    }
-
     fn get_position(&self) -> Point3<f32> {
         Point3::new(
             self.position.0 * CHUNK_SIZE_FLOAT,
@@ -202,17 +214,28 @@ impl Chunk for MarchingCubesChunk {
         )
     }
 
-    fn get_shader_source() -> (String, String) {
+    fn get_shader_source() -> (ShaderSource, ShaderSource) {
         (
-            include_str!("vertex.glsl").to_string(),
-            include_str!("fragment.glsl").to_string(),
+            ShaderSource::new(
+                "terrain/marching_cubes/vertex.glsl",
+                include_str!("vertex.glsl"),
+            ),
+            ShaderSource::new(
+                "terrain/marching_cubes/fragment.glsl",
+                include_str!("fragment.glsl"),
+            )
+            .preprocess(include_environment),
         )
     }
 
-    fn get_textures() -> Vec<Texture> {
+    fn get_textures() -> Vec<Arc<Texture>> {
         Vec::new()
     }
 
+    fn get_buffer_size(&self) -> usize {
+        self.mesh.as_ref().map_or(0, |mesh| mesh.get_buffer_size())
+    }
+
     fn get_triangle_count(&self) -> usize {
         if let Some(mesh) = &self.mesh {
             mesh.get_triangle_count()
@@ -242,45 +265,33 @@ impl Chunk for MarchingCubesChunk {
     }
 }
 
+impl InspectableComponent for MarchingCubesChunk {
+    fn get_inspector_name(&self) -> &str {
+        "Chunk"
+    }
+}
+
 impl Component for MarchingCubesChunk {
     fn update(&mut self, _: &mut Scene, _: &mut Entity, _: f64) {}
 
-    fn render(
-        &self,
-        scene: &Scene,
-        _: &Entity,
-        view_projection: &Matrix4<f32>,
-        parent_transform: &Matrix4<f32>,
-    ) {
-        if let Some(terrain) = scene.get_component::<Terrain<MarchingCubesChunk>>() {
-            let shader = terrain.get_shader();
-            if let Some(mesh) = &self.mesh {
-                if !mesh.is_buffered() {
-                    panic!("Mesh is not buffered");
-                }
-                shader.bind();
-                shader.set_uniform_mat4("viewProjection", &view_projection);
-                unsafe {
-                    gl::Enable(gl::CULL_FACE);
-                }
-                mesh.render(
-                    &shader,
-                    &(parent_transform
-                        * Matrix4::from_translation(Vector3::new(
-                            self.position.0 * CHUNK_SIZE_FLOAT,
-                            self.position.1 * CHUNK_SIZE_FLOAT,
-                            self.position.2 * CHUNK_SIZE_FLOAT,
-                        ))),
-                    None,
-                );
-                unsafe {
-                    gl::Disable(gl::CULL_FACE);
-                }
-            }
-        }
+    /// The mesh is drawn by the terrain's batch
+    fn render(&self, _: &Scene, _: &Entity, _: &Matrix4<f32>, _: &Matrix4<f32>) {}
+
+    fn get_event_priority(&self, _: EventCategory) -> Option<i32> {
+        None
     }
 
-    fn handle_event(&mut self, _: &mut Glfw, _: &mut glfw::Window, _: &WindowEvent) {}
+    fn handle_event(&mut self, _: &mut Glfw, _: &mut glfw::Window, _: &WindowEvent) -> bool {
+        false
+    }
+
+    fn as_inspectable(&self) -> Option<&dyn InspectableComponent> {
+        Some(self)
+    }
+
+    fn as_inspectable_mut(&mut self) -> Option<&mut dyn InspectableComponent> {
+        Some(self)
+    }
 }
 
 impl VertexAttributes for Vertex {
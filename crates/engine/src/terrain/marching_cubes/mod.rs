@@ -9,6 +9,7 @@ const CHUNK_SIZE: usize = 128;
 pub struct MarchingCubesChunk {
     position: (f32, f32, f32),
     blocks: ArrayBase<ndarray::OwnedRepr<f32>, ndarray::Dim<[usize; 3]>>,
+    iso_value: f32,
     mesh: Option<ChunkMesh<Vertex>>,
 }
 
@@ -7,16 +7,33 @@ layout (location = 2) in vec3 color;
 out vec3 Normal;
 out vec3 Color;
 out vec3 toLightVector;
+out vec3 WorldPosition;
 
 uniform vec3 lightPosition;
 uniform mat4 model;
+// set while the terrain draws its chunk batch, each draw is offset by the origin of its chunk
+uniform bool batched;
 uniform mat4 viewProjection;
 
+layout (std430, binding = 4) readonly buffer ChunkOrigins {
+    vec4 chunkOrigins[];
+};
+// set while the debug controller tints the chunks by their level of detail, which the batch
+// stores in the w component of the chunk origins
+uniform bool lodColoring;
+
+vec3 lodTint(uint lod) {
+    const vec3 tints[4] = vec3[](vec3(1.0, 0.4, 0.4), vec3(0.4, 1.0, 0.4), vec3(0.4, 0.6, 1.0), vec3(1.0, 1.0, 0.4));
+    return tints[lod % 4u];
+}
+
 void main()
 {
-    vec4 worldPosition = model * vec4(position, 1.0);
+    vec3 localPosition = batched ? position + chunkOrigins[gl_DrawID].xyz : position;
+    vec4 worldPosition = model * vec4(localPosition, 1.0);
     gl_Position = viewProjection * worldPosition;
     Normal = normals;
-    Color = color;
+    Color = batched && lodColoring ? color * lodTint(uint(chunkOrigins[gl_DrawID].w)) : color;
     toLightVector = lightPosition - worldPosition.xyz;
+    WorldPosition = worldPosition.xyz;
 }
\ No newline at end of file
@@ -0,0 +1,73 @@
+use crate::terrain::{
+    deltas::ChunkDeltas,
+    world_gen::{WorldGenConfig, GENERATOR_VERSION},
+};
+
+use super::{MigrationHook, MigrationPolicy, WorldMigration};
+
+impl WorldMigration {
+    pub fn new() -> Self {
+        Self {
+            hooks: Default::default(),
+        }
+    }
+
+    /// Runs `hook` when a world is regenerated across `version`, e.g. to carry over changes
+    /// whose meaning changed with the generator. Setting a chunk's `generator_version` to None
+    /// moves it to the current generator
+    pub fn add_hook(
+        &mut self,
+        version: u32,
+        hook: impl Fn(&mut WorldGenConfig, &mut ChunkDeltas) + 'static,
+    ) {
+        self.hooks
+            .entry(version)
+            .or_default()
+            .push(Box::new(hook) as MigrationHook);
+    }
+
+    /// Selects the generator versions `config` generates with for the world saved in `deltas`
+    /// according to `config.migration_policy`. With `Regenerate` the changed chunks are pinned
+    /// to the saved version, the hooks of the versions in between run and the deltas are moved
+    /// to `GENERATOR_VERSION`. Returns true if the deltas changed and should be saved again
+    pub fn migrate(&self, config: &mut WorldGenConfig, deltas: &mut ChunkDeltas) -> bool {
+        let saved_version = deltas.get_generator_version();
+        let migrated = saved_version < GENERATOR_VERSION
+            && config.migration_policy == MigrationPolicy::Regenerate;
+        if migrated {
+            log::info!(
+                "Migrating world from generator version {} to {}",
+                saved_version,
+                GENERATOR_VERSION
+            );
+            for (_, delta) in deltas.get_chunks_mut() {
+                delta.generator_version.get_or_insert(saved_version);
+            }
+            for hooks in self
+                .hooks
+                .range(saved_version + 1..=GENERATOR_VERSION)
+                .map(|(_, hooks)| hooks)
+            {
+                for hook in hooks {
+                    hook(config, deltas);
+                }
+            }
+            deltas.set_generator_version(GENERATOR_VERSION);
+        } else if saved_version < GENERATOR_VERSION {
+            log::info!(
+                "World is locked to generator version {}, the current one is {}",
+                saved_version,
+                GENERATOR_VERSION
+            );
+        }
+        config.generator_version = deltas.get_generator_version().min(GENERATOR_VERSION);
+        config.pinned_chunks = deltas.get_pinned_chunks();
+        migrated
+    }
+}
+
+impl Default for WorldMigration {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,27 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::{deltas::ChunkDeltas, world_gen::WorldGenConfig};
+
+mod migration;
+
+/// What happens to a world saved with an older generator than `GENERATOR_VERSION`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MigrationPolicy {
+    /// Untouched chunks are generated with the current generator, chunks with changes keep the
+    /// generator they were changed under so the changes still line up
+    #[default]
+    Regenerate,
+    /// The whole world keeps being generated with the generator it was saved with
+    Lock,
+}
+
+pub type MigrationHook = Box<dyn Fn(&mut WorldGenConfig, &mut ChunkDeltas)>;
+
+/// Brings a world's config and saved changes to the current generator before the terrain is
+/// created, see `WorldMigration::migrate`
+pub struct WorldMigration {
+    /// Hooks keyed by the generator version they migrate to
+    hooks: BTreeMap<u32, Vec<MigrationHook>>,
+}
@@ -1,44 +1,299 @@
-use std::sync::mpsc;
+use std::{
+    collections::{HashMap, VecDeque},
+    marker::PhantomData,
+    path::PathBuf,
+    sync::{atomic::AtomicBool, mpsc, Arc, Mutex},
+};
 
 use cgmath::Point3;
+use gl::types::GLuint;
 use glfw::MouseButton;
 
 use crate::core::{
+    entity::EntityHandle,
     mouse_picker::MousePicker,
     renderer::{
         line::Line,
+        render_workers::Recording,
         shader::{DynamicVertexArray, Shader, VertexAttributes},
+        shader_manager::ShaderSource,
         texture::Texture,
     },
+    save_worker::SaveEvent,
 };
 
+use codec::CodecError;
+use deltas::{ChunkDeltas, PlacedBlueprint};
+use lightmap::{ChunkLightmap, Lightmap};
+use resources::Ore;
+use structures::{PendingStructures, StructureBlock};
+use vegetation::VegetationInstance;
+use world_gen::WorldGenConfig;
+
 pub const CHUNK_RADIUS: usize = 5;
 pub const CHUNK_SIZE: usize = 128;
 pub const CHUNK_SIZE_FLOAT: f32 = CHUNK_SIZE as f32;
-pub const USE_LOD: bool = false;
 
+/// When set, the terrain is drawn as wireframe while everything else stays filled
+pub static WIREFRAME: AtomicBool = AtomicBool::new(false);
+/// When set, chunks are tinted by the level of detail they were generated with
+pub static LOD_COLORING: AtomicBool = AtomicBool::new(false);
+
+pub mod biome;
+pub mod block_light;
+pub mod blueprint;
+mod chunk_batch;
+mod chunk_grid;
+pub mod codec;
+pub mod deltas;
 pub mod dual_contouring;
+pub mod hydrology;
+pub mod lightmap;
 pub mod marching_cubes;
+pub mod migration;
+pub mod resources;
+mod seams;
+pub mod structures;
+pub mod surface;
 mod terrain;
+pub mod vegetation;
 pub mod voxel;
+pub mod world_gen;
+
+/// Where chunk meshes are generated, chunks that have no gpu path always mesh on the cpu
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MeshingBackend {
+    #[default]
+    Cpu,
+    Gpu,
+    /// Chunks are created with `Chunk::new_data_only` and never meshed, for a net `Server`
+    /// that only keeps their blocks
+    DataOnly,
+}
 
 pub struct Terrain<T: Chunk> {
     chunk_receiver: mpsc::Receiver<T>,
+    /// Generated chunks waiting for their meshes to be uploaded to the GPU
+    upload_queue: VecDeque<T>,
+    /// Static meshes of all uploaded chunks, drawn together in `render`
+    batch: ChunkBatch<T::Vertex>,
+    /// Draws of the batch for the current frame, recorded in `record` and submitted by every
+    /// pass drawing the terrain
+    frame_draws: Mutex<FrameDraws>,
     shader: Shader,
-    textures: Vec<Texture>,
+    textures: Vec<Arc<Texture>>,
     mouse_picker: MousePicker,
+    config: Arc<WorldGenConfig>,
+    meshing_backend: MeshingBackend,
+    /// Uploaded chunks keyed by their chunk coordinates
+    chunks: ChunkGrid,
+    /// Incremented for every change of the loaded chunks
+    version: u64,
+    subscribers: Vec<mpsc::Sender<ChunkEvent>>,
+    /// Baked lighting of the uploaded chunks, created with the first chunk that has a lightmap
+    lightmap: Option<Lightmap>,
+    /// Changes to the generated chunks, applied to every chunk as it is uploaded
+    deltas: ChunkDeltas,
+    /// World positions and search radii of props to remove in the next update
+    pending_prop_removals: Vec<(Point3<f32>, f32)>,
+    /// Blueprints to place into the loaded chunks in the next update
+    pending_blueprints: Vec<PlacedBlueprint>,
+    /// Structure blocks the chunk loaders pass between neighbouring chunks
+    structures: Arc<Mutex<PendingStructures>>,
+    /// Blocks set with `edit_blocks` per chunk, waiting for the next update or for the chunk to
+    /// be uploaded
+    pending_edits: HashMap<ChunkCoord, Vec<StructureBlock>>,
+    /// Chunks restored with `restore_chunk`, waiting for the next update or to replace the
+    /// generated chunk when it is uploaded. Only kept for chunks within `chunk_radius`, which
+    /// are all loaded eventually
+    pending_snapshots: HashMap<ChunkCoord, T>,
+    /// Clicks of the mouse picker that dig or place a block in the next update
+    pending_lines: Vec<(Line, MouseButton)>,
+    /// File the deltas are saved to whenever they change, see `Terrain::set_save_path`
+    save_path: Option<PathBuf>,
+    /// Outcomes of the saves, the ones for `save_path` are emitted on the scene's event bus
+    save_events: Option<mpsc::Receiver<SaveEvent>>,
+}
+
+/// Chunk the terrain has uploaded, for systems like physics, navmeshes or minimaps that follow
+/// the loaded terrain without reaching into its entities
+#[derive(Clone, Debug)]
+pub struct LoadedChunk {
+    pub coord: ChunkCoord,
+    pub position: Point3<f32>,
+    pub bounds: ChunkBounds,
+    /// Terrain version the chunk last changed in, see `Terrain::get_version`
+    pub version: u64,
+    /// Child of the terrain entity that holds the chunk component and with it the mesh
+    pub entity: EntityHandle,
+    pub triangle_count: usize,
+}
+
+/// Position of a chunk on the chunk grid, its world position divided by `CHUNK_SIZE`
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct ChunkCoord {
+    pub x: i32,
+    pub y: i32,
+    pub z: i32,
+}
+
+/// Loaded chunks keyed by their chunk coordinates, for cheap lookups of the chunk at a position
+/// and of the neighbours of a chunk. Iterates in the order the chunks were inserted
+#[derive(Default)]
+pub struct ChunkGrid {
+    chunks: Vec<LoadedChunk>,
+    /// Index into `chunks` per coordinate
+    indices: HashMap<ChunkCoord, usize>,
+}
+
+/// Change of the loaded chunks, sent to the receivers returned by `Terrain::subscribe` and
+/// emitted on the scene's event bus
+#[derive(Clone, Debug)]
+pub enum ChunkEvent {
+    Loaded(LoadedChunk),
+    /// A scattered prop was removed, `id` is its index in the chunk's scattered instances
+    PropRemoved {
+        chunk: Point3<f32>,
+        id: u32,
+        position: Point3<f32>,
+    },
+    /// The blocks and with them the mesh of a loaded chunk changed, e.g. by an edit or a dig.
+    /// `version` is the terrain version it changed in, see `LoadedChunk::version`
+    Modified {
+        coord: ChunkCoord,
+        version: u64,
+    },
+}
+
+/// Emitted on the scene's event bus when the contents of a loaded chunk change, e.g. when a
+/// prop in it was removed
+#[derive(Clone, Debug)]
+pub struct ChunkModified {
+    pub bounds: ChunkBounds,
+}
+
+/// Emitted on the scene's event bus when a block holding ore was removed from a loaded chunk,
+/// e.g. by digging it
+#[derive(Clone, Copy, Debug)]
+pub struct OreMined {
+    pub ore: Ore,
+    /// Amount of the ore the block yields, see `Ore::get_yield`
+    pub amount: u32,
+    pub chunk: ChunkCoord,
+}
+
+/// Border vertex of a chunk that the neighbouring chunk does not continue, found by
+/// `Terrain::find_seams`
+#[derive(Clone, Debug)]
+pub struct Seam {
+    pub position: Point3<f32>,
+    pub kind: SeamKind,
+    /// Distance to the closest vertex of the neighbour for position seams, angle between the
+    /// normals in radians for normal seams
+    pub error: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SeamKind {
+    /// The neighbour has no vertex at the position
+    Position,
+    /// The neighbour has a vertex at the position but its normal points elsewhere
+    Normal,
 }
 
 pub trait Chunk {
-    fn new(seed: u64, position: (f32, f32, f32), lod: usize) -> Self;
+    type Vertex: VertexAttributes + Clone;
+
+    fn new(config: &WorldGenConfig, position: (f32, f32, f32), lod: usize) -> Self;
+    /// Creates the chunk without meshing it when the terrain uses `MeshingBackend::Gpu`,
+    /// `generate_gpu_mesh` is called on the render thread afterwards
+    fn new_deferred(config: &WorldGenConfig, position: (f32, f32, f32), lod: usize) -> Self
+    where
+        Self: Sized,
+    {
+        Self::new(config, position, lod)
+    }
+    /// Creates the chunk with its blocks only, without lighting or meshing it, for a net
+    /// `Server` that never renders it. Chunks without a separate path are created with `new`
+    fn new_data_only(config: &WorldGenConfig, position: (f32, f32, f32), lod: usize) -> Self
+    where
+        Self: Sized,
+    {
+        Self::new(config, position, lod)
+    }
+    fn generate_gpu_mesh(&mut self) {}
+    /// Buffers the meshes the chunk draws itself, the static mesh is batched by the terrain
     fn buffer_data(&mut self);
+    /// Static mesh in chunk space that the terrain copies into its batch on upload
+    fn get_mesh(&self) -> Option<&ChunkMesh<Self::Vertex>>;
     fn get_bounds(&self) -> ChunkBounds;
-    fn process_line(&mut self, line: &Line, button: &MouseButton) -> bool;
     fn get_position(&self) -> Point3<f32>;
-    fn get_shader_source() -> (String, String);
-    fn get_textures() -> Vec<Texture>;
+    fn get_shader_source() -> (ShaderSource, ShaderSource);
+    /// Bound to the texture units in order, shared through the `AssetManager`
+    fn get_textures() -> Vec<Arc<Texture>>;
     fn get_triangle_count(&self) -> usize;
+    /// Level of detail the chunk was requested with, 0 for chunks without levels of detail
+    fn get_lod(&self) -> usize {
+        0
+    }
+    /// Grass, rocks and trees to place on the chunk once it is uploaded
+    fn get_vegetation(&self) -> Vec<VegetationInstance> {
+        Vec::new()
+    }
+    /// Type of the block at `position` in chunk space, 0 for air. None for chunks without blocks
+    fn get_block(&self, _position: (usize, usize, usize)) -> Option<u32> {
+        None
+    }
+    /// Replaces the blocks at positions in chunk space, 0 removes a block, and remeshes the chunk
+    /// once. Returns false if the chunk has no blocks or none of them changed
+    fn set_blocks(&mut self, _blocks: &[((usize, usize, usize), u32)]) -> bool {
+        false
+    }
+    /// Returns and clears the ores mined by removing blocks with `set_blocks` and how much of
+    /// each they yield
+    fn take_mining_yields(&mut self) -> Vec<(Ore, u32)> {
+        Vec::new()
+    }
+    /// Blocks or edits of the chunk encoded with the `codec` module, for saving it into a
+    /// `RegionFile`. None for chunks that are regenerated from the seed as a whole
+    fn serialize(&self) -> Option<Vec<u8>> {
+        None
+    }
+    /// Restores a chunk saved with `serialize` at the grid `position`, chunk types without a
+    /// serialized form return `CodecError::Unsupported`
+    fn deserialize(
+        _config: &WorldGenConfig,
+        _position: (f32, f32, f32),
+        _data: &[u8],
+    ) -> Result<Self, CodecError>
+    where
+        Self: Sized,
+    {
+        Err(CodecError::Unsupported)
+    }
+    /// Restores a chunk saved with `serialize` like `deserialize`, but without lighting or
+    /// meshing it, see `new_data_only`
+    fn deserialize_data_only(
+        config: &WorldGenConfig,
+        position: (f32, f32, f32),
+        data: &[u8],
+    ) -> Result<Self, CodecError>
+    where
+        Self: Sized,
+    {
+        Self::deserialize(config, position, data)
+    }
+    /// Ambient occlusion and sky visibility baked while generating the chunk
+    fn get_lightmap(&self) -> Option<&ChunkLightmap> {
+        None
+    }
+    /// Number of bytes uploaded to the GPU when buffering the chunk's meshes
+    fn get_buffer_size(&self) -> usize;
     fn get_vertices(&self) -> Vec<[f32; 3]>;
+    /// Normals of the vertices in the order of `get_vertices`, empty if the chunk has none
+    fn get_normals(&self) -> Vec<[f32; 3]> {
+        Vec::new()
+    }
     fn get_indices(&self) -> Vec<[u32; 3]>;
 }
 
@@ -48,7 +303,66 @@ pub struct ChunkMesh<T: VertexAttributes> {
     vertices: Vec<T>,
 }
 
-#[derive(Eq, PartialEq, Hash, Debug)]
+/// Chunk meshes with the same vertex layout packed into shared buffers and drawn with a single
+/// `glMultiDrawElementsIndirect`, each draw reads its chunk origin from a storage buffer
+pub struct ChunkBatch<T: VertexAttributes> {
+    vertex_array: GLuint,
+    vertex_buffer: GLuint,
+    index_buffer: GLuint,
+    indirect_buffer: GLuint,
+    origin_buffer: GLuint,
+    vertex_capacity: usize,
+    index_capacity: usize,
+    vertex_count: usize,
+    index_count: usize,
+    /// Shared with the `RenderWorkers` while they record the draws
+    regions: Arc<Vec<BatchRegion>>,
+    vertex: PhantomData<T>,
+}
+
+/// Part of the batch buffers holding the mesh of one chunk
+#[derive(Clone)]
+struct BatchRegion {
+    origin: Point3<f32>,
+    bounds: ChunkBounds,
+    lod: usize,
+    first_index: u32,
+    index_count: u32,
+    base_vertex: i32,
+}
+
+/// Draws of the visible chunks of a `ChunkBatch`, recorded on worker threads and submitted
+/// with `ChunkBatch::submit` on the thread owning the GL context
+#[derive(Default)]
+pub struct ChunkDrawList {
+    commands: Vec<DrawElementsIndirectCommand>,
+    /// Origin of the chunk of every command, with its level of detail in w
+    origins: Vec<[f32; 4]>,
+}
+
+/// Draws of a `ChunkBatch` the `RenderWorkers` are recording, see `ChunkBatch::record`
+pub struct ChunkRecording(Recording<ChunkDrawList>);
+
+/// Draws of a terrain's batch for the frame being rendered
+#[derive(Default)]
+enum FrameDraws {
+    #[default]
+    None,
+    Recording(ChunkRecording),
+    Recorded(ChunkDrawList),
+}
+
+/// Layout of one draw in the indirect buffer, as read by `glMultiDrawElementsIndirect`
+#[repr(C)]
+struct DrawElementsIndirectCommand {
+    count: u32,
+    instance_count: u32,
+    first_index: u32,
+    base_vertex: i32,
+    base_instance: u32,
+}
+
+#[derive(Clone, Eq, PartialEq, Hash, Debug)]
 pub struct ChunkBounds {
     pub min: (i32, i32, i32),
     pub max: (i32, i32, i32),
@@ -0,0 +1,40 @@
+use std::sync::atomic::AtomicBool;
+
+use libnoise::{Perlin, Scale};
+use ndarray::Array3;
+use serde::{Deserialize, Serialize};
+
+mod resources;
+
+/// When set, ore veins are rendered through the terrain
+pub static XRAY: AtomicBool = AtomicBool::new(false);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[repr(u32)]
+pub enum Ore {
+    Coal = 1,
+    Iron = 2,
+    Gold = 3,
+}
+
+/// Placement parameters of one kind of ore
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct OreVein {
+    pub ore: Ore,
+    pub frequency: f64,
+    /// Width of the vein, larger values produce thicker veins
+    pub threshold: f32,
+    pub min_height: f32,
+    pub max_height: f32,
+}
+
+/// Places ore veins where two 3D noise fields cross zero at the same time
+pub struct ResourceGenerator {
+    veins: Vec<OreVein>,
+    noise: Vec<(Scale<3, Perlin<3>>, Scale<3, Perlin<3>>)>,
+}
+
+/// Resources stored alongside the blocks of a chunk
+pub struct ResourceLayer {
+    resources: Array3<Option<Ore>>,
+}
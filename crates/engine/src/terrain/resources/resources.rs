@@ -0,0 +1,174 @@
+use std::collections::HashMap;
+
+use libnoise::{Generator, Source};
+use ndarray::Array3;
+
+use crate::terrain::{world_gen::WorldGenConfig, CHUNK_SIZE};
+
+use super::{Ore, OreVein, ResourceGenerator, ResourceLayer};
+
+const NOISE_OFFSET: f64 = 16777216.0;
+
+impl Ore {
+    /// Ore with the discriminant `id`, as stored by the chunk codec
+    pub fn from_id(id: u32) -> Option<Ore> {
+        match id {
+            1 => Some(Ore::Coal),
+            2 => Some(Ore::Iron),
+            3 => Some(Ore::Gold),
+            _ => None,
+        }
+    }
+
+    pub fn get_name(&self) -> &'static str {
+        match self {
+            Ore::Coal => "Coal",
+            Ore::Iron => "Iron",
+            Ore::Gold => "Gold",
+        }
+    }
+
+    pub fn get_color(&self) -> [f32; 3] {
+        match self {
+            Ore::Coal => [0.1, 0.1, 0.1],
+            Ore::Iron => [0.8, 0.55, 0.4],
+            Ore::Gold => [1.0, 0.85, 0.1],
+        }
+    }
+
+    /// Amount of the resource a player receives for mining one block
+    pub fn get_yield(&self) -> u32 {
+        match self {
+            Ore::Coal => 4,
+            Ore::Iron => 2,
+            Ore::Gold => 1,
+        }
+    }
+}
+
+impl OreVein {
+    pub fn defaults() -> Vec<OreVein> {
+        // rarest ores come first so they are not overwritten by common ones
+        vec![
+            OreVein {
+                ore: Ore::Gold,
+                frequency: 0.05,
+                threshold: 0.04,
+                min_height: 0.0,
+                max_height: 30.0,
+            },
+            OreVein {
+                ore: Ore::Iron,
+                frequency: 0.04,
+                threshold: 0.06,
+                min_height: 0.0,
+                max_height: 60.0,
+            },
+            OreVein {
+                ore: Ore::Coal,
+                frequency: 0.03,
+                threshold: 0.08,
+                min_height: 10.0,
+                max_height: 120.0,
+            },
+        ]
+    }
+}
+
+impl ResourceGenerator {
+    pub fn new(config: &WorldGenConfig) -> Self {
+        ResourceGenerator::with_veins(config.seed, config.ores.clone())
+    }
+
+    pub fn with_veins(seed: u64, veins: Vec<OreVein>) -> Self {
+        let noise = veins
+            .iter()
+            .enumerate()
+            .map(|(i, vein)| {
+                let seed = seed.wrapping_add(100 + 2 * i as u64);
+                (
+                    Source::perlin(seed).scale([vein.frequency; 3]),
+                    Source::perlin(seed.wrapping_add(1)).scale([vein.frequency; 3]),
+                )
+            })
+            .collect();
+        Self { veins, noise }
+    }
+
+    /// Returns the ore at the world position `(x, y, z)`, ignoring whether there is a block
+    pub fn sample(&self, x: f64, y: f64, z: f64) -> Option<Ore> {
+        let point = [x + NOISE_OFFSET, y + NOISE_OFFSET, z + NOISE_OFFSET];
+        for (vein, (a, b)) in self.veins.iter().zip(&self.noise) {
+            if (y as f32) < vein.min_height || (y as f32) > vein.max_height {
+                continue;
+            }
+            if (a.sample(point) as f32).abs() < vein.threshold
+                && (b.sample(point) as f32).abs() < vein.threshold
+            {
+                return Some(vein.ore);
+            }
+        }
+        None
+    }
+}
+
+impl ResourceLayer {
+    /// Generates the resources of a chunk starting at the world position `origin`.
+    ///
+    /// Ores are only placed where `is_solid` returns true for the local block position.
+    pub fn generate(
+        generator: &ResourceGenerator,
+        origin: (f64, f64, f64),
+        is_solid: impl Fn(usize, usize, usize) -> bool,
+    ) -> Self {
+        let resources = Array3::from_shape_fn([CHUNK_SIZE, CHUNK_SIZE, CHUNK_SIZE], |(x, y, z)| {
+            if !is_solid(x, y, z) {
+                return None;
+            }
+            generator.sample(
+                origin.0 + x as f64,
+                origin.1 + y as f64,
+                origin.2 + z as f64,
+            )
+        });
+        Self { resources }
+    }
+
+    /// Resources of a chunk from `ore`, which is called with every local block position
+    pub fn from_fn(ore: impl Fn(usize, usize, usize) -> Option<Ore>) -> Self {
+        Self {
+            resources: Array3::from_shape_fn([CHUNK_SIZE, CHUNK_SIZE, CHUNK_SIZE], |(x, y, z)| {
+                ore(x, y, z)
+            }),
+        }
+    }
+
+    pub fn get(&self, position: (usize, usize, usize)) -> Option<Ore> {
+        self.resources.get(position).copied().flatten()
+    }
+
+    pub fn set(&mut self, position: (usize, usize, usize), ore: Option<Ore>) {
+        if let Some(resource) = self.resources.get_mut(position) {
+            *resource = ore;
+        }
+    }
+
+    /// Removes and returns the ore at `position`, used when the block containing it is mined
+    pub fn take(&mut self, position: (usize, usize, usize)) -> Option<Ore> {
+        self.resources.get_mut(position).and_then(|ore| ore.take())
+    }
+
+    pub fn count(&self) -> HashMap<Ore, usize> {
+        let mut counts = HashMap::new();
+        for ore in self.resources.iter().flatten() {
+            *counts.entry(*ore).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = ((usize, usize, usize), Ore)> + '_ {
+        self.resources
+            .indexed_iter()
+            .filter_map(|(position, ore)| ore.map(|ore| (position, ore)))
+    }
+}
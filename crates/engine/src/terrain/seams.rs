@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+
+use cgmath::{InnerSpace, Point3, Vector3};
+
+use crate::core::entity::{component::Component, Entity};
+
+use super::{Chunk, ChunkCoord, Seam, SeamKind, Terrain};
+
+/// Distance from the border between two chunks within which both meshes have vertices, the
+/// meshers overlap their neighbours by one cell
+const BORDER_BAND: f32 = 1.0;
+
+type BorderVertex = (Point3<f32>, Vector3<f32>);
+
+impl<T: Chunk + Component> Terrain<T> {
+    /// Compares the vertices along the borders of adjacent loaded chunks and returns those that
+    /// the neighbour has no vertex within `position_epsilon` of, or whose normals differ by more
+    /// than `normal_epsilon` radians. `entity` is the entity of the terrain
+    pub fn find_seams(
+        &self,
+        entity: &Entity,
+        position_epsilon: f32,
+        normal_epsilon: f32,
+    ) -> Vec<Seam> {
+        let vertices: HashMap<ChunkCoord, Vec<BorderVertex>> = self
+            .chunks
+            .iter()
+            .filter_map(|loaded| {
+                let chunk = entity.get_child(&loaded.entity)?.get_own_component::<T>()?;
+                let origin = chunk.get_position();
+                let vertices = chunk
+                    .get_vertices()
+                    .iter()
+                    .zip(chunk.get_normals())
+                    .map(|(vertex, normal)| {
+                        (
+                            Point3::new(
+                                origin.x + vertex[0],
+                                origin.y + vertex[1],
+                                origin.z + vertex[2],
+                            ),
+                            Vector3::from(normal),
+                        )
+                    })
+                    .collect();
+                Some((loaded.coord, vertices))
+            })
+            .collect();
+
+        let mut seams = Vec::new();
+        for loaded in self.chunks.iter() {
+            let Some(chunk_vertices) = vertices.get(&loaded.coord) else {
+                continue;
+            };
+            let max = [
+                loaded.bounds.max.0,
+                loaded.bounds.max.1,
+                loaded.bounds.max.2,
+            ];
+            // every pair is compared once, from the chunk on the negative side of their face
+            for (axis, offset) in [(0, (1, 0, 0)), (1, (0, 1, 0)), (2, (0, 0, 1))] {
+                let Some(other_vertices) = self
+                    .chunks
+                    .get_neighbor(&loaded.coord, offset)
+                    .and_then(|neighbor| vertices.get(&neighbor.coord))
+                else {
+                    continue;
+                };
+                let plane = max[axis] as f32;
+                let border = Terrain::<T>::get_border_vertices(chunk_vertices, axis, plane);
+                let other_border = Terrain::<T>::get_border_vertices(other_vertices, axis, plane);
+                // chunks without geometry at the border cannot disagree about it
+                if border.is_empty() || other_border.is_empty() {
+                    continue;
+                }
+                for (from, to) in [(&border, &other_border), (&other_border, &border)] {
+                    Terrain::<T>::compare_border(
+                        from,
+                        to,
+                        position_epsilon,
+                        normal_epsilon,
+                        &mut seams,
+                    );
+                }
+            }
+        }
+        seams
+    }
+
+    fn get_border_vertices(
+        vertices: &[BorderVertex],
+        axis: usize,
+        plane: f32,
+    ) -> Vec<BorderVertex> {
+        vertices
+            .iter()
+            .filter(|(position, _)| (position[axis] - plane).abs() <= BORDER_BAND)
+            .cloned()
+            .collect()
+    }
+
+    /// Adds a seam for every vertex of `border` that `other` does not continue
+    fn compare_border(
+        border: &[BorderVertex],
+        other: &[BorderVertex],
+        position_epsilon: f32,
+        normal_epsilon: f32,
+        seams: &mut Vec<Seam>,
+    ) {
+        // vertices of the neighbour hashed into cells of the epsilon, so only the surrounding
+        // cells have to be searched
+        let cell_size = position_epsilon.max(f32::EPSILON);
+        let get_cell = |position: &Point3<f32>| {
+            (
+                (position.x / cell_size).floor() as i32,
+                (position.y / cell_size).floor() as i32,
+                (position.z / cell_size).floor() as i32,
+            )
+        };
+        let mut cells: HashMap<(i32, i32, i32), Vec<usize>> = HashMap::new();
+        for (index, (position, _)) in other.iter().enumerate() {
+            cells.entry(get_cell(position)).or_default().push(index);
+        }
+        for (position, normal) in border {
+            let cell = get_cell(position);
+            let mut closest: Option<(f32, usize)> = None;
+            for x in -1..=1 {
+                for y in -1..=1 {
+                    for z in -1..=1 {
+                        let Some(indices) = cells.get(&(cell.0 + x, cell.1 + y, cell.2 + z)) else {
+                            continue;
+                        };
+                        for &index in indices {
+                            let distance = (other[index].0 - position).magnitude();
+                            if closest.is_none_or(|(closest, _)| distance < closest) {
+                                closest = Some((distance, index));
+                            }
+                        }
+                    }
+                }
+            }
+            match closest {
+                Some((distance, index)) if distance <= position_epsilon => {
+                    let angle = normal
+                        .normalize()
+                        .dot(other[index].1.normalize())
+                        .clamp(-1.0, 1.0)
+                        .acos();
+                    if angle > normal_epsilon {
+                        seams.push(Seam {
+                            position: *position,
+                            kind: SeamKind::Normal,
+                            error: angle,
+                        });
+                    }
+                }
+                closest => seams.push(Seam {
+                    position: *position,
+                    kind: SeamKind::Position,
+                    error: closest.map_or(f32::INFINITY, |(distance, _)| distance),
+                }),
+            }
+        }
+    }
+}
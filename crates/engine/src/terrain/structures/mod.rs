@@ -0,0 +1,60 @@
+use std::collections::{HashMap, HashSet};
+
+use super::{biome::BiomeMap, world_gen::WorldGenConfig, ChunkCoord};
+
+mod structures;
+
+/// Block at a world block position, 0 places air
+pub type StructureBlock = ((i32, i32, i32), u32);
+
+/// Places structures of one kind while chunks are generated, like ruins, towers or boulders.
+/// Register generators with `StructureRegistry::register` before creating the terrain.
+///
+/// Called on the chunk loader threads once per chunk with the structures whose origin lies in
+/// that chunk. The result has to depend only on the context, so regenerating a chunk places
+/// the same structures. Structures may reach into neighbouring chunks, the terrain hands those
+/// blocks on to the neighbours whether they were generated already or not
+pub trait StructureGenerator: Send + Sync {
+    /// Name the generator is registered under, also mixed into its random seed
+    fn get_name(&self) -> &str;
+    fn generate(&self, context: &StructureContext) -> Vec<Structure>;
+}
+
+/// Blocks of a placed structure
+#[derive(Clone, Debug, Default)]
+pub struct Structure {
+    pub blocks: Vec<StructureBlock>,
+}
+
+/// Chunk a `StructureGenerator` places structures in
+pub struct StructureContext<'a> {
+    pub config: &'a WorldGenConfig,
+    pub coord: ChunkCoord,
+    biome_map: &'a BiomeMap,
+}
+
+/// Structure generators the chunk loaders run, shared by all terrains
+pub struct StructureRegistry;
+
+/// Places blueprints from the `BlueprintLibrary` on the surface, e.g. buildings saved with the
+/// blueprint editor. Each chunk gets at most one of them
+pub struct BlueprintStructures {
+    name: String,
+    blueprints: Vec<String>,
+    /// Chance of a chunk to get a structure, `0.0..=1.0`
+    chance: f64,
+    /// Blocks the anchor is placed above the surface, negative values sink the structure in
+    height_offset: i32,
+}
+
+/// Structure blocks on their way to the chunks they fall into, shared by the chunk loaders
+/// and the terrain or owned by a net `Server`
+#[derive(Default)]
+pub(crate) struct PendingStructures {
+    /// Blocks for chunks that were not generated yet, taken by the chunks as they generate
+    pending: HashMap<ChunkCoord, Vec<StructureBlock>>,
+    /// Blocks for chunks that were generated before the structure that reaches into them,
+    /// applied by the terrain when it uploads the chunk or in its next update
+    late: HashMap<ChunkCoord, Vec<StructureBlock>>,
+    generated: HashSet<ChunkCoord>,
+}
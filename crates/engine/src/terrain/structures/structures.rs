@@ -0,0 +1,215 @@
+use std::{
+    collections::{hash_map::DefaultHasher, HashMap},
+    hash::{Hash, Hasher},
+    sync::{Arc, RwLock},
+};
+
+use lazy_static::lazy_static;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::terrain::{
+    biome::{BiomeMap, BiomeSample},
+    blueprint::BlueprintLibrary,
+    world_gen::WorldGenConfig,
+    ChunkCoord, CHUNK_SIZE,
+};
+
+use super::{
+    BlueprintStructures, PendingStructures, Structure, StructureBlock, StructureContext,
+    StructureGenerator, StructureRegistry,
+};
+
+lazy_static! {
+    static ref STRUCTURE_GENERATORS: RwLock<Vec<Arc<dyn StructureGenerator>>> =
+        RwLock::new(Vec::new());
+}
+
+impl StructureRegistry {
+    /// Adds `generator`, replacing a generator registered with the same name. Only chunks
+    /// generated afterwards get its structures
+    pub fn register(generator: impl StructureGenerator + 'static) {
+        let mut generators = STRUCTURE_GENERATORS.write().unwrap();
+        generators.retain(|registered| registered.get_name() != generator.get_name());
+        generators.push(Arc::new(generator));
+    }
+
+    /// Removes the generator `name`, returns false if there is none
+    pub fn unregister(name: &str) -> bool {
+        let mut generators = STRUCTURE_GENERATORS.write().unwrap();
+        let count = generators.len();
+        generators.retain(|registered| registered.get_name() != name);
+        generators.len() != count
+    }
+
+    /// Names of the registered generators in the order they run
+    pub fn get_names() -> Vec<String> {
+        STRUCTURE_GENERATORS
+            .read()
+            .unwrap()
+            .iter()
+            .map(|generator| generator.get_name().to_string())
+            .collect()
+    }
+
+    /// Blocks of the structures of every generator whose origin lies in the chunk at `coord`,
+    /// later generators overwrite the blocks of earlier ones
+    pub fn generate(config: &WorldGenConfig, coord: ChunkCoord) -> Vec<StructureBlock> {
+        let generators = STRUCTURE_GENERATORS.read().unwrap().clone();
+        if generators.is_empty() {
+            return Vec::new();
+        }
+        let biome_map = BiomeMap::new(config);
+        let context = StructureContext {
+            config,
+            coord,
+            biome_map: &biome_map,
+        };
+        generators
+            .iter()
+            .flat_map(|generator| generator.generate(&context))
+            .flat_map(|structure| structure.blocks)
+            .collect()
+    }
+}
+
+impl Structure {
+    pub fn new(blocks: Vec<StructureBlock>) -> Self {
+        Self { blocks }
+    }
+}
+
+impl StructureContext<'_> {
+    /// World block position of the chunk's minimum corner
+    pub fn get_min(&self) -> (i32, i32, i32) {
+        let size = CHUNK_SIZE as i32;
+        (
+            self.coord.x * size,
+            self.coord.y * size,
+            self.coord.z * size,
+        )
+    }
+
+    /// Whether the world block position lies inside the chunk
+    pub fn contains(&self, position: (i32, i32, i32)) -> bool {
+        let (min, size) = (self.get_min(), CHUNK_SIZE as i32);
+        (min.0..min.0 + size).contains(&position.0)
+            && (min.1..min.1 + size).contains(&position.1)
+            && (min.2..min.2 + size).contains(&position.2)
+    }
+
+    /// Biome, height and surface of the terrain column at the world position `x`, `z`
+    pub fn sample(&self, x: i32, z: i32) -> BiomeSample {
+        self.biome_map.sample(x as f64, z as f64)
+    }
+
+    /// World y of the highest solid block of the column at `x`, `z`, caves aside
+    pub fn get_surface_height(&self, x: i32, z: i32) -> i32 {
+        self.sample(x, z).height.floor() as i32
+    }
+
+    /// Generator seeded from the world seed, the chunk and `salt`, usually the generator's
+    /// name so different generators do not place their structures at the same spots
+    pub fn create_rng(&self, salt: &str) -> ChaCha8Rng {
+        let mut hasher = DefaultHasher::new();
+        self.config.seed.hash(&mut hasher);
+        self.coord.hash(&mut hasher);
+        salt.hash(&mut hasher);
+        ChaCha8Rng::seed_from_u64(hasher.finish())
+    }
+}
+
+impl BlueprintStructures {
+    /// Generator `name` placing one of `blueprints` in a chunk with the probability `chance`
+    pub fn new(name: &str, blueprints: Vec<String>, chance: f64) -> Self {
+        Self {
+            name: name.to_string(),
+            blueprints,
+            chance: chance.clamp(0.0, 1.0),
+            height_offset: 1,
+        }
+    }
+
+    /// Places the anchor `offset` blocks above the surface, 1 by default so the anchor block
+    /// rests on top of the ground
+    pub fn with_height_offset(mut self, offset: i32) -> Self {
+        self.height_offset = offset;
+        self
+    }
+}
+
+impl StructureGenerator for BlueprintStructures {
+    fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    fn generate(&self, context: &StructureContext) -> Vec<Structure> {
+        if self.blueprints.is_empty() {
+            return Vec::new();
+        }
+        let mut rng = context.create_rng(&self.name);
+        if !rng.gen_bool(self.chance) {
+            return Vec::new();
+        }
+        let name = &self.blueprints[rng.gen_range(0..self.blueprints.len())];
+        let Some(blueprint) = BlueprintLibrary::get(name) else {
+            return Vec::new();
+        };
+        let min = context.get_min();
+        let size = CHUNK_SIZE as i32;
+        let (x, z) = (
+            min.0 + rng.gen_range(0..size),
+            min.2 + rng.gen_range(0..size),
+        );
+        let position = (x, context.get_surface_height(x, z) + self.height_offset, z);
+        // the origin decides which chunk places the structure, so it is placed exactly once
+        if !context.contains(position) {
+            return Vec::new();
+        }
+        vec![Structure::new(
+            blueprint.get_placed_blocks(position).collect(),
+        )]
+    }
+}
+
+impl PendingStructures {
+    /// Records that the chunk at `coord` was generated with the structure `blocks` of its own,
+    /// passes the blocks outside of it on to their chunks and returns the blocks for it,
+    /// including those other chunks queued for it before
+    pub fn distribute(
+        &mut self,
+        coord: ChunkCoord,
+        blocks: Vec<StructureBlock>,
+    ) -> Vec<StructureBlock> {
+        self.generated.insert(coord);
+        let mut own = self.pending.remove(&coord).unwrap_or_default();
+        let mut others: HashMap<ChunkCoord, Vec<StructureBlock>> = HashMap::new();
+        for block in blocks {
+            let target = ChunkCoord::from_block(block.0);
+            if target == coord {
+                own.push(block);
+            } else {
+                others.entry(target).or_default().push(block);
+            }
+        }
+        for (target, blocks) in others {
+            let queue = if self.generated.contains(&target) {
+                &mut self.late
+            } else {
+                &mut self.pending
+            };
+            queue.entry(target).or_default().extend(blocks);
+        }
+        own
+    }
+
+    /// Blocks that reached the chunk at `coord` after it was generated
+    pub fn take_late(&mut self, coord: &ChunkCoord) -> Option<Vec<StructureBlock>> {
+        self.late.remove(coord)
+    }
+
+    /// Chunks with blocks that reached them after they were generated
+    pub fn get_late_chunks(&self) -> Vec<ChunkCoord> {
+        self.late.keys().copied().collect()
+    }
+}
@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+
+mod surface;
+
+/// Materials the terrain surface can be made of
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[repr(u32)]
+pub enum SurfaceMaterial {
+    Seabed = 0,
+    Sand = 1,
+    Grass = 2,
+    Dirt = 3,
+    Rock = 4,
+    Strata = 5,
+    Snow = 6,
+}
+
+/// Local shape of the terrain at a surface vertex, derived from the density field
+#[derive(Clone, Copy, Debug)]
+pub struct SurfaceSample {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+    /// Angle between the surface normal and the up axis in radians
+    pub slope: f32,
+    /// Laplacian of the density field, positive on ridges and negative in hollows
+    pub curvature: f32,
+    /// Material the biome at this vertex uses for flat ground
+    pub biome_material: SurfaceMaterial,
+    pub biome_color: [f32; 3],
+}
+
+/// What the generator decided to put on a surface vertex
+#[derive(Clone, Copy, Debug)]
+pub struct Surface {
+    pub material: SurfaceMaterial,
+    /// Density of grass and other scatter in the range `0.0..=1.0`
+    pub vegetation: f32,
+}
+
+/// Hook that is run for every surface vertex while meshing.
+///
+/// Rules run in the order they were added and each one sees the result of the previous rules.
+pub trait SurfaceRule: Send + Sync {
+    fn apply(&self, sample: &SurfaceSample, surface: &mut Surface);
+}
+
+pub struct SurfaceRules {
+    rules: Vec<Box<dyn SurfaceRule>>,
+}
+
+/// Assigns seabed, sand, grass, rock and snow by height
+pub struct HeightBandRule {
+    pub sea_level: f32,
+    pub beach_height: f32,
+    pub rock_height: f32,
+    pub snow_height: f32,
+}
+
+/// Removes grass from steep slopes and exposes rock strata on cliffs and overhangs
+pub struct SlopeRule {
+    pub max_grass_slope: f32,
+    pub cliff_slope: f32,
+}
+
+/// Thins out vegetation on exposed ridges and lets it grow denser in hollows
+pub struct CurvatureRule {
+    pub strength: f32,
+}
@@ -0,0 +1,161 @@
+use std::f32::consts::FRAC_PI_4;
+
+use super::{
+    CurvatureRule, HeightBandRule, SlopeRule, Surface, SurfaceMaterial, SurfaceRule, SurfaceRules,
+    SurfaceSample,
+};
+
+const STRATA_THICKNESS: f32 = 3.0;
+
+impl SurfaceMaterial {
+    pub fn get_color(&self, height: f32) -> [f32; 3] {
+        match self {
+            SurfaceMaterial::Seabed => [0.1, 0.2, 0.8],
+            SurfaceMaterial::Sand => [0.76, 0.698, 0.502],
+            SurfaceMaterial::Grass => [0.0, 0.5, 0.1],
+            SurfaceMaterial::Dirt => [0.4, 0.3, 0.2],
+            SurfaceMaterial::Rock => [0.5, 0.5, 0.5],
+            SurfaceMaterial::Strata => {
+                // alternate between two shades of rock to get visible layers on cliff faces
+                if (height / STRATA_THICKNESS).floor() as i32 % 2 == 0 {
+                    [0.55, 0.5, 0.45]
+                } else {
+                    [0.42, 0.38, 0.35]
+                }
+            }
+            SurfaceMaterial::Snow => [0.95, 0.95, 0.95],
+        }
+    }
+}
+
+impl SurfaceSample {
+    pub fn new(position: [f32; 3], normal: [f32; 3], curvature: f32) -> Self {
+        let length = (normal[0] * normal[0] + normal[1] * normal[1] + normal[2] * normal[2]).sqrt();
+        let up = if length > 0.0 {
+            normal[1] / length
+        } else {
+            1.0
+        };
+        Self {
+            position,
+            normal,
+            slope: up.clamp(-1.0, 1.0).acos(),
+            curvature,
+            biome_material: SurfaceMaterial::Grass,
+            biome_color: SurfaceMaterial::Grass.get_color(position[1]),
+        }
+    }
+
+    pub fn with_biome(mut self, material: SurfaceMaterial, color: [f32; 3]) -> Self {
+        self.biome_material = material;
+        self.biome_color = color;
+        self
+    }
+
+    /// Color of `material` at this vertex, the biome's own surface material uses the blended biome color
+    pub fn get_color(&self, material: SurfaceMaterial) -> [f32; 3] {
+        if material == self.biome_material {
+            self.biome_color
+        } else {
+            material.get_color(self.get_height())
+        }
+    }
+
+    pub fn get_height(&self) -> f32 {
+        self.position[1]
+    }
+
+    /// Whether the surface faces downwards
+    pub fn is_overhang(&self) -> bool {
+        self.slope > std::f32::consts::FRAC_PI_2
+    }
+}
+
+impl SurfaceRules {
+    pub fn new() -> Self {
+        Self { rules: Vec::new() }
+    }
+
+    pub fn with_rule<R: SurfaceRule + 'static>(mut self, rule: R) -> Self {
+        self.rules.push(Box::new(rule));
+        self
+    }
+
+    pub fn add_rule<R: SurfaceRule + 'static>(&mut self, rule: R) {
+        self.rules.push(Box::new(rule));
+    }
+
+    pub fn evaluate(&self, sample: &SurfaceSample) -> Surface {
+        let mut surface = Surface {
+            material: SurfaceMaterial::Grass,
+            vegetation: 1.0,
+        };
+        for rule in &self.rules {
+            rule.apply(sample, &mut surface);
+        }
+        surface
+    }
+}
+
+impl Default for SurfaceRules {
+    fn default() -> Self {
+        SurfaceRules::new()
+            .with_rule(HeightBandRule {
+                sea_level: 50.0,
+                beach_height: 51.0,
+                rock_height: 80.0,
+                snow_height: 90.0,
+            })
+            .with_rule(SlopeRule {
+                max_grass_slope: FRAC_PI_4,
+                cliff_slope: 1.0,
+            })
+            .with_rule(CurvatureRule { strength: 4.0 })
+    }
+}
+
+impl SurfaceRule for HeightBandRule {
+    fn apply(&self, sample: &SurfaceSample, surface: &mut Surface) {
+        let height = sample.get_height();
+        surface.material = if height < self.sea_level {
+            SurfaceMaterial::Seabed
+        } else if height < self.beach_height {
+            SurfaceMaterial::Sand
+        } else if height > self.snow_height {
+            SurfaceMaterial::Snow
+        } else if height > self.rock_height {
+            SurfaceMaterial::Rock
+        } else {
+            sample.biome_material
+        };
+        if surface.material != SurfaceMaterial::Grass {
+            surface.vegetation = 0.0;
+        }
+    }
+}
+
+impl SurfaceRule for SlopeRule {
+    fn apply(&self, sample: &SurfaceSample, surface: &mut Surface) {
+        if sample.is_overhang() {
+            surface.material = SurfaceMaterial::Rock;
+            surface.vegetation = 0.0;
+            return;
+        }
+        if sample.slope > self.cliff_slope && surface.material != SurfaceMaterial::Seabed {
+            surface.material = SurfaceMaterial::Strata;
+            surface.vegetation = 0.0;
+        } else if sample.slope > self.max_grass_slope {
+            if surface.material == SurfaceMaterial::Grass {
+                surface.material = SurfaceMaterial::Dirt;
+            }
+            surface.vegetation = 0.0;
+        }
+    }
+}
+
+impl SurfaceRule for CurvatureRule {
+    fn apply(&self, sample: &SurfaceSample, surface: &mut Surface) {
+        surface.vegetation =
+            (surface.vegetation * (1.0 - sample.curvature * self.strength)).clamp(0.0, 1.0);
+    }
+}
@@ -1,30 +1,62 @@
 use std::{
-    cmp::max,
-    sync::mpsc::{self, Sender},
+    collections::{HashMap, VecDeque},
+    path::Path,
+    sync::{
+        atomic::Ordering,
+        mpsc::{self, Sender},
+        Arc, Mutex,
+    },
     thread,
+    time::Instant,
 };
 
 use cgmath::{EuclideanSpace, Matrix4, Point3};
 use glfw::MouseButton;
+use log::Level;
 use rapier3d::prelude::*;
 
-use crate::core::{
-    entity::{
-        component::{camera_component::CameraComponent, Component},
-        Entity,
-    },
-    mouse_picker::MousePicker,
-    physics::rigidbody::RigidBody,
-    renderer::{
-        light::skylight::SkyLight,
-        line::Line,
-        shader::{DynamicVertexArray, Shader, VertexAttributes},
+use crate::{
+    core::{
+        entity::{
+            component::{camera_component::CameraComponent, Component, InspectableComponent},
+            Entity,
+        },
+        event::{EventCategory, DEFAULT_EVENT_PRIORITY},
+        input::{Binding, Input},
+        logger::TimingSpan,
+        mouse_picker::MousePicker,
+        physics::rigidbody::RigidBody,
+        profiler::Profiler,
+        renderer::{
+            gl_debug,
+            light::skylight::SkyLight,
+            line::Line,
+            shader::{DynamicVertexArray, Shader, VertexAttributes},
+        },
+        save_worker::SaveWorker,
+        scene::Scene,
+        view_frustum::ViewFrustum,
     },
-    scene::Scene,
-    view_frustum::ViewFrustum,
+    profile_scope,
+};
+
+use super::{
+    blueprint::{Blueprint, BlueprintLibrary},
+    codec::CodecError,
+    deltas::{ChunkDeltas, PlacedBlueprint},
+    lightmap::Lightmap,
+    structures::{PendingStructures, StructureBlock, StructureRegistry},
+    vegetation::VegetationComponent,
+    world_gen::WorldGenConfig,
+    Chunk, ChunkBatch, ChunkBounds, ChunkCoord, ChunkEvent, ChunkGrid, ChunkMesh, ChunkModified,
+    ChunkRecording, FrameDraws, LoadedChunk, MeshingBackend, OreMined, Terrain, CHUNK_SIZE,
+    CHUNK_SIZE_FLOAT, LOD_COLORING, WIREFRAME,
 };
 
-use super::{Chunk, ChunkBounds, ChunkMesh, Terrain, CHUNK_RADIUS, CHUNK_SIZE, CHUNK_SIZE_FLOAT};
+/// Distance between the points along a clicked line that are tested for blocks
+const PICK_STEP: f32 = 0.1;
+/// Block type the "place" action places, stone
+const PLACED_BLOCK: u32 = 2;
 
 impl ChunkBounds {
     pub fn parse(position: cgmath::Vector3<f32>) -> Self {
@@ -85,44 +117,191 @@ impl ChunkBounds {
 }
 
 impl<T: Chunk + Component + Send + 'static> Terrain<T> {
-    pub fn new(seed: u64) -> Self {
+    pub fn new(config: WorldGenConfig) -> Self {
+        Self::with_meshing_backend(config, MeshingBackend::Cpu)
+    }
+
+    /// Creates the terrain, chunks are meshed on the loader threads or with compute shaders depending on `meshing_backend`
+    pub fn with_meshing_backend(config: WorldGenConfig, meshing_backend: MeshingBackend) -> Self {
+        let config = Arc::new(config);
         let (tx, rx) = mpsc::channel();
-        let origin = T::new(seed, (0.0, 0.0, 0.0), 0);
+        let structures = Arc::new(Mutex::new(PendingStructures::default()));
+        let origin =
+            Terrain::create_chunk(&config, meshing_backend, &structures, (0.0, 0.0, 0.0), 0);
         tx.send(origin).unwrap();
-        let shader_source = T::get_shader_source();
-        let shader = Shader::new(&shader_source.0, &shader_source.1);
+        let (vertex_source, fragment_source) = T::get_shader_source();
+        let shader = Shader::load(vertex_source, fragment_source);
 
         let tx1 = tx.clone();
         let tx2 = tx.clone();
         let tx3 = tx.clone();
         let tx4 = tx.clone();
-        let _ = thread::spawn(move || Terrain::chunkloader(seed, CHUNK_RADIUS as i32, 1, 1, tx1));
-        let _ = thread::spawn(move || Terrain::chunkloader(seed, CHUNK_RADIUS as i32, -1, 1, tx2));
-        let _ = thread::spawn(move || Terrain::chunkloader(seed, CHUNK_RADIUS as i32, 1, -1, tx3));
-        let _ = thread::spawn(move || Terrain::chunkloader(seed, CHUNK_RADIUS as i32, -1, -1, tx4));
+        let config1 = config.clone();
+        let config2 = config.clone();
+        let config3 = config.clone();
+        let config4 = config.clone();
+        let structures1 = structures.clone();
+        let structures2 = structures.clone();
+        let structures3 = structures.clone();
+        let structures4 = structures.clone();
+        let _ = thread::spawn(move || {
+            Terrain::chunkloader(config1, meshing_backend, structures1, 1, 1, tx1)
+        });
+        let _ = thread::spawn(move || {
+            Terrain::chunkloader(config2, meshing_backend, structures2, -1, 1, tx2)
+        });
+        let _ = thread::spawn(move || {
+            Terrain::chunkloader(config3, meshing_backend, structures3, 1, -1, tx3)
+        });
+        let _ = thread::spawn(move || {
+            Terrain::chunkloader(config4, meshing_backend, structures4, -1, -1, tx4)
+        });
 
         Self {
             chunk_receiver: rx,
+            upload_queue: VecDeque::new(),
+            batch: ChunkBatch::new(),
+            frame_draws: Mutex::new(FrameDraws::None),
             shader,
             textures: T::get_textures(),
             mouse_picker: MousePicker::new(),
+            config,
+            meshing_backend,
+            chunks: ChunkGrid::new(),
+            version: 0,
+            subscribers: Vec::new(),
+            lightmap: None,
+            deltas: ChunkDeltas::new(),
+            pending_prop_removals: Vec::new(),
+            pending_blueprints: Vec::new(),
+            structures,
+            pending_edits: HashMap::new(),
+            pending_snapshots: HashMap::new(),
+            pending_lines: Vec::new(),
+            save_path: None,
+            save_events: None,
         }
     }
 
+    /// Generates the chunk at the grid `position` with the structures that fall into it, also
+    /// used by the net `Server` to generate the chunks it owns
+    pub(crate) fn create_chunk(
+        config: &WorldGenConfig,
+        meshing_backend: MeshingBackend,
+        structures: &Mutex<PendingStructures>,
+        position: (f32, f32, f32),
+        lod: usize,
+    ) -> T {
+        profile_scope!("chunk_generation");
+        let _span = TimingSpan::new(
+            Level::Debug,
+            "terrain",
+            &format!("Generating chunk {:?} (lod {})", position, lod),
+        );
+        let chunk_config =
+            config.for_chunk((position.0 as i32, position.1 as i32, position.2 as i32));
+        let mut chunk = match meshing_backend {
+            MeshingBackend::Cpu => T::new(&chunk_config, position, lod),
+            MeshingBackend::Gpu => T::new_deferred(&chunk_config, position, lod),
+            MeshingBackend::DataOnly => T::new_data_only(&chunk_config, position, lod),
+        };
+        let coord = ChunkCoord::new(position.0 as i32, position.1 as i32, position.2 as i32);
+        let blocks = StructureRegistry::generate(config, coord);
+        // structures of neighbours generated earlier may reach into the chunk even if it has none
+        let blocks = structures.lock().unwrap().distribute(coord, blocks);
+        Terrain::apply_blocks(&mut chunk, blocks);
+        Profiler::count_generated_chunk();
+        chunk
+    }
+
+    /// Digs the first block along `line` or places one in front of it in the next update,
+    /// depending on whether `button` is bound to "dig" or "place"
     pub fn process_line(&mut self, line: Option<(Line, MouseButton)>) {
-        if let Some((line, _button)) = line {
-            for _chunk_bounds in ChunkBounds::get_chunk_bounds_on_line(&line) {
-                // for chunk in entity.get_with_own_component_mut::<T>() {
-                //     let chunk = chunk.get_component_mut::<T>().unwrap();
-                //     if chunk.get_bounds() == chunk_bounds {
-                //         chunk.process_line(&line, &button);
-                //     }
-                // }
+        if let Some(line) = line {
+            self.pending_lines.push(line);
+        }
+    }
+
+    /// Applies the clicks of `process_line` to the loaded chunks and records the changed blocks
+    /// in the deltas
+    fn dig_pending_lines(&mut self, scene: &mut Scene, entity: &mut Entity) {
+        let mut changed = false;
+        for (line, button) in std::mem::take(&mut self.pending_lines) {
+            let Some((hit, front)) = self.pick_block(entity, &line) else {
+                continue;
+            };
+            let block = if Input::is_bound("dig", Binding::Mouse(button)) {
+                (hit, 0)
+            } else if Input::is_bound("place", Binding::Mouse(button)) {
+                let Some(front) = front else {
+                    continue;
+                };
+                (front, PLACED_BLOCK)
+            } else {
+                continue;
+            };
+            let coord = ChunkCoord::from_block(block.0);
+            if self.modify_loaded_chunk(scene, entity, &coord, |chunk| {
+                Terrain::apply_blocks(chunk, [block])
+            }) {
+                changed |= self.deltas.set_blocks(&[block]);
             }
         }
+        if changed {
+            self.save();
+        }
     }
 
-    fn chunkloader(seed: u64, radius: i32, x_dir: i32, z_dir: i32, tx: Sender<T>) {
+    /// World block position of the first block along `line` in the loaded chunks and of the
+    /// empty block in front of it, if that one lies in a loaded chunk as well
+    fn pick_block(
+        &self,
+        entity: &Entity,
+        line: &Line,
+    ) -> Option<((i32, i32, i32), Option<(i32, i32, i32)>)> {
+        let mut front = None;
+        for i in 0..(line.length / PICK_STEP) as i32 {
+            let position = line.position + line.direction * (i as f32 * PICK_STEP);
+            let block = (
+                position.x.floor() as i32,
+                position.y.floor() as i32,
+                position.z.floor() as i32,
+            );
+            match self.get_block_at(entity, block) {
+                Some(0) => front = Some(block),
+                Some(_) => return Some((block, front)),
+                None => front = None,
+            }
+        }
+        None
+    }
+
+    /// Type of the block at the world block `position`, 0 for air. None outside of the loaded
+    /// chunks and for chunks without blocks. `entity` is the terrain's entity
+    pub fn get_block_at(&self, entity: &Entity, (x, y, z): (i32, i32, i32)) -> Option<u32> {
+        self.chunks
+            .get_at(Point3::new(x as f32, y as f32, z as f32))
+            .and_then(|loaded| entity.get_child(&loaded.entity))
+            .and_then(|chunk_entity| chunk_entity.get_component::<T>())
+            .and_then(|chunk| {
+                let bounds = chunk.get_bounds();
+                chunk.get_block((
+                    (x - bounds.min.0) as usize,
+                    (y - bounds.min.1) as usize,
+                    (z - bounds.min.2) as usize,
+                ))
+            })
+    }
+
+    fn chunkloader(
+        config: Arc<WorldGenConfig>,
+        meshing_backend: MeshingBackend,
+        structures: Arc<Mutex<PendingStructures>>,
+        x_dir: i32,
+        z_dir: i32,
+        tx: Sender<T>,
+    ) {
+        let radius = config.chunk_radius as i32;
         let mut x: i32 = 1;
         let mut z: i32 = 0;
 
@@ -135,7 +314,13 @@ impl<T: Chunk + Component + Send + 'static> Terrain<T> {
             } else {
                 ((z * z_dir) as f32, 0.0, (x * x_dir) as f32)
             };
-            let new_chunk = T::new(seed, position, max(x.abs(), z.abs()) as usize);
+            let new_chunk = Terrain::create_chunk(
+                &config,
+                meshing_backend,
+                &structures,
+                position,
+                ChunkCoord::new(position.0 as i32, 0, position.2 as i32).get_lod(),
+            );
             let result = tx.send(new_chunk);
             if result.is_err() {
                 break;
@@ -151,6 +336,498 @@ impl<T: Chunk + Component + Send + 'static> Terrain<T> {
         }
     }
 
+    /// Buffers `chunk` into the batch, adds it as a child of the terrain entity and notifies the
+    /// subscribers, returns the uploaded size in bytes or None if the chunk already existed
+    fn add_chunk(&mut self, scene: &mut Scene, entity: &mut Entity, mut chunk: T) -> Option<usize> {
+        let coord = ChunkCoord::from_origin(chunk.get_position());
+        if self.chunks.contains(&coord) {
+            return None;
+        }
+        if let Some(snapshot) = self.pending_snapshots.remove(&coord) {
+            chunk = snapshot;
+        }
+        if self.meshing_backend == MeshingBackend::Gpu {
+            let _span = TimingSpan::new(
+                Level::Debug,
+                "terrain",
+                &format!("Meshing chunk {:?} on the gpu", chunk.get_position()),
+            );
+            chunk.generate_gpu_mesh();
+        }
+        let late = self.structures.lock().unwrap().take_late(&coord);
+        if let Some(blocks) = late {
+            Terrain::apply_blocks(&mut chunk, blocks);
+        }
+        if let Some(blocks) = self.pending_edits.remove(&coord) {
+            Terrain::apply_blocks(&mut chunk, blocks);
+        }
+        for placed in self.deltas.get_blueprints() {
+            if let Some(blueprint) = BlueprintLibrary::get(&placed.name) {
+                Terrain::apply_blueprint(&mut chunk, &blueprint, placed.position);
+            }
+        }
+        if let Some(delta) = self.deltas.get(chunk.get_position()) {
+            Terrain::apply_blocks(&mut chunk, delta.blocks.clone());
+        }
+        // blocks removed while generating or restoring the chunk were not mined by the player
+        chunk.take_mining_yields();
+        let size = chunk.get_buffer_size();
+        chunk.buffer_data();
+        if let Some(mesh) = chunk.get_mesh() {
+            self.batch.insert(
+                chunk.get_position(),
+                chunk.get_bounds(),
+                chunk.get_lod(),
+                mesh,
+            );
+        }
+        if let Some(chunk_lightmap) = chunk.get_lightmap() {
+            let chunk_radius = self.config.chunk_radius;
+            self.lightmap
+                .get_or_insert_with(|| Lightmap::new(chunk_radius))
+                .insert(chunk.get_position(), chunk_lightmap);
+        }
+        let mut chunk_entity = Entity::new(&format!(
+            "chunk-{}@{:?}",
+            entity.child_count(),
+            chunk.get_position()
+        ));
+        let vertices: Vec<Point<f32>> = chunk
+            .get_vertices()
+            .iter()
+            .map(|v| Point::from(*v))
+            .collect();
+        let position = chunk.get_position();
+        let collider = ColliderBuilder::trimesh(vertices, chunk.get_indices())
+            .translation(vector![position.x, position.y, position.z])
+            .build();
+        scene.physics_engine.add_collider(collider, None);
+        self.version += 1;
+        let loaded = LoadedChunk {
+            coord,
+            position,
+            bounds: chunk.get_bounds(),
+            version: self.version,
+            entity: chunk_entity.id,
+            triangle_count: chunk.get_triangle_count(),
+        };
+        let vegetation = chunk.get_vegetation();
+        if !vegetation.is_empty() {
+            // the props are scattered the same way every time, only the removed ones are saved
+            let removed = self
+                .deltas
+                .get(position)
+                .map(|delta| delta.removed_props.clone())
+                .unwrap_or_default();
+            chunk_entity.add_component(VegetationComponent::with_removed(
+                vegetation,
+                removed,
+                chunk.get_bounds(),
+            ));
+        }
+        chunk_entity.add_component(chunk);
+        chunk_entity.add_component(RigidBody::new(
+            RigidBodyType::Fixed,
+            scene,
+            &chunk_entity,
+            None,
+        ));
+        entity.add_child(chunk_entity);
+        self.notify(scene, ChunkEvent::Loaded(loaded.clone()));
+        self.chunks.insert(loaded);
+        Some(size)
+    }
+
+    /// Removes the tree or rock closest to the world `position` within `radius` in the next
+    /// update and records it in the deltas, subscribers receive a `PropRemoved` event for it
+    pub fn remove_prop(&mut self, position: Point3<f32>, radius: f32) {
+        self.pending_prop_removals.push((position, radius));
+    }
+
+    fn remove_pending_props(&mut self, scene: &mut Scene, entity: &mut Entity) {
+        let mut changed = false;
+        for (position, radius) in std::mem::take(&mut self.pending_prop_removals) {
+            // props stand on the surface, which can reach above or below the chunk's bounds
+            let Some(chunk) = self.chunks.iter().find(|chunk| {
+                position.x >= chunk.bounds.min.0 as f32
+                    && position.x < chunk.bounds.max.0 as f32
+                    && position.z >= chunk.bounds.min.2 as f32
+                    && position.z < chunk.bounds.max.2 as f32
+            }) else {
+                continue;
+            };
+            let Some(vegetation) = entity
+                .get_child_mut(&chunk.entity)
+                .and_then(|chunk_entity| chunk_entity.get_component_mut::<VegetationComponent>())
+            else {
+                continue;
+            };
+            let Some(id) = vegetation.find_prop(position, radius) else {
+                continue;
+            };
+            let prop_position = vegetation.get_position(id).unwrap_or(position);
+            vegetation.remove(id);
+            let bounds = chunk.bounds.clone();
+            let chunk = chunk.position;
+            changed |= self.deltas.remove_prop(chunk, id);
+            self.notify(
+                scene,
+                ChunkEvent::PropRemoved {
+                    chunk,
+                    id,
+                    position: prop_position,
+                },
+            );
+            scene.emit(ChunkModified { bounds });
+        }
+        if changed {
+            self.save();
+        }
+    }
+
+    /// Places the registered blueprint `name` with its anchor at the world block `position` in
+    /// the next update and records it in the deltas, so chunks loaded later receive it as well.
+    /// Returns false if no blueprint with that name is registered
+    pub fn place_blueprint(&mut self, name: &str, position: (i32, i32, i32)) -> bool {
+        if BlueprintLibrary::get(name).is_none() {
+            return false;
+        }
+        self.deltas.add_blueprint(name, position);
+        self.pending_blueprints.push(PlacedBlueprint {
+            name: name.to_string(),
+            position,
+        });
+        true
+    }
+
+    /// Sets blocks at world block positions, 0 removes a block. Loaded chunks change in the next
+    /// update, the others once they are uploaded. Unlike blueprints the edits are not recorded in
+    /// the deltas, e.g. for edits a server owns and sends again to every client that loads the
+    /// chunk
+    pub fn edit_blocks(&mut self, blocks: impl IntoIterator<Item = StructureBlock>) {
+        for block in blocks {
+            self.pending_edits
+                .entry(ChunkCoord::from_block(block.0))
+                .or_default()
+                .push(block);
+        }
+    }
+
+    /// Replaces the chunk at `coord` with one encoded with `Chunk::serialize`, e.g. a snapshot a
+    /// server sent. A loaded chunk changes in the next update, otherwise the snapshot is uploaded
+    /// in place of the generated chunk. The terrain never unloads chunks and only loads those
+    /// within `chunk_radius` of the origin, snapshots of other chunks are dropped right away so
+    /// they do not wait for a chunk that never loads. Returns false for those
+    pub fn restore_chunk(&mut self, coord: ChunkCoord, data: &[u8]) -> Result<bool, CodecError> {
+        if !self.is_in_radius(&coord) {
+            return Ok(false);
+        }
+        let chunk_config = self.config.for_chunk(coord.into());
+        let chunk = T::deserialize(
+            &chunk_config,
+            (coord.x as f32, coord.y as f32, coord.z as f32),
+            data,
+        )?;
+        self.pending_snapshots.insert(coord, chunk);
+        Ok(true)
+    }
+
+    /// Whether the chunk loaders load the chunk at `coord`
+    fn is_in_radius(&self, coord: &ChunkCoord) -> bool {
+        let radius = self.config.chunk_radius as i32;
+        coord.y == 0 && coord.x.abs() <= radius && coord.z.abs() <= radius
+    }
+
+    /// Copies the blocks of the loaded chunks between the world block positions `min` and `max`
+    /// into a blueprint anchored at `anchor`, blocks outside of loaded chunks are captured as
+    /// air. `entity` is the terrain's entity
+    pub fn capture_blueprint(
+        &self,
+        entity: &Entity,
+        name: &str,
+        min: (i32, i32, i32),
+        max: (i32, i32, i32),
+        anchor: (i32, i32, i32),
+    ) -> Blueprint {
+        Blueprint::capture(name, min, max, anchor, |position| {
+            self.get_block_at(entity, position).unwrap_or(0)
+        })
+    }
+
+    /// Sets the blocks of `blueprint` placed at `position` that lie inside `chunk`, returns true
+    /// if any of them changed
+    fn apply_blueprint(chunk: &mut T, blueprint: &Blueprint, position: (i32, i32, i32)) -> bool {
+        let bounds = chunk.get_bounds();
+        let (min, max) = blueprint.get_bounds(position);
+        if max.0 < bounds.min.0
+            || max.1 < bounds.min.1
+            || max.2 < bounds.min.2
+            || min.0 >= bounds.max.0
+            || min.1 >= bounds.max.1
+            || min.2 >= bounds.max.2
+        {
+            return false;
+        }
+        Terrain::apply_blocks(chunk, blueprint.get_placed_blocks(position))
+    }
+
+    /// Sets the blocks at world block positions that lie inside `chunk`, returns true if any of
+    /// them changed
+    pub(crate) fn apply_blocks(
+        chunk: &mut T,
+        blocks: impl IntoIterator<Item = StructureBlock>,
+    ) -> bool {
+        let bounds = chunk.get_bounds();
+        let blocks: Vec<_> = blocks
+            .into_iter()
+            .filter(|((x, y, z), _)| {
+                (bounds.min.0..bounds.max.0).contains(x)
+                    && (bounds.min.1..bounds.max.1).contains(y)
+                    && (bounds.min.2..bounds.max.2).contains(z)
+            })
+            .map(|((x, y, z), block)| {
+                (
+                    (
+                        (x - bounds.min.0) as usize,
+                        (y - bounds.min.1) as usize,
+                        (z - bounds.min.2) as usize,
+                    ),
+                    block,
+                )
+            })
+            .collect();
+        !blocks.is_empty() && chunk.set_blocks(&blocks)
+    }
+
+    /// Runs `modify` on the loaded chunk at `coord` and, if it reports a change, rebatches its
+    /// mesh, updates the lightmap and emits `ChunkModified`. Returns false if the chunk is not
+    /// loaded or did not change
+    fn modify_loaded_chunk(
+        &mut self,
+        scene: &mut Scene,
+        entity: &mut Entity,
+        coord: &ChunkCoord,
+        modify: impl FnOnce(&mut T) -> bool,
+    ) -> bool {
+        let Some(loaded) = self.chunks.get_mut(coord) else {
+            return false;
+        };
+        let Some(chunk) = entity
+            .get_child_mut(&loaded.entity)
+            .and_then(|chunk_entity| chunk_entity.get_component_mut::<T>())
+        else {
+            return false;
+        };
+        if !modify(chunk) {
+            return false;
+        }
+        let yields = chunk.take_mining_yields();
+        chunk.buffer_data();
+        if let Some(mesh) = chunk.get_mesh() {
+            self.batch.insert(
+                chunk.get_position(),
+                chunk.get_bounds(),
+                chunk.get_lod(),
+                mesh,
+            );
+        }
+        if let (Some(lightmap), Some(chunk_lightmap)) = (&self.lightmap, chunk.get_lightmap()) {
+            lightmap.insert(chunk.get_position(), chunk_lightmap);
+        }
+        self.version += 1;
+        loaded.version = self.version;
+        loaded.triangle_count = chunk.get_triangle_count();
+        let bounds = loaded.bounds.clone();
+        self.notify(
+            scene,
+            ChunkEvent::Modified {
+                coord: *coord,
+                version: self.version,
+            },
+        );
+        scene.emit(ChunkModified { bounds });
+        for (ore, amount) in yields {
+            scene.emit(OreMined {
+                ore,
+                amount,
+                chunk: *coord,
+            });
+        }
+        true
+    }
+
+    fn place_pending_blueprints(&mut self, scene: &mut Scene, entity: &mut Entity) {
+        if self.pending_blueprints.is_empty() {
+            return;
+        }
+        for placed in std::mem::take(&mut self.pending_blueprints) {
+            let Some(blueprint) = BlueprintLibrary::get(&placed.name) else {
+                continue;
+            };
+            let coords: Vec<ChunkCoord> = self.chunks.iter().map(|chunk| chunk.coord).collect();
+            for coord in coords {
+                self.modify_loaded_chunk(scene, entity, &coord, |chunk| {
+                    Terrain::apply_blueprint(chunk, &blueprint, placed.position)
+                });
+            }
+        }
+        self.save();
+    }
+
+    fn apply_pending_edits(&mut self, scene: &mut Scene, entity: &mut Entity) {
+        let coords: Vec<ChunkCoord> = self
+            .pending_edits
+            .keys()
+            .filter(|coord| self.chunks.contains(coord))
+            .copied()
+            .collect();
+        for coord in coords {
+            let blocks = self.pending_edits.remove(&coord).unwrap();
+            self.modify_loaded_chunk(scene, entity, &coord, |chunk| {
+                Terrain::apply_blocks(chunk, blocks)
+            });
+        }
+    }
+
+    /// Sets the blocks of the loaded chunks that differ from their restored snapshots
+    fn apply_pending_snapshots(&mut self, scene: &mut Scene, entity: &mut Entity) {
+        let coords: Vec<ChunkCoord> = self
+            .pending_snapshots
+            .keys()
+            .filter(|coord| self.chunks.contains(coord))
+            .copied()
+            .collect();
+        for coord in coords {
+            let snapshot = self.pending_snapshots.remove(&coord).unwrap();
+            self.modify_loaded_chunk(scene, entity, &coord, |chunk| {
+                let blocks: Vec<_> = (0..CHUNK_SIZE)
+                    .flat_map(|y| {
+                        (0..CHUNK_SIZE).flat_map(move |z| (0..CHUNK_SIZE).map(move |x| (x, y, z)))
+                    })
+                    .filter_map(|position| {
+                        let block = snapshot.get_block(position)?;
+                        (chunk.get_block(position) != Some(block)).then_some((position, block))
+                    })
+                    .collect();
+                let changed = !blocks.is_empty() && chunk.set_blocks(&blocks);
+                // blocks other players removed were not mined here
+                chunk.take_mining_yields();
+                changed
+            });
+        }
+    }
+
+    /// Applies structure blocks that reached loaded chunks after they were generated, e.g. the
+    /// crown of a tree rooted in a chunk that was generated later. Structures are generated
+    /// again with their chunks, so they are not saved in the deltas
+    fn place_late_structures(&mut self, scene: &mut Scene, entity: &mut Entity) {
+        let late: Vec<(ChunkCoord, Vec<StructureBlock>)> = {
+            let mut structures = self.structures.lock().unwrap();
+            structures
+                .get_late_chunks()
+                .into_iter()
+                .filter(|coord| self.chunks.contains(coord))
+                .filter_map(|coord| structures.take_late(&coord).map(|blocks| (coord, blocks)))
+                .collect()
+        };
+        for (coord, blocks) in late {
+            self.modify_loaded_chunk(scene, entity, &coord, |chunk| {
+                Terrain::apply_blocks(chunk, blocks)
+            });
+        }
+    }
+
+    /// Saves the deltas to `path` in the background whenever they change, the outcome of each
+    /// save is emitted on the scene's event bus as a `SaveEvent`
+    pub fn set_save_path<P: AsRef<Path>>(&mut self, path: P) {
+        self.save_path = Some(path.as_ref().to_path_buf());
+        self.save_events.get_or_insert_with(SaveWorker::subscribe);
+    }
+
+    /// Queues the deltas to be written to the save path, if there is one. `Application` waits
+    /// for queued saves when it shuts down
+    pub fn save(&self) {
+        let Some(path) = &self.save_path else {
+            return;
+        };
+        if let Err(err) = self.deltas.save_in_background(path) {
+            log::error!(target: "terrain", "Failed to save world deltas: {}", err);
+        }
+    }
+
+    fn forward_save_events(&self, scene: &mut Scene) {
+        let (Some(path), Some(events)) = (&self.save_path, &self.save_events) else {
+            return;
+        };
+        for event in events.try_iter() {
+            if event.get_path() == path {
+                scene.emit(event);
+            }
+        }
+    }
+
+    /// Changes made to the generated chunks, to be saved with the world
+    pub fn get_deltas(&self) -> &ChunkDeltas {
+        &self.deltas
+    }
+
+    /// Replaces the changes to the generated chunks, e.g. with saved ones. Only affects the
+    /// chunks uploaded afterwards, so it is best called right after creating the terrain. Saves
+    /// from an older generator are passed through `WorldMigration::migrate` with the config first
+    pub fn set_deltas(&mut self, deltas: ChunkDeltas) {
+        self.deltas = deltas;
+    }
+
+    fn notify(&mut self, scene: &mut Scene, event: ChunkEvent) {
+        self.subscribers
+            .retain(|subscriber| subscriber.send(event.clone()).is_ok());
+        scene.emit(event);
+    }
+
+    /// Returns a receiver for the changes of the loaded chunks, it first receives a `Loaded` event
+    /// for every chunk loaded so far. Receivers can be moved to other threads, dropping one
+    /// unsubscribes it
+    pub fn subscribe(&mut self) -> mpsc::Receiver<ChunkEvent> {
+        let (tx, rx) = mpsc::channel();
+        for chunk in self.chunks.iter() {
+            let _ = tx.send(ChunkEvent::Loaded(chunk.clone()));
+        }
+        self.subscribers.push(tx);
+        rx
+    }
+
+    /// Uploaded chunks in the order they were loaded
+    pub fn get_chunks(&self) -> &[LoadedChunk] {
+        self.chunks.as_slice()
+    }
+
+    /// Uploaded chunks keyed by their coordinates, for neighbour and radius queries
+    pub fn get_chunk_grid(&self) -> &ChunkGrid {
+        &self.chunks
+    }
+
+    /// Loaded chunk containing the world `position`
+    pub fn get_chunk_at(&self, position: Point3<f32>) -> Option<&LoadedChunk> {
+        self.chunks.get_at(position)
+    }
+
+    /// Chunks that changed after `version`, for systems polling the terrain instead of subscribing
+    pub fn get_chunks_changed_since(&self, version: u64) -> impl Iterator<Item = &LoadedChunk> {
+        self.chunks
+            .iter()
+            .filter(move |chunk| chunk.version > version)
+    }
+
+    /// Version of the loaded chunks, changes whenever a chunk is loaded or its blocks change
+    pub fn get_version(&self) -> u64 {
+        self.version
+    }
+
+    /// Number of generated chunks that are waiting to be uploaded
+    pub fn get_pending_upload_count(&self) -> usize {
+        self.upload_queue.len()
+    }
+
     pub fn get_triangle_count(&self, entity: &Entity) -> usize {
         let mut count = 0;
         for chunk in entity.get_with_own_component::<T>() {
@@ -167,46 +844,69 @@ impl<T: Chunk + Component + Send + 'static> Terrain<T> {
     pub fn get_mouse_picker(&self) -> &MousePicker {
         &self.mouse_picker
     }
+
+    pub fn get_config(&self) -> &WorldGenConfig {
+        &self.config
+    }
+
+    /// Starts recording the batch draws of the chunks the camera sees within the view radius
+    fn record_draws(&self, scene: &Scene, camera_component: &CameraComponent) -> ChunkRecording {
+        let camera = camera_component.get_camera();
+        let view_projection = camera_component.get_projection().get_matrix() * camera.get_matrix();
+        let camera_position = camera.get_position();
+        let view_radius = scene
+            .config
+            .quality
+            .view_radius
+            .min(self.config.chunk_radius);
+        let view_distance = (view_radius as f32 + 0.5) * CHUNK_SIZE_FLOAT;
+        self.batch.record(move |bounds| {
+            let center = bounds.center();
+            (center.x - camera_position.x).abs() <= view_distance
+                && (center.z - camera_position.z).abs() <= view_distance
+                && ViewFrustum::is_bounds_in_view(&view_projection, camera_position, bounds)
+        })
+    }
+
+    pub fn get_meshing_backend(&self) -> MeshingBackend {
+        self.meshing_backend
+    }
+}
+
+impl<T: Chunk> InspectableComponent for Terrain<T> {
+    fn get_inspector_name(&self) -> &str {
+        "Terrain"
+    }
 }
 
 impl<T: Chunk + Component + Send + 'static> Component for Terrain<T> {
     fn update(&mut self, scene: &mut Scene, entity: &mut Entity, _: f64) {
-        if let Ok(mut chunk) = self.chunk_receiver.try_recv() {
-            chunk.buffer_data();
-            let mut chunk_exists = false;
-            for existing_chunk in entity.get_with_own_component::<T>() {
-                let existing_chunk = existing_chunk.get_component::<T>().unwrap();
-                if existing_chunk.get_position() == chunk.get_position() {
-                    chunk_exists = true;
-                    break;
-                }
+        profile_scope!("chunk_upload");
+        self.upload_queue.extend(self.chunk_receiver.try_iter());
+        // spread uploads over several frames, at least one chunk is uploaded per frame so the queue always drains
+        let start = Instant::now();
+        let mut uploaded_bytes = 0;
+        while let Some(chunk) = self.upload_queue.front() {
+            let size = chunk.get_buffer_size();
+            if uploaded_bytes > 0
+                && (uploaded_bytes + size > scene.config.chunk_upload_budget_bytes
+                    || start.elapsed().as_secs_f64() * 1000.0 > scene.config.chunk_upload_budget_ms)
+            {
+                break;
             }
-            if !chunk_exists {
-                let mut chunk_entity = Entity::new(&format!(
-                    "chunk-{}@{:?}",
-                    entity.child_count(),
-                    chunk.get_position()
-                ));
-                let vertices: Vec<Point<f32>> = chunk
-                    .get_vertices()
-                    .iter()
-                    .map(|v| Point::from(*v))
-                    .collect();
-                let position = chunk.get_position();
-                let collider = ColliderBuilder::trimesh(vertices, chunk.get_indices())
-                    .translation(vector![position.x, position.y, position.z])
-                    .build();
-                scene.physics_engine.add_collider(collider, None);
-                chunk_entity.add_component(chunk);
-                chunk_entity.add_component(RigidBody::new(
-                    RigidBodyType::Fixed,
-                    scene,
-                    &chunk_entity,
-                    None,
-                ));
-                entity.add_child(chunk_entity);
+            let chunk = self.upload_queue.pop_front().unwrap();
+            // chunks meshed on the gpu only know their size after meshing
+            if let Some(size) = self.add_chunk(scene, entity, chunk) {
+                uploaded_bytes += size;
             }
         }
+        self.remove_pending_props(scene, entity);
+        self.place_pending_blueprints(scene, entity);
+        self.place_late_structures(scene, entity);
+        self.dig_pending_lines(scene, entity);
+        self.apply_pending_snapshots(scene, entity);
+        self.apply_pending_edits(scene, entity);
+        self.forward_save_events(scene);
         if let Some(camera_component) = scene.get_component::<CameraComponent>() {
             let camera = camera_component.get_camera();
             let projection = camera_component.get_projection();
@@ -214,10 +914,18 @@ impl<T: Chunk + Component + Send + 'static> Component for Terrain<T> {
         }
     }
 
+    fn record(&self, scene: &Scene, _: &Entity) {
+        let Some(camera_component) = scene.get_component::<CameraComponent>() else {
+            return;
+        };
+        *self.frame_draws.lock().unwrap() =
+            FrameDraws::Recording(self.record_draws(scene, camera_component));
+    }
+
     fn render(
         &self,
         scene: &Scene,
-        entity: &Entity,
+        _: &Entity,
         view_projection: &Matrix4<f32>,
         parent_transform: &Matrix4<f32>,
     ) {
@@ -225,8 +933,6 @@ impl<T: Chunk + Component + Send + 'static> Component for Terrain<T> {
             if let Some(skylight) = scene.get_component::<SkyLight>() {
                 let light_position = skylight.get_position();
                 let light_projection = skylight.get_projection();
-                let camera = camera_component.get_camera();
-                let projection = camera_component.get_projection();
                 for (i, texture) in self.textures.iter().enumerate() {
                     unsafe {
                         gl::ActiveTexture(gl::TEXTURE0 + i as u32);
@@ -242,13 +948,53 @@ impl<T: Chunk + Component + Send + 'static> Component for Terrain<T> {
                 );
                 self.shader
                     .set_uniform_mat4("lightProjection", &light_projection);
-                for chunk in entity.get_with_own_component::<T>() {
-                    if let Some(chunk) = chunk.get_component::<T>() {
-                        if ViewFrustum::is_bounds_in_frustum(projection, camera, chunk.get_bounds())
-                        {
-                            chunk.render(scene, entity, parent_transform, &view_projection);
-                        }
+                self.shader
+                    .set_uniform_mat4("viewProjection", view_projection);
+                self.shader.set_uniform_mat4("model", parent_transform);
+                self.shader
+                    .set_uniform_1i("lodColoring", LOD_COLORING.load(Ordering::Relaxed) as i32);
+                self.shader
+                    .set_uniform_1i("sunOcclusion", self.config.sun_occlusion as i32);
+                let lightmap_unit = self.textures.len() as u32;
+                self.shader
+                    .set_uniform_1i("useLightmap", self.lightmap.is_some() as i32);
+                if let Some(lightmap) = &self.lightmap {
+                    lightmap.bind(lightmap_unit);
+                    let origin = lightmap.get_origin();
+                    let [width, height, depth] = lightmap.get_extent();
+                    self.shader.set_uniform_1i("lightmap", lightmap_unit as i32);
+                    self.shader
+                        .set_uniform_3f("lightmapOrigin", origin.x, origin.y, origin.z);
+                    self.shader
+                        .set_uniform_3f("lightmapExtent", width, height, depth);
+                }
+                let wireframe = WIREFRAME.load(Ordering::Relaxed);
+                unsafe {
+                    gl::Enable(gl::DEPTH_TEST);
+                    gl::Enable(gl::CULL_FACE);
+                    if wireframe {
+                        gl::PolygonMode(gl::FRONT_AND_BACK, gl::LINE);
+                    }
+                }
+                // the chunk entities only draw what is not part of the batch
+                let mut frame_draws = self.frame_draws.lock().unwrap();
+                let draw_list = match std::mem::take(&mut *frame_draws) {
+                    // rendered without the scene starting the recording
+                    FrameDraws::None => self.record_draws(scene, camera_component).wait(),
+                    FrameDraws::Recording(recording) => recording.wait(),
+                    FrameDraws::Recorded(draw_list) => draw_list,
+                };
+                self.batch.submit(&self.shader, &draw_list);
+                *frame_draws = FrameDraws::Recorded(draw_list);
+                unsafe {
+                    if wireframe {
+                        gl::PolygonMode(gl::FRONT_AND_BACK, gl::FILL);
                     }
+                    gl::Disable(gl::CULL_FACE);
+                    gl::Disable(gl::DEPTH_TEST);
+                }
+                if let Some(lightmap) = &self.lightmap {
+                    lightmap.unbind(lightmap_unit);
                 }
                 for (i, _) in self.textures.iter().enumerate() {
                     unsafe {
@@ -260,14 +1006,30 @@ impl<T: Chunk + Component + Send + 'static> Component for Terrain<T> {
         }
     }
 
+    fn get_event_priority(&self, category: EventCategory) -> Option<i32> {
+        match category {
+            EventCategory::Mouse => Some(DEFAULT_EVENT_PRIORITY),
+            _ => None,
+        }
+    }
+
     fn handle_event(
         &mut self,
         glfw: &mut glfw::Glfw,
         window: &mut glfw::Window,
         event: &glfw::WindowEvent,
-    ) {
+    ) -> bool {
         let line = self.mouse_picker.handle_event(glfw, window, event);
         self.process_line(line);
+        false
+    }
+
+    fn as_inspectable(&self) -> Option<&dyn InspectableComponent> {
+        Some(self)
+    }
+
+    fn as_inspectable_mut(&mut self) -> Option<&mut dyn InspectableComponent> {
+        Some(self)
     }
 }
 
@@ -290,12 +1052,20 @@ impl<T: VertexAttributes + Clone> ChunkMesh<T> {
         unsafe {
             gl::Enable(gl::DEPTH_TEST);
         }
-        shader.bind();
         let mut model = transform.clone();
         if let Some(scale) = scale {
             model = model * cgmath::Matrix4::from_scale(scale);
         }
-        shader.set_uniform_mat4("model", &model);
+        self.draw(shader, &model);
+        unsafe {
+            gl::Disable(gl::DEPTH_TEST);
+        }
+    }
+
+    /// Draws the mesh with `model` and the current depth state
+    pub fn draw(&self, shader: &Shader, model: &Matrix4<f32>) {
+        shader.bind();
+        shader.set_uniform_mat4("model", model);
 
         if let Some(vertex_array) = &self.vertex_array {
             vertex_array.bind();
@@ -311,9 +1081,8 @@ impl<T: VertexAttributes + Clone> ChunkMesh<T> {
                     gl::DrawArrays(gl::TRIANGLES, 0, self.vertices.len() as i32);
                 }
             }
-        }
-        unsafe {
-            gl::Disable(gl::DEPTH_TEST);
+            Profiler::count_draw_call();
+            gl_debug::check_errors("ChunkMesh::draw");
         }
     }
 
@@ -321,6 +1090,11 @@ impl<T: VertexAttributes + Clone> ChunkMesh<T> {
         self.vertex_array.is_some()
     }
 
+    pub fn get_buffer_size(&self) -> usize {
+        self.vertices.len() * std::mem::size_of::<T>()
+            + self.indices.as_ref().map_or(0, |indices| indices.len()) * std::mem::size_of::<u32>()
+    }
+
     pub fn get_triangle_count(&self) -> usize {
         if let Some(indices) = &self.indices {
             indices.len() / 3
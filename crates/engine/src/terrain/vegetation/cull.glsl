@@ -0,0 +1,67 @@
+#version 460 core
+
+layout (local_size_x = 64) in;
+
+// VegetationInstance as laid out on the cpu, the vec3 members would be padded in std430
+struct Instance {
+    float data[9];
+};
+
+struct DrawCommand {
+    uint count;
+    uint instanceCount;
+    uint first;
+    uint baseInstance;
+};
+
+layout (std430, binding = 0) readonly buffer Instances {
+    Instance instances[];
+};
+// instance attributes of the draw, grouped by kind starting at the base instance of each draw
+layout (std430, binding = 1) writeonly buffer VisibleInstances {
+    Instance visible[];
+};
+layout (std430, binding = 2) buffer DrawCommands {
+    DrawCommand commands[];
+};
+
+uniform uint instanceCount;
+uniform mat4 model;
+// unit normals pointing into the frustum in xyz, distance in w
+uniform vec4 frustumPlanes[6];
+uniform vec3 cameraPosition;
+// per kind: draw distance, height of the bounding sphere's center and its radius at scale 1
+uniform vec3 cullDistances;
+uniform vec3 boundingCenters;
+uniform vec3 boundingRadii;
+// instances of this kind beyond the impostor distance are counted by the impostor draw
+uniform uint impostorKind;
+uniform float impostorDistance;
+uniform uint impostorDraw;
+
+void main()
+{
+    uint index = gl_GlobalInvocationID.x;
+    if (index >= instanceCount) {
+        return;
+    }
+    Instance instance = instances[index];
+    uint kind = floatBitsToUint(instance.data[8]);
+    float scale = instance.data[4];
+    vec3 position = vec3(instance.data[0], instance.data[1] + boundingCenters[kind] * scale, instance.data[2]);
+    vec3 center = (model * vec4(position, 1.0)).xyz;
+    float radius = boundingRadii[kind] * scale;
+
+    float cameraDistance = distance(center, cameraPosition);
+    if (cameraDistance - radius > cullDistances[kind]) {
+        return;
+    }
+    for (int i = 0; i < 6; i++) {
+        if (dot(frustumPlanes[i].xyz, center) + frustumPlanes[i].w < -radius) {
+            return;
+        }
+    }
+    uint draw = (kind == impostorKind && cameraDistance > impostorDistance) ? impostorDraw : kind;
+    uint slot = atomicAdd(commands[draw].instanceCount, 1u);
+    visible[commands[draw].baseInstance + slot] = instance;
+}
@@ -0,0 +1,13 @@
+#version 460 core
+
+in vec3 Normal;
+in vec3 Color;
+
+layout (location = 0) out vec4 FragColor;
+// model space normal mapped to 0..1
+layout (location = 1) out vec4 FragNormal;
+
+void main() {
+    FragColor = vec4(Color, 1.0);
+    FragNormal = vec4(normalize(Normal) * 0.5 + 0.5, 1.0);
+}
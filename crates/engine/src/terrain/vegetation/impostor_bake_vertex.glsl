@@ -0,0 +1,18 @@
+#version 460 core
+
+layout (location = 0) in vec3 position;
+layout (location = 1) in vec3 normal;
+layout (location = 2) in vec3 color;
+layout (location = 3) in uint kind;
+
+out vec3 Normal;
+out vec3 Color;
+
+uniform mat4 viewProjection;
+
+void main()
+{
+    gl_Position = viewProjection * vec4(position, 1.0);
+    Normal = normal;
+    Color = color;
+}
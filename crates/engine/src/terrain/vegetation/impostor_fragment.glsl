@@ -0,0 +1,64 @@
+#version 460 core
+
+in vec4 FrameCoords;
+in float FrameBlend;
+in vec3 Tint;
+in mat3 NormalBasis;
+in vec3 toLightVector;
+in vec4 fragPosLightSpace;
+in vec3 WorldPosition;
+
+layout (location = 0) out vec4 FragColor;
+// world space normal for the SSAO G-buffer
+layout (location = 1) out vec4 FragNormal;
+// x roughness, y 0 as vegetation doesn't reflect the sky probe itself
+layout (location = 2) out vec4 FragMaterial;
+
+uniform sampler2D shadowMap;
+uniform sampler2D impostorAlbedo;
+uniform sampler2D impostorNormals;
+
+#include "lights.glsl"
+#include "environment.glsl"
+
+float ShadowCalculation(vec4 fragPosLightSpace, vec3 toLightVector, vec3 normal) {
+    vec3 projCoords = fragPosLightSpace.xyz / fragPosLightSpace.w;
+    projCoords = projCoords * 0.5 + 0.5;
+    if (projCoords.z > 1.0) {
+        return 0.0;
+    }
+    float closestDepth = texture(shadowMap, projCoords.xy).r;
+    float currentDepth = projCoords.z;
+    float bias = max(0.01 * (1.0 - dot(normal, toLightVector)), 0.005);
+    float shadow = 0.0;
+    vec2 texelSize = 1.0 / textureSize(shadowMap, 0);
+    for(int x = -2; x <= 2; ++x) {
+        for(int y = -2; y <= 2; ++y) {
+            float pcfDepth = texture(shadowMap, projCoords.xy + vec2(x, y) * texelSize).r;
+            shadow += currentDepth - bias > pcfDepth ? 1.0 : 0.0;
+        }
+    }
+    shadow /= 25.0;
+    return shadow;
+}
+
+void main() {
+    vec4 albedo = mix(texture(impostorAlbedo, FrameCoords.xy), texture(impostorAlbedo, FrameCoords.zw), FrameBlend);
+    if (albedo.a < 0.5) {
+        discard;
+    }
+    // the mipmaps average in the empty texels around the model
+    vec3 color = albedo.rgb / albedo.a * Tint;
+    vec3 baked = mix(texture(impostorNormals, FrameCoords.xy), texture(impostorNormals, FrameCoords.zw), FrameBlend).xyz;
+    vec3 normal = normalize(NormalBasis * (baked * 2.0 - 1.0));
+
+    vec3 unitToLightVector = normalize(toLightVector);
+    float intensity = dot(normal, unitToLightVector);
+    float brightness = max(intensity, 0.5);
+    vec3 diffuse = brightness * vec3(1.0);
+    float shadow = ShadowCalculation(fragPosLightSpace, unitToLightVector, normal);
+    vec3 lighting = skyAmbient(normal) * screenAmbientOcclusion() + (1.0 - shadow) * diffuse + pointLighting(WorldPosition, normal);
+    FragColor = vec4(applyFog(lighting * color, WorldPosition), 1.0);
+    FragNormal = vec4(normal, 1.0);
+    FragMaterial = vec4(1.0, 0.0, 0.0, 1.0);
+}
@@ -0,0 +1,71 @@
+#version 460 core
+
+layout (location = 0) in vec3 instancePosition;
+layout (location = 1) in float instanceRotation;
+layout (location = 2) in float instanceScale;
+layout (location = 3) in vec3 instanceTint;
+layout (location = 4) in uint instanceKind;
+
+// the two frames closest to the direction of the camera, xy and zw
+out vec4 FrameCoords;
+out float FrameBlend;
+out vec3 Tint;
+// rotates the baked model space normals into world space
+out mat3 NormalBasis;
+out vec3 toLightVector;
+out vec4 fragPosLightSpace;
+out vec3 WorldPosition;
+
+uniform vec3 lightPosition;
+uniform vec3 cameraPosition;
+uniform mat4 model;
+uniform mat4 viewProjection;
+uniform mat4 lightProjection;
+// height range and half width of the frames and the number of frames in the atlas
+uniform float impostorMin;
+uniform float impostorMax;
+uniform float impostorRadius;
+uniform float impostorViews;
+
+const float TAU = 6.28318530718;
+// two triangles spanning -1..1 horizontally and 0..1 vertically
+const vec2 corners[6] = vec2[](
+    vec2(-1.0, 0.0), vec2(1.0, 0.0), vec2(1.0, 1.0),
+    vec2(-1.0, 0.0), vec2(1.0, 1.0), vec2(-1.0, 1.0)
+);
+
+vec3 rotateY(vec3 v, float angle) {
+    float c = cos(angle);
+    float s = sin(angle);
+    return vec3(c * v.x + s * v.z, v.y, -s * v.x + c * v.z);
+}
+
+void main()
+{
+    vec2 corner = corners[gl_VertexID];
+    vec3 origin = (model * vec4(instancePosition, 1.0)).xyz;
+    // the quad only turns around the up axis so the trunk stays on the ground
+    vec2 toCamera = cameraPosition.xz - origin.xz;
+    vec3 direction = length(toCamera) > 0.0 ? normalize(vec3(toCamera.x, 0.0, toCamera.y)) : vec3(1.0, 0.0, 0.0);
+    vec3 right = vec3(direction.z, 0.0, -direction.x);
+    float height = mix(impostorMin, impostorMax, corner.y);
+    vec3 worldPosition = origin + (right * corner.x * impostorRadius + vec3(0.0, height, 0.0)) * instanceScale;
+    gl_Position = viewProjection * vec4(worldPosition, 1.0);
+
+    // frames are baked counterclockwise from +x in the space of the unrotated model
+    vec3 local = rotateY(direction, -instanceRotation);
+    float view = mod(atan(local.z, local.x) / TAU * impostorViews, impostorViews);
+    float frame = floor(view);
+    float nextFrame = mod(frame + 1.0, impostorViews);
+    float u = corner.x * 0.5 + 0.5;
+    FrameCoords = vec4((frame + u) / impostorViews, corner.y, (nextFrame + u) / impostorViews, corner.y);
+    FrameBlend = view - frame;
+
+    float c = cos(instanceRotation);
+    float s = sin(instanceRotation);
+    NormalBasis = mat3(model) * mat3(c, 0.0, -s, 0.0, 1.0, 0.0, s, 0.0, c);
+    Tint = instanceTint;
+    fragPosLightSpace = lightProjection * vec4(worldPosition, 1.0);
+    toLightVector = lightPosition - worldPosition;
+    WorldPosition = worldPosition;
+}
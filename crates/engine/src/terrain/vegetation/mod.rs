@@ -0,0 +1,93 @@
+use std::collections::BTreeSet;
+
+use gl::types::GLuint;
+
+use crate::{
+    core::renderer::{
+        impostor::ImpostorAtlas,
+        shader::{DynamicVertexArray, Shader},
+    },
+    terrain::{biome::BiomeType, ChunkBounds},
+};
+
+mod vegetation;
+
+/// Kinds of props scattered on the terrain, all of them share one prototype mesh
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u32)]
+pub enum VegetationKind {
+    Grass = 0,
+    Rock = 1,
+    Tree = 2,
+}
+
+/// Placement of a single prop, uploaded as per-instance vertex attributes
+#[derive(Clone, Copy, Debug)]
+#[repr(C)]
+pub struct VegetationInstance {
+    position: [f32; 3],
+    /// Rotation around the up axis in radians
+    rotation: f32,
+    scale: f32,
+    tint: [f32; 3],
+    kind: u32,
+}
+
+/// Terrain triangle vegetation is scattered on, in world space
+pub struct ScatterTriangle {
+    pub corners: [[f32; 3]; 3],
+    pub color: [f32; 3],
+    /// Vegetation density the surface rules assigned to the triangle, `0.0..=1.0`
+    pub vegetation: f32,
+    pub biome: BiomeType,
+}
+
+/// Grass, rocks and trees of a terrain chunk. Every frame a compute pass culls the instances
+/// against the view frustum and the draw distance of their kind and compacts the visible ones,
+/// which are then drawn with one indirect draw per kind
+pub struct VegetationComponent {
+    vertex_array: DynamicVertexArray<VegetationVertex>,
+    /// Storage buffer with every instance of the chunk, read by the culling pass
+    instance_buffer: GLuint,
+    /// One draw per kind followed by the draw of the impostors, their instance counts are
+    /// filled in by the culling pass
+    indirect_buffer: GLuint,
+    /// Draws with no visible instances, uploaded to reset the indirect buffer before culling
+    commands: [DrawArraysIndirectCommand; 4],
+    /// Reads the impostors from the visible instances, the quad is generated in the shader
+    impostor_vertex_array: GLuint,
+    instance_count: usize,
+    bounds: ChunkBounds,
+    /// Every instance scattered on the chunk, their indices identify them in the chunk deltas
+    scattered: Vec<VegetationInstance>,
+    /// Indices of the scattered instances that were removed and are not drawn
+    removed: BTreeSet<u32>,
+}
+
+struct VegetationRenderer {
+    shader: Shader,
+    cull_shader: Shader,
+    impostor_shader: Shader,
+    /// Trees baked from several directions, drawn instead of the trees far from the camera
+    tree_impostor: ImpostorAtlas,
+}
+
+/// Layout of one draw in the indirect buffer, as read by `glMultiDrawArraysIndirect`
+#[derive(Clone, Copy, Debug, Default)]
+#[repr(C)]
+struct DrawArraysIndirectCommand {
+    count: u32,
+    instance_count: u32,
+    first: u32,
+    base_instance: u32,
+}
+
+#[derive(Clone, Copy)]
+#[repr(C)]
+struct VegetationVertex {
+    position: [f32; 3],
+    normal: [f32; 3],
+    color: [f32; 3],
+    /// Kind the vertex belongs to, each kind is drawn from its own range of the prototypes
+    kind: u32,
+}
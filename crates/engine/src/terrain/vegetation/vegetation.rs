@@ -0,0 +1,716 @@
+use std::{collections::BTreeSet, sync::Mutex};
+
+use cgmath::{InnerSpace, Matrix4, Point3, Vector3};
+use gl::types::{GLsizeiptr, GLuint, GLvoid};
+use glfw::{Glfw, WindowEvent};
+use lazy_static::lazy_static;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    core::{
+        entity::{
+            component::{camera_component::CameraComponent, Component, InspectableComponent},
+            Entity,
+        },
+        event::EventCategory,
+        profiler::Profiler,
+        renderer::{
+            environment::include_environment,
+            impostor::ImpostorAtlas,
+            light::{light_pass::include_lights, skylight::SkyLight},
+            shader::{DynamicVertexArray, Shader, VertexAttributes},
+            shader_manager::ShaderSource,
+        },
+        scene::Scene,
+        view_frustum::ViewFrustum,
+    },
+    terrain::{biome::BiomeType, ChunkBounds},
+};
+
+use super::{
+    DrawArraysIndirectCommand, ScatterTriangle, VegetationComponent, VegetationInstance,
+    VegetationKind, VegetationRenderer, VegetationVertex,
+};
+
+const KINDS: [VegetationKind; 3] = [
+    VegetationKind::Grass,
+    VegetationKind::Rock,
+    VegetationKind::Tree,
+];
+
+/// Instances culled by one invocation group of the culling pass, must match `cull.glsl`
+const CULL_GROUP_SIZE: usize = 64;
+
+/// Directions the tree is baked from and the pixel size of every frame of the impostor
+const IMPOSTOR_VIEWS: usize = 8;
+const IMPOSTOR_FRAME_SIZE: (u32, u32) = (128, 256);
+/// Distance from the camera beyond which trees are drawn as impostors
+const IMPOSTOR_DISTANCE: f32 = 150.0;
+
+lazy_static! {
+    static ref RENDERER: Mutex<Option<VegetationRenderer>> = Mutex::new(None);
+}
+
+impl VegetationKind {
+    /// Instances per square unit of fully vegetated ground in `biome`
+    fn get_density(&self, biome: BiomeType) -> f32 {
+        match (self, biome) {
+            (VegetationKind::Grass, BiomeType::Plains) => 0.25,
+            (VegetationKind::Grass, BiomeType::Forest) => 0.15,
+            (VegetationKind::Grass, BiomeType::Tundra) => 0.04,
+            (VegetationKind::Grass, BiomeType::Mountains) => 0.02,
+            (VegetationKind::Grass, BiomeType::Desert) => 0.0,
+            (VegetationKind::Rock, BiomeType::Desert) => 0.004,
+            (VegetationKind::Rock, BiomeType::Mountains) => 0.006,
+            (VegetationKind::Rock, _) => 0.002,
+            (VegetationKind::Tree, BiomeType::Forest) => 0.008,
+            (VegetationKind::Tree, BiomeType::Plains) => 0.001,
+            (VegetationKind::Tree, BiomeType::Tundra) => 0.0005,
+            (VegetationKind::Tree, _) => 0.0,
+        }
+    }
+
+    /// Smallest up component of the surface normal the kind is placed on
+    fn get_min_normal_y(&self) -> f32 {
+        match self {
+            VegetationKind::Grass => 0.8,
+            VegetationKind::Rock => 0.5,
+            VegetationKind::Tree => 0.9,
+        }
+    }
+
+    /// Rocks lie on bare ground as well, grass and trees follow the vegetation of the surface
+    fn needs_vegetation(&self) -> bool {
+        *self != VegetationKind::Rock
+    }
+
+    /// Distance from the camera beyond which instances of the kind are culled
+    fn get_cull_distance(&self) -> f32 {
+        match self {
+            VegetationKind::Grass => 120.0,
+            VegetationKind::Rock => 300.0,
+            VegetationKind::Tree => 600.0,
+        }
+    }
+
+    /// Height of the center and radius of a sphere around the prototype at scale 1
+    fn get_bounding_sphere(&self) -> (f32, f32) {
+        match self {
+            VegetationKind::Grass => (0.3, 0.5),
+            VegetationKind::Rock => (0.2, 0.5),
+            VegetationKind::Tree => (2.4, 2.9),
+        }
+    }
+}
+
+impl VegetationInstance {
+    /// Scatters instances over `triangles` by surface normal, vegetation density and biome.
+    /// The same seed and triangles always give the same instances
+    pub fn scatter(
+        seed: u64,
+        triangles: impl Iterator<Item = ScatterTriangle>,
+    ) -> Vec<VegetationInstance> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut instances = Vec::new();
+        for triangle in triangles {
+            let [a, b, c] = triangle.corners.map(Vector3::from);
+            let (edge1, edge2) = (b - a, c - a);
+            let cross = edge1.cross(edge2);
+            let area = cross.magnitude() / 2.0;
+            if area <= f32::EPSILON {
+                continue;
+            }
+            let normal_y = cross.normalize().y;
+            for kind in KINDS {
+                if normal_y < kind.get_min_normal_y() {
+                    continue;
+                }
+                let mut expected = kind.get_density(triangle.biome) * area;
+                if kind.needs_vegetation() {
+                    expected *= triangle.vegetation;
+                }
+                let count =
+                    expected.floor() as usize + (rng.gen::<f32>() < expected.fract()) as usize;
+                for _ in 0..count {
+                    let (mut u, mut v) = (rng.gen::<f32>(), rng.gen::<f32>());
+                    // points beyond the diagonal are mirrored back into the triangle
+                    if u + v > 1.0 {
+                        (u, v) = (1.0 - u, 1.0 - v);
+                    }
+                    let position = a + edge1 * u + edge2 * v;
+                    let (scale, tint) = match kind {
+                        VegetationKind::Grass => (
+                            rng.gen_range(0.7..1.3),
+                            triangle.color.map(|c| c * rng.gen_range(0.8..1.2)),
+                        ),
+                        VegetationKind::Rock => {
+                            (rng.gen_range(0.4..1.5), [rng.gen_range(0.8..1.1); 3])
+                        }
+                        VegetationKind::Tree => {
+                            (rng.gen_range(0.8..1.4), [rng.gen_range(0.85..1.1); 3])
+                        }
+                    };
+                    instances.push(VegetationInstance {
+                        position: position.into(),
+                        rotation: rng.gen_range(0.0..std::f32::consts::TAU),
+                        scale,
+                        tint,
+                        kind: kind as u32,
+                    });
+                }
+            }
+        }
+        instances
+    }
+}
+
+impl VegetationComponent {
+    /// Has to be called on the thread owning the gl context
+    pub fn new(instances: &[VegetationInstance], bounds: ChunkBounds) -> Self {
+        Self::with_removed(instances.to_vec(), BTreeSet::new(), bounds)
+    }
+
+    /// Places the `scattered` instances except for those whose index is in `removed`.
+    /// Has to be called on the thread owning the gl context
+    pub fn with_removed(
+        scattered: Vec<VegetationInstance>,
+        removed: BTreeSet<u32>,
+        bounds: ChunkBounds,
+    ) -> Self {
+        let instances: Vec<VegetationInstance> = scattered
+            .iter()
+            .enumerate()
+            .filter(|(id, _)| !removed.contains(&(*id as u32)))
+            .map(|(_, instance)| *instance)
+            .collect();
+        let instances = instances.as_slice();
+        let prototypes = VegetationComponent::create_prototypes();
+        let mut vertex_array = DynamicVertexArray::new();
+        vertex_array.buffer_data(&prototypes, &None);
+        // the culling pass overwrites the instance attributes with the visible instances, the
+        // distant trees are written behind all instances so the buffer needs room for them twice
+        let trees = instances
+            .iter()
+            .filter(|instance| instance.kind == VegetationKind::Tree as u32);
+        let visible: Vec<VegetationInstance> = instances.iter().chain(trees).copied().collect();
+        vertex_array.buffer_instance_data(&visible);
+
+        // the prototypes and the visible instances are both grouped by kind
+        let mut commands = [DrawArraysIndirectCommand::default(); KINDS.len() + 1];
+        let mut base_instance = 0;
+        for (kind, command) in KINDS.iter().zip(commands.iter_mut()) {
+            let kind = *kind as u32;
+            let first = prototypes
+                .iter()
+                .position(|vertex| vertex.kind == kind)
+                .unwrap_or(0);
+            command.first = first as u32;
+            command.count = prototypes[first..]
+                .iter()
+                .take_while(|vertex| vertex.kind == kind)
+                .count() as u32;
+            command.base_instance = base_instance;
+            base_instance += instances
+                .iter()
+                .filter(|instance| instance.kind == kind)
+                .count() as u32;
+        }
+        // one quad per impostor
+        commands[KINDS.len()] = DrawArraysIndirectCommand {
+            count: 6,
+            first: 0,
+            base_instance: instances.len() as u32,
+            ..Default::default()
+        };
+
+        let mut impostor_vertex_array = 0;
+        unsafe {
+            gl::GenVertexArrays(1, &mut impostor_vertex_array);
+            gl::BindVertexArray(impostor_vertex_array);
+            if let Some(visible_buffer) = vertex_array.get_instance_buffer() {
+                gl::BindBuffer(gl::ARRAY_BUFFER, visible_buffer);
+                DynamicVertexArray::<VegetationVertex>::set_attribute_pointers::<VegetationInstance>(
+                    0, 1,
+                );
+                gl::BindBuffer(gl::ARRAY_BUFFER, 0);
+            }
+            gl::BindVertexArray(0);
+        }
+
+        let mut buffers = [0; 2];
+        unsafe {
+            gl::GenBuffers(buffers.len() as i32, buffers.as_mut_ptr());
+            gl::BindBuffer(gl::SHADER_STORAGE_BUFFER, buffers[0]);
+            gl::BufferData(
+                gl::SHADER_STORAGE_BUFFER,
+                std::mem::size_of_val(instances) as GLsizeiptr,
+                instances.as_ptr() as *const GLvoid,
+                gl::STATIC_DRAW,
+            );
+            gl::BindBuffer(gl::SHADER_STORAGE_BUFFER, buffers[1]);
+            gl::BufferData(
+                gl::SHADER_STORAGE_BUFFER,
+                std::mem::size_of_val(&commands) as GLsizeiptr,
+                commands.as_ptr() as *const GLvoid,
+                gl::DYNAMIC_DRAW,
+            );
+            gl::BindBuffer(gl::SHADER_STORAGE_BUFFER, 0);
+        }
+        Self {
+            vertex_array,
+            instance_buffer: buffers[0],
+            indirect_buffer: buffers[1],
+            commands,
+            impostor_vertex_array,
+            instance_count: instances.len(),
+            bounds,
+            scattered,
+            removed,
+        }
+    }
+
+    pub fn get_instance_count(&self) -> usize {
+        self.instance_count
+    }
+
+    /// Index of the tree or rock closest to the world `position` within `radius`, grass is
+    /// ignored
+    pub fn find_prop(&self, position: Point3<f32>, radius: f32) -> Option<u32> {
+        self.scattered
+            .iter()
+            .enumerate()
+            .filter(|(id, instance)| {
+                instance.kind != VegetationKind::Grass as u32
+                    && !self.removed.contains(&(*id as u32))
+            })
+            .map(|(id, instance)| {
+                let distance = (Point3::from(instance.position) - position).magnitude();
+                (id as u32, distance)
+            })
+            .filter(|(_, distance)| *distance <= radius)
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(id, _)| id)
+    }
+
+    /// World position of the scattered instance `id`
+    pub fn get_position(&self, id: u32) -> Option<Point3<f32>> {
+        self.scattered
+            .get(id as usize)
+            .map(|instance| Point3::from(instance.position))
+    }
+
+    /// Stops drawing the scattered instance `id`, returns false if it does not exist or was
+    /// removed before. Has to be called on the thread owning the gl context
+    pub fn remove(&mut self, id: u32) -> bool {
+        if id as usize >= self.scattered.len() || self.removed.contains(&id) {
+            return false;
+        }
+        let scattered = std::mem::take(&mut self.scattered);
+        let mut removed = std::mem::take(&mut self.removed);
+        removed.insert(id);
+        *self = Self::with_removed(scattered, removed, self.bounds.clone());
+        true
+    }
+
+    /// Resets the draws and runs the culling pass, which counts the instances of every kind
+    /// that are in front of the camera of `view_projection` and within the draw distance.
+    /// Trees further away than `IMPOSTOR_DISTANCE` are counted by the impostor draw instead.
+    /// Both distances are divided by `lod_bias`
+    fn cull(
+        &self,
+        shader: &Shader,
+        lod_bias: f32,
+        camera_position: Point3<f32>,
+        view_projection: &Matrix4<f32>,
+        model: &Matrix4<f32>,
+    ) {
+        let Some(visible_buffer) = self.vertex_array.get_instance_buffer() else {
+            return;
+        };
+        unsafe {
+            gl::BindBuffer(gl::SHADER_STORAGE_BUFFER, self.indirect_buffer);
+            gl::BufferSubData(
+                gl::SHADER_STORAGE_BUFFER,
+                0,
+                std::mem::size_of_val(&self.commands) as GLsizeiptr,
+                self.commands.as_ptr() as *const GLvoid,
+            );
+            gl::BindBufferBase(gl::SHADER_STORAGE_BUFFER, 0, self.instance_buffer);
+            gl::BindBufferBase(gl::SHADER_STORAGE_BUFFER, 1, visible_buffer);
+            gl::BindBufferBase(gl::SHADER_STORAGE_BUFFER, 2, self.indirect_buffer);
+        }
+        shader.bind();
+        shader.set_uniform_1ui("instanceCount", self.instance_count as u32);
+        shader.set_uniform_mat4("model", model);
+        for (i, plane) in ViewFrustum::get_planes(view_projection).iter().enumerate() {
+            shader.set_uniform_4f(
+                &format!("frustumPlanes[{}]", i),
+                plane.x,
+                plane.y,
+                plane.z,
+                plane.w,
+            );
+        }
+        shader.set_uniform_3f(
+            "cameraPosition",
+            camera_position.x,
+            camera_position.y,
+            camera_position.z,
+        );
+        let [grass, rock, tree] = KINDS.map(|kind| kind.get_cull_distance() / lod_bias);
+        shader.set_uniform_3f("cullDistances", grass, rock, tree);
+        let [grass, rock, tree] = KINDS.map(|kind| kind.get_bounding_sphere());
+        shader.set_uniform_3f("boundingCenters", grass.0, rock.0, tree.0);
+        shader.set_uniform_3f("boundingRadii", grass.1, rock.1, tree.1);
+        shader.set_uniform_1ui("impostorKind", VegetationKind::Tree as u32);
+        shader.set_uniform_1f("impostorDistance", IMPOSTOR_DISTANCE / lod_bias);
+        shader.set_uniform_1ui("impostorDraw", KINDS.len() as u32);
+        unsafe {
+            gl::DispatchCompute(self.instance_count.div_ceil(CULL_GROUP_SIZE) as u32, 1, 1);
+            // the draw reads the counts as commands and the visible instances as attributes
+            gl::MemoryBarrier(gl::COMMAND_BARRIER_BIT | gl::VERTEX_ATTRIB_ARRAY_BARRIER_BIT);
+            gl::BindBuffer(gl::SHADER_STORAGE_BUFFER, 0);
+        }
+    }
+
+    /// Low poly meshes of all kinds, each vertex tagged with the kind it belongs to
+    fn create_prototypes() -> Vec<VegetationVertex> {
+        let mut vertices = Vec::new();
+
+        // two crossed blades that taper towards the top, lit from above so both sides match
+        let grass = [0.45, 0.75, 0.3];
+        for (x, z) in [(0.4, 0.0), (0.0, 0.4)] {
+            let quad = [
+                [-x, 0.0, -z],
+                [x, 0.0, z],
+                [x * 0.3, 0.6, z * 0.3],
+                [-x * 0.3, 0.6, -z * 0.3],
+            ];
+            for corners in [[quad[0], quad[1], quad[2]], [quad[0], quad[2], quad[3]]] {
+                VegetationComponent::push_triangle(
+                    &mut vertices,
+                    corners,
+                    Some([0.0, 1.0, 0.0]),
+                    grass,
+                    VegetationKind::Grass,
+                );
+            }
+        }
+
+        // flattened octahedron
+        let rock = [0.45, 0.43, 0.4];
+        let ring = [
+            [0.5, 0.1, 0.0],
+            [0.0, 0.1, -0.45],
+            [-0.5, 0.1, 0.0],
+            [0.0, 0.1, 0.45],
+        ];
+        for i in 0..ring.len() {
+            let (current, next) = (ring[i], ring[(i + 1) % ring.len()]);
+            for corners in [
+                [current, next, [0.0, 0.45, 0.0]],
+                [next, current, [0.0, -0.1, 0.0]],
+            ] {
+                VegetationComponent::push_triangle(
+                    &mut vertices,
+                    corners,
+                    None,
+                    rock,
+                    VegetationKind::Rock,
+                );
+            }
+        }
+
+        // square trunk below a cone shaped crown
+        let (bark, leaves) = ([0.35, 0.25, 0.15], [0.15, 0.4, 0.15]);
+        let trunk = [[0.15, 0.0], [0.0, -0.15], [-0.15, 0.0], [0.0, 0.15]];
+        for i in 0..trunk.len() {
+            let ([x0, z0], [x1, z1]) = (trunk[i], trunk[(i + 1) % trunk.len()]);
+            let quad = [[x0, -0.2, z0], [x1, -0.2, z1], [x1, 2.0, z1], [x0, 2.0, z0]];
+            for corners in [[quad[0], quad[1], quad[2]], [quad[0], quad[2], quad[3]]] {
+                VegetationComponent::push_triangle(
+                    &mut vertices,
+                    corners,
+                    None,
+                    bark,
+                    VegetationKind::Tree,
+                );
+            }
+        }
+        let segments = 6;
+        for i in 0..segments {
+            let angle = |i: usize| i as f32 / segments as f32 * std::f32::consts::TAU;
+            let (a0, a1) = (angle(i), angle(i + 1));
+            let base = |a: f32| [a.cos() * 1.2, 1.5, -a.sin() * 1.2];
+            VegetationComponent::push_triangle(
+                &mut vertices,
+                [base(a0), base(a1), [0.0, 5.0, 0.0]],
+                None,
+                leaves,
+                VegetationKind::Tree,
+            );
+            VegetationComponent::push_triangle(
+                &mut vertices,
+                [base(a1), base(a0), [0.0, 1.5, 0.0]],
+                None,
+                leaves,
+                VegetationKind::Tree,
+            );
+        }
+        vertices
+    }
+
+    /// Adds a counter-clockwise triangle, flat shaded unless `normal` is given
+    fn push_triangle(
+        vertices: &mut Vec<VegetationVertex>,
+        corners: [[f32; 3]; 3],
+        normal: Option<[f32; 3]>,
+        color: [f32; 3],
+        kind: VegetationKind,
+    ) {
+        let normal = normal.unwrap_or_else(|| {
+            let [a, b, c] = corners.map(Vector3::from);
+            (b - a).cross(c - a).normalize().into()
+        });
+        for position in corners {
+            vertices.push(VegetationVertex {
+                position,
+                normal,
+                color,
+                kind: kind as u32,
+            });
+        }
+    }
+}
+
+impl VegetationRenderer {
+    fn new() -> Self {
+        Self {
+            shader: Shader::load(
+                ShaderSource::new(
+                    "terrain/vegetation/vertex.glsl",
+                    include_str!("vertex.glsl"),
+                ),
+                ShaderSource::new(
+                    "terrain/vegetation/fragment.glsl",
+                    include_str!("fragment.glsl"),
+                )
+                .preprocess(|source| include_lights(&include_environment(source))),
+            ),
+            cull_shader: Shader::load_compute(ShaderSource::new(
+                "terrain/vegetation/cull.glsl",
+                include_str!("cull.glsl"),
+            )),
+            impostor_shader: Shader::load(
+                ShaderSource::new(
+                    "terrain/vegetation/impostor_vertex.glsl",
+                    include_str!("impostor_vertex.glsl"),
+                ),
+                ShaderSource::new(
+                    "terrain/vegetation/impostor_fragment.glsl",
+                    include_str!("impostor_fragment.glsl"),
+                )
+                .preprocess(|source| include_lights(&include_environment(source))),
+            ),
+            tree_impostor: VegetationRenderer::bake_impostor(VegetationKind::Tree),
+        }
+    }
+
+    /// Bakes the prototype of `kind` from `IMPOSTOR_VIEWS` directions
+    fn bake_impostor(kind: VegetationKind) -> ImpostorAtlas {
+        let vertices: Vec<VegetationVertex> = VegetationComponent::create_prototypes()
+            .into_iter()
+            .filter(|vertex| vertex.kind == kind as u32)
+            .collect();
+        let (min, max) = vertices.iter().fold(
+            (
+                Point3::new(f32::MAX, f32::MAX, f32::MAX),
+                Point3::new(f32::MIN, f32::MIN, f32::MIN),
+            ),
+            |(min, max), vertex| {
+                let [x, y, z] = vertex.position;
+                (
+                    Point3::new(min.x.min(x), min.y.min(y), min.z.min(z)),
+                    Point3::new(max.x.max(x), max.y.max(y), max.z.max(z)),
+                )
+            },
+        );
+        let mut vertex_array = DynamicVertexArray::new();
+        vertex_array.buffer_data(&vertices, &None);
+        let shader = Shader::load(
+            ShaderSource::new(
+                "terrain/vegetation/impostor_bake_vertex.glsl",
+                include_str!("impostor_bake_vertex.glsl"),
+            ),
+            ShaderSource::new(
+                "terrain/vegetation/impostor_bake_fragment.glsl",
+                include_str!("impostor_bake_fragment.glsl"),
+            ),
+        );
+        ImpostorAtlas::bake(
+            IMPOSTOR_VIEWS,
+            IMPOSTOR_FRAME_SIZE,
+            min,
+            max,
+            |view_projection| {
+                shader.bind();
+                shader.set_uniform_mat4("viewProjection", view_projection);
+                vertex_array.draw(gl::TRIANGLES);
+            },
+        )
+    }
+}
+
+impl InspectableComponent for VegetationComponent {
+    fn get_inspector_name(&self) -> &str {
+        "Vegetation"
+    }
+}
+
+impl Component for VegetationComponent {
+    fn update(&mut self, _: &mut Scene, _: &mut Entity, _: f64) {}
+
+    fn render(
+        &self,
+        scene: &Scene,
+        _: &Entity,
+        view_projection: &Matrix4<f32>,
+        parent_transform: &Matrix4<f32>,
+    ) {
+        let (Some(camera), Some(skylight)) = (
+            scene.get_component::<CameraComponent>(),
+            scene.get_component::<SkyLight>(),
+        ) else {
+            return;
+        };
+        if self.get_instance_count() == 0
+            || !ViewFrustum::is_bounds_in_frustum(
+                camera.get_projection(),
+                camera.get_camera(),
+                self.bounds.clone(),
+            )
+        {
+            return;
+        }
+        let mut renderer = RENDERER.lock().unwrap();
+        let renderer = renderer.get_or_insert_with(VegetationRenderer::new);
+        self.cull(
+            &renderer.cull_shader,
+            scene.config.quality.lod_bias.max(f32::EPSILON),
+            camera.get_camera().get_position(),
+            view_projection,
+            parent_transform,
+        );
+        let shader = &renderer.shader;
+        let light_position = skylight.get_position();
+        shader.bind();
+        shader.set_uniform_mat4("viewProjection", view_projection);
+        shader.set_uniform_mat4("model", parent_transform);
+        shader.set_uniform_mat4("lightProjection", &skylight.get_projection());
+        shader.set_uniform_3f(
+            "lightPosition",
+            light_position.x,
+            light_position.y,
+            light_position.z,
+        );
+        // grass blades are seen from both sides
+        unsafe {
+            gl::Disable(gl::CULL_FACE);
+        }
+        self.vertex_array
+            .draw_indirect(self.indirect_buffer, KINDS.len());
+
+        let shader = &renderer.impostor_shader;
+        let impostor = &renderer.tree_impostor;
+        let camera_position = camera.get_camera().get_position();
+        let (min, max) = (impostor.get_min(), impostor.get_max());
+        shader.bind();
+        shader.set_uniform_mat4("viewProjection", view_projection);
+        shader.set_uniform_mat4("model", parent_transform);
+        shader.set_uniform_mat4("lightProjection", &skylight.get_projection());
+        shader.set_uniform_3f(
+            "lightPosition",
+            light_position.x,
+            light_position.y,
+            light_position.z,
+        );
+        shader.set_uniform_3f(
+            "cameraPosition",
+            camera_position.x,
+            camera_position.y,
+            camera_position.z,
+        );
+        shader.set_uniform_1f("impostorMin", min.y);
+        shader.set_uniform_1f("impostorMax", max.y);
+        shader.set_uniform_1f("impostorRadius", impostor.get_radius());
+        shader.set_uniform_1f("impostorViews", impostor.get_views() as f32);
+        shader.set_uniform_1i("impostorAlbedo", 1);
+        shader.set_uniform_1i("impostorNormals", 2);
+        impostor.bind(1, 2);
+        unsafe {
+            gl::BindVertexArray(self.impostor_vertex_array);
+            gl::BindBuffer(gl::DRAW_INDIRECT_BUFFER, self.indirect_buffer);
+            gl::DrawArraysIndirect(
+                gl::TRIANGLES,
+                (KINDS.len() * std::mem::size_of::<DrawArraysIndirectCommand>()) as *const GLvoid,
+            );
+            gl::BindBuffer(gl::DRAW_INDIRECT_BUFFER, 0);
+            gl::BindVertexArray(0);
+            for unit in [1, 2] {
+                gl::ActiveTexture(gl::TEXTURE0 + unit);
+                gl::BindTexture(gl::TEXTURE_2D, 0);
+            }
+            gl::ActiveTexture(gl::TEXTURE0);
+        }
+        Profiler::count_draw_call();
+    }
+
+    fn get_event_priority(&self, _: EventCategory) -> Option<i32> {
+        None
+    }
+
+    fn handle_event(&mut self, _: &mut Glfw, _: &mut glfw::Window, _: &WindowEvent) -> bool {
+        false
+    }
+
+    fn as_inspectable(&self) -> Option<&dyn InspectableComponent> {
+        Some(self)
+    }
+
+    fn as_inspectable_mut(&mut self) -> Option<&mut dyn InspectableComponent> {
+        Some(self)
+    }
+}
+
+impl Drop for VegetationComponent {
+    fn drop(&mut self) {
+        let buffers = [self.instance_buffer, self.indirect_buffer];
+        unsafe {
+            gl::DeleteBuffers(buffers.len() as i32, buffers.as_ptr());
+            gl::DeleteVertexArrays(1, &self.impostor_vertex_array);
+        }
+    }
+}
+
+impl VertexAttributes for VegetationVertex {
+    fn get_vertex_attributes() -> Vec<(usize, GLuint)> {
+        vec![
+            (3, gl::FLOAT),
+            (3, gl::FLOAT),
+            (3, gl::FLOAT),
+            (1, gl::UNSIGNED_INT),
+        ]
+    }
+}
+
+impl VertexAttributes for VegetationInstance {
+    fn get_vertex_attributes() -> Vec<(usize, GLuint)> {
+        vec![
+            (3, gl::FLOAT),
+            (1, gl::FLOAT),
+            (1, gl::FLOAT),
+            (3, gl::FLOAT),
+            (1, gl::UNSIGNED_INT),
+        ]
+    }
+}
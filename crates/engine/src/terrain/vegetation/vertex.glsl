@@ -0,0 +1,40 @@
+#version 460 core
+
+layout (location = 0) in vec3 position;
+layout (location = 1) in vec3 normal;
+layout (location = 2) in vec3 color;
+layout (location = 3) in uint kind;
+layout (location = 4) in vec3 instancePosition;
+layout (location = 5) in float instanceRotation;
+layout (location = 6) in float instanceScale;
+layout (location = 7) in vec3 instanceTint;
+layout (location = 8) in uint instanceKind;
+
+out vec3 Normal;
+out vec3 Color;
+out vec3 toLightVector;
+out vec4 fragPosLightSpace;
+out vec3 WorldPosition;
+
+uniform vec3 lightPosition;
+uniform mat4 model;
+uniform mat4 viewProjection;
+uniform mat4 lightProjection;
+
+vec3 rotateY(vec3 v, float angle) {
+    float c = cos(angle);
+    float s = sin(angle);
+    return vec3(c * v.x + s * v.z, v.y, -s * v.x + c * v.z);
+}
+
+void main()
+{
+    vec3 local = rotateY(position, instanceRotation) * instanceScale;
+    vec4 worldPosition = model * vec4(instancePosition + local, 1.0);
+    gl_Position = viewProjection * worldPosition;
+    Normal = normalize(mat3(model) * rotateY(normal, instanceRotation));
+    Color = color * instanceTint;
+    fragPosLightSpace = lightProjection * worldPosition;
+    toLightVector = lightPosition - worldPosition.xyz;
+    WorldPosition = worldPosition.xyz;
+}
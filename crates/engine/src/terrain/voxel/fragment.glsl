@@ -5,12 +5,33 @@ in vec3 Normal;
 in vec3 toLightVector;
 in vec2 TexCoords;
 flat in uint BlockType;
+in float AmbientOcclusion;
+in vec2 Light;
+in vec3 WorldPosition;
+in vec3 LodTint;
 
 uniform sampler2D texture0;
 uniform sampler2D texture1;
+// baked ambient occlusion in r and sky visibility in g, covering lightmapExtent from lightmapOrigin
+uniform sampler3D lightmap;
+uniform bool useLightmap;
+uniform vec3 lightmapOrigin;
+uniform vec3 lightmapExtent;
 
 out vec4 FragColor;
 
+// color of torches and other light emitting blocks
+const vec3 BLOCK_LIGHT_COLOR = vec3(1.0, 0.8, 0.55);
+// light in fully dark caves, so their walls stay barely visible
+const float MIN_LIGHT = 0.02;
+
+#include "environment.glsl"
+
+// every light level is 80% as bright as the next one
+float lightLevel(float level) {
+    return level > 0.0 ? pow(0.8, 15.0 * (1.0 - level)) : 0.0;
+}
+
 void main()
 {
     vec3 unitNormal = normalize(Normal);
@@ -40,11 +61,26 @@ void main()
     vec3 unitToLightVector = normalize(toLightVector);
     float intensity = dot(normal, unitToLightVector);
     float brightness = max(intensity, 0.5);
-    vec3 diffuse = brightness * vec3(1.0);
+    float bakedLight = 1.0;
+    if (useLightmap) {
+        vec2 baked = texture(lightmap, (WorldPosition - lightmapOrigin) / lightmapExtent).rg;
+        bakedLight = mix(0.35, 1.0, baked.r * mix(0.5, 1.0, baked.g));
+    }
+    // the sun only reaches what the sky lights, torches light caves on their own
+    vec3 light = brightness * lightLevel(Light.x) * vec3(1.0) + lightLevel(Light.y) * BLOCK_LIGHT_COLOR;
+    vec3 diffuse = max(light * mix(0.4, 1.0, AmbientOcclusion) * bakedLight, vec3(MIN_LIGHT));
     vec4 texColor = vec4(0.0);
     if(BlockType == 1)
         texColor = texture(texture0, TexCoords);
     else if(BlockType == 2)
         texColor = texture(texture1, TexCoords);
-    FragColor = texColor * vec4(diffuse, 1.0);
+    else if(BlockType >= 16)
+        texColor = outColor;
+    else if(BlockType == 3) {
+        // light emitting blocks are not lit themselves
+        texColor = outColor;
+        diffuse = vec3(1.0);
+    }
+    vec4 color = texColor * vec4(diffuse * LodTint, 1.0);
+    FragColor = vec4(applyFog(color.rgb, WorldPosition), color.a);
 }
\ No newline at end of file
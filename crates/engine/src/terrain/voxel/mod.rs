@@ -1,9 +1,20 @@
 use ndarray::ArrayBase;
 
-use crate::terrain::ChunkMesh;
+use crate::terrain::{
+    block_light::ChunkLight,
+    lightmap::ChunkLightmap,
+    resources::{Ore, ResourceLayer},
+    ChunkMesh,
+};
 
 pub mod voxel;
 
+/// Block type of torches, which emit light by default, see `LightEmitters`
+pub const TORCH_BLOCK: u32 = 3;
+
+/// Draws the ore veins of the voxel terrain over everything else while `resources::XRAY` is set
+pub struct OreXRayPass;
+
 pub struct Block {
     pub type_id: u32,
 }
@@ -11,7 +22,17 @@ pub struct Block {
 pub struct VoxelChunk {
     position: (f32, f32, f32),
     blocks: ArrayBase<ndarray::OwnedRepr<Option<Block>>, ndarray::Dim<[usize; 3]>>,
+    resources: ResourceLayer,
+    /// Resources collected by mining blocks that have not been picked up by gameplay yet
+    mining_yields: Vec<(Ore, u32)>,
     pub mesh: Option<ChunkMesh<BlockVertex>>,
+    ore_mesh: Option<ChunkMesh<BlockVertex>>,
+    /// None if `WorldGenConfig::bake_lightmaps` is off
+    lightmap: Option<ChunkLightmap>,
+    /// Sky and block light, updated with every edit
+    light: ChunkLight,
+    /// Set for chunks created with `Chunk::new_data_only`, they are never lit or meshed
+    data_only: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -21,4 +42,8 @@ pub struct BlockVertex {
     normal: (f32, f32, f32),
     texture_coords: (f32, f32),
     block_type: u32,
+    /// 0 in fully occluded corners, 1 in open ones
+    ambient_occlusion: f32,
+    /// Skylight and block light in front of the face, 0 for dark and 1 for the brightest level
+    light: (f32, f32),
 }
@@ -4,29 +4,63 @@ layout (location = 0) in vec3 position;
 layout (location = 1) in vec3 normals;
 layout (location = 2) in vec2 texCoords;
 layout (location = 3) in uint block_type;
+layout (location = 4) in float ambientOcclusion;
+// skylight in x and block light in y, 0 for dark and 1 for the brightest level
+layout (location = 5) in vec2 light;
 
 out vec4 outColor;
 out vec3 Normal;
 out vec3 toLightVector;
 out vec2 TexCoords;
 out uint BlockType;
+out float AmbientOcclusion;
+out vec2 Light;
+out vec3 WorldPosition;
+out vec3 LodTint;
 
 uniform vec3 lightPosition;
 uniform mat4 model;
+// set while the terrain draws its chunk batch, each draw is offset by the origin of its chunk
+uniform bool batched;
 uniform mat4 viewProjection;
 
+layout (std430, binding = 4) readonly buffer ChunkOrigins {
+    vec4 chunkOrigins[];
+};
+// set while the debug controller tints the chunks by their level of detail, which the batch
+// stores in the w component of the chunk origins
+uniform bool lodColoring;
+
+vec3 lodTint(uint lod) {
+    const vec3 tints[4] = vec3[](vec3(1.0, 0.4, 0.4), vec3(0.4, 1.0, 0.4), vec3(0.4, 0.6, 1.0), vec3(1.0, 1.0, 0.4));
+    return tints[lod % 4u];
+}
+
 void main()
 {
-    vec4 worldPosition = model * vec4(position, 1.0);
+    vec3 localPosition = batched ? position + chunkOrigins[gl_DrawID].xyz : position;
+    vec4 worldPosition = model * vec4(localPosition, 1.0);
     gl_Position = viewProjection * worldPosition;
     if (block_type == 1.0)
         outColor = vec4(0.3, 0.6, 0.4, 1.0);
     else if (block_type == 2.0)
         outColor = vec4(0.5, 0.5, 0.5, 1.0);
+    else if (block_type == 3)
+        outColor = vec4(1.0, 0.75, 0.35, 1.0);
+    else if (block_type == 17)
+        outColor = vec4(0.1, 0.1, 0.1, 1.0);
+    else if (block_type == 18)
+        outColor = vec4(0.8, 0.55, 0.4, 1.0);
+    else if (block_type == 19)
+        outColor = vec4(1.0, 0.85, 0.1, 1.0);
     else
         outColor = vec4(0.0, 0.0, 0.0, 1.0);
     Normal = normals;
     TexCoords = texCoords;
     BlockType = block_type;
+    AmbientOcclusion = ambientOcclusion;
+    Light = light;
     toLightVector = lightPosition - worldPosition.xyz;
+    WorldPosition = worldPosition.xyz;
+    LodTint = batched && lodColoring ? lodTint(uint(chunkOrigins[gl_DrawID].w)) : vec3(1.0);
 }
\ No newline at end of file
@@ -1,19 +1,44 @@
-use crate::terrain::{Chunk, CHUNK_SIZE, CHUNK_SIZE_FLOAT};
+use std::sync::{atomic::Ordering, Arc};
+
+use crate::terrain::{
+    biome::BiomeMap,
+    block_light::{ChunkLight, LightEmitters, MAX_LIGHT_LEVEL},
+    codec::{BlockRuns, ByteReader, ByteWriter, CodecError, PayloadKind},
+    lightmap::ChunkLightmap,
+    resources::{Ore, ResourceGenerator, ResourceLayer, XRAY},
+    world_gen::WorldGenConfig,
+    Chunk, CHUNK_SIZE, CHUNK_SIZE_FLOAT,
+};
 use crate::{
     core::{
-        entity::{component::Component, Entity},
-        renderer::{line::Line, shader::VertexAttributes, texture::Texture},
+        asset::AssetManager,
+        entity::{
+            component::{camera_component::CameraComponent, Component, InspectableComponent},
+            Entity,
+        },
+        event::EventCategory,
+        renderer::{
+            environment::include_environment,
+            render_graph::{RenderPass, RenderResources, DEBUG_PASS_ORDER},
+            shader::VertexAttributes,
+            shader_manager::ShaderSource,
+            texture::Texture,
+        },
         scene::Scene,
+        window::Window,
     },
     terrain::{ChunkBounds, Terrain},
 };
 
-use cgmath::{Matrix4, Point3, Vector3};
+use cgmath::{EuclideanSpace, Matrix4, Point3};
 use gl::types::GLuint;
 use libnoise::{Generator, Source};
 use ndarray::{Array3, ArrayBase, Dim};
 
-use super::{Block, BlockVertex, ChunkMesh, VoxelChunk};
+use super::{Block, BlockVertex, ChunkMesh, OreXRayPass, VoxelChunk};
+
+/// Ores are passed to the shader as block types starting at this offset
+const ORE_BLOCK_TYPE_OFFSET: u32 = 16;
 
 impl Block {
     pub fn new(type_id: u32) -> Self {
@@ -28,6 +53,8 @@ impl VertexAttributes for BlockVertex {
             (3, gl::FLOAT),        // normal
             (2, gl::FLOAT),        // texture_coords
             (1, gl::UNSIGNED_INT), // block_type
+            (1, gl::FLOAT),        // ambient_occlusion
+            (2, gl::FLOAT),        // light
         ]
     }
 }
@@ -47,8 +74,17 @@ impl VoxelChunk {
             let mut mask = vec![false; CHUNK_SIZE * CHUNK_SIZE];
             let mut flip = vec![false; CHUNK_SIZE * CHUNK_SIZE];
             let mut b_t = vec![0; CHUNK_SIZE * CHUNK_SIZE];
+            let mut ao = vec![[0u8; 4]; CHUNK_SIZE * CHUNK_SIZE];
+            let mut light = vec![(0u8, 0u8); CHUNK_SIZE * CHUNK_SIZE];
             q[d] = 1;
 
+            let normal = match d {
+                0 => (0.0, 1.0, 0.0),
+                1 => (1.0, 0.0, 0.0),
+                2 => (0.0, 0.0, 1.0),
+                _ => (0.0, 0.0, 0.0),
+            };
+
             // Check each slice of the chunk one at a time
             x[d] = -1;
             while x[d] < CHUNK_SIZE as i32 {
@@ -102,6 +138,15 @@ impl VoxelChunk {
                         mask[n] = block_current != block_compare;
                         flip[n] = block_compare;
                         b_t[n] = block_type;
+                        if mask[n] {
+                            // the face is lit from the empty side
+                            let mut air = [x[0], x[1], x[2]];
+                            if block_compare {
+                                air[d] += 1;
+                            }
+                            ao[n] = self.calculate_face_ao(air, u, v);
+                            light[n] = self.light.get(air[0], air[1], air[2]);
+                        }
                         x[u] += 1;
                         n += 1;
                     }
@@ -119,12 +164,15 @@ impl VoxelChunk {
                     while i < CHUNK_SIZE {
                         if mask[n] {
                             // Compute the width of this quad and store it in w
-                            // This is done by searching along the current axis until mask[n + w] is false
+                            // This is done by searching along the current axis until mask[n + w] is false.
+                            // Faces only merge if their corners are equally occluded, the occlusion is interpolated over the quad
                             let mut w = 1;
                             while i + w < CHUNK_SIZE
                                 && mask[n + w]
                                 && flip[n] == flip[n + w]
                                 && b_t[n] == b_t[n + w]
+                                && ao[n] == ao[n + w]
+                                && light[n] == light[n + w]
                             {
                                 w += 1;
                             }
@@ -139,6 +187,8 @@ impl VoxelChunk {
                                     if !mask[n + k + h * CHUNK_SIZE]
                                         || flip[n] != flip[n + k + h * CHUNK_SIZE]
                                         || b_t[n] != b_t[n + k + h * CHUNK_SIZE]
+                                        || ao[n] != ao[n + k + h * CHUNK_SIZE]
+                                        || light[n] != light[n + k + h * CHUNK_SIZE]
                                     {
                                         break 'outer;
                                     }
@@ -156,137 +206,61 @@ impl VoxelChunk {
                             let mut dv = vec![0; 3];
                             dv[v] = h as i32;
 
+                            // Corners of the quad as offsets along u and v, in the order of the vertices
+                            let [ao00, ao10, ao11, ao01] = ao[n];
+                            let corners = if !flip[n] {
+                                [(1, 0, ao10), (0, 0, ao00), (1, 1, ao11), (0, 1, ao01)]
+                            } else {
+                                [(0, 0, ao00), (1, 0, ao10), (0, 1, ao01), (1, 1, ao11)]
+                            };
+
                             // Create a quad for this face. Colour, normal or textures are not stored in this block vertex format.
-                            if !flip[n] {
-                                vertices.extend_from_slice(&[
-                                    BlockVertex {
-                                        position: (
-                                            (x[0] + du[0]) as f32,
-                                            (x[1] + du[1]) as f32,
-                                            (x[2] + du[2]) as f32,
-                                        ),
-                                        normal: match d {
-                                            0 => (0.0, 1.0, 0.0),
-                                            1 => (1.0, 0.0, 0.0),
-                                            2 => (0.0, 0.0, 1.0),
-                                            _ => (0.0, 0.0, 0.0),
-                                        },
-                                        texture_coords: (0.0, 0.0),
-                                        block_type: b_t[n],
-                                    },
-                                    BlockVertex {
-                                        position: (x[0] as f32, x[1] as f32, x[2] as f32),
-                                        normal: match d {
-                                            0 => (0.0, 1.0, 0.0),
-                                            1 => (1.0, 0.0, 0.0),
-                                            2 => (0.0, 0.0, 1.0),
-                                            _ => (0.0, 0.0, 0.0),
-                                        },
-                                        texture_coords: (1.0 * w as f32, 0.0),
-                                        block_type: b_t[n],
-                                    },
-                                    BlockVertex {
-                                        position: (
-                                            (x[0] + du[0] + dv[0]) as f32,
-                                            (x[1] + du[1] + dv[1]) as f32,
-                                            (x[2] + du[2] + dv[2]) as f32,
-                                        ),
-                                        normal: match d {
-                                            0 => (0.0, 1.0, 0.0),
-                                            1 => (1.0, 0.0, 0.0),
-                                            2 => (0.0, 0.0, 1.0),
-                                            _ => (0.0, 0.0, 0.0),
-                                        },
-                                        texture_coords: (0.0, 1.0 * h as f32),
-                                        block_type: b_t[n],
-                                    },
-                                    BlockVertex {
-                                        position: (
-                                            (x[0] + dv[0]) as f32,
-                                            (x[1] + dv[1]) as f32,
-                                            (x[2] + dv[2]) as f32,
-                                        ),
-                                        normal: match d {
-                                            0 => (0.0, 1.0, 0.0),
-                                            1 => (1.0, 0.0, 0.0),
-                                            2 => (0.0, 0.0, 1.0),
-                                            _ => (0.0, 0.0, 0.0),
-                                        },
-                                        texture_coords: (1.0 * w as f32, 1.0 * h as f32),
-                                        block_type: b_t[n],
-                                    },
+                            for (k, (cu, cv, corner_ao)) in corners.into_iter().enumerate() {
+                                vertices.push(BlockVertex {
+                                    position: (
+                                        (x[0] + cu * du[0] + cv * dv[0]) as f32,
+                                        (x[1] + cu * du[1] + cv * dv[1]) as f32,
+                                        (x[2] + cu * du[2] + cv * dv[2]) as f32,
+                                    ),
+                                    normal,
+                                    texture_coords: ((k % 2 * w) as f32, (k / 2 * h) as f32),
+                                    block_type: b_t[n],
+                                    ambient_occlusion: corner_ao as f32 / 3.0,
+                                    light: (
+                                        light[n].0 as f32 / MAX_LIGHT_LEVEL as f32,
+                                        light[n].1 as f32 / MAX_LIGHT_LEVEL as f32,
+                                    ),
+                                });
+                            }
+                            let diagonal_in_second_pair = if !flip[n] {
+                                ao10 + ao01 > ao00 + ao11
+                            } else {
+                                ao00 + ao11 > ao10 + ao01
+                            };
+
+                            // split the quad along the brighter diagonal, otherwise the occlusion of
+                            // a single corner is stretched along the whole diagonal
+                            let vert_count = vertices.len() as u32;
+                            if diagonal_in_second_pair {
+                                indices.extend_from_slice(&[
+                                    vert_count - 4,
+                                    vert_count - 3,
+                                    vert_count - 1,
+                                    vert_count - 4,
+                                    vert_count - 1,
+                                    vert_count - 2,
                                 ]);
                             } else {
-                                vertices.extend_from_slice(&[
-                                    BlockVertex {
-                                        position: (x[0] as f32, x[1] as f32, x[2] as f32),
-                                        normal: match d {
-                                            0 => (0.0, 1.0, 0.0),
-                                            1 => (1.0, 0.0, 0.0),
-                                            2 => (0.0, 0.0, 1.0),
-                                            _ => (0.0, 0.0, 0.0),
-                                        },
-                                        texture_coords: (0.0, 0.0),
-                                        block_type: b_t[n],
-                                    },
-                                    BlockVertex {
-                                        position: (
-                                            (x[0] + du[0]) as f32,
-                                            (x[1] + du[1]) as f32,
-                                            (x[2] + du[2]) as f32,
-                                        ),
-                                        normal: match d {
-                                            0 => (0.0, 1.0, 0.0),
-                                            1 => (1.0, 0.0, 0.0),
-                                            2 => (0.0, 0.0, 1.0),
-                                            _ => (0.0, 0.0, 0.0),
-                                        },
-                                        texture_coords: (1.0 * w as f32, 0.0),
-                                        block_type: b_t[n],
-                                    },
-                                    BlockVertex {
-                                        position: (
-                                            (x[0] + dv[0]) as f32,
-                                            (x[1] + dv[1]) as f32,
-                                            (x[2] + dv[2]) as f32,
-                                        ),
-                                        normal: match d {
-                                            0 => (0.0, 1.0, 0.0),
-                                            1 => (1.0, 0.0, 0.0),
-                                            2 => (0.0, 0.0, 1.0),
-                                            _ => (0.0, 0.0, 0.0),
-                                        },
-                                        texture_coords: (0.0, 1.0 * h as f32),
-                                        block_type: b_t[n],
-                                    },
-                                    BlockVertex {
-                                        position: (
-                                            (x[0] + du[0] + dv[0]) as f32,
-                                            (x[1] + du[1] + dv[1]) as f32,
-                                            (x[2] + du[2] + dv[2]) as f32,
-                                        ),
-                                        normal: match d {
-                                            0 => (0.0, 1.0, 0.0),
-                                            1 => (1.0, 0.0, 0.0),
-                                            2 => (0.0, 0.0, 1.0),
-                                            _ => (0.0, 0.0, 0.0),
-                                        },
-                                        texture_coords: (1.0 * w as f32, 1.0 * h as f32),
-                                        block_type: b_t[n],
-                                    },
+                                indices.extend_from_slice(&[
+                                    vert_count - 4,
+                                    vert_count - 3,
+                                    vert_count - 2,
+                                    vert_count - 2,
+                                    vert_count - 3,
+                                    vert_count - 1,
                                 ]);
                             }
 
-                            let vert_count = vertices.len() as u32;
-                            indices.extend_from_slice(&[
-                                vert_count - 4,
-                                vert_count - 3,
-                                vert_count - 2,
-                                vert_count - 2,
-                                vert_count - 3,
-                                vert_count - 1,
-                            ]);
-
                             // Clear this part of the mask, so we don't add duplicate faces
                             for l in 0..h {
                                 for k in 0..w {
@@ -307,43 +281,333 @@ impl VoxelChunk {
         }
         ChunkMesh::new(vertices, Some(indices))
     }
-}
 
-impl Chunk for VoxelChunk {
-    fn new(seed: u64, position: (f32, f32, f32), _: usize) -> Self {
-        let generator = Source::perlin(seed).scale([0.003; 2]);
-        let hills = Source::perlin(seed).scale([0.01; 2]);
-        let tiny_hills = Source::perlin(seed).scale([0.1; 2]);
-        let offset: f64 = 16777216.0;
-        let blocks: ArrayBase<ndarray::OwnedRepr<Option<Block>>, Dim<[usize; 3]>> = Array3::<
-            Option<Block>,
-        >::from_shape_fn(
-            [CHUNK_SIZE, CHUNK_SIZE, CHUNK_SIZE],
-            |(x, y, z)| {
-                let sample_point = (
-                    (position.0 * CHUNK_SIZE_FLOAT) as f64 + x as f64 + offset,
-                    (position.2 * CHUNK_SIZE_FLOAT) as f64 + z as f64 + offset,
+    /// Occlusion of the corners of a face from the blocks around the empty cell `air` in front of it,
+    /// ordered (0, 0), (1, 0), (1, 1), (0, 1) along the face's u and v axes. 3 is unoccluded, 0 fully occluded
+    fn calculate_face_ao(&self, air: [i32; 3], u: usize, v: usize) -> [u8; 4] {
+        let is_solid = |du: i32, dv: i32| {
+            let mut position = air;
+            position[u] += du;
+            position[v] += dv;
+            if position.iter().any(|p| *p < 0 || *p >= CHUNK_SIZE as i32) {
+                return false;
+            }
+            matches!(
+                self.blocks.get((
+                    position[0] as usize,
+                    position[1] as usize,
+                    position[2] as usize
+                )),
+                Some(Some(_))
+            )
+        };
+        [(-1, -1), (1, -1), (1, 1), (-1, 1)].map(|(cu, cv)| {
+            let side_u = is_solid(cu, 0);
+            let side_v = is_solid(0, cv);
+            if side_u && side_v {
+                0
+            } else {
+                3 - side_u as u8 - side_v as u8 - is_solid(cu, cv) as u8
+            }
+        })
+    }
+
+    /// Builds a mesh of the outer faces of all ore veins, used for the x-ray view
+    fn calculate_ore_mesh(&self) -> ChunkMesh<BlockVertex> {
+        const FACES: [([i32; 3], [[f32; 3]; 4]); 6] = [
+            (
+                [1, 0, 0],
+                [
+                    [1.0, 0.0, 0.0],
+                    [1.0, 1.0, 0.0],
+                    [1.0, 0.0, 1.0],
+                    [1.0, 1.0, 1.0],
+                ],
+            ),
+            (
+                [-1, 0, 0],
+                [
+                    [0.0, 0.0, 1.0],
+                    [0.0, 1.0, 1.0],
+                    [0.0, 0.0, 0.0],
+                    [0.0, 1.0, 0.0],
+                ],
+            ),
+            (
+                [0, 1, 0],
+                [
+                    [0.0, 1.0, 0.0],
+                    [0.0, 1.0, 1.0],
+                    [1.0, 1.0, 0.0],
+                    [1.0, 1.0, 1.0],
+                ],
+            ),
+            (
+                [0, -1, 0],
+                [
+                    [0.0, 0.0, 1.0],
+                    [0.0, 0.0, 0.0],
+                    [1.0, 0.0, 1.0],
+                    [1.0, 0.0, 0.0],
+                ],
+            ),
+            (
+                [0, 0, 1],
+                [
+                    [1.0, 0.0, 1.0],
+                    [1.0, 1.0, 1.0],
+                    [0.0, 0.0, 1.0],
+                    [0.0, 1.0, 1.0],
+                ],
+            ),
+            (
+                [0, 0, -1],
+                [
+                    [0.0, 0.0, 0.0],
+                    [0.0, 1.0, 0.0],
+                    [1.0, 0.0, 0.0],
+                    [1.0, 1.0, 0.0],
+                ],
+            ),
+        ];
+        let mut vertices: Vec<BlockVertex> = Vec::new();
+        let mut indices: Vec<u32> = Vec::new();
+        for ((x, y, z), ore) in self.resources.iter() {
+            for (direction, corners) in FACES.iter() {
+                let neighbour = (
+                    x as i32 + direction[0],
+                    y as i32 + direction[1],
+                    z as i32 + direction[2],
                 );
-                let noise_value = (1.0 + generator.sample([sample_point.0, sample_point.1])) / 2.0;
-                let hills_value =
-                    (1.0 + hills.sample([sample_point.0, sample_point.1])) / 2.0 * 0.2;
-                let tiny_hills_value =
-                    (1.0 + tiny_hills.sample([sample_point.0, sample_point.1])) / 2.0 * 0.01;
-                if ((noise_value + hills_value + tiny_hills_value) * CHUNK_SIZE as f64) < (y as f64)
+                // faces between two blocks of the same ore are never visible
+                if neighbour.0 >= 0
+                    && neighbour.1 >= 0
+                    && neighbour.2 >= 0
+                    && self.resources.get((
+                        neighbour.0 as usize,
+                        neighbour.1 as usize,
+                        neighbour.2 as usize,
+                    )) == Some(ore)
                 {
-                    return None;
+                    continue;
                 }
-                Some(Block::new(1))
-            },
-        );
+                let vert_count = vertices.len() as u32;
+                for (i, corner) in corners.iter().enumerate() {
+                    vertices.push(BlockVertex {
+                        position: (
+                            x as f32 + corner[0],
+                            y as f32 + corner[1],
+                            z as f32 + corner[2],
+                        ),
+                        normal: (
+                            direction[0] as f32,
+                            direction[1] as f32,
+                            direction[2] as f32,
+                        ),
+                        texture_coords: ((i / 2) as f32, (i % 2) as f32),
+                        block_type: ORE_BLOCK_TYPE_OFFSET + ore as u32,
+                        ambient_occlusion: 1.0,
+                        light: (1.0, 0.0),
+                    });
+                }
+                indices.extend_from_slice(&[
+                    vert_count,
+                    vert_count + 1,
+                    vert_count + 2,
+                    vert_count + 2,
+                    vert_count + 1,
+                    vert_count + 3,
+                ]);
+            }
+        }
+        ChunkMesh::new(vertices, Some(indices))
+    }
+
+    /// Lights and meshes the chunk at the grid `position` made of `blocks`, unless it is
+    /// `data_only`
+    fn from_blocks(
+        config: &WorldGenConfig,
+        position: (f32, f32, f32),
+        blocks: Array3<Option<Block>>,
+        resources: ResourceLayer,
+        data_only: bool,
+    ) -> Self {
         let mut chunk = VoxelChunk {
             position,
             blocks,
+            resources,
+            mining_yields: Vec::new(),
             mesh: None,
+            ore_mesh: None,
+            lightmap: None,
+            light: ChunkLight::default(),
+            data_only,
         };
+        if data_only {
+            return chunk;
+        }
+        chunk.light = chunk.calculate_light();
+        if config.bake_lightmaps {
+            chunk.lightmap = Some(ChunkLightmap::bake(CHUNK_SIZE, |x, y, z| {
+                chunk.blocks[[x, y, z]].is_some()
+            }));
+        }
         chunk.mesh = Some(chunk.calculate_mesh());
+        chunk.ore_mesh = Some(chunk.calculate_ore_mesh());
         chunk
     }
+
+    /// Local block positions in the order the codec stores them, x fastest, then z, then y
+    fn get_codec_order() -> impl Iterator<Item = (usize, usize, usize)> {
+        (0..CHUNK_SIZE).flat_map(|y| {
+            (0..CHUNK_SIZE).flat_map(move |z| (0..CHUNK_SIZE).map(move |x| (x, y, z)))
+        })
+    }
+
+    /// Lights the whole chunk, called once its blocks are generated
+    fn calculate_light(&self) -> ChunkLight {
+        let emitters = LightEmitters::get_all();
+        ChunkLight::propagate(
+            CHUNK_SIZE,
+            |x, y, z| self.blocks[[x, y, z]].is_some(),
+            |x, y, z| {
+                self.blocks[[x, y, z]]
+                    .as_ref()
+                    .and_then(|block| emitters.get(&block.type_id).copied())
+                    .unwrap_or(0)
+            },
+        )
+    }
+
+    /// Updates the light around the blocks at the chunk space positions `changed`
+    fn relight(&mut self, changed: &[(usize, usize, usize)]) {
+        let blocks = &self.blocks;
+        self.light.update(
+            changed,
+            |x, y, z| blocks[[x, y, z]].is_some(),
+            |x, y, z| {
+                blocks[[x, y, z]]
+                    .as_ref()
+                    .map_or(0, |block| LightEmitters::get(block.type_id))
+            },
+        );
+    }
+
+    /// Skylight and block light at the chunk space `position`, 0 to `MAX_LIGHT_LEVEL`
+    pub fn get_light(&self, position: (usize, usize, usize)) -> (u8, u8) {
+        self.light
+            .get(position.0 as i32, position.1 as i32, position.2 as i32)
+    }
+
+    pub fn get_resource(&self, position: (usize, usize, usize)) -> Option<Ore> {
+        self.resources.get(position)
+    }
+
+    /// Returns the ore at the world position `position` if it lies inside this chunk
+    pub fn get_resource_at(&self, position: Point3<f32>) -> Option<Ore> {
+        if !self.get_bounds().contains(position) {
+            return None;
+        }
+        self.resources.get((
+            (position.x - self.position.0 * CHUNK_SIZE_FLOAT) as usize,
+            (position.y - self.position.1 * CHUNK_SIZE_FLOAT) as usize,
+            (position.z - self.position.2 * CHUNK_SIZE_FLOAT) as usize,
+        ))
+    }
+
+    pub fn get_resources(&self) -> &ResourceLayer {
+        &self.resources
+    }
+
+    /// Generates the blocks and resources of the chunk at the grid `position`
+    fn generate(config: &WorldGenConfig, position: (f32, f32, f32), data_only: bool) -> Self {
+        let biome_map = BiomeMap::new(config);
+        let cave = Source::perlin(config.seed).scale([config.cave_frequency; 3]);
+        let offset: f64 = 16777216.0;
+        let mut columns = Vec::with_capacity(CHUNK_SIZE * CHUNK_SIZE);
+        for x in 0..CHUNK_SIZE {
+            for z in 0..CHUNK_SIZE {
+                columns.push(biome_map.sample(
+                    (position.0 * CHUNK_SIZE_FLOAT) as f64 + x as f64,
+                    (position.2 * CHUNK_SIZE_FLOAT) as f64 + z as f64,
+                ));
+            }
+        }
+        let blocks: ArrayBase<ndarray::OwnedRepr<Option<Block>>, Dim<[usize; 3]>> =
+            Array3::<Option<Block>>::from_shape_fn(
+                [CHUNK_SIZE, CHUNK_SIZE, CHUNK_SIZE],
+                |(x, y, z)| {
+                    let column = &columns[x * CHUNK_SIZE + z];
+                    let height = column.height - position.1 * CHUNK_SIZE_FLOAT;
+                    if height < y as f32 {
+                        return None;
+                    }
+                    let cave_value =
+                        (1.0 + cave.sample([
+                            (position.0 * CHUNK_SIZE_FLOAT) as f64 + x as f64 + offset,
+                            (position.1 * CHUNK_SIZE_FLOAT) as f64 + y as f64 + offset,
+                            (position.2 * CHUNK_SIZE_FLOAT) as f64 + z as f64 + offset,
+                        ])) / 2.0;
+                    if cave_value as f32 > config.cave_threshold - column.cave_density * 2.0 {
+                        return None;
+                    }
+                    if height - (y as f32) < config.surface_depth {
+                        Some(Block::new(column.surface_block))
+                    } else {
+                        Some(Block::new(2))
+                    }
+                },
+            );
+        let resources = ResourceLayer::generate(
+            &ResourceGenerator::new(config),
+            (
+                (position.0 * CHUNK_SIZE_FLOAT) as f64,
+                (position.1 * CHUNK_SIZE_FLOAT) as f64,
+                (position.2 * CHUNK_SIZE_FLOAT) as f64,
+            ),
+            |x, y, z| matches!(&blocks[[x, y, z]], Some(block) if block.type_id == 2),
+        );
+        VoxelChunk::from_blocks(config, position, blocks, resources, data_only)
+    }
+
+    /// Restores the chunk at the grid `position` from data encoded with `serialize`
+    fn decode(
+        config: &WorldGenConfig,
+        position: (f32, f32, f32),
+        data: &[u8],
+        data_only: bool,
+    ) -> Result<Self, CodecError> {
+        let mut reader = ByteReader::new(data);
+        PayloadKind::Blocks.read_header(&mut reader)?;
+        let block_count = CHUNK_SIZE * CHUNK_SIZE * CHUNK_SIZE;
+        let block_runs = BlockRuns::read(&mut reader, block_count)?;
+        let resource_runs = BlockRuns::read(&mut reader, block_count)?;
+        let mut blocks =
+            Array3::<Option<Block>>::from_shape_fn([CHUNK_SIZE, CHUNK_SIZE, CHUNK_SIZE], |_| None);
+        for ((x, y, z), type_id) in VoxelChunk::get_codec_order().zip(block_runs.decode()) {
+            blocks[[x, y, z]] = (type_id != 0).then(|| Block::new(type_id));
+        }
+        let mut ores = Array3::<u32>::zeros([CHUNK_SIZE, CHUNK_SIZE, CHUNK_SIZE]);
+        for ((x, y, z), ore) in VoxelChunk::get_codec_order().zip(resource_runs.decode()) {
+            ores[[x, y, z]] = ore;
+        }
+        let resources = ResourceLayer::from_fn(|x, y, z| Ore::from_id(ores[[x, y, z]]));
+        Ok(VoxelChunk::from_blocks(
+            config, position, blocks, resources, data_only,
+        ))
+    }
+}
+
+impl Chunk for VoxelChunk {
+    type Vertex = BlockVertex;
+
+    fn new(config: &WorldGenConfig, position: (f32, f32, f32), _: usize) -> Self {
+        VoxelChunk::generate(config, position, false)
+    }
+
+    fn new_data_only(config: &WorldGenConfig, position: (f32, f32, f32), _: usize) -> Self {
+        VoxelChunk::generate(config, position, true)
+    }
     fn get_bounds(&self) -> ChunkBounds {
         ChunkBounds {
             min: (
@@ -360,63 +624,13 @@ impl Chunk for VoxelChunk {
     }
 
     fn buffer_data(&mut self) {
-        if let Some(mesh) = &mut self.mesh {
-            mesh.buffer_data();
+        if let Some(ore_mesh) = &mut self.ore_mesh {
+            ore_mesh.buffer_data();
         }
     }
 
-    fn process_line(&mut self, line: &Line, button: &glfw::MouseButton) -> bool {
-        // calculate the block that the line intersects with
-        let step_size = 0.1;
-        let max_distance = line.length;
-
-        let mut modified = false;
-        let mut last_position = (0, 0, 0);
-        for i in 0..(max_distance / step_size) as i32 {
-            let position = line.position + line.direction * (i as f32 * step_size);
-            // check if position is within the bounds of this chunk
-            if position.x < self.position.0 * CHUNK_SIZE_FLOAT
-                || position.x >= (self.position.0 + 1.0) * CHUNK_SIZE_FLOAT
-            {
-                continue;
-            }
-            if position.y < self.position.1 * CHUNK_SIZE_FLOAT
-                || position.y >= (self.position.1 + 1.0) * CHUNK_SIZE_FLOAT
-            {
-                continue;
-            }
-            if position.z < self.position.2 * CHUNK_SIZE_FLOAT
-                || position.z >= (self.position.2 + 1.0) * CHUNK_SIZE_FLOAT
-            {
-                continue;
-            }
-            let block_position = (
-                (position.x - self.position.0 * CHUNK_SIZE_FLOAT) as usize,
-                (position.y - self.position.1 * CHUNK_SIZE_FLOAT) as usize,
-                (position.z - self.position.2 * CHUNK_SIZE_FLOAT) as usize,
-            );
-            if let Some(block) = self.blocks.get(block_position) {
-                if block.is_some() {
-                    if button == &glfw::MouseButton::Button1 {
-                        // println!("(Terrain {},{},{}) Block hit at {:?}", self.position.0, self.position.1, self.position.2, block_position);
-                        self.blocks[[block_position.0, block_position.1, block_position.2]] = None;
-                        self.mesh = Some(self.calculate_mesh());
-                        modified = true;
-                        break;
-                    }
-                    if button == &glfw::MouseButton::Button2 {
-                        // println!("(Terrain {},{},{}) Block hit at {:?}", self.position.0, self.position.1, self.position.2, block_position);
-                        self.blocks[[last_position.0, last_position.1, last_position.2]] =
-                            Some(Block::new(2));
-                        self.mesh = Some(self.calculate_mesh());
-                        modified = true;
-                        break;
-                    }
-                }
-            }
-            last_position = block_position;
-        }
-        modified
+    fn get_mesh(&self) -> Option<&ChunkMesh<BlockVertex>> {
+        self.mesh.as_ref()
     }
 
     fn get_position(&self) -> Point3<f32> {
@@ -427,20 +641,109 @@ impl Chunk for VoxelChunk {
         )
     }
 
-    fn get_shader_source() -> (String, String) {
+    fn get_shader_source() -> (ShaderSource, ShaderSource) {
         (
-            include_str!("vertex.glsl").to_string(),
-            include_str!("fragment.glsl").to_string(),
+            ShaderSource::new("terrain/voxel/vertex.glsl", include_str!("vertex.glsl")),
+            ShaderSource::new("terrain/voxel/fragment.glsl", include_str!("fragment.glsl"))
+                .preprocess(include_environment),
         )
     }
 
-    fn get_textures() -> Vec<Texture> {
-        let grass_texture = Texture::new();
-        grass_texture.load_from_file(std::path::Path::new("assets/grass.png"));
-        let stone_texture = Texture::new();
-        stone_texture.load_from_file(std::path::Path::new("assets/stone.png"));
+    fn get_textures() -> Vec<Arc<Texture>> {
+        ["grass.png", "stone.png"]
+            .into_iter()
+            .map(|path| AssetManager::load::<Texture>(path).expect("Image not found"))
+            .collect()
+    }
+
+    fn get_block(&self, position: (usize, usize, usize)) -> Option<u32> {
+        let block = self.blocks.get(position)?;
+        Some(block.as_ref().map_or(0, |block| block.type_id))
+    }
+
+    fn set_blocks(&mut self, blocks: &[((usize, usize, usize), u32)]) -> bool {
+        let mut changed = Vec::new();
+        for &(position, type_id) in blocks {
+            if self
+                .get_block(position)
+                .is_none_or(|current| current == type_id)
+            {
+                continue;
+            }
+            self.blocks[[position.0, position.1, position.2]] =
+                (type_id != 0).then(|| Block::new(type_id));
+            // removing a block mines its ore, placed blocks never contain resources
+            if let Some(ore) = self.resources.take(position) {
+                if type_id == 0 {
+                    self.mining_yields.push((ore, ore.get_yield()));
+                }
+            }
+            changed.push(position);
+        }
+        if changed.is_empty() {
+            return false;
+        }
+        if self.data_only {
+            return true;
+        }
+        self.relight(&changed);
+        if self.lightmap.is_some() {
+            self.lightmap = Some(ChunkLightmap::bake(CHUNK_SIZE, |x, y, z| {
+                self.blocks[[x, y, z]].is_some()
+            }));
+        }
+        self.mesh = Some(self.calculate_mesh());
+        self.ore_mesh = Some(self.calculate_ore_mesh());
+        true
+    }
 
-        vec![grass_texture, stone_texture]
+    fn take_mining_yields(&mut self) -> Vec<(Ore, u32)> {
+        std::mem::take(&mut self.mining_yields)
+    }
+
+    fn serialize(&self) -> Option<Vec<u8>> {
+        let mut writer = ByteWriter::new();
+        PayloadKind::Blocks.write_header(&mut writer);
+        BlockRuns::encode(VoxelChunk::get_codec_order().map(|(x, y, z)| {
+            self.blocks[[x, y, z]]
+                .as_ref()
+                .map_or(0, |block| block.type_id)
+        }))
+        .write(&mut writer);
+        BlockRuns::encode(
+            VoxelChunk::get_codec_order()
+                .map(|position| self.resources.get(position).map_or(0, |ore| ore as u32)),
+        )
+        .write(&mut writer);
+        Some(writer.into_bytes())
+    }
+
+    fn deserialize(
+        config: &WorldGenConfig,
+        position: (f32, f32, f32),
+        data: &[u8],
+    ) -> Result<Self, CodecError> {
+        VoxelChunk::decode(config, position, data, false)
+    }
+
+    fn deserialize_data_only(
+        config: &WorldGenConfig,
+        position: (f32, f32, f32),
+        data: &[u8],
+    ) -> Result<Self, CodecError> {
+        VoxelChunk::decode(config, position, data, true)
+    }
+
+    fn get_lightmap(&self) -> Option<&ChunkLightmap> {
+        self.lightmap.as_ref()
+    }
+
+    fn get_buffer_size(&self) -> usize {
+        self.mesh.as_ref().map_or(0, |mesh| mesh.get_buffer_size())
+            + self
+                .ore_mesh
+                .as_ref()
+                .map_or(0, |mesh| mesh.get_buffer_size())
     }
 
     fn get_triangle_count(&self) -> usize {
@@ -472,43 +775,77 @@ impl Chunk for VoxelChunk {
     }
 }
 
+impl InspectableComponent for VoxelChunk {
+    fn get_inspector_name(&self) -> &str {
+        "Chunk"
+    }
+}
+
 impl Component for VoxelChunk {
     fn update(&mut self, _: &mut Scene, _: &mut Entity, _: f64) {}
 
-    fn render(
-        &self,
-        scene: &Scene,
-        _: &Entity,
-        view_projection: &Matrix4<f32>,
-        parent_transform: &Matrix4<f32>,
-    ) {
-        if let Some(terrain) = scene.get_component::<Terrain<VoxelChunk>>() {
-            let shader = terrain.get_shader();
-            if let Some(mesh) = &self.mesh {
-                if !mesh.is_buffered() {
-                    panic!("Mesh is not buffered");
-                }
-                shader.bind();
-                shader.set_uniform_mat4("viewProjection", &view_projection);
-                unsafe {
-                    gl::Enable(gl::CULL_FACE);
-                }
-                mesh.render(
-                    &shader,
-                    &(parent_transform
-                        * Matrix4::from_translation(Vector3::new(
-                            self.position.0 * CHUNK_SIZE_FLOAT,
-                            self.position.1 * CHUNK_SIZE_FLOAT,
-                            self.position.2 * CHUNK_SIZE_FLOAT,
-                        ))),
-                    None,
+    fn get_event_priority(&self, _: EventCategory) -> Option<i32> {
+        None
+    }
+
+    fn handle_event(
+        &mut self,
+        _: &mut glfw::Glfw,
+        _: &mut glfw::Window,
+        _: &glfw::WindowEvent,
+    ) -> bool {
+        false
+    }
+
+    fn as_inspectable(&self) -> Option<&dyn InspectableComponent> {
+        Some(self)
+    }
+
+    fn as_inspectable_mut(&mut self) -> Option<&mut dyn InspectableComponent> {
+        Some(self)
+    }
+}
+
+impl RenderPass for OreXRayPass {
+    fn get_name(&self) -> &str {
+        "Ore x-ray"
+    }
+
+    fn get_order(&self) -> i32 {
+        DEBUG_PASS_ORDER
+    }
+
+    fn render(&mut self, scene: &Scene, _: &Window, _: &mut RenderResources) {
+        if !XRAY.load(Ordering::Relaxed) {
+            return;
+        }
+        let (Some(terrain), Some(camera)) = (
+            scene.get_component::<Terrain<VoxelChunk>>(),
+            scene.get_component::<CameraComponent>(),
+        ) else {
+            return;
+        };
+        let shader = terrain.get_shader();
+        shader.bind();
+        shader.set_uniform_mat4("viewProjection", &camera.get_view_projection());
+        // the veins are drawn over everything, including the geometry drawn after the terrain
+        unsafe {
+            gl::Disable(gl::DEPTH_TEST);
+            gl::DepthMask(gl::FALSE);
+        }
+        for entity in scene.get_entities_with_component::<VoxelChunk>() {
+            let Some(chunk) = entity.get_component::<VoxelChunk>() else {
+                continue;
+            };
+            if let Some(ore_mesh) = chunk.ore_mesh.as_ref().filter(|mesh| mesh.is_buffered()) {
+                ore_mesh.draw(
+                    shader,
+                    &Matrix4::from_translation(chunk.get_position().to_vec()),
                 );
-                unsafe {
-                    gl::Disable(gl::CULL_FACE);
-                }
             }
         }
+        unsafe {
+            gl::DepthMask(gl::TRUE);
+        }
     }
-
-    fn handle_event(&mut self, _: &mut glfw::Glfw, _: &mut glfw::Window, _: &glfw::WindowEvent) {}
 }
@@ -0,0 +1,67 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::{biome::Biome, migration::MigrationPolicy, resources::OreVein};
+
+mod world_gen;
+
+/// Version of the terrain generators, saved with the world's deltas. Bump it whenever a change
+/// makes an existing seed generate different terrain, and keep the previous behavior behind
+/// `config.generator_version` so worlds locked to an older version generate as before.
+///
+/// 2: the terrain surface is clamped to `MAX_SURFACE_HEIGHT`
+pub const GENERATOR_VERSION: u32 = 2;
+
+/// Parameters of the terrain generators, loadable from a RON or TOML file
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct WorldGenConfig {
+    pub seed: u64,
+    /// Number of chunks loaded in every direction around the origin
+    pub chunk_radius: usize,
+    pub cave_frequency: f64,
+    /// Cave noise above this value is hollowed out, lower values produce more caves
+    pub cave_threshold: f32,
+    pub climate_frequency: f64,
+    /// Distance in climate space over which two biomes blend into each other
+    pub biome_blend_width: f32,
+    /// Number of blocks below the surface that use the biome's surface block
+    pub surface_depth: f32,
+    /// Layered height noise used by the marching cubes generator
+    pub height_layers: Vec<NoiseLayer>,
+    /// Iso value of the marching cubes surface
+    pub iso_value: f32,
+    /// Shades the dual contouring terrain by how far the surrounding terrain hides the sun, which
+    /// gives soft self shadows at any distance without a shadow map. Costs a coarse height grid
+    /// and a horizon trace per vertex while generating the chunks
+    pub sun_occlusion: bool,
+    /// Darkens the sky ambient light of the dual contouring terrain inside caves and under
+    /// overhangs. Costs a few rays through the cave noise per vertex below the surface while
+    /// generating the chunks
+    pub interior_darkening: bool,
+    /// Bakes the ambient occlusion and sky visibility of the block terrain into a lightmap. Costs
+    /// a few sky traces per 4x4x4 blocks while generating the chunks
+    pub bake_lightmaps: bool,
+    /// Meshes the dual contouring chunks of the outer rings around the origin at a lower
+    /// resolution, halving it with every ring from the second one on. The borders between chunks
+    /// of different resolutions are covered with skirts
+    pub level_of_detail: bool,
+    pub biomes: Vec<Biome>,
+    pub ores: Vec<OreVein>,
+    /// How worlds saved with an older generator are loaded
+    pub migration_policy: MigrationPolicy,
+    /// Generator version the chunks are generated with, set by `WorldMigration::migrate`
+    #[serde(skip)]
+    pub generator_version: u32,
+    /// Chunks generated with an older generator by their grid position, set by
+    /// `WorldMigration::migrate`
+    #[serde(skip)]
+    pub pinned_chunks: BTreeMap<(i32, i32, i32), u32>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct NoiseLayer {
+    pub frequency: f64,
+    pub amplitude: f64,
+}
@@ -0,0 +1,89 @@
+use std::{borrow::Cow, path::Path};
+
+use crate::terrain::{biome::Biome, resources::OreVein, CHUNK_RADIUS};
+
+use super::{NoiseLayer, WorldGenConfig, GENERATOR_VERSION};
+
+impl WorldGenConfig {
+    /// Loads a config from `path`, the format is chosen by the file extension (`.ron` or `.toml`)
+    pub fn load(path: &str) -> Result<WorldGenConfig, Box<dyn std::error::Error>> {
+        let source = std::fs::read_to_string(path)?;
+        match Path::new(path).extension().and_then(|e| e.to_str()) {
+            Some("toml") => WorldGenConfig::from_toml(&source),
+            Some("ron") => WorldGenConfig::from_ron(&source),
+            _ => Err(format!("Unsupported world generation config format: {}", path).into()),
+        }
+    }
+
+    pub fn from_ron(source: &str) -> Result<WorldGenConfig, Box<dyn std::error::Error>> {
+        Ok(ron::from_str(source)?)
+    }
+
+    pub fn from_toml(source: &str) -> Result<WorldGenConfig, Box<dyn std::error::Error>> {
+        Ok(toml::from_str(source)?)
+    }
+
+    pub fn to_ron(&self) -> Result<String, Box<dyn std::error::Error>> {
+        Ok(ron::ser::to_string_pretty(
+            self,
+            ron::ser::PrettyConfig::default(),
+        )?)
+    }
+
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.seed = seed;
+        self
+    }
+
+    /// The config to generate the chunk at the grid position `chunk` with, a copy with the
+    /// older generator version if the chunk is pinned to one
+    pub fn for_chunk(&self, chunk: (i32, i32, i32)) -> Cow<'_, WorldGenConfig> {
+        match self.pinned_chunks.get(&chunk) {
+            Some(&version) if version != self.generator_version => {
+                let mut config = self.clone();
+                config.generator_version = version;
+                config.pinned_chunks.clear();
+                Cow::Owned(config)
+            }
+            _ => Cow::Borrowed(self),
+        }
+    }
+}
+
+impl Default for WorldGenConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            chunk_radius: CHUNK_RADIUS,
+            cave_frequency: 0.1,
+            cave_threshold: 0.8,
+            climate_frequency: 0.0007,
+            biome_blend_width: 0.35,
+            surface_depth: 3.0,
+            height_layers: vec![
+                NoiseLayer {
+                    frequency: 0.003,
+                    amplitude: 1.0,
+                },
+                NoiseLayer {
+                    frequency: 0.01,
+                    amplitude: 0.2,
+                },
+                NoiseLayer {
+                    frequency: 0.1,
+                    amplitude: 0.01,
+                },
+            ],
+            iso_value: 0.3,
+            sun_occlusion: true,
+            interior_darkening: true,
+            bake_lightmaps: true,
+            level_of_detail: false,
+            biomes: Biome::defaults(),
+            ores: OreVein::defaults(),
+            migration_policy: Default::default(),
+            generator_version: GENERATOR_VERSION,
+            pinned_chunks: Default::default(),
+        }
+    }
+}
@@ -0,0 +1,522 @@
+use std::{
+    cell::RefCell,
+    error::Error,
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::{mpsc::Receiver, Arc},
+    time::Instant,
+};
+
+use cgmath::{InnerSpace, Matrix4, Point3, Quaternion, SquareMatrix, Vector3};
+
+use ferrite::{
+    core::{
+        determinism::Determinism,
+        net::{Client, ClientEvent, Server},
+        renderer::{render_workers::RenderWorkers, transparency::TransparencyQueue},
+        save_worker::{SaveEvent, SaveWorker},
+        utils::DataSource,
+    },
+    prelude::{
+        Application, Asset, AssetManager, ComponentParams, ComponentRegistry, Layer,
+        RenderSettings, Scene, SceneFile, Window, WorldGenConfig,
+    },
+    terrain::{
+        deltas::ChunkDeltas, dual_contouring::DualContouringChunk, voxel::VoxelChunk, Chunk,
+        ChunkCoord,
+    },
+};
+
+type Test = fn(&mut Application);
+
+const TIMESTEP: f64 = 1.0 / 60.0;
+
+fn main() {
+    let tests: [(&str, Test); 11] = [
+        (
+            "layers_update_with_fixed_timestep",
+            layers_update_with_fixed_timestep,
+        ),
+        (
+            "scene_events_flush_every_frame",
+            scene_events_flush_every_frame,
+        ),
+        ("saves_flush_on_detach", saves_flush_on_detach),
+        ("clients_see_the_same_edits", clients_see_the_same_edits),
+        (
+            "scene_files_use_registered_components",
+            scene_files_use_registered_components,
+        ),
+        ("assets_load_once_per_path", assets_load_once_per_path),
+        (
+            "render_settings_stay_in_range",
+            render_settings_stay_in_range,
+        ),
+        (
+            "transparent_draws_run_back_to_front",
+            transparent_draws_run_back_to_front,
+        ),
+        ("mined_ore_yields_once", mined_ore_yields_once),
+        ("lod_borders_have_no_gaps", lod_borders_have_no_gaps),
+        ("render_workers_resume_panics", render_workers_resume_panics),
+    ];
+    let mut application = Application::headless(640, 360);
+    Determinism::enable(0, TIMESTEP);
+    for (name, test) in tests {
+        test(&mut application);
+        application.detach_layers();
+        println!("test {} ... ok", name);
+    }
+}
+
+#[derive(Clone)]
+struct Tick(u32);
+
+/// Records its updates and the events its scene delivered, shared with the test
+struct RecordingLayer {
+    scene: Scene,
+    ticks: Receiver<Tick>,
+    frames: DataSource<u32>,
+    received: Rc<RefCell<Vec<u32>>>,
+    elapsed: DataSource<f64>,
+}
+
+impl Layer for RecordingLayer {
+    fn on_update(&mut self, _window: &Window, delta_time: f64) {
+        let frame = self.frames.read() + 1;
+        self.frames.write(frame);
+        self.elapsed.write(self.elapsed.read() + delta_time);
+        self.scene.emit(Tick(frame));
+        self.scene.update(delta_time);
+        self.received
+            .borrow_mut()
+            .extend(self.ticks.try_iter().map(|tick| tick.0));
+    }
+
+    fn on_event(
+        &mut self,
+        _glfw: &mut glfw::Glfw,
+        _window: &mut glfw::Window,
+        _event: &glfw::WindowEvent,
+    ) -> bool {
+        false
+    }
+
+    fn get_name(&self) -> &str {
+        "Recording"
+    }
+}
+
+/// Queues several versions of a save file as it is detached
+struct SavingLayer {
+    path: PathBuf,
+}
+
+impl Layer for SavingLayer {
+    fn on_update(&mut self, _window: &Window, _delta_time: f64) {}
+
+    fn on_detach(&mut self) {
+        for version in 1..=3 {
+            SaveWorker::write(&self.path, format!("version {}", version).into_bytes());
+        }
+    }
+
+    fn on_event(
+        &mut self,
+        _glfw: &mut glfw::Glfw,
+        _window: &mut glfw::Window,
+        _event: &glfw::WindowEvent,
+    ) -> bool {
+        false
+    }
+
+    fn get_name(&self) -> &str {
+        "Saving"
+    }
+}
+
+impl RecordingLayer {
+    fn new() -> Self {
+        let mut scene = Scene::new();
+        let ticks = scene.subscribe::<Tick>();
+        Self {
+            scene,
+            ticks,
+            frames: DataSource::new(0),
+            received: Rc::new(RefCell::new(Vec::new())),
+            elapsed: DataSource::new(0.0),
+        }
+    }
+}
+
+fn layers_update_with_fixed_timestep(application: &mut Application) {
+    assert!(application.is_headless());
+    let layer = RecordingLayer::new();
+    let frames = layer.frames.clone();
+    let elapsed = layer.elapsed.clone();
+    application.add_layer(Box::new(layer));
+
+    application.run_frames(10);
+    assert_eq!(frames.read(), 10);
+    assert!((elapsed.read() - 10.0 * TIMESTEP).abs() < 1e-9);
+
+    application.run_frames(5);
+    assert_eq!(frames.read(), 15);
+}
+
+fn scene_events_flush_every_frame(application: &mut Application) {
+    let layer = RecordingLayer::new();
+    let received = layer.received.clone();
+    application.add_layer(Box::new(layer));
+
+    application.run_frames(3);
+    assert_eq!(*received.borrow(), vec![1, 2, 3]);
+}
+
+fn saves_flush_on_detach(application: &mut Application) {
+    let path = std::env::temp_dir().join("ferrite_headless_save.txt");
+    let _ = std::fs::remove_file(&path);
+    let events = SaveWorker::subscribe();
+    application.add_layer(Box::new(SavingLayer { path: path.clone() }));
+    application.run_frames(1);
+
+    application.detach_layers();
+    assert_eq!(std::fs::read_to_string(&path).unwrap(), "version 3");
+    let saved: Vec<SaveEvent> = events
+        .try_iter()
+        .filter(|event| event.get_path() == path)
+        .collect();
+    assert!(matches!(saved.as_slice(), [SaveEvent::Saved { .. }]));
+    let _ = std::fs::remove_file(&path);
+}
+
+/// Updates the server and the clients until `done` returns true for them and the events of the
+/// second client, returns the events
+fn exchange(
+    server: &mut Server<VoxelChunk>,
+    clients: &mut [Client; 2],
+    done: impl Fn(&[Client; 2], &[ClientEvent]) -> bool,
+) -> Vec<ClientEvent> {
+    let start = Instant::now();
+    let mut events = Vec::new();
+    while !done(clients, &events) {
+        assert!(start.elapsed().as_secs() < 5, "timed out, got {:?}", events);
+        server.update();
+        clients[0].update();
+        events.extend(clients[1].update());
+    }
+    events
+}
+
+fn clients_see_the_same_edits(_application: &mut Application) {
+    let config = WorldGenConfig::default().with_seed(7);
+    let mut server = Server::<VoxelChunk>::bind("127.0.0.1:0", config.clone()).unwrap();
+    let address = server.get_address().unwrap();
+    let mut clients = [
+        Client::connect(address).unwrap(),
+        Client::connect(address).unwrap(),
+    ];
+    exchange(&mut server, &mut clients, |clients, _| {
+        clients.iter().all(|client| client.get_player().is_some())
+    });
+    for client in &clients {
+        assert_eq!(client.get_config().unwrap().seed, config.seed);
+    }
+
+    let edit = ((3, 40, -5), 2);
+    clients[0].edit_blocks(vec![edit]);
+    exchange(&mut server, &mut clients, |_, events| {
+        events.contains(&ClientEvent::BlocksEdited { blocks: vec![edit] })
+    });
+    let coord = ChunkCoord::new(0, 0, -1);
+    assert_eq!(server.get_edits(&coord), vec![edit]);
+    // no client loaded the chunk yet, the server generates it once it is requested
+    assert!(server.get_chunk(&coord).is_none());
+
+    // a chunk loaded later is sent with the edits made before
+    clients[1].request_chunk(coord);
+    let events = exchange(&mut server, &mut clients, |_, events| {
+        events
+            .iter()
+            .any(|event| matches!(event, ClientEvent::ChunkReceived { .. }))
+    });
+    let Some(ClientEvent::ChunkReceived {
+        coord: received,
+        data,
+    }) = events
+        .into_iter()
+        .find(|event| matches!(event, ClientEvent::ChunkReceived { .. }))
+    else {
+        unreachable!()
+    };
+    assert_eq!(received, coord);
+    let local = (3, 40, 123);
+    assert_eq!(server.get_chunk(&coord).unwrap().get_block(local), Some(2));
+    let chunk = VoxelChunk::deserialize(&config, (0.0, 0.0, -1.0), &data).unwrap();
+    assert_eq!(chunk.get_block(local), Some(2));
+
+    let player = clients[0].get_player().unwrap();
+    let position = Point3::new(1.0, 2.0, 3.0);
+    clients[0].set_player_transform(position, Quaternion::new(1.0, 0.0, 0.0, 0.0));
+    exchange(&mut server, &mut clients, |_, events| {
+        events.contains(&ClientEvent::EntityMoved { id: player })
+    });
+    assert_eq!(clients[1].get_entity(player).unwrap().position, position);
+}
+
+fn mined_ore_yields_once(_application: &mut Application) {
+    let mut config = WorldGenConfig::default().with_seed(7);
+    config.bake_lightmaps = false;
+    let mut chunk = VoxelChunk::new(&config, (0.0, 0.0, 0.0), 0);
+    let (position, ore) = chunk.get_resources().iter().next().unwrap();
+    assert!(chunk.set_blocks(&[(position, 0)]));
+    assert_eq!(chunk.take_mining_yields(), vec![(ore, ore.get_yield())]);
+    assert!(chunk.take_mining_yields().is_empty());
+    assert_eq!(chunk.get_resource(position), None);
+
+    // the dig is saved with the chunk's deltas
+    let block = (position.0 as i32, position.1 as i32, position.2 as i32);
+    let mut deltas = ChunkDeltas::new();
+    assert!(deltas.set_blocks(&[(block, 0)]));
+    assert!(!deltas.set_blocks(&[(block, 0)]));
+    let delta = deltas.get(chunk.get_position()).unwrap();
+    assert_eq!(delta.blocks.get(&block), Some(&0));
+}
+
+/// Distance along `direction` at which the ray from `origin` hits the triangle, either side
+fn intersect(
+    origin: Point3<f32>,
+    direction: Vector3<f32>,
+    [a, b, c]: [Point3<f32>; 3],
+) -> Option<f32> {
+    let (ab, ac) = (b - a, c - a);
+    let p = direction.cross(ac);
+    let determinant = ab.dot(p);
+    if determinant.abs() < 1e-6 {
+        return None;
+    }
+    let offset = origin - a;
+    let u = offset.dot(p) / determinant;
+    let q = offset.cross(ab);
+    let v = direction.dot(q) / determinant;
+    if u < 0.0 || v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+    Some(ac.dot(q) / determinant)
+}
+
+fn get_triangles(chunk: &impl Chunk) -> Vec<[Point3<f32>; 3]> {
+    let origin = chunk.get_position();
+    let vertices = chunk.get_vertices();
+    chunk
+        .get_indices()
+        .iter()
+        .map(|triangle| triangle.map(|i| origin + Vector3::from(vertices[i as usize])))
+        .collect()
+}
+
+fn lod_borders_have_no_gaps(_application: &mut Application) {
+    let mut config = WorldGenConfig::default().with_seed(3);
+    config.level_of_detail = true;
+    config.sun_occlusion = false;
+    config.interior_darkening = false;
+    // the second and third ring are meshed with 64 and 32 cells per side
+    let fine = DualContouringChunk::new(&config, (2.0, 0.0, 0.0), 2);
+    let coarse = DualContouringChunk::new(&config, (3.0, 0.0, 0.0), 3);
+    let triangles = [get_triangles(&fine), get_triangles(&coarse)];
+    let all = || triangles.iter().flatten();
+
+    let border = coarse.get_position().x;
+    let top = |triangles: &[[Point3<f32>; 3]], x: f32, z: f32| {
+        triangles
+            .iter()
+            .filter_map(|triangle| {
+                intersect(Point3::new(x, 1000.0, z), -Vector3::unit_y(), *triangle)
+            })
+            .filter(|t| *t >= 0.0)
+            .reduce(f32::min)
+            .map(|t| 1000.0 - t)
+    };
+    let mut columns = 0;
+    for z in (4..124).step_by(8).map(|z| z as f32 + 0.5) {
+        let (Some(fine_height), Some(coarse_height)) = (
+            top(&triangles[0], border - 1.0, z),
+            top(&triangles[1], border + 5.0, z),
+        ) else {
+            continue;
+        };
+        columns += 1;
+        // a ray crossing the border between the two surfaces leaks through any gap
+        let (low, high) = (
+            fine_height.min(coarse_height),
+            fine_height.max(coarse_height),
+        );
+        for step in 1..4 {
+            let height = low + (high - low) * step as f32 / 4.0;
+            let origin = Point3::new(border - 1.0, height, z);
+            assert!(
+                all().any(|triangle| intersect(origin, Vector3::unit_x(), *triangle)
+                    .is_some_and(|t| (0.0..=6.0).contains(&t))),
+                "gap at the lod border at z {} between {} and {}",
+                z,
+                low,
+                high
+            );
+        }
+    }
+    assert!(columns > 0);
+}
+
+fn render_workers_resume_panics(_application: &mut Application) {
+    let jobs = (0..RenderWorkers::get_count() * 4).map(|i| move || i * 2);
+    let results = RenderWorkers::record(jobs).wait();
+    assert_eq!(
+        results,
+        (0..results.len()).map(|i| i * 2).collect::<Vec<_>>()
+    );
+
+    let recording = RenderWorkers::record([|| -> u32 { panic!("recording failed") }]);
+    let panic = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| recording.wait()));
+    assert_eq!(
+        panic.unwrap_err().downcast_ref::<&str>(),
+        Some(&"recording failed")
+    );
+    // the worker that panicked keeps taking jobs
+    let jobs = (0..RenderWorkers::get_count()).map(|_| || 1);
+    assert_eq!(
+        RenderWorkers::record(jobs).wait().len(),
+        RenderWorkers::get_count()
+    );
+}
+
+#[derive(Default, serde::Deserialize)]
+#[serde(default)]
+struct TagParams {
+    tags: Vec<String>,
+}
+
+fn scene_files_use_registered_components(_application: &mut Application) {
+    ComponentRegistry::register("tags", |_, entity, params: &ComponentParams| {
+        for tag in params.parse::<TagParams>()?.tags {
+            entity.add_tag(&tag);
+        }
+        Ok(())
+    });
+    let file = SceneFile::from_ron(
+        r#"SceneFile(
+            entities: [
+                (
+                    name: "parent",
+                    position: (1.0, 2.0, 3.0),
+                    components: [(type: "tags", params: (tags: ["a", "b"]))],
+                    children: [(name: "child", components: [(type: "tags")])],
+                ),
+            ],
+        )"#,
+    )
+    .unwrap();
+    let mut scene = Scene::new();
+    let handles = file.spawn(&mut scene).unwrap();
+    assert_eq!(handles.len(), 1);
+    let parent = scene.get_entity(&handles[0]).unwrap();
+    assert_eq!(parent.get_tags(), &vec!["a".to_string(), "b".to_string()]);
+    assert_eq!(parent.get_position(), Point3::new(1.0, 2.0, 3.0));
+    assert_eq!(parent.get_children()[0].get_name(), "child");
+
+    let unknown = SceneFile::from_ron(r#"(entities: [(name: "e", components: [(type: "none")])])"#)
+        .unwrap()
+        .spawn(&mut scene);
+    assert!(unknown.is_err());
+    assert!(ComponentRegistry::unregister("tags"));
+}
+
+/// Contents of a text file
+struct Note(String);
+
+impl Asset for Note {
+    fn load(path: &Path) -> Result<Self, Box<dyn Error>> {
+        Ok(Note(std::fs::read_to_string(path)?))
+    }
+}
+
+fn assets_load_once_per_path(_application: &mut Application) {
+    let base = std::env::temp_dir().join("ferrite_assets_base");
+    let overrides = std::env::temp_dir().join("ferrite_assets_overrides");
+    for directory in [&base, &overrides] {
+        std::fs::create_dir_all(directory).unwrap();
+    }
+    std::fs::write(base.join("note.txt"), "base").unwrap();
+    std::fs::write(base.join("other.txt"), "other").unwrap();
+    std::fs::write(overrides.join("note.txt"), "override").unwrap();
+    let search_paths = AssetManager::get_search_paths();
+    AssetManager::set_search_paths(vec![base.clone()]);
+    AssetManager::add_search_path(&overrides);
+
+    let loaded = AssetManager::get_loaded_count();
+    let note = AssetManager::load::<Note>("note.txt").unwrap();
+    assert_eq!(note.0, "override");
+    assert!(Arc::ptr_eq(
+        &note,
+        &AssetManager::load::<Note>("note.txt").unwrap()
+    ));
+    assert_eq!(AssetManager::load::<Note>("other.txt").unwrap().0, "other");
+    assert_eq!(AssetManager::get_loaded_count(), loaded + 1);
+    assert!(AssetManager::load::<Note>("missing.txt").is_err());
+
+    drop(note);
+    assert!(AssetManager::get::<Note>("note.txt").is_none());
+    assert_eq!(AssetManager::get_loaded_count(), loaded);
+
+    AssetManager::set_search_paths(search_paths);
+    for directory in [&base, &overrides] {
+        let _ = std::fs::remove_dir_all(directory);
+    }
+}
+
+fn render_settings_stay_in_range(application: &mut Application) {
+    let previous = RenderSettings::get();
+    RenderSettings::set(RenderSettings {
+        msaa_samples: 6,
+        vsync: true,
+        resolution_scale: 10.0,
+    });
+    let settings = RenderSettings::get();
+    assert_eq!(settings.msaa_samples, 4);
+    assert_eq!(settings.resolution_scale, 2.0);
+    assert_eq!(settings.get_render_size(640, 360), (1280, 720));
+
+    RenderSettings::set(RenderSettings {
+        msaa_samples: 1,
+        vsync: false,
+        resolution_scale: 0.5,
+    });
+    assert!(!RenderSettings::get().is_msaa_enabled());
+    RenderSettings::cycle_msaa_samples();
+    assert_eq!(RenderSettings::get().msaa_samples, 2);
+    // applying vsync to the hidden window does nothing
+    application.run_frames(1);
+
+    RenderSettings::set(previous);
+}
+
+fn transparent_draws_run_back_to_front(application: &mut Application) {
+    let order = RefCell::new(Vec::new());
+    // the camera looks down the negative z axis from the origin
+    let mut queue = TransparencyQueue::new(Matrix4::identity());
+    queue.push(Point3::new(0.0, 0.0, -2.0), || {
+        order.borrow_mut().push("near")
+    });
+    queue.push(Point3::new(0.0, 0.0, -10.0), || {
+        order.borrow_mut().push("far")
+    });
+    queue.push_at_depth(f32::INFINITY, || order.borrow_mut().push("ocean"));
+    queue.push(Point3::new(5.0, 0.0, -2.0), || {
+        order.borrow_mut().push("beside")
+    });
+    assert_eq!(queue.len(), 4);
+    queue.render();
+    assert_eq!(*order.borrow(), ["ocean", "far", "near", "beside"]);
+
+    // a frame with the dedicated pass still renders
+    application.run_frames(1);
+}
@@ -0,0 +1,339 @@
+use ferrite::core::{
+    input::{ActionMap, Binding, Input},
+    renderer::{
+        text::TextAlignment,
+        ui::{
+            definition::{UIBindings, UIDefinition},
+            harness::UITestHarness,
+            primitives::{Anchor, Layout},
+            UIElement, UIRenderer, UI,
+        },
+    },
+    utils::DataSource,
+};
+use glfw::{Action, Key, Modifiers, MouseButton, StandardCursor, WindowEvent};
+
+type Test = fn(&mut UITestHarness);
+
+fn main() {
+    let tests: [(&str, Test); 13] = [
+        ("button_click_calls_on_click", button_click_calls_on_click),
+        ("button_hover_changes_cursor", button_hover_changes_cursor),
+        ("input_types_while_focused", input_types_while_focused),
+        ("input_edits_at_caret", input_edits_at_caret),
+        (
+            "drag_value_drags_with_modifiers",
+            drag_value_drags_with_modifiers,
+        ),
+        ("drag_value_types_after_click", drag_value_types_after_click),
+        ("list_view_selects_rows", list_view_selects_rows),
+        (
+            "anchored_button_follows_resize",
+            anchored_button_follows_resize,
+        ),
+        (
+            "scroll_container_clips_and_scrolls",
+            scroll_container_clips_and_scrolls,
+        ),
+        ("text_wraps_between_words", text_wraps_between_words),
+        ("ui_scale_scales_hit_testing", ui_scale_scales_hit_testing),
+        (
+            "action_map_editor_rebinds_actions",
+            action_map_editor_rebinds_actions,
+        ),
+        (
+            "definition_binds_named_actions",
+            definition_binds_named_actions,
+        ),
+    ];
+    let mut harness = UITestHarness::new();
+    for (name, test) in tests {
+        harness.reset();
+        test(&mut harness);
+        println!("test {} ... ok", name);
+    }
+}
+
+fn button_click_calls_on_click(harness: &mut UITestHarness) {
+    let clicks = DataSource::new(0);
+    let counter = clicks.clone();
+    harness.add(UI::button(
+        "Save",
+        Box::new(move |_| counter.write(counter.read() + 1)),
+        |button| button.position(10.0, 10.0),
+    ));
+
+    assert!(harness.click(20.0, 15.0));
+    assert!(!harness.click(300.0, 300.0));
+    assert_eq!(clicks.read(), 1);
+}
+
+fn button_hover_changes_cursor(harness: &mut UITestHarness) {
+    harness.add(UI::button("Save", Box::new(|_| {}), |button| {
+        button.position(10.0, 10.0)
+    }));
+
+    harness.move_cursor(20.0, 15.0);
+    assert_eq!(harness.get_cursor(), Some(StandardCursor::Hand));
+    harness.move_cursor(300.0, 300.0);
+    assert_eq!(harness.get_cursor(), None);
+}
+
+fn input_types_while_focused(harness: &mut UITestHarness) {
+    let name = DataSource::new(String::new());
+    harness.add(UI::input(name.clone(), |input| {
+        input.position(10.0, 10.0).size(200.0, 26.0)
+    }));
+
+    harness.type_text("ignored");
+    assert_eq!(name.read(), "");
+
+    assert!(harness.click(20.0, 20.0));
+    harness.type_text("stone");
+    assert!(harness.press_key(Key::Backspace));
+    assert_eq!(name.read(), "ston");
+
+    harness.click(400.0, 400.0);
+    harness.type_text("x");
+    assert_eq!(name.read(), "ston");
+}
+
+fn input_edits_at_caret(harness: &mut UITestHarness) {
+    let name = DataSource::new(String::from("stone"));
+    harness.add(UI::input(name.clone(), |input| {
+        input.position(10.0, 10.0).size(200.0, 26.0)
+    }));
+    harness.render();
+
+    assert!(harness.click(190.0, 20.0));
+    harness.press_key(Key::Left);
+    harness.press_key(Key::Left);
+    harness.type_text("x");
+    assert_eq!(name.read(), "stoxne");
+
+    harness.press_key(Key::Home);
+    harness.hold_key(Key::LeftShift);
+    harness.press_key(Key::Right);
+    harness.press_key(Key::Right);
+    harness.release_key(Key::LeftShift);
+    harness.hold_key(Key::LeftControl);
+    harness.press_key(Key::C);
+    harness.release_key(Key::LeftControl);
+    assert_eq!(harness.get_clipboard().as_deref(), Some("st"));
+
+    harness.press_key(Key::End);
+    harness.hold_key(Key::LeftControl);
+    harness.press_key(Key::V);
+    harness.press_key(Key::A);
+    harness.release_key(Key::LeftControl);
+    assert_eq!(name.read(), "stoxnest");
+    harness.press_key(Key::Backspace);
+    assert_eq!(name.read(), "");
+
+    harness.set_clipboard("line\nbreak");
+    harness.hold_key(Key::LeftControl);
+    harness.press_key(Key::V);
+    harness.release_key(Key::LeftControl);
+    assert_eq!(name.read(), "linebreak");
+}
+
+fn drag_value_drags_with_modifiers(harness: &mut UITestHarness) {
+    let value = DataSource::new(10.0_f32);
+    harness.add(UI::drag_value(value.clone(), |drag_value| {
+        drag_value
+            .position(10.0, 10.0)
+            .speed(0.5)
+            .precision(1)
+            .range(0.0, 100.0)
+    }));
+
+    assert!(harness.drag((20.0, 20.0), (60.0, 20.0)));
+    assert_eq!(value.read(), 30.0);
+
+    harness.hold_key(Key::LeftShift);
+    harness.drag((20.0, 20.0), (24.0, 20.0));
+    harness.release_key(Key::LeftShift);
+    assert_eq!(value.read(), 50.0);
+
+    harness.drag((20.0, 20.0), (500.0, 20.0));
+    assert_eq!(value.read(), 100.0);
+}
+
+fn drag_value_types_after_click(harness: &mut UITestHarness) {
+    let value = DataSource::new(3_u32);
+    harness.add(UI::drag_value(value.clone(), |drag_value| {
+        drag_value.position(10.0, 10.0).precision(0)
+    }));
+
+    harness.click(20.0, 20.0);
+    harness.press_key(Key::Backspace);
+    harness.type_text("42");
+    harness.press_key(Key::Enter);
+    assert_eq!(value.read(), 42);
+
+    harness.click(20.0, 20.0);
+    harness.type_text("7");
+    harness.press_key(Key::Escape);
+    assert_eq!(value.read(), 42);
+
+    harness.click(20.0, 20.0);
+    harness.press_key(Key::Backspace);
+    harness.press_key(Key::Backspace);
+    harness.type_text("-");
+    harness.press_key(Key::Enter);
+    assert_eq!(value.read(), 42);
+}
+
+fn list_view_selects_rows(harness: &mut UITestHarness) {
+    let selected = DataSource::new(0_usize);
+    let target = selected.clone();
+    harness.add(UI::list_view(
+        Box::new(|| 100),
+        Box::new(|index| -> Box<dyn UIElement> {
+            UI::text(&format!("Item {}", index), 16.0, |text| text)
+        }),
+        move |list_view| {
+            list_view
+                .position(10.0, 10.0)
+                .size(200.0, 200.0)
+                .row_height(20.0)
+                .on_select(Box::new(move |_, index| target.write(index)))
+        },
+    ));
+
+    assert!(harness.click(50.0, 55.0));
+    assert_eq!(selected.read(), 2);
+    assert!(harness.press_key(Key::Down));
+    assert_eq!(selected.read(), 3);
+    assert!(harness.press_key(Key::End));
+    assert_eq!(selected.read(), 99);
+
+    harness.click(400.0, 400.0);
+    assert!(!harness.press_key(Key::Home));
+    assert_eq!(selected.read(), 99);
+}
+
+fn anchored_button_follows_resize(harness: &mut UITestHarness) {
+    let button = UI::button("Save", Box::new(|_| {}), |button| {
+        button.position(-110.0, -30.0)
+    });
+    harness
+        .ui
+        .add_with_layout(button, Layout::new(Anchor::BottomRight));
+
+    // 100x20 button, 10 pixels in from the bottom right corner
+    assert!(harness.click(1070.0 + 10.0, 670.0 + 5.0));
+    harness.resize(800, 600);
+    assert!(!harness.click(1070.0 + 10.0, 670.0 + 5.0));
+    assert!(harness.click(590.0 + 10.0, 550.0 + 5.0));
+}
+
+fn scroll_container_clips_and_scrolls(harness: &mut UITestHarness) {
+    let clicked = DataSource::new(0_usize);
+    let targets = clicked.clone();
+    harness.add(UI::scroll_container(move |mut scroll_container| {
+        scroll_container = scroll_container
+            .position(10.0, 10.0, 0.0)
+            .size(200.0, 100.0);
+        for i in 0..10 {
+            let target = targets.clone();
+            scroll_container = scroll_container.add_child(
+                None,
+                UI::button(
+                    &format!("Item {}", i),
+                    Box::new(move |_| target.write(i)),
+                    |b| b,
+                ),
+            );
+        }
+        scroll_container
+    }));
+    harness.render();
+
+    // buttons are 25 pixels apart starting 5 pixels below the top of the viewport
+    assert!(harness.click(20.0, 45.0));
+    assert_eq!(clicked.read(), 1);
+    // the fifth button lies below the viewport
+    assert!(!harness.click(20.0, 120.0));
+    assert_eq!(clicked.read(), 1);
+
+    assert!(harness.scroll(50.0, 50.0, -1.0));
+    harness.render();
+    assert!(harness.click(20.0, 40.0));
+    assert_eq!(clicked.read(), 2);
+    assert!(harness.click(20.0, 95.0));
+    assert_eq!(clicked.read(), 4);
+    assert!(!harness.scroll(400.0, 400.0, -1.0));
+}
+
+fn text_wraps_between_words(harness: &mut UITestHarness) {
+    let mut single_line = UI::text("alpha beta gamma", 16.0, |text| text);
+    let mut wrapped = UI::text("alpha beta gamma", 16.0, |text| {
+        text.max_width(60.0).alignment(TextAlignment::Right)
+    });
+    let mut broken = UI::text("alpha\nbeta", 16.0, |text| text);
+    single_line.render(&mut harness.scene);
+    wrapped.render(&mut harness.scene);
+    broken.render(&mut harness.scene);
+
+    // every word gets a line of its own, right aligned within the maximum width
+    let line_height = single_line.get_size().height;
+    assert!(wrapped.get_size().height > 2.0 * line_height);
+    assert!(wrapped.get_size().width <= 60.0 + 1.0);
+    assert!(wrapped.get_size().width < single_line.get_size().width);
+    assert!(broken.get_size().height > line_height);
+}
+
+fn ui_scale_scales_hit_testing(harness: &mut UITestHarness) {
+    UIRenderer::set_scale(2.0);
+    let clicked = DataSource::new(0_usize);
+    let counter = clicked.clone();
+    harness.add(UI::button(
+        "Save",
+        Box::new(move |_| counter.write(counter.read() + 1)),
+        |button| button.position(10.0, 10.0),
+    ));
+    harness.render();
+
+    // the 100x20 button covers 20 to 220 and 20 to 60 in window pixels
+    assert!(harness.click(200.0, 40.0));
+    assert!(!harness.click(230.0, 40.0));
+    assert_eq!(clicked.read(), 1);
+}
+
+fn action_map_editor_rebinds_actions(harness: &mut UITestHarness) {
+    Input::set_action_map(ActionMap::default());
+    harness.add(UI::action_map_editor(|editor| editor.position(10.0, 10.0)));
+    harness.render();
+
+    // rows are 22 high in alphabetical order, "dig" is the fourth
+    let dig = (50.0, 10.0 + 3.0 * 22.0 + 11.0);
+    assert!(harness.click(dig.0, dig.1));
+    assert!(harness.press_key(Key::G));
+    assert_eq!(
+        Input::get_action_map().get_bindings("dig"),
+        &[Binding::Key(Key::G)]
+    );
+
+    // right clicking restores the default
+    harness.move_cursor(dig.0, dig.1);
+    assert!(harness.send(WindowEvent::MouseButton(
+        MouseButton::Button2,
+        Action::Press,
+        Modifiers::empty()
+    )));
+    assert!(Input::is_bound("dig", Binding::Mouse(MouseButton::Button1)));
+}
+
+fn definition_binds_named_actions(harness: &mut UITestHarness) {
+    let resets = DataSource::new(0);
+    let counter = resets.clone();
+    let bindings = UIBindings::new().action("reset", move |_| counter.write(counter.read() + 1));
+    let bound: UIDefinition = ron::from_str(r#"Button(label: "Reset", action: "reset")"#).unwrap();
+    let unbound: UIDefinition = ron::from_str(r#"Button(label: "Reset", action: "rest")"#).unwrap();
+    harness.add(bindings.build(&bound));
+    harness.add(bindings.build(&unbound));
+
+    assert!(harness.click(20.0, 10.0));
+    assert_eq!(resets.read(), 1);
+}
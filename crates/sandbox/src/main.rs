@@ -1,76 +1,256 @@
-use cgmath::Deg;
 use glfw::{Glfw, WindowEvent};
 
 use ferrite::{
     core::{
-        application::{Application, Layer},
-        camera::{Camera, CameraController, Projection},
-        entity::{
-            component::{camera_component::CameraComponent, debug_component::DebugController},
-            Entity,
-        },
-        model::{
-            animation_graph::{AnimationGraph, State},
-            Animation,
-        },
+        input::{ActionMap, Binding, GamepadEvent, Input},
+        logger::Logger,
+        net::{Server, ServerEvent, DEFAULT_PORT},
+        photo_mode::PhotoMode,
+        profiler::Profiler,
         renderer::{
-            light::skylight::SkyLight,
-            ui::{primitives::UIElementHandle, UIRenderer, UI},
+            motion_blur::{MotionBlurPass, VelocityPass},
+            render_settings::{MAX_MSAA_SAMPLES, MAX_RESOLUTION_SCALE, MIN_RESOLUTION_SCALE},
+            ssr::SsrPass,
+            ui::{
+                cursor::CursorTheme,
+                definition::{UIBindings, UIDefinitionFile},
+                primitives::{Anchor, Layout},
+            },
         },
-        scene::Scene,
-        window::Window,
+        time_control::{MAX_TIME_SCALE, MIN_TIME_SCALE},
+    },
+    prelude::*,
+    terrain::{
+        blueprint::{BlueprintEditor, BlueprintLibrary},
+        dual_contouring::DualContouringChunk,
+        voxel::VoxelChunk,
     },
-    player::Player,
-    terrain::{dual_contouring::DualContouringChunk, Terrain},
 };
 use std::error::Error;
 
+const WORLD_GEN_CONFIG: &str = "assets/worldgen.ron";
+const ENGINE_CONFIG: &str = "assets/engine.ron";
+/// Entities of the world, the scene compiled in is used if the file does not exist
+const SCENE: &str = "assets/scene.ron";
+const DEFAULT_SCENE: &str = include_str!("scene.ron");
+const FONTS: &str = "assets/fonts";
+/// Optional images replacing the system cursors over the UI
+const CURSOR_THEME: &str = "assets/cursors/theme.ron";
+/// Structure templates saved with the blueprint editor, registered on startup
+const BLUEPRINTS: &str = "assets/blueprints";
+const CONTROLS: &str = "assets/controls.ron";
+/// Optional debug panels, edits to the file show up while the sandbox runs
+const DEBUG_UI: &str = "assets/debug_ui.ron";
+/// Distance from the camera within which trees and rocks are chopped
+const CHOP_RADIUS: f32 = 6.0;
+/// Updates per second of the headless server
+const SERVER_TICK_RATE: u64 = 30;
+/// Region files the headless server saves the edited chunks to
+const SERVER_WORLD: &str = "assets/server_world";
+
 fn main() {
+    // `sandbox --server [address]` hosts the world without a window
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(index) = args.iter().position(|arg| arg == "--server") {
+        let address = args
+            .get(index + 1)
+            .cloned()
+            .unwrap_or_else(|| format!("0.0.0.0:{}", DEFAULT_PORT));
+        if let Err(err) = run_server(&address) {
+            log::error!("The server stopped: {}", err);
+        }
+        return;
+    }
     let mut application = Application::new(1280, 720, "Engine");
-    if let Ok(layer) = WorldLayer::new(1280, 720) {
+    if let Ok(layer) = WorldLayer::new() {
         application.add_layer(Box::new(layer));
         application.start();
     }
 }
 
+fn run_server(address: &str) -> Result<(), Box<dyn Error>> {
+    Logger::init();
+    let config = if std::path::Path::new(WORLD_GEN_CONFIG).exists() {
+        WorldGenConfig::load(WORLD_GEN_CONFIG)?
+    } else {
+        WorldGenConfig::default().with_seed(2)
+    };
+    // the server owns a voxel world, the chunks players can edit block by block
+    let mut server = Server::<VoxelChunk>::bind(address, config)?;
+    server.set_save_directory(SERVER_WORLD);
+    loop {
+        for event in server.update() {
+            match event {
+                ServerEvent::Connected { client, .. } => log::info!("Client {} joined", client),
+                ServerEvent::Disconnected { client } => log::info!("Client {} left", client),
+                ServerEvent::BlocksEdited { client, blocks } => {
+                    log::debug!("Client {} edited {} blocks", client, blocks.len())
+                }
+            }
+        }
+        std::thread::sleep(std::time::Duration::from_millis(1000 / SERVER_TICK_RATE));
+    }
+}
+
+/// Saves the engine config along with the current render settings
+fn save_engine_config(scene: &mut Scene) {
+    scene.config.render = RenderSettings::get();
+    if let Err(err) = scene.config.save(ENGINE_CONFIG) {
+        log::error!("Failed to save engine config: {}", err);
+    }
+}
+
 struct WorldLayer {
     scene: Scene,
     ui: UIRenderer,
+    photo_mode: PhotoMode,
+    debug_ui: UIDefinitionFile,
+    blueprint_editor: BlueprintEditor,
+    /// Blueprint placed with "blueprint_place", the last one saved or loaded
+    blueprint: Option<String>,
 }
 
 impl WorldLayer {
-    pub fn new(width: u32, height: u32) -> Result<WorldLayer, Box<dyn Error>> {
+    pub fn new() -> Result<WorldLayer, Box<dyn Error>> {
         let mut scene = Scene::new();
+        if std::path::Path::new(ENGINE_CONFIG).exists() {
+            scene.config = EngineConfig::load(ENGINE_CONFIG)?;
+        } else {
+            // The sandbox shows off motion blur until the config turns it off
+            scene.config.motion_blur.enabled = true;
+        }
+        RenderSettings::set(scene.config.render.clone());
         scene.add_shadow_map(4096, 4096);
-        let mut camera = Camera::new((0.0, 0.0, 0.0), Deg(-263.0), Deg(-30.0));
-        camera.set_relative_position((0.25, 1.33, -2.05));
-        let projection: Projection = Projection::new(width, height, Deg(45.0), 0.1, 100.0);
-        let camera_controller = CameraController::new(10.0, 1.0);
-        let mut entity = Entity::new("camera");
-        entity.add_component(CameraComponent::new(camera, projection, camera_controller));
-        scene.add_entity(entity);
+        scene.add_render_pass(Box::new(VelocityPass::new()));
+        scene.add_render_pass(Box::new(SsrPass::new()));
+        scene.add_render_pass(Box::new(MotionBlurPass::new()));
+        if std::path::Path::new(SCENE).exists() {
+            scene.load_file(SCENE)?;
+        } else {
+            SceneFile::from_ron(DEFAULT_SCENE)?.spawn(&mut scene)?;
+        }
 
-        let mut skylight = Entity::new("skylight");
-        skylight.add_component(SkyLight::new((10.0, 600.0, 10.0)));
-        scene.add_entity(skylight);
+        let mut actions = if std::path::Path::new(CONTROLS).exists() {
+            ActionMap::load(CONTROLS)?
+        } else {
+            ActionMap::default()
+        };
+        if actions.get_bindings("chop").is_empty() {
+            actions.bind("chop", Binding::Key(glfw::Key::E));
+        }
+        for (action, key) in [
+            ("blueprint_corner", glfw::Key::B),
+            ("blueprint_anchor", glfw::Key::V),
+            ("blueprint_save", glfw::Key::N),
+            ("blueprint_place", glfw::Key::P),
+        ] {
+            if actions.get_bindings(action).is_empty() {
+                actions.bind(action, Binding::Key(key));
+            }
+        }
+        Input::set_action_map(actions);
 
+        if std::path::Path::new(FONTS).exists() {
+            FontManager::load_directory(FONTS)?;
+        }
         let ui = UIRenderer::new();
+        if std::path::Path::new(CURSOR_THEME).exists() {
+            CursorTheme::set(CursorTheme::load(CURSOR_THEME)?);
+        }
+        if std::path::Path::new(BLUEPRINTS).exists() {
+            BlueprintLibrary::load_directory(BLUEPRINTS)?;
+        }
 
-        let mut terrain_entity = Entity::new("terrain");
-        terrain_entity.add_component(Terrain::<DualContouringChunk>::new(2));
-        terrain_entity.add_child(Player::new(
-            &mut scene,
-            (0.0, 55.0, 0.0),
-            create_animation_graph()?,
-        )?);
+        let camera_speed = scene
+            .get_component::<CameraComponent>()
+            .unwrap()
+            .get_camera_controller()
+            .get_speed_ref();
+        let bindings = UIBindings::new()
+            .number("camera_speed", camera_speed)
+            .action("save_engine_config", save_engine_config);
 
-        scene.add_entity(terrain_entity);
+        Ok(Self {
+            scene,
+            ui,
+            photo_mode: PhotoMode::new(),
+            debug_ui: UIDefinitionFile::new(DEBUG_UI, bindings),
+            blueprint_editor: BlueprintEditor::new(),
+            blueprint: BlueprintLibrary::get_names().pop(),
+        })
+    }
 
-        let mut debug = Entity::new("debug");
-        debug.add_component(DebugController::new());
-        scene.add_entity(debug);
+    /// World block position of the camera, where the blueprint editor selects and places
+    fn get_camera_block(&self) -> Option<(i32, i32, i32)> {
+        let position = self
+            .scene
+            .get_component::<CameraComponent>()?
+            .get_camera()
+            .get_position();
+        Some((
+            position.x.floor() as i32,
+            position.y.floor() as i32,
+            position.z.floor() as i32,
+        ))
+    }
 
-        Ok(Self { scene, ui })
+    /// Selects, saves and places structure templates, returns true if `event` triggered an
+    /// action of the blueprint editor
+    fn handle_blueprint_event(&mut self, event: &WindowEvent) -> bool {
+        let actions = [
+            "blueprint_corner",
+            "blueprint_anchor",
+            "blueprint_save",
+            "blueprint_place",
+        ];
+        let Some(action) = Input::get_pressed_action(&actions, event) else {
+            return false;
+        };
+        let Some(position) = self.get_camera_block() else {
+            return false;
+        };
+        match action {
+            "blueprint_corner" => self.blueprint_editor.select_corner(position),
+            "blueprint_anchor" => self.blueprint_editor.set_anchor(position),
+            "blueprint_save" => {
+                let name = format!("structure-{}", BlueprintLibrary::get_names().len());
+                let Some(entity) = self
+                    .scene
+                    .get_entities_with_component::<Terrain<DualContouringChunk>>()
+                    .into_iter()
+                    .next()
+                else {
+                    return false;
+                };
+                let terrain = entity
+                    .get_component::<Terrain<DualContouringChunk>>()
+                    .unwrap();
+                match self
+                    .blueprint_editor
+                    .save(terrain, entity, &name, BLUEPRINTS)
+                {
+                    Ok(_) => {
+                        log::info!("Saved blueprint {}", name);
+                        self.blueprint_editor.clear();
+                        self.blueprint = Some(name);
+                    }
+                    Err(err) => log::error!("Failed to save blueprint {}: {}", name, err),
+                }
+            }
+            _ => {
+                let (Some(name), Some(terrain)) = (
+                    &self.blueprint,
+                    self.scene
+                        .get_component_mut::<Terrain<DualContouringChunk>>(),
+                ) else {
+                    return false;
+                };
+                if !terrain.place_blueprint(name, position) {
+                    log::warn!("Blueprint {} is not registered", name);
+                }
+            }
+        }
+        true
     }
 }
 
@@ -91,7 +271,9 @@ impl Layer for WorldLayer {
                 )
                 .add_child(
                     Some(UIElementHandle::from(2)),
-                    UI::input(camera_speed_ref, |input| input.size(190.0, 26.0)),
+                    UI::drag_value(camera_speed_ref, |input| {
+                        input.size(190.0, 26.0).speed(0.1).range(0.0, 1000.0)
+                    }),
                 )
                 .add_child(
                     Some(UIElementHandle::from(3)),
@@ -108,265 +290,198 @@ impl Layer for WorldLayer {
                     ),
                 )
         }));
+        Profiler::set_enabled(true);
+        // the panels on the right stay at the window's edge when it is resized
+        self.ui.add_with_layout(
+            UI::panel("Profiler", |builder| {
+                builder.position(-10.0, 10.0, 0.0).add_child(
+                    Some(UIElementHandle::from(20)),
+                    UI::profiler_graph(|graph| graph),
+                )
+            }),
+            Layout::new(Anchor::TopRight),
+        );
+        self.ui.add_with_layout(
+            UI::collapsible("Inspector", |builder| {
+                builder
+                    .position(-10.0, 300.0, 0.0)
+                    .size(350.0, 500.0)
+                    .add_child(
+                        Some(UIElementHandle::from(30)),
+                        UI::inspector(|inspector| inspector.size(340.0, 460.0)),
+                    )
+            }),
+            Layout::new(Anchor::TopRight),
+        );
+        self.ui.add(UI::panel("Camera mode", |builder| {
+            builder
+                .position(10.0, 340.0, 0.0)
+                .size(200.0, 120.0)
+                .add_child(
+                    Some(UIElementHandle::from(9)),
+                    UI::scroll_container(|mut scroll_container| {
+                        scroll_container = scroll_container.size(190.0, 80.0);
+                        for (i, controller_type) in
+                            CameraControllerType::all().into_iter().enumerate()
+                        {
+                            scroll_container = scroll_container.add_child(
+                                Some(UIElementHandle::from(10 + i as u64)),
+                                UI::button(
+                                    controller_type.get_name(),
+                                    Box::new(move |scene| {
+                                        scene.set_camera_controller(controller_type);
+                                        save_engine_config(scene);
+                                    }),
+                                    |b| b,
+                                ),
+                            );
+                        }
+                        scroll_container
+                    }),
+                )
+        }));
+        let time_scale = self.scene.time_control.get_time_scale_ref();
+        self.ui.add(UI::collapsible("Time", |builder| {
+            builder
+                .position(220.0, 10.0, 0.0)
+                .size(200.0, 120.0)
+                .add_child(
+                    Some(UIElementHandle::from(50)),
+                    UI::drag_value(time_scale, |input| {
+                        input
+                            .size(190.0, 26.0)
+                            .speed(0.01)
+                            .range(MIN_TIME_SCALE as f64, MAX_TIME_SCALE as f64)
+                    }),
+                )
+                .add_child(
+                    Some(UIElementHandle::from(51)),
+                    UI::button(
+                        "Pause",
+                        Box::new(|scene| scene.time_control.toggle_paused()),
+                        |b| b,
+                    ),
+                )
+                .add_child(
+                    Some(UIElementHandle::from(52)),
+                    UI::button("Step", Box::new(|scene| scene.time_control.step()), |b| b),
+                )
+        }));
+        self.ui.add(UI::collapsible("Render settings", |builder| {
+            builder
+                .position(430.0, 10.0, 0.0)
+                .size(200.0, 190.0)
+                .add_child(
+                    Some(UIElementHandle::from(60)),
+                    UI::drag_value(RenderSettings::get_resolution_scale_ref(), |input| {
+                        input
+                            .size(190.0, 26.0)
+                            .speed(0.01)
+                            .range(MIN_RESOLUTION_SCALE as f64, MAX_RESOLUTION_SCALE as f64)
+                    }),
+                )
+                .add_child(
+                    Some(UIElementHandle::from(61)),
+                    UI::drag_value(RenderSettings::get_msaa_samples_ref(), |input| {
+                        input
+                            .size(190.0, 26.0)
+                            .speed(0.1)
+                            .range(0.0, MAX_MSAA_SAMPLES as f64)
+                    }),
+                )
+                .add_child(
+                    Some(UIElementHandle::from(62)),
+                    UI::button(
+                        "Toggle vsync",
+                        Box::new(|_| RenderSettings::toggle_vsync()),
+                        |b| b,
+                    ),
+                )
+                .add_child(
+                    Some(UIElementHandle::from(63)),
+                    UI::button("Save", Box::new(save_engine_config), |b| b),
+                )
+        }));
+        self.ui.add(UI::collapsible("Controls", |builder| {
+            builder
+                .position(10.0, 480.0, 0.0)
+                .size(370.0, 240.0)
+                .add_child(
+                    Some(UIElementHandle::from(40)),
+                    UI::scroll_container(|scroll_container| {
+                        scroll_container.size(360.0, 200.0).add_child(
+                            Some(UIElementHandle::from(41)),
+                            UI::action_map_editor(|editor| {
+                                editor.on_change(Box::new(|actions| {
+                                    if let Err(err) = actions.save(CONTROLS) {
+                                        log::error!("Failed to save controls: {}", err);
+                                    }
+                                }))
+                            }),
+                        )
+                    }),
+                )
+        }));
     }
 
     fn on_update(&mut self, window: &Window, delta_time: f64) {
+        if self.photo_mode.is_active() {
+            self.photo_mode.update(&mut self.scene, window, delta_time);
+            return;
+        }
         self.scene.update(delta_time);
         self.scene.render(window);
 
+        self.debug_ui.poll(&mut self.ui);
         self.ui.render(&mut self.scene);
     }
 
-    fn on_event(&mut self, glfw: &mut Glfw, window: &mut glfw::Window, event: &WindowEvent) {
+    fn on_event(
+        &mut self,
+        glfw: &mut Glfw,
+        window: &mut glfw::Window,
+        event: &WindowEvent,
+    ) -> bool {
+        if self
+            .photo_mode
+            .handle_event(&mut self.scene, window, glfw, event)
+        {
+            return true;
+        }
         if self.ui.handle_events(&mut self.scene, window, glfw, &event) {
-            return;
+            return true;
+        }
+        if self.handle_blueprint_event(event) {
+            return true;
         }
-        self.scene.handle_event(glfw, window, event);
+        if Input::is_action_pressed("chop", event) {
+            let position = self
+                .scene
+                .get_component::<CameraComponent>()
+                .map(|camera| camera.get_camera().get_position());
+            if let (Some(position), Some(terrain)) = (
+                position,
+                self.scene
+                    .get_component_mut::<Terrain<DualContouringChunk>>(),
+            ) {
+                terrain.remove_prop(position, CHOP_RADIUS);
+                return true;
+            }
+        }
+        self.scene.handle_event(glfw, window, event)
+    }
+
+    fn on_gamepad_event(
+        &mut self,
+        glfw: &mut Glfw,
+        window: &mut glfw::Window,
+        event: &GamepadEvent,
+    ) -> bool {
+        self.ui
+            .handle_gamepad_event(&mut self.scene, window, glfw, event)
     }
 
     fn get_name(&self) -> &str {
         "World"
     }
 }
-
-fn create_animation_graph() -> Result<AnimationGraph, Box<dyn Error>> {
-    // Animation Graph visualization
-    //
-    // +-----------------+      +-----------------+       +-----------------+
-    // | forward_left    |------| walk            |-------| forward_right   |
-    // +-----------------+      +-----------------+       +-----------------+
-    //        |                        |                        |
-    // +-----------------+      +-----------------+       +-----------------+
-    // | left            |------| idle            |-------| right           |
-    // +-----------------+      +-----------------+       +-----------------+
-    //        |                        |                        |
-    // +-----------------+      +-----------------+       +-----------------+
-    // | backward_left   |------| back            |-------| backward_right  |
-    // +-----------------+      +-----------------+       +-----------------+
-
-    let mut animation_graph = AnimationGraph::new();
-    animation_graph.add_input("forward", 0.0);
-    animation_graph.add_input("backward", 0.0);
-    animation_graph.add_input("left", 0.0);
-    animation_graph.add_input("right", 0.0);
-
-    let transition_speed = 0.5;
-
-    let mut idle_state = State::new("idle");
-    idle_state.add_animation(Animation::from_file("idle", "Idle.fbx")?);
-    idle_state.add_transition(
-        "walk",
-        Box::new(|inputs| {
-            inputs["forward"] - inputs["backward"] > 0.0 && inputs["left"] - inputs["right"] == 0.0
-        }),
-        transition_speed,
-    );
-    idle_state.add_transition(
-        "back",
-        Box::new(|inputs| {
-            inputs["forward"] - inputs["backward"] < 0.0 && inputs["left"] - inputs["right"] == 0.0
-        }),
-        transition_speed,
-    );
-    idle_state.add_transition(
-        "left",
-        Box::new(|inputs| {
-            inputs["forward"] - inputs["backward"] == 0.0 && inputs["left"] - inputs["right"] > 0.0
-        }),
-        transition_speed,
-    );
-    idle_state.add_transition(
-        "right",
-        Box::new(|inputs| {
-            inputs["forward"] - inputs["backward"] == 0.0 && inputs["left"] - inputs["right"] < 0.0
-        }),
-        transition_speed,
-    );
-    animation_graph.set_default_state(idle_state);
-
-    let mut walk_state = State::new("walk");
-    walk_state.add_animation(Animation::from_file("walk", "Walk.fbx")?);
-    walk_state.add_transition(
-        "idle",
-        Box::new(|inputs| {
-            inputs["forward"] - inputs["backward"] == 0.0 && inputs["left"] - inputs["right"] == 0.0
-        }),
-        transition_speed,
-    );
-    walk_state.add_transition(
-        "forward_left",
-        Box::new(|inputs| {
-            inputs["forward"] - inputs["backward"] > 0.0 && inputs["left"] - inputs["right"] > 0.0
-        }),
-        transition_speed,
-    );
-    walk_state.add_transition(
-        "forward_right",
-        Box::new(|inputs| {
-            inputs["forward"] - inputs["backward"] > 0.0 && inputs["left"] - inputs["right"] < 0.0
-        }),
-        transition_speed,
-    );
-    animation_graph.add_state(walk_state);
-
-    let mut run_state = State::new("run");
-    run_state.add_animation(Animation::from_file("run", "Run.fbx")?);
-    animation_graph.add_state(run_state);
-
-    let mut back_state = State::new("back");
-    back_state.add_animation(Animation::from_file("back", "Walk_Backwards.fbx")?);
-    back_state.add_transition(
-        "idle",
-        Box::new(|inputs| {
-            inputs["forward"] - inputs["backward"] == 0.0 && inputs["left"] - inputs["right"] == 0.0
-        }),
-        transition_speed,
-    );
-    back_state.add_transition(
-        "backward_left",
-        Box::new(|inputs| {
-            inputs["forward"] - inputs["backward"] < 0.0 && inputs["left"] - inputs["right"] > 0.0
-        }),
-        transition_speed,
-    );
-    back_state.add_transition(
-        "backward_right",
-        Box::new(|inputs| {
-            inputs["forward"] - inputs["backward"] < 0.0 && inputs["left"] - inputs["right"] < 0.0
-        }),
-        transition_speed,
-    );
-    animation_graph.add_state(back_state);
-
-    let mut left_state = State::new("left");
-    left_state.add_animation(Animation::from_file("left", "Walk_Left.fbx")?);
-    left_state.add_transition(
-        "idle",
-        Box::new(|inputs| {
-            inputs["forward"] - inputs["backward"] == 0.0 && inputs["left"] - inputs["right"] == 0.0
-        }),
-        transition_speed,
-    );
-    left_state.add_transition(
-        "forward_left",
-        Box::new(|inputs| {
-            inputs["forward"] - inputs["backward"] > 0.0 && inputs["left"] - inputs["right"] > 0.0
-        }),
-        transition_speed,
-    );
-    left_state.add_transition(
-        "backward_left",
-        Box::new(|inputs| {
-            inputs["forward"] - inputs["backward"] < 0.0 && inputs["left"] - inputs["right"] > 0.0
-        }),
-        transition_speed,
-    );
-    animation_graph.add_state(left_state);
-
-    let mut right_state = State::new("right");
-    right_state.add_animation(Animation::from_file("right", "Walk_Right.fbx")?);
-    right_state.add_transition(
-        "idle",
-        Box::new(|inputs| {
-            inputs["forward"] - inputs["backward"] == 0.0 && inputs["left"] - inputs["right"] == 0.0
-        }),
-        transition_speed,
-    );
-    right_state.add_transition(
-        "forward_right",
-        Box::new(|inputs| {
-            inputs["forward"] - inputs["backward"] > 0.0 && inputs["left"] - inputs["right"] < 0.0
-        }),
-        transition_speed,
-    );
-    right_state.add_transition(
-        "backward_right",
-        Box::new(|inputs| {
-            inputs["forward"] - inputs["backward"] < 0.0 && inputs["left"] - inputs["right"] < 0.0
-        }),
-        transition_speed,
-    );
-    animation_graph.add_state(right_state);
-
-    let mut forward_left_state = State::new("forward_left");
-    forward_left_state.add_animation(Animation::from_file("walk", "Walk.fbx")?);
-    forward_left_state.add_animation(Animation::from_file("left", "Walk_Left.fbx")?);
-    forward_left_state.add_transition(
-        "walk",
-        Box::new(|inputs| {
-            inputs["forward"] - inputs["backward"] > 0.0 && inputs["left"] - inputs["right"] == 0.0
-        }),
-        transition_speed,
-    );
-    forward_left_state.add_transition(
-        "left",
-        Box::new(|inputs| {
-            inputs["forward"] - inputs["backward"] == 0.0 && inputs["left"] - inputs["right"] > 0.0
-        }),
-        transition_speed,
-    );
-    forward_left_state.sync_animations(true);
-    animation_graph.add_state(forward_left_state);
-
-    let mut forward_right_state = State::new("forward_right");
-    forward_right_state.add_animation(Animation::from_file("walk", "Walk.fbx")?);
-    forward_right_state.add_animation(Animation::from_file("right", "Walk_Right.fbx")?);
-    forward_right_state.add_transition(
-        "walk",
-        Box::new(|inputs| {
-            inputs["forward"] - inputs["backward"] > 0.0 && inputs["left"] - inputs["right"] == 0.0
-        }),
-        transition_speed,
-    );
-    forward_right_state.add_transition(
-        "right",
-        Box::new(|inputs| {
-            inputs["forward"] - inputs["backward"] == 0.0 && inputs["left"] - inputs["right"] < 0.0
-        }),
-        transition_speed,
-    );
-    forward_right_state.sync_animations(true);
-    animation_graph.add_state(forward_right_state);
-
-    let mut backward_left_state = State::new("backward_left");
-    backward_left_state.add_animation(Animation::from_file("back", "Walk_Backwards.fbx")?);
-    backward_left_state.add_animation(Animation::from_file("left", "Walk_Left.fbx")?);
-    backward_left_state.add_transition(
-        "back",
-        Box::new(|inputs| {
-            inputs["forward"] - inputs["backward"] < 0.0 && inputs["left"] - inputs["right"] == 0.0
-        }),
-        transition_speed,
-    );
-    backward_left_state.add_transition(
-        "left",
-        Box::new(|inputs| {
-            inputs["forward"] - inputs["backward"] == 0.0 && inputs["left"] - inputs["right"] > 0.0
-        }),
-        transition_speed,
-    );
-    backward_left_state.sync_animations(true);
-    animation_graph.add_state(backward_left_state);
-
-    let mut backward_right_state = State::new("backward_right");
-    backward_right_state.add_animation(Animation::from_file("back", "Walk_Backwards.fbx")?);
-    backward_right_state.add_animation(Animation::from_file("right", "Walk_Right.fbx")?);
-    backward_right_state.add_transition(
-        "back",
-        Box::new(|inputs| {
-            inputs["forward"] - inputs["backward"] < 0.0 && inputs["left"] - inputs["right"] == 0.0
-        }),
-        transition_speed,
-    );
-    backward_right_state.add_transition(
-        "right",
-        Box::new(|inputs| {
-            inputs["forward"] - inputs["backward"] == 0.0 && inputs["left"] - inputs["right"] < 0.0
-        }),
-        transition_speed,
-    );
-    backward_right_state.sync_animations(true);
-    animation_graph.add_state(backward_right_state);
-
-    Ok(animation_graph)
-}